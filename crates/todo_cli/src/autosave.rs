@@ -0,0 +1,78 @@
+//! Rate-limited saving for interactive mode.
+//!
+//! An interactive session journals every change instead of rewriting the store, so a burst of
+//! commands costs one small append each. The store itself is rewritten at most once per
+//! `interactive.autosave_secs`, on `flush`, and when the session ends. Until then the journal
+//! keeps every change on disk, and other commands read it as part of the store.
+
+use std::time::{Duration, Instant};
+use todo_core::error::AppError;
+
+#[derive(Debug)]
+pub struct Autosave {
+    /// `None` when every change is saved right away.
+    interval: Option<Duration>,
+    last_save: Instant,
+}
+
+impl Autosave {
+    /// Starts journaling changes, unless `interval_secs` is 0.
+    pub fn start(interval_secs: u64) -> Self {
+        let interval = (interval_secs > 0).then(|| Duration::from_secs(interval_secs));
+        if interval.is_some() {
+            todo_core::task_api::defer_store_writes(true);
+        }
+        Autosave {
+            interval,
+            last_save: Instant::now(),
+        }
+    }
+
+    /// Call after each command: rewrites the store once the interval has passed since the
+    /// last rewrite.
+    pub fn tick(&mut self) -> Result<(), AppError> {
+        let now = Instant::now();
+        if self.due(now) {
+            todo_core::task_api::flush_store()?;
+            self.last_save = now;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the store with anything still journaled and stops journaling.
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.interval.is_none() {
+            return Ok(());
+        }
+        todo_core::task_api::defer_store_writes(false);
+        todo_core::task_api::flush_store().map(|_| ())
+    }
+
+    fn due(&self, now: Instant) -> bool {
+        self.interval
+            .is_some_and(|interval| now.duration_since(self.last_save) >= interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Autosave;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn due_only_once_the_interval_has_passed() {
+        let started = Instant::now();
+        let autosave = Autosave {
+            interval: Some(Duration::from_secs(5)),
+            last_save: started,
+        };
+        let immediate = Autosave {
+            interval: None,
+            last_save: started,
+        };
+
+        assert!(!autosave.due(started + Duration::from_secs(4)));
+        assert!(autosave.due(started + Duration::from_secs(5)));
+        assert!(!immediate.due(started + Duration::from_secs(60)));
+    }
+}
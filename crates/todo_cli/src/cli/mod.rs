@@ -1,4 +1,11 @@
-use clap::{Parser, Subcommand};
+use crate::schema::SchemaKind;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use todo_core::export::digest::DigestPeriod;
+use todo_core::model::MarkerColor;
+use todo_core::stats::GroupBy;
+#[cfg(feature = "sync")]
+use todo_core::sync::Prefer;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -10,9 +17,51 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Emit JSON in the pre-`overdue` shape, with overdue folded into `status` (deprecated; use
+    /// --json --compat 0)
+    #[arg(long = "legacy-json", global = true)]
+    pub legacy_json: bool,
+
+    /// Print JSON in the shape of an earlier schema version, for scripts not yet updated
+    #[arg(long, value_name = "VERSION", global = true, value_parser = crate::compat::parse_version)]
+    pub compat: Option<u32>,
+
+    /// Wrap JSON output as {"schema_version", "kind", "data"}; see `todo schema`
+    #[arg(long, global = true)]
+    pub envelope: bool,
+
+    /// Spell everything out in words, one fact per line, without colors, tables, or symbols
+    #[arg(long, global = true)]
+    pub accessible: bool,
+
+    /// Fit tables and `show` to this many columns instead of the terminal's width
+    #[arg(long, value_name = "COLUMNS", global = true, value_parser = crate::layout::parse_width)]
+    pub width: Option<usize>,
+
+    /// Print everything at once, even with `pager` on in the config
+    #[arg(long = "no-pager", global = true)]
+    pub no_pager: bool,
+
+    /// Fail instead of carrying on after a warning, such as an unreadable config or an old store
+    #[arg(long, global = true)]
+    pub strict: bool,
+
     /// Override configuration values (format KEY=VALUE)
     #[arg(long = "config-override", value_name = "KEY=VALUE", global = true)]
     pub config_override: Vec<String>,
+
+    /// Read the config from this file instead of `TODOAPP_CONFIG_PATH` or the default
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Use the config and tasks of a named profile, kept in `profiles/NAME/` beside the default config
+    #[arg(long, value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+
+    /// Start an interactive session that writes each line typed, and what it printed, to this
+    /// file; replay it with `todo run PATH --replay`
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -20,16 +69,34 @@ pub enum Command {
     /// Add a new task
     ///
     /// Example: todo add "Buy milk" --urgent
+    /// Example: todo add "Quarterly report" --tag work --note "Use the new template"
+    /// Example: todo add "Draft budget" --project finance
+    /// Example: todo add "Water plants" --once-per-day
     Add {
         title: Option<String>,
         #[arg(long)]
         urgent: bool,
+        /// Tag the task (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Attach free-form notes
+        #[arg(long, value_name = "TEXT")]
+        note: Option<String>,
+        /// Assign the task to a project
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// Add nothing if an open task with this title was added or is planned today
+        #[arg(long)]
+        once_per_day: bool,
     },
     /// Focus on a specific task
     ///
     /// Example: todo focus 1
     Focus {
         id: String,
+        /// Focus even when `wip_limit.today` is full and set to block
+        #[arg(long)]
+        force: bool,
     },
     /// Mark a task as urgent or clear urgency
     ///
@@ -40,6 +107,73 @@ pub enum Command {
         #[arg(long)]
         clear: bool,
     },
+    /// Mark a task with a color and/or an emoji, shown before its title in lists
+    ///
+    /// Example: todo mark 1 --color red
+    /// Example: todo mark 1 --emoji 🔥
+    /// Example: todo mark 1 --clear
+    Mark {
+        id: String,
+        #[arg(long, value_enum, value_name = "COLOR")]
+        color: Option<MarkColor>,
+        /// One emoji or at most two characters
+        #[arg(long, value_name = "SYMBOL")]
+        emoji: Option<String>,
+        /// Remove the color and the emoji
+        #[arg(long, conflicts_with_all = ["color", "emoji"])]
+        clear: bool,
+    },
+    /// Show or change a task's custom key/value metadata
+    ///
+    /// Values are plain text; filters and `query` read them as meta.KEY.
+    ///
+    /// Example: todo meta 1 set sprint 42
+    /// Example: todo meta 1 unset sprint
+    /// Example: todo meta 1
+    Meta {
+        id: String,
+        #[command(subcommand)]
+        action: Option<MetaCommand>,
+    },
+    /// Add a comment to a task's discussion thread
+    ///
+    /// Comments are timestamped and attributed to --author, or to the current user ($USER).
+    ///
+    /// Example: todo comment 1 "Waiting on design review"
+    /// Example: todo comment 1 "Picked this up" --author sam
+    Comment {
+        id: String,
+        text: String,
+        #[arg(long, value_name = "NAME")]
+        author: Option<String>,
+    },
+    /// Link a task to an external ticket, or remove the link
+    ///
+    /// The provider is picked by key prefix from `tickets.providers` in the config unless
+    /// --provider names one.
+    ///
+    /// Example: todo link-ticket 1 JIRA-123 --url https://jira.example.com/browse/JIRA-123
+    /// Example: todo link-ticket 1 --clear
+    LinkTicket {
+        id: String,
+        #[arg(required_unless_present = "clear", value_name = "KEY")]
+        key: Option<String>,
+        /// Link shown alongside the ticket key
+        #[arg(long, conflicts_with = "clear")]
+        url: Option<String>,
+        /// Configured provider to fetch the ticket from
+        #[arg(long, conflicts_with = "clear")]
+        provider: Option<String>,
+        #[arg(long, conflicts_with = "key")]
+        clear: bool,
+    },
+    /// Update linked tasks from their tickets' titles and statuses
+    ///
+    /// Pending tasks take their ticket's current title and are completed when the ticket is
+    /// closed.
+    ///
+    /// Example: todo refresh-tickets
+    RefreshTickets,
     /// Edit a task's title
     ///
     /// Example: todo edit 1 "Buy organic milk"
@@ -50,14 +184,32 @@ pub enum Command {
     /// Delete a task
     ///
     /// Example: todo delete 1
-    Delete {
-        id: String,
-    },
+    Delete { id: String },
     /// Show details of a task
     ///
+    /// --verbose adds who last changed the task, from which host, and the task's entries in
+    /// the store's activity log.
+    ///
     /// Example: todo show 1
+    /// Example: todo show 1 --verbose
     Show {
         id: String,
+        #[arg(short = 'v', long)]
+        verbose: bool,
+    },
+    /// Mark a pending or waiting task as in progress
+    ///
+    /// Example: todo start 1
+    Start { id: String },
+    /// Mark a task as waiting on something else, optionally until a date
+    ///
+    /// Example: todo wait 1
+    /// Example: todo wait 1 --until "2023-12-27 09:00"
+    Wait {
+        id: String,
+        /// When to check back, in any form `schedule` takes
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
     },
     /// Mark a task as completed
     ///
@@ -69,16 +221,98 @@ pub enum Command {
         #[arg(short = 'm', long = "message", value_name = "MESSAGE")]
         message_flag: Option<String>,
     },
-    /// Schedule a task for a specific time
+    /// Drop a task without completing it, keeping it for reports
+    ///
+    /// Example: todo cancel 1
+    /// Example: todo cancel 1 -m "Vendor went out of business"
+    Cancel {
+        id: String,
+        /// Why it was dropped; shown by `show` and kept in the JSON output
+        #[arg(short = 'm', long = "message", value_name = "REASON")]
+        reason: Option<String>,
+    },
+    /// Reopen the task completed by the last `done`, within 60 seconds
+    ///
+    /// Example: todo undo
+    Undo,
+    /// Turn a checklist from the `routines` config into today's tasks
+    ///
+    /// Routine tasks are archived once their day is over, done or not.
+    ///
+    /// Example: todo routine start morning
+    /// Example: todo routine list
+    Routine {
+        #[command(subcommand)]
+        action: RoutineCommand,
+    },
+    /// Add tasks on a fixed cadence from rules, with `seed run` from cron
+    ///
+    /// Each time a rule falls due, `seed run` adds one task planned for that time. Running it
+    /// again, or late, adds nothing more.
+    ///
+    /// Example: todo seed --rule "every monday 9am: Weekly report"
+    /// Example: todo seed --rule "every weekday 8:30am: Check the build"
+    /// Example: todo seed list
+    /// Example: todo seed run
+    #[command(args_conflicts_with_subcommands = true)]
+    Seed {
+        #[command(subcommand)]
+        action: Option<SeedCommand>,
+        /// Add a rule: `every DAY [TIME]: TITLE`, where DAY is day, weekday, or a day of the
+        /// week
+        #[arg(long, value_name = "RULE")]
+        rule: Option<String>,
+    },
+    /// Sync tasks with another copy of the store from the `remotes` config
+    ///
+    /// A task changed on both sides since the last sync is a conflict. On a terminal you are
+    /// asked which side to keep, or which side to take each field from; otherwise pass
+    /// --prefer.
+    ///
+    /// Example: todo sync shared
+    /// Example: todo sync shared --prefer newest
+    /// Example: todo sync shared --dry-run
+    /// Example: todo sync status
+    /// Example: todo sync flush
+    #[cfg(feature = "sync")]
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Sync {
+        #[command(subcommand)]
+        action: Option<SyncCommand>,
+        /// Name of a remote under `remotes` in the config
+        #[arg(required = true)]
+        remote: Option<String>,
+        /// Settle every conflict this way instead of asking
+        #[arg(long, value_enum, value_name = "SIDE")]
+        prefer: Option<SyncPrefer>,
+        /// Print what would be pushed, pulled, and deleted, and each conflict, without
+        /// changing anything
+        #[arg(long, conflicts_with = "prefer")]
+        dry_run: bool,
+    },
+    /// Set a task's deadline, or with --start when you plan to work on it
+    ///
+    /// A task is overdue once its deadline passes. `list today` goes by the start, or by the
+    /// deadline for a task without one.
     ///
     /// Example: todo schedule 1 "2023-12-25 10:00"
     /// Example: todo schedule 1 "2023-12-25 10:00:00"
     /// Example: todo schedule 1 "2023-12-25" (Defaults to midnight)
+    /// Example: todo schedule 1 "2023-12-22" --start
     Schedule {
         id: String,
         datetime: String,
+        /// Set the planned start (start_at) instead of the deadline (due_at)
+        #[arg(long)]
+        start: bool,
+        /// Schedule even when `wip_limit.today` is full and set to block
+        #[arg(long)]
+        force: bool,
+        /// Accept a deadline that has already passed, leaving the task overdue
+        #[arg(long, conflicts_with = "start")]
+        allow_past: bool,
     },
-    /// Reschedule a task
+    /// Move the deadline of an overdue task
     ///
     /// Example: todo reschedule 1 "2023-12-26 14:00"
     /// Example: todo reschedule 1 "2023-12-26 14:00:00"
@@ -86,19 +320,675 @@ pub enum Command {
     Reschedule {
         id: String,
         datetime: String,
+        /// Reschedule even when `wip_limit.today` is full and set to block
+        #[arg(long)]
+        force: bool,
     },
     /// Send notifications for due tasks
     ///
     /// Example: todo notify
-    Notify,
+    /// Example: todo notify install-schedule --every 15m
+    /// Example: todo notify --log ~/.local/state/todo-notify.ndjson
+    Notify {
+        #[command(subcommand)]
+        action: Option<NotifyCommand>,
+        /// Append a JSON line describing the run to FILE: tasks selected, notified, and
+        /// failed, and how long it took
+        #[arg(long, value_name = "FILE")]
+        log: Option<PathBuf>,
+    },
+    /// Explore the CLI against a temporary store of sample tasks
+    ///
+    /// Without a command, starts an interactive session; the sandbox is discarded on exit.
+    ///
+    /// Example: todo demo
+    /// Example: todo demo list today
+    Demo {
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "COMMAND"
+        )]
+        args: Vec<String>,
+    },
+    /// Define and switch contexts: named filters applied to every list
+    ///
+    /// Filters combine project:NAME, tag:NAME, status:pending|completed|overdue, urgent:yes|no,
+    /// title words, and `query` comparisons such as "due_at < now + 2d" with and/or/not
+    /// and parentheses.
+    ///
+    /// Example: todo context define work "project:work or tag:work"
+    /// Example: todo context set work
+    /// Example: todo context none
+    Context {
+        #[command(subcommand)]
+        action: Option<ContextCommand>,
+    },
+    /// Print tasks matching an expression as JSON, ignoring the active context
+    ///
+    /// Compare fields with =, !=, ~ (contains), and, on timestamps, <, <=, >, >=; combine with
+    /// and/or/not. Timestamps accept now, today, now + 2d, or dates like 2025-12-24.
+    ///
+    /// Example: todo query "status = pending and due_at < now + 2d and tag ~ 'work'"
+    /// Example: todo query "project = none or completed_at >= today - 1w"
+    Query { expression: String },
+    /// Find tasks whose title, notes, or tags contain every word, ignoring case
+    ///
+    /// Stores of 500 or more tasks are searched through an index kept next to the store.
+    ///
+    /// Example: todo search passport
+    /// Example: todo search renew passport --json
+    /// Example: todo search passport --status waiting
+    Search {
+        #[arg(required = true, value_name = "WORD")]
+        words: Vec<String>,
+        /// Only tasks in this status, as for `list --status`
+        #[arg(long, value_name = "STATUS")]
+        status: Option<String>,
+    },
+    /// Maintain the search index
+    ///
+    /// Example: todo index rebuild
+    Index {
+        #[command(subcommand)]
+        action: IndexCommand,
+    },
+    /// Save named checkpoints of the whole store and roll back to them
+    ///
+    /// Restoring first saves the current store as `pre-restore`, so a restore can be undone.
+    ///
+    /// Example: todo snapshot save "before cleanup"
+    /// Example: todo snapshot restore before-cleanup
+    Snapshot {
+        #[command(subcommand)]
+        action: Option<SnapshotCommand>,
+    },
+    /// Hand store events to external automations: pull them, then acknowledge them
+    ///
+    /// Events (task_added, task_updated, task_completed, task_reopened, task_cancelled,
+    /// task_removed) are kept from the first pull on, until acknowledged.
+    ///
+    /// Example: todo --json events pull --ack
+    /// Example: todo events ack 42
+    Events {
+        #[command(subcommand)]
+        action: EventsCommand,
+    },
+    /// POST store events to the `webhooks` in the config
+    ///
+    /// Each change is queued for every webhook that wants its event, then posted in the
+    /// background right after the command that made it. Deliveries that fail stay queued.
+    ///
+    /// Example: todo webhooks status
+    /// Example: todo webhooks deliver
+    Webhooks {
+        #[command(subcommand)]
+        action: WebhooksCommand,
+    },
+    /// Show tasks added, removed, or changed between two store files
+    ///
+    /// Without NEW, compares OLD against the current store.
+    ///
+    /// Example: todo diff backup/tasks.json
+    /// Example: todo diff before.json after.json --json
+    Diff {
+        #[arg(value_name = "OLD")]
+        old: PathBuf,
+        #[arg(value_name = "NEW")]
+        new: Option<PathBuf>,
+    },
+    /// Export all tasks as a standalone, read-only page or a CSV file for spreadsheets
+    ///
+    /// --verify writes every task in each format `import` reads back, reads it into an empty
+    /// store, and reports the fields that did not survive; nothing is exported.
+    ///
+    /// Example: todo export --format html > tasks.html
+    /// Example: todo export --format csv --output tasks.csv
+    /// Example: todo export --verify
+    Export {
+        #[arg(long, value_enum, default_value = "html")]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Check that tasks survive a round trip through every format that can be read back
+        #[arg(long, conflicts_with_all = ["format", "output"])]
+        verify: bool,
+    },
+    /// Add tasks from a file, such as a CSV file saved from a spreadsheet
+    ///
+    /// Map task fields to columns by number, counting from 1, or by header name; without
+    /// --map, columns named title, due, start, tags, project, notes, and urgent are read.
+    /// Nothing is added unless every row can be.
+    ///
+    /// Example: todo import --format csv --map title=2,due=5,tags=7 tasks.csv
+    /// Example: todo import --map "title=Task,due=Due date" --date-format DD/MM/YYYY tasks.csv
+    /// Example: todo import tasks.csv --dry-run
+    Import {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ImportFormat,
+        /// FIELD=COLUMN pairs, comma-separated
+        #[arg(long, value_name = "FIELD=COLUMN,...")]
+        map: Option<String>,
+        /// How dates in the file are written, such as DD/MM/YYYY or MM/DD/YYYY (default
+        /// YYYY-MM-DD)
+        #[arg(long, value_name = "FORMAT")]
+        date_format: Option<String>,
+        /// The first row is a task, not column names
+        #[arg(long)]
+        no_header: bool,
+        /// Character between cells, such as ';'
+        #[arg(long, default_value = ",", value_name = "CHAR")]
+        delimiter: char,
+        /// Show the tasks that would be added without adding them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export tasks and the tickets linking them as a Graphviz graph, clustered by project
+    ///
+    /// Ticket links are the only edges: tasks have no dependency or subtask relations yet, so a
+    /// task without a ticket link is drawn on its own.
+    ///
+    /// Example: todo graph --format dot | dot -Tsvg > tasks.svg
+    /// Example: todo graph --output tasks.dot
+    Graph {
+        #[arg(long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Apply the `retention` rules: archive old completed tasks and purge old archived ones
+    ///
+    /// Archived tasks are kept in `<store>.archive.json`.
+    ///
+    /// Example: todo maintain
+    Maintain,
+    /// Rewrite the store in the current schema, keeping the old file as `<store>.pre-migrate.json`
+    ///
+    /// Example: todo migrate
+    /// Example: todo migrate --lenient
+    Migrate {
+        /// Coerce values older versions or hand edits wrote, such as "urgent": "yes", and list each change
+        #[arg(long)]
+        lenient: bool,
+    },
+    /// Show the store's size, tasks per status, oldest open task, journal, and last snapshot
+    ///
+    /// Example: todo info
+    /// Example: todo info --json
+    Info,
+    /// Check that the store, its sidecar files, and the config belong to you and nobody else can write them
+    ///
+    /// Exits with status 1 while problems remain.
+    ///
+    /// Example: todo doctor
+    /// Example: todo doctor --fix-permissions
+    Doctor {
+        /// Chmod files to 600 and directories to 700, and drop write access for others on the folders holding them
+        #[arg(long)]
+        fix_permissions: bool,
+    },
+    /// Keep tokens for ticket providers in the OS keyring; config refers to them as `${secret:NAME}`
+    ///
+    /// Example: echo "$TOKEN" | todo secret set github_token
+    /// Example: todo secret check github_token
+    Secret {
+        #[command(subcommand)]
+        action: SecretCommand,
+    },
+    /// Write changes still held in the journal into the store file
+    ///
+    /// Interactive mode journals changes and rewrites the store at most every
+    /// `interactive.autosave_secs`; this rewrites it now.
+    ///
+    /// Example: todo flush
+    Flush,
+    /// Summarize completed, slipped, and upcoming tasks for the last and next period
+    ///
+    /// Example: todo digest
+    /// Example: todo digest --period week --format email | sendmail me@example.com
+    Digest {
+        #[arg(long, value_enum, default_value = "week")]
+        period: DigestSpan,
+        #[arg(long, value_enum, default_value = "md")]
+        format: DigestFormat,
+    },
+    /// Run the lines of a file as if typed in interactive mode
+    ///
+    /// With --replay, FILE is a transcript written by `todo --record`: each command runs again
+    /// at the time it was recorded, and any that prints something different is reported.
+    ///
+    /// Example: todo run setup.todo
+    /// Example: TODOAPP_STORE_PATH=/tmp/repro.json todo run session.log --replay
+    Run {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        #[arg(long)]
+        replay: bool,
+    },
+    /// Accept commands over a Unix socket, one per line, each answered with one JSON line
+    ///
+    /// Replies are the `--json --envelope` output of the command, or an error envelope.
+    ///
+    /// Example: todo listen --socket /run/user/1000/todo.sock
+    /// Example: echo 'add "Buy milk"' | socat - UNIX-CONNECT:/run/user/1000/todo.sock
+    Listen {
+        #[arg(long, value_name = "PATH")]
+        socket: PathBuf,
+    },
+    /// Run a command for each task that starts matching a filter, changes, or stops matching
+    ///
+    /// The store is checked every few seconds until stopped. {id}, {title}, {status},
+    /// {project}, {due_at}, and {change} (added, changed, or removed) in the command are
+    /// replaced with the task's values, quoted for the shell. Tasks that already match when the
+    /// watch starts run nothing.
+    ///
+    /// Example: todo watch --filter "status:pending" --exec "notify-send {title}"
+    /// Example: todo watch --filter "tag:deploy" --on removed --exec "echo {title} >> shipped.log"
+    Watch {
+        /// Tasks to watch, in the filter syntax contexts use
+        #[arg(long, value_name = "FILTER")]
+        filter: String,
+        /// Shell command to run for each change
+        #[arg(long, value_name = "COMMAND")]
+        exec: String,
+        /// Changes that run the command, comma-separated
+        #[arg(long, value_enum, value_delimiter = ',', default_values = ["added", "changed"])]
+        on: Vec<WatchChange>,
+        /// Seconds between checks of the store
+        #[arg(long, default_value = "2", value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+    /// Show pending, urgent, and completed counts with the next due and focused tasks
+    ///
+    /// Reads the small status file kept next to the store, so it stays cheap to poll.
+    ///
+    /// Example: todo status
+    /// Example: todo status --waybar
+    Status {
+        /// Print {"text", "tooltip", "class"} for a Waybar custom module; class is "urgent"
+        /// while a task is overdue
+        #[arg(long)]
+        waybar: bool,
+    },
+    /// Summarize your own usage patterns from the local usage log
+    ///
+    /// Nothing leaves this machine; set `insights.record_usage = false` to stop recording.
+    ///
+    /// Example: todo insights
+    Insights,
+    /// Reports over the store's history
+    ///
+    /// Example: todo report accuracy
+    Report {
+        #[command(subcommand)]
+        report: ReportCommand,
+    },
+    /// Print the JSON Schema for CLI output
+    ///
+    /// Example: todo schema
+    /// Example: todo schema task
+    Schema {
+        #[arg(value_enum)]
+        kind: Option<SchemaKind>,
+    },
+    /// Set up the config and store: theme, notifications, where tasks are kept, and a sample task
+    ///
+    /// Asks each question on the terminal; `--defaults` takes every default answer without
+    /// asking, for scripts. An existing config is never replaced.
+    ///
+    /// Example: todo init
+    /// Example: todo init --defaults
+    Init {
+        /// Take the default answer to every question instead of asking
+        #[arg(long)]
+        defaults: bool,
+    },
     /// List tasks
     ///
     /// Example: todo list today
     /// Example: todo list backlog
+    /// Example: todo list week --iso 2026-W07
+    /// Example: todo list today --wide
+    /// Example: todo list backlog --group-by tag
+    /// Example: todo list backlog --project work
     List {
         #[command(subcommand)]
         list: ListCommand,
+        /// Include urgency and completion columns in plain output
+        #[arg(long, global = true)]
+        wide: bool,
+        /// Split the list into sections with subtotals
+        #[arg(long, global = true, value_enum, value_name = "FIELD")]
+        group_by: Option<GroupField>,
+        /// Only tasks in this project
+        #[arg(long, global = true, value_name = "NAME")]
+        project: Option<String>,
+        /// Only tasks with this tag
+        #[arg(long, global = true, value_name = "TAG")]
+        tag: Option<String>,
+        /// Only tasks in this status: pending, in_progress, waiting, completed, open, or overdue
+        #[arg(long, global = true, value_name = "STATUS")]
+        status: Option<String>,
+    },
+    /// Show open tasks planned for the next few days, one section per day
+    ///
+    /// Unlike `list today`, nothing overdue from earlier days is included.
+    ///
+    /// Example: todo upcoming
+    /// Example: todo upcoming --days 14
+    Upcoming {
+        /// How many days to cover, starting today
+        #[arg(long, default_value = "7", value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+        /// Include urgency and completion columns
+        #[arg(long)]
+        wide: bool,
     },
+    /// Go through the overdue tasks one at a time and move each deadline with a key
+    ///
+    /// t moves it to today, m to tomorrow, and w to next Monday, keeping its time of day; s skips
+    /// the task and q the rest. Nothing is saved until the last answer, then all at once.
+    ///
+    /// Example: todo triage
+    Triage,
+    /// Write a note for a day, shown above `list today`, under the day in `upcoming`, and in
+    /// `digest`
+    ///
+    /// Text is added to the day's note on a line of its own. Without text, prints the note.
+    ///
+    /// Example: todo journal "Offsite in Berlin; keep the afternoon free"
+    /// Example: todo journal 2025-12-24 "Office closes at noon"
+    /// Example: todo journal yesterday
+    Journal {
+        /// The day: YYYY-MM-DD, today, yesterday, or tomorrow (default today)
+        #[arg(value_name = "DATE")]
+        date: Option<String>,
+        /// Text to add to the day's note
+        #[arg(value_name = "TEXT")]
+        text: Option<String>,
+        /// Remove the day's note
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Single HTML file with inline styles, grouped into today, backlog, and done
+    Html,
+    /// The columns `import` reads: title, due, start, tags, project, notes, and urgent
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// Comma-separated values, as spreadsheets save them
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT source
+    Dot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MarkColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl From<MarkColor> for MarkerColor {
+    fn from(color: MarkColor) -> Self {
+        match color {
+            MarkColor::Red => MarkerColor::Red,
+            MarkColor::Orange => MarkerColor::Orange,
+            MarkColor::Yellow => MarkerColor::Yellow,
+            MarkColor::Green => MarkerColor::Green,
+            MarkColor::Blue => MarkerColor::Blue,
+            MarkColor::Purple => MarkerColor::Purple,
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SyncPrefer {
+    /// Keep the task in this store
+    Local,
+    /// Keep the task on the remote
+    Remote,
+    /// Keep whichever side changed the task last
+    Newest,
+}
+
+#[cfg(feature = "sync")]
+impl From<SyncPrefer> for Prefer {
+    fn from(prefer: SyncPrefer) -> Self {
+        match prefer {
+            SyncPrefer::Local => Prefer::Local,
+            SyncPrefer::Remote => Prefer::Remote,
+            SyncPrefer::Newest => Prefer::Newest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WatchChange {
+    /// A task started matching the filter
+    Added,
+    /// A matching task was edited
+    Changed,
+    /// A task stopped matching the filter or was deleted
+    Removed,
+}
+
+impl WatchChange {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WatchChange::Added => "added",
+            WatchChange::Changed => "changed",
+            WatchChange::Removed => "removed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DigestSpan {
+    Day,
+    Week,
+}
+
+impl From<DigestSpan> for DigestPeriod {
+    fn from(span: DigestSpan) -> Self {
+        match span {
+            DigestSpan::Day => DigestPeriod::Day,
+            DigestSpan::Week => DigestPeriod::Week,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DigestFormat {
+    /// Markdown document
+    Md,
+    /// Plain-text email message with Subject and Date headers, ready for `sendmail`
+    Email,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupField {
+    Tag,
+    Project,
+    Status,
+    Day,
+}
+
+impl GroupField {
+    pub fn name(self) -> &'static str {
+        match self {
+            GroupField::Tag => "tag",
+            GroupField::Project => "project",
+            GroupField::Status => "status",
+            GroupField::Day => "day",
+        }
+    }
+
+    /// Section title for tasks that have no value for this field.
+    pub fn missing_label(self) -> &'static str {
+        match self {
+            GroupField::Tag => "(no tag)",
+            GroupField::Project => "(no project)",
+            GroupField::Status => "(no status)",
+            GroupField::Day => "(unscheduled)",
+        }
+    }
+}
+
+impl From<GroupField> for GroupBy {
+    fn from(field: GroupField) -> Self {
+        match field {
+            GroupField::Tag => GroupBy::Tag,
+            GroupField::Project => GroupBy::Project,
+            GroupField::Status => GroupBy::Status,
+            GroupField::Day => GroupBy::Day,
+        }
+    }
+}
+
+impl Command {
+    /// Command path and target task id written to the local usage log, or `None` for commands
+    /// that are not recorded. Titles, notes, and dates are never part of the entry.
+    pub fn usage_entry(&self) -> Option<(&'static str, Option<&str>)> {
+        let entry = match self {
+            Command::Add { .. } => ("add", None),
+            Command::Focus { id, .. } => ("focus", Some(id.as_str())),
+            Command::Urgent { id, .. } => ("urgent", Some(id.as_str())),
+            Command::Mark { id, .. } => ("mark", Some(id.as_str())),
+            Command::Meta { id, action } => match action {
+                None | Some(MetaCommand::List) => ("meta list", Some(id.as_str())),
+                Some(MetaCommand::Set { .. }) => ("meta set", Some(id.as_str())),
+                Some(MetaCommand::Unset { .. }) => ("meta unset", Some(id.as_str())),
+            },
+            Command::Comment { id, .. } => ("comment", Some(id.as_str())),
+            Command::LinkTicket { id, .. } => ("link-ticket", Some(id.as_str())),
+            Command::RefreshTickets => ("refresh-tickets", None),
+            Command::Edit { id, .. } => ("edit", Some(id.as_str())),
+            Command::Delete { id } => ("delete", Some(id.as_str())),
+            Command::Show { id, .. } => ("show", Some(id.as_str())),
+            Command::Start { id } => ("start", Some(id.as_str())),
+            Command::Wait { id, .. } => ("wait", Some(id.as_str())),
+            Command::Done { id, .. } => ("done", id.as_deref()),
+            Command::Cancel { id, .. } => ("cancel", Some(id.as_str())),
+            Command::Undo => ("undo", None),
+            Command::Routine { action } => match action {
+                RoutineCommand::List => ("routine list", None),
+                RoutineCommand::Start { .. } => ("routine start", None),
+            },
+            Command::Seed { action, .. } => match action {
+                None => ("seed", None),
+                Some(SeedCommand::List) => ("seed list", None),
+                Some(SeedCommand::Remove { .. }) => ("seed remove", None),
+                Some(SeedCommand::Run) => ("seed run", None),
+            },
+            #[cfg(feature = "sync")]
+            Command::Sync {
+                action: Some(SyncCommand::Status),
+                ..
+            } => ("sync status", None),
+            #[cfg(feature = "sync")]
+            Command::Sync {
+                action: Some(SyncCommand::Queue),
+                ..
+            } => ("sync queue", None),
+            #[cfg(feature = "sync")]
+            Command::Sync {
+                action: Some(SyncCommand::Flush { .. }),
+                ..
+            } => ("sync flush", None),
+            #[cfg(feature = "sync")]
+            Command::Sync { .. } => ("sync", None),
+            Command::Schedule { id, .. } => ("schedule", Some(id.as_str())),
+            Command::Reschedule { id, .. } => ("reschedule", Some(id.as_str())),
+            Command::Notify { action, .. } => match action {
+                None => ("notify", None),
+                Some(NotifyCommand::InstallSchedule { .. }) => ("notify install-schedule", None),
+                Some(NotifyCommand::UninstallSchedule) => ("notify uninstall-schedule", None),
+                Some(NotifyCommand::Test) => ("notify test", None),
+                Some(NotifyCommand::RegisterWindowsApp) => ("notify register-windows-app", None),
+            },
+            // The sandbox is gone by the time the entry would be written.
+            Command::Demo { .. } => return None,
+            Command::Context { action } => match action {
+                None | Some(ContextCommand::List) => ("context list", None),
+                Some(ContextCommand::Define { .. }) => ("context define", None),
+                Some(ContextCommand::Delete { .. }) => ("context delete", None),
+                Some(ContextCommand::Set { .. }) => ("context set", None),
+                Some(ContextCommand::None) => ("context none", None),
+            },
+            Command::Upcoming { .. } => ("upcoming", None),
+            Command::Triage => ("triage", None),
+            Command::Journal { .. } => ("journal", None),
+            Command::Query { .. } => ("query", None),
+            Command::Search { .. } => ("search", None),
+            Command::Index { action } => match action {
+                IndexCommand::Rebuild => ("index rebuild", None),
+            },
+            Command::Snapshot { action } => match action {
+                None | Some(SnapshotCommand::List) => ("snapshot list", None),
+                Some(SnapshotCommand::Save { .. }) => ("snapshot save", None),
+                Some(SnapshotCommand::Restore { .. }) => ("snapshot restore", None),
+            },
+            Command::Events { action } => match action {
+                EventsCommand::Pull { .. } => ("events pull", None),
+                EventsCommand::Ack { .. } => ("events ack", None),
+            },
+            Command::Webhooks { action } => match action {
+                WebhooksCommand::Deliver => ("webhooks deliver", None),
+                WebhooksCommand::Status => ("webhooks status", None),
+            },
+            Command::Run { .. } => ("run", None),
+            Command::Diff { .. } => ("diff", None),
+            Command::Export { .. } => ("export", None),
+            Command::Import { .. } => ("import", None),
+            Command::Graph { .. } => ("graph", None),
+            Command::Maintain => ("maintain", None),
+            Command::Info => ("info", None),
+            Command::Migrate { .. } => ("migrate", None),
+            Command::Doctor { .. } => ("doctor", None),
+            Command::Secret { action } => match action {
+                SecretCommand::Set { .. } => ("secret set", None),
+                SecretCommand::Delete { .. } => ("secret delete", None),
+                SecretCommand::Check { .. } => ("secret check", None),
+            },
+            Command::Flush => ("flush", None),
+            Command::Digest { .. } => ("digest", None),
+            Command::Listen { .. } => ("listen", None),
+            Command::Watch { .. } => ("watch", None),
+            Command::Status { .. } => ("status", None),
+            Command::Insights => ("insights", None),
+            Command::Report { report } => match report {
+                ReportCommand::Accuracy => ("report accuracy", None),
+            },
+            Command::Schema { .. } => ("schema", None),
+            Command::Init { .. } => ("init", None),
+            Command::List { list, .. } => match list {
+                ListCommand::Today => ("list today", None),
+                ListCommand::Backlog => ("list backlog", None),
+                ListCommand::Week { .. } => ("list week", None),
+                ListCommand::Quarter { .. } => ("list quarter", None),
+            },
+        };
+        Some(entry)
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -111,17 +1001,255 @@ pub enum ListCommand {
     ///
     /// Example: todo list backlog
     Backlog,
+    /// List tasks scheduled in an ISO week, Monday to Sunday; this week by default
+    ///
+    /// Example: todo list week
+    /// Example: todo list week --iso 2026-W07
+    Week {
+        /// The week to list instead of this one
+        #[arg(long, value_name = "YYYY-Www")]
+        iso: Option<String>,
+    },
+    /// List tasks scheduled in a calendar quarter; this quarter by default
+    ///
+    /// Example: todo list quarter
+    /// Example: todo list quarter --iso 2026-Q1
+    Quarter {
+        /// The quarter to list instead of this one
+        #[arg(long, value_name = "YYYY-Qn")]
+        iso: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ContextCommand {
+    /// Show defined contexts and the active one
+    ///
+    /// Example: todo context list
+    List,
+    /// Define or replace a context
+    ///
+    /// Example: todo context define home "tag:home and not status:completed"
+    Define { name: String, filter: String },
+    /// Delete a context, clearing it first if it is active
+    ///
+    /// Example: todo context delete home
+    Delete { name: String },
+    /// Apply a context to every list until `todo context none`
+    ///
+    /// Example: todo context set work
+    Set { name: String },
+    /// Stop applying the active context
+    ///
+    /// Example: todo context none
+    None,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetaCommand {
+    /// Show the task's metadata
+    ///
+    /// Example: todo meta 1 list
+    List,
+    /// Set a key, replacing any previous value
+    ///
+    /// Example: todo meta 1 set sprint 42
+    Set { key: String, value: String },
+    /// Remove a key
+    ///
+    /// Example: todo meta 1 unset sprint
+    Unset { key: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Show saved snapshots, oldest first
+    ///
+    /// Example: todo snapshot list
+    List,
+    /// Save the current store under a name
+    ///
+    /// Example: todo snapshot save "before cleanup"
+    Save { name: String },
+    /// Replace the store with a saved snapshot
+    ///
+    /// Example: todo snapshot restore "before cleanup"
+    Restore { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EventsCommand {
+    /// Print unacknowledged events, oldest first
+    ///
+    /// Example: todo --json events pull --limit 100
+    Pull {
+        /// Acknowledge the printed events right away, so the next pull skips them
+        #[arg(long)]
+        ack: bool,
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+    },
+    /// Acknowledge every event up to and including SEQ, once they are handled
+    ///
+    /// Example: todo events ack 42
+    Ack {
+        #[arg(value_name = "SEQ")]
+        seq: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WebhooksCommand {
+    /// Post every queued delivery now, retrying each a few times
+    Deliver,
+    /// List the deliveries still queued, with their attempts and last error
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// Rebuild the search index from the store, e.g. after editing the store file by hand
+    ///
+    /// Example: todo index rebuild
+    Rebuild,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RoutineCommand {
+    /// Show the configured routines and their items
+    ///
+    /// Example: todo routine list
+    List,
+    /// Add a task due by the end of today for each item, once a day
+    ///
+    /// Example: todo routine start morning
+    Start { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SeedCommand {
+    /// Show the rules, numbered, with when each last added a task
+    ///
+    /// Example: todo seed list
+    List,
+    /// Remove a rule by its number in `seed list`
+    ///
+    /// Example: todo seed remove 2
+    Remove { number: usize },
+    /// Add a task for each rule that has fallen due since it last added one
+    ///
+    /// Example: todo seed run
+    Run,
+}
+
+#[cfg(feature = "sync")]
+#[derive(Subcommand, Debug)]
+pub enum SyncCommand {
+    /// Show each remote with its last sync, local changes since, and unsettled conflicts
+    ///
+    /// Reads only files next to the store; no remote is contacted.
+    ///
+    /// Example: todo sync status
+    Status,
+    /// List the changes made here that each remote has not received yet
+    ///
+    /// Changes stay queued while a remote is unreachable, until a sync reaches it again.
+    /// Reads only files next to the store; no remote is contacted.
+    ///
+    /// Example: todo sync queue
+    Queue,
+    /// Sync every remote in the queue, retrying the unreachable ones
+    ///
+    /// A remote that still fails keeps its changes queued; the others are synced anyway.
+    ///
+    /// Example: todo sync flush
+    /// Example: todo sync flush --prefer newest
+    Flush {
+        /// Settle every conflict this way instead of asking
+        #[arg(long, value_enum, value_name = "SIDE")]
+        prefer: Option<SyncPrefer>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCommand {
+    /// Compare estimates with tracked time per tag, for completed tasks
+    ///
+    /// Set an estimate with `todo meta <id> set estimate 2h`; `start` tracks time until the
+    /// task is completed, waiting, or cancelled. Tasks without both are counted but left out.
+    ///
+    /// Example: todo report accuracy
+    Accuracy,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SecretCommand {
+    /// Save a secret, reading its value from the first line of stdin so it stays out of shell history
+    ///
+    /// Example: todo secret set github_token
+    Set { name: String },
+    /// Remove a secret from the keyring
+    ///
+    /// Example: todo secret delete github_token
+    Delete { name: String },
+    /// Check that a secret can be read, from `TODOAPP_SECRET_<NAME>` or the keyring
+    ///
+    /// Example: todo secret check github_token
+    Check { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NotifyCommand {
+    /// Run `notify` periodically via a systemd user timer or Windows Scheduled Task
+    ///
+    /// Example: todo notify install-schedule --every 30m
+    InstallSchedule {
+        #[arg(long, default_value = "15m", value_name = "INTERVAL")]
+        every: String,
+    },
+    /// Remove the schedule created by install-schedule
+    ///
+    /// Example: todo notify uninstall-schedule
+    UninstallSchedule,
+    /// Send a sample notification through the configured backend and each route
+    ///
+    /// Reports which destinations worked and fails if any did not.
+    ///
+    /// Example: todo notify test
+    Test,
+    /// Register notifications.windows.app_id so toasts show its display name and icon
+    ///
+    /// Writes HKEY_CURRENT_USER\Software\Classes\AppUserModelId\<app_id>; Windows only.
+    ///
+    /// Example: todo notify register-windows-app
+    RegisterWindowsApp,
 }
 
 /// Flag name used to identify config override arguments by the runtime.
 pub const CONFIG_OVERRIDE_FLAG: &str = "--config-override";
 
+/// Flag names the runtime reads before loading the config, since they decide which config and
+/// store to use.
+pub const CONFIG_FLAG: &str = "--config";
+pub const PROFILE_FLAG: &str = "--profile";
+pub const RECORD_FLAG: &str = "--record";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfigOverrideTarget {
     Theme,
     Alias(String),
 }
 
+impl ConfigOverrideTarget {
+    /// The config field the override changes, as `allow_overrides` names it.
+    pub fn field(&self) -> &'static str {
+        match self {
+            ConfigOverrideTarget::Theme => "theme",
+            ConfigOverrideTarget::Alias(_) => "aliases",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedConfigOverride {
     pub target: ConfigOverrideTarget,
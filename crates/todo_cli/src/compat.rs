@@ -0,0 +1,108 @@
+//! Older output shapes and flags, kept so scripts written against them keep working while they
+//! move to the current forms.
+//!
+//! `--compat VERSION` asks for the output of an earlier [`JSON_SCHEMA_VERSION`]. Each entry in
+//! [`DEPRECATIONS`] names the old form, its replacement, and the last version that still
+//! accepts it; using an old form prints a warning pointing at the new one.
+
+use crate::cli::Cli;
+use crate::schema::JSON_SCHEMA_VERSION;
+
+/// The oldest version `--compat` accepts: 0 is the JSON printed before the schema was
+/// versioned, with overdue folded into `status`.
+pub const OLDEST_COMPAT: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation {
+    pub old: &'static str,
+    pub new: &'static str,
+    /// The last `--compat` version, and release line, that still accepts the old form.
+    pub removed_after: u32,
+}
+
+pub const DEPRECATIONS: [Deprecation; 2] = [
+    Deprecation {
+        old: "--legacy-json",
+        new: "--json --compat 0",
+        removed_after: 1,
+    },
+    Deprecation {
+        old: "--compat 0 (status \"pending (overdue)\", no `overdue` field)",
+        new: "--json, reading the `overdue` field",
+        removed_after: 1,
+    },
+];
+
+/// Parses `--compat`: a version from [`OLDEST_COMPAT`] up to the current one, optionally
+/// written with a leading `v`.
+pub fn parse_version(value: &str) -> Result<u32, String> {
+    let trimmed = value.trim();
+    let digits = trimmed.strip_prefix('v').unwrap_or(trimmed);
+    match digits.parse::<u32>() {
+        Ok(version) if (OLDEST_COMPAT..=JSON_SCHEMA_VERSION).contains(&version) => Ok(version),
+        _ => Err(format!(
+            "unsupported version '{trimmed}'; expected {OLDEST_COMPAT} to {JSON_SCHEMA_VERSION}"
+        )),
+    }
+}
+
+/// JSON tasks in the version 0 shape: only pending and completed, and overdue as
+/// `"pending (overdue)"`.
+pub fn legacy_status(cli: &Cli) -> bool {
+    cli.legacy_json || cli.compat == Some(0)
+}
+
+/// One warning for each old form `cli` uses, saying what replaces it.
+pub fn warnings(cli: &Cli) -> Vec<String> {
+    let used = [cli.legacy_json, cli.compat == Some(0)];
+    DEPRECATIONS
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| *used)
+        .map(|(deprecation, _)| {
+            format!(
+                "{} is deprecated and goes away after version {}; use {}",
+                deprecation.old, deprecation.removed_after, deprecation.new
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{legacy_status, parse_version, warnings};
+    use crate::cli::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn parse_version_accepts_known_versions_only() {
+        assert_eq!(parse_version("0"), Ok(0));
+        assert_eq!(parse_version(" v1 "), Ok(1));
+        assert_eq!(
+            parse_version("7").unwrap_err(),
+            "unsupported version '7'; expected 0 to 1"
+        );
+        assert!(parse_version("latest").is_err());
+    }
+
+    #[test]
+    fn old_forms_select_the_legacy_shape_and_warn() {
+        let current = Cli::parse_from(["todo", "--json", "list", "today"]);
+        let pinned = Cli::parse_from(["todo", "--json", "--compat", "1", "list", "today"]);
+        let legacy = Cli::parse_from(["todo", "--legacy-json", "list", "today"]);
+        let compat = Cli::parse_from(["todo", "--json", "--compat", "0", "list", "today"]);
+
+        assert!(!legacy_status(&current));
+        assert!(warnings(&current).is_empty());
+        assert!(!legacy_status(&pinned));
+        assert!(warnings(&pinned).is_empty());
+        assert!(legacy_status(&legacy));
+        assert_eq!(
+            warnings(&legacy),
+            ["--legacy-json is deprecated and goes away after version 1; use --json --compat 0"]
+        );
+        assert!(legacy_status(&compat));
+        assert_eq!(warnings(&compat).len(), 1);
+        assert!(warnings(&compat)[0].ends_with("use --json, reading the `overdue` field"));
+    }
+}
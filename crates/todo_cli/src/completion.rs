@@ -0,0 +1,210 @@
+//! Tab completion for interactive mode: command names first, then pending task IDs for
+//! commands whose first argument is a task ID.
+
+use crate::cli::Cli;
+use clap::CommandFactory;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::collections::{BTreeSet, HashMap};
+use todo_core::model::Task;
+use todo_core::storage::json_store;
+use todo_core::text::fold_case;
+
+/// Words interactive mode handles itself rather than passing to the command parser.
+const SESSION_WORDS: [&str; 5] = ["exit", "help", "quit", "set", "unset"];
+
+pub struct ReplHelper {
+    commands: BTreeSet<String>,
+    id_commands: BTreeSet<String>,
+    aliases: HashMap<String, String>,
+}
+
+impl ReplHelper {
+    pub fn new(aliases: &HashMap<String, String>) -> Self {
+        let mut commands: BTreeSet<String> =
+            SESSION_WORDS.iter().map(|word| word.to_string()).collect();
+        let mut id_commands = BTreeSet::new();
+        for command in Cli::command().get_subcommands() {
+            let name = command.get_name().to_string();
+            if command
+                .get_positionals()
+                .next()
+                .is_some_and(|arg| arg.get_id() == "id")
+            {
+                id_commands.insert(name.clone());
+            }
+            commands.insert(name);
+        }
+        commands.extend(aliases.keys().cloned());
+
+        ReplHelper {
+            commands,
+            id_commands,
+            aliases: aliases.clone(),
+        }
+    }
+
+    /// Start of the word under the cursor and the candidates that can replace it. `tasks` is
+    /// only read when a task ID is being completed.
+    pub fn candidates(
+        &self,
+        line: &str,
+        pos: usize,
+        tasks: impl FnOnce() -> Vec<Task>,
+    ) -> (usize, Vec<Pair>) {
+        let before = &line[..pos];
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, ch)| ch.is_whitespace())
+            .map(|(index, ch)| index + ch.len_utf8())
+            .unwrap_or(0);
+        let word = &before[start..];
+        let previous: Vec<&str> = before[..start]
+            .split_whitespace()
+            .filter(|word| !word.starts_with('-'))
+            .collect();
+
+        let candidates = match previous.as_slice() {
+            [] => self
+                .commands
+                .iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| Pair {
+                    display: command.clone(),
+                    replacement: command.clone(),
+                })
+                .collect(),
+            [command] if self.id_commands.contains(self.resolve(command)) => {
+                task_candidates(&tasks(), word)
+            }
+            _ => Vec::new(),
+        };
+        (start, candidates)
+    }
+
+    /// The command an alias expands to, or the word itself.
+    fn resolve<'a>(&'a self, word: &'a str) -> &'a str {
+        self.aliases
+            .get(word)
+            .and_then(|replacement| replacement.split_whitespace().next())
+            .unwrap_or(word)
+    }
+}
+
+/// Pending tasks whose ID starts with `word` or whose title contains it, ignoring case.
+fn task_candidates(tasks: &[Task], word: &str) -> Vec<Pair> {
+    let needle = fold_case(word);
+    tasks
+        .iter()
+        .filter(|task| task.status.is_open())
+        .filter(|task| {
+            task.id.starts_with(word)
+                || (!needle.is_empty() && fold_case(&task.title).contains(&needle))
+        })
+        .map(|task| Pair {
+            display: format!("{}  {}", task.id, task.title),
+            replacement: task.id.clone(),
+        })
+        .collect()
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        // Reloaded on every request so tasks added earlier in the session are offered. A store
+        // that cannot be read simply offers nothing.
+        Ok(self.candidates(line, pos, || {
+            json_store::store_path()
+                .and_then(|path| json_store::load_tasks(&path))
+                .unwrap_or_default()
+        }))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplHelper;
+    use std::collections::HashMap;
+    use todo_core::model::{Task, TaskStatus};
+
+    fn task(id: &str, title: &str, status: TaskStatus) -> Task {
+        Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            status,
+            created_at: "2025-12-20T00:00:00Z".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            task("1042", "Write report", TaskStatus::Pending),
+            task("1043", "Buy milk", TaskStatus::Pending),
+            task("1044", "Old errand", TaskStatus::Completed),
+            task("2001", "Report expenses", TaskStatus::Pending),
+        ]
+    }
+
+    fn replacements(helper: &ReplHelper, line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = helper.candidates(line, line.len(), tasks);
+        (
+            start,
+            pairs.into_iter().map(|pair| pair.replacement).collect(),
+        )
+    }
+
+    #[test]
+    fn completes_pending_ids_after_id_commands() {
+        let helper = ReplHelper::new(&HashMap::new());
+
+        assert_eq!(
+            replacements(&helper, "done "),
+            (
+                5,
+                vec!["1042".to_string(), "1043".to_string(), "2001".to_string()]
+            )
+        );
+        assert_eq!(
+            replacements(&helper, "done 104"),
+            (5, vec!["1042".to_string(), "1043".to_string()])
+        );
+        assert_eq!(
+            replacements(&helper, "--json show REPORT"),
+            (12, vec!["1042".to_string(), "2001".to_string()])
+        );
+        assert!(replacements(&helper, "done 1042 ").1.is_empty());
+        assert!(replacements(&helper, "add ").1.is_empty());
+    }
+
+    #[test]
+    fn completes_command_names_and_aliases() {
+        let aliases = HashMap::from([("d".to_string(), "done".to_string())]);
+        let helper = ReplHelper::new(&aliases);
+
+        let (_, commands) = replacements(&helper, "de");
+        assert_eq!(commands, vec!["delete".to_string(), "demo".to_string()]);
+        assert!(replacements(&helper, "").1.contains(&"exit".to_string()));
+        assert_eq!(replacements(&helper, "d 2").1, vec!["2001".to_string()]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Panic hook that saves a crash report instead of dumping a raw backtrace on end users.
+
+use crate::cli::Cli;
+use clap::CommandFactory;
+use std::backtrace::Backtrace;
+use std::collections::HashSet;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use todo_core::error::AppError;
+
+const REDACTED: &str = "<redacted>";
+
+/// Replaces the default panic output with a one-line apology pointing at a report under
+/// `<config dir>/crash-reports`. Setting `RUST_BACKTRACE` keeps the default output as well.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // `todo list | head` closes stdout early; that is not a crash worth reporting. Exit
+        // quietly with the status a shell reports for SIGPIPE.
+        if panic_message(info).contains("Broken pipe") {
+            std::process::exit(141);
+        }
+
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let report = render_report(info, &redact_args(&args), &Backtrace::force_capture());
+        match write_report(&report) {
+            Ok(path) => eprintln!(
+                "todo_opt ran into an unexpected problem and had to stop. Sorry! A crash report was saved to {}",
+                path.display()
+            ),
+            Err(_) => {
+                eprintln!("todo_opt ran into an unexpected problem and had to stop. Sorry!");
+                eprintln!("{report}");
+            }
+        }
+
+        if std::env::var_os("RUST_BACKTRACE").is_some() {
+            default_hook(info);
+        }
+    }));
+}
+
+/// Keeps subcommand names and flag names; every other argument may contain task titles or
+/// notes and is replaced.
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let subcommands = subcommand_names();
+    args.iter()
+        .map(|arg| {
+            if let Some((flag, _)) = arg.split_once('=')
+                && flag.starts_with('-')
+            {
+                format!("{flag}={REDACTED}")
+            } else if arg.starts_with('-') || subcommands.contains(arg.as_str()) {
+                arg.clone()
+            } else {
+                REDACTED.to_string()
+            }
+        })
+        .collect()
+}
+
+fn subcommand_names() -> HashSet<String> {
+    fn collect(command: &clap::Command, names: &mut HashSet<String>) {
+        for sub in command.get_subcommands() {
+            names.insert(sub.get_name().to_string());
+            collect(sub, names);
+        }
+    }
+
+    let mut names = HashSet::new();
+    collect(&Cli::command(), &mut names);
+    names
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    info.payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
+}
+
+fn render_report(info: &PanicHookInfo<'_>, args: &[String], backtrace: &Backtrace) -> String {
+    let message = panic_message(info);
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    format_report(&message, &location, args, &backtrace.to_string())
+}
+
+fn format_report(message: &str, location: &str, args: &[String], backtrace: &str) -> String {
+    let timestamp = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| "unknown".to_string());
+    let store_path = todo_core::storage::json_store::store_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|err| format!("unavailable ({err})"));
+
+    format!(
+        "todo_opt crash report\n\
+         version: {}\n\
+         time: {timestamp}\n\
+         os: {} ({})\n\
+         args: {}\n\
+         store: {store_path}\n\
+         panic: {message}\n\
+         location: {location}\n\
+         \n\
+         backtrace:\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        args.join(" "),
+    )
+}
+
+fn write_report(report: &str) -> Result<PathBuf, AppError> {
+    let config_path = todo_core::config::config_path()?;
+    let dir = config_path
+        .parent()
+        .ok_or_else(|| AppError::invalid_data("config path has no parent directory"))?
+        .join("crash-reports");
+    std::fs::create_dir_all(&dir).map_err(|err| AppError::io(err.to_string()))?;
+
+    let stamp = OffsetDateTime::now_utc()
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let path = dir.join(format!("crash-{stamp}-{}.txt", std::process::id()));
+    std::fs::write(&path, report).map_err(|err| AppError::io(err.to_string()))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_report, redact_args};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn redact_args_keeps_commands_and_flags_only() {
+        let redacted = redact_args(&strings(&[
+            "--json",
+            "add",
+            "Call my doctor",
+            "--note=private",
+            "--tag",
+            "health",
+            "list",
+            "today",
+        ]));
+
+        assert_eq!(
+            redacted,
+            strings(&[
+                "--json",
+                "add",
+                "<redacted>",
+                "--note=<redacted>",
+                "--tag",
+                "<redacted>",
+                "list",
+                "today",
+            ])
+        );
+    }
+
+    #[test]
+    fn report_includes_context_for_triage() {
+        let report = format_report(
+            "boom",
+            "src/main.rs:1:1",
+            &strings(&["show", "<redacted>"]),
+            "frame 0",
+        );
+
+        assert!(report.contains(&format!("version: {}", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("args: show <redacted>"));
+        assert!(report.contains("store: "));
+        assert!(report.contains("panic: boom"));
+        assert!(report.contains("location: src/main.rs:1:1"));
+        assert!(report.contains("backtrace:\nframe 0"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Sandbox for `todo demo`: a throwaway store seeded with example tasks.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime, UtcOffset};
+use todo_core::error::AppError;
+use todo_core::model::{CompletionEntry, Task, TaskStatus};
+use todo_core::storage::json_store::{self, TaskState};
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether this process is running against a demo sandbox.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Temporary store directory, removed when dropped.
+pub struct DemoSandbox {
+    dir: PathBuf,
+}
+
+impl DemoSandbox {
+    /// Creates the sandbox store and redirects this process's store path to it.
+    pub fn start() -> Result<Self, AppError> {
+        let nanos = todo_core::clock::now_utc().unix_timestamp_nanos();
+        let dir = std::env::temp_dir().join(format!("todoapp-demo-{}-{nanos}", std::process::id()));
+        let sandbox = DemoSandbox { dir };
+        let store = sandbox.store_path();
+
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let now = todo_core::clock::now_utc().to_offset(offset);
+        json_store::save_state(&store, &sample_state(now)?)?;
+        json_store::override_store_path(store)?;
+        ACTIVE.store(true, Ordering::Relaxed);
+
+        Ok(sandbox)
+    }
+
+    pub fn store_path(&self) -> PathBuf {
+        self.dir.join("tasks.json")
+    }
+}
+
+impl Drop for DemoSandbox {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+/// Example tasks relative to `now`, covering overdue, urgent, scheduled, tagged, completed,
+/// and focused tasks so every command has something to show.
+pub fn sample_state(now: OffsetDateTime) -> Result<TaskState, AppError> {
+    let at = |offset: Duration| -> Result<String, AppError> {
+        (now + offset)
+            .format(&Rfc3339)
+            .map_err(|err| AppError::invalid_data(err.to_string()))
+    };
+    let task = |id: &str, title: &str, created: Duration| -> Result<Task, AppError> {
+        Ok(Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            created_at: at(created)?,
+            ..Default::default()
+        })
+    };
+
+    let mut renew = task("1001", "Renew passport", -Duration::days(10))?;
+    renew.due_at = Some(at(-Duration::days(2))?);
+    renew.urgent = true;
+    renew.tags = vec!["personal".to_string()];
+
+    let mut standup = task("1002", "Prepare stand-up notes", -Duration::days(1))?;
+    standup.due_at = Some(at(Duration::hours(1))?);
+    standup.tags = vec!["work".to_string()];
+    standup.project = Some("team".to_string());
+
+    let mut review = task("1003", "Review pull request", -Duration::days(1))?;
+    review.due_at = Some(at(-Duration::hours(3))?);
+    review.tags = vec!["work".to_string()];
+    review.project = Some("storage".to_string());
+    review.notes = Some("Focus on the storage changes first.".to_string());
+
+    let mut groceries = task("1004", "Buy groceries", -Duration::days(3))?;
+    groceries.due_at = Some(at(Duration::days(1))?);
+    groceries.tags = vec!["personal".to_string(), "errands".to_string()];
+
+    let reading = task("1005", "Read the clap documentation", -Duration::days(5))?;
+
+    let mut report = task("1006", "Send weekly report", -Duration::days(7))?;
+    let completed_at = at(-Duration::days(1))?;
+    report.status = TaskStatus::Completed;
+    report.completed_at = Some(completed_at.clone());
+    report.completion_history = vec![CompletionEntry {
+        message: "Sent to the team".to_string(),
+        completed_at,
+    }];
+    report.tags = vec!["work".to_string()];
+    report.project = Some("team".to_string());
+
+    Ok(TaskState {
+        tasks: vec![renew, standup, review, groceries, reading, report],
+        focused_task_id: Some("1003".to_string()),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sample_state;
+    use time::OffsetDateTime;
+    use todo_core::model::TaskStatus;
+
+    #[test]
+    fn sample_state_covers_common_cases() {
+        let state = sample_state(OffsetDateTime::now_utc()).unwrap();
+
+        let focused = state.focused_task_id.as_deref().unwrap();
+        assert!(state.tasks.iter().any(|task| task.id == focused));
+        assert!(state.tasks.iter().any(|task| task.urgent));
+        assert!(state.tasks.iter().any(|task| task.due_at.is_none()));
+        assert!(
+            state
+                .tasks
+                .iter()
+                .any(|task| task.status == TaskStatus::Completed)
+        );
+    }
+}
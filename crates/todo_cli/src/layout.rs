@@ -0,0 +1,111 @@
+//! How wide plain output may be: tables drop their less important columns and cut long titles
+//! to fit, and `show` wraps long values under their label instead of letting the terminal break
+//! them mid-word.
+//!
+//! `--width` wins; otherwise a terminal on stdout is measured, with `COLUMNS` standing in when
+//! it can't be. Output that isn't going to a terminal has no width, so pipes and files get
+//! whole titles.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use todo_core::text;
+
+/// The narrowest `--width` accepted. A task table this narrow is down to its IDs and titles;
+/// below it, titles would be cut to a few characters.
+pub const MIN_WIDTH: usize = 40;
+
+/// Wrapped text never gets narrower than this, however deep the indent.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// `--width` for the command being run; zero when it wasn't given.
+static OVERRIDE: AtomicUsize = AtomicUsize::new(0);
+
+/// Parses `--width`: a column count of at least [`MIN_WIDTH`].
+pub fn parse_width(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(width) if width >= MIN_WIDTH => Ok(width),
+        _ => Err(format!(
+            "invalid width '{}'; expected a number of columns, at least {MIN_WIDTH}",
+            value.trim()
+        )),
+    }
+}
+
+/// Set per command, so `--width` typed on one interactive line doesn't stick to the next.
+pub fn set_width(width: Option<usize>) {
+    OVERRIDE.store(width.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Columns available to plain output, or `None` when it may run as wide as it needs.
+pub fn width() -> Option<usize> {
+    match OVERRIDE.load(Ordering::Relaxed) {
+        0 => {}
+        width => return Some(width),
+    }
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(columns), _)| usize::from(columns))
+        .or_else(|| std::env::var("COLUMNS").ok()?.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+}
+
+/// `text` as lines to print after a prefix `indent` columns wide, each later line indented by
+/// as much. Without a known width only the line breaks already in `text` apply.
+pub fn hanging(text: &str, indent: usize) -> Vec<String> {
+    match width() {
+        Some(width) => text::wrap_to_width(text, width.saturating_sub(indent).max(MIN_WRAP_WIDTH)),
+        None => {
+            let lines: Vec<String> = text.lines().map(str::to_string).collect();
+            if lines.is_empty() {
+                vec![String::new()]
+            } else {
+                lines
+            }
+        }
+    }
+}
+
+/// Terminal columns `styled` occupies once its color escapes are left out.
+pub fn visible_width(styled: &str) -> usize {
+    text::display_width(&strip_styles(styled))
+}
+
+/// `styled` without the palette's color escapes.
+pub fn strip_styles(styled: &str) -> String {
+    let mut plain = String::with_capacity(styled.len());
+    let mut chars = styled.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Palette escapes are all `ESC [ ... m`.
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_width, visible_width};
+
+    #[test]
+    fn parse_width_requires_room_for_a_table() {
+        assert_eq!(parse_width("80"), Ok(80));
+        assert!(parse_width("39").is_err());
+        assert!(parse_width("wide").is_err());
+    }
+
+    #[test]
+    fn visible_width_skips_color_escapes() {
+        assert_eq!(visible_width("\x1b[38;5;196m●\x1b[0m"), 1);
+        assert_eq!(visible_width("🔥\x1b[38;5;33m●\x1b[0m"), 3);
+        assert_eq!(visible_width("plain"), 5);
+    }
+}
@@ -1 +1,15 @@
+pub mod autosave;
 pub mod cli;
+pub mod compat;
+pub mod completion;
+pub mod crash;
+pub mod demo;
+pub mod layout;
+pub mod listen;
+pub mod pager;
+pub mod schema;
+pub mod session;
+pub mod suggest;
+pub mod transcript;
+pub mod warnings;
+pub mod watch;
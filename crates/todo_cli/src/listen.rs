@@ -0,0 +1,195 @@
+//! Control socket for `todo listen`: one command line in, one JSON reply line out.
+//!
+//! Each command runs as `todo_opt --json --envelope <args>` in a child process against the
+//! listener's store and config, so a reply is exactly the envelope that invocation prints: the
+//! `{"schema_version", "kind", "data"}` result, or the error envelope on failure. Commands are
+//! run one at a time, in the order they arrive, across all connections.
+
+use crate::schema;
+use serde_json::Value;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use todo_core::error::AppError;
+
+/// Turns one request line into the arguments to run, or the error to reply with.
+pub type RequestParser = dyn Fn(&str) -> Result<Vec<String>, AppError> + Send + Sync;
+
+/// Accept connections on `socket` until the process is stopped. Blank lines are ignored.
+#[cfg(unix)]
+pub fn serve(socket: &Path, parse: Box<RequestParser>) -> Result<(), AppError> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::sync::{Arc, Mutex};
+
+    let store_path = todo_core::storage::json_store::store_path()?;
+    let config_path = todo_core::config::config_path()?;
+    let socket_file = SocketFile::bind(socket)?;
+    let listener = socket_file.listen()?;
+    eprintln!("Listening on {}", socket.display());
+
+    let parse: Arc<RequestParser> = Arc::from(parse);
+    let store_path = Arc::new(store_path);
+    let config_path = Arc::new(config_path);
+    // Each command is a full store transaction; running them concurrently could lose updates.
+    let turn = Arc::new(Mutex::new(()));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let parse = Arc::clone(&parse);
+        let store_path = Arc::clone(&store_path);
+        let config_path = Arc::clone(&config_path);
+        let turn = Arc::clone(&turn);
+        std::thread::spawn(move || {
+            let Ok(reader) = stream.try_clone() else {
+                return;
+            };
+            let mut writer = stream;
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let reply = {
+                    let _turn = turn.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    match parse(&line) {
+                        Ok(args) => run_request(&store_path, &config_path, &args),
+                        Err(err) => error_reply(&err),
+                    }
+                };
+                if writeln!(writer, "{reply}").is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve(_socket: &Path, _parse: Box<RequestParser>) -> Result<(), AppError> {
+    Err(AppError::invalid_input(
+        "listen needs Unix domain sockets, which this platform does not provide",
+    ))
+}
+
+/// Runs one command against `store_path` with the config at `config_path` and returns its reply
+/// as a single line.
+pub fn run_request(store_path: &Path, config_path: &Path, args: &[String]) -> String {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(err) => return error_reply(&AppError::io(err.to_string())),
+    };
+    // The reply format is fixed; repeating either flag would be a parse error in the child.
+    let args = args
+        .iter()
+        .filter(|arg| !matches!(arg.as_str(), "--json" | "--envelope"));
+    let output = match Command::new(exe)
+        .args(["--json", "--envelope"])
+        .args(args)
+        .env("TODOAPP_STORE_PATH", store_path)
+        .env("TODOAPP_CONFIG_PATH", config_path)
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) => return error_reply(&AppError::io(err.to_string())),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if let Ok(reply) = serde_json::from_str::<Value>(stdout.trim()) {
+        return reply.to_string();
+    }
+    if output.status.success() {
+        return error_reply(&AppError::invalid_data("command did not print JSON"));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("ERROR: "))
+        .unwrap_or("command failed");
+    let warnings: Vec<String> = stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("WARNING: "))
+        .map(str::to_string)
+        .collect();
+    schema::error_envelope(&AppError::invalid_input(message), &warnings).to_string()
+}
+
+pub fn error_reply(err: &AppError) -> String {
+    schema::error_envelope(err, &[]).to_string()
+}
+
+/// The socket path, removed again when the listener stops.
+#[cfg(unix)]
+struct SocketFile {
+    path: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+impl SocketFile {
+    /// Claims `path`, clearing a socket left behind by a listener that did not shut down.
+    fn bind(path: &Path) -> Result<Self, AppError> {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::UnixStream;
+
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(AppError::invalid_input(format!(
+                    "{} exists and is not a socket",
+                    path.display()
+                )));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(AppError::invalid_input(format!(
+                    "another listener is using {}",
+                    path.display()
+                )));
+            }
+            std::fs::remove_file(path).map_err(|err| AppError::io(err.to_string()))?;
+        }
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(|err| AppError::io(err.to_string()))?;
+        }
+
+        Ok(SocketFile {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Binds the socket in a `0o700` directory of its own and moves it into place only once
+    /// it is `0o600`, so no other user can connect while the mode is still the umask's.
+    fn listen(&self) -> Result<std::os::unix::net::UnixListener, AppError> {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        use std::os::unix::net::UnixListener;
+
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let staging = self
+            .path
+            .with_file_name(format!(".{name}.{}", std::process::id()));
+        let staged = staging.join("socket");
+        let listener = std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&staging)
+            .and_then(|()| UnixListener::bind(&staged))
+            .and_then(|listener| {
+                std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+                std::fs::rename(&staged, &self.path)?;
+                Ok(listener)
+            });
+        std::fs::remove_dir_all(&staging).ok();
+        listener.map_err(|err| AppError::io(format!("{}: {err}", self.path.display())))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
@@ -1,32 +1,150 @@
 use clap::{CommandFactory, Parser};
-use std::collections::{HashMap, HashSet};
-use std::io::{self, BufRead};
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{CompletionType, Editor};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::Write;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use todo_cli::autosave::Autosave;
+#[cfg(feature = "sync")]
+use todo_cli::cli::SyncCommand;
 use todo_cli::cli::{
-    CONFIG_OVERRIDE_FLAG, Cli, Command, ConfigOverrideTarget, ListCommand, ParsedConfigOverride,
+    CONFIG_FLAG, CONFIG_OVERRIDE_FLAG, Cli, Command, ConfigOverrideTarget, ContextCommand,
+    DigestFormat, EventsCommand, ExportFormat, GraphFormat, GroupField, ImportFormat, IndexCommand,
+    ListCommand, MetaCommand, NotifyCommand, PROFILE_FLAG, ParsedConfigOverride, RECORD_FLAG,
+    ReportCommand, RoutineCommand, SecretCommand, SeedCommand, SnapshotCommand, WebhooksCommand,
     parse_config_override,
 };
+use todo_cli::compat;
+use todo_cli::completion::ReplHelper;
+use todo_cli::demo;
+use todo_cli::layout;
+use todo_cli::listen;
+use todo_cli::pager;
+use todo_cli::schema::{self, SchemaKind};
+use todo_cli::session::{Session, SessionVar};
+use todo_cli::suggest;
+use todo_cli::transcript::{self, Recorder, Step};
+use todo_cli::warnings::{self, warn};
+use todo_cli::watch;
 use todo_core::config::{
-    Config, ConfigOverrides, Palette, canonical_theme_name, merge_overrides, palette_for_theme,
+    Config, ConfigOverrides, NotificationBackend, NotificationConfig, Palette, Setup, THEMES,
+    canonical_theme_name, merge_overrides, palette_for_theme,
 };
+use todo_core::diff::StoreDiff;
 use todo_core::error::AppError;
-use todo_core::model::{Task, TaskStatus};
+use todo_core::export::verify::RoundTrip;
+use todo_core::filter::{Filter, StatusFilter};
+use todo_core::import::ImportedTask;
+use todo_core::import::csv::{ColumnMap, CsvOptions, DateOrder};
+use todo_core::model::{Cancellation, Marker, MarkerColor, Task, TaskStatus, TicketLink};
+use todo_core::notify::run_log::{self, RunRecord};
+use todo_core::period::Period;
+use todo_core::secrets::SecretStore;
+use todo_core::stats::{AccuracyReport, GroupBy, Insights, TagAccuracy, TaskGroup};
+use todo_core::storage::activity::{ActivityChange, ActivityEntry};
+use todo_core::storage::backup::Backup;
+use todo_core::storage::json_store;
+use todo_core::storage::status::StatusSummary;
+#[cfg(feature = "sync")]
+use todo_core::sync::{ChangeKind, Conflict, Prefer, Resolution};
+use todo_core::task_api::{
+    ContextList, Maintenance, PlannedChange, StoreInfo, TaskDetail, TriageDay,
+};
+#[cfg(feature = "sync")]
+use todo_core::task_api::{RemoteStatus, SyncQueue, SyncReport};
 
 fn status_label(status: TaskStatus) -> &'static str {
     match status {
         TaskStatus::Pending => "pending",
+        TaskStatus::InProgress => "in_progress",
+        TaskStatus::Waiting => "waiting",
         TaskStatus::Completed => "completed",
+        TaskStatus::Cancelled => "cancelled",
     }
 }
 
 use time::format_description::FormatItem;
 use time::macros::format_description;
 
-const DISPLAY_FORMAT: &[FormatItem<'static>] = format_description!("[hour]:[minute]:[second] - [day]/[month]/[year]");
+const DISPLAY_FORMAT: &[FormatItem<'static>] =
+    format_description!("[hour]:[minute]:[second] - [day]/[month]/[year]");
+/// How `GroupBy::Day` writes its group keys.
+const AGENDA_DATE_FORMAT: &[FormatItem<'static>] = format_description!("[year]-[month]-[day]");
+const AGENDA_HEADING_FORMAT: &[FormatItem<'static>] = format_description!("[day]/[month]/[year]");
+/// Set to keep the queued webhook deliveries from being posted after each command.
+const DISABLE_WEBHOOKS_ENV: &str = "TODOAPP_DISABLE_WEBHOOKS";
+const INSIGHTS_TOP_COMMANDS: usize = 5;
+const INSIGHTS_TOP_HOURS: usize = 3;
+const INSIGHTS_BAR_WIDTH: usize = 20;
+
+/// The `--strict` failure once anything was worked around, such as a config file that could not
+/// be read, which leaves the command running on the defaults.
+fn strict_failure() -> Option<AppError> {
+    let count = warnings::count();
+    let noun = if count == 1 { "warning" } else { "warnings" };
+    (count > 0).then(|| AppError::invalid_data(format!("--strict: failing after {count} {noun}")))
+}
 
+use tabled::settings::location::ByColumnName;
+use tabled::settings::{Remove, Style};
 use tabled::{Table, Tabled};
-use tabled::settings::Style;
 
-#[derive(Tabled)]
+/// Task tables never cut titles shorter than this, even when the other columns fill the width.
+const MIN_TITLE_WIDTH: usize = 12;
+
+/// Task table columns dropped, first to last, while the rest leave titles too little room.
+const DROPPABLE_TASK_COLUMNS: [&str; 8] = [
+    "History",
+    "Completed",
+    "Urgent",
+    "Created",
+    "Start",
+    "Due",
+    "Focus",
+    "Status",
+];
+
+/// The same for the `import --dry-run` preview.
+const DROPPABLE_IMPORT_COLUMNS: [&str; 5] = ["Urgent", "Project", "Tags", "Start", "Due"];
+
+/// Fit a table to `width` columns. `probe` is the table with every title empty, where the
+/// Title column is as wide as its header: columns named in `droppable` leave it in turn until
+/// the rest leave titles at least [`MIN_TITLE_WIDTH`]. Returns the dropped columns and how wide
+/// titles may then be.
+fn fit_to_width(
+    mut probe: Table,
+    droppable: &[&'static str],
+    width: usize,
+) -> (Vec<&'static str>, usize) {
+    probe.with(Style::modern());
+    let mut dropped = Vec::new();
+    loop {
+        let others = probe
+            .to_string()
+            .lines()
+            .next()
+            .map(layout::visible_width)
+            .unwrap_or(0)
+            .saturating_sub("Title".len());
+        match droppable.get(dropped.len()) {
+            Some(&column) if others + MIN_TITLE_WIDTH > width => {
+                drop_columns(&mut probe, &[column]);
+                dropped.push(column);
+            }
+            _ => return (dropped, width.saturating_sub(others).max("Title".len())),
+        }
+    }
+}
+
+fn drop_columns(table: &mut Table, columns: &[&str]) {
+    for column in columns {
+        table.with(Remove::column(ByColumnName::new(*column)));
+    }
+}
+
+#[derive(Tabled, Clone)]
 struct TaskRow {
     #[tabled(rename = "Focus")]
     prefix: String,
@@ -38,16 +156,37 @@ struct TaskRow {
     status: String,
     #[tabled(rename = "Created")]
     created_at: String,
-    #[tabled(rename = "Scheduled")]
-    scheduled_at: String,
+    #[tabled(rename = "Start")]
+    start_at: String,
+    #[tabled(rename = "Due")]
+    due_at: String,
+}
+
+/// `list --wide` adds urgency and completion details to the regular columns.
+#[derive(Tabled)]
+struct WideTaskRow {
+    #[tabled(inline)]
+    base: TaskRow,
+    #[tabled(rename = "Urgent")]
+    urgent: String,
+    #[tabled(rename = "Completed")]
+    completed_at: String,
+    #[tabled(rename = "History")]
+    history: String,
 }
 
 fn print_tasks_plain(
     tasks: &[Task],
     focused_task_id: Option<&str>,
     palette: &Palette,
+    wide: bool,
+    config: &Config,
 ) -> Result<(), AppError> {
+    if palette.accessible {
+        return print_tasks_accessible(tasks, focused_task_id, wide, config);
+    }
     let mut rows = Vec::new();
+    let mut titles = Vec::new();
 
     for task in tasks {
         let prefix = if focused_task_id == Some(task.id.as_str()) {
@@ -55,430 +194,3995 @@ fn print_tasks_plain(
         } else {
             String::new()
         };
-        
-        let scheduled_at = match task.scheduled_at.as_deref() {
+
+        let table_time = |field: &str, value: Option<&str>| match value {
             Some(ts) => {
-                let parsed = time::OffsetDateTime::parse(ts, &time::format_description::well_known::Rfc3339)
-                    .map_err(|_| AppError::invalid_data("invalid scheduled_at format"))?;
-                parsed.format(DISPLAY_FORMAT).unwrap_or_else(|_| ts.to_string())
+                let parsed =
+                    time::OffsetDateTime::parse(ts, &time::format_description::well_known::Rfc3339)
+                        .map_err(|_| AppError::invalid_data(format!("invalid {field} format")))?;
+                Ok(palette
+                    .mutedize(
+                        &parsed
+                            .format(DISPLAY_FORMAT)
+                            .unwrap_or_else(|_| ts.to_string()),
+                    )
+                    .to_string())
             }
-            None => "-".to_string(),
+            None => Ok(palette.mutedize("-").to_string()),
         };
-        let scheduled_display = palette.mutedize(&scheduled_at).to_string();
-        
-        let overdue = todo_core::task_api::task_overdue(task)?;
+        let start_display = table_time("start_at", task.start_at.as_deref())?;
+        let due_display = table_time("due_at", task.due_at.as_deref())?;
+
+        let overdue = todo_core::task_api::task_overdue(task, config.overdue_grace)?;
         let status = if overdue {
             format!("{} (overdue)", status_label(task.status))
+        } else if let Some(left) = todo_core::task_api::task_due_in(task, config.due_soon_within)? {
+            format!("{} (due in {})", status_label(task.status), time_left(left))
         } else {
             status_label(task.status).to_string()
         };
         let status_text = palette.accentize(&status).to_string();
-        
-        let created_at_display = time::OffsetDateTime::parse(&task.created_at, &time::format_description::well_known::Rfc3339)
-            .map(|dt| dt.format(DISPLAY_FORMAT).unwrap_or_else(|_| task.created_at.clone()))
-            .unwrap_or_else(|_| task.created_at.clone());
 
-        let title = palette.accentize(&task.title).to_string();
+        let created_at_display = display_time(&task.created_at);
+
+        titles.push((
+            task.marker.as_ref().map(|marker| palette.marker(marker)),
+            task.title.as_str(),
+        ));
 
         rows.push(TaskRow {
             prefix,
             id: task.id.clone(),
-            title,
+            title: String::new(),
             status: status_text,
             created_at: created_at_display,
-            scheduled_at: scheduled_display,
+            start_at: start_display,
+            due_at: due_display,
         });
     }
 
-    if !rows.is_empty() {
-        let mut table = Table::new(rows);
-        table.with(Style::modern());
-        println!("{}", table);
-    } else {
+    if rows.is_empty() {
         println!("No tasks found.");
+        return Ok(());
     }
 
-    Ok(())
-}
-
-fn resolve_aliases(mut args: Vec<String>, config: &Config) -> Result<Vec<String>, AppError> {
-    loop {
-        if args.is_empty() {
-            break;
+    let build = |rows: Vec<TaskRow>| {
+        if wide {
+            Table::new(rows.into_iter().zip(tasks).map(|(base, task)| {
+                WideTaskRow {
+                    base,
+                    urgent: if task.urgent {
+                        palette.accentize("yes")
+                    } else {
+                        String::new()
+                    },
+                    completed_at: palette.mutedize(
+                        &task
+                            .completed_at
+                            .as_deref()
+                            .map(display_time)
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                    history: match task.completion_history.last() {
+                        Some(last) if task.completion_history.len() > 1 => {
+                            format!(
+                                "{} (+{} earlier)",
+                                last.message,
+                                task.completion_history.len() - 1
+                            )
+                        }
+                        Some(last) => last.message.clone(),
+                        None => "-".to_string(),
+                    },
+                }
+            }))
+        } else {
+            Table::new(rows)
         }
+    };
 
-        let alias_index = args
-            .iter()
-            .position(|arg| !arg.starts_with('-'))
-            .unwrap_or(usize::MAX);
-        if alias_index == usize::MAX {
-            break;
+    let (dropped, title_width) = match layout::width() {
+        Some(width) => {
+            let (dropped, title_width) =
+                fit_to_width(build(rows.clone()), &DROPPABLE_TASK_COLUMNS, width);
+            (dropped, Some(title_width))
         }
-
-        let alias_name = args[alias_index].clone();
-        let replacement = match config.aliases.get(&alias_name) {
-            Some(value) => value,
-            None => break,
+        None => (Vec::new(), None),
+    };
+    for (row, (marker, title)) in rows.iter_mut().zip(titles) {
+        let marker_width = marker
+            .as_deref()
+            .map(|marker| layout::visible_width(marker) + 1)
+            .unwrap_or(0);
+        let title = match title_width {
+            Some(width) => {
+                todo_core::text::truncate_to_width(title, width.saturating_sub(marker_width).max(1))
+            }
+            None => title.to_string(),
+        };
+        row.title = match marker {
+            Some(marker) => format!("{} {}", marker, palette.accentize(&title)),
+            None => palette.accentize(&title),
         };
-
-        let alias_tokens = parse_command_line(replacement).map_err(|err| {
-            AppError::invalid_data(format!(
-                "alias '{alias}' invalid: {error}",
-                alias = alias_name,
-                error = err
-            ))
-        })?;
-
-        let mut resolved = Vec::new();
-        resolved.extend(args[..alias_index].iter().cloned());
-        resolved.extend(alias_tokens);
-        resolved.extend(args[(alias_index + 1)..].iter().cloned());
-        args = resolved;
     }
 
-    Ok(args)
+    let mut table = build(rows);
+    table.with(Style::modern());
+    drop_columns(&mut table, &dropped);
+    println!("{}", table);
+
+    Ok(())
 }
 
-// Parse a command-line string using the same quoting rules as interactive mode.
-fn parse_command_line(line: &str) -> Result<Vec<String>, AppError> {
-    let mut args = Vec::new();
-    let mut current = String::new();
-    let mut in_quotes = false;
-    let mut escape = false;
+/// The task list without a table: a heading line per task, then one fact per line, with markers
+/// such as FOCUSED and OVERDUE spelled out.
+fn print_tasks_accessible(
+    tasks: &[Task],
+    focused_task_id: Option<&str>,
+    wide: bool,
+    config: &Config,
+) -> Result<(), AppError> {
+    if tasks.is_empty() {
+        println!("No tasks found.");
+        return Ok(());
+    }
 
-    for ch in line.chars() {
-        if escape {
-            if ch != '"' && ch != '\\' {
-                current.push('\\');
+    for (index, task) in tasks.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("Task {}: {}", task.id, task.title);
+        if focused_task_id == Some(task.id.as_str()) {
+            println!("FOCUSED");
+        }
+        println!("Status: {}", status_label(task.status));
+        if let Some(marker) = task.marker.as_ref() {
+            println!("Marker: {}", marker_words(marker));
+        }
+        if todo_core::task_api::task_overdue(task, config.overdue_grace)? {
+            println!("OVERDUE");
+        } else if let Some(left) = todo_core::task_api::task_due_in(task, config.due_soon_within)? {
+            println!("DUE IN {}", time_left(left));
+        }
+        if wide && task.urgent {
+            println!("URGENT");
+        }
+        println!("Created: {}", display_time(&task.created_at));
+        if let Some(start_at) = task.start_at.as_deref() {
+            println!("Start: {}", display_time(start_at));
+        }
+        println!(
+            "Due: {}",
+            task.due_at
+                .as_deref()
+                .map(display_time)
+                .unwrap_or_else(|| "none".to_string())
+        );
+        if wide {
+            if let Some(completed_at) = task.completed_at.as_deref() {
+                println!("Completed: {}", display_time(completed_at));
+            }
+            for entry in &task.completion_history {
+                println!("History: {}", entry.message);
             }
-            current.push(ch);
-            escape = false;
-            continue;
         }
+    }
 
-        if in_quotes && ch == '\\' {
-            escape = true;
-            continue;
-        }
+    Ok(())
+}
 
-        if ch == '"' {
-            in_quotes = !in_quotes;
-            continue;
-        }
+#[derive(Tabled, Clone)]
+struct ImportRow {
+    #[tabled(rename = "Row")]
+    row: usize,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Start")]
+    start_at: String,
+    #[tabled(rename = "Due")]
+    due_at: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "Project")]
+    project: String,
+    #[tabled(rename = "Urgent")]
+    urgent: String,
+}
 
-        if ch.is_whitespace() && !in_quotes {
-            if !current.is_empty() {
-                args.push(current.clone());
-                current.clear();
+/// The tasks `import --dry-run` would add, each next to the file row it came from.
+fn print_import_preview(imported: &[ImportedTask], tasks: &[Task], palette: &Palette) {
+    if palette.accessible {
+        for (index, (source, task)) in imported.iter().zip(tasks).enumerate() {
+            if index > 0 {
+                println!();
+            }
+            println!("Row {}: {}", source.row, task.title);
+            if let Some(start_at) = task.start_at.as_deref() {
+                println!("Start: {}", display_time(start_at));
+            }
+            if let Some(due_at) = task.due_at.as_deref() {
+                println!("Due: {}", display_time(due_at));
+            }
+            if !task.tags.is_empty() {
+                println!("Tags: {}", task.tags.join(", "));
+            }
+            if let Some(project) = task.project.as_deref() {
+                println!("Project: {project}");
+            }
+            if task.urgent {
+                println!("URGENT");
             }
-            continue;
         }
-
-        current.push(ch);
+        return;
     }
 
-    if in_quotes {
-        return Err(AppError::invalid_input("unterminated quote in command"));
+    let optional =
+        |value: Option<String>| palette.mutedize(&value.unwrap_or_else(|| "-".to_string()));
+    let mut rows: Vec<ImportRow> = imported
+        .iter()
+        .zip(tasks)
+        .map(|(source, task)| ImportRow {
+            row: source.row,
+            title: String::new(),
+            start_at: optional(task.start_at.as_deref().map(display_time)),
+            due_at: optional(task.due_at.as_deref().map(display_time)),
+            tags: optional((!task.tags.is_empty()).then(|| task.tags.join(", "))),
+            project: optional(task.project.clone()),
+            urgent: if task.urgent {
+                palette.accentize("yes")
+            } else {
+                String::new()
+            },
+        })
+        .collect();
+    let (dropped, title_width) = match layout::width() {
+        Some(width) => {
+            let (dropped, title_width) =
+                fit_to_width(Table::new(rows.clone()), &DROPPABLE_IMPORT_COLUMNS, width);
+            (dropped, Some(title_width))
+        }
+        None => (Vec::new(), None),
+    };
+    for (row, task) in rows.iter_mut().zip(tasks) {
+        let title = match title_width {
+            Some(width) => todo_core::text::truncate_to_width(&task.title, width),
+            None => task.title.clone(),
+        };
+        row.title = palette.accentize(&title);
     }
+    let mut table = Table::new(rows);
+    table.with(Style::modern());
+    drop_columns(&mut table, &dropped);
+    println!("{table}");
+}
 
-    if !current.is_empty() {
-        args.push(current);
+fn print_contexts_plain(contexts: &ContextList, palette: &Palette) {
+    if contexts.contexts.is_empty() {
+        println!("No contexts defined.");
+        return;
     }
 
-    Ok(args)
-}
-
-fn validate_alias_definitions(aliases: &HashMap<String, String>) -> Result<(), AppError> {
-    for (alias, replacement) in aliases {
-        let tokens = parse_command_line(replacement)
-            .map_err(|err| AppError::invalid_input(format!("alias '{alias}' invalid: {err}")))?;
-        if tokens.is_empty() {
-            return Err(AppError::invalid_input(format!(
-                "alias '{alias}' has empty expansion"
-            )));
+    let width = contexts.contexts.keys().map(String::len).max().unwrap_or(0);
+    for (name, filter) in &contexts.contexts {
+        if palette.accessible {
+            let active = if contexts.active.as_deref() == Some(name.as_str()) {
+                " (active)"
+            } else {
+                ""
+            };
+            println!("Context {name}{active}: {filter}");
+            continue;
         }
+        let marker = if contexts.active.as_deref() == Some(name.as_str()) {
+            palette.accentize("*")
+        } else {
+            " ".to_string()
+        };
+        println!("{marker} {name:<width$}  {}", palette.mutedize(filter));
     }
+}
 
-    for alias in aliases.keys() {
-        let mut visited = HashSet::new();
-        let mut current = alias.as_str();
-
-        loop {
-            if !visited.insert(current) {
-                return Err(AppError::invalid_input(format!(
-                    "alias '{}' introduces a cycle",
-                    alias
-                )));
-            }
+fn print_contexts_json(contexts: &ContextList, envelope: bool) {
+    let entries: Vec<serde_json::Value> = contexts
+        .contexts
+        .iter()
+        .map(|(name, filter)| serde_json::json!({ "name": name, "filter": filter }))
+        .collect();
+    let json = serde_json::json!({
+        "active": contexts.active,
+        "contexts": entries,
+    });
+    emit_json(SchemaKind::Contexts, json, envelope);
+}
 
-            let expansion = match aliases.get(current) {
-                Some(value) => value,
-                None => break,
-            };
+fn print_snapshots_plain(snapshots: &[Backup], palette: &Palette) {
+    if snapshots.is_empty() {
+        println!("No snapshots saved.");
+        return;
+    }
 
-            let first_token = expansion.split_whitespace().next().unwrap_or("");
-            if first_token.is_empty() {
-                break;
-            }
+    let width = snapshots
+        .iter()
+        .map(|backup| backup.name.len())
+        .max()
+        .unwrap_or(0);
+    for backup in snapshots {
+        let noun = if backup.task_count == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        println!(
+            "{:<width$}  {}  {} {noun}",
+            backup.name,
+            palette.mutedize(&display_time(&backup.created_at)),
+            backup.task_count
+        );
+    }
+}
 
-            if first_token == current {
-                return Err(AppError::invalid_input(format!(
-                    "alias '{}' expands to itself",
-                    alias
-                )));
-            }
+fn print_snapshots_json(snapshots: &[Backup], envelope: bool) {
+    let entries: Vec<serde_json::Value> = snapshots
+        .iter()
+        .map(|backup| {
+            serde_json::json!({
+                "name": backup.name,
+                "path": backup.path.display().to_string(),
+                "created_at": backup.created_at,
+                "task_count": backup.task_count,
+            })
+        })
+        .collect();
+    emit_json(
+        SchemaKind::Snapshots,
+        serde_json::json!({ "snapshots": entries }),
+        envelope,
+    );
+}
 
-            if !aliases.contains_key(first_token) {
-                break;
-            }
+fn print_task_groups_plain(
+    label: impl Fn(Option<&str>) -> String,
+    groups: &[TaskGroup],
+    total: usize,
+    focused_task_id: Option<&str>,
+    palette: &Palette,
+    wide: bool,
+    config: &Config,
+) -> Result<(), AppError> {
+    if groups.is_empty() {
+        println!("No tasks found.");
+        return Ok(());
+    }
 
-            current = first_token;
+    for group in groups {
+        let name = label(group.key.as_deref());
+        if palette.accessible {
+            let noun = if group.tasks.len() == 1 {
+                "task"
+            } else {
+                "tasks"
+            };
+            println!("Group {name}: {} {noun}", group.tasks.len());
+        } else {
+            println!(
+                "{} {}",
+                palette.accentize(&name),
+                palette.mutedize(&format!("({})", group.tasks.len()))
+            );
         }
+        print_tasks_plain(&group.tasks, focused_task_id, palette, wide, config)?;
+        println!();
     }
+    let noun = if total == 1 { "task" } else { "tasks" };
+    println!("Total: {total} {noun}");
 
     Ok(())
 }
 
-fn print_tasks_json(tasks: &[Task]) -> Result<(), AppError> {
-    let mut payload = Vec::with_capacity(tasks.len());
-    for task in tasks {
-        let overdue = todo_core::task_api::task_overdue(task)?;
-        let status = if overdue {
-            format!("{} (overdue)", status_label(task.status))
+/// `upcoming`: one section per day, headed by its weekday and date, with the day's journal
+/// note above its tasks. Days with a note but nothing planned get a section too. Each task is
+/// in exactly one day's group.
+fn print_agenda_plain(
+    groups: &[TaskGroup],
+    notes: &BTreeMap<String, String>,
+    days: u32,
+    today: time::Date,
+    palette: &Palette,
+    wide: bool,
+    config: &Config,
+) -> Result<(), AppError> {
+    if groups.is_empty() && notes.is_empty() {
+        let span = if days == 1 {
+            "today".to_string()
         } else {
-            status_label(task.status).to_string()
+            format!("in the next {days} days")
         };
-        payload.push(serde_json::json!({
-            "id": task.id,
-            "title": task.title,
-            "status": status,
-            "created_at": task.created_at,
-            "scheduled_at": task.scheduled_at,
-        }));
+        println!("Nothing planned {span}.");
+        return Ok(());
+    }
+
+    let keys: BTreeSet<&str> = groups
+        .iter()
+        .filter_map(|group| group.key.as_deref())
+        .chain(notes.keys().map(String::as_str))
+        .collect();
+    for key in keys {
+        let date = time::Date::parse(key, AGENDA_DATE_FORMAT)
+            .map_err(|_| AppError::invalid_data(format!("invalid agenda day '{key}'")))?;
+        let relative = match (date - today).whole_days() {
+            0 => " (today)",
+            1 => " (tomorrow)",
+            _ => "",
+        };
+        let heading = format!("{}{relative}", day_heading(date)?);
+        let tasks = groups
+            .iter()
+            .find(|group| group.key.as_deref() == Some(key))
+            .map(|group| group.tasks.as_slice())
+            .unwrap_or_default();
+        if palette.accessible {
+            let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+            println!("{heading}: {} {noun}", tasks.len());
+        } else if tasks.is_empty() {
+            println!("{}", palette.accentize(&heading));
+        } else {
+            println!(
+                "{} {}",
+                palette.accentize(&heading),
+                palette.mutedize(&format!("({})", tasks.len()))
+            );
+        }
+        if let Some(note) = notes.get(key) {
+            print_day_note_plain("Journal", note, palette);
+        }
+        if !tasks.is_empty() {
+            print_tasks_plain(tasks, None, palette, wide, config)?;
+        }
+        println!();
     }
-    println!("{}", serde_json::Value::Array(payload));
+    let total: usize = groups.iter().map(|group| group.tasks.len()).sum();
+    let noun = if total == 1 { "task" } else { "tasks" };
+    println!("Total: {total} {noun}");
+
     Ok(())
 }
 
-fn print_task_json(task: &Task) {
-    let json = serde_json::json!({
-        "id": task.id,
-        "title": task.title,
-        "status": task.status,
-        "created_at": task.created_at,
-        "scheduled_at": task.scheduled_at,
-    });
-    println!("{}", json);
+/// A day as the agenda heads it, e.g. "Monday 22/12/2025".
+fn day_heading(date: time::Date) -> Result<String, AppError> {
+    let formatted = date
+        .format(AGENDA_HEADING_FORMAT)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    Ok(format!("{} {formatted}", date.weekday()))
 }
 
-fn print_task_json_with_overdue(task: &Task) -> Result<(), AppError> {
-    let overdue = todo_core::task_api::task_overdue(task)?;
-    let status = if overdue {
-        format!("{} (overdue)", status_label(task.status))
+/// A `journal` note under a `label` line, each of its lines indented and wrapped to the width.
+fn print_day_note_plain(label: &str, note: &str, palette: &Palette) {
+    if palette.accessible {
+        println!("{label}:");
     } else {
-        status_label(task.status).to_string()
-    };
-    let json = serde_json::json!({
-        "id": task.id,
-        "title": task.title,
-        "status": status,
-        "created_at": task.created_at,
-        "scheduled_at": task.scheduled_at,
-    });
-    println!("{}", json);
-    Ok(())
+        println!("{}", palette.mutedize(label));
+    }
+    for line in note.lines() {
+        for wrapped in layout::hanging(line, 2) {
+            println!("  {wrapped}");
+        }
+    }
 }
 
-fn print_completed_task_json(task: &Task) {
-    let json = serde_json::json!({
-        "id": task.id,
-        "title": task.title,
-        "status": task.status,
-        "created_at": task.created_at,
-        "scheduled_at": task.scheduled_at,
-        "completed_at": task.completed_at,
-        "completion_history": task.completion_history,
-    });
-    println!("{}", json);
+fn print_day_note_json(date: time::Date, note: Option<&str>, envelope: bool) {
+    let json = serde_json::json!({ "date": date.to_string(), "note": note });
+    emit_json(SchemaKind::DayNote, json, envelope);
 }
 
-fn normalize_parse_error(err: clap::Error) -> AppError {
-    let rendered = err.to_string();
-    let first_line = rendered.lines().next().unwrap_or("invalid command").trim();
-    let message = first_line
-        .strip_prefix("error: ")
-        .unwrap_or(first_line)
-        .to_string();
-    AppError::invalid_input(message)
-}
+/// Formats `export` writes that nothing reads back, so `export --verify` can't check them.
+const EXPORT_ONLY_FORMATS: [&str; 2] = ["html", "dot"];
 
-fn print_help() {
-    let mut cmd = Cli::command();
-    let help = cmd.render_help();
-    println!("{help}");
+fn print_export_verify_plain(trips: &[RoundTrip], palette: &Palette) {
+    for trip in trips {
+        let noun = if trip.tasks == 1 { "task" } else { "tasks" };
+        if trip.is_lossless() {
+            println!(
+                "{}: {} {noun} came back intact",
+                palette.accentize(trip.format),
+                trip.tasks
+            );
+        } else {
+            println!(
+                "{}: LOST FIELDS in a round trip of {} {noun}",
+                palette.accentize(trip.format),
+                trip.tasks
+            );
+            for lost in &trip.lost {
+                let count = lost.task_ids.len();
+                let noun = if count == 1 { "task" } else { "tasks" };
+                println!(
+                    "  {} changed in {count} {noun}: {}",
+                    lost.field,
+                    lost.task_ids.join(", ")
+                );
+            }
+        }
+        if !trip.not_carried.is_empty() {
+            println!(
+                "  {}",
+                palette.mutedize(&format!("Not carried: {}", trip.not_carried.join(", ")))
+            );
+        }
+    }
+    println!(
+        "{}",
+        palette.mutedize(&format!(
+            "Export only, not checked: {}",
+            EXPORT_ONLY_FORMATS.join(", ")
+        ))
+    );
+}
+
+fn print_export_verify_json(trips: &[RoundTrip], envelope: bool) {
+    let formats: Vec<serde_json::Value> = trips
+        .iter()
+        .map(|trip| {
+            serde_json::json!({
+                "format": trip.format,
+                "tasks": trip.tasks,
+                "lossless": trip.is_lossless(),
+                "lost": trip.lost.iter().map(|lost| serde_json::json!({
+                    "field": lost.field,
+                    "task_ids": lost.task_ids,
+                })).collect::<Vec<_>>(),
+                "not_carried": trip.not_carried,
+            })
+        })
+        .collect();
+    let json = serde_json::json!({
+        "lossless": trips.iter().all(RoundTrip::is_lossless),
+        "formats": formats,
+        "export_only": EXPORT_ONLY_FORMATS,
+    });
+    emit_json(SchemaKind::ExportVerify, json, envelope);
+}
+
+/// `journal`'s first argument is a day when it reads as one; otherwise it is the text of
+/// today's note.
+fn looks_like_day(value: &str) -> bool {
+    let value = value.trim();
+    matches!(
+        value.to_lowercase().as_str(),
+        "today" | "yesterday" | "tomorrow"
+    ) || (value.contains('-') && value.chars().all(|c| c.is_ascii_digit() || c == '-'))
+}
+
+fn display_time(value: &str) -> String {
+    time::OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+        .map(|dt| {
+            dt.format(DISPLAY_FORMAT)
+                .unwrap_or_else(|_| value.to_string())
+        })
+        .unwrap_or_else(|_| value.to_string())
+}
+
+/// An indented `prefix` (`prefix_width` columns once styled) and `text` after it, with any lines
+/// `text` wraps to lined up under its first.
+fn print_hanging(prefix: &str, prefix_width: usize, text: &str) {
+    let indent = 2 + prefix_width + 2;
+    for (index, line) in layout::hanging(text, indent).iter().enumerate() {
+        if index == 0 {
+            println!("  {prefix}  {line}");
+        } else {
+            println!("{:indent$}{line}", "");
+        }
+    }
+}
+
+fn print_task_detail(detail: &TaskDetail, palette: &Palette) -> Result<(), AppError> {
+    let task = &detail.task;
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let optional_time =
+        |value: Option<&str>| value.map(display_time).unwrap_or_else(|| "-".to_string());
+    let label_text = match task.waiting_until.as_deref() {
+        Some(until) => format!(
+            "{} until {}",
+            status_label(task.status),
+            display_time(until)
+        ),
+        None => status_label(task.status).to_string(),
+    };
+    let status = if detail.overdue && palette.accessible {
+        format!("{label_text}, OVERDUE")
+    } else if detail.overdue {
+        format!("{label_text} (overdue)")
+    } else {
+        label_text
+    };
+    let tags = if task.tags.is_empty() {
+        "-".to_string()
+    } else {
+        task.tags.join(", ")
+    };
+
+    let rows = [
+        ("Title", task.title.clone()),
+        ("ID", task.id.clone()),
+        ("Status", status),
+        (
+            "Marker",
+            match task.marker.as_ref() {
+                Some(marker) if palette.accessible => marker_words(marker),
+                Some(marker) => palette.marker(marker),
+                None => "-".to_string(),
+            },
+        ),
+        ("Urgent", yes_no(task.urgent).to_string()),
+        ("Focused", yes_no(detail.focused).to_string()),
+        ("Created", display_time(&task.created_at)),
+        ("Start", optional_time(task.start_at.as_deref())),
+        ("Due", optional_time(task.due_at.as_deref())),
+        ("Completed", optional_time(task.completed_at.as_deref())),
+        (
+            "Cancelled",
+            task.cancellation
+                .as_ref()
+                .map(cancellation_label)
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        ("Tags", tags),
+        (
+            "Project",
+            task.project.clone().unwrap_or_else(|| "-".to_string()),
+        ),
+        (
+            "Ticket",
+            task.ticket
+                .as_ref()
+                .map(ticket_label)
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    ];
+    // Wide enough for the longest label ("Completed"/"Cancelled").
+    let label = |text: &str| palette.mutedize(&format!("{text:<9}"));
+    // Long values wrap under where they start, after the label and its two spaces.
+    let indent = 11;
+    for (name, value) in rows {
+        let lines = layout::hanging(&value, indent);
+        for (index, line) in lines.iter().enumerate() {
+            let line = if matches!(name, "Title" | "Status") {
+                palette.accentize(line)
+            } else {
+                line.clone()
+            };
+            if index == 0 {
+                println!("{}  {}", label(name), line);
+            } else {
+                println!("{:indent$}{}", "", line);
+            }
+        }
+    }
+
+    match task.notes.as_deref() {
+        Some(notes) => {
+            println!("{}", palette.mutedize("Notes"));
+            for line in layout::hanging(notes, 2) {
+                println!("  {}", line);
+            }
+        }
+        None => println!("{}  -", label("Notes")),
+    }
+
+    if task.metadata.is_empty() {
+        println!("{}  -", label("Metadata"));
+    } else {
+        println!("{}", palette.mutedize("Metadata"));
+        print_metadata(&task.metadata, palette);
+    }
+
+    if task.completion_history.is_empty() {
+        println!("{}  -", label("History"));
+    } else {
+        println!("{}", palette.mutedize("History"));
+        for entry in &task.completion_history {
+            let when = display_time(&entry.completed_at);
+            print_hanging(
+                &palette.mutedize(&when),
+                todo_core::text::display_width(&when),
+                &entry.message,
+            );
+        }
+    }
+
+    if task.comments.is_empty() {
+        println!("{}  -", label("Comments"));
+    } else {
+        println!("{}", palette.mutedize("Comments"));
+        for comment in &task.comments {
+            let byline = format!("{} {}", display_time(&comment.created_at), comment.author);
+            print_hanging(
+                &palette.mutedize(&byline),
+                todo_core::text::display_width(&byline),
+                &comment.text,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The `show --verbose` extras: who last changed the task and its activity log entries.
+fn print_task_provenance(task: &Task, activity: &[ActivityEntry], palette: &Palette) {
+    let label = |text: &str| palette.mutedize(&format!("{text:<9}"));
+    let modified = match (task.modified_at.as_deref(), task.modified_by.as_deref()) {
+        (Some(at), Some(by)) => format!("{} by {by}", display_time(at)),
+        (Some(at), None) => display_time(at),
+        (None, Some(by)) => format!("by {by}"),
+        (None, None) => "-".to_string(),
+    };
+    println!("{}  {}", label("Modified"), modified);
+
+    if activity.is_empty() {
+        println!("{}  -", label("Activity"));
+    } else {
+        println!("{}", palette.mutedize("Activity"));
+        for entry in activity {
+            println!(
+                "  {}  {}",
+                palette.mutedize(&format!("{} {}", display_time(&entry.at), entry.by)),
+                change_label(entry.change)
+            );
+        }
+    }
+}
+
+/// `--author`, or the login name from the environment.
+fn comment_author(author: Option<String>) -> String {
+    author.unwrap_or_else(|| {
+        ["USER", "USERNAME"]
+            .iter()
+            .find_map(|var| {
+                std::env::var(var)
+                    .ok()
+                    .filter(|name| !name.trim().is_empty())
+            })
+            .unwrap_or_else(|| "unknown".to_string())
+    })
+}
+
+fn ticket_label(link: &TicketLink) -> String {
+    let mut label = link.key.clone();
+    if let Some(status) = link.status.as_deref() {
+        label.push_str(&format!(" ({status})"));
+    }
+    if let Some(url) = link.url.as_deref() {
+        label.push_str(&format!("  {url}"));
+    }
+    label
+}
+
+/// The marker spelled out, such as "red, 🔥", where a colored dot would not be read aloud.
+fn marker_words(marker: &Marker) -> String {
+    marker
+        .color
+        .map(MarkerColor::as_str)
+        .into_iter()
+        .chain(marker.symbol.as_deref())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn cancellation_label(cancellation: &Cancellation) -> String {
+    let at = display_time(&cancellation.cancelled_at);
+    match cancellation.reason.as_deref() {
+        Some(reason) => format!("{at}  {reason}"),
+        None => at,
+    }
+}
+
+fn print_metadata(metadata: &BTreeMap<String, String>, palette: &Palette) {
+    let width = metadata.keys().map(String::len).max().unwrap_or(0);
+    for (key, value) in metadata {
+        println!("  {}  {value}", palette.mutedize(&format!("{key:<width$}")));
+    }
+}
+
+/// Widest focused title, in terminal columns, shown in a status bar before it is cut off with
+/// an ellipsis.
+const WAYBAR_TITLE_WIDTH: usize = 32;
+
+/// Completions this week against `goals.completions_per_week`.
+#[derive(Debug, Clone, Copy)]
+struct WeeklyGoal {
+    target: Option<usize>,
+    completed: usize,
+}
+
+impl WeeklyGoal {
+    /// `null` without a goal.
+    fn json(self) -> serde_json::Value {
+        match self.target {
+            Some(target) => serde_json::json!({
+                "target": target,
+                "completed": self.completed,
+                "reached": self.completed >= target,
+            }),
+            None => serde_json::Value::Null,
+        }
+    }
+}
+
+fn weekly_goal_line(target: usize, completed: usize) -> String {
+    if completed >= target {
+        format!("Weekly goal reached: {completed} of {target} completions. Well done!")
+    } else {
+        format!(
+            "Weekly goal: {completed} of {target} completions, {} to go",
+            target - completed
+        )
+    }
+}
+
+fn status_lines(
+    summary: &StatusSummary,
+    overdue: bool,
+    week: WeeklyGoal,
+    accessible: bool,
+) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} pending ({} urgent), {} completed",
+        summary.pending, summary.urgent, summary.completed
+    )];
+    if let (Some(next_due), Some(title)) = (
+        summary.next_due.as_deref(),
+        summary.next_due_title.as_deref(),
+    ) {
+        let suffix = match (overdue, accessible) {
+            (true, true) => ", OVERDUE",
+            (true, false) => " (overdue)",
+            (false, _) => "",
+        };
+        lines.push(format!(
+            "Next due: {title} at {}{suffix}",
+            display_time(next_due)
+        ));
+    }
+    if let Some(title) = summary.focused_title.as_deref() {
+        lines.push(format!("Focus: {title}"));
+    }
+    if let Some(target) = week.target {
+        lines.push(weekly_goal_line(target, week.completed));
+    }
+    lines
+}
+
+fn print_status_plain(summary: &StatusSummary, overdue: bool, week: WeeklyGoal, palette: &Palette) {
+    for (index, line) in status_lines(summary, overdue, week, palette.accessible)
+        .iter()
+        .enumerate()
+    {
+        if index == 0 {
+            println!("{}", palette.accentize(line));
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+fn print_status_json(
+    summary: &StatusSummary,
+    overdue: bool,
+    week: WeeklyGoal,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let mut json =
+        serde_json::to_value(summary).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    json["overdue"] = serde_json::Value::Bool(overdue);
+    // The stored count belongs to the week the summary was written in.
+    json["completed_this_week"] = serde_json::json!(week.completed);
+    json["week"] =
+        serde_json::json!(Period::week_of(todo_core::task_api::local_today()?).to_string());
+    json["weekly_goal"] = week.json();
+    emit_json(SchemaKind::Status, json, envelope);
+    Ok(())
+}
+
+fn print_info_plain(info: &StoreInfo, palette: &Palette) {
+    println!("{}", palette.accentize("Store"));
+    println!(
+        "  {} ({})",
+        info.path.display(),
+        format_bytes(info.store_bytes)
+    );
+
+    println!();
+    println!(
+        "{}",
+        palette.accentize(&format!("Tasks ({})", info.total()))
+    );
+    let width = info
+        .by_status
+        .iter()
+        .map(|(status, _)| status_label(*status).len())
+        .max()
+        .unwrap_or(0);
+    for (status, count) in &info.by_status {
+        println!("  {:<width$}  {count}", status_label(*status));
+    }
+    match &info.oldest_open {
+        Some(task) => println!(
+            "  Oldest open: {} ({}), created {}",
+            task.title,
+            task.id,
+            display_time(&task.created_at)
+        ),
+        None => println!("  No open tasks."),
+    }
+
+    println!();
+    println!("{}", palette.accentize("Journal"));
+    if info.journal_entries == 0 {
+        println!("  Empty.");
+    } else {
+        let noun = if info.journal_entries == 1 {
+            "change"
+        } else {
+            "changes"
+        };
+        println!(
+            "  {} {noun} ({})",
+            info.journal_entries,
+            format_bytes(info.journal_bytes)
+        );
+        println!("  hint: run `todo flush` to fold them into the store file");
+    }
+
+    println!();
+    println!("{}", palette.accentize("Last snapshot"));
+    match &info.last_backup {
+        Some(backup) => println!("  {} ({})", backup.name, display_time(&backup.created_at)),
+        None => {
+            println!("  None yet.");
+            println!("  hint: run `todo snapshot save` to keep a copy of the store");
+        }
+    }
+
+    if info.total() >= todo_core::storage::journal::JOURNAL_MIN_TASKS {
+        println!();
+        println!(
+            "hint: set `retention.archive_completed_after_days` and run `todo maintain` to move old completed tasks out of the store"
+        );
+    }
+}
+
+fn print_info_json(info: &StoreInfo, envelope: bool) {
+    let mut tasks = serde_json::Map::new();
+    tasks.insert("total".to_string(), serde_json::json!(info.total()));
+    for (status, count) in &info.by_status {
+        tasks.insert(status_label(*status).to_string(), serde_json::json!(count));
+    }
+    let json = serde_json::json!({
+        "path": info.path.display().to_string(),
+        "store_bytes": info.store_bytes,
+        "tasks": tasks,
+        "oldest_open": info.oldest_open.as_ref().map(|task| serde_json::json!({
+            "id": task.id,
+            "title": task.title,
+            "created_at": task.created_at,
+        })),
+        "journal": {
+            "entries": info.journal_entries,
+            "bytes": info.journal_bytes,
+        },
+        "last_backup": info.last_backup.as_ref().map(|backup| serde_json::json!({
+            "name": backup.name,
+            "created_at": backup.created_at,
+        })),
+    });
+    emit_json(SchemaKind::Info, json, envelope);
+}
+
+/// `512 B`, `1.5 KiB`, `12.0 MiB`.
+fn format_bytes(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    if bytes < KIB {
+        format!("{bytes} B")
+    } else if bytes < MIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    }
+}
+
+/// One compact line, as Waybar's `return-type: json` expects. Both bars render `text` and
+/// `tooltip` as Pango markup, so titles are escaped.
+fn print_status_waybar(
+    summary: &StatusSummary,
+    overdue: bool,
+    week: WeeklyGoal,
+) -> Result<(), AppError> {
+    let text = match summary.focused_title.as_deref() {
+        Some(title) => todo_core::text::truncate_to_width(title, WAYBAR_TITLE_WIDTH),
+        None => format!("{} pending", summary.pending),
+    };
+    let class = if overdue {
+        "urgent"
+    } else if summary.pending > 0 {
+        "pending"
+    } else {
+        "empty"
+    };
+    let json = serde_json::json!({
+        "text": pango_escape(&text),
+        "tooltip": pango_escape(&status_lines(summary, overdue, week, false).join("\n")),
+        "class": class,
+    });
+    println!(
+        "{}",
+        serde_json::to_string(&json).map_err(|err| AppError::invalid_data(err.to_string()))?
+    );
+    Ok(())
+}
+
+fn pango_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn warn_over_wip_limit(change: &PlannedChange) {
+    if let Some(exceeded) = change.over_limit {
+        warn(format!(
+            "today now has {} planned tasks, over wip_limit.today = {}",
+            exceeded.planned, exceeded.limit
+        ));
+    }
+}
+
+/// The `notifications.on_schedule` confirmation; the task is already saved, so a notifier
+/// that fails only earns a warning.
+fn confirm_schedule(task: &Task, config: &NotificationConfig) {
+    if let Err(err) = todo_core::task_api::confirm_schedule(task, config) {
+        warn(format!("could not confirm the schedule: {}", err.message()));
+    }
+}
+
+fn print_insights_plain(insights: &Insights, week: WeeklyGoal, palette: &Palette) {
+    println!(
+        "{}",
+        palette.accentize(&format!(
+            "Most-used commands ({} runs)",
+            insights.total_runs
+        ))
+    );
+    if insights.top_commands.is_empty() {
+        println!("  No commands recorded yet.");
+    }
+    let width = insights
+        .top_commands
+        .iter()
+        .take(INSIGHTS_TOP_COMMANDS)
+        .map(|(command, _)| command.len())
+        .max()
+        .unwrap_or(0);
+    for (command, count) in insights.top_commands.iter().take(INSIGHTS_TOP_COMMANDS) {
+        println!("  {command:<width$}  {count}");
+    }
+
+    println!();
+    println!("{}", palette.accentize("Busiest completion hours"));
+    let hours = insights.busiest_hours(INSIGHTS_TOP_HOURS);
+    if hours.is_empty() {
+        println!("  No completions yet.");
+    }
+    let most = hours.first().map(|(_, count)| *count).unwrap_or(1);
+    for (hour, count) in hours {
+        if palette.accessible {
+            let noun = if count == 1 {
+                "completion"
+            } else {
+                "completions"
+            };
+            println!("  {hour:02}:00  {count} {noun}");
+            continue;
+        }
+        let bar = "#".repeat((count * INSIGHTS_BAR_WIDTH).div_ceil(most));
+        println!("  {hour:02}:00  {}  {count}", palette.mutedize(&bar));
+    }
+
+    println!();
+    println!("{}", palette.accentize("Snoozes"));
+    match insights.average_reschedules() {
+        Some(average) => println!(
+            "  {} reschedules across {} tasks ({average:.1} per task)",
+            insights.reschedules, insights.rescheduled_tasks
+        ),
+        None => println!("  No tasks rescheduled yet."),
+    }
+
+    println!();
+    println!("{}", palette.accentize("This week"));
+    match week.target {
+        Some(target) => println!("  {}", weekly_goal_line(target, week.completed)),
+        None => {
+            let noun = if week.completed == 1 {
+                "completion"
+            } else {
+                "completions"
+            };
+            println!("  {} {noun}", week.completed);
+        }
+    }
+}
+
+fn print_insights_json(insights: &Insights, week: WeeklyGoal, envelope: bool) {
+    let top_commands: Vec<serde_json::Value> = insights
+        .top_commands
+        .iter()
+        .map(|(command, count)| serde_json::json!({ "command": command, "count": count }))
+        .collect();
+    let json = serde_json::json!({
+        "total_runs": insights.total_runs,
+        "top_commands": top_commands,
+        "completions_by_hour": insights.completions_by_hour,
+        "reschedules": {
+            "total": insights.reschedules,
+            "tasks": insights.rescheduled_tasks,
+            "average_per_task": insights.average_reschedules(),
+        },
+        "completions_this_week": insights.completions_this_week,
+        "weekly_goal": week.json(),
+    });
+    emit_json(SchemaKind::Insights, json, envelope);
+}
+
+fn print_accuracy_plain(report: &AccuracyReport, palette: &Palette) {
+    println!("{}", palette.accentize("Estimate accuracy"));
+    if report.tags.is_empty() {
+        println!("  No completed tasks with both an estimate and tracked time yet.");
+    }
+    let label = |row: &TagAccuracy| {
+        row.tag
+            .clone()
+            .unwrap_or_else(|| GroupField::Tag.missing_label().to_string())
+    };
+    let width = report
+        .tags
+        .iter()
+        .map(|row| label(row).len())
+        .max()
+        .unwrap_or(0);
+    for row in &report.tags {
+        let noun = if row.tasks == 1 { "task" } else { "tasks" };
+        let ratio = row
+            .ratio()
+            .map(|ratio| format!("{ratio:.2}x"))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "  {:<width$}  {} {noun}  estimated {}  tracked {}  {ratio}",
+            label(row),
+            row.tasks,
+            todo_core::duration::format(row.estimated),
+            todo_core::duration::format(row.tracked),
+        );
+    }
+    if report.skipped > 0 {
+        let noun = if report.skipped == 1 { "task" } else { "tasks" };
+        println!();
+        println!(
+            "{}",
+            palette.mutedize(&format!(
+                "{} completed {noun} left out: no estimate or no tracked time.",
+                report.skipped
+            ))
+        );
+    }
+}
+
+fn print_accuracy_json(report: &AccuracyReport, envelope: bool) {
+    let tags: Vec<serde_json::Value> = report
+        .tags
+        .iter()
+        .map(|row| {
+            serde_json::json!({
+                "tag": row.tag,
+                "tasks": row.tasks,
+                "estimated_minutes": row.estimated.whole_minutes(),
+                "tracked_minutes": row.tracked.whole_minutes(),
+                "ratio": row.ratio(),
+            })
+        })
+        .collect();
+    let json = serde_json::json!({ "tags": tags, "skipped": report.skipped });
+    emit_json(SchemaKind::ReportAccuracy, json, envelope);
+}
+
+fn print_store_diff_plain(diff: &StoreDiff, palette: &Palette) {
+    if diff.is_empty() {
+        println!("No differences.");
+        return;
+    }
+
+    // The section headings already say what happened, so screen readers skip the markers.
+    let (added, removed, changed) = if palette.accessible {
+        ("", "", "")
+    } else {
+        ("+ ", "- ", "~ ")
+    };
+    if !diff.added.is_empty() {
+        println!(
+            "{}",
+            palette.accentize(&format!("Added ({})", diff.added.len()))
+        );
+        for task in &diff.added {
+            println!("  {added}{}  {}", task.id, task.title);
+        }
+        println!();
+    }
+    if !diff.removed.is_empty() {
+        println!(
+            "{}",
+            palette.accentize(&format!("Removed ({})", diff.removed.len()))
+        );
+        for task in &diff.removed {
+            println!("  {removed}{}  {}", task.id, task.title);
+        }
+        println!();
+    }
+    if !diff.changed.is_empty() {
+        println!(
+            "{}",
+            palette.accentize(&format!("Changed ({})", diff.changed.len()))
+        );
+        for change in &diff.changed {
+            println!("  {changed}{}  {}", change.after.id, change.after.title);
+            for field in &change.fields {
+                if palette.accessible {
+                    println!(
+                        "      {} changed from {} to {}",
+                        field.field, field.before, field.after
+                    );
+                    continue;
+                }
+                println!(
+                    "      {}: {} -> {}",
+                    field.field,
+                    palette.mutedize(&field.before.to_string()),
+                    field.after
+                );
+            }
+        }
+        println!();
+    }
+
+    println!(
+        "Summary: {} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+}
+
+fn print_store_diff_json(
+    diff: &StoreDiff,
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let changed: Vec<serde_json::Value> = diff
+        .changed
+        .iter()
+        .map(|change| {
+            let fields: Vec<serde_json::Value> = change
+                .fields
+                .iter()
+                .map(|field| {
+                    serde_json::json!({
+                        "field": field.field,
+                        "before": field.before,
+                        "after": field.after,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "id": change.after.id,
+                "title": change.after.title,
+                "changes": fields,
+            })
+        })
+        .collect();
+    let json = serde_json::json!({
+        "added": list_json(&diff.added, legacy_json, config)?,
+        "removed": list_json(&diff.removed, legacy_json, config)?,
+        "changed": changed,
+    });
+    emit_json(SchemaKind::Diff, json, envelope);
+    Ok(())
+}
+
+fn print_maintenance_plain(maintenance: &Maintenance, palette: &Palette) {
+    if maintenance.archived.is_empty() && maintenance.purged.is_empty() {
+        println!("Nothing to archive or purge.");
+        return;
+    }
+
+    if !maintenance.archived.is_empty() {
+        let noun = if maintenance.archived.len() == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        println!(
+            "{}",
+            palette.accentize(&format!(
+                "Archived {} completed {noun}",
+                maintenance.archived.len()
+            ))
+        );
+        for task in &maintenance.archived {
+            println!("  {}  {}", task.id, task.title);
+        }
+    }
+    if !maintenance.purged.is_empty() {
+        let noun = if maintenance.purged.len() == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        println!(
+            "{}",
+            palette.accentize(&format!(
+                "Purged {} archived {noun}",
+                maintenance.purged.len()
+            ))
+        );
+        for task in &maintenance.purged {
+            println!("  {}  {}", task.id, palette.mutedize(&task.title));
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+/// With `dry_run`, the report is a plan: headings say what would happen, and conflicts are
+/// listed field by field instead of how they were settled.
+fn print_sync_plain(report: &SyncReport, dry_run: bool, palette: &Palette) {
+    let plan = &report.plan;
+    let remote = &report.remote;
+    if plan.is_empty() {
+        println!("Already in sync with {remote}.");
+        return;
+    }
+
+    if dry_run {
+        let conflicts = if plan.conflicts.len() == 1 {
+            "conflict"
+        } else {
+            "conflicts"
+        };
+        println!(
+            "Sync plan for {remote}, nothing changed yet: {} to push, {} to pull, {} to delete on {remote}, {} to delete here, {} {conflicts}",
+            plan.push.len(),
+            plan.pull.len(),
+            plan.delete_remote.len(),
+            plan.delete_local.len(),
+            plan.conflicts.len()
+        );
+    }
+    let sections = if dry_run {
+        [
+            (&plan.push, format!("To push to {remote}")),
+            (&plan.pull, format!("To pull from {remote}")),
+            (&plan.delete_remote, format!("To delete on {remote}")),
+            (&plan.delete_local, "To delete here".to_string()),
+        ]
+    } else {
+        [
+            (&plan.push, format!("Pushed to {remote}")),
+            (&plan.pull, format!("Pulled from {remote}")),
+            (&plan.delete_remote, format!("Deleted on {remote}")),
+            (&plan.delete_local, "Deleted here".to_string()),
+        ]
+    };
+    for (tasks, heading) in sections {
+        if tasks.is_empty() {
+            continue;
+        }
+        let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+        println!(
+            "{}",
+            palette.accentize(&format!("{heading}: {} {noun}", tasks.len()))
+        );
+        for task in tasks {
+            println!("  {}  {}", task.id, task.title);
+        }
+    }
+    if dry_run && !plan.conflicts.is_empty() {
+        let noun = if plan.conflicts.len() == 1 {
+            "conflict"
+        } else {
+            "conflicts"
+        };
+        println!(
+            "{}",
+            palette.accentize(&format!("To settle: {} {noun}", plan.conflicts.len()))
+        );
+        for conflict in &plan.conflicts {
+            println!("  {}  {}", conflict.local.id, conflict.local.title);
+            for change in &conflict.fields {
+                println!(
+                    "    {}: here {}, on {remote} {}",
+                    change.field, change.before, change.after
+                );
+            }
+        }
+    } else if !plan.conflicts.is_empty() {
+        let noun = if plan.conflicts.len() == 1 {
+            "conflict"
+        } else {
+            "conflicts"
+        };
+        println!(
+            "{}",
+            palette.accentize(&format!("Settled {} {noun}", plan.conflicts.len()))
+        );
+        for (conflict, resolution) in plan.conflicts.iter().zip(&report.resolutions) {
+            let kept = match resolution {
+                Resolution::Local => "kept the local task".to_string(),
+                Resolution::Remote => format!("kept the task from {remote}"),
+                Resolution::Merge(fields) if fields.is_empty() => "kept the local task".to_string(),
+                Resolution::Merge(fields) => format!("took {} from {remote}", fields.join(", ")),
+            };
+            println!(
+                "  {}  {} ({})",
+                conflict.local.id,
+                conflict.local.title,
+                palette.mutedize(&kept)
+            );
+        }
+    }
 }
 
-fn run_command(cli: Cli, palette: &Palette) -> Result<(), AppError> {
-    match cli.command {
-        Command::Add { title, urgent } => {
-            let title = match title {
-                Some(value) if !value.trim().is_empty() => value,
-                _ => return Err(AppError::invalid_input("title is required")),
+#[cfg(feature = "sync")]
+fn print_sync_json(
+    report: &SyncReport,
+    dry_run: bool,
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    emit_json(
+        SchemaKind::Sync,
+        sync_json(report, dry_run, legacy_json, config)?,
+        envelope,
+    );
+    Ok(())
+}
+
+#[cfg(feature = "sync")]
+fn sync_json(
+    report: &SyncReport,
+    dry_run: bool,
+    legacy_json: bool,
+    config: &Config,
+) -> Result<serde_json::Value, AppError> {
+    let plan = &report.plan;
+    let conflicts: Vec<serde_json::Value> = plan
+        .conflicts
+        .iter()
+        .enumerate()
+        .map(|(index, conflict)| {
+            // A dry run settles nothing, so there is no resolution yet.
+            let (resolution, from_remote) = match report.resolutions.get(index) {
+                None => (None, &[][..]),
+                Some(Resolution::Local) => (Some("local"), &[][..]),
+                Some(Resolution::Remote) => (Some("remote"), &[][..]),
+                Some(Resolution::Merge(fields)) => (Some("merge"), fields.as_slice()),
+            };
+            let changes: Vec<serde_json::Value> = conflict
+                .fields
+                .iter()
+                .map(|change| serde_json::json!({"field": change.field, "local": change.before, "remote": change.after}))
+                .collect();
+            serde_json::json!({
+                "id": conflict.local.id,
+                "title": conflict.local.title,
+                "fields": conflict.fields.iter().map(|change| change.field.as_str()).collect::<Vec<_>>(),
+                "changes": changes,
+                "resolution": resolution,
+                "from_remote": from_remote,
+            })
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "remote": report.remote,
+        "dry_run": dry_run,
+        "pushed": list_json(&plan.push, legacy_json, config)?,
+        "pulled": list_json(&plan.pull, legacy_json, config)?,
+        "deleted_remote": list_json(&plan.delete_remote, legacy_json, config)?,
+        "deleted_local": list_json(&plan.delete_local, legacy_json, config)?,
+        "conflicts": conflicts,
+    }))
+}
+
+#[cfg(feature = "sync")]
+fn print_sync_status_plain(statuses: &[RemoteStatus], palette: &Palette) {
+    if statuses.is_empty() {
+        println!("No remotes configured; add them under `remotes` in the config.");
+        return;
+    }
+    for (index, status) in statuses.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!(
+            "{}  {}",
+            palette.accentize(&status.name),
+            status.path.display()
+        );
+        let synced_at = status
+            .synced_at
+            .as_deref()
+            .map(display_time)
+            .unwrap_or_else(|| "never".to_string());
+        println!("  Last sync: {synced_at}");
+        let noun = if status.local_changes == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        println!("  Changed here since: {} {noun}", status.local_changes);
+        if !status.conflicts.is_empty() {
+            println!("  Unsettled conflicts: {}", status.conflicts.len());
+            for conflict in &status.conflicts {
+                println!(
+                    "    {}  {} ({}; found {})",
+                    conflict.task_id,
+                    conflict.title,
+                    conflict.fields.join(", "),
+                    display_time(&conflict.found_at)
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+fn print_sync_status_json(statuses: &[RemoteStatus], envelope: bool) {
+    let json: Vec<serde_json::Value> = statuses
+        .iter()
+        .map(|status| {
+            serde_json::json!({
+                "name": status.name,
+                "path": status.path.display().to_string(),
+                "synced_at": status.synced_at,
+                "local_changes": status.local_changes,
+                "conflicts": status.conflicts,
+            })
+        })
+        .collect();
+    emit_json(SchemaKind::SyncStatus, json.into(), envelope);
+}
+
+#[cfg(feature = "sync")]
+fn print_sync_queue_plain(queues: &[SyncQueue], palette: &Palette) {
+    if queues.is_empty() {
+        println!("Nothing queued.");
+        return;
+    }
+    for (index, queue) in queues.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!(
+            "{}  {}",
+            palette.accentize(&queue.remote),
+            queue.path.display()
+        );
+        if let Some(unreachable) = &queue.unreachable {
+            println!(
+                "  Unreachable since {}: {}",
+                display_time(&unreachable.since),
+                unreachable.error
+            );
+        }
+        let noun = if queue.changes.len() == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        println!("  Queued: {} {noun}", queue.changes.len());
+        for change in &queue.changes {
+            let kind = match change.kind {
+                ChangeKind::Added => "added",
+                ChangeKind::Changed => "changed",
+                ChangeKind::Deleted => "deleted",
+            };
+            println!(
+                "    {}  {}  {}",
+                palette.mutedize(&format!("{kind:<7}")),
+                change.task.id,
+                change.task.title
+            );
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+fn print_sync_queue_json(queues: &[SyncQueue], envelope: bool) {
+    let json: Vec<serde_json::Value> = queues
+        .iter()
+        .map(|queue| {
+            let changes: Vec<serde_json::Value> = queue
+                .changes
+                .iter()
+                .map(|change| serde_json::json!({"change": change.kind, "id": change.task.id, "title": change.task.title}))
+                .collect();
+            serde_json::json!({
+                "remote": queue.remote,
+                "path": queue.path.display().to_string(),
+                "unreachable": queue.unreachable,
+                "changes": changes,
+            })
+        })
+        .collect();
+    emit_json(SchemaKind::SyncQueue, json.into(), envelope);
+}
+
+#[cfg(feature = "sync")]
+const SYNC_UNANSWERED: &str = "no answer given; nothing was synced";
+
+#[cfg(feature = "sync")]
+/// Show a sync conflict on stderr and ask which side to keep, or which side each field
+/// comes from.
+fn ask_sync_resolution(conflict: &Conflict, remote: &str) -> Result<Resolution, AppError> {
+    eprintln!(
+        "Conflict: {} ({}) changed here and on {remote}",
+        conflict.local.title, conflict.local.id
+    );
+    for change in &conflict.fields {
+        eprintln!(
+            "  {}: here {}, on {remote} {}",
+            change.field, change.before, change.after
+        );
+    }
+    loop {
+        match ask(
+            "Keep [l]ocal, [r]emote, or [m]erge field by field? ",
+            SYNC_UNANSWERED,
+        )?
+        .as_str()
+        {
+            "l" | "local" => return Ok(Resolution::Local),
+            "r" | "remote" => return Ok(Resolution::Remote),
+            "m" | "merge" => break,
+            _ => eprintln!("Answer l, r, or m."),
+        }
+    }
+    let mut from_remote = Vec::new();
+    for change in &conflict.fields {
+        loop {
+            match ask(
+                &format!("  {}: [l]ocal or [r]emote? ", change.field),
+                SYNC_UNANSWERED,
+            )?
+            .as_str()
+            {
+                "l" | "local" => break,
+                "r" | "remote" => {
+                    from_remote.push(change.field.clone());
+                    break;
+                }
+                _ => eprintln!("Answer l or r."),
+            }
+        }
+    }
+    Ok(Resolution::Merge(from_remote))
+}
+
+/// Ask on stderr and read a line from stdin, trimmed and lowercased. Fails with `unanswered`
+/// when stdin ends first.
+fn ask(question: &str, unanswered: &str) -> Result<String, AppError> {
+    Ok(ask_line(question, unanswered)?.to_lowercase())
+}
+
+/// Ask where each overdue task's deadline goes. `q` keeps the answers so far and skips the
+/// rest.
+fn ask_triage(overdue: &[Task]) -> Result<Vec<(String, TriageDay)>, AppError> {
+    let mut moves = Vec::new();
+    for (number, task) in overdue.iter().enumerate() {
+        let due = task.due_at.as_deref().map(display_time).unwrap_or_default();
+        eprintln!(
+            "[{}/{}] {} ({}), due {due}",
+            number + 1,
+            overdue.len(),
+            task.title,
+            task.id
+        );
+        let day = loop {
+            let answer = ask(
+                "  [t]oday, to[m]orrow, next [w]eek, [s]kip, or [q]uit? ",
+                "no answer given; nothing was rescheduled",
+            )?;
+            match answer.as_str() {
+                "t" | "today" => break Some(TriageDay::Today),
+                "m" | "tomorrow" => break Some(TriageDay::Tomorrow),
+                "w" | "week" => break Some(TriageDay::NextWeek),
+                "s" | "skip" => break None,
+                "q" | "quit" => return Ok(moves),
+                _ => eprintln!("  Answer t, m, w, s, or q."),
+            }
+        };
+        if let Some(day) = day {
+            moves.push((task.id.clone(), day));
+        }
+    }
+    Ok(moves)
+}
+
+fn print_triage_plain(moved: &[Task], overdue: usize, palette: &Palette) {
+    if moved.is_empty() {
+        println!("Nothing was rescheduled.");
+        return;
+    }
+    let noun = if overdue == 1 { "task" } else { "tasks" };
+    println!("Rescheduled {} of {overdue} overdue {noun}:", moved.len());
+    for task in moved {
+        let due = task.due_at.as_deref().map(display_time).unwrap_or_default();
+        println!(
+            "  {} ({}) -> {}",
+            palette.accentize(&task.title),
+            task.id,
+            palette.mutedize(&due)
+        );
+    }
+}
+
+fn print_maintenance_json(
+    maintenance: &Maintenance,
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let json = serde_json::json!({
+        "archived": list_json(&maintenance.archived, legacy_json, config)?,
+        "purged": list_json(&maintenance.purged, legacy_json, config)?,
+    });
+    emit_json(SchemaKind::Maintenance, json, envelope);
+    Ok(())
+}
+
+/// Taken before `run_command` consumes the parsed command line.
+fn owned_usage_entry(cli: &Cli) -> Option<(&'static str, Option<String>)> {
+    cli.command
+        .usage_entry()
+        .map(|(command, task_id)| (command, task_id.map(str::to_string)))
+}
+
+// Best effort: the usage log must never make a command fail.
+fn record_usage(entry: Option<(&'static str, Option<String>)>, config: &Config) {
+    if !config.insights.record_usage {
+        return;
+    }
+    if let Some((command, task_id)) = entry {
+        let _ = todo_core::usage::record(command, task_id.as_deref());
+    }
+}
+
+fn resolve_aliases(mut args: Vec<String>, config: &Config) -> Result<Vec<String>, AppError> {
+    loop {
+        if args.is_empty() {
+            break;
+        }
+
+        let alias_index = args
+            .iter()
+            .position(|arg| !arg.starts_with('-'))
+            .unwrap_or(usize::MAX);
+        if alias_index == usize::MAX {
+            break;
+        }
+
+        let alias_name = args[alias_index].clone();
+        let replacement = match config.aliases.get(&alias_name) {
+            Some(value) => value,
+            None => break,
+        };
+
+        let alias_tokens = parse_command_line(replacement).map_err(|err| {
+            AppError::invalid_data(format!(
+                "alias '{alias}' invalid: {error}",
+                alias = alias_name,
+                error = err
+            ))
+        })?;
+
+        let mut resolved = Vec::new();
+        resolved.extend(args[..alias_index].iter().cloned());
+        resolved.extend(alias_tokens);
+        resolved.extend(args[(alias_index + 1)..].iter().cloned());
+        args = resolved;
+    }
+
+    Ok(args)
+}
+
+// Parse a command-line string using the same quoting rules as interactive mode.
+fn parse_command_line(line: &str) -> Result<Vec<String>, AppError> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escape = false;
+
+    for ch in line.chars() {
+        if escape {
+            if ch != '"' && ch != '\\' {
+                current.push('\\');
+            }
+            current.push(ch);
+            escape = false;
+            continue;
+        }
+
+        if in_quotes && ch == '\\' {
+            escape = true;
+            continue;
+        }
+
+        if ch == '"' {
+            in_quotes = !in_quotes;
+            continue;
+        }
+
+        if ch.is_whitespace() && !in_quotes {
+            if !current.is_empty() {
+                args.push(current.clone());
+                current.clear();
+            }
+            continue;
+        }
+
+        current.push(ch);
+    }
+
+    if in_quotes {
+        return Err(AppError::invalid_input("unterminated quote in command"));
+    }
+
+    if !current.is_empty() {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+fn validate_alias_definitions(aliases: &HashMap<String, String>) -> Result<(), AppError> {
+    for (alias, replacement) in aliases {
+        let tokens = parse_command_line(replacement)
+            .map_err(|err| AppError::invalid_input(format!("alias '{alias}' invalid: {err}")))?;
+        if tokens.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "alias '{alias}' has empty expansion"
+            )));
+        }
+    }
+
+    for alias in aliases.keys() {
+        let mut visited = HashSet::new();
+        let mut current = alias.as_str();
+
+        loop {
+            if !visited.insert(current) {
+                return Err(AppError::invalid_input(format!(
+                    "alias '{}' introduces a cycle",
+                    alias
+                )));
+            }
+
+            let expansion = match aliases.get(current) {
+                Some(value) => value,
+                None => break,
+            };
+
+            let first_token = expansion.split_whitespace().next().unwrap_or("");
+            if first_token.is_empty() {
+                break;
+            }
+
+            if first_token == current {
+                return Err(AppError::invalid_input(format!(
+                    "alias '{}' expands to itself",
+                    alias
+                )));
+            }
+
+            if !aliases.contains_key(first_token) {
+                break;
+            }
+
+            current = first_token;
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds overdue information to a task object. `status` stays machine-readable with a separate
+/// `overdue` flag; the version 0 shape (`--compat 0`) has the old `"pending (overdue)"` status
+/// instead.
+fn insert_overdue_status(
+    json: &mut serde_json::Value,
+    task: &Task,
+    legacy_json: bool,
+    config: &Config,
+) -> Result<(), AppError> {
+    let overdue = todo_core::task_api::task_overdue(task, config.overdue_grace)?;
+    if legacy_json {
+        // Older scripts only know pending and completed.
+        let status = if task.status.is_open() {
+            TaskStatus::Pending
+        } else {
+            task.status
+        };
+        json["status"] = if overdue {
+            format!("{} (overdue)", status_label(status)).into()
+        } else {
+            status_label(status).into()
+        };
+    } else {
+        json["overdue"] = overdue.into();
+        json["due_in_seconds"] = todo_core::task_api::task_due_in(task, config.due_soon_within)?
+            .map(|left| left.whole_seconds())
+            .into();
+    }
+    Ok(())
+}
+
+/// Time to a deadline such as `1h23m`, rounded up to the minute so one seconds away never reads
+/// `0m`.
+fn time_left(left: time::Duration) -> String {
+    let minutes = (left.whole_seconds() + 59).div_euclid(60);
+    todo_core::duration::format(time::Duration::minutes(minutes))
+}
+
+fn emit_json(kind: SchemaKind, value: serde_json::Value, envelope: bool) {
+    if envelope {
+        println!("{}", schema::envelope(kind, value, &warnings::take()));
+    } else {
+        println!("{}", value);
+    }
+}
+
+fn list_json(
+    tasks: &[Task],
+    legacy_json: bool,
+    config: &Config,
+) -> Result<serde_json::Value, AppError> {
+    let mut payload = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let mut json = serde_json::json!({
+            "id": task.id,
+            "title": task.title,
+            "status": status_label(task.status),
+            "created_at": task.created_at,
+            "scheduled_at": task.due_at,
+            "start_at": task.start_at,
+            "due_at": task.due_at,
+            "urgent": task.urgent,
+            "completed_at": task.completed_at,
+            "completion_history": task.completion_history,
+            "tags": task.tags,
+            "notes": task.notes,
+            "project": task.project,
+            "metadata": task.metadata,
+            "ticket": task.ticket,
+            "comments": task.comments,
+            "modified_by": task.modified_by,
+            "modified_at": task.modified_at,
+            "waiting_until": task.waiting_until,
+            "cancellation": task.cancellation,
+            "routine": task.routine,
+            "marker": task.marker,
+        "sessions": task.sessions,
+            "sessions": task.sessions,
+        });
+        insert_overdue_status(&mut json, task, legacy_json, config)?;
+        payload.push(json);
+    }
+    Ok(serde_json::Value::Array(payload))
+}
+
+fn print_tasks_json(
+    tasks: &[Task],
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let json = list_json(tasks, legacy_json, config)?;
+    emit_json(SchemaKind::List, json, envelope);
+    Ok(())
+}
+
+/// `list today --json`: the list shape, with each task's `section` from `groups`.
+fn print_today_json(
+    tasks: &[Task],
+    groups: &[TaskGroup],
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let sections: HashMap<&str, Option<&str>> = groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .tasks
+                .iter()
+                .map(|task| (task.id.as_str(), group.key.as_deref()))
+        })
+        .collect();
+    let mut json = list_json(tasks, legacy_json, config)?;
+    if let serde_json::Value::Array(items) = &mut json {
+        for (item, task) in items.iter_mut().zip(tasks) {
+            item["section"] = sections.get(task.id.as_str()).copied().flatten().into();
+        }
+    }
+    emit_json(SchemaKind::List, json, envelope);
+    Ok(())
+}
+
+/// Heading of a `list today` section.
+fn section_label(key: Option<&str>) -> String {
+    match key {
+        Some("morning") => "Morning",
+        Some("afternoon") => "Afternoon",
+        Some("evening") => "Evening",
+        _ => "Unscheduled",
+    }
+    .to_string()
+}
+
+fn print_task_groups_json(
+    field: GroupField,
+    groups: &[TaskGroup],
+    total: usize,
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let mut payload = Vec::with_capacity(groups.len());
+    for group in groups {
+        payload.push(serde_json::json!({
+            "key": group.key,
+            "count": group.tasks.len(),
+            "tasks": list_json(&group.tasks, legacy_json, config)?,
+        }));
+    }
+    let json = serde_json::json!({
+        "group_by": field.name(),
+        "groups": payload,
+        "total": total,
+    });
+    emit_json(SchemaKind::Groups, json, envelope);
+    Ok(())
+}
+
+fn print_task_json(task: &Task, envelope: bool) {
+    let json = serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "status": task.status,
+        "created_at": task.created_at,
+        "scheduled_at": task.due_at,
+        "start_at": task.start_at,
+        "due_at": task.due_at,
+    });
+    emit_json(SchemaKind::Task, json, envelope);
+}
+
+/// [`print_task_json`] plus `before` and `after` objects holding the title as it was and is.
+fn print_edited_task_json(task: &Task, before_title: &str, envelope: bool) {
+    let json = serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "status": task.status,
+        "created_at": task.created_at,
+        "scheduled_at": task.due_at,
+        "start_at": task.start_at,
+        "due_at": task.due_at,
+        "before": { "title": before_title },
+        "after": { "title": task.title },
+    });
+    emit_json(SchemaKind::Task, json, envelope);
+}
+
+fn print_task_json_with_overdue(
+    task: &Task,
+    legacy_json: bool,
+    config: &Config,
+    envelope: bool,
+) -> Result<(), AppError> {
+    let mut json = serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "status": status_label(task.status),
+        "created_at": task.created_at,
+        "scheduled_at": task.due_at,
+        "start_at": task.start_at,
+        "due_at": task.due_at,
+        "metadata": task.metadata,
+        "comments": task.comments,
+        "modified_by": task.modified_by,
+        "modified_at": task.modified_at,
+        "waiting_until": task.waiting_until,
+        "cancellation": task.cancellation,
+        "routine": task.routine,
+        "marker": task.marker,
+        "sessions": task.sessions,
+    });
+    insert_overdue_status(&mut json, task, legacy_json, config)?;
+    emit_json(SchemaKind::Task, json, envelope);
+    Ok(())
+}
+
+fn print_completed_task_json(task: &Task, envelope: bool) {
+    let json = serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "status": task.status,
+        "created_at": task.created_at,
+        "scheduled_at": task.due_at,
+        "start_at": task.start_at,
+        "due_at": task.due_at,
+        "completed_at": task.completed_at,
+        "completion_history": task.completion_history,
+    });
+    emit_json(SchemaKind::Task, json, envelope);
+}
+
+fn print_schedule_installation_json(
+    installation: &todo_core::notify::schedule::ScheduleInstallation,
+    installed: bool,
+    envelope: bool,
+) {
+    let files: Vec<String> = installation
+        .files
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    let json = serde_json::json!({
+        "installed": installed,
+        "mechanism": installation.mechanism,
+        "interval_minutes": installation.interval_minutes,
+        "files": files,
+    });
+    emit_json(SchemaKind::Schedule, json, envelope);
+}
+
+fn reject_in_demo(command: &str) -> Result<(), AppError> {
+    if demo::is_active() {
+        return Err(AppError::invalid_input(format!(
+            "{command} is not available in demo mode"
+        )));
+    }
+    Ok(())
+}
+
+fn wants_error_envelope(cli: &Cli) -> bool {
+    cli.envelope && (cli.json || cli.legacy_json)
+}
+
+/// Errors always go to stderr; `--json --envelope` callers also get an error envelope on
+/// stdout so they can parse failures the same way as results.
+fn report_command_error(err: &AppError, envelope: bool) {
+    if envelope {
+        println!("{}", schema::error_envelope(err, &warnings::take()));
+    }
+    eprintln!("ERROR: {}", err);
+    if let Some(hint) = err.hint() {
+        eprintln!("  hint: {hint}");
+    }
+}
+
+fn normalize_parse_error(err: clap::Error) -> AppError {
+    let rendered = err.to_string();
+    let first_line = rendered.lines().next().unwrap_or("invalid command").trim();
+    let message = first_line
+        .strip_prefix("error: ")
+        .unwrap_or(first_line)
+        .to_string();
+    AppError::invalid_input(message)
+}
+
+/// [`normalize_parse_error`] for a command typed by the user, with a "did you mean" hint when
+/// an unknown subcommand is close to a real one or, at the top level, to a configured alias.
+fn command_parse_error(err: clap::Error, argv: &[String], config: &Config) -> AppError {
+    let hint = unknown_subcommand_hint(&err, argv, config);
+    let err = normalize_parse_error(err);
+    match hint {
+        Some(hint) => AppError::invalid_input(format!("{}; did you mean `{hint}`?", err.message())),
+        None => err,
+    }
+}
+
+fn unknown_subcommand_hint(err: &clap::Error, argv: &[String], config: &Config) -> Option<String> {
+    use clap::error::{ContextKind, ContextValue, ErrorKind};
+    if err.kind() != ErrorKind::InvalidSubcommand {
+        return None;
+    }
+    let Some(ContextValue::String(word)) = err.get(ContextKind::InvalidSubcommand) else {
+        return None;
+    };
+    // The typo is a subcommand of whichever command precedes it, such as `list tody`.
+    let root = Cli::command();
+    let mut parent = &root;
+    let mut depth = 0;
+    for arg in argv.iter().skip(1).take_while(|arg| *arg != word) {
+        if let Some(subcommand) = parent.find_subcommand(arg) {
+            parent = subcommand;
+            depth += 1;
+        }
+    }
+    let mut candidates: Vec<&str> = parent
+        .get_subcommands()
+        .flat_map(|subcommand| {
+            std::iter::once(subcommand.get_name()).chain(subcommand.get_all_aliases())
+        })
+        .collect();
+    if depth == 0 {
+        candidates.extend(config.aliases.keys().map(String::as_str));
+    }
+    suggest::closest(word, candidates).map(str::to_string)
+}
+
+/// Arguments for one `listen` request, checked here so malformed lines are answered without
+/// starting a child process.
+fn listen_request_args(line: &str, config: &Config) -> Result<Vec<String>, AppError> {
+    let args = resolve_aliases(parse_command_line(line)?, config)?;
+    let mut argv = Vec::with_capacity(args.len() + 1);
+    argv.push("todo".to_string());
+    argv.extend(args.iter().cloned());
+    let cli = Cli::try_parse_from(argv).map_err(normalize_parse_error)?;
+    if matches!(cli.command, Command::Listen { .. } | Command::Watch { .. }) {
+        return Err(AppError::invalid_input(
+            "listen and watch cannot be started from a listen request",
+        ));
+    }
+    Ok(args)
+}
+
+fn print_help() {
+    let mut cmd = Cli::command();
+    let help = cmd.render_help();
+    println!("{help}");
+}
+
+/// Commands whose output can run past a screen, shown in the pager when `pager` is on.
+fn pages(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::List { .. }
+            | Command::Upcoming { .. }
+            | Command::Search { .. }
+            | Command::Query { .. }
+            | Command::Show { .. }
+    )
+}
+
+fn run_command(mut cli: Cli, config: &Config, palette: &Palette) -> Result<(), AppError> {
+    if config.pager
+        && !cli.no_pager
+        && !cli.json
+        && pages(&cli.command)
+        && io::stdout().is_terminal()
+    {
+        // The pager captures stdout, so the width is taken from the terminal beforehand.
+        cli.no_pager = true;
+        cli.width = cli.width.or_else(layout::width);
+        return pager::page(|| run_command(cli, config, palette));
+    }
+    for warning in compat::warnings(&cli) {
+        warn(warning);
+    }
+    // `--legacy-json` selects the old JSON shape, so it implies JSON output. From here on
+    // `legacy_json` means the version 0 shape, however it was asked for.
+    cli.json |= cli.legacy_json;
+    cli.legacy_json = compat::legacy_status(&cli);
+    // Also covers `--accessible` typed on an interactive-mode line.
+    let palette = &if cli.accessible {
+        Palette::for_screen_readers()
+    } else {
+        palette.clone()
+    };
+    layout::set_width(cli.width);
+    match cli.command {
+        Command::Add {
+            title,
+            urgent,
+            tags,
+            note,
+            project,
+            once_per_day,
+        } => {
+            let title = match title {
+                Some(value) if !value.trim().is_empty() => value,
+                _ => return Err(AppError::invalid_input("title is required")),
+            };
+
+            let (task, added) = todo_core::task_api::transaction(|txn| {
+                txn.set_title_rules(config.titles);
+                if once_per_day && let Some(task) = txn.open_task_titled_today(&title) {
+                    return Ok((task, false));
+                }
+                let mut task = txn.add(&title, urgent)?;
+                if !tags.is_empty() {
+                    task = txn.set_tags(&task.id, &tags)?;
+                }
+                if note.is_some() {
+                    task = txn.set_notes(&task.id, note.as_deref())?;
+                }
+                if project.is_some() {
+                    task = txn.set_project(&task.id, project.as_deref())?;
+                }
+                Ok((task, true))
+            })?;
+            if cli.json {
+                print_task_json(&task, cli.envelope);
+            } else {
+                let title_display = palette.accentize(&task.title);
+                let action = if added {
+                    "Added task"
+                } else {
+                    "Already added today"
+                };
+                println!("{}: {} ({})", action, title_display, task.id);
+            }
+        }
+        Command::Focus { id, force } => {
+            let change =
+                todo_core::task_api::set_focus_within_limit(&id, &config.wip_limit, force)?;
+            warn_over_wip_limit(&change);
+            let task = change.task;
+            if cli.json {
+                print_task_json(&task, cli.envelope);
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Focused task: {} ({})", title_display, task.id);
+            }
+        }
+        Command::Urgent { id, clear } => {
+            let task = todo_core::task_api::set_task_urgent(&id, !clear)?;
+            if cli.json {
+                print_task_json(&task, cli.envelope);
+            } else {
+                let title_display = palette.accentize(&task.title);
+                let action = if clear {
+                    "Cleared urgent flag"
+                } else {
+                    "Marked as urgent"
+                };
+                println!("{action}: {} ({})", title_display, task.id);
+            }
+        }
+        Command::Mark {
+            id,
+            color,
+            emoji,
+            clear,
+        } => {
+            if !clear && color.is_none() && emoji.is_none() {
+                return Err(AppError::invalid_input("give --color, --emoji, or --clear"));
+            }
+            let task =
+                todo_core::task_api::mark_task(&id, color.map(Into::into), emoji.as_deref())?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                match task.marker.as_ref() {
+                    Some(marker) => println!(
+                        "Marked task: {} {} ({})",
+                        palette.marker(marker),
+                        title_display,
+                        task.id
+                    ),
+                    None => println!("Cleared marker: {} ({})", title_display, task.id),
+                }
+            }
+        }
+        Command::Meta { id, action } => {
+            let (task, message) = match action.unwrap_or(MetaCommand::List) {
+                MetaCommand::List => (todo_core::task_api::get_task_by_id(&id)?, None),
+                MetaCommand::Set { key, value } => {
+                    let task = todo_core::task_api::set_task_metadata(&id, &key, Some(&value))?;
+                    (task, Some(format!("Set {}", key.trim())))
+                }
+                MetaCommand::Unset { key } => {
+                    let task = todo_core::task_api::set_task_metadata(&id, &key, None)?;
+                    (task, Some(format!("Removed {}", key.trim())))
+                }
+            };
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else if let Some(message) = message {
+                let title_display = palette.accentize(&task.title);
+                println!("{message}: {} ({})", title_display, task.id);
+            } else if task.metadata.is_empty() {
+                println!(
+                    "No metadata on {} ({})",
+                    palette.accentize(&task.title),
+                    task.id
+                );
+            } else {
+                print_metadata(&task.metadata, palette);
+            }
+        }
+        Command::Comment { id, text, author } => {
+            let task = todo_core::task_api::add_comment(&id, &comment_author(author), &text)?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Commented on task: {} ({})", title_display, task.id);
+            }
+        }
+        Command::LinkTicket {
+            id,
+            key,
+            url,
+            provider,
+            clear,
+        } => {
+            let link = match key {
+                Some(key) if !clear => Some(TicketLink {
+                    key,
+                    url,
+                    provider,
+                    status: None,
+                    synced_at: None,
+                }),
+                _ => None,
+            };
+            let task = todo_core::task_api::link_ticket(&id, link)?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                match task.ticket.as_ref() {
+                    Some(link) => {
+                        println!("Linked {}: {} ({})", link.key, title_display, task.id)
+                    }
+                    None => println!("Removed ticket link: {} ({})", title_display, task.id),
+                }
+            }
+        }
+        Command::RefreshTickets => {
+            let refresh = todo_core::task_api::refresh_tickets(&config.tickets)?;
+            for failure in &refresh.failures {
+                warn(format!(
+                    "Unable to refresh {} ({}): {}",
+                    failure.key, failure.task_id, failure.error
+                ));
+            }
+            if cli.json {
+                let tasks: Vec<Task> = refresh.synced.into_iter().map(|sync| sync.task).collect();
+                print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                for sync in &refresh.synced {
+                    let title_display = palette.accentize(&sync.task.title);
+                    if sync.retitled {
+                        println!("Retitled: {} ({})", title_display, sync.task.id);
+                    }
+                    if sync.completed {
+                        println!(
+                            "Completed (ticket closed): {} ({})",
+                            title_display, sync.task.id
+                        );
+                    }
+                }
+                let count = refresh.synced.len();
+                let noun = if count == 1 { "ticket" } else { "tickets" };
+                println!("Refreshed {count} {noun}.");
+            }
+        }
+        Command::Edit { id, new_title } => {
+            let (before, task) = todo_core::task_api::transaction(|txn| {
+                txn.set_title_rules(config.titles);
+                let before = txn
+                    .tasks()
+                    .iter()
+                    .find(|task| task.id == id.trim())
+                    .map(|task| task.title.clone());
+                let task = txn.edit(&id, &new_title)?;
+                Ok((before.unwrap_or_default(), task))
+            })?;
+            if cli.json {
+                print_edited_task_json(&task, &before, cli.envelope);
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Updated task: {} ({})", title_display, task.id);
+                if before != task.title && palette.accessible {
+                    println!("Previous title: {before}");
+                } else if before != task.title {
+                    println!("  {} → {}", palette.strike(&before), title_display);
+                }
+            }
+        }
+        Command::Delete { id } => {
+            let task = todo_core::task_api::delete_task(&id)?;
+            if cli.json {
+                print_task_json(&task, cli.envelope);
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Deleted task: {} ({})", title_display, task.id);
+            }
+        }
+        Command::Show { id, verbose } => {
+            if cli.json {
+                let task = todo_core::task_api::get_task_by_id(&id)?;
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let detail = todo_core::task_api::get_task_detail(&id, config.overdue_grace)?;
+                print_task_detail(&detail, palette)?;
+                if verbose {
+                    let activity = todo_core::task_api::task_activity(&detail.task.id)?;
+                    print_task_provenance(&detail.task, &activity, palette);
+                }
+            }
+        }
+        Command::Done {
+            id,
+            message,
+            message_flag,
+        } => {
+            let task = match id {
+                Some(id) => {
+                    if message.is_some() && message_flag.is_some() {
+                        return Err(AppError::invalid_input("message provided twice"));
+                    }
+                    let message_input = message.as_deref().or(message_flag.as_deref());
+                    todo_core::task_api::complete_task(&id, message_input)?
+                }
+                None => todo_core::task_api::complete_focused_task(message_flag.as_deref())?,
+            };
+            if cli.json {
+                print_completed_task_json(&task, cli.envelope);
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Completed task: {} ({})", title_display, task.id);
+                println!(
+                    "{}",
+                    palette.mutedize(&format!(
+                        "Undo with `todo undo` (valid {}s).",
+                        todo_core::task_api::UNDO_WINDOW_SECS
+                    ))
+                );
+                if let Some(target) = config.goals.completions_per_week {
+                    let summary = todo_core::task_api::status_summary()?;
+                    if summary.completed_in_week_of(todo_core::task_api::local_today()?) == target {
+                        println!("{}", palette.accentize(&weekly_goal_line(target, target)));
+                    }
+                }
+            }
+        }
+        Command::Start { id } => {
+            let task = todo_core::task_api::start_task(&id)?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Started task: {} ({})", title_display, task.id);
+            }
+        }
+        Command::Wait { id, until } => {
+            let task = todo_core::task_api::wait_task(&id, until.as_deref())?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                match task.waiting_until.as_deref() {
+                    Some(until) => println!(
+                        "Waiting on task: {} ({}) until {}",
+                        title_display,
+                        task.id,
+                        palette.mutedize(until)
+                    ),
+                    None => println!("Waiting on task: {} ({})", title_display, task.id),
+                }
+            }
+        }
+        Command::Cancel { id, reason } => {
+            let task = todo_core::task_api::cancel_task(&id, reason.as_deref())?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Cancelled task: {} ({})", title_display, task.id);
+            }
+        }
+        Command::Routine {
+            action: RoutineCommand::List,
+        } => {
+            if config.routines.is_empty() {
+                println!("No routines configured; add them under `routines` in the config.");
+            } else {
+                for (name, items) in &config.routines {
+                    println!("{}: {}", palette.accentize(name), items.join(", "));
+                }
+            }
+        }
+        Command::Routine {
+            action: RoutineCommand::Start { name },
+        } => {
+            let tasks = todo_core::task_api::start_routine(&config.routines, &name)?;
+            if cli.json {
+                print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+                println!(
+                    "Started routine {}: {} {noun} for today",
+                    palette.accentize(name.trim()),
+                    tasks.len()
+                );
+                for task in &tasks {
+                    println!("  {} ({})", task.title, task.id);
+                }
+            }
+        }
+        Command::Seed {
+            action: None,
+            rule: Some(rule),
+        } => {
+            let seed = todo_core::task_api::add_seed_rule(&rule)?;
+            println!("Added seed rule: {}", palette.accentize(&seed.rule));
+        }
+        Command::Seed {
+            action: None | Some(SeedCommand::List),
+            ..
+        } => {
+            let seeds = todo_core::task_api::seed_rules()?;
+            if seeds.is_empty() {
+                println!(
+                    "No seed rules; add one with `todo seed --rule \"every monday 9am: Weekly report\"`."
+                );
+            }
+            for (index, seed) in seeds.iter().enumerate() {
+                let last = match seed.last_seeded_at.as_deref() {
+                    Some(at) => format!("last added {}", display_time(at)),
+                    None => "not added yet".to_string(),
+                };
+                println!("{}. {} ({last})", index + 1, palette.accentize(&seed.rule));
+            }
+        }
+        Command::Seed {
+            action: Some(SeedCommand::Remove { number }),
+            ..
+        } => {
+            let seed = todo_core::task_api::remove_seed_rule(number)?;
+            println!("Removed seed rule: {}", seed.rule);
+        }
+        Command::Seed {
+            action: Some(SeedCommand::Run),
+            ..
+        } => {
+            let tasks = todo_core::task_api::run_seed_rules()?;
+            if cli.json {
+                print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
+            } else if tasks.is_empty() {
+                println!("No seed rules due.");
+            } else {
+                for task in &tasks {
+                    let start = task
+                        .start_at
+                        .as_deref()
+                        .map(display_time)
+                        .unwrap_or_default();
+                    println!(
+                        "Added task: {} ({}) for {start}",
+                        palette.accentize(&task.title),
+                        task.id
+                    );
+                }
+            }
+        }
+        #[cfg(feature = "sync")]
+        Command::Sync {
+            action: Some(SyncCommand::Status),
+            ..
+        } => {
+            let statuses = todo_core::task_api::sync_status(&config.remotes, config.overdue_grace)?;
+            if cli.json {
+                print_sync_status_json(&statuses, cli.envelope);
+            } else {
+                print_sync_status_plain(&statuses, palette);
+            }
+        }
+        #[cfg(feature = "sync")]
+        Command::Sync {
+            action: Some(SyncCommand::Queue),
+            ..
+        } => {
+            let queues = todo_core::task_api::sync_queue(&config.remotes, config.overdue_grace)?;
+            if cli.json {
+                print_sync_queue_json(&queues, cli.envelope);
+            } else {
+                print_sync_queue_plain(&queues, palette);
+            }
+        }
+        #[cfg(feature = "sync")]
+        Command::Sync {
+            action: Some(SyncCommand::Flush { prefer }),
+            ..
+        } => {
+            let ask_user = prefer.is_none() && io::stdin().is_terminal();
+            let mut resolve = |remote: &str, conflict: &Conflict| match prefer {
+                Some(prefer) => Ok(Prefer::from(prefer).resolve(conflict)),
+                None if ask_user => ask_sync_resolution(conflict, remote),
+                None => Err(AppError::invalid_input(format!(
+                    "task {} changed here and on {remote}; rerun with --prefer local|remote|newest",
+                    conflict.local.id
+                ))),
+            };
+            let flush = todo_core::task_api::flush_sync_queue(
+                &config.remotes,
+                config.overdue_grace,
+                &mut resolve,
+            )?;
+            for failure in &flush.failures {
+                warn(format!(
+                    "Still queued for {}: {}",
+                    failure.remote, failure.error
+                ));
+            }
+            if cli.json {
+                let synced = flush
+                    .synced
+                    .iter()
+                    .map(|report| sync_json(report, false, cli.legacy_json, config))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let failed: Vec<serde_json::Value> = flush
+                    .failures
+                    .iter()
+                    .map(|failure| {
+                        serde_json::json!({"remote": failure.remote, "code": failure.error.code(), "message": failure.error.message()})
+                    })
+                    .collect();
+                emit_json(
+                    SchemaKind::SyncFlush,
+                    serde_json::json!({"synced": synced, "failed": failed}),
+                    cli.envelope,
+                );
+            } else if flush.synced.is_empty() && flush.failures.is_empty() {
+                println!("Nothing queued.");
+            } else {
+                for report in &flush.synced {
+                    print_sync_plain(report, false, palette);
+                }
+            }
+        }
+        // Without a subcommand clap requires the remote, so it is never empty here.
+        #[cfg(feature = "sync")]
+        Command::Sync {
+            remote,
+            dry_run: true,
+            ..
+        } => {
+            let remote = remote.unwrap_or_default();
+            let report =
+                todo_core::task_api::plan_sync(&config.remotes, &remote, config.overdue_grace)?;
+            if cli.json {
+                print_sync_json(&report, true, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                print_sync_plain(&report, true, palette);
+            }
+        }
+        #[cfg(feature = "sync")]
+        Command::Sync { remote, prefer, .. } => {
+            let remote = remote.unwrap_or_default();
+            let ask_user = prefer.is_none() && io::stdin().is_terminal();
+            let mut resolve = |conflict: &Conflict| match prefer {
+                Some(prefer) => Ok(Prefer::from(prefer).resolve(conflict)),
+                None if ask_user => ask_sync_resolution(conflict, remote.trim()),
+                None => Err(AppError::invalid_input(format!(
+                    "task {} changed here and on {}; rerun with --prefer local|remote|newest",
+                    conflict.local.id,
+                    remote.trim()
+                ))),
+            };
+            let report = todo_core::task_api::sync_remote(
+                &config.remotes,
+                &remote,
+                config.overdue_grace,
+                &mut resolve,
+            )?;
+            if cli.json {
+                print_sync_json(&report, false, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                print_sync_plain(&report, false, palette);
+            }
+        }
+        Command::Undo => {
+            let task = todo_core::task_api::undo_completion()?;
+            if cli.json {
+                print_task_json_with_overdue(&task, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                let title_display = palette.accentize(&task.title);
+                println!("Reopened task: {} ({})", title_display, task.id);
+            }
+        }
+        Command::Schedule {
+            id,
+            datetime,
+            start,
+            force,
+            allow_past,
+        } => {
+            let change = if start {
+                todo_core::task_api::schedule_start_within_limit(
+                    &id,
+                    &datetime,
+                    &config.wip_limit,
+                    force,
+                )?
+            } else {
+                todo_core::task_api::schedule_task_within_limit(
+                    &id,
+                    &datetime,
+                    &config.wip_limit,
+                    force,
+                    allow_past,
+                    config.overdue_grace,
+                )?
+            };
+            warn_over_wip_limit(&change);
+            let task = change.task;
+            confirm_schedule(&task, &config.notifications);
+            if cli.json {
+                print_task_json(&task, cli.envelope);
+            } else if start {
+                let start_at = task.start_at.as_deref().unwrap_or("-");
+                let title_display = palette.accentize(&task.title);
+                println!(
+                    "Planned task: {} ({}) to start at {}",
+                    title_display,
+                    task.id,
+                    palette.mutedize(start_at)
+                );
+            } else {
+                let scheduled_at = task.due_at.as_deref().unwrap_or("-");
+                let title_display = palette.accentize(&task.title);
+                let scheduled_display = palette.mutedize(scheduled_at);
+                println!(
+                    "Scheduled task: {} ({}) at {}",
+                    title_display, task.id, scheduled_display
+                );
+            }
+        }
+        Command::Reschedule {
+            id,
+            datetime,
+            force,
+        } => {
+            let change = todo_core::task_api::reschedule_task_within_limit(
+                &id,
+                &datetime,
+                &config.wip_limit,
+                force,
+                config.overdue_grace,
+            )?;
+            warn_over_wip_limit(&change);
+            let task = change.task;
+            confirm_schedule(&task, &config.notifications);
+            if cli.json {
+                print_task_json(&task, cli.envelope);
+            } else {
+                let scheduled_at = task.due_at.as_deref().unwrap_or("-");
+                let title_display = palette.accentize(&task.title);
+                let scheduled_display = palette.mutedize(scheduled_at);
+                println!(
+                    "Rescheduled task: {} ({}) at {}",
+                    title_display, task.id, scheduled_display
+                );
+            }
+        }
+        Command::Notify {
+            action: Some(NotifyCommand::InstallSchedule { every }),
+            ..
+        } => {
+            reject_in_demo("notify install-schedule")?;
+            let installation = todo_core::notify::schedule::install_schedule(&every)?;
+            if cli.json {
+                print_schedule_installation_json(&installation, true, cli.envelope);
+            } else {
+                let minutes = installation.interval_minutes.unwrap_or_default();
+                println!(
+                    "Installed notification schedule via {} (every {} minutes)",
+                    installation.mechanism, minutes
+                );
+                for file in &installation.files {
+                    println!("  wrote {}", palette.mutedize(&file.display().to_string()));
+                }
+            }
+        }
+        Command::Notify {
+            action: Some(NotifyCommand::UninstallSchedule),
+            ..
+        } => {
+            reject_in_demo("notify uninstall-schedule")?;
+            let installation = todo_core::notify::schedule::uninstall_schedule()?;
+            if cli.json {
+                print_schedule_installation_json(&installation, false, cli.envelope);
+            } else {
+                println!(
+                    "Removed notification schedule via {}",
+                    installation.mechanism
+                );
+            }
+        }
+        Command::Notify {
+            action: Some(NotifyCommand::Test),
+            ..
+        } => {
+            if std::env::var_os("TODOAPP_DISABLE_NOTIFICATIONS").is_some() {
+                warn("TODOAPP_DISABLE_NOTIFICATIONS is set, so no notification is actually sent");
+            }
+            let checks = todo_core::notify::send_test_notifications(&config.notifications);
+            if cli.json {
+                let json: Vec<serde_json::Value> = checks
+                    .iter()
+                    .map(|check| {
+                        let error = check.result.as_ref().err().map(
+                            |err| serde_json::json!({"code": err.code(), "message": err.message()}),
+                        );
+                        serde_json::json!({
+                            "destination": check.destination,
+                            "backend": check.backend.as_str(),
+                            "ok": check.result.is_ok(),
+                            "error": error,
+                        })
+                    })
+                    .collect();
+                emit_json(
+                    SchemaKind::NotifyTest,
+                    serde_json::Value::Array(json),
+                    cli.envelope,
+                );
+            } else {
+                for check in &checks {
+                    let destination = format!("{} ({})", check.destination, check.backend.as_str());
+                    match &check.result {
+                        Ok(()) => println!("ok      {}", palette.accentize(&destination)),
+                        Err(err) => println!(
+                            "failed  {}: {}",
+                            palette.accentize(&destination),
+                            err.message()
+                        ),
+                    }
+                }
+            }
+            let failed = checks.iter().filter(|check| check.result.is_err()).count();
+            if failed > 0 {
+                return Err(AppError::io(format!(
+                    "{failed} of {} notification destinations failed",
+                    checks.len()
+                )));
+            }
+        }
+        Command::Notify {
+            action: Some(NotifyCommand::RegisterWindowsApp),
+            ..
+        } => {
+            reject_in_demo("notify register-windows-app")?;
+            let registration = todo_core::notify::windows_app::register_windows_app(
+                &config.notifications.windows,
+            )?;
+            if cli.json {
+                let json = serde_json::json!({
+                    "app_id": registration.app_id,
+                    "display_name": registration.display_name,
+                    "key": registration.key,
+                    "configured": registration.configured,
+                });
+                emit_json(SchemaKind::WindowsApp, json, cli.envelope);
+            } else {
+                println!(
+                    "Registered {} as {}",
+                    palette.accentize(&registration.display_name),
+                    registration.app_id
+                );
+                println!("  wrote {}", palette.mutedize(&registration.key));
+            }
+            if !registration.configured {
+                warn(format!(
+                    "set notifications.windows.app_id to \"{}\" so toasts use it",
+                    registration.app_id
+                ));
+            }
+        }
+        Command::Notify { action: None, log } => {
+            let started = todo_core::clock::now_utc();
+            let timer = std::time::Instant::now();
+            let result = todo_core::task_api::notify_overdue_or_urgent_with_config(
+                &config.notifications,
+                config.overdue_grace,
+            );
+            if let Some(log) = log {
+                let logged = RunRecord::new(&result, started, timer.elapsed())
+                    .and_then(|record| run_log::append(&log, &record));
+                if let Err(err) = logged {
+                    warn(format!("Unable to log the notify run: {}", err.message()));
+                }
+            }
+            let outcome = result?;
+            if !outcome.failures.is_empty() {
+                for failure in &outcome.failures {
+                    warn(format!(
+                        "Unable to notify {}: {}",
+                        failure.task_id, failure.error
+                    ));
+                }
+            }
+            let tasks = outcome.tasks;
+            if cli.json {
+                print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
+            } else if tasks.is_empty() {
+                println!("No notifications sent.");
+            } else if !outcome.digests.is_empty() {
+                for digest in &outcome.digests {
+                    println!("Sent digest notification: {}", palette.accentize(digest));
+                }
+            } else {
+                for task in tasks {
+                    let title_display = palette.accentize(&task.title);
+                    println!("Notified task: {} ({})", title_display, task.id);
+                }
+            }
+        }
+        Command::Demo { args } => {
+            if demo::is_active() {
+                return Err(AppError::invalid_input("already running in demo mode"));
+            }
+            let _sandbox = demo::DemoSandbox::start()?;
+            // Sample tasks should never pop up real desktop notifications.
+            let mut demo_config = config.clone();
+            demo_config.notifications.backend = NotificationBackend::Console;
+
+            if args.is_empty() {
+                println!(
+                    "{}",
+                    palette.accentize(
+                        "Demo mode: sample tasks in a temporary store; nothing is saved."
+                    )
+                );
+                println!(
+                    "{}",
+                    palette.mutedize(
+                        "Try `list today`, `show 1003`, `done 1002`. Type `exit` to leave."
+                    )
+                );
+                run_interactive(&demo_config, palette, None)?;
+            } else {
+                // Global flags given after `demo` are parsed by the outer command line.
+                let mut argv = vec!["todo".to_string()];
+                for (enabled, flag) in [
+                    (cli.json, "--json"),
+                    (cli.legacy_json, "--legacy-json"),
+                    (cli.envelope, "--envelope"),
+                ] {
+                    if enabled {
+                        argv.push(flag.to_string());
+                    }
+                }
+                argv.extend(args);
+                let demo_cli = Cli::try_parse_from(argv).map_err(normalize_parse_error)?;
+                run_command(demo_cli, &demo_config, palette)?;
+            }
+        }
+        Command::Listen { socket } => {
+            let config = config.clone();
+            listen::serve(
+                &socket,
+                Box::new(move |line| listen_request_args(line, &config)),
+            )?;
+        }
+        Command::Watch {
+            filter,
+            exec,
+            on,
+            interval,
+        } => {
+            watch::run(
+                &filter,
+                &exec,
+                &on,
+                std::time::Duration::from_secs(interval),
+                config.overdue_grace,
+            )?;
+        }
+        Command::Status { waybar } => {
+            let summary = todo_core::task_api::status_summary()?;
+            let overdue = summary.overdue(todo_core::clock::now_utc(), config.overdue_grace);
+            let week = WeeklyGoal {
+                target: config.goals.completions_per_week,
+                completed: summary.completed_in_week_of(todo_core::task_api::local_today()?),
+            };
+            if waybar {
+                print_status_waybar(&summary, overdue, week)?;
+            } else if cli.json {
+                print_status_json(&summary, overdue, week, cli.envelope)?;
+            } else {
+                print_status_plain(&summary, overdue, week, palette);
+            }
+        }
+        Command::Insights => {
+            let insights = todo_core::task_api::usage_insights()?;
+            let week = WeeklyGoal {
+                target: config.goals.completions_per_week,
+                completed: insights.completions_this_week,
             };
-
-            let task = todo_core::task_api::add_task_with_urgency(&title, urgent)?;
             if cli.json {
-                print_task_json(&task);
+                print_insights_json(&insights, week, cli.envelope);
             } else {
-                let title_display = palette.accentize(&task.title);
-                println!("Added task: {} ({})", title_display, task.id);
+                print_insights_plain(&insights, week, palette);
             }
         }
-        Command::Focus { id } => {
-            let task = todo_core::task_api::set_focus(&id)?;
+        Command::Report {
+            report: ReportCommand::Accuracy,
+        } => {
+            let report = todo_core::task_api::estimate_accuracy()?;
             if cli.json {
-                print_task_json(&task);
+                print_accuracy_json(&report, cli.envelope);
             } else {
-                let title_display = palette.accentize(&task.title);
-                println!("Focused task: {} ({})", title_display, task.id);
+                print_accuracy_plain(&report, palette);
             }
         }
-        Command::Urgent { id, clear } => {
-            let task = todo_core::task_api::set_task_urgent(&id, !clear)?;
+        Command::Schema { kind } => {
+            let value = match kind {
+                Some(kind) => schema::schema(kind),
+                None => {
+                    let schemas: serde_json::Map<String, serde_json::Value> = SchemaKind::ALL
+                        .into_iter()
+                        .map(|kind| (kind.name().to_string(), schema::schema(kind)))
+                        .collect();
+                    serde_json::json!({
+                        "schema_version": schema::JSON_SCHEMA_VERSION,
+                        "schemas": schemas,
+                    })
+                }
+            };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&value)
+                    .map_err(|err| AppError::invalid_data(err.to_string()))?
+            );
+        }
+        Command::Init { defaults } => {
+            let (setup, sample_task) = if defaults {
+                (Setup::default(), true)
+            } else {
+                ask_setup()?
+            };
+            let report = set_up(&setup, sample_task)?;
             if cli.json {
-                print_task_json(&task);
+                let json = serde_json::json!({
+                    "config_path": report.config_path,
+                    "store_path": report.store_path,
+                    "theme": report.setup.theme,
+                    "notifications": report.setup.notifications,
+                    "sample_task": report.sample_task.as_ref().map(|task| serde_json::json!({
+                        "id": task.id,
+                        "title": task.title,
+                        "status": task.status,
+                        "created_at": task.created_at,
+                    })),
+                });
+                emit_json(SchemaKind::Init, json, cli.envelope);
             } else {
-                let title_display = palette.accentize(&task.title);
-                let action = if clear {
-                    "Cleared urgent flag"
+                print_init_plain(&report, palette);
+            }
+        }
+        Command::List {
+            list,
+            wide,
+            group_by,
+            project,
+            tag,
+            status,
+        } => {
+            let status = status.as_deref().map(StatusFilter::parse).transpose()?;
+            let today = matches!(list, ListCommand::Today);
+            let (mut tasks, focused_task_id, context, day_note) = match list {
+                ListCommand::Today => {
+                    let result = todo_core::task_api::list_today_with_order(
+                        &config.ordering.today,
+                        config.overdue_grace,
+                    )?;
+                    (
+                        result.tasks,
+                        result.focused_task_id,
+                        result.context,
+                        result.day_note,
+                    )
+                }
+                ListCommand::Backlog => {
+                    let tasks = todo_core::task_api::list_backlog(config.overdue_grace)?;
+                    (
+                        tasks,
+                        None,
+                        todo_core::task_api::list_contexts()?.active,
+                        None,
+                    )
+                }
+                ListCommand::Week { iso } => {
+                    let period = match iso {
+                        Some(week) => Period::parse_week(&week)?,
+                        None => Period::week_of(todo_core::task_api::local_today()?),
+                    };
+                    let tasks = todo_core::task_api::list_period(period, config.overdue_grace)?;
+                    (
+                        tasks,
+                        None,
+                        todo_core::task_api::list_contexts()?.active,
+                        None,
+                    )
+                }
+                ListCommand::Quarter { iso } => {
+                    let period = match iso {
+                        Some(quarter) => Period::parse_quarter(&quarter)?,
+                        None => Period::quarter_of(todo_core::task_api::local_today()?),
+                    };
+                    let tasks = todo_core::task_api::list_period(period, config.overdue_grace)?;
+                    (
+                        tasks,
+                        None,
+                        todo_core::task_api::list_contexts()?.active,
+                        None,
+                    )
+                }
+            };
+            let filters: Vec<Filter> = [
+                project.map(|name| Filter::Project(name.trim().to_lowercase())),
+                tag.map(|name| Filter::Tag(name.trim().to_lowercase())),
+                status.map(Filter::Status),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if !filters.is_empty() {
+                let now = todo_core::clock::now_utc();
+                tasks.retain(|task| {
+                    filters
+                        .iter()
+                        .all(|filter| filter.matches(task, now, config.overdue_grace))
+                });
+            }
+            if let Some(note) = day_note.as_deref()
+                && !cli.json
+            {
+                print_day_note_plain("Journal", note, palette);
+                println!();
+            }
+            match group_by {
+                Some(field) => {
+                    let groups = todo_core::task_api::group_tasks(
+                        &tasks,
+                        field.into(),
+                        config.overdue_grace,
+                    )?;
+                    if cli.json {
+                        print_task_groups_json(
+                            field,
+                            &groups,
+                            tasks.len(),
+                            cli.legacy_json,
+                            config,
+                            cli.envelope,
+                        )?;
+                    } else {
+                        print_task_groups_plain(
+                            |key| key.unwrap_or(field.missing_label()).to_string(),
+                            &groups,
+                            tasks.len(),
+                            focused_task_id.as_deref(),
+                            palette,
+                            wide,
+                            config,
+                        )?;
+                    }
+                }
+                None if today => {
+                    let sections = config.day_sections.sections()?;
+                    let groups = todo_core::task_api::group_tasks(
+                        &tasks,
+                        GroupBy::Section(sections),
+                        config.overdue_grace,
+                    )?;
+                    if cli.json {
+                        print_today_json(&tasks, &groups, cli.legacy_json, config, cli.envelope)?;
+                    } else if config.day_sections.enabled {
+                        print_task_groups_plain(
+                            section_label,
+                            &groups,
+                            tasks.len(),
+                            focused_task_id.as_deref(),
+                            palette,
+                            wide,
+                            config,
+                        )?;
+                    } else {
+                        print_tasks_plain(
+                            &tasks,
+                            focused_task_id.as_deref(),
+                            palette,
+                            wide,
+                            config,
+                        )?;
+                    }
+                }
+                None if cli.json => {
+                    print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?
+                }
+                None => {
+                    print_tasks_plain(&tasks, focused_task_id.as_deref(), palette, wide, config)?
+                }
+            }
+            if let Some(context) = context.as_deref()
+                && !cli.json
+            {
+                println!(
+                    "{}",
+                    palette.mutedize(&format!(
+                        "Context: {context} (`todo context none` to show all tasks)"
+                    ))
+                );
+            }
+        }
+        Command::Upcoming { days, wide } => {
+            let tasks = todo_core::task_api::list_upcoming(days, config.overdue_grace)?;
+            let groups =
+                todo_core::task_api::group_tasks(&tasks, GroupBy::Day, config.overdue_grace)?;
+            if cli.json {
+                print_task_groups_json(
+                    GroupField::Day,
+                    &groups,
+                    tasks.len(),
+                    cli.legacy_json,
+                    config,
+                    cli.envelope,
+                )?;
+            } else {
+                let today = todo_core::task_api::local_today()?;
+                let last = today.saturating_add(time::Duration::days(i64::from(days) - 1));
+                let notes = todo_core::task_api::day_notes_between(today, last)?;
+                print_agenda_plain(&groups, &notes, days, today, palette, wide, config)?;
+            }
+        }
+        Command::Journal { date, text, clear } => {
+            let (date, text) = match (date, text) {
+                (Some(first), None) if !clear && !looks_like_day(&first) => (None, Some(first)),
+                other => other,
+            };
+            let day = match date.as_deref() {
+                Some(value) => todo_core::task_api::parse_day(value)?,
+                None => todo_core::task_api::local_today()?,
+            };
+            let heading = day_heading(day)?;
+            if clear {
+                let removed = todo_core::task_api::clear_day_note(day)?;
+                if cli.json {
+                    print_day_note_json(day, None, cli.envelope);
+                } else if removed.is_some() {
+                    println!("Cleared the journal for {heading}");
                 } else {
-                    "Marked as urgent"
+                    println!("No journal for {heading}.");
+                }
+            } else {
+                let note = match text {
+                    Some(text) => Some(todo_core::task_api::add_day_note(day, &text)?),
+                    None => todo_core::task_api::day_note(day)?,
                 };
-                println!("{action}: {} ({})", title_display, task.id);
+                match note {
+                    _ if cli.json => print_day_note_json(day, note.as_deref(), cli.envelope),
+                    Some(note) => {
+                        print_day_note_plain(&format!("Journal for {heading}"), &note, palette)
+                    }
+                    None => println!("No journal for {heading}."),
+                }
             }
         }
-        Command::Edit { id, new_title } => {
-            let task = todo_core::task_api::edit_task(&id, &new_title)?;
+        Command::Triage => {
+            let overdue = todo_core::task_api::overdue_tasks(config.overdue_grace)?;
+            let moves = ask_triage(&overdue)?;
+            let moved = if moves.is_empty() {
+                Vec::new()
+            } else {
+                todo_core::task_api::triage_overdue(&moves, config.overdue_grace)?
+            };
             if cli.json {
-                print_task_json(&task);
+                print_tasks_json(&moved, cli.legacy_json, config, cli.envelope)?;
+            } else if overdue.is_empty() {
+                println!("Nothing is overdue.");
             } else {
-                let title_display = palette.accentize(&task.title);
-                println!("Updated task: {} ({})", title_display, task.id);
+                print_triage_plain(&moved, overdue.len(), palette);
             }
         }
-        Command::Delete { id } => {
-            let task = todo_core::task_api::delete_task(&id)?;
+        Command::Query { expression } => {
+            let tasks = todo_core::task_api::query_tasks(&expression, config.overdue_grace)?;
+            print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
+        }
+        Command::Search { words, status } => {
+            let status = status.as_deref().map(StatusFilter::parse).transpose()?;
+            let mut tasks = todo_core::task_api::search_tasks(&words.join(" "))?;
+            if let Some(status) = status {
+                let now = todo_core::clock::now_utc();
+                tasks
+                    .retain(|task| Filter::Status(status).matches(task, now, config.overdue_grace));
+            }
             if cli.json {
-                print_task_json(&task);
+                print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
             } else {
-                let title_display = palette.accentize(&task.title);
-                println!("Deleted task: {} ({})", title_display, task.id);
+                print_tasks_plain(&tasks, None, palette, false, config)?;
             }
         }
-        Command::Show { id } => {
-            let task = todo_core::task_api::get_task_by_id(&id)?;
+        Command::Index {
+            action: IndexCommand::Rebuild,
+        } => {
+            let count = todo_core::task_api::rebuild_search_index()?;
+            let noun = if count == 1 { "task" } else { "tasks" };
+            println!("Rebuilt the search index ({count} {noun}).");
+        }
+        Command::Snapshot { action } => {
+            let message = match action.unwrap_or(SnapshotCommand::List) {
+                SnapshotCommand::List => None,
+                SnapshotCommand::Save { name } => {
+                    let backup = todo_core::task_api::save_snapshot(&name)?;
+                    Some(format!("Saved snapshot: {}", backup.name))
+                }
+                SnapshotCommand::Restore { name } => {
+                    let restore = todo_core::task_api::restore_snapshot(&name)?;
+                    Some(format!(
+                        "Restored snapshot: {} (previous tasks saved as {})",
+                        restore.restored.name, restore.previous.name
+                    ))
+                }
+            };
             if cli.json {
-                print_task_json_with_overdue(&task)?;
+                print_snapshots_json(&todo_core::task_api::list_snapshots()?, cli.envelope);
+            } else if let Some(message) = message {
+                println!("{}", palette.accentize(&message));
             } else {
-                print_tasks_plain(std::slice::from_ref(&task), None, palette)?;
+                print_snapshots_plain(&todo_core::task_api::list_snapshots()?, palette);
             }
         }
-        Command::Done {
-            id,
-            message,
-            message_flag,
+        Command::Events {
+            action: EventsCommand::Pull { ack, limit },
         } => {
-            let task = match id {
-                Some(id) => {
-                    if message.is_some() && message_flag.is_some() {
-                        return Err(AppError::invalid_input("message provided twice"));
-                    }
-                    let message_input = message.as_deref().or(message_flag.as_deref());
-                    todo_core::task_api::complete_task(&id, message_input)?
+            let events = todo_core::task_api::pull_events(limit, ack)?;
+            if cli.json {
+                let json = serde_json::to_value(&events)
+                    .map_err(|err| AppError::invalid_data(err.to_string()))?;
+                emit_json(SchemaKind::Events, json, cli.envelope);
+            } else if events.is_empty() {
+                println!("No events.");
+            } else {
+                for event in &events {
+                    println!(
+                        "{:>5}  {}  {}  {} ({}) by {}",
+                        event.seq,
+                        palette.mutedize(&event.at),
+                        event.kind.as_str(),
+                        palette.accentize(&event.title),
+                        event.task_id,
+                        event.by
+                    );
                 }
-                None => todo_core::task_api::complete_focused_task(message_flag.as_deref())?,
-            };
+            }
+        }
+        Command::Events {
+            action: EventsCommand::Ack { seq },
+        } => {
+            let acked = todo_core::task_api::ack_events(seq)?;
             if cli.json {
-                print_completed_task_json(&task);
+                emit_json(
+                    SchemaKind::EventsAck,
+                    serde_json::json!({"acknowledged": acked, "through": seq}),
+                    cli.envelope,
+                );
             } else {
-                let title_display = palette.accentize(&task.title);
-                println!("Completed task: {} ({})", title_display, task.id);
+                let noun = if acked == 1 { "event" } else { "events" };
+                println!("Acknowledged {acked} {noun} through #{seq}");
             }
         }
-        Command::Schedule { id, datetime } => {
-            let task = todo_core::task_api::schedule_task(&id, &datetime)?;
+        Command::Webhooks {
+            action: WebhooksCommand::Deliver,
+        } => {
+            let report = todo_core::task_api::deliver_webhooks()?;
+            for (url, error) in &report.failed {
+                warn(format!("Still queued for {url}: {error}"));
+            }
             if cli.json {
-                print_task_json(&task);
+                let failed: Vec<serde_json::Value> = report
+                    .failed
+                    .iter()
+                    .map(|(url, error)| serde_json::json!({"url": url, "error": error}))
+                    .collect();
+                emit_json(
+                    SchemaKind::WebhooksDeliver,
+                    serde_json::json!({"delivered": report.delivered, "queued": report.queued, "failed": failed}),
+                    cli.envelope,
+                );
+            } else if report.delivered == 0 && report.queued == 0 {
+                println!("Nothing queued.");
             } else {
-                let scheduled_at = task.scheduled_at.as_deref().unwrap_or("-");
-                let title_display = palette.accentize(&task.title);
-                let scheduled_display = palette.mutedize(scheduled_at);
                 println!(
-                    "Scheduled task: {} ({}) at {}",
-                    title_display, task.id, scheduled_display
+                    "Delivered {}, {} still queued",
+                    report.delivered, report.queued
                 );
             }
         }
-        Command::Reschedule { id, datetime } => {
-            let task = todo_core::task_api::reschedule_task(&id, &datetime)?;
+        Command::Webhooks {
+            action: WebhooksCommand::Status,
+        } => {
+            let queued = todo_core::task_api::queued_webhooks()?;
             if cli.json {
-                print_task_json(&task);
+                let json = serde_json::to_value(&queued)
+                    .map_err(|err| AppError::invalid_data(err.to_string()))?;
+                emit_json(SchemaKind::WebhooksStatus, json, cli.envelope);
+            } else if queued.is_empty() {
+                println!("Nothing queued.");
             } else {
-                let scheduled_at = task.scheduled_at.as_deref().unwrap_or("-");
-                let title_display = palette.accentize(&task.title);
-                let scheduled_display = palette.mutedize(scheduled_at);
+                for delivery in &queued {
+                    let error = delivery
+                        .last_error
+                        .as_deref()
+                        .map(|error| format!(" ({} attempts, last: {error})", delivery.attempts))
+                        .unwrap_or_default();
+                    println!(
+                        "{:>5}  {}  {}{}",
+                        delivery.event.seq,
+                        delivery.event.kind.as_str(),
+                        delivery.url,
+                        palette.mutedize(&error)
+                    );
+                }
+            }
+        }
+        Command::Run {
+            file,
+            replay: false,
+        } => run_script(&file, config, palette)?,
+        Command::Run { file, replay: true } => replay_transcript(&file, config, palette)?,
+        Command::Diff { old, new } => {
+            let diff = todo_core::task_api::diff_stores(&old, new.as_deref())?;
+            if cli.json {
+                print_store_diff_json(&diff, cli.legacy_json, config, cli.envelope)?;
+            } else {
+                print_store_diff_plain(&diff, palette);
+            }
+        }
+        Command::Export { verify: true, .. } => {
+            let trips = todo_core::task_api::verify_exports()?;
+            let lossless = trips.iter().all(RoundTrip::is_lossless);
+            if cli.json {
+                print_export_verify_json(&trips, cli.envelope);
+            } else {
+                print_export_verify_plain(&trips, palette);
+                if !lossless {
+                    return Err(AppError::invalid_data("fields were lost in a round trip")
+                        .with_hint("a format that should carry them needs fixing"));
+                }
+            }
+        }
+        Command::Export { format, output, .. } => {
+            let (rendered, count) = match format {
+                ExportFormat::Html => {
+                    let snapshot = todo_core::task_api::export_snapshot(config.overdue_grace)?;
+                    let count = snapshot.today.len() + snapshot.backlog.len() + snapshot.done.len();
+                    (todo_core::export::html::render(&snapshot)?, count)
+                }
+                ExportFormat::Csv => {
+                    let tasks = todo_core::task_api::all_tasks()?;
+                    (todo_core::export::csv::render(&tasks), tasks.len())
+                }
+            };
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)
+                        .map_err(|err| AppError::io(format!("{}: {}", path.display(), err)))?;
+                    let noun = if count == 1 { "task" } else { "tasks" };
+                    println!("Exported {count} {noun} to {}", path.display());
+                }
+                None => print!("{rendered}"),
+            }
+        }
+        Command::Import {
+            file,
+            format,
+            map,
+            date_format,
+            no_header,
+            delimiter,
+            dry_run,
+        } => {
+            let content = std::fs::read_to_string(&file)
+                .map_err(|err| AppError::io(format!("{}: {}", file.display(), err)))?;
+            let imported = match format {
+                ImportFormat::Csv => {
+                    let options = CsvOptions {
+                        map: map.as_deref().map(ColumnMap::parse).transpose()?,
+                        date_order: date_format
+                            .as_deref()
+                            .map(DateOrder::parse)
+                            .transpose()?
+                            .unwrap_or_default(),
+                        header: !no_header,
+                        delimiter,
+                    };
+                    todo_core::import::csv::read(&content, &options)?
+                }
+            };
+            let tasks = todo_core::task_api::import_tasks(&imported, dry_run)?;
+            let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+            if cli.json {
+                print_tasks_json(&tasks, cli.legacy_json, config, cli.envelope)?;
+            } else if tasks.is_empty() {
+                println!("No tasks found in {}.", file.display());
+            } else if dry_run {
+                print_import_preview(&imported, &tasks, palette);
                 println!(
-                    "Rescheduled task: {} ({}) at {}",
-                    title_display, task.id, scheduled_display
+                    "Would import {} {noun} from {}; nothing was saved.",
+                    tasks.len(),
+                    file.display()
                 );
+            } else {
+                println!("Imported {} {noun} from {}", tasks.len(), file.display());
             }
         }
-        Command::Notify => {
-            let outcome = todo_core::task_api::notify_overdue_or_urgent()?;
-            if !outcome.failures.is_empty() {
-                for failure in &outcome.failures {
-                    eprintln!(
-                        "WARNING: Unable to notify {}: {}",
-                        failure.task_id, failure.error
+        Command::Graph { format, output } => {
+            let tasks = todo_core::task_api::all_tasks()?;
+            let rendered = match format {
+                GraphFormat::Dot => todo_core::export::dot::render(&tasks),
+            };
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)
+                        .map_err(|err| AppError::io(format!("{}: {}", path.display(), err)))?;
+                    let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+                    println!(
+                        "Wrote graph of {} {noun} to {}",
+                        tasks.len(),
+                        path.display()
                     );
                 }
+                None => print!("{rendered}"),
             }
-            let tasks = outcome.tasks;
+        }
+        Command::Maintain => {
+            let retention = &config.retention;
+            if retention.archive_completed_after_days.is_none()
+                && retention.purge_archived_after_days.is_none()
+            {
+                return Err(AppError::invalid_input(
+                    "no retention rules configured; set retention.archive_completed_after_days or retention.purge_archived_after_days",
+                ));
+            }
+            let maintenance = todo_core::task_api::maintain(retention)?;
             if cli.json {
-                print_tasks_json(&tasks)?;
-            } else if tasks.is_empty() {
-                println!("No notifications sent.");
+                print_maintenance_json(&maintenance, cli.legacy_json, config, cli.envelope)?;
             } else {
-                for task in tasks {
-                    let title_display = palette.accentize(&task.title);
-                    println!("Notified task: {} ({})", title_display, task.id);
+                print_maintenance_plain(&maintenance, palette);
+            }
+        }
+        Command::Migrate { lenient } => {
+            let migration = todo_core::task_api::migrate_store(lenient)?;
+            for coercion in &migration.coercions {
+                println!("Coerced {coercion}");
+            }
+            let latest = todo_core::storage::json_store::SCHEMA_VERSION;
+            match &migration.original {
+                Some(original) => println!(
+                    "Migrated the store from schema_version {} to {latest}; the old file is kept at {}",
+                    migration.from_version,
+                    original.display()
+                ),
+                None => {
+                    println!("The store already uses schema_version {latest}; nothing to migrate.")
                 }
             }
         }
-        Command::List { list } => match list {
-            ListCommand::Today => {
-                let result = todo_core::task_api::list_today_with_focus()?;
-                if cli.json {
-                    print_tasks_json(&result.tasks)?;
+        Command::Info => {
+            let info = todo_core::task_api::store_info()?;
+            if cli.json {
+                print_info_json(&info, cli.envelope);
+            } else {
+                print_info_plain(&info, palette);
+            }
+        }
+        Command::Doctor { fix_permissions } => {
+            let issues = if fix_permissions {
+                let repair = todo_core::task_api::fix_permissions()?;
+                for path in &repair.fixed {
+                    println!("Fixed permissions: {}", path.display());
+                }
+                repair.remaining
+            } else {
+                todo_core::task_api::permission_issues()?
+            };
+            if issues.is_empty() {
+                println!("No problems found.");
+            } else {
+                for issue in &issues {
+                    println!("{issue}");
+                }
+                let next = if fix_permissions {
+                    "only the owner of those paths can fix them"
                 } else {
-                    print_tasks_plain(&result.tasks, result.focused_task_id.as_deref(), palette)?;
+                    "run `todo doctor --fix-permissions`"
+                };
+                let noun = if issues.len() == 1 {
+                    "problem"
+                } else {
+                    "problems"
+                };
+                return Err(AppError::invalid_data(format!(
+                    "{} {noun} found; {next}",
+                    issues.len()
+                )));
+            }
+        }
+        Command::Secret { action } => match action {
+            SecretCommand::Set { name } => {
+                todo_core::secrets::validate_name(&name)?;
+                if io::stdin().is_terminal() {
+                    eprintln!("Enter the value for {name} and press Enter:");
                 }
+                let mut value = String::new();
+                io::stdin()
+                    .lock()
+                    .read_line(&mut value)
+                    .map_err(|err| AppError::io(err.to_string()))?;
+                let value = value.trim();
+                if value.is_empty() {
+                    return Err(AppError::invalid_input("secret values cannot be empty"));
+                }
+                todo_core::secrets::Keyring.set(&name, value)?;
+                println!("Saved secret {name} to the OS keyring.");
             }
-            ListCommand::Backlog => {
-                let tasks = todo_core::task_api::list_backlog()?;
-                if cli.json {
-                    print_tasks_json(&tasks)?;
+            SecretCommand::Delete { name } => {
+                todo_core::secrets::validate_name(&name)?;
+                if todo_core::secrets::Keyring.delete(&name)? {
+                    println!("Deleted secret {name}.");
                 } else {
-                    print_tasks_plain(&tasks, None, palette)?;
+                    println!(
+                        "{}",
+                        palette.mutedize(&format!("No secret named {name} in the OS keyring."))
+                    );
                 }
             }
+            SecretCommand::Check { name } => {
+                todo_core::secrets::resolve(&todo_core::secrets::Keyring, &name)?;
+                println!("Secret {name} is available.");
+            }
         },
+        Command::Flush => {
+            if todo_core::task_api::flush_store()? {
+                println!("Saved pending changes to the store.");
+            } else {
+                println!("{}", palette.mutedize("Nothing to save."));
+            }
+        }
+        Command::Digest { period, format } => {
+            let digest = todo_core::task_api::digest(period.into())?;
+            let rendered = match format {
+                DigestFormat::Md => todo_core::export::digest::render_markdown(&digest)?,
+                DigestFormat::Email => todo_core::export::digest::render_email(&digest)?,
+            };
+            print!("{rendered}");
+        }
+        Command::Context { action } => {
+            let (contexts, message) = match action.unwrap_or(ContextCommand::List) {
+                ContextCommand::List => (todo_core::task_api::list_contexts()?, None),
+                ContextCommand::Define { name, filter } => (
+                    todo_core::task_api::define_context(&name, &filter)?,
+                    Some(format!("Defined context: {}", name.trim())),
+                ),
+                ContextCommand::Delete { name } => (
+                    todo_core::task_api::delete_context(&name)?,
+                    Some(format!("Deleted context: {}", name.trim())),
+                ),
+                ContextCommand::Set { name } => (
+                    todo_core::task_api::set_context(Some(&name))?,
+                    Some(format!("Context set: {}", name.trim())),
+                ),
+                ContextCommand::None => (
+                    todo_core::task_api::set_context(None)?,
+                    Some("Context cleared".to_string()),
+                ),
+            };
+            if cli.json {
+                print_contexts_json(&contexts, cli.envelope);
+            } else if let Some(message) = message {
+                println!("{}", palette.accentize(&message));
+            } else {
+                print_contexts_plain(&contexts, palette);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Best effort: a broken store is reported by the command itself, not by the hint.
+fn print_stale_notification_hint(config: &Config) {
+    let warning = match todo_core::task_api::stale_notification_warning(
+        &config.notifications,
+        config.overdue_grace,
+    ) {
+        Ok(Some(warning)) => warning,
+        _ => return,
+    };
+    let noun = if warning.overdue_urgent_count == 1 {
+        "urgent task is"
+    } else {
+        "urgent tasks are"
+    };
+    eprintln!(
+        "hint: {} {noun} overdue and no notification was sent in the last {}h; run `todo notify` or automate it with `todo notify install-schedule`",
+        warning.overdue_urgent_count, config.notifications.stale_hint_hours
+    );
+}
+
+const CHANGES_BANNER_LINES: usize = 5;
+
+fn change_label(change: ActivityChange) -> &'static str {
+    match change {
+        ActivityChange::Added => "added",
+        ActivityChange::Updated => "updated",
+        ActivityChange::Completed => "completed",
+        ActivityChange::Reopened => "reopened",
+        ActivityChange::Cancelled => "cancelled",
+        ActivityChange::Removed => "removed",
+    }
+}
+
+// Best effort, like the hint above: only people sharing a store ever see this.
+fn print_changes_by_others(palette: &Palette) {
+    let changes: Vec<ActivityEntry> = match todo_core::task_api::changes_by_others() {
+        Ok(changes) if !changes.is_empty() => changes,
+        _ => return,
+    };
+    let mut people: Vec<&str> = Vec::new();
+    for change in &changes {
+        if !people.contains(&change.by.as_str()) {
+            people.push(&change.by);
+        }
+    }
+    let noun = if changes.len() == 1 {
+        "change"
+    } else {
+        "changes"
+    };
+    eprintln!(
+        "Since you last looked: {} {noun} by {}",
+        changes.len(),
+        people.join(", ")
+    );
+    // Newest first, so the cap hides the oldest changes.
+    for change in changes.iter().rev().take(CHANGES_BANNER_LINES) {
+        eprintln!(
+            "  {} {} #{} {}",
+            change.by,
+            change_label(change.change),
+            change.task_id,
+            change.title
+        );
+    }
+    if changes.len() > CHANGES_BANNER_LINES {
+        let ellipsis = if palette.accessible { "" } else { "… " };
+        eprintln!(
+            "  {ellipsis}and {} more",
+            changes.len() - CHANGES_BANNER_LINES
+        );
+    }
+}
+
+/// Start a detached `webhooks deliver` when the command queued new deliveries, so a slow or
+/// unreachable webhook never holds up the prompt. Earlier failures wait for the next change or
+/// an explicit `webhooks deliver`. The command's changes are already saved by then, so a
+/// delivery that cannot start is only a warning.
+fn deliver_webhooks_in_background(config: &Config) {
+    if config.webhooks.is_empty() || std::env::var_os(DISABLE_WEBHOOKS_ENV).is_some() {
+        return;
+    }
+    match todo_core::task_api::queued_webhooks() {
+        Ok(queued) if queued.iter().any(|delivery| delivery.attempts == 0) => {}
+        Ok(_) => return,
+        Err(err) => return warn(format!("webhooks were not delivered: {err}")),
+    }
+    let spawned = (|| -> Result<(), AppError> {
+        let exe = std::env::current_exe().map_err(|err| AppError::io(err.to_string()))?;
+        std::process::Command::new(exe)
+            .args(["webhooks", "deliver"])
+            .env(
+                "TODOAPP_STORE_PATH",
+                todo_core::storage::json_store::store_path()?,
+            )
+            .env("TODOAPP_CONFIG_PATH", todo_core::config::config_path()?)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|err| AppError::io(format!("failed to start webhooks deliver: {err}")))?;
+        Ok(())
+    })();
+    if let Err(err) = spawned {
+        warn(format!("webhooks were not delivered: {err}"));
+    }
+}
+
+// Best effort, like the hint above: retention rules never make the command itself fail.
+fn run_startup_maintenance(config: &Config) {
+    match todo_core::task_api::maintain_if_due(&config.retention) {
+        Ok(Some(maintenance))
+            if !maintenance.archived.is_empty() || !maintenance.purged.is_empty() =>
+        {
+            eprintln!(
+                "Retention: archived {} and purged {} tasks (retention.on_startup)",
+                maintenance.archived.len(),
+                maintenance.purged.len()
+            );
+        }
+        Ok(_) => {}
+        Err(err) => warn(format!("retention rules were not applied: {}", err)),
+    }
+    if config.routines.is_empty() {
+        return;
+    }
+    match todo_core::task_api::archive_expired_routines() {
+        Ok(archived) if !archived.is_empty() => {
+            let noun = if archived.len() == 1 { "task" } else { "tasks" };
+            eprintln!(
+                "Routines: archived {} {noun} from earlier days",
+                archived.len()
+            );
+        }
+        Ok(_) => {}
+        Err(err) => warn(format!("routine tasks were not archived: {}", err)),
+    }
+}
+
+// Best effort, like the other startup checks: `doctor` is where a failed check gets reported.
+fn warn_about_permissions(config: &Config) {
+    if config.allow_shared_store {
+        return;
+    }
+    if let Ok(issues) = todo_core::task_api::permission_issues() {
+        for issue in issues {
+            warn(format!("{issue}; see `todo doctor`"));
+        }
     }
+}
 
-    Ok(())
+/// Interactive mode; with `record`, each line typed and its output are written there as well.
+fn run_interactive(
+    config: &Config,
+    palette: &Palette,
+    record: Option<&Path>,
+) -> Result<(), AppError> {
+    let mut session = Session::default();
+    let mut recorder = record.map(Recorder::create).transpose()?;
+    let mut autosave = Autosave::start(config.interactive.autosave_secs);
+    let result = if io::stdin().is_terminal() {
+        run_interactive_terminal(config, palette, &mut session, &mut autosave, &mut recorder)
+    } else {
+        run_interactive_piped(config, palette, &mut session, &mut autosave, &mut recorder)
+    };
+    // Save on exit, even when reading input failed.
+    let saved = autosave.finish();
+    result.and(saved)
 }
 
-fn run_interactive(config: &Config, palette: &Palette) -> Result<(), AppError> {
+fn run_interactive_piped(
+    config: &Config,
+    palette: &Palette,
+    session: &mut Session,
+    autosave: &mut Autosave,
+    recorder: &mut Option<Recorder>,
+) -> Result<(), AppError> {
     let mut input = String::new();
     let stdin = io::stdin();
     let mut stdin_lock = stdin.lock();
@@ -493,62 +4197,417 @@ fn run_interactive(config: &Config, palette: &Palette) -> Result<(), AppError> {
             break;
         }
 
-        let line = input.trim();
-        if line.is_empty() {
-            continue;
+        if !run_interactive_line(input.trim(), config, palette, session, recorder.as_mut()) {
+            break;
         }
+        autosave_after_command(autosave);
+    }
 
-        if line.eq_ignore_ascii_case("exit") || line.eq_ignore_ascii_case("quit") {
-            break;
+    Ok(())
+}
+
+/// Ask on stderr and read a line from stdin, trimmed. Fails with `unanswered` when stdin ends
+/// first.
+fn ask_line(question: &str, unanswered: &str) -> Result<String, AppError> {
+    eprint!("{question}");
+    io::stderr()
+        .flush()
+        .map_err(|err| AppError::io(err.to_string()))?;
+    let mut answer = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|err| AppError::io(err.to_string()))?;
+    if read == 0 {
+        return Err(AppError::invalid_input(unanswered));
+    }
+    Ok(answer.trim().to_string())
+}
+
+/// Empty takes `default`.
+fn ask_yes_no(question: &str, default: bool) -> Result<bool, AppError> {
+    loop {
+        match ask_line(question, SETUP_UNANSWERED)?
+            .to_lowercase()
+            .as_str()
+        {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => eprintln!("Answer y or n."),
         }
+    }
+}
 
-        if line == "help" || line == "?" {
-            print_help();
-            continue;
+const SETUP_UNANSWERED: &str = "no answer given; nothing was set up";
+const SAMPLE_TASK_TITLE: &str = "Look around, then mark this task done";
+
+/// What `init` wrote and added.
+struct InitReport {
+    config_path: PathBuf,
+    store_path: PathBuf,
+    setup: Setup,
+    sample_task: Option<Task>,
+}
+
+/// `init`'s questions, with the default answer in brackets. The store location is only asked
+/// when `--profile` or `TODOAPP_STORE_PATH` has not already decided it.
+fn ask_setup() -> Result<(Setup, bool), AppError> {
+    let mut setup = Setup::default();
+    eprintln!("Setting up todo_opt. Press Enter to take the answer in brackets.");
+    let themes = THEMES.join(", ");
+    setup.theme = loop {
+        let question = format!("Theme ({themes}) [{}]: ", setup.theme);
+        let answer = ask_line(&question, SETUP_UNANSWERED)?;
+        if answer.is_empty() {
+            break setup.theme;
+        }
+        match canonical_theme_name(&answer) {
+            Some(name) if THEMES.contains(&name.as_str()) => break name,
+            _ => eprintln!("Answer one of {themes}."),
+        }
+    };
+    setup.notifications = ask_yes_no("Notify you about overdue and urgent tasks? [Y/n] ", true)?;
+    let default_store = json_store::default_store_path()?;
+    if json_store::store_path()? == default_store {
+        let question = format!("Where should tasks be kept? [{}] ", default_store.display());
+        let answer = ask_line(&question, SETUP_UNANSWERED)?;
+        if !answer.is_empty() {
+            let path = absolute_path(&answer)?;
+            setup.store_path = (path != default_store).then_some(path);
         }
+    }
+    let sample_task = ask_yes_no("Add a sample task to start with? [Y/n] ", true)?;
+    Ok((setup, sample_task))
+}
 
-        let args = match parse_command_line(line) {
-            Ok(args) => args,
-            Err(err) => {
-                eprintln!("ERROR: {}", err);
-                continue;
-            }
-        };
+/// `answer` as an absolute path, reading a leading `~` as the home folder.
+fn absolute_path(answer: &str) -> Result<PathBuf, AppError> {
+    let home = std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" });
+    let rest = answer
+        .strip_prefix("~/")
+        .or_else(|| answer.strip_prefix("~\\"));
+    let path = match (rest, home) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(answer),
+    };
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    let cwd = std::env::current_dir().map_err(|err| AppError::io(err.to_string()))?;
+    Ok(cwd.join(path))
+}
 
-        if args.is_empty() {
-            continue;
+/// Write the config for `setup`, point this process at the store it chose, and add the sample
+/// task when the store is new.
+fn set_up(setup: &Setup, sample_task: bool) -> Result<InitReport, AppError> {
+    let config_path = todo_core::config::config_path()?;
+    todo_core::config::write_setup(&config_path, setup)?;
+    if let Some(path) = &setup.store_path {
+        json_store::set_configured_store_path(path.clone())?;
+    }
+    let store_path = json_store::store_path()?;
+    let sample_task = if sample_task && !store_path.exists() {
+        Some(todo_core::task_api::add_task(SAMPLE_TASK_TITLE)?)
+    } else {
+        None
+    };
+    Ok(InitReport {
+        config_path,
+        store_path,
+        setup: setup.clone(),
+        sample_task,
+    })
+}
+
+fn print_init_plain(report: &InitReport, palette: &Palette) {
+    println!("Wrote {}", report.config_path.display());
+    println!("  Theme: {}", report.setup.theme);
+    if report.setup.notifications {
+        println!(
+            "  Notifications: on; `todo notify install-schedule` checks for due tasks in the background"
+        );
+    } else {
+        println!("  Notifications: off");
+    }
+    println!("  Tasks: {}", report.store_path.display());
+    if let Some(task) = &report.sample_task {
+        println!(
+            "Added a sample task: {} ({})",
+            palette.accentize(&task.title),
+            task.id
+        );
+    }
+}
+
+/// A first run in a terminal: neither a config nor a store yet, and a command other than
+/// `init` or `demo`.
+fn offers_first_run_setup(cli: &Cli) -> bool {
+    !matches!(cli.command, Command::Init { .. } | Command::Demo { .. })
+        && !cli.json
+        && io::stdin().is_terminal()
+        && io::stderr().is_terminal()
+        && todo_core::config::config_path().is_ok_and(|path| !path.exists())
+        && json_store::store_path().is_ok_and(|path| !path.exists())
+}
+
+/// Offer `init` before the command runs; declining changes nothing, so the offer comes again
+/// until a config or store exists.
+fn offer_first_run_setup(palette: &Palette) -> Result<(), AppError> {
+    if !ask_yes_no("No config or tasks yet. Set up todo_opt now? [Y/n] ", true)? {
+        eprintln!("Skipped; run `todo init` to set up later.");
+        return Ok(());
+    }
+    let (setup, sample_task) = ask_setup()?;
+    print_init_plain(&set_up(&setup, sample_task)?, palette);
+    Ok(())
+}
+
+/// Interactive mode on a terminal, with line editing, history, and tab completion of command
+/// names and pending task IDs.
+fn run_interactive_terminal(
+    config: &Config,
+    palette: &Palette,
+    session: &mut Session,
+    autosave: &mut Autosave,
+    recorder: &mut Option<Recorder>,
+) -> Result<(), AppError> {
+    let editor_config = rustyline::Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut editor: Editor<ReplHelper, DefaultHistory> =
+        Editor::with_config(editor_config).map_err(|err| AppError::io(err.to_string()))?;
+    editor.set_helper(Some(ReplHelper::new(&config.aliases)));
+
+    loop {
+        match editor.readline("") {
+            Ok(line) => {
+                editor.add_history_entry(line.as_str()).ok();
+                if !run_interactive_line(line.trim(), config, palette, session, recorder.as_mut()) {
+                    break;
+                }
+                autosave_after_command(autosave);
+            }
+            // Ctrl-C abandons the current line, as in a shell.
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(AppError::io(err.to_string())),
         }
+    }
 
-        let args = match resolve_aliases(args, config) {
-            Ok(resolved) => resolved,
-            Err(err) => {
-                eprintln!("ERROR: {}", err);
-                continue;
+    Ok(())
+}
+
+// A failed save leaves the changes in the journal, so the session carries on.
+fn autosave_after_command(autosave: &mut Autosave) {
+    if let Err(err) = autosave.tick() {
+        warn(format!("autosave failed: {}", err));
+    }
+}
+
+/// Runs one line of interactive input; returns `false` once the session should end. With
+/// `recorder`, a command runs in a child process so its output can be written to the
+/// transcript too.
+fn run_interactive_line(
+    line: &str,
+    config: &Config,
+    palette: &Palette,
+    session: &mut Session,
+    recorder: Option<&mut Recorder>,
+) -> bool {
+    if line.is_empty() {
+        return true;
+    }
+
+    if line.eq_ignore_ascii_case("exit") || line.eq_ignore_ascii_case("quit") {
+        return false;
+    }
+
+    if line == "help" || line == "?" {
+        print_help();
+        return true;
+    }
+
+    let args = match interactive_args(line, config, palette, session) {
+        Ok(Some(args)) => args,
+        // `set` and `unset` are recorded too, since later lines depend on them.
+        Ok(None) => {
+            if let Some(recorder) = recorder {
+                record_step(recorder, line, None);
             }
-        };
-        let mut argv = Vec::with_capacity(args.len() + 1);
-        argv.push("todo".to_string());
-        argv.extend(args);
+            return true;
+        }
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            return true;
+        }
+    };
+    if let Some(recorder) = recorder {
+        record_step(recorder, line, Some(&args));
+        return true;
+    }
+    let mut argv = Vec::with_capacity(args.len() + 1);
+    argv.push("todo".to_string());
+    argv.extend(args);
 
-        let cli = match Cli::try_parse_from(argv) {
-            Ok(cli) => cli,
-            Err(err) => {
-                use clap::error::ErrorKind;
-                match err.kind() {
-                    ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
-                        print!("{}", err);
-                        continue;
-                    }
-                    _ => {
-                        eprintln!("ERROR: {}", normalize_parse_error(err));
-                        continue;
-                    }
+    let cli = match Cli::try_parse_from(&argv) {
+        Ok(cli) => cli,
+        Err(err) => {
+            use clap::error::ErrorKind;
+            match err.kind() {
+                ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
+                    print!("{}", err);
+                    return true;
+                }
+                _ => {
+                    eprintln!("ERROR: {}", command_parse_error(err, &argv, config));
+                    return true;
                 }
             }
+        }
+    };
+
+    let envelope_errors = wants_error_envelope(&cli);
+    let usage = owned_usage_entry(&cli);
+    match run_command(cli, config, palette) {
+        Ok(()) => record_usage(usage, config),
+        Err(err) => report_command_error(&err, envelope_errors),
+    }
+
+    true
+}
+
+/// The args a line of interactive input runs with, after aliases and session variables.
+/// `None` for a blank line, or for `set` and `unset`, which are applied to `session` here.
+fn interactive_args(
+    line: &str,
+    config: &Config,
+    palette: &Palette,
+    session: &mut Session,
+) -> Result<Option<Vec<String>>, AppError> {
+    let args = parse_command_line(line)?;
+    if args.is_empty() {
+        return Ok(None);
+    }
+    if matches!(args[0].as_str(), "set" | "unset") {
+        run_session_command(&args, session, palette)?;
+        return Ok(None);
+    }
+    let mut args = resolve_aliases(args, config)?;
+    session.apply(&mut args);
+    Ok(Some(args))
+}
+
+/// Writes `line` to the transcript, first running `args` in a child process and passing on
+/// what it prints.
+fn record_step(recorder: &mut Recorder, line: &str, args: Option<&[String]>) {
+    let recorded = transcript::timestamp().and_then(|at| {
+        let (stdout, stderr) = match args {
+            Some(args) => transcript::run_command(args, &at)?,
+            None => (String::new(), String::new()),
         };
+        print!("{stdout}");
+        eprint!("{stderr}");
+        recorder.record(&Step {
+            at,
+            input: line.to_string(),
+            stdout,
+            stderr,
+        })
+    });
+    if let Err(err) = recorded {
+        warn(format!("line not recorded: {err}"));
+    }
+}
 
-        if let Err(err) = run_command(cli, palette) {
-            eprintln!("ERROR: {}", err);
+/// `todo run FILE`: each line as if typed in interactive mode. Blank lines and lines starting
+/// with `#` are skipped.
+fn run_script(path: &Path, config: &Config, palette: &Palette) -> Result<(), AppError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| AppError::io(format!("{}: {err}", path.display())))?;
+    let mut session = Session::default();
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        if !run_interactive_line(line, config, palette, &mut session, None) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// `todo run FILE --replay`: each command of a transcript again, pinned to the time it was
+/// recorded, failing if any prints something different.
+fn replay_transcript(path: &Path, config: &Config, palette: &Palette) -> Result<(), AppError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| AppError::io(format!("{}: {err}", path.display())))?;
+    let mut session = Session::default();
+    let (mut replayed, mut differing) = (0, 0);
+    for (index, step) in transcript::parse(&text)?.iter().enumerate() {
+        println!("{}", palette.mutedize(&format!("> {}", step.input)));
+        let Some(args) = interactive_args(&step.input, config, palette, &mut session)? else {
+            continue;
+        };
+        let (stdout, stderr) = transcript::run_command(&args, &step.at)?;
+        print!("{stdout}");
+        eprint!("{stderr}");
+        replayed += 1;
+        if !stdout.lines().eq(step.stdout.lines()) || !stderr.lines().eq(step.stderr.lines()) {
+            differing += 1;
+            warn(format!(
+                "step {} (`{}`) printed something different than when recorded",
+                index + 1,
+                step.input
+            ));
+        }
+    }
+    if differing > 0 {
+        return Err(AppError::invalid_data(format!(
+            "{differing} of {replayed} replayed commands printed something different than when recorded"
+        )));
+    }
+    println!("Replayed {replayed} commands; all printed what they did when recorded.");
+    Ok(())
+}
+
+/// `set`, `set NAME VALUE`, or `unset NAME` inside interactive mode.
+fn run_session_command(
+    args: &[String],
+    session: &mut Session,
+    palette: &Palette,
+) -> Result<(), AppError> {
+    match args {
+        [command] if command == "set" => {
+            let mut any = false;
+            for (var, value) in session.values() {
+                println!("{:<8} {}", var.name(), palette.accentize(value));
+                any = true;
+            }
+            if !any {
+                println!("No session variables set.");
+            }
+        }
+        [command, name, value] if command == "set" => {
+            let var = session.set(name, value)?;
+            println!(
+                "Session {}: {} (applies to add and list)",
+                var.name(),
+                palette.accentize(value.trim())
+            );
+        }
+        [command, name] if command == "unset" => {
+            let var = SessionVar::parse(name)?;
+            if session.unset(name)? {
+                println!("Cleared session {}.", var.name());
+            } else {
+                println!("Session {} was not set.", var.name());
+            }
+        }
+        _ => {
+            return Err(AppError::invalid_input(
+                "usage: set [NAME VALUE] | unset NAME",
+            ));
         }
     }
 
@@ -556,9 +4615,10 @@ fn run_interactive(config: &Config, palette: &Palette) -> Result<(), AppError> {
 }
 
 fn main() {
-    let config_load = todo_core::config::load_config_with_fallback();
-    if let Some(err) = config_load.error.as_ref() {
+    todo_cli::crash::install_panic_hook();
+    if let Err(err) = todo_core::clock::pin_from_env() {
         eprintln!("ERROR: {}", err);
+        std::process::exit(1);
     }
 
     let raw_args: Vec<String> = std::env::args_os()
@@ -566,7 +4626,32 @@ fn main() {
         .map(|arg| arg.to_string_lossy().into_owned())
         .collect();
 
-    let (filtered_args, override_entries, override_tokens) =
+    // `--config` and `--profile` choose which config to load, so they are applied first.
+    let (raw_args, locations, location_tokens) = match extract_location_args(&raw_args) {
+        Ok(tuple) => tuple,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = check_locations_allowed(&locations) {
+        report_command_error(&err, false);
+        std::process::exit(1);
+    }
+    if let Err(err) = apply_locations(locations) {
+        eprintln!("ERROR: {}", err);
+        std::process::exit(1);
+    }
+
+    let config_load = todo_core::config::load_config_with_fallback();
+    if let Some(err) = config_load.error.as_ref() {
+        warn(format!("{}; using the default config", err.message()));
+    }
+    for warning in &config_load.warnings {
+        warn(warning);
+    }
+
+    let (filtered_args, override_entries, mut override_tokens) =
         match extract_config_override_args(&raw_args) {
             Ok(tuple) => tuple,
             Err(err) => {
@@ -574,18 +4659,48 @@ fn main() {
                 std::process::exit(1);
             }
         };
+    override_tokens.extend(location_tokens);
 
+    if let Err(err) = check_allowed_overrides(
+        &override_entries,
+        config_load.config.allow_overrides.as_deref(),
+    ) {
+        report_command_error(&err, false);
+        std::process::exit(1);
+    }
     let overrides = build_config_overrides(&override_entries);
     let effective_config = merge_overrides(&config_load.config, &overrides);
-    let palette = palette_for_theme(effective_config.theme.as_deref());
+    let palette = if effective_config.accessible {
+        Palette::for_screen_readers()
+    } else {
+        palette_for_theme(effective_config.theme.as_deref())
+    };
 
     if let Err(err) = validate_alias_definitions(&effective_config.aliases) {
         eprintln!("ERROR: {}", err);
         std::process::exit(1);
     }
+    if let Some(path) = effective_config.store_path.clone()
+        && let Err(err) = json_store::set_configured_store_path(path)
+    {
+        eprintln!("ERROR: {}", err);
+        std::process::exit(1);
+    }
+
+    let (filtered_args, record) = match extract_record_arg(filtered_args) {
+        Ok(tuple) => tuple,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            std::process::exit(1);
+        }
+    };
 
     if filtered_args.is_empty() {
-        if let Err(err) = run_interactive(&effective_config, &palette) {
+        warn_about_permissions(&effective_config);
+        print_changes_by_others(&palette);
+        run_startup_maintenance(&effective_config);
+        print_stale_notification_hint(&effective_config);
+        if let Err(err) = run_interactive(&effective_config, &palette, record.as_deref()) {
             eprintln!("ERROR: {}", err);
             std::process::exit(1);
         }
@@ -611,7 +4726,7 @@ fn main() {
     cli_argv.extend(override_tokens.iter().cloned());
     cli_argv.extend(parsed_args[insert_index..].iter().cloned());
 
-    let cli = match Cli::try_parse_from(cli_argv) {
+    let cli = match Cli::try_parse_from(&cli_argv) {
         Ok(cli) => cli,
         Err(err) => {
             use clap::error::ErrorKind;
@@ -621,22 +4736,83 @@ fn main() {
                     std::process::exit(0);
                 }
                 _ => {
-                    eprintln!("ERROR: {}", normalize_parse_error(err));
+                    eprintln!(
+                        "ERROR: {}",
+                        command_parse_error(err, &cli_argv, &effective_config)
+                    );
                     std::process::exit(1);
                 }
             }
         }
     };
 
-    if let Err(err) = run_command(cli, &palette) {
-        eprintln!("ERROR: {}", err);
+    let palette = if cli.accessible {
+        Palette::for_screen_readers()
+    } else {
+        palette
+    };
+    let envelope_errors = wants_error_envelope(&cli);
+    let strict = cli.strict;
+    if !matches!(cli.command, Command::Doctor { .. }) {
+        warn_about_permissions(&effective_config);
+    }
+    // Checked before anything runs, so a script stops before touching a degraded store.
+    if strict {
+        match todo_core::task_api::store_warnings() {
+            Ok(warnings) => warnings.iter().for_each(warn),
+            Err(err) => {
+                report_command_error(&err, envelope_errors);
+                std::process::exit(1);
+            }
+        }
+        if let Some(err) = strict_failure() {
+            report_command_error(&err, envelope_errors);
+            std::process::exit(1);
+        }
+    }
+    // Before anything commits, so the startup maintenance below queues for these too.
+    if let Err(err) = todo_core::task_api::subscribe_webhooks(&effective_config.webhooks) {
+        report_command_error(&err, envelope_errors);
+        std::process::exit(1);
+    }
+
+    if !matches!(cli.command, Command::Demo { .. }) {
+        print_changes_by_others(&palette);
+    }
+    if !matches!(cli.command, Command::Maintain | Command::Demo { .. }) {
+        run_startup_maintenance(&effective_config);
+    }
+    if !matches!(cli.command, Command::Notify { .. } | Command::Demo { .. }) {
+        print_stale_notification_hint(&effective_config);
+    }
+
+    if offers_first_run_setup(&cli)
+        && let Err(err) = offer_first_run_setup(&palette)
+    {
+        report_command_error(&err, envelope_errors);
+        std::process::exit(1);
+    }
+    let usage = owned_usage_entry(&cli);
+    let delivers_webhooks = !matches!(cli.command, Command::Webhooks { .. });
+    if let Err(err) = run_command(cli, &effective_config, &palette) {
+        report_command_error(&err, envelope_errors);
+        std::process::exit(1);
+    }
+    record_usage(usage, &effective_config);
+    if delivers_webhooks {
+        deliver_webhooks_in_background(&effective_config);
+    }
+    // Warnings printed while the command ran; its changes are kept, but the exit status is not 0.
+    if strict && let Some(err) = strict_failure() {
+        report_command_error(&err, envelope_errors);
         std::process::exit(1);
     }
 }
 
-fn extract_config_override_args(
-    raw_args: &[String],
-) -> Result<(Vec<String>, Vec<ParsedConfigOverride>, Vec<String>), AppError> {
+/// Remaining args, parsed overrides, and the raw override tokens to re-inject for clap.
+type ExtractedOverrides = (Vec<String>, Vec<ParsedConfigOverride>, Vec<String>);
+
+fn extract_config_override_args(raw_args: &[String]) -> Result<ExtractedOverrides, AppError> {
     let mut filtered = Vec::new();
     let mut overrides = Vec::new();
     let mut tokens = Vec::new();
@@ -668,6 +4844,167 @@ fn extract_config_override_args(
     Ok((filtered, overrides, tokens))
 }
 
+/// The remaining args and the `--record` path. Recording only applies to an interactive
+/// session, so the path comes with no other args.
+fn extract_record_arg(args: Vec<String>) -> Result<(Vec<String>, Option<PathBuf>), AppError> {
+    let end = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let prefix = format!("{RECORD_FLAG}=");
+    let Some(index) = args[..end]
+        .iter()
+        .position(|arg| arg == RECORD_FLAG || arg.starts_with(&prefix))
+    else {
+        return Ok((args, None));
+    };
+    let mut rest = args;
+    let flag = rest.remove(index);
+    let path = match flag.strip_prefix(&prefix) {
+        Some(value) => value.to_string(),
+        None if index < rest.len() => rest.remove(index),
+        None => {
+            return Err(AppError::invalid_input(format!(
+                "missing value for {RECORD_FLAG}"
+            )));
+        }
+    };
+    if !rest.is_empty() {
+        return Err(AppError::invalid_input(format!(
+            "{RECORD_FLAG} records an interactive session; leave out the command"
+        )));
+    }
+    Ok((rest, Some(PathBuf::from(path))))
+}
+
+/// Values of `--config` and `--profile`, in that order.
+type Locations = (Option<PathBuf>, Option<String>);
+
+/// Remaining args, the `--config` and `--profile` values, and their raw tokens to re-inject for clap.
+fn extract_location_args(
+    raw_args: &[String],
+) -> Result<(Vec<String>, Locations, Vec<String>), AppError> {
+    let mut filtered = Vec::new();
+    let (mut config, mut profile) = (None, None);
+    let mut tokens = Vec::new();
+    let mut iter = raw_args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            filtered.push(arg.clone());
+            filtered.extend(iter.by_ref().cloned());
+            break;
+        }
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) if flag == CONFIG_FLAG || flag == PROFILE_FLAG => {
+                (flag, value.to_string())
+            }
+            _ if arg == CONFIG_FLAG || arg == PROFILE_FLAG => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| AppError::invalid_input(format!("missing value for {arg}")))?;
+                (arg.as_str(), value.clone())
+            }
+            _ => {
+                filtered.push(arg.clone());
+                continue;
+            }
+        };
+        if flag == CONFIG_FLAG {
+            config = Some(PathBuf::from(&value));
+        } else {
+            profile = Some(value.clone());
+        }
+        tokens.push(format!("{flag}={value}"));
+    }
+
+    Ok((filtered, (config, profile), tokens))
+}
+
+/// Point the config and store paths at the profile, with `--config` taking precedence for the config.
+fn apply_locations((config, profile): Locations) -> Result<(), AppError> {
+    let mut config_path = config;
+    if let Some(name) = profile {
+        let (profile_config, store) = todo_core::config::profile_paths(&name)?;
+        todo_core::storage::json_store::override_store_path(store)?;
+        config_path.get_or_insert(profile_config);
+    }
+    if let Some(path) = config_path {
+        todo_core::config::override_config_path(path)?;
+    }
+    Ok(())
+}
+
+/// Refuse loading another config in place of one that sets `allow_overrides`, which would drop
+/// its limit along with it: `TODOAPP_CONFIG_PATH` in place of the default config, and `--config`
+/// or `--profile` in place of whichever of those two applies.
+fn check_locations_allowed((config, profile): &Locations) -> Result<(), AppError> {
+    let default = todo_core::config::default_config_path().ok();
+    let from_env =
+        todo_core::config::env_config_path().filter(|path| Some(path) != default.as_ref());
+    let flag = match (config, profile) {
+        (Some(_), _) => Some(CONFIG_FLAG),
+        (None, Some(_)) => Some(PROFILE_FLAG),
+        (None, None) => None,
+    };
+    let mut swaps = Vec::new();
+    if from_env.is_some()
+        && let Some(default) = default.clone()
+    {
+        swaps.push((todo_core::config::CONFIG_ENV_VAR, default));
+    }
+    if let Some(flag) = flag
+        && let Some(replaced) = from_env.or(default)
+    {
+        swaps.push((flag, replaced));
+    }
+    for (source, replaced) in swaps {
+        if todo_core::config::load_config_with_fallback_from_path(&replaced)
+            .config
+            .allow_overrides
+            .is_some()
+        {
+            return Err(AppError::invalid_input(format!(
+                "{source} cannot load another config here"
+            ))
+            .with_hint("allow_overrides in the config also keeps it from being swapped"));
+        }
+    }
+    Ok(())
+}
+
+/// Refuse overrides of fields the config's `allow_overrides` leaves out.
+fn check_allowed_overrides(
+    entries: &[ParsedConfigOverride],
+    allowed: Option<&[String]>,
+) -> Result<(), AppError> {
+    let Some(allowed) = allowed else {
+        return Ok(());
+    };
+    for entry in entries {
+        let field = entry.target.field();
+        if allowed
+            .iter()
+            .any(|name| name.trim().eq_ignore_ascii_case(field))
+        {
+            continue;
+        }
+        let hint = if allowed.is_empty() {
+            "allow_overrides in the config allows no overrides".to_string()
+        } else {
+            format!(
+                "allow_overrides in the config allows: {}",
+                allowed.join(", ")
+            )
+        };
+        return Err(AppError::invalid_input(format!(
+            "{CONFIG_OVERRIDE_FLAG} cannot change '{field}' here"
+        ))
+        .with_hint(hint));
+    }
+    Ok(())
+}
+
 fn build_config_overrides(entries: &[ParsedConfigOverride]) -> ConfigOverrides {
     let mut overrides = ConfigOverrides::default();
     for entry in entries {
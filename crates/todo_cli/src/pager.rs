@@ -0,0 +1,407 @@
+//! The built-in pager for `list`, `search`, and `show` output taller than the terminal, turned
+//! on with `pager` in the config. It draws on the alternate screen, so quitting leaves the
+//! terminal as it was, and needs no `$PAGER`.
+//!
+//! Keys: `q` quits; `j`/`k` or the arrow keys move a line, space/`b` or Page Down/Up a screen,
+//! `g`/`G` to the top and bottom; `/` searches, case-insensitively, and `n`/`N` find the next
+//! and previous match.
+
+use crate::layout;
+use todo_core::error::AppError;
+use todo_core::text;
+
+/// Runs `run` with stdout captured, then shows what it printed: in the pager when it's taller
+/// than the terminal, as usual otherwise. Both stdin and stdout must be a terminal to page.
+#[cfg(unix)]
+pub fn page<T>(run: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
+    use std::io::{IsTerminal, Write};
+
+    let (result, output) = terminal::capture(run)?;
+    let height =
+        terminal_size::terminal_size().map(|(_, terminal_size::Height(rows))| usize::from(rows));
+    match height {
+        Some(height) if output.lines().count() >= height && std::io::stdin().is_terminal() => {
+            terminal::show(View::new(&output, height.saturating_sub(1)))?;
+        }
+        _ => {
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(output.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|err| AppError::io(err.to_string()))?;
+        }
+    }
+    result
+}
+
+/// Without a way to capture stdout, output goes straight to the terminal.
+#[cfg(not(unix))]
+pub fn page<T>(run: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
+    run()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Quit,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+    Search,
+    Next,
+    Previous,
+}
+
+/// The captured lines and which of them are on screen.
+#[derive(Debug)]
+struct View {
+    lines: Vec<String>,
+    /// Each line without colors and case-folded, for searching.
+    folded: Vec<String>,
+    top: usize,
+    height: usize,
+    query: Option<String>,
+    /// The line of the last match; `n` and `N` search on from it.
+    matched: Option<usize>,
+}
+
+impl View {
+    fn new(output: &str, height: usize) -> Self {
+        let lines: Vec<String> = output.lines().map(str::to_string).collect();
+        let folded = lines
+            .iter()
+            .map(|line| text::fold_case(&layout::strip_styles(line)))
+            .collect();
+        Self {
+            lines,
+            folded,
+            top: 0,
+            height: height.max(1),
+            query: None,
+            matched: None,
+        }
+    }
+
+    fn last_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.height)
+    }
+
+    fn scroll(&mut self, by: isize) {
+        self.top = self.top.saturating_add_signed(by).min(self.last_top());
+        self.matched = None;
+    }
+
+    fn apply(&mut self, key: Key) {
+        let page = self.height as isize;
+        match key {
+            Key::Down => self.scroll(1),
+            Key::Up => self.scroll(-1),
+            Key::PageDown => self.scroll(page),
+            Key::PageUp => self.scroll(-page),
+            Key::Top => self.scroll(-(self.top as isize)),
+            Key::Bottom => self.scroll(self.last_top() as isize),
+            Key::Quit | Key::Search | Key::Next | Key::Previous => {}
+        }
+    }
+
+    /// Moves to the next match of the query after the last one, or before it when not
+    /// `forward`, bringing it to the top of the screen. False when there is none.
+    fn find(&mut self, forward: bool) -> bool {
+        let Some(query) = self.query.as_deref().map(text::fold_case) else {
+            return false;
+        };
+        let from = self.matched.unwrap_or(self.top);
+        let found = if forward {
+            let start = if self.matched.is_some() {
+                from + 1
+            } else {
+                from
+            };
+            (start..self.lines.len()).find(|&index| self.folded[index].contains(&query))
+        } else {
+            (0..from)
+                .rev()
+                .find(|&index| self.folded[index].contains(&query))
+        };
+        match found {
+            Some(index) => {
+                self.top = index.min(self.last_top());
+                self.matched = Some(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn status(&self) -> String {
+        let last = (self.top + self.height).min(self.lines.len());
+        let search = if self.query.is_some() {
+            "  n/N next/previous"
+        } else {
+            ""
+        };
+        format!(
+            "lines {}-{last} of {}  q quit  / search{search}",
+            self.top + 1,
+            self.lines.len()
+        )
+    }
+}
+
+#[cfg(unix)]
+mod terminal {
+    use super::{Key, View};
+    use rustix::event::{PollFd, PollFlags, Timespec};
+    use rustix::termios::{self, OptionalActions, Termios};
+    use std::fs::{self, OpenOptions};
+    use std::io::{self, Read, Seek, SeekFrom, Write};
+    use todo_core::error::AppError;
+
+    fn io_error(err: impl std::fmt::Display) -> AppError {
+        AppError::io(format!("pager: {err}"))
+    }
+
+    /// Runs `run` with stdout going to an unlinked temporary file, and returns what it wrote.
+    pub(super) fn capture<T>(
+        run: impl FnOnce() -> Result<T, AppError>,
+    ) -> Result<(Result<T, AppError>, String), AppError> {
+        let path = std::env::temp_dir().join(format!("todo-pager-{}", std::process::id()));
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(io_error)?;
+        fs::remove_file(&path).map_err(io_error)?;
+
+        io::stdout().flush().map_err(io_error)?;
+        let terminal = rustix::io::dup(io::stdout()).map_err(io_error)?;
+        rustix::stdio::dup2_stdout(&file).map_err(io_error)?;
+        let result = run();
+        let flushed = io::stdout().flush();
+        rustix::stdio::dup2_stdout(&terminal).map_err(io_error)?;
+        flushed.map_err(io_error)?;
+
+        let mut output = String::new();
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| file.read_to_string(&mut output))
+            .map_err(io_error)?;
+        Ok((result, output))
+    }
+
+    /// Raw input and the alternate screen, for as long as it lives.
+    struct Screen {
+        saved: Termios,
+        out: io::Stdout,
+    }
+
+    impl Screen {
+        fn enter() -> Result<Self, AppError> {
+            let saved = termios::tcgetattr(io::stdin()).map_err(io_error)?;
+            let mut raw = saved.clone();
+            raw.make_raw();
+            termios::tcsetattr(io::stdin(), OptionalActions::Flush, &raw).map_err(io_error)?;
+            let mut screen = Self {
+                saved,
+                out: io::stdout(),
+            };
+            // Alternate screen, cursor hidden, long lines clipped rather than wrapped.
+            screen.write("\x1b[?1049h\x1b[?25l\x1b[?7l")?;
+            Ok(screen)
+        }
+
+        fn write(&mut self, text: &str) -> Result<(), AppError> {
+            self.out
+                .write_all(text.as_bytes())
+                .and_then(|_| self.out.flush())
+                .map_err(io_error)
+        }
+
+        fn draw(&mut self, view: &View, status: &str) -> Result<(), AppError> {
+            let mut frame = String::from("\x1b[H\x1b[2J");
+            for line in view.lines.iter().skip(view.top).take(view.height) {
+                frame.push_str(line);
+                frame.push_str("\x1b[0m\r\n");
+            }
+            frame.push_str(&format!(
+                "\x1b[{};1H\x1b[7m{status}\x1b[0m",
+                view.height + 1
+            ));
+            self.write(&frame)
+        }
+
+        /// Reads a search typed on the status line; `None` when Escape or Ctrl-C cancels it.
+        fn prompt(&mut self, view: &View) -> Result<Option<String>, AppError> {
+            let mut typed: Vec<u8> = Vec::new();
+            loop {
+                let shown = String::from_utf8_lossy(&typed).into_owned();
+                self.write(&format!(
+                    "\x1b[{};1H\x1b[2K/{shown}\x1b[?25h",
+                    view.height + 1
+                ))?;
+                let byte = read_byte(None)?;
+                match byte {
+                    Some(b'\r' | b'\n') => break,
+                    Some(0x1b | 0x03) | None => {
+                        self.write("\x1b[?25l")?;
+                        return Ok(None);
+                    }
+                    Some(0x7f | 0x08) => {
+                        // Drop a whole character, continuation bytes first.
+                        while typed.last().is_some_and(|byte| byte & 0xc0 == 0x80) {
+                            typed.pop();
+                        }
+                        typed.pop();
+                    }
+                    Some(byte) if byte >= 0x20 => typed.push(byte),
+                    Some(_) => {}
+                }
+            }
+            self.write("\x1b[?25l")?;
+            let query = String::from_utf8_lossy(&typed).trim().to_string();
+            Ok((!query.is_empty()).then_some(query))
+        }
+    }
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = self.write("\x1b[?7h\x1b[?25h\x1b[?1049l");
+            let _ = termios::tcsetattr(io::stdin(), OptionalActions::Flush, &self.saved);
+        }
+    }
+
+    /// One byte from stdin, or `None` when `timeout_ms` passes first or input ends.
+    fn read_byte(timeout_ms: Option<i64>) -> Result<Option<u8>, AppError> {
+        let stdin = io::stdin();
+        if let Some(ms) = timeout_ms {
+            let timeout = Timespec {
+                tv_sec: 0,
+                tv_nsec: ms * 1_000_000,
+            };
+            let mut fds = [PollFd::new(&stdin, PollFlags::IN)];
+            if rustix::event::poll(&mut fds, Some(&timeout)).map_err(io_error)? == 0 {
+                return Ok(None);
+            }
+        }
+        let mut byte = [0u8];
+        match rustix::io::read(&stdin, &mut byte).map_err(io_error)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    fn read_key() -> Result<Option<Key>, AppError> {
+        let Some(byte) = read_byte(None)? else {
+            return Ok(Some(Key::Quit));
+        };
+        Ok(match byte {
+            b'q' | b'Q' | 0x03 => Some(Key::Quit),
+            b'j' | b'\r' | b'\n' => Some(Key::Down),
+            b'k' => Some(Key::Up),
+            b' ' | b'f' => Some(Key::PageDown),
+            b'b' => Some(Key::PageUp),
+            b'g' => Some(Key::Top),
+            b'G' => Some(Key::Bottom),
+            b'/' => Some(Key::Search),
+            b'n' => Some(Key::Next),
+            b'N' => Some(Key::Previous),
+            // A lone Escape quits; arrow and paging keys arrive as `ESC [ ...` straight after it.
+            0x1b => match read_byte(Some(50))? {
+                None => Some(Key::Quit),
+                Some(b'[') => match read_byte(Some(50))? {
+                    Some(b'A') => Some(Key::Up),
+                    Some(b'B') => Some(Key::Down),
+                    Some(b'H') => Some(Key::Top),
+                    Some(b'F') => Some(Key::Bottom),
+                    Some(b'5') => read_byte(Some(50))?.map(|_| Key::PageUp),
+                    Some(b'6') => read_byte(Some(50))?.map(|_| Key::PageDown),
+                    _ => None,
+                },
+                Some(_) => None,
+            },
+            _ => None,
+        })
+    }
+
+    pub(super) fn show(mut view: View) -> Result<(), AppError> {
+        let mut screen = Screen::enter()?;
+        let mut message: Option<String> = None;
+        loop {
+            if let Some((_, terminal_size::Height(rows))) = terminal_size::terminal_size() {
+                view.height = usize::from(rows).saturating_sub(1).max(1);
+                view.top = view.top.min(view.last_top());
+            }
+            let status = message.take().unwrap_or_else(|| view.status());
+            screen.draw(&view, &status)?;
+            let Some(key) = read_key()? else {
+                continue;
+            };
+            let found = match key {
+                Key::Quit => return Ok(()),
+                Key::Search => match screen.prompt(&view)? {
+                    Some(query) => {
+                        view.query = Some(query);
+                        view.matched = None;
+                        view.find(true)
+                    }
+                    None => true,
+                },
+                Key::Next => view.find(true),
+                Key::Previous => view.find(false),
+                key => {
+                    view.apply(key);
+                    true
+                }
+            };
+            if !found && let Some(query) = view.query.as_deref() {
+                message = Some(format!("Pattern not found: {query}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, View};
+
+    fn view(height: usize) -> View {
+        let output: String = (1..=10)
+            .map(|n| format!("\x1b[38;5;208mTask {n}\x1b[0m\n"))
+            .collect();
+        View::new(&output, height)
+    }
+
+    #[test]
+    fn scrolling_stays_within_the_output() {
+        let mut view = view(4);
+        view.apply(Key::Up);
+        assert_eq!(view.top, 0);
+        view.apply(Key::PageDown);
+        assert_eq!(view.top, 4);
+        view.apply(Key::Bottom);
+        assert_eq!(view.top, 6);
+        view.apply(Key::Down);
+        assert_eq!(view.top, 6);
+        assert_eq!(view.status(), "lines 7-10 of 10  q quit  / search");
+        view.apply(Key::Top);
+        assert_eq!(view.top, 0);
+    }
+
+    #[test]
+    fn search_ignores_colors_and_case_and_steps_through_matches() {
+        let mut view = view(4);
+        view.query = Some("TASK 1".to_string());
+
+        assert!(view.find(true));
+        assert_eq!((view.top, view.matched), (0, Some(0)));
+        assert!(view.find(true));
+        // "Task 10" is on the last screen, so the view stops there with the match in sight.
+        assert_eq!((view.top, view.matched), (6, Some(9)));
+        assert!(!view.find(true));
+        assert!(view.find(false));
+        assert_eq!(view.matched, Some(0));
+    }
+}
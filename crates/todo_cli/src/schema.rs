@@ -0,0 +1,1004 @@
+//! JSON Schemas for the shapes the CLI prints with `--json`, and the optional envelope that
+//! tags each payload with [`JSON_SCHEMA_VERSION`].
+//!
+//! Bump [`JSON_SCHEMA_VERSION`] whenever a field is removed, renamed, or changes type; adding
+//! optional fields is not a breaking change.
+
+use clap::ValueEnum;
+use serde_json::{Value, json};
+use todo_core::error::AppError;
+
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaKind {
+    /// Array of tasks (`list`, `notify`)
+    List,
+    /// A single task (`add`, `edit`, `done`, `show`, ...)
+    Task,
+    /// Failure envelope printed with `--json --envelope`
+    Error,
+    /// Result of `notify install-schedule` / `uninstall-schedule`
+    Schedule,
+    /// Usage summary printed by `insights`
+    Insights,
+    /// Grouped tasks printed by `list --group-by`
+    Groups,
+    /// Defined contexts printed by `context`
+    Contexts,
+    /// Task changes printed by `diff`
+    Diff,
+    /// Saved checkpoints printed by `snapshot`
+    Snapshots,
+    /// Store summary printed by `status`
+    Status,
+    /// Archived and purged tasks printed by `maintain`
+    Maintenance,
+    /// Tasks copied each way by `sync`
+    Sync,
+    /// Remotes and their last sync printed by `sync status`
+    SyncStatus,
+    /// Changes waiting for each remote printed by `sync queue`
+    SyncQueue,
+    /// Remotes synced and still failing printed by `sync flush`
+    SyncFlush,
+    /// App identity registered by `notify register-windows-app`
+    WindowsApp,
+    /// Sample notification results printed by `notify test`
+    NotifyTest,
+    /// Store events printed by `events pull`
+    Events,
+    /// Count of events acknowledged by `events ack`
+    EventsAck,
+    /// Deliveries posted and still queued by `webhooks deliver`
+    WebhooksDeliver,
+    /// Queued deliveries printed by `webhooks status`
+    WebhooksStatus,
+    /// Store size and health printed by `info`
+    Info,
+    /// Estimates against tracked time printed by `report accuracy`
+    ReportAccuracy,
+    /// A day's note printed by `journal`
+    DayNote,
+    /// Fields lost in each format's round trip, printed by `export --verify`
+    ExportVerify,
+    /// The config and store set up by `init`
+    Init,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 26] = [
+        SchemaKind::List,
+        SchemaKind::Task,
+        SchemaKind::Error,
+        SchemaKind::Schedule,
+        SchemaKind::Insights,
+        SchemaKind::Groups,
+        SchemaKind::Contexts,
+        SchemaKind::Diff,
+        SchemaKind::Snapshots,
+        SchemaKind::Status,
+        SchemaKind::Maintenance,
+        SchemaKind::Sync,
+        SchemaKind::SyncStatus,
+        SchemaKind::SyncQueue,
+        SchemaKind::SyncFlush,
+        SchemaKind::WindowsApp,
+        SchemaKind::NotifyTest,
+        SchemaKind::Events,
+        SchemaKind::EventsAck,
+        SchemaKind::WebhooksDeliver,
+        SchemaKind::WebhooksStatus,
+        SchemaKind::Info,
+        SchemaKind::ReportAccuracy,
+        SchemaKind::DayNote,
+        SchemaKind::ExportVerify,
+        SchemaKind::Init,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaKind::List => "list",
+            SchemaKind::Task => "task",
+            SchemaKind::Error => "error",
+            SchemaKind::Schedule => "schedule",
+            SchemaKind::Insights => "insights",
+            SchemaKind::Groups => "groups",
+            SchemaKind::Contexts => "contexts",
+            SchemaKind::Diff => "diff",
+            SchemaKind::Snapshots => "snapshots",
+            SchemaKind::Status => "status",
+            SchemaKind::Maintenance => "maintenance",
+            SchemaKind::Sync => "sync",
+            SchemaKind::SyncStatus => "sync_status",
+            SchemaKind::SyncQueue => "sync_queue",
+            SchemaKind::SyncFlush => "sync_flush",
+            SchemaKind::WindowsApp => "windows_app",
+            SchemaKind::NotifyTest => "notify_test",
+            SchemaKind::Events => "events",
+            SchemaKind::EventsAck => "events_ack",
+            SchemaKind::WebhooksDeliver => "webhooks_deliver",
+            SchemaKind::WebhooksStatus => "webhooks_status",
+            SchemaKind::Info => "info",
+            SchemaKind::ReportAccuracy => "report_accuracy",
+            SchemaKind::DayNote => "day_note",
+            SchemaKind::ExportVerify => "export_verify",
+            SchemaKind::Init => "init",
+        }
+    }
+}
+
+/// Wraps a payload as `{"schema_version", "kind", "data", "warnings"}`.
+pub fn envelope(kind: SchemaKind, data: Value, warnings: &[String]) -> Value {
+    json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "kind": kind.name(),
+        "data": data,
+        "warnings": warnings,
+    })
+}
+
+pub fn error_envelope(err: &AppError, warnings: &[String]) -> Value {
+    let mut data = json!({
+        "code": err.code(),
+        "message": err.message(),
+    });
+    if let Some(hint) = err.hint() {
+        data["hint"] = json!(hint);
+    }
+    envelope(SchemaKind::Error, data, warnings)
+}
+
+/// JSON Schema (draft 2020-12) for the envelope of the given kind.
+pub fn schema(kind: SchemaKind) -> Value {
+    let data = match kind {
+        SchemaKind::List => json!({
+            "type": "array",
+            "items": task_schema(),
+        }),
+        SchemaKind::Task => task_schema(),
+        SchemaKind::Error => json!({
+            "type": "object",
+            "required": ["code", "message"],
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "enum": ["invalid_input", "invalid_data", "io_error"],
+                },
+                "message": { "type": "string" },
+                "hint": {
+                    "type": "string",
+                    "description": "What to do about the error, such as a command to run first. Left out when there is none.",
+                },
+            },
+        }),
+        SchemaKind::Schedule => json!({
+            "type": "object",
+            "required": ["installed", "mechanism", "interval_minutes", "files"],
+            "properties": {
+                "installed": { "type": "boolean" },
+                "mechanism": { "type": "string" },
+                "interval_minutes": { "type": ["integer", "null"], "minimum": 1 },
+                "files": { "type": "array", "items": { "type": "string" } },
+            },
+        }),
+        SchemaKind::Groups => json!({
+            "type": "object",
+            "required": ["group_by", "groups", "total"],
+            "properties": {
+                "group_by": { "type": "string", "enum": ["tag", "project", "status", "day"] },
+                "groups": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["key", "count", "tasks"],
+                        "properties": {
+                            "key": {
+                                "type": ["string", "null"],
+                                "description": "null for tasks without a tag, project, or schedule.",
+                            },
+                            "count": { "type": "integer", "minimum": 1 },
+                            "tasks": { "type": "array", "items": task_schema() },
+                        },
+                    },
+                },
+                "total": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Distinct tasks; with group_by tag a task can appear in several groups.",
+                },
+            },
+        }),
+        SchemaKind::Contexts => json!({
+            "type": "object",
+            "required": ["active", "contexts"],
+            "properties": {
+                "active": { "type": ["string", "null"] },
+                "contexts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "filter"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "filter": { "type": "string" },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::Snapshots => json!({
+            "type": "object",
+            "required": ["snapshots"],
+            "properties": {
+                "snapshots": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "path", "created_at", "task_count"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "path": { "type": "string" },
+                            "created_at": { "type": "string", "format": "date-time" },
+                            "task_count": { "type": "integer", "minimum": 0 },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::Status => json!({
+            "type": "object",
+            "required": ["updated_at", "pending", "urgent", "completed", "overdue"],
+            "properties": {
+                "updated_at": { "type": "string", "format": "date-time" },
+                "pending": { "type": "integer", "minimum": 0 },
+                "urgent": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Pending tasks marked urgent.",
+                },
+                "completed": { "type": "integer", "minimum": 0 },
+                "next_due": {
+                    "type": ["string", "null"],
+                    "format": "date-time",
+                    "description": "Earliest due_at among pending tasks.",
+                },
+                "next_due_title": { "type": ["string", "null"] },
+                "focused_title": { "type": ["string", "null"] },
+                "overdue": {
+                    "type": "boolean",
+                    "description": "next_due has passed.",
+                },
+                "completed_this_week": { "type": "integer", "minimum": 0 },
+                "week": {
+                    "type": "string",
+                    "description": "The current local ISO week, such as 2026-W07.",
+                },
+                "weekly_goal": weekly_goal_schema(),
+            },
+        }),
+        SchemaKind::Maintenance => json!({
+            "type": "object",
+            "required": ["archived", "purged"],
+            "properties": {
+                "archived": {
+                    "type": "array",
+                    "items": task_schema(),
+                    "description": "Completed tasks moved from the store to the archive.",
+                },
+                "purged": {
+                    "type": "array",
+                    "items": task_schema(),
+                    "description": "Archived tasks dropped for good.",
+                },
+            },
+        }),
+        SchemaKind::Sync => json!({
+            "type": "object",
+            "required": ["remote", "dry_run", "pushed", "pulled", "deleted_remote", "deleted_local", "conflicts"],
+            "properties": {
+                "remote": { "type": "string" },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "The lists are a plan and nothing was changed.",
+                },
+                "pushed": {
+                    "type": "array",
+                    "items": task_schema(),
+                    "description": "Tasks added or changed here, copied to the remote.",
+                },
+                "pulled": {
+                    "type": "array",
+                    "items": task_schema(),
+                    "description": "Tasks added or changed on the remote, copied here.",
+                },
+                "deleted_remote": { "type": "array", "items": task_schema() },
+                "deleted_local": { "type": "array", "items": task_schema() },
+                "conflicts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "title", "fields", "changes", "resolution", "from_remote"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "title": { "type": "string", "description": "Title of the task here." },
+                            "fields": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Fields changed differently on both sides.",
+                            },
+                            "changes": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["field", "local", "remote"],
+                                    "properties": {
+                                        "field": { "type": "string" },
+                                        "local": {},
+                                        "remote": {},
+                                    },
+                                },
+                            },
+                            "resolution": {
+                                "type": ["string", "null"],
+                                "enum": ["local", "remote", "merge", null],
+                                "description": "null in a dry run.",
+                            },
+                            "from_remote": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "With merge, the fields taken from the remote task.",
+                            },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::SyncStatus => json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["name", "path", "synced_at", "local_changes", "conflicts"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "path": { "type": "string" },
+                    "synced_at": {
+                        "type": ["string", "null"],
+                        "format": "date-time",
+                        "description": "When the last sync finished; null before the first.",
+                    },
+                    "local_changes": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Tasks added, changed, or deleted here since the last sync.",
+                    },
+                    "conflicts": {
+                        "type": "array",
+                        "description": "Left by a sync that stopped before settling them.",
+                        "items": {
+                            "type": "object",
+                            "required": ["task_id", "title", "fields", "found_at"],
+                            "properties": {
+                                "task_id": { "type": "string" },
+                                "title": { "type": "string" },
+                                "fields": { "type": "array", "items": { "type": "string" } },
+                                "found_at": { "type": "string", "format": "date-time" },
+                            },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::SyncQueue => json!({
+            "type": "array",
+            "description": "Only remotes with something queued.",
+            "items": {
+                "type": "object",
+                "required": ["remote", "path", "unreachable", "changes"],
+                "properties": {
+                    "remote": { "type": "string" },
+                    "path": { "type": "string" },
+                    "unreachable": {
+                        "type": ["object", "null"],
+                        "description": "Set when the last sync could not reach the remote.",
+                        "required": ["since", "error"],
+                        "properties": {
+                            "since": { "type": "string", "format": "date-time" },
+                            "error": { "type": "string" },
+                        },
+                    },
+                    "changes": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["change", "id", "title"],
+                            "properties": {
+                                "change": { "type": "string", "enum": ["added", "changed", "deleted"] },
+                                "id": { "type": "string" },
+                                "title": { "type": "string" },
+                            },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::WindowsApp => json!({
+            "type": "object",
+            "required": ["app_id", "display_name", "key", "configured"],
+            "properties": {
+                "app_id": { "type": "string" },
+                "display_name": { "type": "string" },
+                "key": { "type": "string", "description": "Registry key written under HKEY_CURRENT_USER." },
+                "configured": {
+                    "type": "boolean",
+                    "description": "False when app_id is the default and notifications.windows.app_id still needs setting.",
+                },
+            },
+        }),
+        SchemaKind::NotifyTest => json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["destination", "backend", "ok", "error"],
+                "properties": {
+                    "destination": {
+                        "type": "string",
+                        "description": "`default`, or the route such as `project:work` or `tag:personal`.",
+                    },
+                    "backend": {
+                        "type": "string",
+                        "enum": ["auto", "desktop", "console", "webhook", "none"],
+                    },
+                    "ok": { "type": "boolean" },
+                    "error": {
+                        "oneOf": [
+                            { "type": "null" },
+                            {
+                                "type": "object",
+                                "required": ["code", "message"],
+                                "properties": {
+                                    "code": { "type": "string", "enum": ["invalid_input", "invalid_data", "io_error"] },
+                                    "message": { "type": "string" },
+                                },
+                            },
+                        ],
+                    },
+                },
+            },
+        }),
+        SchemaKind::Events => json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["seq", "type", "at", "by", "task_id", "title"],
+                "properties": {
+                    "seq": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Increases by one per event and is never reused; pass it to `events ack`.",
+                    },
+                    "type": {
+                        "type": "string",
+                        "enum": [
+                            "task_added",
+                            "task_updated",
+                            "task_completed",
+                            "task_reopened",
+                            "task_cancelled",
+                            "task_removed",
+                        ],
+                    },
+                    "at": { "type": "string", "format": "date-time" },
+                    "by": { "type": "string" },
+                    "task_id": { "type": "string" },
+                    "title": { "type": "string", "description": "Title after the change, or before it for removals." },
+                },
+            },
+        }),
+        SchemaKind::EventsAck => json!({
+            "type": "object",
+            "required": ["acknowledged", "through"],
+            "properties": {
+                "acknowledged": { "type": "integer", "minimum": 0 },
+                "through": { "type": "integer", "minimum": 0 },
+            },
+        }),
+        SchemaKind::WebhooksDeliver => json!({
+            "type": "object",
+            "required": ["delivered", "queued", "failed"],
+            "properties": {
+                "delivered": { "type": "integer", "minimum": 0 },
+                "queued": { "type": "integer", "minimum": 0, "description": "Deliveries left for the next run." },
+                "failed": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["url", "error"],
+                        "properties": {
+                            "url": { "type": "string" },
+                            "error": { "type": "string" },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::WebhooksStatus => json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["id", "url", "event", "attempts", "last_error"],
+                "properties": {
+                    "id": { "type": "integer", "minimum": 1 },
+                    "url": { "type": "string" },
+                    "event": { "type": "object", "description": "The event as `events pull` prints it." },
+                    "attempts": { "type": "integer", "minimum": 0 },
+                    "last_error": { "type": ["string", "null"] },
+                },
+            },
+        }),
+        SchemaKind::Info => json!({
+            "type": "object",
+            "required": ["path", "store_bytes", "tasks", "oldest_open", "journal", "last_backup"],
+            "properties": {
+                "path": { "type": "string" },
+                "store_bytes": { "type": "integer", "minimum": 0 },
+                "tasks": {
+                    "type": "object",
+                    "description": "`total` and the count for each status.",
+                    "required": ["total", "pending", "in_progress", "waiting", "completed", "cancelled"],
+                    "additionalProperties": { "type": "integer", "minimum": 0 },
+                },
+                "oldest_open": {
+                    "type": ["object", "null"],
+                    "required": ["id", "title", "created_at"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "title": { "type": "string" },
+                        "created_at": { "type": "string", "format": "date-time" },
+                    },
+                },
+                "journal": {
+                    "type": "object",
+                    "description": "Changes not yet folded into the store file; `todo flush` folds them in.",
+                    "required": ["entries", "bytes"],
+                    "properties": {
+                        "entries": { "type": "integer", "minimum": 0 },
+                        "bytes": { "type": "integer", "minimum": 0 },
+                    },
+                },
+                "last_backup": {
+                    "type": ["object", "null"],
+                    "required": ["name", "created_at"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "created_at": { "type": "string", "format": "date-time" },
+                    },
+                },
+            },
+        }),
+        SchemaKind::DayNote => json!({
+            "type": "object",
+            "required": ["date", "note"],
+            "properties": {
+                "date": { "type": "string", "format": "date" },
+                "note": {
+                    "type": ["string", "null"],
+                    "description": "One line per `journal` entry; null when the day has none.",
+                },
+            },
+        }),
+        SchemaKind::ExportVerify => json!({
+            "type": "object",
+            "required": ["lossless", "formats", "export_only"],
+            "properties": {
+                "lossless": { "type": "boolean" },
+                "formats": {
+                    "type": "array",
+                    "description": "The store file, then each `export` format `import` reads.",
+                    "items": {
+                        "type": "object",
+                        "required": ["format", "tasks", "lossless", "lost", "not_carried"],
+                        "properties": {
+                            "format": { "type": "string" },
+                            "tasks": { "type": "integer", "minimum": 0 },
+                            "lossless": { "type": "boolean" },
+                            "lost": {
+                                "type": "array",
+                                "description": "Fields the format carries that came back different.",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["field", "task_ids"],
+                                    "properties": {
+                                        "field": { "type": "string" },
+                                        "task_ids": { "type": "array", "items": { "type": "string" } },
+                                    },
+                                },
+                            },
+                            "not_carried": {
+                                "type": "array",
+                                "description": "Fields some task has that the format has no place for.",
+                                "items": { "type": "string" },
+                            },
+                        },
+                    },
+                },
+                "export_only": {
+                    "type": "array",
+                    "description": "Formats nothing reads back, which are not checked.",
+                    "items": { "type": "string" },
+                },
+            },
+        }),
+        SchemaKind::SyncFlush => json!({
+            "type": "object",
+            "required": ["synced", "failed"],
+            "properties": {
+                "synced": {
+                    "type": "array",
+                    "items": schema(SchemaKind::Sync)["properties"]["data"].clone(),
+                },
+                "failed": {
+                    "type": "array",
+                    "description": "Remotes whose changes stay queued.",
+                    "items": {
+                        "type": "object",
+                        "required": ["remote", "code", "message"],
+                        "properties": {
+                            "remote": { "type": "string" },
+                            "code": { "type": "string", "enum": ["invalid_input", "invalid_data", "io_error"] },
+                            "message": { "type": "string" },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::Diff => json!({
+            "type": "object",
+            "required": ["added", "removed", "changed"],
+            "properties": {
+                "added": { "type": "array", "items": task_schema() },
+                "removed": { "type": "array", "items": task_schema() },
+                "changed": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "title", "changes"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "title": { "type": "string", "description": "Title in the new store." },
+                            "changes": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["field", "before", "after"],
+                                    "properties": {
+                                        "field": { "type": "string" },
+                                        "before": {},
+                                        "after": {},
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        }),
+        SchemaKind::Insights => json!({
+            "type": "object",
+            "required": ["total_runs", "top_commands", "completions_by_hour", "reschedules"],
+            "properties": {
+                "total_runs": { "type": "integer", "minimum": 0 },
+                "top_commands": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["command", "count"],
+                        "properties": {
+                            "command": { "type": "string" },
+                            "count": { "type": "integer", "minimum": 1 },
+                        },
+                    },
+                },
+                "completions_by_hour": {
+                    "type": "array",
+                    "items": { "type": "integer", "minimum": 0 },
+                    "minItems": 24,
+                    "maxItems": 24,
+                },
+                "reschedules": {
+                    "type": "object",
+                    "required": ["total", "tasks", "average_per_task"],
+                    "properties": {
+                        "total": { "type": "integer", "minimum": 0 },
+                        "tasks": { "type": "integer", "minimum": 0 },
+                        "average_per_task": { "type": ["number", "null"] },
+                    },
+                },
+                "completions_this_week": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Completions so far in the current local ISO week.",
+                },
+                "weekly_goal": weekly_goal_schema(),
+            },
+        }),
+        SchemaKind::ReportAccuracy => json!({
+            "type": "object",
+            "required": ["tags", "skipped"],
+            "properties": {
+                "tags": {
+                    "type": "array",
+                    "description": "Tags in name order, then untagged tasks under a null tag.",
+                    "items": {
+                        "type": "object",
+                        "required": ["tag", "tasks", "estimated_minutes", "tracked_minutes", "ratio"],
+                        "properties": {
+                            "tag": { "type": ["string", "null"] },
+                            "tasks": { "type": "integer", "minimum": 1 },
+                            "estimated_minutes": { "type": "integer", "minimum": 0 },
+                            "tracked_minutes": { "type": "integer", "minimum": 0 },
+                            "ratio": {
+                                "type": ["number", "null"],
+                                "description": "Tracked over estimated time; above 1 ran over.",
+                            },
+                        },
+                    },
+                },
+                "skipped": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Completed tasks without an estimate or a finished work session.",
+                },
+            },
+        }),
+        SchemaKind::Init => json!({
+            "type": "object",
+            "required": ["config_path", "store_path", "theme", "notifications", "sample_task"],
+            "properties": {
+                "config_path": { "type": "string" },
+                "store_path": { "type": "string" },
+                "theme": { "type": "string" },
+                "notifications": {
+                    "type": "boolean",
+                    "description": "false when `notifications.backend` was set to `none`.",
+                },
+                "sample_task": {
+                    "oneOf": [task_schema(), { "type": "null" }],
+                    "description": "The task added to start with; null when none was.",
+                },
+            },
+        }),
+    };
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("urn:todo-opt:schema:{JSON_SCHEMA_VERSION}:{}", kind.name()),
+        "title": format!("todo_opt {} output", kind.name()),
+        "description": "With --envelope the payload is wrapped in this object; without it, only the `data` value is printed.",
+        "type": "object",
+        "required": ["schema_version", "kind", "data", "warnings"],
+        "properties": {
+            "schema_version": { "const": JSON_SCHEMA_VERSION },
+            "kind": { "const": kind.name() },
+            "data": data,
+            "warnings": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Conditions the command carried on past, as printed after `WARNING:` on stderr. Empty when there were none.",
+            },
+        },
+    })
+}
+
+/// Progress toward `goals.completions_per_week`, or `null` when no goal is set.
+fn weekly_goal_schema() -> Value {
+    json!({
+        "type": ["object", "null"],
+        "required": ["target", "completed", "reached"],
+        "properties": {
+            "target": { "type": "integer", "minimum": 0 },
+            "completed": { "type": "integer", "minimum": 0 },
+            "reached": { "type": "boolean" },
+        },
+    })
+}
+
+/// Every field a task object may carry. Commands print a subset; only the identity fields
+/// are always present.
+fn task_schema() -> Value {
+    let timestamp = json!({ "type": "string", "format": "date-time" });
+    let optional_timestamp = json!({ "type": ["string", "null"], "format": "date-time" });
+    json!({
+        "type": "object",
+        "required": ["id", "title", "status", "created_at"],
+        "properties": {
+            "id": { "type": "string" },
+            "title": { "type": "string" },
+            "status": {
+                "type": "string",
+                "enum": ["pending", "in_progress", "waiting", "completed", "cancelled"],
+                "description": "With --legacy-json, in_progress and waiting report \"pending\", and overdue tasks \"pending (overdue)\".",
+            },
+            "overdue": { "type": "boolean" },
+            "section": {
+                "type": ["string", "null"],
+                "enum": ["morning", "afternoon", "evening", null],
+                "description": "list today only: the part of the day the planned time falls in, split at day_sections; null when the task has no planned time.",
+            },
+            "due_in_seconds": {
+                "type": ["integer", "null"],
+                "description": "Seconds until due_at while it is within due_soon_within and not yet passed; null otherwise.",
+            },
+            "created_at": timestamp,
+            "scheduled_at": {
+                "type": ["string", "null"],
+                "format": "date-time",
+                "description": "Same as due_at, the name it had before start_at was split off.",
+            },
+            "start_at": optional_timestamp,
+            "due_at": optional_timestamp,
+            "completed_at": optional_timestamp,
+            "urgent": { "type": "boolean" },
+            "completion_history": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["message", "completed_at"],
+                    "properties": {
+                        "message": { "type": "string" },
+                        "completed_at": timestamp,
+                    },
+                },
+            },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "notes": { "type": ["string", "null"] },
+            "project": { "type": ["string", "null"] },
+            "metadata": {
+                "type": "object",
+                "additionalProperties": { "type": "string" },
+                "description": "Custom key/value pairs set with `meta`.",
+            },
+            "ticket": {
+                "type": ["object", "null"],
+                "required": ["key"],
+                "properties": {
+                    "key": { "type": "string" },
+                    "url": { "type": ["string", "null"] },
+                    "provider": { "type": ["string", "null"] },
+                    "status": {
+                        "type": ["string", "null"],
+                        "description": "Ticket status at the last refresh-tickets.",
+                    },
+                    "synced_at": optional_timestamp,
+                },
+            },
+            "comments": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["author", "text", "created_at"],
+                    "properties": {
+                        "author": { "type": "string" },
+                        "text": { "type": "string" },
+                        "created_at": { "type": "string", "format": "date-time" },
+                    },
+                },
+                "description": "Discussion added with `comment`, oldest first.",
+            },
+            "modified_by": {
+                "type": ["string", "null"],
+                "description": "`user@host` that last changed the task.",
+            },
+            "modified_at": optional_timestamp,
+            "waiting_until": {
+                "type": ["string", "null"],
+                "format": "date-time",
+                "description": "Set by `wait --until`; cleared by `start` and `done`.",
+            },
+            "cancellation": {
+                "type": ["object", "null"],
+                "required": ["cancelled_at"],
+                "properties": {
+                    "cancelled_at": timestamp,
+                    "reason": { "type": ["string", "null"] },
+                },
+                "description": "Set by `cancel` on tasks with status cancelled.",
+            },
+            "routine": {
+                "type": ["string", "null"],
+                "description": "Routine the task was started from; archived once its day is over.",
+            },
+            "marker": {
+                "type": ["object", "null"],
+                "properties": {
+                    "color": {
+                        "type": ["string", "null"],
+                        "enum": ["red", "orange", "yellow", "green", "blue", "purple", null],
+                    },
+                    "symbol": { "type": ["string", "null"] },
+                },
+                "description": "Set by `mark`.",
+            },
+            "sessions": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["started_at"],
+                    "properties": {
+                        "started_at": timestamp,
+                        "ended_at": optional_timestamp,
+                    },
+                },
+                "description": "Time in progress, oldest first: opened by `start`, closed by `done`, `wait`, or `cancel`.",
+            },
+            "before": {
+                "type": "object",
+                "properties": { "title": { "type": "string" } },
+                "description": "Only from `edit`: the title before the edit.",
+            },
+            "after": {
+                "type": "object",
+                "properties": { "title": { "type": "string" } },
+                "description": "Only from `edit`: the title after the edit.",
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JSON_SCHEMA_VERSION, SchemaKind, envelope, error_envelope, schema};
+    use serde_json::json;
+    use todo_core::error::AppError;
+
+    #[test]
+    fn envelope_tags_payload_with_version_and_kind() {
+        let wrapped = envelope(SchemaKind::List, json!([]), &[]);
+        let warned = envelope(
+            SchemaKind::List,
+            json!([]),
+            &["config.json is not valid JSON".to_string()],
+        );
+
+        assert_eq!(wrapped["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(wrapped["kind"], "list");
+        assert_eq!(wrapped["data"], json!([]));
+        assert_eq!(wrapped["warnings"], json!([]));
+        assert_eq!(warned["warnings"], json!(["config.json is not valid JSON"]));
+    }
+
+    #[test]
+    fn error_envelope_carries_code_and_message() {
+        let wrapped = error_envelope(&AppError::invalid_input("task not found"), &[]);
+        let hinted = error_envelope(
+            &AppError::invalid_input("task is not scheduled")
+                .with_hint("use `todo schedule 1 <datetime>` first"),
+            &[],
+        );
+
+        assert_eq!(wrapped["kind"], "error");
+        assert_eq!(wrapped["data"]["code"], "invalid_input");
+        assert_eq!(wrapped["data"]["message"], "task not found");
+        assert!(wrapped["data"].get("hint").is_none());
+        assert_eq!(
+            hinted["data"]["hint"],
+            "use `todo schedule 1 <datetime>` first"
+        );
+    }
+
+    #[test]
+    fn schemas_pin_version_and_kind() {
+        for kind in SchemaKind::ALL {
+            let value = schema(kind);
+            assert_eq!(
+                value["properties"]["schema_version"]["const"],
+                JSON_SCHEMA_VERSION
+            );
+            assert_eq!(value["properties"]["kind"]["const"], kind.name());
+        }
+        assert_eq!(
+            schema(SchemaKind::List)["properties"]["data"]["items"]["required"],
+            json!(["id", "title", "status", "created_at"])
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Session variables for interactive mode.
+//!
+//! `set project work` makes every later `add` and `list` in the session behave as if it were
+//! given `--project work`, until `unset project`. A flag typed on the command itself wins over
+//! the session value.
+
+use std::collections::BTreeMap;
+use todo_core::error::AppError;
+
+/// Commands that accept every session variable's flag.
+const SESSION_COMMANDS: [&str; 2] = ["add", "list"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SessionVar {
+    Project,
+    Tag,
+}
+
+impl SessionVar {
+    pub const ALL: [SessionVar; 2] = [SessionVar::Project, SessionVar::Tag];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SessionVar::Project => "project",
+            SessionVar::Tag => "tag",
+        }
+    }
+
+    fn flag(self) -> &'static str {
+        match self {
+            SessionVar::Project => "--project",
+            SessionVar::Tag => "--tag",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, AppError> {
+        SessionVar::ALL
+            .into_iter()
+            .find(|var| var.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                AppError::invalid_input(format!(
+                    "unknown session variable '{name}' (expected project or tag)"
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Session {
+    values: BTreeMap<SessionVar, String>,
+}
+
+impl Session {
+    pub fn set(&mut self, name: &str, value: &str) -> Result<SessionVar, AppError> {
+        let var = SessionVar::parse(name)?;
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(AppError::invalid_input(format!(
+                "{} must not be empty",
+                var.name()
+            )));
+        }
+        self.values.insert(var, value.to_string());
+        Ok(var)
+    }
+
+    /// Clears `name`, returning whether it was set.
+    pub fn unset(&mut self, name: &str) -> Result<bool, AppError> {
+        let var = SessionVar::parse(name)?;
+        Ok(self.values.remove(&var).is_some())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = (SessionVar, &str)> {
+        self.values
+            .iter()
+            .map(|(var, value)| (*var, value.as_str()))
+    }
+
+    /// Adds the flag for each variable to `args` (alias-resolved, without the program name)
+    /// when the command is `add` or `list` and the flag was not given.
+    pub fn apply(&self, args: &mut Vec<String>) {
+        let command = args.iter().find(|arg| !arg.starts_with('-'));
+        if !command.is_some_and(|command| SESSION_COMMANDS.contains(&command.as_str())) {
+            return;
+        }
+
+        // Flags after `--` would be read as positional values.
+        let end = args
+            .iter()
+            .position(|arg| arg == "--")
+            .unwrap_or(args.len());
+        let mut injected = Vec::new();
+        for (var, value) in self.values() {
+            let flag = var.flag();
+            let given = args[..end]
+                .iter()
+                .any(|arg| arg == flag || arg.starts_with(&format!("{flag}=")));
+            if !given {
+                injected.push(flag.to_string());
+                injected.push(value.to_string());
+            }
+        }
+        args.splice(end..end, injected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn apply_adds_flags_to_add_and_list_only() {
+        let mut session = Session::default();
+        session.set("project", "work").unwrap();
+        session.set("TAG", "q4").unwrap();
+
+        let mut add = args("add Draft -- --literal");
+        let mut list = args("--json list today");
+        let mut done = args("done 12");
+        session.apply(&mut add);
+        session.apply(&mut list);
+        session.apply(&mut done);
+
+        assert_eq!(add, args("add Draft --project work --tag q4 -- --literal"));
+        assert_eq!(list, args("--json list today --project work --tag q4"));
+        assert_eq!(done, args("done 12"));
+    }
+
+    #[test]
+    fn explicit_flags_win_and_unset_clears() {
+        let mut session = Session::default();
+        session.set("project", "work").unwrap();
+
+        let mut add = args("add Draft --project=home");
+        session.apply(&mut add);
+
+        assert_eq!(add, args("add Draft --project=home"));
+        assert!(session.unset("project").unwrap());
+        assert!(!session.unset("project").unwrap());
+        assert_eq!(session.values().count(), 0);
+        assert!(session.set("priority", "high").is_err());
+        assert!(session.set("project", "  ").is_err());
+    }
+}
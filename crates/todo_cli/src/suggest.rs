@@ -0,0 +1,66 @@
+//! "Did you mean" hints for mistyped command names. clap only compares against its own
+//! subcommands, so callers pass config aliases in with them.
+
+/// The candidate closest to `word`, if it is close enough to be a typo of it: one edit for
+/// words of up to four letters, two for longer ones, ignoring case. A swap of two
+/// neighbouring letters counts as one edit. Ties go to the candidate listed first; a word
+/// that is a candidate itself gets nothing.
+pub fn closest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let candidates: Vec<&str> = candidates.into_iter().collect();
+    if candidates.contains(&word) {
+        return None;
+    }
+    let lowered = word.to_lowercase();
+    let allowed = if lowered.chars().count() <= 4 { 1 } else { 2 };
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(&lowered, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= allowed)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edits (insert, delete, replace, or swap neighbours) needed to turn `a` into `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows[i][j]: edits between the first i chars of a and the first j of b.
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::closest;
+
+    #[test]
+    fn closest_finds_typos_and_ignores_far_words() {
+        let commands = ["list", "last", "add", "done", "schedule", "ls"];
+
+        assert_eq!(closest("lsit", commands), Some("list"));
+        assert_eq!(closest("LIST", ["lst", "list"]), Some("list"));
+        assert_eq!(closest("shedule", commands), Some("schedule"));
+        assert_eq!(closest("dne", commands), Some("done"));
+        assert_eq!(closest("list", commands), None);
+        assert_eq!(closest("frobnicate", commands), None);
+        assert_eq!(closest("xy", commands), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Recorded interactive sessions: `todo --record FILE` writes one, `todo run FILE --replay`
+//! plays it back.
+//!
+//! Each line typed is a step of up to four kinds of lines: `@ TIME`, the moment it was typed;
+//! `> LINE`, the line itself; then `| TEXT` for each line the command printed to stdout and
+//! `! TEXT` for each line on stderr. Lines starting with `#` are comments.
+//!
+//! A recorded command runs as `todo_opt <args>` in a child process with
+//! [`todo_core::clock::NOW_ENV`] set to its `@` time, so replaying it against the same
+//! starting store makes the same ids and timestamps and prints the same output.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use time::format_description::well_known::Rfc3339;
+use todo_core::error::AppError;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Step {
+    /// RFC3339 time the line was typed, pinned as the clock while it runs.
+    pub at: String,
+    pub input: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Step {
+    /// The step's lines, each ending in a newline.
+    pub fn render(&self) -> String {
+        let mut text = format!("@ {}\n> {}\n", self.at, self.input);
+        for line in self.stdout.lines() {
+            text.push_str(&format!("| {line}\n"));
+        }
+        for line in self.stderr.lines() {
+            text.push_str(&format!("! {line}\n"));
+        }
+        text
+    }
+}
+
+/// Appends steps to a transcript as they happen, so a session that crashes keeps what ran.
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self, AppError> {
+        let mut file =
+            File::create(path).map_err(|err| AppError::io(format!("{}: {err}", path.display())))?;
+        writeln!(
+            file,
+            "# todo_opt {} session; replay with `todo run FILE --replay`",
+            env!("CARGO_PKG_VERSION")
+        )
+        .map_err(|err| AppError::io(err.to_string()))?;
+        Ok(Self { file })
+    }
+
+    pub fn record(&mut self, step: &Step) -> Result<(), AppError> {
+        self.file
+            .write_all(step.render().as_bytes())
+            .map_err(|err| AppError::io(err.to_string()))
+    }
+}
+
+/// The steps of a transcript, in order.
+pub fn parse(text: &str) -> Result<Vec<Step>, AppError> {
+    let mut steps: Vec<Step> = Vec::new();
+    let mut pending_at: Option<String> = None;
+    for (index, line) in text.lines().enumerate() {
+        let invalid = |message: &str| {
+            AppError::invalid_input(format!("transcript line {}: {message}", index + 1))
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (marker, rest) = line.split_at(1);
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        match marker {
+            "@" => pending_at = Some(rest.to_string()),
+            ">" => {
+                let at = pending_at
+                    .take()
+                    .ok_or_else(|| invalid("`>` line without an `@` time before it"))?;
+                steps.push(Step {
+                    at,
+                    input: rest.to_string(),
+                    ..Step::default()
+                });
+            }
+            "|" | "!" => {
+                let step = steps
+                    .last_mut()
+                    .ok_or_else(|| invalid("output before the first `>` line"))?;
+                let output = if marker == "|" {
+                    &mut step.stdout
+                } else {
+                    &mut step.stderr
+                };
+                output.push_str(rest);
+                output.push('\n');
+            }
+            _ => {
+                return Err(invalid(
+                    "expected a line starting with `@`, `>`, `|`, `!`, or `#`",
+                ));
+            }
+        }
+    }
+    Ok(steps)
+}
+
+/// The current time, as the `@` time of a step typed now.
+pub fn timestamp() -> Result<String, AppError> {
+    todo_core::clock::now_utc()
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))
+}
+
+/// Run `args` as `todo_opt` against the current store and config with the clock pinned to
+/// `at`; returns what it printed to stdout and stderr.
+pub fn run_command(args: &[String], at: &str) -> Result<(String, String), AppError> {
+    let exe = std::env::current_exe().map_err(|err| AppError::io(err.to_string()))?;
+    let output = Command::new(exe)
+        .args(args)
+        .env(
+            "TODOAPP_STORE_PATH",
+            todo_core::storage::json_store::store_path()?,
+        )
+        .env("TODOAPP_CONFIG_PATH", todo_core::config::config_path()?)
+        .env(todo_core::clock::NOW_ENV, at)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| AppError::io(format!("failed to run command: {err}")))?;
+    Ok((
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Step, parse};
+
+    #[test]
+    fn rendered_steps_parse_back() {
+        let steps = [
+            Step {
+                at: "2025-12-20T12:00:00Z".to_string(),
+                input: "add \"Buy milk\"".to_string(),
+                stdout: "Added task: Buy milk (1200)\n".to_string(),
+                stderr: String::new(),
+            },
+            Step {
+                at: "2025-12-20T12:01:30.5Z".to_string(),
+                input: "done 9".to_string(),
+                stdout: String::new(),
+                stderr: "ERROR: invalid_input - task not found\n  hint: | and ! survive\n"
+                    .to_string(),
+            },
+        ];
+        let text = format!("# header\n{}\n{}", steps[0].render(), steps[1].render());
+
+        assert_eq!(parse(&text).unwrap(), steps);
+        assert_eq!(
+            parse("> list").unwrap_err().message(),
+            "transcript line 1: `>` line without an `@` time before it"
+        );
+        assert!(parse("@ 2025-12-20T12:00:00Z\nlist").is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Warnings: conditions a command carried on past, such as a config file that could not be read
+//! or a notification that could not be sent, kept apart from the error that ends a command.
+//!
+//! Each is printed to stderr with a `WARNING:` prefix as it happens and kept until the next
+//! `--json --envelope` payload, which lists them under `warnings`. `--strict` turns any of them
+//! into a failed exit status.
+
+use std::fmt::Display;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Every warning this process has printed, for `--strict`.
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Warnings not yet listed in an envelope.
+static PENDING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn warn(message: impl Display) {
+    let message = message.to_string();
+    eprintln!("WARNING: {message}");
+    COUNT.fetch_add(1, Ordering::Relaxed);
+    PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(message);
+}
+
+pub fn count() -> usize {
+    COUNT.load(Ordering::Relaxed)
+}
+
+/// The warnings since the last call, oldest first, so a long-running session lists each in one
+/// envelope only.
+pub fn take() -> Vec<String> {
+    std::mem::take(
+        &mut *PENDING
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, take, warn};
+
+    #[test]
+    fn take_drains_warnings_but_keeps_the_count() {
+        take();
+        let before = count();
+        warn("first");
+        warn(format!("second {}", 2));
+        assert_eq!(take(), ["first", "second 2"]);
+        assert!(take().is_empty());
+        assert_eq!(count(), before + 2);
+    }
+}
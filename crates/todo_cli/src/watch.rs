@@ -0,0 +1,179 @@
+//! `todo watch`: run a command for each task that starts matching a filter, changes while it
+//! matches, or stops matching, for automations that would otherwise need Rust.
+//!
+//! The store is reloaded every interval and the matching tasks compared with the previous
+//! reload, so changes from any process are seen, and time-based terms such as
+//! `status:overdue` are picked up as time passes. Tasks that match when the watch starts are the
+//! baseline: nothing runs for them.
+//!
+//! `{id}`, `{title}`, `{status}`, `{project}`, `{due_at}`, and `{change}` in the command are
+//! replaced with the task's values, each quoted for the shell so a title can't run commands of
+//! its own. Other braces, such as `${HOME}`, are left for the shell.
+
+use crate::cli::WatchChange;
+use crate::warnings::warn;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use todo_core::diff;
+use todo_core::error::AppError;
+use todo_core::model::Task;
+use todo_core::task_api;
+
+/// Watch tasks matching `filter` until the process is stopped, running `exec` for each change
+/// in `on`. Commands run one at a time, in store order. `overdue_grace` is how long past its
+/// deadline a task may run before `status:overdue` matches it.
+pub fn run(
+    filter: &str,
+    exec: &str,
+    on: &[WatchChange],
+    interval: Duration,
+    overdue_grace: time::Duration,
+) -> Result<(), AppError> {
+    let mut matched = task_api::query_tasks(filter, overdue_grace)?;
+    eprintln!(
+        "Watching {} matching tasks every {}s; press Ctrl-C to stop",
+        matched.len(),
+        interval.as_secs()
+    );
+    loop {
+        std::thread::sleep(interval);
+        // A store caught mid-replace or briefly unreadable is read again on the next round.
+        let current = match task_api::query_tasks(filter, overdue_grace) {
+            Ok(tasks) => tasks,
+            Err(err) => {
+                warn(err.message());
+                continue;
+            }
+        };
+        let changes = diff::diff_tasks(&matched, &current)?;
+        let mut due = Vec::new();
+        if on.contains(&WatchChange::Added) {
+            due.extend(changes.added.iter().map(|task| (WatchChange::Added, task)));
+        }
+        if on.contains(&WatchChange::Changed) {
+            due.extend(
+                changes
+                    .changed
+                    .iter()
+                    .map(|change| (WatchChange::Changed, &change.after)),
+            );
+        }
+        if on.contains(&WatchChange::Removed) {
+            due.extend(
+                changes
+                    .removed
+                    .iter()
+                    .map(|task| (WatchChange::Removed, task)),
+            );
+        }
+        for (change, task) in due {
+            run_exec(&render(exec, task, change));
+        }
+        matched = current;
+    }
+}
+
+/// `exec` with the placeholders replaced by `task`'s values, quoted for the shell.
+pub fn render(exec: &str, task: &Task, change: WatchChange) -> String {
+    let value = |name: &str| -> Option<String> {
+        Some(match name {
+            "id" => task.id.clone(),
+            "title" => task.title.clone(),
+            "status" => task.status.as_str().to_string(),
+            "project" => task.project.clone().unwrap_or_default(),
+            "due_at" => task.due_at.clone().unwrap_or_default(),
+            "change" => change.as_str().to_string(),
+            _ => return None,
+        })
+    };
+
+    let mut rendered = String::with_capacity(exec.len());
+    let mut rest = exec;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after
+            .find('}')
+            .and_then(|close| Some((close, value(&after[..close])?)))
+        {
+            Some((close, value)) => {
+                rendered.push_str(&quote(&value));
+                rest = &after[close + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(not(windows))]
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+// cmd has no escape for a double quote inside a quoted argument, so any are dropped.
+#[cfg(windows)]
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', ""))
+}
+
+fn run_exec(command: &str) {
+    #[cfg(not(windows))]
+    let mut shell = Command::new("sh");
+    #[cfg(not(windows))]
+    shell.arg("-c");
+    #[cfg(windows)]
+    let mut shell = Command::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C");
+
+    match shell.arg(command).stdin(Stdio::null()).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => warn(format!("`{command}` failed with {status}")),
+        Err(err) => warn(format!("could not run `{command}`: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::cli::WatchChange;
+    use todo_core::model::Task;
+
+    fn task(title: &str) -> Task {
+        Task::builder().id("7").title(title).build().unwrap()
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn render_quotes_values_and_leaves_other_braces_alone() {
+        assert_eq!(
+            render(
+                "notify-send {title} {change}",
+                &task("Buy milk"),
+                WatchChange::Added
+            ),
+            "notify-send 'Buy milk' 'added'"
+        );
+        assert_eq!(
+            render(
+                "echo {title}",
+                &task("It's $(rm -rf ~)"),
+                WatchChange::Changed
+            ),
+            r"echo 'It'\''s $(rm -rf ~)'"
+        );
+        assert_eq!(
+            render(
+                "echo ${HOME} {project}{unknown} {",
+                &task("x"),
+                WatchChange::Removed
+            ),
+            "echo ${HOME} ''{unknown} {"
+        );
+    }
+}
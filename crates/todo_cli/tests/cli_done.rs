@@ -1,113 +1,77 @@
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
-use time::OffsetDateTime;
-use time::format_description::well_known::Rfc3339;
-
-fn temp_path(file_name: &str) -> PathBuf {
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+use std::process::{Command, Output};
+use time::Duration;
+use todo_core::clock;
+use todo_core::model::{CompletionEntry, TaskStatus};
+use todo_core::storage::json_store::TaskState;
+use todo_core::testing::{FixedClock, Task, TaskBuilder, TempStore};
+
+fn task(title: &str) -> TaskBuilder {
+    todo_core::testing::task("task-1", title).created_at("2025-12-20T00:00:00Z")
 }
 
-fn write_store(path: &PathBuf, tasks: serde_json::Value) {
-    let content = serde_json::json!({
-        "schema_version": 2,
-        "tasks": tasks
-    });
-    std::fs::write(path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+fn store_with(tasks: Vec<TaskBuilder>, focused_task_id: Option<&str>) -> TempStore {
+    TempStore::with_state(&TaskState {
+        tasks: tasks
+            .into_iter()
+            .map(|task| task.build().expect("valid test task"))
+            .collect(),
+        focused_task_id: focused_task_id.map(str::to_string),
+        ..Default::default()
+    })
 }
 
-fn write_store_with_focus(path: &PathBuf, tasks: serde_json::Value, focused_task_id: Option<&str>) {
-    let content = serde_json::json!({
-        "schema_version": 4,
-        "focused_task_id": focused_task_id,
-        "tasks": tasks
-    });
-    std::fs::write(path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+/// Runs with the clock pinned to [`FixedClock::default`], so completion times are known.
+fn run(store: &TempStore, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(args)
+        .env("TODOAPP_STORE_PATH", store.path())
+        .env(clock::NOW_ENV, now())
+        .output()
+        .expect("failed to run done command")
+}
+
+fn now() -> String {
+    FixedClock::default().rfc3339(Duration::ZERO)
+}
+
+fn stored(store: &TempStore) -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string(store.path()).unwrap()).unwrap()
 }
 
 #[test]
 fn done_command_marks_completed_and_records_history() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "old",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "task-1", "ship it"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("old")], None);
+
+    let output = run(&store, &["done", "task-1", "ship it"]);
 
     assert!(output.status.success());
 
-    let stored: serde_json::Value =
-        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
-    std::fs::remove_file(&store_path).ok();
+    let stored = stored(&store);
 
     assert_eq!(stored["tasks"][0]["status"], "completed");
-    assert!(stored["tasks"][0]["completed_at"].is_string());
+    assert_eq!(stored["tasks"][0]["completed_at"], now());
     let history = stored["tasks"][0]["completion_history"]
         .as_array()
         .expect("history array");
     assert_eq!(history.len(), 1);
     assert_eq!(history[0]["message"], "ship it");
-    assert!(history[0]["completed_at"].is_string());
-    OffsetDateTime::parse(
-        history[0]["completed_at"]
-            .as_str()
-            .expect("history completed_at string"),
-        &Rfc3339,
-    )
-    .expect("history completed_at rfc3339");
+    assert_eq!(history[0]["completed_at"], now());
 }
 
 #[test]
 fn done_command_rejects_already_completed() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-completed.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "old",
-                "status": "completed",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null,
-                "completed_at": "2025-12-21T10:00:00Z",
-                "completion_history": [
-                    {
-                        "message": "done",
-                        "completed_at": "2025-12-21T10:00:00Z"
-                    }
-                ]
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "task-1"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = TempStore::with_tasks(vec![Task {
+        status: TaskStatus::Completed,
+        completed_at: Some("2025-12-21T10:00:00Z".to_string()),
+        completion_history: vec![CompletionEntry {
+            message: "done".to_string(),
+            completed_at: "2025-12-21T10:00:00Z".to_string(),
+        }],
+        ..task("old").build().unwrap()
+    }]);
+
+    let output = run(&store, &["done", "task-1"]);
 
-    std::fs::remove_file(&store_path).ok();
     assert!(!output.status.success());
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("ERROR: invalid_input"));
@@ -115,18 +79,10 @@ fn done_command_rejects_already_completed() {
 
 #[test]
 fn done_command_reports_missing_id() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-missing.json");
-
-    write_store(&store_path, serde_json::json!([]));
+    let store = store_with(Vec::new(), None);
 
-    let output = Command::new(exe)
-        .args(["done", "task-1"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let output = run(&store, &["done", "task-1"]);
 
-    std::fs::remove_file(&store_path).ok();
     assert!(!output.status.success());
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("ERROR: invalid_input"));
@@ -134,29 +90,10 @@ fn done_command_reports_missing_id() {
 
 #[test]
 fn done_command_rejects_blank_message() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-blank-message.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "old",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "task-1", "   "])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("old")], None);
+
+    let output = run(&store, &["done", "task-1", "   "]);
 
-    std::fs::remove_file(&store_path).ok();
     assert!(!output.status.success());
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("ERROR: invalid_input"));
@@ -164,29 +101,10 @@ fn done_command_rejects_blank_message() {
 
 #[test]
 fn done_command_plain_text_output() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-text.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "old",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "task-1"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("old")], None);
+
+    let output = run(&store, &["done", "task-1"]);
 
-    std::fs::remove_file(&store_path).ok();
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("Completed task:"));
@@ -194,29 +112,10 @@ fn done_command_plain_text_output() {
 
 #[test]
 fn done_command_json_includes_fields() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-json.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "old",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": "2025-12-21T10:00:00Z"
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["--json", "done", "task-1", "finished"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("old").due_at("2025-12-21T10:00:00Z")], None);
+
+    let output = run(&store, &["--json", "done", "task-1", "finished"]);
 
-    std::fs::remove_file(&store_path).ok();
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     let parsed: serde_json::Value = serde_json::from_str(&stdout).expect("json output");
@@ -226,58 +125,24 @@ fn done_command_json_includes_fields() {
     assert_eq!(parsed["status"], "completed");
     assert_eq!(parsed["created_at"], "2025-12-20T00:00:00Z");
     assert_eq!(parsed["scheduled_at"], "2025-12-21T10:00:00Z");
-    assert!(parsed["completed_at"].is_string());
-    OffsetDateTime::parse(
-        parsed["completed_at"]
-            .as_str()
-            .expect("completed_at string"),
-        &Rfc3339,
-    )
-    .expect("completed_at rfc3339");
+    assert_eq!(parsed["completed_at"], now());
     let history = parsed["completion_history"]
         .as_array()
         .expect("history array");
     assert_eq!(history.len(), 1);
     assert_eq!(history[0]["message"], "finished");
-    assert!(history[0]["completed_at"].is_string());
-    OffsetDateTime::parse(
-        history[0]["completed_at"]
-            .as_str()
-            .expect("history completed_at string"),
-        &Rfc3339,
-    )
-    .expect("history completed_at rfc3339");
+    assert_eq!(history[0]["completed_at"], now());
 }
 
 #[test]
 fn done_command_id_message_flag_records_history() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-id-message.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "flag",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "--message", "ship it", "task-1"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("flag")], None);
+
+    let output = run(&store, &["done", "--message", "ship it", "task-1"]);
 
     assert!(output.status.success());
 
-    let stored: serde_json::Value =
-        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
-    std::fs::remove_file(&store_path).ok();
+    let stored = stored(&store);
 
     let history = stored["tasks"][0]["completion_history"]
         .as_array()
@@ -288,29 +153,10 @@ fn done_command_id_message_flag_records_history() {
 
 #[test]
 fn done_command_rejects_duplicate_message_inputs() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-duplicate-message.json");
-
-    write_store(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "dup",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "task-1", "note", "--message", "ship it"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("dup")], None);
+
+    let output = run(&store, &["done", "task-1", "note", "--message", "ship it"]);
 
-    std::fs::remove_file(&store_path).ok();
     assert!(!output.status.success());
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("ERROR: invalid_input"));
@@ -318,88 +164,38 @@ fn done_command_rejects_duplicate_message_inputs() {
 
 #[test]
 fn done_command_id_clears_focus() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-id-clears-focus.json");
-
-    write_store_with_focus(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "focus",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-        Some("task-1"),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "task-1"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("focus")], Some("task-1"));
+
+    let output = run(&store, &["done", "task-1"]);
 
     assert!(output.status.success());
 
-    let stored: serde_json::Value =
-        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
-    std::fs::remove_file(&store_path).ok();
+    let stored = stored(&store);
 
     assert!(stored["focused_task_id"].is_null());
 }
 
 #[test]
 fn done_command_without_id_uses_focused_task() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-focus.json");
-
-    write_store_with_focus(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "focus",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-        Some("task-1"),
-    );
-
-    let output = Command::new(exe)
-        .args(["done"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("focus")], Some("task-1"));
+
+    let output = run(&store, &["done"]);
 
     assert!(output.status.success());
 
-    let stored: serde_json::Value =
-        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
-    std::fs::remove_file(&store_path).ok();
+    let stored = stored(&store);
 
     assert_eq!(stored["tasks"][0]["status"], "completed");
-    assert!(stored["tasks"][0]["completed_at"].is_string());
+    assert_eq!(stored["tasks"][0]["completed_at"], now());
     assert!(stored["focused_task_id"].is_null());
 }
 
 #[test]
 fn done_command_without_id_rejects_missing_focus() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-no-focus.json");
+    let store = store_with(Vec::new(), None);
 
-    write_store_with_focus(&store_path, serde_json::json!([]), None);
+    let output = run(&store, &["done"]);
 
-    let output = Command::new(exe)
-        .args(["done"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
-
-    std::fs::remove_file(&store_path).ok();
     assert!(!output.status.success());
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("ERROR: invalid_input"));
@@ -407,46 +203,18 @@ fn done_command_without_id_rejects_missing_focus() {
 
 #[test]
 fn done_command_focus_message_flag_records_history() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
-    let store_path = temp_path("cli-done-focus-message.json");
-
-    write_store_with_focus(
-        &store_path,
-        serde_json::json!([
-            {
-                "id": "task-1",
-                "title": "focus",
-                "status": "pending",
-                "created_at": "2025-12-20T00:00:00Z",
-                "scheduled_at": null
-            }
-        ]),
-        Some("task-1"),
-    );
-
-    let output = Command::new(exe)
-        .args(["done", "--message", "ship it"])
-        .env("TODOAPP_STORE_PATH", &store_path)
-        .output()
-        .expect("failed to run done command");
+    let store = store_with(vec![task("focus")], Some("task-1"));
+
+    let output = run(&store, &["done", "--message", "ship it"]);
 
     assert!(output.status.success());
 
-    let stored: serde_json::Value =
-        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
-    std::fs::remove_file(&store_path).ok();
+    let stored = stored(&store);
 
     let history = stored["tasks"][0]["completion_history"]
         .as_array()
         .expect("history array");
     assert_eq!(history.len(), 1);
     assert_eq!(history[0]["message"], "ship it");
-    assert!(history[0]["completed_at"].is_string());
-    OffsetDateTime::parse(
-        history[0]["completed_at"]
-            .as_str()
-            .expect("history completed_at string"),
-        &Rfc3339,
-    )
-    .expect("history completed_at rfc3339");
+    assert_eq!(history[0]["completed_at"], now());
 }
@@ -12,7 +12,7 @@ fn temp_path(file_name: &str) -> PathBuf {
 }
 
 fn run_interactive(input: &str) -> std::process::Output {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-interactive.json");
 
     let mut child = Command::new(exe)
@@ -69,3 +69,231 @@ fn interactive_add_command_succeeds() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("Added task:"));
 }
+
+#[test]
+fn interactive_session_project_applies_to_add_and_list() {
+    let output = run_interactive(
+        "add \"Unfiled\"\nset project work\nadd \"Write spec\"\n--json list backlog\nunset project\nadd \"Loose end\"\nexit\n",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Session project: work"));
+    assert!(stdout.contains("Cleared session project."));
+    let listed: serde_json::Value = stdout
+        .lines()
+        .find(|line| line.starts_with('['))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .expect("list output");
+    let titles: Vec<&str> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Write spec"]);
+    assert_eq!(listed[0]["project"], "work");
+}
+
+#[test]
+fn interactive_changes_are_journaled_until_flush_or_exit() {
+    let store_path = temp_path("cli-interactive-autosave.json");
+    let mut journal_path = store_path.clone().into_os_string();
+    journal_path.push(".journal");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn interactive session");
+    child
+        .stdin
+        .as_mut()
+        .expect("stdin")
+        .write_all(b"add \"First\"\nadd \"Second\"\nflush\nflush\nadd \"Third\"\nexit\n")
+        .expect("failed to write to stdin");
+    let output = child
+        .wait_with_output()
+        .expect("failed to read interactive output");
+    let journal_left = std::path::Path::new(&journal_path).exists();
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Saved pending changes to the store.\nNothing to save.\n"),
+        "{stdout}"
+    );
+    assert!(!journal_left);
+    let titles: Vec<&str> = stored["tasks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["First", "Second", "Third"]);
+}
+
+#[test]
+fn recorded_session_replays_with_the_same_ids_into_a_fresh_store() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let (recorded_store, replayed_store) =
+        (temp_path("cli-record.json"), temp_path("cli-replay.json"));
+    let transcript = temp_path("cli-record.log");
+    let mut child = Command::new(exe)
+        .args(["--record", transcript.to_str().unwrap()])
+        .env("TODOAPP_STORE_PATH", &recorded_store)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn interactive session");
+    child
+        .stdin
+        .as_mut()
+        .expect("stdin")
+        .write_all(b"set project work\nadd \"Buy milk\"\n--json list backlog\nexit\n")
+        .expect("failed to write to stdin");
+    let recorded = child
+        .wait_with_output()
+        .expect("failed to read interactive output");
+    let replay = |store: &PathBuf| {
+        Command::new(exe)
+            .args(["run", transcript.to_str().unwrap(), "--replay"])
+            .env("TODOAPP_STORE_PATH", store)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to replay")
+    };
+    let replayed = replay(&replayed_store);
+    let tasks = |store: &PathBuf| -> serde_json::Value {
+        let state: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(store).unwrap()).unwrap();
+        state["tasks"].clone()
+    };
+    let (original, copy) = (tasks(&recorded_store), tasks(&replayed_store));
+    // Replayed again, the id is taken, so the new task gets another and both lines differ.
+    let diverged = replay(&replayed_store);
+    let log = std::fs::read_to_string(&transcript).unwrap();
+    for path in [&recorded_store, &replayed_store, &transcript] {
+        std::fs::remove_file(path).ok();
+    }
+
+    assert!(recorded.status.success());
+    assert!(String::from_utf8_lossy(&recorded.stdout).contains("Added task: Buy milk ("));
+    assert!(log.contains("> set project work\n@ "));
+    assert!(log.contains("> add \"Buy milk\"\n| Added task: Buy milk ("));
+    assert!(
+        replayed.status.success(),
+        "{}",
+        String::from_utf8_lossy(&replayed.stderr)
+    );
+    assert!(
+        String::from_utf8_lossy(&replayed.stdout)
+            .ends_with("Replayed 2 commands; all printed what they did when recorded.\n")
+    );
+    assert_eq!(copy[0]["id"], original[0]["id"]);
+    assert_eq!(copy[0]["created_at"], original[0]["created_at"]);
+    assert_eq!(copy[0]["project"], "work");
+    assert!(!diverged.status.success());
+    let stderr = String::from_utf8_lossy(&diverged.stderr);
+    assert!(stderr.contains(
+        "WARNING: step 2 (`add \"Buy milk\"`) printed something different than when recorded"
+    ));
+    assert!(
+        stderr.contains("2 of 2 replayed commands printed something different than when recorded")
+    );
+}
+
+#[test]
+fn triage_moves_each_overdue_deadline_with_one_key_and_saves_once() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-triage.json");
+    // 2025-12-22 is a Monday.
+    let todo = |args: &[&str], input: &str| {
+        let mut child = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_NOW", "2025-12-22T10:00:00Z")
+            .env("TZ", "UTC")
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to spawn todo_opt");
+        child
+            .stdin
+            .as_mut()
+            .expect("stdin")
+            .write_all(input.as_bytes())
+            .expect("failed to write to stdin");
+        child.wait_with_output().expect("failed to run todo_opt")
+    };
+    let add = |title: &str, due: &str| {
+        let added = todo(&["--json", "add", title], "");
+        let task: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+        let id = task["id"].as_str().unwrap().to_string();
+        assert!(
+            todo(&["schedule", &id, due, "--allow-past"], "")
+                .status
+                .success()
+        );
+        id
+    };
+    let report = add("Report", "2025-12-19 09:00:00");
+    let call = add("Call", "2025-12-20 16:00:00");
+    let invoices = add("Invoices", "2025-12-21 08:00:00");
+    add("Later", "2025-12-23 09:00:00");
+    let due_at = |id: &str| {
+        let state: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+        let task = state["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|task| task["id"] == id)
+            .unwrap()
+            .clone();
+        task["due_at"].as_str().unwrap().to_string()
+    };
+
+    let abandoned = todo(&["triage"], "m\n");
+    let call_after_abandon = due_at(&call);
+    let output = todo(&["triage"], "x\nt\ns\nw\n");
+    let moved = [due_at(&report), due_at(&call), due_at(&invoices)];
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(!abandoned.status.success());
+    assert!(
+        String::from_utf8_lossy(&abandoned.stderr)
+            .contains("no answer given; nothing was rescheduled")
+    );
+    assert_eq!(call_after_abandon, "2025-12-20T16:00:00Z");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[1/3] Report ("), "{stderr}");
+    assert!(stderr.contains("Answer t, m, w, s, or q."));
+    assert!(!stderr.contains("Later"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("Rescheduled 2 of 3 overdue tasks:\n"),
+        "{stdout}"
+    );
+    assert_eq!(
+        moved,
+        [
+            "2025-12-22T23:59:59Z",
+            "2025-12-20T16:00:00Z",
+            "2025-12-29T08:00:00Z"
+        ]
+    );
+}
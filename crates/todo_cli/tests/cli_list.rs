@@ -26,7 +26,7 @@ fn local_now_strings() -> (String, String, String) {
 
 #[test]
 fn list_today_plain_text_filters_tasks() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-list-today.json");
     let (yesterday, today, tomorrow) = local_now_strings();
 
@@ -76,7 +76,7 @@ fn list_today_plain_text_filters_tasks() {
 
 #[test]
 fn list_backlog_json_filters_tasks() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-list-backlog.json");
     let (_, today, tomorrow) = local_now_strings();
 
@@ -124,7 +124,7 @@ fn list_backlog_json_filters_tasks() {
 
 #[test]
 fn list_today_json_marks_overdue() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-list-today-json.json");
     let (yesterday, today, _) = local_now_strings();
 
@@ -166,12 +166,536 @@ fn list_today_json_marks_overdue() {
         .iter()
         .find(|task| task["id"] == "task-1")
         .expect("overdue task present");
-    assert!(overdue["status"].as_str().unwrap_or("").contains("overdue"));
+    assert_eq!(overdue["status"], "pending");
+    assert_eq!(overdue["overdue"], true);
+}
+
+#[test]
+fn list_today_legacy_json_folds_overdue_into_status() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-today-legacy-json.json");
+    let (yesterday, _, _) = local_now_strings();
+
+    let content = serde_json::json!({
+        "schema_version": 2,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "overdue task",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": yesterday
+            }
+        ]
+    });
+
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let output = Command::new(exe)
+        .args(["--legacy-json", "list", "today"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run list today command");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json output");
+    let task = &parsed[0];
+    assert_eq!(task["status"], "pending (overdue)");
+    assert!(task.get("overdue").is_none());
+}
+
+#[test]
+fn list_today_compat_0_matches_legacy_json_and_both_warn() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-today-compat.json");
+    let (yesterday, _, _) = local_now_strings();
+
+    let content = serde_json::json!({
+        "schema_version": 2,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "overdue task",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": yesterday
+            }
+        ]
+    });
+
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .output()
+            .expect("failed to run list today command")
+    };
+    let legacy = run(&["--legacy-json", "list", "today"]);
+    let compat = run(&["--json", "--compat", "0", "list", "today"]);
+    let current = run(&["--json", "--compat", "1", "list", "today"]);
+    let unknown = run(&["--json", "--compat", "9", "list", "today"]);
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(legacy.status.success());
+    assert!(compat.status.success());
+    assert_eq!(legacy.stdout, compat.stdout);
+    assert!(
+        String::from_utf8_lossy(&legacy.stderr).contains("WARNING: --legacy-json is deprecated")
+    );
+    assert!(String::from_utf8_lossy(&legacy.stderr).contains("use --json --compat 0"));
+    assert!(String::from_utf8_lossy(&compat.stderr).contains("WARNING: --compat 0"));
+    let parsed: serde_json::Value = serde_json::from_slice(&current.stdout).expect("json output");
+    assert_eq!(parsed[0]["overdue"], true);
+    assert!(current.stderr.is_empty());
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("unsupported version '9'"));
+}
+
+#[test]
+fn list_today_keys_off_start_while_overdue_keys_off_due() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-start-due.json");
+    let (yesterday, today, tomorrow) = local_now_strings();
+
+    let content = serde_json::json!({
+        "schema_version": 17,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "start today, due tomorrow",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": tomorrow
+            },
+            {
+                "id": "task-2",
+                "title": "start tomorrow, due yesterday",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "start_at": tomorrow,
+                "due_at": yesterday
+            },
+            {
+                "id": "task-3",
+                "title": "scheduled before the split",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": yesterday
+            }
+        ]
+    });
+
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .output()
+            .expect("failed to run command");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("json output")
+    };
+
+    let planned = run(&["--json", "schedule", "task-1", &today, "--start"]);
+    let today_list = run(&["--json", "list", "today"]);
+    let backlog = run(&["--json", "list", "backlog"]);
+
+    std::fs::remove_file(&store_path).ok();
+    assert_eq!(planned["start_at"], today);
+    assert_eq!(planned["due_at"], tomorrow);
+
+    let summary = |list: &serde_json::Value| -> Vec<(String, bool)> {
+        list.as_array()
+            .expect("json array")
+            .iter()
+            .map(|task| {
+                (
+                    task["id"].as_str().unwrap().to_string(),
+                    task["overdue"].as_bool().unwrap(),
+                )
+            })
+            .collect()
+    };
+    let mut today_summary = summary(&today_list);
+    today_summary.sort();
+    assert_eq!(
+        today_summary,
+        [("task-1".to_string(), false), ("task-3".to_string(), true)]
+    );
+    assert_eq!(summary(&backlog), [("task-2".to_string(), true)]);
+    assert_eq!(
+        today_list
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|task| task["id"] == "task-3")
+            .unwrap()["due_at"],
+        yesterday
+    );
+}
+
+#[test]
+fn list_today_leaves_tasks_within_overdue_grace_on_time() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-grace.json");
+    let config_path = temp_path("cli-list-grace-config.json");
+    let now = OffsetDateTime::now_utc();
+    let ago = |minutes: i64| (now - Duration::minutes(minutes)).format(&Rfc3339).unwrap();
+
+    let content = serde_json::json!({
+        "schema_version": 18,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "just due",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "due_at": ago(5)
+            },
+            {
+                "id": "task-2",
+                "title": "well past due",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "due_at": ago(20)
+            }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let list = |config: serde_json::Value| {
+        std::fs::write(&config_path, config.to_string()).unwrap();
+        let output = Command::new(exe)
+            .args(["--json", "list", "today", "--status", "overdue"])
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .output()
+            .expect("failed to run list today command");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("json output");
+        parsed
+            .as_array()
+            .expect("json array")
+            .iter()
+            .map(|task| task["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let without_grace = list(serde_json::json!({}));
+    let with_grace = list(serde_json::json!({ "overdue_grace": "15m" }));
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    assert_eq!(without_grace, ["task-2", "task-1"]);
+    assert_eq!(with_grace, ["task-2"]);
+}
+
+#[test]
+fn list_counts_down_to_deadlines_within_due_soon_within() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-due-soon.json");
+    let config_path = temp_path("cli-list-due-soon-config.json");
+    let now = OffsetDateTime::now_utc();
+    let from_now = |minutes: i64| (now + Duration::minutes(minutes)).format(&Rfc3339).unwrap();
+
+    let content = serde_json::json!({
+        "schema_version": 18,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "due soon",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "start_at": from_now(-60),
+                "due_at": from_now(90)
+            },
+            {
+                "id": "task-2",
+                "title": "due later",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "start_at": from_now(-60),
+                "due_at": from_now(180)
+            }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+    std::fs::write(&config_path, r#"{ "due_soon_within": "2h" }"#).unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .output()
+            .expect("failed to run list today command");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let json = run(&["--json", "list", "today"]);
+    let plain = run(&["list", "today"]);
+    let accessible = run(&["--accessible", "list", "today"]);
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    let parsed: serde_json::Value = serde_json::from_str(&json).expect("json output");
+    let tasks = parsed.as_array().expect("json array");
+    let soon = tasks.iter().find(|task| task["id"] == "task-1").unwrap();
+    let later = tasks.iter().find(|task| task["id"] == "task-2").unwrap();
+    let seconds = soon["due_in_seconds"].as_i64().unwrap();
+    assert!((5340..=5400).contains(&seconds), "{seconds}");
+    assert_eq!(soon["overdue"], false);
+    assert!(later["due_in_seconds"].is_null());
+    assert!(plain.contains("pending (due in 1h30m)"), "{plain}");
+    assert_eq!(plain.matches("due in").count(), 1);
+    assert!(accessible.contains("\nDUE IN 1h30m\n"), "{accessible}");
+}
+
+#[test]
+fn list_week_iso_selects_tasks_scheduled_that_week() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-week.json");
+
+    let content = serde_json::json!({
+        "schema_version": 2,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "sunday before",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": "2026-02-08T12:00:00Z"
+            },
+            {
+                "id": "task-2",
+                "title": "in week 7",
+                "status": "completed",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": "2026-02-11T12:00:00Z"
+            },
+            {
+                "id": "task-3",
+                "title": "next quarter",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": "2026-04-02T12:00:00Z"
+            }
+        ]
+    });
+
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let list = |args: &[&str]| {
+        Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .output()
+            .expect("failed to run list command")
+    };
+    let ids = |output: &std::process::Output| -> Vec<String> {
+        assert!(output.status.success());
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("json output");
+        parsed
+            .as_array()
+            .expect("json array")
+            .iter()
+            .map(|task| task["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let week = list(&["--json", "list", "week", "--iso", "2026-W07"]);
+    let quarter = list(&["--json", "list", "quarter", "--iso", "2026-Q1"]);
+    let bad = list(&["list", "week", "--iso", "2026-07"]);
+
+    std::fs::remove_file(&store_path).ok();
+    assert_eq!(ids(&week), ["task-2"]);
+    assert_eq!(ids(&quarter), ["task-1", "task-2"]);
+    assert!(!bad.status.success());
+    let stderr = String::from_utf8_lossy(&bad.stderr);
+    assert!(stderr.contains("invalid week '2026-07'"));
+}
+
+#[test]
+fn upcoming_groups_open_tasks_by_day_from_today() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-upcoming.json");
+    let task = |id: &str, status: &str, due_at: &str| {
+        serde_json::json!({
+            "id": id,
+            "title": id,
+            "status": status,
+            "created_at": "2025-12-20T00:00:00Z",
+            "due_at": due_at
+        })
+    };
+    // Monday 2025-12-22, 08:00 UTC.
+    let content = serde_json::json!({
+        "schema_version": 18,
+        "tasks": [
+            task("sunday", "pending", "2025-12-21T10:00:00Z"),
+            task("wednesday", "pending", "2025-12-24T09:00:00Z"),
+            task("monday-late", "pending", "2025-12-22T17:00:00Z"),
+            task("monday-early", "in_progress", "2025-12-22T10:00:00Z"),
+            task("done", "completed", "2025-12-23T10:00:00Z"),
+            task("next-week", "pending", "2025-12-29T10:00:00Z")
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let upcoming = |args: &[&str]| {
+        let output = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_NOW", "2025-12-22T08:00:00Z")
+            .env("TZ", "UTC")
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("failed to run upcoming");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let plain = upcoming(&["upcoming"]);
+    let fortnight: serde_json::Value =
+        serde_json::from_str(&upcoming(&["--json", "upcoming", "--days", "14"])).unwrap();
+    let one_day = upcoming(&["upcoming", "--days", "1"]);
+    std::fs::remove_file(&store_path).ok();
+
+    let headings: Vec<&str> = plain
+        .lines()
+        .filter(|line| line.ends_with(')') && line.contains('/'))
+        .collect();
+    assert_eq!(
+        headings,
+        ["Monday 22/12/2025 (today) (2)", "Wednesday 24/12/2025 (1)"]
+    );
+    assert!(plain.find("monday-early").unwrap() < plain.find("monday-late").unwrap());
+    assert!(!plain.contains("sunday") && !plain.contains("done") && !plain.contains("next-week"));
+    assert!(plain.ends_with("Total: 3 tasks\n"));
+    assert_eq!(fortnight["group_by"], "day");
+    assert_eq!(fortnight["total"], 4);
+    assert_eq!(fortnight["groups"][2]["key"], "2025-12-29");
+    assert!(one_day.contains("Monday 22/12/2025 (today)"));
+}
+
+#[test]
+fn list_today_splits_into_time_of_day_sections() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-sections.json");
+    let config_path = temp_path("cli-list-sections-config.json");
+    let task = |id: &str, due_at: &str| {
+        serde_json::json!({
+            "id": id,
+            "title": id,
+            "status": "pending",
+            "created_at": "2025-12-20T00:00:00Z",
+            "due_at": due_at
+        })
+    };
+    let content = serde_json::json!({
+        "schema_version": 18,
+        "tasks": [
+            task("dinner", "2025-12-22T18:30:00Z"),
+            task("stand-up", "2025-12-22T09:00:00Z"),
+            task("review", "2025-12-21T13:00:00Z")
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let list = |config: serde_json::Value, args: &[&str]| {
+        std::fs::write(&config_path, config.to_string()).unwrap();
+        let output = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TODOAPP_NOW", "2025-12-22T08:00:00Z")
+            .env("TZ", "UTC")
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("failed to run list today");
+        (
+            output.status.success(),
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    let (_, plain, _) = list(serde_json::json!({}), &["list", "today"]);
+    let (_, json, _) = list(
+        serde_json::json!({ "day_sections": { "evening": "13:00" } }),
+        &["--json", "list", "today"],
+    );
+    let (_, flat, _) = list(
+        serde_json::json!({ "day_sections": { "enabled": false } }),
+        &["list", "today"],
+    );
+    let (ok, _, error) = list(
+        serde_json::json!({ "day_sections": { "evening": "11:00" } }),
+        &["list", "today"],
+    );
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    let headings: Vec<&str> = plain
+        .lines()
+        .filter(|line| line.ends_with(')') && !line.contains('|'))
+        .collect();
+    assert_eq!(headings, ["Morning (1)", "Afternoon (1)", "Evening (1)"]);
+    assert!(plain.find("stand-up").unwrap() < plain.find("review").unwrap());
+    assert!(plain.ends_with("Total: 3 tasks\n"));
+    let sections: Vec<(String, serde_json::Value)> =
+        serde_json::from_str::<serde_json::Value>(&json)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| {
+                (
+                    task["id"].as_str().unwrap().to_string(),
+                    task["section"].clone(),
+                )
+            })
+            .collect();
+    let expected = [
+        ("review", "evening"),
+        ("stand-up", "morning"),
+        ("dinner", "evening"),
+    ];
+    assert_eq!(
+        sections,
+        expected.map(|(id, section)| (id.to_string(), serde_json::json!(section)))
+    );
+    assert!(!flat.contains("Morning") && flat.contains("stand-up"));
+    assert!(!ok);
+    assert!(
+        error.contains("day_sections.evening must be later than day_sections.afternoon"),
+        "{error}"
+    );
 }
 
 #[test]
 fn list_reports_invalid_scheduled_at() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-list-invalid.json");
 
     let content = serde_json::json!({
@@ -203,7 +727,7 @@ fn list_reports_invalid_scheduled_at() {
 
 #[test]
 fn list_today_places_focused_task_first_with_prefix() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-list-focus.json");
     let (_, today, tomorrow) = local_now_strings();
 
@@ -246,16 +770,18 @@ fn list_today_places_focused_task_first_with_prefix() {
     std::fs::remove_file(&store_path).ok();
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut lines = stdout.lines();
-    let first_line = lines.next().unwrap_or("");
-    assert!(first_line.starts_with("[FOCUS] "));
-    assert!(first_line.contains("task-2"));
+    let first_row = stdout
+        .lines()
+        .find(|line| line.contains("task-"))
+        .unwrap_or("");
+    assert!(first_row.contains('*'));
+    assert!(first_row.contains("task-2"));
     assert!(stdout.contains("today task"));
 }
 
 #[test]
 fn list_today_does_not_show_focus_prefix_when_focused_task_missing() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-list-focus-missing.json");
     let (_, today, tomorrow) = local_now_strings();
 
@@ -294,3 +820,353 @@ fn list_today_does_not_show_focus_prefix_when_focused_task_missing() {
     assert!(!stdout.contains("[FOCUS]"));
     assert!(stdout.contains("today task"));
 }
+
+fn write_completion_store(store_path: &PathBuf) {
+    let content = serde_json::json!({
+        "schema_version": 7,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "urgent task",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "urgent": true
+            },
+            {
+                "id": "task-2",
+                "title": "finished task",
+                "status": "completed",
+                "created_at": "2025-12-20T00:00:00Z",
+                "completed_at": "2025-12-21T10:00:00Z",
+                "completion_history": [
+                    { "message": "first pass", "completed_at": "2025-12-20T10:00:00Z" },
+                    { "message": "shipped", "completed_at": "2025-12-21T10:00:00Z" }
+                ]
+            }
+        ]
+    });
+    std::fs::write(store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+}
+
+#[test]
+fn list_json_includes_urgency_and_completion_info() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-json-completion.json");
+    write_completion_store(&store_path);
+
+    let output = Command::new(exe)
+        .args(["--json", "list", "backlog"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run list backlog command");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json output");
+    let tasks = parsed.as_array().expect("json array");
+    assert_eq!(tasks[0]["urgent"], true);
+    assert!(tasks[0]["completed_at"].is_null());
+    assert_eq!(tasks[1]["urgent"], false);
+    assert_eq!(tasks[1]["completed_at"], "2025-12-21T10:00:00Z");
+    assert_eq!(tasks[1]["completion_history"][1]["message"], "shipped");
+}
+
+#[test]
+fn list_wide_adds_urgency_and_completion_columns() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-wide.json");
+    write_completion_store(&store_path);
+
+    let narrow = Command::new(exe)
+        .args(["list", "backlog"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run list backlog command");
+    let wide = Command::new(exe)
+        .args(["list", "backlog", "--wide"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run list backlog --wide command");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(narrow.status.success());
+    assert!(wide.status.success());
+    let narrow = String::from_utf8_lossy(&narrow.stdout);
+    let wide = String::from_utf8_lossy(&wide.stdout);
+    assert!(!narrow.contains("Urgent"));
+    assert!(wide.contains("Urgent"));
+    assert!(wide.contains("History"));
+    let finished_row = wide
+        .lines()
+        .find(|line| line.contains("task-2"))
+        .expect("finished task row");
+    assert!(finished_row.contains("shipped (+1 earlier)"));
+}
+
+fn write_grouping_store(store_path: &PathBuf) {
+    let content = serde_json::json!({
+        "schema_version": 8,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "write spec",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "tags": ["work", "docs"],
+                "project": "launch"
+            },
+            {
+                "id": "task-2",
+                "title": "water plants",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z"
+            },
+            {
+                "id": "task-3",
+                "title": "book venue",
+                "status": "completed",
+                "created_at": "2025-12-20T00:00:00Z",
+                "tags": ["work"],
+                "project": "launch"
+            }
+        ]
+    });
+    std::fs::write(store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+}
+
+#[test]
+fn list_group_by_tag_prints_sections_with_subtotals() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-group-tag.json");
+    write_grouping_store(&store_path);
+
+    let output = Command::new(exe)
+        .args(["list", "backlog", "--group-by", "tag"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run list backlog --group-by tag");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let headers: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.ends_with(')') && !line.contains('│'))
+        .collect();
+    assert_eq!(headers, vec!["docs (1)", "work (2)", "(no tag) (1)"]);
+    assert!(stdout.trim_end().ends_with("Total: 3 tasks"));
+}
+
+#[test]
+fn list_group_by_project_json_nests_tasks() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-group-project.json");
+    write_grouping_store(&store_path);
+
+    let output = Command::new(exe)
+        .args(["--json", "list", "backlog", "--group-by", "project"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run list backlog --group-by project");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json output");
+    assert_eq!(parsed["group_by"], "project");
+    assert_eq!(parsed["total"], 3);
+    assert_eq!(parsed["groups"][0]["key"], "launch");
+    assert_eq!(parsed["groups"][0]["count"], 2);
+    assert_eq!(parsed["groups"][0]["tasks"][1]["id"], "task-3");
+    assert_eq!(parsed["groups"][0]["tasks"][1]["project"], "launch");
+    assert!(parsed["groups"][1]["key"].is_null());
+    assert_eq!(parsed["groups"][1]["tasks"][0]["id"], "task-2");
+}
+
+#[test]
+fn list_and_show_fit_long_titles_to_the_width() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-width.json");
+    let long_title =
+        "Write the quarterly planning document covering hiring, budget, and the offsite agenda";
+    let content = serde_json::json!({
+        "schema_version": 18,
+        "tasks": [
+            { "id": "long", "title": long_title, "status": "pending", "created_at": "2025-12-20T00:00:00Z" },
+            { "id": "short", "title": "Buy milk", "status": "pending", "created_at": "2025-12-20T00:00:00Z" }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TZ", "UTC")
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("failed to run todo");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let fitted = run(&["list", "backlog", "--width", "90"]);
+    let piped = run(&["list", "backlog"]);
+    let shown = run(&["show", "long", "--width", "50"]);
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(
+        fitted.lines().all(|line| line.chars().count() <= 90),
+        "{fitted}"
+    );
+    assert!(fitted.contains("│ Write the quarterly p… │"), "{fitted}");
+    assert!(fitted.contains("Buy milk"));
+    // Without --width, output that isn't a terminal keeps whole titles.
+    assert!(piped.contains(long_title), "{piped}");
+    let title: Vec<&str> = shown
+        .lines()
+        .take_while(|line| !line.starts_with("ID"))
+        .collect();
+    assert_eq!(
+        title,
+        [
+            "Title      Write the quarterly planning document",
+            "           covering hiring, budget, and the",
+            "           offsite agenda",
+        ]
+    );
+}
+
+#[test]
+fn tables_drop_columns_to_fit_the_width() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-narrow.json");
+    let csv_path = temp_path("cli-list-narrow.csv");
+    let content = serde_json::json!({
+        "schema_version": 18,
+        "tasks": [
+            {
+                "id": "long",
+                "title": "Write the quarterly planning document covering hiring and budget",
+                "status": "in_progress",
+                "created_at": "2025-12-20T00:00:00Z",
+                "start_at": "2025-12-22T09:00:00Z",
+                "due_at": "2025-12-22T17:00:00Z",
+                "urgent": true,
+                "completion_history": [{ "completed_at": "2025-12-19T10:00:00Z", "message": "Reopened after the first draft" }]
+            },
+            { "id": "short", "title": "Buy milk", "status": "pending", "created_at": "2025-12-20T00:00:00Z", "due_at": "2025-12-23T08:00:00Z" }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+    std::fs::write(
+        &csv_path,
+        "title,due,tags,project\nRenew the passport before the trip to Lisbon,2026-01-03,admin travel,household\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TZ", "UTC")
+            .env("NO_COLOR", "1")
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run todo");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let csv = csv_path.to_str().unwrap();
+    let mut outputs = Vec::new();
+    for width in [40, 50, 64, 80, 200] {
+        let width_arg = width.to_string();
+        for args in [
+            &["list", "week", "--iso", "2025-W52"][..],
+            &["list", "week", "--iso", "2025-W52", "--wide"],
+            &["import", "--format", "csv", "--dry-run", csv],
+        ] {
+            let args = [&["--width", width_arg.as_str()][..], args].concat();
+            outputs.push((width, run(&args)));
+        }
+    }
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&csv_path).ok();
+
+    for (width, output) in &outputs {
+        let mut table = output
+            .lines()
+            .filter(|line| line.starts_with(['┌', '│', '├', '└']));
+        assert!(
+            table.all(|line| line.chars().count() <= *width),
+            "wider than {width}:\n{output}"
+        );
+    }
+    // The narrowest tables keep IDs and the start of each title; wider ones get columns back.
+    let (_, narrowest) = &outputs[0];
+    assert!(narrowest.contains("│ long "), "{narrowest}");
+    assert!(narrowest.contains("│ Write the"), "{narrowest}");
+    assert!(!narrowest.contains("Created"), "{narrowest}");
+    let (_, widest) = &outputs[outputs.len() - 2];
+    assert!(
+        widest.contains("Created") && widest.contains("History"),
+        "{widest}"
+    );
+}
+
+#[test]
+fn pager_leaves_output_that_is_not_a_terminal_alone() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-list-pager.json");
+    let config_path = temp_path("cli-list-pager-config.json");
+    let tasks: Vec<serde_json::Value> = (0..80)
+        .map(|n| {
+            serde_json::json!({
+                "id": format!("t{n}"),
+                "title": format!("Task {n}"),
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z"
+            })
+        })
+        .collect();
+    std::fs::write(
+        &store_path,
+        serde_json::json!({ "schema_version": 18, "tasks": tasks }).to_string(),
+    )
+    .unwrap();
+
+    let list = |pager: bool| {
+        std::fs::write(
+            &config_path,
+            serde_json::json!({ "pager": pager }).to_string(),
+        )
+        .unwrap();
+        let output = Command::new(exe)
+            .args(["list", "backlog"])
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TZ", "UTC")
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("failed to run list backlog");
+        assert!(output.status.success());
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    let (paged, warnings) = list(true);
+    let (plain, _) = list(false);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert_eq!(paged, plain);
+    assert!(!paged.contains('\x1b'));
+    assert!(warnings.is_empty(), "{warnings}");
+}
@@ -0,0 +1,398 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+fn temp_path(file_name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+}
+
+fn write_store(path: &PathBuf, tasks: serde_json::Value) {
+    let content = serde_json::json!({
+        "schema_version": 5,
+        "tasks": tasks
+    });
+    std::fs::write(path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+}
+
+#[test]
+fn notify_command_plain_text_outputs_notified_tasks() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-notify-text.json");
+    let past = (OffsetDateTime::now_utc() - Duration::days(1))
+        .format(&Rfc3339)
+        .unwrap();
+
+    write_store(
+        &store_path,
+        serde_json::json!([
+            {
+                "id": "task-1",
+                "title": "overdue",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": past,
+                "urgent": false
+            },
+            {
+                "id": "task-2",
+                "title": "urgent",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null,
+                "urgent": true
+            }
+        ]),
+    );
+
+    let output = Command::new(exe)
+        .args(["notify"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env(
+            "TODOAPP_CONFIG_PATH",
+            temp_path("cli-notify-no-config.json"),
+        )
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run notify command");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Notified task: overdue (task-1)"));
+    assert!(stdout.contains("Notified task: urgent (task-2)"));
+}
+
+#[test]
+fn notify_command_sends_digest_when_threshold_reached() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-notify-digest.json");
+    let config_path = temp_path("cli-notify-digest-config.json");
+    let past = (OffsetDateTime::now_utc() - Duration::days(1))
+        .format(&Rfc3339)
+        .unwrap();
+
+    write_store(
+        &store_path,
+        serde_json::json!([
+            {
+                "id": "task-1",
+                "title": "overdue",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": past,
+                "urgent": false
+            },
+            {
+                "id": "task-2",
+                "title": "urgent",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null,
+                "urgent": true
+            }
+        ]),
+    );
+    std::fs::write(
+        &config_path,
+        serde_json::json!({ "notifications": { "digest_after": 2 } }).to_string(),
+    )
+    .unwrap();
+
+    let output = Command::new(exe)
+        .args(["notify"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_CONFIG_PATH", &config_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run notify command");
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Sent digest notification: 2 tasks need attention"));
+    assert!(stdout.contains("top: overdue, urgent"));
+    assert!(!stdout.contains("Notified task:"));
+}
+
+#[test]
+fn notify_command_console_backend_prints_to_stderr() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-notify-console.json");
+    let config_path = temp_path("cli-notify-console-config.json");
+
+    write_store(
+        &store_path,
+        serde_json::json!([
+            {
+                "id": "task-2",
+                "title": "urgent",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null,
+                "urgent": true
+            }
+        ]),
+    );
+    std::fs::write(
+        &config_path,
+        serde_json::json!({ "notifications": { "backend": "console" } }).to_string(),
+    )
+    .unwrap();
+
+    let output = Command::new(exe)
+        .args(["notify"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_CONFIG_PATH", &config_path)
+        .output()
+        .expect("failed to run notify command");
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[todoapp] urgent (task-2) [urgent]"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Notified task: urgent (task-2)"));
+}
+
+#[test]
+fn notify_install_schedule_rejects_bad_interval() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+
+    let output = Command::new(exe)
+        .args(["notify", "install-schedule", "--every", "often"])
+        .env(
+            "TODOAPP_CONFIG_PATH",
+            temp_path("cli-notify-install-config.json"),
+        )
+        .output()
+        .expect("failed to run notify install-schedule");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ERROR: invalid_input - interval must look like '15m' or '1h'"));
+}
+
+#[test]
+fn notify_log_appends_a_json_line_per_run() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-notify-log.json");
+    let log_path = temp_path("cli-notify-log.ndjson");
+    write_store(
+        &store_path,
+        serde_json::json!([
+            {
+                "id": "task-1",
+                "title": "urgent",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null,
+                "urgent": true
+            }
+        ]),
+    );
+
+    let notify = || {
+        Command::new(exe)
+            .args(["notify", "--log"])
+            .arg(&log_path)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env(
+                "TODOAPP_CONFIG_PATH",
+                temp_path("cli-notify-log-config.json"),
+            )
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run notify command")
+    };
+    let first = notify();
+    std::fs::write(&store_path, "not json").unwrap();
+    let second = notify();
+    let log = std::fs::read_to_string(&log_path).unwrap_or_default();
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&log_path).ok();
+    assert!(first.status.success());
+    assert!(!second.status.success());
+    let records: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["notified"], serde_json::json!(["task-1"]));
+    assert!(records[0]["duration_ms"].is_u64());
+    assert!(records[0]["error"].is_null());
+    assert_eq!(records[1]["error"]["code"], "invalid_data");
+}
+
+#[test]
+fn notify_test_reports_each_destination_and_fails_if_one_does() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let config_path = temp_path("cli-notify-test-config.json");
+    let config = serde_json::json!({
+        "notifications": {
+            "backend": "console",
+            "routes": [{ "project": "work", "backend": "webhook" }]
+        }
+    });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    let output = Command::new(exe)
+        .args(["notify", "test"])
+        .env("TODOAPP_CONFIG_PATH", &config_path)
+        .env("TODOAPP_STORE_PATH", temp_path("cli-notify-test.json"))
+        .env_remove("TODOAPP_DISABLE_NOTIFICATIONS")
+        .output()
+        .expect("failed to run notify test");
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[todoapp] Test notification: todo can reach you here"));
+    assert!(stdout.contains("ok      default (console)"));
+    assert!(stdout.contains(
+        "failed  project:work (webhook): the webhook notification backend needs a webhook_url"
+    ));
+    assert!(stderr.contains("1 of 2 notification destinations failed"));
+}
+
+#[test]
+fn notify_register_windows_app_checks_the_app_id_before_registering() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let config_path = temp_path("cli-notify-register-config.json");
+    let config = serde_json::json!({
+        "notifications": { "windows": { "app_id": "My Todo" } }
+    });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    let output = Command::new(exe)
+        .args(["notify", "register-windows-app"])
+        .env("TODOAPP_CONFIG_PATH", &config_path)
+        .output()
+        .expect("failed to run notify register-windows-app");
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(
+        "ERROR: invalid_input - notifications.windows.app_id 'My Todo' must be 1 to 128 characters"
+    ));
+}
+
+#[test]
+fn stale_notification_hint_printed_for_overdue_urgent_tasks() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-notify-stale.json");
+    let past = (OffsetDateTime::now_utc() - Duration::days(2))
+        .format(&Rfc3339)
+        .unwrap();
+
+    write_store(
+        &store_path,
+        serde_json::json!([
+            {
+                "id": "task-1",
+                "title": "urgent overdue",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": past,
+                "urgent": true
+            }
+        ]),
+    );
+
+    let list_output = Command::new(exe)
+        .args(["list", "backlog"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env(
+            "TODOAPP_CONFIG_PATH",
+            temp_path("cli-notify-stale-config.json"),
+        )
+        .output()
+        .expect("failed to run list command");
+
+    let notify_output = Command::new(exe)
+        .args(["notify"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env(
+            "TODOAPP_CONFIG_PATH",
+            temp_path("cli-notify-stale-config.json"),
+        )
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run notify command");
+
+    let after_output = Command::new(exe)
+        .args(["list", "backlog"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env(
+            "TODOAPP_CONFIG_PATH",
+            temp_path("cli-notify-stale-config.json"),
+        )
+        .output()
+        .expect("failed to run list command");
+
+    std::fs::remove_file(&store_path).ok();
+    assert!(list_output.status.success());
+    let stderr = String::from_utf8_lossy(&list_output.stderr);
+    assert!(stderr.contains("hint: 1 urgent task is overdue"));
+    assert!(notify_output.status.success());
+    let after_stderr = String::from_utf8_lossy(&after_output.stderr);
+    assert!(!after_stderr.contains("hint:"));
+}
+
+#[test]
+fn stale_notification_hint_does_not_load_the_store() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-notify-stale-unread.json");
+    let config_path = temp_path("cli-notify-stale-unread-config.json");
+    let past = (OffsetDateTime::now_utc() - Duration::days(2))
+        .format(&Rfc3339)
+        .unwrap();
+
+    write_store(
+        &store_path,
+        serde_json::json!([
+            {
+                "id": "task-1",
+                "title": "urgent overdue",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": past,
+                "urgent": false
+            }
+        ]),
+    );
+    let run = |args: &[&str]| {
+        Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .output()
+            .expect("failed to run command")
+    };
+    let marked = run(&["urgent", "task-1"]);
+    // Only the status summary is left readable, so the hint cannot come from the store.
+    std::fs::write(&store_path, "not a store").unwrap();
+    let status_output = run(&["status"]);
+
+    let mut status_path = store_path.clone().into_os_string();
+    status_path.push(".status.json");
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&status_path).ok();
+    assert!(marked.status.success());
+    assert!(status_output.status.success());
+    let stderr = String::from_utf8_lossy(&status_output.stderr);
+    assert!(
+        stderr.contains("hint: 1 urgent task is overdue"),
+        "{stderr}"
+    );
+}
@@ -35,7 +35,7 @@ fn past_future_strings() -> (String, String) {
 
 #[test]
 fn reschedule_plain_text_output_includes_datetime() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-plain.json");
     let (past, future) = past_future_strings();
 
@@ -68,7 +68,7 @@ fn reschedule_plain_text_output_includes_datetime() {
 
 #[test]
 fn reschedule_updates_task_and_persists() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule.json");
     let (past, future) = past_future_strings();
 
@@ -101,7 +101,7 @@ fn reschedule_updates_task_and_persists() {
     let stored: serde_json::Value =
         serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).expect("stored json");
     assert_eq!(
-        stored["tasks"][0]["scheduled_at"],
+        stored["tasks"][0]["due_at"],
         serde_json::Value::String(future)
     );
 
@@ -110,7 +110,7 @@ fn reschedule_updates_task_and_persists() {
 
 #[test]
 fn reschedule_rejects_invalid_datetime() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-invalid.json");
     let (past, _) = past_future_strings();
 
@@ -143,7 +143,7 @@ fn reschedule_rejects_invalid_datetime() {
 
 #[test]
 fn reschedule_rejects_missing_id() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-missing-id.json");
     let (past, _) = past_future_strings();
 
@@ -176,7 +176,7 @@ fn reschedule_rejects_missing_id() {
 
 #[test]
 fn reschedule_rejects_unknown_id() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-missing.json");
     let (past, _) = past_future_strings();
 
@@ -210,7 +210,7 @@ fn reschedule_rejects_unknown_id() {
 
 #[test]
 fn reschedule_rejects_unscheduled_task() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-unscheduled.json");
 
     let content = serde_json::json!({
@@ -239,11 +239,12 @@ fn reschedule_rejects_unscheduled_task() {
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("ERROR: invalid_input"));
     assert!(stderr.contains("task is not scheduled"));
+    assert!(stderr.contains("\n  hint: use `todo schedule task-1 <datetime>` first\n"));
 }
 
 #[test]
 fn reschedule_rejects_non_overdue_task() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-not-overdue.json");
     let (_, future) = past_future_strings();
 
@@ -277,7 +278,7 @@ fn reschedule_rejects_non_overdue_task() {
 
 #[test]
 fn reschedule_updates_list_filters() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-reschedule-list.json");
     let (today, tomorrow) = local_now_strings();
     let (yesterday, _) = past_future_strings();
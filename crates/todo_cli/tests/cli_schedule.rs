@@ -12,7 +12,7 @@ fn temp_path(file_name: &str) -> PathBuf {
 
 #[test]
 fn schedule_updates_task_and_persists() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-schedule.json");
 
     let content = serde_json::json!({
@@ -33,6 +33,7 @@ fn schedule_updates_task_and_persists() {
     let output = Command::new(exe)
         .args(["--json", "schedule", "task-1", "2025-12-21T09:00:00Z"])
         .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_NOW", "2025-12-20T12:00:00Z")
         .output()
         .expect("failed to run schedule command");
 
@@ -44,7 +45,7 @@ fn schedule_updates_task_and_persists() {
     let stored: serde_json::Value =
         serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).expect("stored json");
     assert_eq!(
-        stored["tasks"][0]["scheduled_at"],
+        stored["tasks"][0]["due_at"],
         serde_json::Value::String("2025-12-21T09:00:00Z".to_string())
     );
 
@@ -53,7 +54,7 @@ fn schedule_updates_task_and_persists() {
 
 #[test]
 fn schedule_rejects_invalid_datetime() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-schedule-invalid.json");
 
     let content = serde_json::json!({
@@ -85,7 +86,7 @@ fn schedule_rejects_invalid_datetime() {
 
 #[test]
 fn schedule_rejects_missing_id() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-schedule-missing-id.json");
 
     let content = serde_json::json!({
@@ -117,7 +118,7 @@ fn schedule_rejects_missing_id() {
 
 #[test]
 fn schedule_rejects_unknown_id() {
-    let exe = env!("CARGO_BIN_EXE_todo_cli");
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
     let store_path = temp_path("cli-schedule-missing.json");
 
     let content = serde_json::json!({
@@ -147,3 +148,176 @@ fn schedule_rejects_unknown_id() {
     assert!(stderr.contains("ERROR: invalid_input"));
     assert!(stderr.contains("task not found"));
 }
+
+#[test]
+fn schedule_respects_blocking_wip_limit_unless_forced() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-schedule-wip.json");
+    let config_path = temp_path("cli-schedule-wip-config.json");
+
+    let content = serde_json::json!({
+        "schema_version": 3,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "planned",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": "2025-12-20T09:00:00Z"
+            },
+            {
+                "id": "task-2",
+                "title": "extra",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null
+            }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+    let config = serde_json::json!({ "wip_limit": { "today": 1, "enforce": "block" } });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    let schedule = |args: &[&str]| {
+        Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TODOAPP_NOW", "2025-12-20T08:00:00Z")
+            .output()
+            .expect("failed to run schedule command")
+    };
+    let blocked = schedule(&["schedule", "task-2", "2025-12-20T10:00:00Z"]);
+    let forced = schedule(&["schedule", "task-2", "2025-12-20T10:00:00Z", "--force"]);
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(!blocked.status.success());
+    assert!(
+        String::from_utf8_lossy(&blocked.stderr)
+            .contains("today would have 2 planned tasks, over wip_limit.today = 1; use --force")
+    );
+    assert!(forced.status.success());
+    assert!(
+        String::from_utf8_lossy(&forced.stderr)
+            .contains("WARNING: today now has 2 planned tasks, over wip_limit.today = 1")
+    );
+    assert!(String::from_utf8_lossy(&forced.stdout).starts_with("Scheduled task: extra (task-2)"));
+}
+
+#[test]
+fn schedule_confirms_with_a_notification_when_on_schedule_is_set() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-schedule-confirm.json");
+    let config_path = temp_path("cli-schedule-confirm-config.json");
+
+    let content = serde_json::json!({
+        "schema_version": 3,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "demo",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null
+            }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+    let config = serde_json::json!({
+        "notifications": { "on_schedule": true, "backend": "console" }
+    });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    let schedule = |args: &[&str], config: Option<&PathBuf>| {
+        let mut command = Command::new(exe);
+        command
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env_remove("TODOAPP_DISABLE_NOTIFICATIONS");
+        match config {
+            Some(path) => command.env("TODOAPP_CONFIG_PATH", path),
+            None => command.env(
+                "TODOAPP_CONFIG_PATH",
+                temp_path("cli-schedule-confirm-none.json"),
+            ),
+        };
+        command.output().expect("failed to run schedule command")
+    };
+    let confirmed = schedule(
+        &["schedule", "task-1", "2025-12-24T16:00:00Z", "--allow-past"],
+        Some(&config_path),
+    );
+    let quiet = schedule(
+        &["reschedule", "task-1", "2025-12-25T16:00:00Z", "--force"],
+        None,
+    );
+
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(confirmed.status.success());
+    assert!(
+        String::from_utf8_lossy(&confirmed.stderr)
+            .contains("[todoapp] Scheduled demo (task-1): due 2025-12-2")
+    );
+    assert!(
+        quiet.status.success(),
+        "{}",
+        String::from_utf8_lossy(&quiet.stderr)
+    );
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("Scheduled demo"));
+}
+
+#[test]
+fn schedule_refuses_a_past_deadline_unless_allowed() {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let store_path = temp_path("cli-schedule-past.json");
+
+    let content = serde_json::json!({
+        "schema_version": 3,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "demo",
+                "status": "pending",
+                "created_at": "2025-12-20T00:00:00Z",
+                "scheduled_at": null
+            }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+
+    let schedule = |args: &[&str]| {
+        Command::new(exe)
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_NOW", "2025-12-20T12:00:00Z")
+            .output()
+            .expect("failed to run schedule command")
+    };
+    let refused = schedule(&["schedule", "task-1", "2025-12-20T09:00:00Z"]);
+    let untouched: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    let allowed = schedule(&["schedule", "task-1", "2025-12-20T09:00:00Z", "--allow-past"]);
+    let planned = schedule(&["schedule", "task-1", "2025-12-19", "--start"]);
+
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(
+        stderr.contains(
+            "2025-12-20T09:00:00Z is in the past, so the task would be overdue right away"
+        )
+    );
+    assert!(stderr.contains("hint: pass --allow-past to schedule it anyway"));
+    assert!(untouched["tasks"][0]["due_at"].is_null());
+    assert!(allowed.status.success());
+    assert!(
+        planned.status.success(),
+        "a start in the past is fine: {}",
+        String::from_utf8_lossy(&planned.stderr)
+    );
+}
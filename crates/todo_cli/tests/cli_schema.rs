@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn temp_path(file_name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+}
+
+fn run(store_path: &Path, args: &[&str]) -> Output {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    Command::new(exe)
+        .args(args)
+        .env("TODOAPP_STORE_PATH", store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run command")
+}
+
+fn parse_stdout(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).expect("json output")
+}
+
+#[test]
+fn schema_command_prints_every_kind() {
+    let store_path = temp_path("cli-schema-all.json");
+    let output = run(&store_path, &["schema"]);
+
+    assert!(output.status.success());
+    let parsed = parse_stdout(&output);
+    assert_eq!(parsed["schema_version"], 1);
+    for kind in ["list", "task", "error", "schedule"] {
+        assert_eq!(parsed["schemas"][kind]["properties"]["kind"]["const"], kind);
+    }
+}
+
+#[test]
+fn envelope_output_matches_published_task_schema() {
+    let store_path = temp_path("cli-schema-envelope.json");
+
+    let schema = parse_stdout(&run(&store_path, &["schema", "list"]));
+    let added = run(&store_path, &["--json", "--envelope", "add", "Enveloped"]);
+    let listed = run(&store_path, &["--json", "--envelope", "list", "backlog"]);
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(added.status.success());
+    let added = parse_stdout(&added);
+    assert_eq!(added["schema_version"], 1);
+    assert_eq!(added["kind"], "task");
+    assert_eq!(added["data"]["title"], "Enveloped");
+
+    let listed = parse_stdout(&listed);
+    assert_eq!(listed["kind"], "list");
+    let item_schema = &schema["properties"]["data"]["items"];
+    let task = listed["data"][0].as_object().expect("task object");
+    for required in item_schema["required"].as_array().unwrap() {
+        assert!(task.contains_key(required.as_str().unwrap()));
+    }
+    for key in task.keys() {
+        assert!(
+            item_schema["properties"].get(key).is_some(),
+            "{key} missing from schema"
+        );
+    }
+}
+
+#[test]
+fn envelope_reports_errors_on_stdout() {
+    let store_path = temp_path("cli-schema-error.json");
+    let output = run(&store_path, &["--json", "--envelope", "show", "missing"]);
+
+    assert!(!output.status.success());
+    let parsed = parse_stdout(&output);
+    assert_eq!(parsed["kind"], "error");
+    assert_eq!(parsed["data"]["code"], "invalid_input");
+    assert_eq!(parsed["data"]["message"], "task not found");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("ERROR: invalid_input"));
+}
+
+#[test]
+fn plain_json_output_is_unchanged_without_envelope() {
+    let store_path = temp_path("cli-schema-plain.json");
+    let output = run(&store_path, &["--json", "add", "Bare"]);
+    std::fs::remove_file(&store_path).ok();
+
+    let parsed = parse_stdout(&output);
+    assert_eq!(parsed["title"], "Bare");
+    assert!(parsed.get("schema_version").is_none());
+}
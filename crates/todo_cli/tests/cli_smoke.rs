@@ -0,0 +1,2379 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn temp_path(file_name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+}
+
+fn run(store_path: &Path, args: &[&str]) -> Output {
+    let exe = env!("CARGO_BIN_EXE_todo_opt");
+    let output = Command::new(exe)
+        .args(args)
+        .env("TODOAPP_STORE_PATH", store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run command");
+    assert!(
+        output.status.success(),
+        "{args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn stored_state(store_path: &Path) -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string(store_path).unwrap()).unwrap()
+}
+
+#[test]
+fn focus_is_preserved_through_edit() {
+    let store_path = temp_path("cli-smoke-focus-edit.json");
+
+    let added = run(&store_path, &["--json", "add", "Draft report"]);
+    let task: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = task["id"].as_str().unwrap().to_string();
+
+    run(&store_path, &["focus", &id]);
+    run(&store_path, &["edit", &id, "Final report"]);
+    let after_edit = stored_state(&store_path);
+
+    // `done` without an id completes the focused task, so it must still be focused.
+    let done = run(&store_path, &["--json", "done"]);
+    let completed: serde_json::Value = serde_json::from_slice(&done.stdout).unwrap();
+    let after_done = stored_state(&store_path);
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(after_edit["focused_task_id"], id.as_str());
+    assert_eq!(after_edit["tasks"][0]["title"], "Final report");
+    assert_eq!(completed["id"], id.as_str());
+    assert_eq!(completed["title"], "Final report");
+    assert!(after_done["focused_task_id"].is_null());
+}
+
+#[test]
+fn show_prints_detail_view() {
+    let store_path = temp_path("cli-smoke-show.json");
+
+    let added = run(
+        &store_path,
+        &[
+            "--json",
+            "add",
+            "Plan trip",
+            "--urgent",
+            "--tag",
+            "travel",
+            "--note",
+            "Book hotel",
+        ],
+    );
+    let task: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = task["id"].as_str().unwrap().to_string();
+    run(&store_path, &["focus", &id]);
+    run(&store_path, &["done", &id, "--message", "booked"]);
+
+    let shown = run(&store_path, &["show", &id]);
+    std::fs::remove_file(&store_path).ok();
+    let stdout = String::from_utf8_lossy(&shown.stdout);
+    let line = |label: &str| {
+        stdout
+            .lines()
+            .find(|line| line.starts_with(label))
+            .unwrap_or_else(|| panic!("missing {label} in:\n{stdout}"))
+            .to_string()
+    };
+
+    assert!(line("Title").contains("Plan trip"));
+    assert!(line("Status").contains("completed"));
+    assert!(line("Urgent").ends_with("yes"));
+    assert!(line("Focused").ends_with("no"));
+    assert!(line("Tags").ends_with("travel"));
+    assert!(stdout.contains("  Book hotel"));
+    assert!(stdout.contains("booked"));
+}
+
+#[test]
+fn init_writes_the_answers_and_never_replaces_a_config() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let store_path = temp_path("cli-smoke-init.json");
+    let config_path = temp_path("cli-smoke-init-config.json");
+    let configured_store = temp_path("cli-smoke-init-configured.json");
+    let init = |args: &[&str], answers: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to run init");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(answers.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let asked = init(&["init"], "Solarized\nn\n\n");
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let state = stored_state(&store_path);
+    let again = init(&["init", "--defaults"], "");
+    std::fs::write(
+        &config_path,
+        serde_json::json!({ "store_path": configured_store }).to_string(),
+    )
+    .unwrap();
+    let configured = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["add", "Kept where the config says"])
+        .env_remove("TODOAPP_STORE_PATH")
+        .env("TODOAPP_CONFIG_PATH", &config_path)
+        .output()
+        .expect("failed to run add");
+    let configured_state = stored_state(&configured_store);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    std::fs::remove_file(&configured_store).ok();
+
+    assert!(
+        asked.status.success(),
+        "{}",
+        String::from_utf8_lossy(&asked.stderr)
+    );
+    assert_eq!(
+        written,
+        serde_json::json!({ "theme": "solarized", "notifications": { "backend": "none" } })
+    );
+    assert_eq!(
+        state["tasks"][0]["title"],
+        "Look around, then mark this task done"
+    );
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already exists"));
+    assert!(
+        configured.status.success(),
+        "{}",
+        String::from_utf8_lossy(&configured.stderr)
+    );
+    assert_eq!(
+        configured_state["tasks"][0]["title"],
+        "Kept where the config says"
+    );
+}
+
+#[test]
+fn demo_runs_against_sample_data_without_touching_the_store() {
+    let store_path = temp_path("cli-smoke-demo.json");
+
+    let listed = run(&store_path, &["demo", "--json", "list", "backlog"]);
+    run(&store_path, &["demo", "add", "Sandboxed task"]);
+
+    let tasks: serde_json::Value = serde_json::from_slice(&listed.stdout).unwrap();
+    assert!(!tasks.as_array().unwrap().is_empty());
+    assert!(!store_path.exists());
+}
+
+#[test]
+fn insights_summarizes_recorded_usage() {
+    let store_path = temp_path("cli-smoke-insights.json");
+    let usage_path = PathBuf::from(format!("{}.usage.jsonl", store_path.display()));
+
+    let added = run(&store_path, &["--json", "add", "Water plants"]);
+    let task: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = task["id"].as_str().unwrap().to_string();
+    run(
+        &store_path,
+        &["schedule", &id, "2020-01-01 09:00", "--allow-past"],
+    );
+    run(&store_path, &["reschedule", &id, "2020-01-02 09:00"]);
+    run(&store_path, &["reschedule", &id, "2020-01-03 09:00"]);
+    run(&store_path, &["done", &id]);
+
+    let insights = run(&store_path, &["--json", "insights"]);
+    let log = std::fs::read_to_string(&usage_path).unwrap();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&usage_path).ok();
+    let insights: serde_json::Value = serde_json::from_slice(&insights.stdout).unwrap();
+
+    assert!(!log.contains("Water plants"));
+    assert_eq!(insights["total_runs"], 5);
+    assert_eq!(insights["top_commands"][0]["command"], "reschedule");
+    assert_eq!(insights["top_commands"][0]["count"], 2);
+    assert_eq!(insights["reschedules"]["tasks"], 1);
+    assert_eq!(insights["reschedules"]["average_per_task"], 2.0);
+    let completions: u64 = insights["completions_by_hour"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|count| count.as_u64().unwrap())
+        .sum();
+    assert_eq!(completions, 1);
+}
+
+#[test]
+fn report_accuracy_compares_estimates_with_tracked_sessions() {
+    let store_path = temp_path("cli-smoke-accuracy.json");
+    let usage_path = PathBuf::from(format!("{}.usage.jsonl", store_path.display()));
+
+    let ids: Vec<String> = ["Write spec", "Review spec"]
+        .iter()
+        .map(|title| {
+            let added = run(&store_path, &["--json", "add", title, "--tag", "work"]);
+            let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+            added["id"].as_str().unwrap().to_string()
+        })
+        .collect();
+    run(&store_path, &["meta", &ids[0], "set", "estimate", "2h"]);
+    let unreadable = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["meta", &ids[1], "set", "estimate", "soon"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .unwrap();
+    run(&store_path, &["start", &ids[0]]);
+    run(&store_path, &["done", &ids[0]]);
+    run(&store_path, &["done", &ids[1]]);
+    let shown = run(&store_path, &["--json", "show", &ids[0]]);
+    let report = run(&store_path, &["--json", "report", "accuracy"]);
+    let plain = run(&store_path, &["report", "accuracy"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&usage_path).ok();
+
+    assert!(!unreadable.status.success());
+    let shown: serde_json::Value = serde_json::from_slice(&shown.stdout).unwrap();
+    assert_eq!(shown["sessions"].as_array().unwrap().len(), 1);
+    assert!(shown["sessions"][0]["ended_at"].is_string());
+    let report: serde_json::Value = serde_json::from_slice(&report.stdout).unwrap();
+    assert_eq!(report["skipped"], 1);
+    assert_eq!(report["tags"].as_array().unwrap().len(), 1);
+    assert_eq!(report["tags"][0]["tag"], "work");
+    assert_eq!(report["tags"][0]["tasks"], 1);
+    assert_eq!(report["tags"][0]["estimated_minutes"], 120);
+    let plain = String::from_utf8_lossy(&plain.stdout);
+    assert!(plain.contains("work  1 task  estimated 2h  tracked 0m"));
+    assert!(plain.contains("1 completed task left out"));
+}
+
+#[test]
+fn context_filters_lists_until_cleared() {
+    let store_path = temp_path("cli-smoke-context.json");
+
+    run(&store_path, &["add", "Write spec", "--project", "work"]);
+    run(&store_path, &["add", "Water plants", "--tag", "home"]);
+    run(
+        &store_path,
+        &["context", "define", "work", "project:work or tag:work"],
+    );
+    run(&store_path, &["context", "set", "work"]);
+
+    let filtered = run(&store_path, &["--json", "list", "backlog"]);
+    let plain = run(&store_path, &["list", "backlog"]);
+    let contexts = run(&store_path, &["--json", "context"]);
+    run(&store_path, &["context", "none"]);
+    let all = run(&store_path, &["--json", "list", "backlog"]);
+    std::fs::remove_file(&store_path).ok();
+
+    let filtered: serde_json::Value = serde_json::from_slice(&filtered.stdout).unwrap();
+    let contexts: serde_json::Value = serde_json::from_slice(&contexts.stdout).unwrap();
+    let all: serde_json::Value = serde_json::from_slice(&all.stdout).unwrap();
+    assert_eq!(filtered.as_array().unwrap().len(), 1);
+    assert_eq!(filtered[0]["title"], "Write spec");
+    assert!(String::from_utf8_lossy(&plain.stdout).contains("Context: work"));
+    assert_eq!(contexts["active"], "work");
+    assert_eq!(
+        contexts["contexts"][0]["filter"],
+        "project:work or tag:work"
+    );
+    assert_eq!(all.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn export_html_writes_standalone_page() {
+    let store_path = temp_path("cli-smoke-export.json");
+    let html_path = temp_path("cli-smoke-export.html");
+
+    run(&store_path, &["add", "Plan <launch>", "--tag", "work"]);
+    let written = run(
+        &store_path,
+        &[
+            "export",
+            "--format",
+            "html",
+            "--output",
+            html_path.to_str().unwrap(),
+        ],
+    );
+    let html = std::fs::read_to_string(&html_path).unwrap();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&html_path).ok();
+
+    assert!(String::from_utf8_lossy(&written.stdout).starts_with("Exported 1 task to "));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Plan &lt;launch&gt;"));
+    assert!(html.contains("<h2>Backlog <span class=\"count\">(1)</span></h2>"));
+    assert!(!html.contains("<script"));
+}
+
+#[test]
+fn comment_adds_attributed_entries_shown_by_show() {
+    let store_path = temp_path("cli-smoke-comment.json");
+
+    let added = run(&store_path, &["--json", "add", "Review PR"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    let commented = run(
+        &store_path,
+        &["comment", id, "Waiting on CI", "--author", "ana"],
+    );
+    run(
+        &store_path,
+        &["comment", id, "CI is green", "--author", "ben"],
+    );
+    let shown = run(&store_path, &["--json", "show", id]);
+    let plain = run(&store_path, &["show", id]);
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(String::from_utf8_lossy(&commented.stdout).starts_with("Commented on task: Review PR"));
+    let shown: serde_json::Value = serde_json::from_slice(&shown.stdout).unwrap();
+    assert_eq!(shown["comments"][0]["author"], "ana");
+    assert_eq!(shown["comments"][0]["text"], "Waiting on CI");
+    assert_eq!(shown["comments"][1]["author"], "ben");
+    let plain = String::from_utf8_lossy(&plain.stdout);
+    assert!(plain.contains("Comments\n"));
+    assert!(plain.contains(" ben  CI is green\n"));
+}
+
+#[test]
+fn graph_dot_links_tasks_to_their_ticket() {
+    let store_path = temp_path("cli-smoke-graph.json");
+
+    let added = run(
+        &store_path,
+        &["--json", "add", "Fix \"login\"", "--project", "web"],
+    );
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    run(&store_path, &["link-ticket", id, "ENG-7"]);
+    let dot = run(&store_path, &["graph", "--format", "dot"]);
+    std::fs::remove_file(&store_path).ok();
+
+    let dot = String::from_utf8_lossy(&dot.stdout);
+    assert!(dot.starts_with("digraph tasks {\n"));
+    assert!(dot.contains("label=\"web\";"));
+    assert!(dot.contains(&format!(
+        "\"task:{id}\" [label=\"Fix \\\"login\\\"\\n#{id}\"]"
+    )));
+    assert!(dot.contains(&format!(
+        "\"task:{id}\" -> \"ticket:ENG-7\" [style=dashed];"
+    )));
+}
+
+#[test]
+fn digest_email_lists_completed_and_upcoming_tasks() {
+    let store_path = temp_path("cli-smoke-digest.json");
+
+    let added = run(&store_path, &["--json", "add", "Send report"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    run(
+        &store_path,
+        &[
+            "done",
+            added["id"].as_str().unwrap(),
+            "-m",
+            "Sent to finance",
+        ],
+    );
+    let later = run(&store_path, &["--json", "add", "Plan sprint"]);
+    let later: serde_json::Value = serde_json::from_slice(&later.stdout).unwrap();
+    let in_two_days = (time::OffsetDateTime::now_utc() + time::Duration::days(2))
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap();
+    run(
+        &store_path,
+        &["schedule", later["id"].as_str().unwrap(), &in_two_days],
+    );
+    let email = run(
+        &store_path,
+        &["digest", "--period", "week", "--format", "email"],
+    );
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(email.status.success());
+    let email = String::from_utf8_lossy(&email.stdout);
+    assert!(email.starts_with("Subject: Weekly digest: 1 completed, 0 slipped, 1 upcoming\n"));
+    assert!(email.contains("\n\n# Weekly digest\n"));
+    assert!(email.contains("- **Send report**"));
+    assert!(email.contains("  > Sent to finance\n"));
+    assert!(email.contains("- **Plan sprint**"));
+}
+
+#[test]
+fn diff_compares_saved_copy_with_live_store() {
+    let store_path = temp_path("cli-smoke-diff.json");
+    let copy_path = temp_path("cli-smoke-diff-before.json");
+
+    run(&store_path, &["add", "Keep me"]);
+    let added = run(&store_path, &["--json", "add", "Finish draft"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    std::fs::copy(&store_path, &copy_path).unwrap();
+    let unchanged = run(&store_path, &["diff", copy_path.to_str().unwrap()]);
+    run(&store_path, &["done", added["id"].as_str().unwrap()]);
+    run(&store_path, &["add", "Brand new"]);
+    let plain = run(&store_path, &["diff", copy_path.to_str().unwrap()]);
+    let json = run(
+        &store_path,
+        &["--json", "diff", copy_path.to_str().unwrap()],
+    );
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&copy_path).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&unchanged.stdout).trim(),
+        "No differences."
+    );
+    let plain = String::from_utf8_lossy(&plain.stdout);
+    assert!(plain.contains("Added (1)"));
+    assert!(plain.contains("Brand new"));
+    assert!(plain.contains("status: \"pending\" -> \"completed\""));
+    assert!(plain.contains("Summary: 1 added, 0 removed, 1 changed"));
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(json["added"][0]["title"], "Brand new");
+    assert_eq!(json["removed"].as_array().unwrap().len(), 0);
+    assert_eq!(json["changed"][0]["id"], added["id"]);
+}
+
+#[test]
+fn snapshot_restore_rolls_back_bulk_changes() {
+    let store_path = temp_path("cli-smoke-snapshot.json");
+
+    run(&store_path, &["add", "Keep me"]);
+    let saved = run(&store_path, &["snapshot", "save", "Before cleanup"]);
+    run(&store_path, &["add", "Oops"]);
+    let restored = run(&store_path, &["snapshot", "restore", "before cleanup"]);
+    let tasks = run(&store_path, &["--json", "list", "backlog"]);
+    let snapshots = run(&store_path, &["--json", "snapshot", "list"]);
+    let mut backups = store_path.clone().into_os_string();
+    backups.push(".backups");
+    std::fs::remove_dir_all(&backups).ok();
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(String::from_utf8_lossy(&saved.stdout).contains("Saved snapshot: before-cleanup"));
+    assert!(String::from_utf8_lossy(&restored.stdout).contains("saved as pre-restore"));
+    let tasks: serde_json::Value = serde_json::from_slice(&tasks.stdout).unwrap();
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    assert_eq!(tasks[0]["title"], "Keep me");
+    let snapshots: serde_json::Value = serde_json::from_slice(&snapshots.stdout).unwrap();
+    let names: Vec<&str> = snapshots["snapshots"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|snapshot| snapshot["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"before-cleanup") && names.contains(&"pre-restore"));
+}
+
+#[test]
+fn query_prints_matching_tasks_as_json() {
+    let store_path = temp_path("cli-smoke-query.json");
+
+    let soon = run(
+        &store_path,
+        &["--json", "add", "Ship release", "--tag", "work"],
+    );
+    let soon: serde_json::Value = serde_json::from_slice(&soon.stdout).unwrap();
+    let soon_id = soon["id"].as_str().unwrap();
+    run(&store_path, &["schedule", soon_id, "2099-01-01"]);
+    run(&store_path, &["add", "Water plants", "--tag", "home"]);
+    let matched = run(
+        &store_path,
+        &[
+            "query",
+            "status = pending and scheduled_at > now and tag ~ 'wor'",
+        ],
+    );
+    let unscheduled = run(&store_path, &["query", "scheduled_at = none"]);
+    let invalid = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["query", "scheduled_at ~ now"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&store_path).ok();
+
+    let matched: serde_json::Value = serde_json::from_slice(&matched.stdout).unwrap();
+    assert_eq!(matched.as_array().unwrap().len(), 1);
+    assert_eq!(matched[0]["id"], soon_id);
+    assert_eq!(matched[0]["status"], "pending");
+    let unscheduled: serde_json::Value = serde_json::from_slice(&unscheduled.stdout).unwrap();
+    assert_eq!(unscheduled[0]["title"], "Water plants");
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn search_matches_words_and_index_rebuild_reports_its_size() {
+    let store_path = temp_path("cli-smoke-search.json");
+
+    run(
+        &store_path,
+        &["add", "Renew passport", "--note", "Bring two photos"],
+    );
+    run(&store_path, &["add", "Buy milk", "--tag", "errands"]);
+    let rebuilt = run(&store_path, &["index", "rebuild"]);
+    run(&store_path, &["add", "Passport photos"]);
+    let matched = run(&store_path, &["--json", "search", "PHOTOS", "passport"]);
+    let errands = run(&store_path, &["search", "errand"]);
+    let nothing = run(&store_path, &["search", "visa"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(store_path.with_file_name(format!(
+        "{}.search.idx",
+        store_path.file_name().unwrap().to_string_lossy()
+    )))
+    .ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&rebuilt.stdout),
+        "Rebuilt the search index (2 tasks).\n"
+    );
+    let matched: serde_json::Value = serde_json::from_slice(&matched.stdout).unwrap();
+    let titles: Vec<&str> = matched
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Renew passport", "Passport photos"]);
+    assert!(String::from_utf8_lossy(&errands.stdout).contains("Buy milk"));
+    assert!(String::from_utf8_lossy(&nothing.stdout).contains("No tasks found."));
+}
+
+#[test]
+fn unicode_titles_are_normalized_searched_and_truncated_by_width() {
+    let store_path = temp_path("cli-smoke-unicode.json");
+
+    let added = run(
+        &store_path,
+        &[
+            "--json",
+            "add",
+            "Cafe\u{301} 東京 오피스 회의 준비 자료 정리 🚀🚀",
+        ],
+    );
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    run(&store_path, &["focus", id]);
+    let found = run(&store_path, &["--json", "search", "CAFÉ", "東京"]);
+    let waybar = run(&store_path, &["status", "--waybar"]);
+    let mut status_path = store_path.clone().into_os_string();
+    status_path.push(".status.json");
+    std::fs::remove_file(&status_path).ok();
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(
+        added["title"],
+        "Caf\u{e9} 東京 오피스 회의 준비 자료 정리 🚀🚀"
+    );
+    let found: serde_json::Value = serde_json::from_slice(&found.stdout).unwrap();
+    assert_eq!(found[0]["id"], id);
+    // 32 columns: the wide Hangul syllables count twice.
+    let waybar: serde_json::Value = serde_json::from_slice(&waybar.stdout).unwrap();
+    assert_eq!(waybar["text"], "Caf\u{e9} 東京 오피스 회의 준비 자료…");
+}
+
+#[test]
+fn accessible_output_spells_markers_out_one_fact_per_line() {
+    let store_path = temp_path("cli-smoke-accessible.json");
+
+    let added = run(&store_path, &["--json", "add", "Call plumber"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    run(&store_path, &["add", "Water plants"]);
+    run(
+        &store_path,
+        &["schedule", id, "2000-01-01 09:00", "--allow-past"],
+    );
+    run(&store_path, &["focus", id]);
+    let today = run(&store_path, &["--accessible", "list", "today"]);
+    let backlog = run(&store_path, &["--accessible", "list", "backlog"]);
+    let edited = run(
+        &store_path,
+        &["edit", id, "Call the plumber", "--accessible"],
+    );
+    std::fs::remove_file(&store_path).ok();
+
+    let today = String::from_utf8_lossy(&today.stdout).into_owned();
+    assert!(today.starts_with(&format!("Group Morning: 1 task\nTask {id}: Call plumber\nFOCUSED\nStatus: pending\nOVERDUE\nCreated: ")));
+    let backlog = String::from_utf8_lossy(&backlog.stdout).into_owned();
+    assert!(backlog.contains(": Water plants\nStatus: pending\nCreated: "));
+    assert!(backlog.ends_with("Due: none\n"));
+    for listed in [&today, &backlog] {
+        assert!(
+            listed.is_ascii() && !listed.contains('\x1b'),
+            "plain words expected:\n{listed}"
+        );
+    }
+    assert_eq!(
+        String::from_utf8_lossy(&edited.stdout),
+        format!("Updated task: Call the plumber ({id})\nPrevious title: Call plumber\n")
+    );
+}
+
+#[test]
+fn meta_sets_values_that_json_and_query_can_read() {
+    let store_path = temp_path("cli-smoke-meta.json");
+
+    let added = run(&store_path, &["--json", "add", "Ship release"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    run(&store_path, &["add", "Water plants"]);
+    let set = run(&store_path, &["meta", id, "set", "sprint", "42"]);
+    run(&store_path, &["meta", id, "set", "owner", "sam"]);
+    run(&store_path, &["meta", id, "unset", "owner"]);
+    let listed = run(&store_path, &["--json", "list", "backlog"]);
+    let queried = run(&store_path, &["query", "meta.sprint = 42"]);
+    let shown = run(&store_path, &["show", id]);
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(String::from_utf8_lossy(&set.stdout).starts_with("Set sprint: Ship release"));
+    let listed: serde_json::Value = serde_json::from_slice(&listed.stdout).unwrap();
+    let task = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|task| task["id"] == id)
+        .unwrap();
+    assert_eq!(task["metadata"], serde_json::json!({ "sprint": "42" }));
+    let queried: serde_json::Value = serde_json::from_slice(&queried.stdout).unwrap();
+    assert_eq!(queried.as_array().unwrap().len(), 1);
+    assert_eq!(queried[0]["id"], id);
+    assert!(String::from_utf8_lossy(&shown.stdout).contains("sprint  42"));
+}
+
+#[test]
+fn link_ticket_sets_and_clears_the_task_ticket() {
+    let store_path = temp_path("cli-smoke-ticket.json");
+
+    let added = run(&store_path, &["--json", "add", "Fix login"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    let linked = run(
+        &store_path,
+        &[
+            "link-ticket",
+            id,
+            "JIRA-123",
+            "--url",
+            "https://jira.example.com/browse/JIRA-123",
+        ],
+    );
+    let listed = run(&store_path, &["--json", "list", "backlog"]);
+    let shown = run(&store_path, &["show", id]);
+    run(&store_path, &["link-ticket", id, "--clear"]);
+    let cleared = run(&store_path, &["--json", "list", "backlog"]);
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(String::from_utf8_lossy(&linked.stdout).starts_with("Linked JIRA-123: Fix login"));
+    let listed: serde_json::Value = serde_json::from_slice(&listed.stdout).unwrap();
+    assert_eq!(listed[0]["ticket"]["key"], "JIRA-123");
+    assert_eq!(
+        listed[0]["ticket"]["url"],
+        "https://jira.example.com/browse/JIRA-123"
+    );
+    assert!(String::from_utf8_lossy(&shown.stdout).contains("JIRA-123"));
+    let cleared: serde_json::Value = serde_json::from_slice(&cleared.stdout).unwrap();
+    assert!(cleared[0]["ticket"].is_null());
+}
+
+#[test]
+fn status_waybar_switches_class_when_overdue() {
+    let store_path = temp_path("cli-smoke-status.json");
+
+    let added = run(&store_path, &["--json", "add", "Pay <rent>"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    let pending = run(&store_path, &["status", "--waybar"]);
+    run(&store_path, &["schedule", id, "2000-01-01", "--allow-past"]);
+    let overdue = run(&store_path, &["status", "--waybar"]);
+    let json = run(&store_path, &["--json", "status"]);
+    let mut status_path = store_path.clone().into_os_string();
+    status_path.push(".status.json");
+    std::fs::remove_file(&status_path).ok();
+    std::fs::remove_file(&store_path).ok();
+
+    let pending: serde_json::Value = serde_json::from_slice(&pending.stdout).unwrap();
+    assert_eq!(pending["class"], "pending");
+    assert_eq!(pending["text"], "1 pending");
+    let overdue: serde_json::Value = serde_json::from_slice(&overdue.stdout).unwrap();
+    assert_eq!(overdue["class"], "urgent");
+    assert!(
+        overdue["tooltip"]
+            .as_str()
+            .unwrap()
+            .contains("Pay &lt;rent&gt;")
+    );
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(json["next_due"], "2000-01-01T00:00:00Z");
+    assert_eq!(json["overdue"], true);
+}
+
+#[cfg(unix)]
+#[test]
+fn listen_answers_each_line_with_a_json_envelope() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+
+    let store_path = temp_path("cli-smoke-listen.json");
+    let socket_path = temp_path("cli-smoke-listen.sock");
+    let mut listener = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["listen", "--socket", socket_path.to_str().unwrap()])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stream = None;
+    for _ in 0..100 {
+        if let Ok(connected) = UnixStream::connect(&socket_path) {
+            stream = Some(connected);
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let mut stream = stream.expect("listener did not start");
+    let mode = std::fs::metadata(&socket_path)
+        .unwrap()
+        .permissions()
+        .mode();
+    stream
+        .write_all(b"add \"Buy milk\"\n\nlist backlog\ndone missing\n")
+        .unwrap();
+    let replies: Vec<serde_json::Value> = BufReader::new(stream.try_clone().unwrap())
+        .lines()
+        .take(3)
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    listener.kill().ok();
+    listener.wait().ok();
+    std::fs::remove_file(&socket_path).ok();
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(replies[0]["kind"], "task");
+    assert_eq!(replies[0]["data"]["title"], "Buy milk");
+    assert_eq!(replies[1]["kind"], "list");
+    assert_eq!(replies[1]["data"][0]["title"], "Buy milk");
+    assert_eq!(replies[2]["kind"], "error");
+    assert_eq!(replies[2]["data"]["code"], "invalid_input");
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[cfg(unix)]
+#[test]
+fn listen_runs_commands_with_the_config_it_was_started_with() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let store_path = temp_path("cli-smoke-listen-config.json");
+    let config_path = temp_path("cli-smoke-listen-config-work.json");
+    let socket_path = temp_path("cli-smoke-listen-config.sock");
+    std::fs::write(
+        &config_path,
+        r#"{"titles": {"trim_trailing_punctuation": true}}"#,
+    )
+    .unwrap();
+    let mut listener = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args([
+            "--config",
+            config_path.to_str().unwrap(),
+            "listen",
+            "--socket",
+            socket_path.to_str().unwrap(),
+        ])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stream = None;
+    for _ in 0..100 {
+        if let Ok(connected) = UnixStream::connect(&socket_path) {
+            stream = Some(connected);
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let mut stream = stream.expect("listener did not start");
+    stream.write_all(b"add \"Buy milk!\"\n").unwrap();
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).unwrap();
+    listener.kill().ok();
+    listener.wait().ok();
+    std::fs::remove_file(&socket_path).ok();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+    assert_eq!(reply["data"]["title"], "Buy milk");
+}
+
+#[test]
+fn undo_reopens_the_task_just_completed() {
+    let store_path = temp_path("cli-smoke-undo.json");
+
+    let added = run(&store_path, &["--json", "add", "Water plants"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    let done = run(&store_path, &["done", id]);
+    let undone = run(&store_path, &["--json", "undo"]);
+    let mut undo_path = store_path.clone().into_os_string();
+    undo_path.push(".undo.json");
+    let record_left = std::path::Path::new(&undo_path).exists();
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(String::from_utf8_lossy(&done.stdout).contains("Undo with `todo undo` (valid 60s)."));
+    let undone: serde_json::Value = serde_json::from_slice(&undone.stdout).unwrap();
+    assert_eq!(undone["id"], id);
+    assert_eq!(undone["status"], "pending");
+    assert!(undone["completed_at"].is_null());
+    assert!(!record_left);
+}
+
+#[test]
+fn maintain_archives_completed_tasks_past_retention() {
+    let store_path = temp_path("cli-smoke-maintain.json");
+    let config_path = temp_path("cli-smoke-maintain-config.json");
+    let content = serde_json::json!({
+        "schema_version": 3,
+        "tasks": [
+            {
+                "id": "task-1",
+                "title": "Shipped long ago",
+                "status": "completed",
+                "created_at": "2020-01-01T00:00:00Z",
+                "completed_at": "2020-01-02T00:00:00Z"
+            },
+            {
+                "id": "task-2",
+                "title": "Still open",
+                "status": "pending",
+                "created_at": "2020-01-01T00:00:00Z"
+            }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&content).unwrap()).unwrap();
+    let config = serde_json::json!({ "retention": { "archive_completed_after_days": 30 } });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["--json", "maintain"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_CONFIG_PATH", &config_path)
+        .output()
+        .expect("failed to run maintain");
+    let list = run(&store_path, &["--json", "list", "backlog"]);
+    let mut archive_path = store_path.clone().into_os_string();
+    archive_path.push(".archive.json");
+    let archive: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&archive_path).unwrap()).unwrap();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    std::fs::remove_file(&archive_path).ok();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["archived"][0]["id"], "task-1");
+    assert_eq!(report["purged"].as_array().unwrap().len(), 0);
+    let listed: serde_json::Value = serde_json::from_slice(&list.stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(archive["tasks"][0]["task"]["title"], "Shipped long ago");
+}
+
+#[test]
+fn strict_fails_on_config_and_store_warnings_before_running() {
+    let store_path = temp_path("cli-smoke-strict.json");
+    let config_path = temp_path("cli-smoke-strict-config.json");
+    let missing_config = temp_path("cli-smoke-strict-none.json");
+    let legacy = serde_json::json!({
+        "schema_version": 4,
+        "tasks": [
+            { "id": "task-1", "title": "Water plants", "status": "pending", "created_at": "2025-12-20T00:00:00Z" }
+        ]
+    });
+    std::fs::write(&store_path, serde_json::to_string_pretty(&legacy).unwrap()).unwrap();
+    std::fs::write(&config_path, r#"{"wip_limit": {"todya": 3}}"#).unwrap();
+    let run_with = |config: &Path, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", config)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run command")
+    };
+
+    let lenient = run_with(&config_path, &["list", "backlog"]);
+    let strict = run_with(&config_path, &["--strict", "add", "Buy milk"]);
+    let legacy_only = run_with(&missing_config, &["--strict", "list", "backlog"]);
+    run_with(&missing_config, &["add", "Buy milk"]);
+    let upgraded = run_with(&missing_config, &["--strict", "--json", "list", "backlog"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(lenient.status.success());
+    let stderr = String::from_utf8_lossy(&lenient.stderr);
+    assert!(
+        stderr.contains("WARNING: ") && stderr.contains("unknown key `wip_limit.todya` is ignored"),
+        "{stderr}"
+    );
+    assert!(!strict.status.success());
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(stderr.contains("uses schema_version 4"), "{stderr}");
+    assert!(
+        stderr.contains("--strict: failing after 2 warnings"),
+        "{stderr}"
+    );
+    assert!(!legacy_only.status.success());
+    assert!(
+        String::from_utf8_lossy(&legacy_only.stderr)
+            .contains("--strict: failing after 1 warning\n")
+    );
+    assert!(
+        upgraded.status.success(),
+        "{}",
+        String::from_utf8_lossy(&upgraded.stderr)
+    );
+    let listed: serde_json::Value = serde_json::from_slice(&upgraded.stdout).unwrap();
+    assert_eq!(
+        listed.as_array().unwrap().len(),
+        2,
+        "the strict add must not have run"
+    );
+}
+
+#[test]
+fn envelope_lists_warnings_and_only_strict_fails_on_them() {
+    let store_path = temp_path("cli-smoke-warnings.json");
+    let config_path = temp_path("cli-smoke-warnings-config.json");
+    std::fs::write(&config_path, "{ not json").unwrap();
+    let run_with = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .output()
+            .expect("failed to run command")
+    };
+
+    let lenient = run_with(&["--json", "--envelope", "list", "backlog"]);
+    let strict = run_with(&["--strict", "--json", "--envelope", "list", "backlog"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(
+        lenient.status.success(),
+        "{}",
+        String::from_utf8_lossy(&lenient.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&lenient.stderr);
+    assert!(
+        stderr.starts_with("WARNING: ") && !stderr.contains("ERROR"),
+        "{stderr}"
+    );
+    let envelope: serde_json::Value = serde_json::from_slice(&lenient.stdout).unwrap();
+    assert_eq!(envelope["kind"], "list");
+    let warnings = envelope["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0]
+            .as_str()
+            .unwrap()
+            .ends_with("; using the default config")
+    );
+    assert!(!strict.status.success());
+    let envelope: serde_json::Value = serde_json::from_slice(&strict.stdout).unwrap();
+    assert_eq!(envelope["kind"], "error");
+    assert_eq!(
+        envelope["data"]["message"],
+        "--strict: failing after 1 warning"
+    );
+    assert_eq!(envelope["warnings"].as_array().unwrap().len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_reports_and_fixes_store_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_path("cli-smoke-doctor");
+    std::fs::create_dir(&dir).unwrap();
+    let store_path = dir.join("tasks.json");
+    let config_path = dir.join("config.json");
+    let run_here = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run command")
+    };
+
+    run_here(&["add", "Water plants"]);
+    std::fs::set_permissions(&store_path, std::fs::Permissions::from_mode(0o666)).unwrap();
+    let listed = run_here(&["list", "backlog"]);
+    let report = run_here(&["doctor"]);
+    let fixed = run_here(&["doctor", "--fix-permissions"]);
+    let clean = run_here(&["doctor"]);
+    let mode = std::fs::metadata(&store_path).unwrap().permissions().mode() & 0o777;
+    std::fs::remove_dir_all(&dir).ok();
+
+    let problem = format!(
+        "{} is writable by other users (mode 666)",
+        store_path.display()
+    );
+    assert!(listed.status.success());
+    assert!(
+        String::from_utf8_lossy(&listed.stderr)
+            .contains(&format!("WARNING: {problem}; see `todo doctor`\n"))
+    );
+    assert!(!report.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&report.stdout),
+        format!("{problem}\n")
+    );
+    assert!(
+        String::from_utf8_lossy(&report.stderr)
+            .contains("1 problem found; run `todo doctor --fix-permissions`")
+    );
+    assert!(
+        fixed.status.success(),
+        "{}",
+        String::from_utf8_lossy(&fixed.stderr)
+    );
+    assert!(
+        String::from_utf8_lossy(&fixed.stdout)
+            .contains(&format!("Fixed permissions: {}\n", store_path.display()))
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&clean.stdout),
+        "No problems found.\n"
+    );
+    assert_eq!(mode, 0o600);
+}
+
+#[test]
+fn mistyped_commands_suggest_the_closest_command_or_alias() {
+    let store_path = temp_path("cli-smoke-typo.json");
+    let config_path = temp_path("cli-smoke-typo-config.json");
+    std::fs::write(&config_path, r#"{"aliases": {"standup": "list today"}}"#).unwrap();
+    let config = config_path.to_str().unwrap();
+    let run_failing = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .output()
+            .expect("failed to run command");
+        assert!(!output.status.success());
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+
+    let command = run_failing(&["lsit", "today"]);
+    let nested = run_failing(&["list", "tody"]);
+    let alias = run_failing(&["--config", config, "standpu"]);
+    let unknown = run_failing(&["frobnicate"]);
+    std::fs::remove_file(&config_path).ok();
+
+    assert_eq!(
+        command,
+        "ERROR: invalid_input - unrecognized subcommand 'lsit'; did you mean `list`?\n"
+    );
+    assert!(nested.contains("did you mean `today`?"), "{nested}");
+    assert!(alias.contains("did you mean `standup`?"), "{alias}");
+    assert!(!unknown.contains("did you mean"), "{unknown}");
+}
+
+#[test]
+fn profiles_and_config_flag_choose_the_config_and_store() {
+    let home = temp_path("cli-smoke-profiles");
+    std::fs::create_dir(&home).unwrap();
+    let config_path = home.join("custom-config.json");
+    std::fs::write(&config_path, r#"{"aliases": {"bl": "list backlog"}}"#).unwrap();
+    let run_home = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("HOME", &home)
+            .env_remove("TODOAPP_STORE_PATH")
+            .env_remove("TODOAPP_CONFIG_PATH")
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run command")
+    };
+
+    let added = run_home(&["--profile", "work", "add", "Review budget"]);
+    let work = run_home(&["list", "backlog", "--profile=work"]);
+    let default = run_home(&["list", "backlog"]);
+    let aliased = run_home(&[
+        "--config",
+        config_path.to_str().unwrap(),
+        "--profile",
+        "work",
+        "bl",
+    ]);
+    let invalid = run_home(&["--profile", "../work", "list", "backlog"]);
+    let work_store = home.join(".config/todoapp/profiles/work/tasks.json");
+    let work_store_exists = work_store.exists();
+    std::fs::remove_dir_all(&home).ok();
+
+    assert!(
+        added.status.success(),
+        "{}",
+        String::from_utf8_lossy(&added.stderr)
+    );
+    assert!(work_store_exists);
+    assert!(String::from_utf8_lossy(&work.stdout).contains("Review budget"));
+    assert!(!String::from_utf8_lossy(&default.stdout).contains("Review budget"));
+    assert!(
+        aliased.status.success(),
+        "{}",
+        String::from_utf8_lossy(&aliased.stderr)
+    );
+    assert!(String::from_utf8_lossy(&aliased.stdout).contains("Review budget"));
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("profile names use letters"));
+}
+
+#[test]
+fn secret_check_reads_the_environment_and_rejects_bad_names() {
+    let store_path = temp_path("cli-smoke-secret.json");
+    let check = |name: &str| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(["secret", "check", name])
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .env("TODOAPP_SECRET_SMOKE_TOKEN", "abc123")
+            .output()
+            .expect("failed to run command")
+    };
+
+    let available = check("smoke_token");
+    let invalid = check("Smoke-Token");
+
+    assert!(
+        available.status.success(),
+        "{}",
+        String::from_utf8_lossy(&available.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&available.stdout),
+        "Secret smoke_token is available.\n"
+    );
+    assert!(!String::from_utf8_lossy(&available.stdout).contains("abc123"));
+    assert!(!invalid.status.success());
+    assert!(
+        String::from_utf8_lossy(&invalid.stderr).contains("secret names use lowercase letters")
+    );
+}
+
+#[test]
+fn commands_report_changes_other_people_made_since_last_look() {
+    let store_path = temp_path("cli-smoke-activity.json");
+    let run_as = |user: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .env("USER", user)
+            .env("HOSTNAME", "desk")
+            .output()
+            .expect("failed to run command")
+    };
+
+    run_as("ana", &["add", "Water plants"]);
+    let first = run_as("ana", &["list", "backlog"]);
+    let added = run_as("ben", &["--json", "add", "Buy milk"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    run_as("ben", &["done", id]);
+    let second = run_as("ana", &["list", "backlog"]);
+    let third = run_as("ana", &["list", "backlog"]);
+    for suffix in [".activity.jsonl", ".seen.json"] {
+        let mut sidecar = store_path.clone().into_os_string();
+        sidecar.push(suffix);
+        std::fs::remove_file(sidecar).ok();
+    }
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(!String::from_utf8_lossy(&first.stderr).contains("Since you last looked"));
+    let banner = String::from_utf8_lossy(&second.stderr);
+    assert!(
+        banner.contains("Since you last looked: 2 changes by ben@desk\n"),
+        "{banner}"
+    );
+    assert!(
+        banner.contains(&format!("  ben@desk completed #{id} Buy milk\n")),
+        "{banner}"
+    );
+    assert!(
+        banner.contains(&format!("  ben@desk added #{id} Buy milk\n")),
+        "{banner}"
+    );
+    assert!(!String::from_utf8_lossy(&third.stderr).contains("Since you last looked"));
+}
+
+#[test]
+fn show_verbose_reports_who_changed_the_task() {
+    let store_path = temp_path("cli-smoke-show-verbose.json");
+    let run_as = |user: &str, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .env("USER", user)
+            .env("HOSTNAME", "laptop")
+            .output()
+            .expect("failed to run command");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    };
+
+    let added = run_as("ana", &["--json", "add", "Water plants"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+    run_as("ben", &["urgent", id]);
+    let plain = run_as("ana", &["show", id]);
+    let verbose = run_as("ana", &["show", id, "--verbose"]);
+    let shown = run_as("ana", &["--json", "show", id]);
+    let mut activity_path = store_path.clone().into_os_string();
+    activity_path.push(".activity.jsonl");
+    let mut seen_path = store_path.clone().into_os_string();
+    seen_path.push(".seen.json");
+    std::fs::remove_file(&activity_path).ok();
+    std::fs::remove_file(&seen_path).ok();
+    std::fs::remove_file(&store_path).ok();
+
+    assert!(!String::from_utf8_lossy(&plain.stdout).contains("Modified"));
+    let verbose = String::from_utf8_lossy(&verbose.stdout);
+    assert!(verbose.contains(" by ben@laptop\n"), "{verbose}");
+    assert!(verbose.contains(" ana@laptop  added\n"), "{verbose}");
+    assert!(verbose.contains(" ben@laptop  updated\n"), "{verbose}");
+    let shown: serde_json::Value = serde_json::from_slice(&shown.stdout).unwrap();
+    assert_eq!(shown["modified_by"], "ben@laptop");
+}
+
+#[test]
+fn start_and_wait_set_statuses_that_list_and_search_filter_on() {
+    let store_path = temp_path("cli-smoke-start-wait.json");
+    let ids: Vec<String> = ["Draft report", "Report sign-off", "Report typos"]
+        .iter()
+        .map(|title| {
+            let added = run(&store_path, &["--json", "add", title]);
+            let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+            added["id"].as_str().unwrap().to_string()
+        })
+        .collect();
+
+    let started = run(&store_path, &["start", &ids[0]]);
+    let waiting = run(
+        &store_path,
+        &["--json", "wait", &ids[1], "--until", "2030-01-15 09:00"],
+    );
+    let in_progress = run(
+        &store_path,
+        &["--json", "list", "backlog", "--status", "in_progress"],
+    );
+    let open = run(
+        &store_path,
+        &["--json", "search", "report", "--status", "open"],
+    );
+    let pending = run(
+        &store_path,
+        &["--json", "search", "report", "--status", "pending"],
+    );
+    let legacy = run(&store_path, &["--legacy-json", "list", "backlog"]);
+    let state = stored_state(&store_path);
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&started.stdout),
+        format!("Started task: Draft report ({})\n", ids[0])
+    );
+    let waiting: serde_json::Value = serde_json::from_slice(&waiting.stdout).unwrap();
+    assert_eq!(waiting["status"], "waiting");
+    assert!(
+        waiting["waiting_until"]
+            .as_str()
+            .unwrap()
+            .starts_with("2030-01-15T09:00:00")
+    );
+    let ids_of = |output: &Output| -> Vec<String> {
+        let tasks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        tasks
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(ids_of(&in_progress), vec![ids[0].clone()]);
+    assert_eq!(ids_of(&open).len(), 3);
+    assert_eq!(ids_of(&pending), vec![ids[2].clone()]);
+    let legacy: serde_json::Value = serde_json::from_slice(&legacy.stdout).unwrap();
+    assert!(
+        legacy
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|task| task["status"] == "pending")
+    );
+    assert!(state["schema_version"].as_u64().unwrap() >= 14);
+    assert_eq!(state["tasks"][0]["status"], "in_progress");
+}
+
+#[test]
+fn cancel_keeps_the_task_with_its_reason() {
+    let store_path = temp_path("cli-smoke-cancel.json");
+    let added = run(&store_path, &["--json", "add", "Book venue"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+
+    let cancelled = run(&store_path, &["cancel", id, "-m", "Event moved online"]);
+    let shown = run(&store_path, &["show", id]);
+    let listed = run(
+        &store_path,
+        &["--json", "list", "backlog", "--status", "cancelled"],
+    );
+    let open = run(
+        &store_path,
+        &["--json", "list", "backlog", "--status", "open"],
+    );
+    let done = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["done", id])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run command");
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&cancelled.stdout),
+        format!("Cancelled task: Book venue ({id})\n")
+    );
+    let shown = String::from_utf8_lossy(&shown.stdout);
+    assert!(shown.contains("cancelled"), "{shown}");
+    assert!(shown.contains("Event moved online"), "{shown}");
+    let listed: serde_json::Value = serde_json::from_slice(&listed.stdout).unwrap();
+    assert_eq!(listed[0]["status"], "cancelled");
+    assert_eq!(listed[0]["cancellation"]["reason"], "Event moved online");
+    assert!(listed[0]["completed_at"].is_null());
+    let open: serde_json::Value = serde_json::from_slice(&open.stdout).unwrap();
+    assert_eq!(open, serde_json::json!([]));
+    assert!(!done.status.success());
+    assert!(String::from_utf8_lossy(&done.stderr).contains("task already cancelled"));
+}
+
+#[test]
+fn routine_start_adds_todays_tasks_once() {
+    let store_path = temp_path("cli-smoke-routine.json");
+    let config_path = temp_path("cli-smoke-routine-config.json");
+    std::fs::write(
+        &config_path,
+        r#"{"routines": {"morning": ["Stretch", "Email triage"]}}"#,
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+
+    let listed = run(&store_path, &["--config", config, "routine", "list"]);
+    let started = run(
+        &store_path,
+        &["--config", config, "routine", "start", "morning"],
+    );
+    let today = run(&store_path, &["--json", "list", "today"]);
+    let again = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["--config", config, "routine", "start", "morning"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .output()
+        .expect("failed to run command");
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&listed.stdout),
+        "morning: Stretch, Email triage\n"
+    );
+    assert!(
+        String::from_utf8_lossy(&started.stdout)
+            .starts_with("Started routine morning: 2 tasks for today\n")
+    );
+    let today: serde_json::Value = serde_json::from_slice(&today.stdout).unwrap();
+    let routines: Vec<(&str, &str)> = today
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| {
+            (
+                task["title"].as_str().unwrap(),
+                task["routine"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        routines,
+        vec![("Stretch", "morning"), ("Email triage", "morning")]
+    );
+    assert!(
+        today
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|task| task["overdue"] == false)
+    );
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already started today"));
+}
+
+#[test]
+fn mark_shows_a_color_and_emoji_before_the_title() {
+    let store_path = temp_path("cli-smoke-mark.json");
+    let added = run(&store_path, &["--json", "add", "Renew passport"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+
+    let colored = run(&store_path, &["mark", id, "--color", "red"]);
+    let emoji = run(&store_path, &["mark", id, "--emoji", "🔥"]);
+    let listed = run(&store_path, &["list", "backlog"]);
+    let json = run(&store_path, &["--json", "list", "backlog"]);
+    let nothing = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["mark", id])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run command");
+    let cleared = run(&store_path, &["mark", id, "--clear"]);
+    let stored = stored_state(&store_path);
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&colored.stdout),
+        format!("Marked task: [red] Renew passport ({id})\n")
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&emoji.stdout),
+        format!("Marked task: 🔥[red] Renew passport ({id})\n")
+    );
+    assert!(String::from_utf8_lossy(&listed.stdout).contains("🔥[red] Renew passport"));
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(
+        json[0]["marker"],
+        serde_json::json!({"color": "red", "symbol": "🔥"})
+    );
+    assert!(!nothing.status.success());
+    assert!(String::from_utf8_lossy(&nothing.stderr).contains("give --color, --emoji, or --clear"));
+    assert_eq!(
+        String::from_utf8_lossy(&cleared.stdout),
+        format!("Cleared marker: Renew passport ({id})\n")
+    );
+    assert!(stored["tasks"][0]["marker"].is_null());
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn sync_copies_tasks_and_needs_a_preference_for_conflicts_without_a_terminal() {
+    let store_path = temp_path("cli-smoke-sync.json");
+    let remote_path = temp_path("cli-smoke-sync-remote.json");
+    let config_path = temp_path("cli-smoke-sync-config.json");
+    let config = serde_json::json!({"remotes": {"shared": {"path": remote_path}}});
+    std::fs::write(&config_path, config.to_string()).unwrap();
+    let config = config_path.to_str().unwrap();
+    let added = run(&store_path, &["--json", "add", "Pay rent"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+
+    let first = run(&store_path, &["--config", config, "sync", "shared"]);
+    run(&remote_path, &["edit", id, "Pay the rent"]);
+    run(&store_path, &["edit", id, "Pay rent early"]);
+    let planned = run(
+        &store_path,
+        &["--config", config, "sync", "shared", "--dry-run"],
+    );
+    let unsettled = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["--config", config, "sync", "shared"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run command");
+    let status = run(&store_path, &["--config", config, "sync", "status"]);
+    let settled = run(
+        &store_path,
+        &[
+            "--config", config, "--json", "sync", "shared", "--prefer", "remote",
+        ],
+    );
+    let status_after = run(
+        &store_path,
+        &["--config", config, "--json", "sync", "status"],
+    );
+    let again = run(&store_path, &["--config", config, "sync", "shared"]);
+    let local = stored_state(&store_path);
+    for path in [&store_path, &remote_path, &config_path] {
+        std::fs::remove_file(path).ok();
+    }
+    let mut sync_state = store_path.clone().into_os_string();
+    sync_state.push(".sync.json");
+    std::fs::remove_file(sync_state).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&first.stdout),
+        format!("Pushed to shared: 1 task\n  {id}  Pay rent\n")
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&planned.stdout),
+        format!(
+            "Sync plan for shared, nothing changed yet: 0 to push, 0 to pull, 0 to delete on shared, 0 to delete here, 1 conflict\n\
+             To settle: 1 conflict\n  {id}  Pay rent early\n    title: here \"Pay rent early\", on shared \"Pay the rent\"\n"
+        )
+    );
+    assert!(!unsettled.status.success());
+    assert!(
+        String::from_utf8_lossy(&unsettled.stderr)
+            .contains("rerun with --prefer local|remote|newest")
+    );
+    let status = String::from_utf8_lossy(&status.stdout);
+    assert!(status.contains("Changed here since: 1 task"), "{status}");
+    assert!(status.contains("Unsettled conflicts: 1"), "{status}");
+    assert!(
+        status.contains(&format!("{id}  Pay rent early (title; found ")),
+        "{status}"
+    );
+    let status_after: serde_json::Value = serde_json::from_slice(&status_after.stdout).unwrap();
+    assert_eq!(status_after[0]["name"], "shared");
+    assert_eq!(status_after[0]["local_changes"], 0);
+    assert_eq!(status_after[0]["conflicts"], serde_json::json!([]));
+    let settled: serde_json::Value = serde_json::from_slice(&settled.stdout).unwrap();
+    assert_eq!(
+        settled["conflicts"][0]["fields"],
+        serde_json::json!(["title"])
+    );
+    assert_eq!(settled["conflicts"][0]["resolution"], "remote");
+    assert_eq!(
+        String::from_utf8_lossy(&again.stdout),
+        "Already in sync with shared.\n"
+    );
+    assert_eq!(local["tasks"][0]["title"], "Pay the rent");
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn sync_with_a_scoped_remote_skips_other_tasks_and_excluded_fields() {
+    let store_path = temp_path("cli-smoke-sync-scope.json");
+    let remote_path = temp_path("cli-smoke-sync-scope-remote.json");
+    let config_path = temp_path("cli-smoke-sync-scope-config.json");
+    let config = serde_json::json!({"remotes": {"shared": {
+        "path": remote_path,
+        "filter": "tag:shared",
+        "exclude_fields": ["notes"]
+    }}});
+    std::fs::write(&config_path, config.to_string()).unwrap();
+    let config = config_path.to_str().unwrap();
+    run(
+        &store_path,
+        &[
+            "add",
+            "Plan trip",
+            "--tag",
+            "shared",
+            "--note",
+            "Passport in drawer",
+        ],
+    );
+    run(&store_path, &["add", "Dentist"]);
+
+    let synced = run(&store_path, &["--config", config, "sync", "shared"]);
+    let remote = stored_state(&remote_path);
+    let bad_config =
+        serde_json::json!({"remotes": {"shared": {"path": remote_path, "filter": "colour:red"}}});
+    std::fs::write(&config_path, bad_config.to_string()).unwrap();
+    let bad = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["--config", config, "sync", "shared"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run command");
+    for path in [&store_path, &remote_path, &config_path] {
+        std::fs::remove_file(path).ok();
+    }
+    let mut sync_state = store_path.clone().into_os_string();
+    sync_state.push(".sync.json");
+    std::fs::remove_file(sync_state).ok();
+
+    assert!(synced.status.success());
+    let tasks = remote["tasks"].as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["title"], "Plan trip");
+    assert!(tasks[0]["notes"].is_null());
+    assert!(!bad.status.success());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("remotes.shared.filter"));
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn sync_queue_keeps_changes_for_an_unreachable_remote_until_flush() {
+    let store_path = temp_path("cli-smoke-sync-queue.json");
+    let share = temp_path("cli-smoke-sync-queue-share");
+    let unmounted = temp_path("cli-smoke-sync-queue-unmounted");
+    let config_path = temp_path("cli-smoke-sync-queue-config.json");
+    std::fs::create_dir_all(&share).unwrap();
+    let remote_path = share.join("tasks.json");
+    let config = serde_json::json!({"remotes": {"shared": {"path": remote_path}}});
+    std::fs::write(&config_path, config.to_string()).unwrap();
+    let config = config_path.to_str().unwrap();
+    run(&store_path, &["add", "Pay rent"]);
+    run(&store_path, &["--config", config, "sync", "shared"]);
+    let added = run(&store_path, &["--json", "add", "Call mum"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap();
+
+    std::fs::rename(&share, &unmounted).unwrap();
+    let offline = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["--config", config, "sync", "shared"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run command");
+    let queue = run(&store_path, &["--config", config, "sync", "queue"]);
+    let queue_json = run(
+        &store_path,
+        &["--config", config, "--json", "sync", "queue"],
+    );
+    std::fs::rename(&unmounted, &share).unwrap();
+    let flushed = run(
+        &store_path,
+        &["--config", config, "--json", "sync", "flush"],
+    );
+    let empty = run(&store_path, &["--config", config, "sync", "queue"]);
+    let remote = stored_state(&remote_path);
+    for path in [&store_path, &config_path] {
+        std::fs::remove_file(path).ok();
+    }
+    std::fs::remove_dir_all(&share).ok();
+    let mut sync_state = store_path.clone().into_os_string();
+    sync_state.push(".sync.json");
+    std::fs::remove_file(sync_state).ok();
+
+    assert!(!offline.status.success());
+    assert!(String::from_utf8_lossy(&offline.stderr).contains("remote 'shared' is unreachable"));
+    let queue = String::from_utf8_lossy(&queue.stdout);
+    assert!(queue.contains("  Unreachable since "), "{queue}");
+    assert!(queue.contains("  Queued: 1 task\n"), "{queue}");
+    assert!(
+        queue.contains(&format!("added    {id}  Call mum")),
+        "{queue}"
+    );
+    let queue_json: serde_json::Value = serde_json::from_slice(&queue_json.stdout).unwrap();
+    assert_eq!(queue_json[0]["remote"], "shared");
+    assert_eq!(queue_json[0]["changes"][0]["change"], "added");
+    let flushed: serde_json::Value = serde_json::from_slice(&flushed.stdout).unwrap();
+    assert_eq!(flushed["synced"][0]["remote"], "shared");
+    assert_eq!(flushed["synced"][0]["pushed"][0]["id"], id);
+    assert_eq!(flushed["failed"], serde_json::json!([]));
+    assert_eq!(String::from_utf8_lossy(&empty.stdout), "Nothing queued.\n");
+    assert_eq!(remote["tasks"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn events_pull_hands_out_changes_made_after_the_first_pull_until_acked() {
+    let store_path = temp_path("cli-smoke-events.json");
+    let pull = |args: &[&str]| -> serde_json::Value {
+        let output = run(&store_path, &[&["--json", "events", "pull"], args].concat());
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let id_of = |output: Output| -> String {
+        let task: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        task["id"].as_str().unwrap().to_string()
+    };
+
+    let early = id_of(run(
+        &store_path,
+        &["--json", "add", "Before anyone listened"],
+    ));
+    let first = pull(&[]);
+    let ship = id_of(run(&store_path, &["--json", "add", "Ship"]));
+    run(&store_path, &["done", &ship]);
+    let peeked = pull(&["--limit", "1"]);
+    let taken = pull(&["--ack"]);
+    let drained = pull(&[]);
+    run(&store_path, &["delete", &early]);
+    let listed = run(&store_path, &["events", "pull"]);
+    let acked = run(&store_path, &["events", "ack", "3"]);
+    let empty = pull(&[]);
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(first, serde_json::json!([]));
+    assert_eq!(peeked.as_array().unwrap().len(), 1);
+    let kinds: Vec<(u64, &str, &str)> = taken
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| {
+            (
+                event["seq"].as_u64().unwrap(),
+                event["type"].as_str().unwrap(),
+                event["task_id"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (1, "task_added", ship.as_str()),
+            (2, "task_completed", ship.as_str())
+        ]
+    );
+    assert_eq!(drained, serde_json::json!([]));
+    let listed = String::from_utf8_lossy(&listed.stdout);
+    assert!(listed.contains(&format!(
+        "task_removed  Before anyone listened ({early}) by "
+    )));
+    assert!(String::from_utf8_lossy(&acked.stdout).contains("Acknowledged 1 event through #3"));
+    assert_eq!(empty, serde_json::json!([]));
+}
+
+#[test]
+fn webhook_deliveries_stay_queued_with_their_error_while_the_webhook_is_down() {
+    let store_path = temp_path("cli-smoke-webhooks.json");
+    let config_path = temp_path("cli-smoke-webhooks-config.json");
+    let config = serde_json::json!({
+        "webhooks": [{ "url": "http://127.0.0.1:9/", "events": ["task_completed"] }]
+    });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+    let todo = |args: &[&str]| -> Output {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .env("TODOAPP_DISABLE_WEBHOOKS", "1")
+            .output()
+            .expect("failed to run command");
+        assert!(
+            output.status.success(),
+            "{args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    };
+    let status = || -> serde_json::Value {
+        serde_json::from_slice(&todo(&["--json", "webhooks", "status"]).stdout).unwrap()
+    };
+
+    let added: serde_json::Value =
+        serde_json::from_slice(&todo(&["--json", "add", "Ship"]).stdout).unwrap();
+    let id = added["id"].as_str().unwrap().to_string();
+    todo(&["done", &id]);
+    let queued = status();
+    let delivered = todo(&["--json", "webhooks", "deliver"]);
+    let after = status();
+    let mut queue_path = store_path.clone().into_os_string();
+    queue_path.push(".webhooks.json");
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+    std::fs::remove_file(&queue_path).ok();
+
+    let queued = queued.as_array().unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0]["event"]["type"], "task_completed");
+    assert_eq!(queued[0]["event"]["task_id"], id.as_str());
+    assert_eq!(queued[0]["attempts"], 0);
+    let report: serde_json::Value = serde_json::from_slice(&delivered.stdout).unwrap();
+    assert_eq!(report["delivered"], 0);
+    assert_eq!(report["queued"], 1);
+    assert_eq!(report["failed"][0]["url"], "http://127.0.0.1:9/");
+    assert!(
+        String::from_utf8_lossy(&delivered.stderr)
+            .contains("WARNING: Still queued for http://127.0.0.1:9/")
+    );
+    assert_eq!(after[0]["attempts"], 1);
+    assert!(
+        after[0]["last_error"]
+            .as_str()
+            .unwrap()
+            .starts_with("webhook failed")
+    );
+}
+
+#[test]
+fn add_once_per_day_reuses_todays_open_task() {
+    let store_path = temp_path("cli-smoke-once-per-day.json");
+
+    let first = run(
+        &store_path,
+        &["--json", "add", "Water plants", "--once-per-day"],
+    );
+    let again = run(&store_path, &["add", "water PLANTS ", "--once-per-day"]);
+    let first: serde_json::Value = serde_json::from_slice(&first.stdout).unwrap();
+    let id = first["id"].as_str().unwrap().to_string();
+    let after_again = stored_state(&store_path);
+    run(&store_path, &["done", &id]);
+    run(&store_path, &["add", "Water plants", "--once-per-day"]);
+    let after_done = stored_state(&store_path);
+    std::fs::remove_file(&store_path).ok();
+
+    assert_eq!(
+        String::from_utf8_lossy(&again.stdout),
+        format!("Already added today: Water plants ({id})\n")
+    );
+    assert_eq!(after_again["tasks"].as_array().unwrap().len(), 1);
+    assert_eq!(after_done["tasks"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn seed_run_adds_each_due_rule_once() {
+    let store_path = temp_path("cli-smoke-seed.json");
+    let mut seeds_path = store_path.clone().into_os_string();
+    seeds_path.push(".seeds.json");
+    let at = |now: &str, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .env("TODOAPP_NOW", now)
+            .env("TZ", "UTC")
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{args:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // 2025-12-22 is a Monday.
+    at(
+        "2025-12-20T12:00:00Z",
+        &["seed", "--rule", "every mon 9am: Weekly report"],
+    );
+    let early = at("2025-12-21T12:00:00Z", &["seed", "run"]);
+    let due = at("2025-12-22T09:30:00Z", &["--json", "seed", "run"]);
+    let rerun = at("2025-12-22T10:00:00Z", &["seed", "run"]);
+    let listed = at("2025-12-22T10:00:00Z", &["seed", "list"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&seeds_path).ok();
+
+    let due: serde_json::Value = serde_json::from_str(&due).unwrap();
+    assert_eq!(early, "No seed rules due.\n");
+    assert_eq!(due[0]["title"], "Weekly report");
+    assert_eq!(due[0]["start_at"], "2025-12-22T09:00:00Z");
+    assert_eq!(rerun, "No seed rules due.\n");
+    assert!(
+        listed.starts_with("1. every monday 09:00: Weekly report (last added "),
+        "{listed}"
+    );
+}
+
+#[test]
+fn weekly_goal_is_cheered_once_and_shown_by_status_and_insights() {
+    let store_path = temp_path("cli-smoke-goal.json");
+    let config_path = temp_path("cli-smoke-goal-config.json");
+    std::fs::write(
+        &config_path,
+        r#"{ "goals": { "completions_per_week": 2 } }"#,
+    )
+    .unwrap();
+    let todo = |now: &str, args: &[&str]| -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .env("TODOAPP_NOW", now)
+            .env("TZ", "UTC")
+            .output()
+            .expect("failed to run command");
+        assert!(
+            output.status.success(),
+            "{args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    // Monday to Sunday, 2025-12-22 to 2025-12-28.
+    let monday = "2025-12-22T09:00:00Z";
+    let id = |added: String| {
+        let task: serde_json::Value = serde_json::from_str(&added).unwrap();
+        task["id"].as_str().unwrap().to_string()
+    };
+    let first = id(todo(monday, &["--json", "add", "One"]));
+    let second = id(todo(monday, &["--json", "add", "Two"]));
+    let third = id(todo(monday, &["--json", "add", "Three"]));
+
+    let one = todo(monday, &["done", &first]);
+    let two = todo("2025-12-24T09:00:00Z", &["done", &second]);
+    let three = todo("2025-12-24T10:00:00Z", &["done", &third]);
+    let status = todo("2025-12-28T20:00:00Z", &["status"]);
+    let insights: serde_json::Value =
+        serde_json::from_str(&todo("2025-12-28T20:00:00Z", &["--json", "insights"])).unwrap();
+    let next_week: serde_json::Value =
+        serde_json::from_str(&todo("2025-12-29T08:00:00Z", &["--json", "status"])).unwrap();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    let cheer = "Weekly goal reached: 2 of 2 completions. Well done!";
+    assert!(!one.contains("Weekly goal"), "{one}");
+    assert!(two.contains(cheer), "{two}");
+    assert!(!three.contains("Weekly goal"), "{three}");
+    assert!(
+        status.contains("Weekly goal reached: 3 of 2 completions."),
+        "{status}"
+    );
+    assert_eq!(insights["completions_this_week"], 3);
+    assert_eq!(insights["weekly_goal"]["reached"], true);
+    assert_eq!(next_week["week"], "2026-W01");
+    assert_eq!(
+        next_week["weekly_goal"],
+        serde_json::json!({ "target": 2, "completed": 0, "reached": false })
+    );
+}
+
+#[test]
+fn info_reports_counts_oldest_open_task_and_last_snapshot() {
+    let store_path = temp_path("cli-smoke-info.json");
+
+    let empty = run(&store_path, &["info"]);
+    run(&store_path, &["add", "Oldest"]);
+    let finished = run(&store_path, &["--json", "add", "Finished"]);
+    let finished: serde_json::Value = serde_json::from_slice(&finished.stdout).unwrap();
+    run(&store_path, &["done", finished["id"].as_str().unwrap()]);
+    run(&store_path, &["snapshot", "save", "Weekly"]);
+    let info = run(&store_path, &["--json", "info"]);
+    let mut backups = store_path.clone().into_os_string();
+    backups.push(".backups");
+    std::fs::remove_dir_all(&backups).ok();
+    std::fs::remove_file(&store_path).ok();
+
+    let empty = String::from_utf8_lossy(&empty.stdout);
+    assert!(empty.contains("No open tasks."), "{empty}");
+    assert!(empty.contains("hint: run `todo snapshot save`"), "{empty}");
+    let info: serde_json::Value = serde_json::from_slice(&info.stdout).unwrap();
+    assert_eq!(info["tasks"]["total"], 2);
+    assert_eq!(info["tasks"]["pending"], 1);
+    assert_eq!(info["tasks"]["completed"], 1);
+    assert_eq!(info["oldest_open"]["title"], "Oldest");
+    assert_eq!(info["last_backup"]["name"], "weekly");
+    assert_eq!(info["journal"]["entries"], 0);
+    assert!(info["store_bytes"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn migrate_lenient_coerces_legacy_values_and_keeps_the_old_file() {
+    let store_path = temp_path("cli-smoke-migrate.json");
+    let mut original_path = store_path.clone().into_os_string();
+    original_path.push(".pre-migrate.json");
+    let legacy = r#"{"schema_version": 3, "tasks": [
+        {"id": "1", "title": "Old", "status": "Done", "created_at": "2025-12-01T09:00:00Z", "urgent": "yes"}
+    ]}"#;
+    std::fs::write(&store_path, legacy).unwrap();
+    let todo = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .unwrap()
+    };
+
+    let strict = todo(&["migrate"]);
+    let lenient = todo(&["migrate", "--lenient"]);
+    let task = todo(&["--json", "show", "1"]);
+    let original = std::fs::read_to_string(&original_path).unwrap_or_default();
+    let store: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&store_path).unwrap()).unwrap();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&original_path).ok();
+
+    assert!(!strict.status.success());
+    assert!(String::from_utf8_lossy(&strict.stderr).contains("todo migrate --lenient"));
+    let lenient = String::from_utf8(lenient.stdout).unwrap();
+    assert!(
+        lenient.contains("Coerced task 1: status \"Done\" -> \"completed\"\n"),
+        "{lenient}"
+    );
+    assert!(
+        lenient.contains("Coerced task 1: urgent \"yes\" -> true\n"),
+        "{lenient}"
+    );
+    assert!(lenient.contains("from schema_version 3 to"), "{lenient}");
+    let task: serde_json::Value = serde_json::from_slice(&task.stdout).unwrap();
+    assert_eq!(task["status"], "completed");
+    assert_eq!(store["tasks"][0]["urgent"], true);
+    assert_eq!(original, legacy);
+}
+
+#[test]
+fn journal_notes_add_up_and_show_above_list_today() {
+    let store_path = temp_path("cli-smoke-journal.json");
+
+    run(&store_path, &["journal", "Offsite in Berlin"]);
+    run(&store_path, &["journal", "today", "Bring slides"]);
+    let written = run(&store_path, &["--json", "journal"]);
+    let listed = run(&store_path, &["list", "today"]);
+    let cleared = run(&store_path, &["journal", "--clear"]);
+    let after = run(&store_path, &["--json", "journal"]);
+    std::fs::remove_file(&store_path).ok();
+
+    let written: serde_json::Value = serde_json::from_slice(&written.stdout).unwrap();
+    assert_eq!(written["note"], "Offsite in Berlin\nBring slides");
+    let listed = String::from_utf8(listed.stdout).unwrap();
+    assert_eq!(
+        listed.lines().take(3).collect::<Vec<_>>(),
+        ["Journal", "  Offsite in Berlin", "  Bring slides"]
+    );
+    assert!(String::from_utf8_lossy(&cleared.stdout).starts_with("Cleared the journal for "));
+    let after: serde_json::Value = serde_json::from_slice(&after.stdout).unwrap();
+    assert_eq!(after["date"], written["date"]);
+    assert!(after["note"].is_null());
+}
+
+#[test]
+fn title_rules_clean_up_added_and_edited_titles() {
+    let store_path = temp_path("cli-smoke-titles.json");
+    let config_path = temp_path("cli-smoke-titles-config.json");
+    let config = serde_json::json!({
+        "titles": { "sentence_case": true, "trim_trailing_punctuation": true, "strip_emoji": true }
+    });
+    std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+    let run_with = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run command");
+        assert!(
+            output.status.success(),
+            "{args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    };
+
+    let added = run_with(&["--json", "add", "🛒 BUY MILK!!"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let id = added["id"].as_str().unwrap().to_string();
+    run_with(&["edit", &id, "Fix the API Bug On Staging. ✅"]);
+    let untouched = run(&store_path, &["--json", "add", "Keep THIS as typed!"]);
+    let untouched: serde_json::Value = serde_json::from_slice(&untouched.stdout).unwrap();
+    let state = stored_state(&store_path);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert_eq!(added["title"], "Buy milk");
+    assert_eq!(state["tasks"][0]["title"], "Fix the API bug on staging");
+    assert_eq!(untouched["title"], "Keep THIS as typed!");
+}
+
+#[test]
+fn allow_overrides_limits_which_fields_config_override_may_change() {
+    let store_path = temp_path("cli-smoke-allow-overrides.json");
+    let config_path = temp_path("cli-smoke-allow-overrides-config.json");
+    std::fs::write(&config_path, r#"{"allow_overrides": ["theme"]}"#).unwrap();
+    let run_with = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &config_path)
+            .output()
+            .expect("failed to run command")
+    };
+
+    let themed = run_with(&["--config-override", "theme=noir", "list", "today"]);
+    let aliased = run_with(&["--config-override", "aliases.ls=list", "list", "today"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&config_path).ok();
+
+    assert!(
+        themed.status.success(),
+        "{}",
+        String::from_utf8_lossy(&themed.stderr)
+    );
+    assert!(!aliased.status.success());
+    let stderr = String::from_utf8_lossy(&aliased.stderr);
+    assert!(
+        stderr.contains("--config-override cannot change 'aliases' here"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("allow_overrides in the config allows: theme"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn allow_overrides_cannot_be_dropped_by_loading_another_config() {
+    let store_path = temp_path("cli-smoke-locked.json");
+    let locked_path = temp_path("cli-smoke-locked-config.json");
+    let mine_path = temp_path("cli-smoke-locked-mine.json");
+    std::fs::write(&locked_path, r#"{"allow_overrides": []}"#).unwrap();
+    std::fs::write(&mine_path, "{}").unwrap();
+    let mine = mine_path.to_str().unwrap();
+    let run_with = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(args)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", &locked_path)
+            .output()
+            .expect("failed to run command")
+    };
+
+    let swapped = run_with(&[
+        "--config",
+        mine,
+        "--config-override",
+        "theme=noir",
+        "list",
+        "backlog",
+    ]);
+    let profiled = run_with(&["--profile", "mine", "list", "backlog"]);
+    let overridden = run_with(&["--config-override", "theme=noir", "list", "backlog"]);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&locked_path).ok();
+    std::fs::remove_file(&mine_path).ok();
+
+    assert!(!swapped.status.success());
+    let stderr = String::from_utf8_lossy(&swapped.stderr);
+    assert!(
+        stderr.contains("--config cannot load another config here"),
+        "{stderr}"
+    );
+    assert!(!profiled.status.success());
+    assert!(
+        String::from_utf8_lossy(&profiled.stderr)
+            .contains("--profile cannot load another config here")
+    );
+    assert!(!overridden.status.success());
+}
+
+// The default config lives under HOME, which only unix builds read.
+#[cfg(unix)]
+#[test]
+fn allow_overrides_in_the_default_config_refuses_todoapp_config_path() {
+    let home = temp_path("cli-smoke-locked-home");
+    let default_dir = home.join(".config").join("todoapp");
+    std::fs::create_dir_all(&default_dir).unwrap();
+    let default_path = default_dir.join("config.json");
+    std::fs::write(&default_path, r#"{"allow_overrides": []}"#).unwrap();
+    let store_path = temp_path("cli-smoke-locked-home.json");
+    let mine_path = temp_path("cli-smoke-locked-home-mine.json");
+    std::fs::write(&mine_path, "{}").unwrap();
+    let run_with = |config_path: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+            .args(["list", "backlog"])
+            .env("HOME", &home)
+            .env("TODOAPP_STORE_PATH", &store_path)
+            .env("TODOAPP_CONFIG_PATH", config_path)
+            .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+            .output()
+            .expect("failed to run command")
+    };
+
+    let swapped = run_with(&mine_path);
+    let same = run_with(&default_path);
+    std::fs::remove_dir_all(&home).ok();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&mine_path).ok();
+
+    assert!(!swapped.status.success());
+    let stderr = String::from_utf8_lossy(&swapped.stderr);
+    assert!(
+        stderr.contains("TODOAPP_CONFIG_PATH cannot load another config here"),
+        "{stderr}"
+    );
+    // Commands such as `listen` pass the config they run with on to the processes they start.
+    assert!(
+        same.status.success(),
+        "{}",
+        String::from_utf8_lossy(&same.stderr)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn watch_runs_the_command_as_tasks_join_and_leave_the_filter() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let store_path = temp_path("cli-smoke-watch.json");
+    let log_path = temp_path("cli-smoke-watch.log");
+    run(&store_path, &["add", "Already there"]);
+    let exec = format!("echo {{change}} {{title}} >> '{}'", log_path.display());
+    let mut watcher = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args([
+            "watch",
+            "--filter",
+            "status:pending",
+            "--on",
+            "added,removed",
+            "--interval",
+            "1",
+            "--exec",
+            &exec,
+        ])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .env("TODOAPP_DISABLE_NOTIFICATIONS", "1")
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start watch");
+    let mut started = String::new();
+    BufReader::new(watcher.stderr.take().unwrap())
+        .read_line(&mut started)
+        .unwrap();
+
+    let added = run(&store_path, &["--json", "add", "It's $(shipped)"]);
+    let added: serde_json::Value = serde_json::from_slice(&added.stdout).unwrap();
+    let wait_for_lines = |count: usize| {
+        for _ in 0..50 {
+            let log = std::fs::read_to_string(&log_path).unwrap_or_default();
+            if log.lines().count() >= count {
+                return log;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        std::fs::read_to_string(&log_path).unwrap_or_default()
+    };
+    let after_add = wait_for_lines(1);
+    run(&store_path, &["done", added["id"].as_str().unwrap()]);
+    let after_done = wait_for_lines(2);
+    watcher.kill().ok();
+    watcher.wait().ok();
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&log_path).ok();
+
+    assert!(started.contains("Watching 1 matching tasks"), "{started}");
+    assert_eq!(after_add, "added It's $(shipped)\n");
+    assert_eq!(
+        after_done,
+        "added It's $(shipped)\nremoved It's $(shipped)\n"
+    );
+}
+
+#[test]
+fn import_csv_maps_columns_and_previews_with_dry_run() {
+    let store_path = temp_path("cli-smoke-import.json");
+    let csv_path = temp_path("cli-smoke-import.csv");
+    std::fs::write(
+        &csv_path,
+        "Task,Owner,Due,Labels\n\"Renew passport, soon\",me,24/12/2025 9:30,admin travel\nBook flights,me,03/01/2026,\n",
+    )
+    .unwrap();
+    let csv = csv_path.to_str().unwrap();
+    let args = [
+        "import",
+        "--format",
+        "csv",
+        "--map",
+        "title=1,due=3,tags=Labels",
+        "--date-format",
+        "DD/MM/YYYY",
+        csv,
+    ];
+
+    let preview = run(&store_path, &[&args[..], &["--dry-run"]].concat());
+    let untouched = store_path.exists();
+    let imported = run(&store_path, &args);
+    let failed = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["import", "--map", "title=1,due=3", csv])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run import");
+    let state = stored_state(&store_path);
+    std::fs::remove_file(&store_path).ok();
+    std::fs::remove_file(&csv_path).ok();
+
+    let preview = String::from_utf8_lossy(&preview.stdout);
+    assert!(
+        preview.contains("Renew passport, soon") && preview.contains("admin, travel"),
+        "{preview}"
+    );
+    assert!(preview.contains("Would import 2 tasks"), "{preview}");
+    assert!(!untouched);
+    assert!(String::from_utf8_lossy(&imported.stdout).contains("Imported 2 tasks"));
+    assert!(!failed.status.success());
+    assert!(
+        String::from_utf8_lossy(&failed.stderr)
+            .contains("row 2: due '24/12/2025 9:30' is not a date")
+    );
+    let tasks = state["tasks"].as_array().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["title"], "Renew passport, soon");
+    assert_eq!(tasks[0]["tags"], serde_json::json!(["admin", "travel"]));
+    assert!(
+        tasks[0]["due_at"]
+            .as_str()
+            .unwrap()
+            .starts_with("2025-12-24T09:30:00")
+    );
+    assert!(
+        tasks[1]["due_at"]
+            .as_str()
+            .unwrap()
+            .starts_with("2026-01-03T00:00:00")
+    );
+}
+
+#[test]
+fn export_verify_reports_fields_lost_in_a_round_trip() {
+    let store_path = temp_path("cli-smoke-verify.json");
+    let csv_path = temp_path("cli-smoke-verify.csv");
+    let copy_path = temp_path("cli-smoke-verify-copy.json");
+    run(
+        &store_path,
+        &["add", "Pay \"rent\", today", "--urgent", "--tag", "home"],
+    );
+    let clean = run(&store_path, &["export", "--verify"]);
+    run(
+        &store_path,
+        &[
+            "export",
+            "--format",
+            "csv",
+            "--output",
+            csv_path.to_str().unwrap(),
+        ],
+    );
+    run(&copy_path, &["import", csv_path.to_str().unwrap()]);
+    run(
+        &store_path,
+        &["add", "Plan launch", "--tag", "side project"],
+    );
+    let lossy = Command::new(env!("CARGO_BIN_EXE_todo_opt"))
+        .args(["--json", "export", "--verify"])
+        .env("TODOAPP_STORE_PATH", &store_path)
+        .output()
+        .expect("failed to run export --verify");
+    let copy = stored_state(&copy_path);
+    for path in [&store_path, &csv_path, &copy_path] {
+        std::fs::remove_file(path).ok();
+    }
+
+    let clean = String::from_utf8_lossy(&clean.stdout);
+    assert!(clean.contains("csv: 1 task came back intact"), "{clean}");
+    assert_eq!(copy["tasks"][0]["title"], "Pay \"rent\", today");
+    assert_eq!(copy["tasks"][0]["urgent"], true);
+    let report: serde_json::Value = serde_json::from_slice(&lossy.stdout).unwrap();
+    assert_eq!(report["lossless"], false);
+    assert_eq!(report["formats"][0]["lossless"], true);
+    assert_eq!(report["formats"][1]["lost"][0]["field"], "tags");
+}
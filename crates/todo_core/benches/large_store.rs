@@ -0,0 +1,211 @@
+//! Latency of `list`, `add`, `done`, and `search` against generated stores of 10k and 100k tasks.
+//!
+//! Run with: `cargo bench -p todo_core --bench large_store [-- SIZE...]`
+//!
+//! Each operation goes through the public task API, which is what the CLI calls, and is timed
+//! over [`ITERATIONS`] runs on a fresh store. Results are printed in the libtest bench format,
+//! so the benchmark workflow tracks them like any other `cargo bench` output. The median is
+//! also checked against [`BUDGETS`]; any operation over its budget makes the run exit with
+//! status 1. Budgets are generous enough for slow shared CI runners: they catch
+//! order-of-magnitude regressions, and the workflow's relative alert catches the rest.
+
+use std::time::{Duration, Instant};
+use todo_core::model::{Task, TaskStatus};
+use todo_core::storage::json_store::{self, TaskState};
+use todo_core::task_api;
+
+const DEFAULT_SIZES: [usize; 2] = [10_000, 100_000];
+const ITERATIONS: usize = 10;
+
+/// Largest acceptable median per operation, by store size. Sizes without an entry are
+/// measured but not checked.
+const BUDGETS: [(usize, Operation, Duration); 8] = [
+    (10_000, Operation::List, Duration::from_millis(250)),
+    (10_000, Operation::Add, Duration::from_millis(400)),
+    (10_000, Operation::Done, Duration::from_millis(400)),
+    (10_000, Operation::Search, Duration::from_millis(50)),
+    (100_000, Operation::List, Duration::from_millis(2_500)),
+    (100_000, Operation::Add, Duration::from_millis(4_000)),
+    (100_000, Operation::Done, Duration::from_millis(4_000)),
+    (100_000, Operation::Search, Duration::from_millis(150)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    List,
+    Add,
+    Done,
+    Search,
+}
+
+impl Operation {
+    const ALL: [Operation; 4] = [
+        Operation::List,
+        Operation::Add,
+        Operation::Done,
+        Operation::Search,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Operation::List => "list",
+            Operation::Add => "add",
+            Operation::Done => "done",
+            Operation::Search => "search",
+        }
+    }
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`; anything that is not a number is ignored.
+    let mut sizes: Vec<usize> = std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.replace('_', "").parse().ok())
+        .collect();
+    if sizes.is_empty() {
+        sizes = DEFAULT_SIZES.to_vec();
+    }
+
+    let dir = std::env::temp_dir().join(format!("todoapp-large-store-{}", std::process::id()));
+    let store = dir.join("tasks.json");
+    json_store::override_store_path(store.clone()).unwrap();
+
+    let mut over_budget = Vec::new();
+    for size in sizes {
+        for operation in Operation::ALL {
+            std::fs::create_dir_all(&dir).unwrap();
+            json_store::save_state(&store, &seed(size)).unwrap();
+            let samples = measure(operation, size);
+            std::fs::remove_dir_all(&dir).ok();
+
+            let median = samples[samples.len() / 2];
+            report(operation, size, median, spread(&samples, median));
+            if let Some(budget) = budget(size, operation)
+                && median > budget
+            {
+                over_budget.push(format!(
+                    "{} on {size} tasks: median {median:.2?}, budget {budget:.2?}",
+                    operation.name()
+                ));
+            }
+        }
+    }
+
+    if !over_budget.is_empty() {
+        eprintln!("over the performance budget:");
+        for line in &over_budget {
+            eprintln!("  {line}");
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Sorted durations of [`ITERATIONS`] runs of `operation`.
+fn measure(operation: Operation, size: usize) -> Vec<Duration> {
+    let mut samples: Vec<Duration> = (0..ITERATIONS)
+        .map(|iteration| {
+            let started = Instant::now();
+            match operation {
+                Operation::List => {
+                    task_api::list_backlog_with_focus(time::Duration::ZERO).unwrap();
+                }
+                Operation::Add => {
+                    task_api::add_task(&format!("Added during benchmark {iteration}")).unwrap();
+                }
+                Operation::Done => {
+                    task_api::complete_task(&pending_id(iteration, size), None).unwrap();
+                }
+                // The first search also builds the index.
+                Operation::Search => {
+                    let query = format!("number {}", size / 2 + iteration * 4 + 1);
+                    task_api::search_tasks(&query).unwrap();
+                }
+            }
+            started.elapsed()
+        })
+        .collect();
+    samples.sort();
+    samples
+}
+
+/// Median absolute deviation, reported as libtest's `+/-`.
+fn spread(samples: &[Duration], median: Duration) -> Duration {
+    let mut deviations: Vec<Duration> = samples
+        .iter()
+        .map(|sample| sample.abs_diff(median))
+        .collect();
+    deviations.sort();
+    deviations[deviations.len() / 2]
+}
+
+fn budget(size: usize, operation: Operation) -> Option<Duration> {
+    BUDGETS
+        .iter()
+        .find(|(budget_size, budget_operation, _)| {
+            *budget_size == size && *budget_operation == operation
+        })
+        .map(|(_, _, budget)| *budget)
+}
+
+fn report(operation: Operation, size: usize, median: Duration, spread: Duration) {
+    println!(
+        "test large_store::{}_{size} ... bench: {:>15} ns/iter (+/- {})",
+        operation.name(),
+        thousands(median.as_nanos()),
+        thousands(spread.as_nanos())
+    );
+}
+
+fn thousands(value: u128) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Every fourth seeded task is completed; the rest are pending.
+fn pending_id(iteration: usize, size: usize) -> String {
+    ((iteration * 4 + 1) % size).to_string()
+}
+
+/// A store shaped like long-lived real ones: mostly pending, with schedules, tags, and
+/// projects on a share of the tasks.
+fn seed(size: usize) -> TaskState {
+    TaskState {
+        tasks: (0..size)
+            .map(|index| {
+                let completed = index.is_multiple_of(4);
+                Task {
+                    id: index.to_string(),
+                    title: format!("Benchmark task number {index}"),
+                    status: if completed {
+                        TaskStatus::Completed
+                    } else {
+                        TaskStatus::Pending
+                    },
+                    created_at: "2025-12-20T00:00:00Z".to_string(),
+                    due_at: index
+                        .is_multiple_of(3)
+                        .then(|| "2025-12-22T09:00:00Z".to_string()),
+                    completed_at: completed.then(|| "2025-12-21T08:00:00Z".to_string()),
+                    urgent: index.is_multiple_of(7),
+                    tags: if index.is_multiple_of(5) {
+                        vec!["errand".to_string()]
+                    } else {
+                        Vec::new()
+                    },
+                    project: index
+                        .is_multiple_of(2)
+                        .then(|| format!("project-{}", index % 20)),
+                    ..Default::default()
+                }
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
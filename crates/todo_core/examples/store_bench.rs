@@ -0,0 +1,110 @@
+//! Compares completing tasks one at a time with full snapshot rewrites against journaled commits.
+//!
+//! Run with: `cargo run --release -p todo_core --example store_bench -- [tasks] [mutations]`
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use todo_core::model::{Task, TaskStatus};
+use todo_core::storage::json_store::{self, TaskState};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let task_count: usize = args.next().and_then(|v| v.parse().ok()).unwrap_or(5000);
+    let mutations: usize = args.next().and_then(|v| v.parse().ok()).unwrap_or(100);
+
+    let rewrite = run(task_count, mutations, "rewrite", |path, _, after| {
+        json_store::save_state(path, after).unwrap()
+    });
+    let journaled = run(task_count, mutations, "journal", |path, before, after| {
+        json_store::commit_state(path, before, after).unwrap()
+    });
+
+    println!("{task_count} tasks, {mutations} completions (load + mutate + save each)");
+    report("full rewrite", rewrite, mutations);
+    report("journal", journaled, mutations);
+
+    let (full_scan, indexed) = lookups(task_count, mutations);
+    println!("{task_count} tasks, {mutations} point lookups");
+    report("full load", full_scan, mutations);
+    report("index", indexed, mutations);
+
+    std::fs::remove_dir(bench_path("rewrite").parent().unwrap()).ok();
+}
+
+fn run(
+    task_count: usize,
+    mutations: usize,
+    label: &str,
+    save: impl Fn(&Path, &TaskState, &TaskState),
+) -> Duration {
+    let path = bench_path(label);
+    json_store::save_state(&path, &seed(task_count)).unwrap();
+
+    let started = Instant::now();
+    for index in 0..mutations {
+        let before = json_store::load_state(&path).unwrap();
+        let mut after = before.clone();
+        let task = &mut after.tasks[index % task_count];
+        task.status = TaskStatus::Completed;
+        task.completed_at = Some("2025-12-21T08:00:00Z".to_string());
+        save(&path, &before, &after);
+    }
+    let elapsed = started.elapsed();
+
+    std::fs::remove_file(path.with_file_name(format!("{label}.json.journal"))).ok();
+    std::fs::remove_file(path.with_file_name(format!("{label}.json.idx"))).ok();
+    std::fs::remove_file(&path).ok();
+    elapsed
+}
+
+fn lookups(task_count: usize, lookups: usize) -> (Duration, Duration) {
+    let path = bench_path("lookup");
+    json_store::save_state(&path, &seed(task_count)).unwrap();
+    let ids: Vec<String> = (0..lookups)
+        .map(|index| (index * 7919 % task_count).to_string())
+        .collect();
+
+    let started = Instant::now();
+    for id in &ids {
+        let state = json_store::load_state(&path).unwrap();
+        assert!(state.tasks.iter().any(|task| &task.id == id));
+    }
+    let full_scan = started.elapsed();
+
+    let started = Instant::now();
+    for id in &ids {
+        assert!(json_store::load_task(&path, id).unwrap().is_some());
+    }
+    let indexed = started.elapsed();
+
+    std::fs::remove_file(path.with_file_name("lookup.json.idx")).ok();
+    std::fs::remove_file(&path).ok();
+    (full_scan, indexed)
+}
+
+fn seed(task_count: usize) -> TaskState {
+    TaskState {
+        tasks: (0..task_count)
+            .map(|index| Task {
+                id: index.to_string(),
+                title: format!("Benchmark task number {index}"),
+                created_at: "2025-12-20T00:00:00Z".to_string(),
+                due_at: Some("2025-12-22T09:00:00Z".to_string()),
+                urgent: index % 7 == 0,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn bench_path(label: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("todoapp-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(format!("{label}.json"))
+}
+
+fn report(name: &str, elapsed: Duration, mutations: usize) {
+    let per_op = elapsed / mutations.max(1) as u32;
+    println!("  {name:<12} total {elapsed:>10.2?}  per op {per_op:>10.2?}");
+}
@@ -0,0 +1,54 @@
+//! The current time, for everything that stamps a change or compares against now.
+//!
+//! [`set_source`] is the one way to replace the system clock. The CLI uses it to pin the time
+//! to [`NOW_ENV`] with [`pin_from_env`]: `todo --record` pins each command to the moment it was
+//! typed and `todo run --replay` pins it to the same moment again, so a replay makes the same
+//! task ids and timestamps as the recording.
+
+use crate::error::AppError;
+use std::sync::OnceLock;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+pub const NOW_ENV: &str = "TODOAPP_NOW";
+
+static PINNED: OnceLock<OffsetDateTime> = OnceLock::new();
+static SOURCE: OnceLock<fn() -> OffsetDateTime> = OnceLock::new();
+
+/// The [`set_source`] clock, or the system clock.
+pub fn now_utc() -> OffsetDateTime {
+    SOURCE
+        .get()
+        .map_or_else(OffsetDateTime::now_utc, |source| source())
+}
+
+/// Read the time from `source` instead of the system clock, which a browser build of the
+/// standard library does not have; a web UI passes one backed by the JavaScript `Date`. It can
+/// only be set once.
+pub fn set_source(source: fn() -> OffsetDateTime) -> Result<(), AppError> {
+    SOURCE
+        .set(source)
+        .map_err(|_| AppError::invalid_input("the clock source is already set"))
+}
+
+/// Pin the clock with [`set_source`] to [`NOW_ENV`] when it is set to a valid time; otherwise
+/// leave it alone.
+pub fn pin_from_env() -> Result<(), AppError> {
+    let Some(at) = std::env::var(NOW_ENV)
+        .ok()
+        .and_then(|value| OffsetDateTime::parse(value.trim(), &Rfc3339).ok())
+    else {
+        return Ok(());
+    };
+    PINNED
+        .set(at.to_offset(time::UtcOffset::UTC))
+        .map_err(|_| AppError::invalid_input("the clock is already pinned"))?;
+    set_source(pinned)
+}
+
+fn pinned() -> OffsetDateTime {
+    PINNED
+        .get()
+        .copied()
+        .unwrap_or_else(OffsetDateTime::now_utc)
+}
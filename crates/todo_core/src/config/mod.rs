@@ -1,19 +1,44 @@
 use crate::error::AppError;
+use crate::model::{Marker, MarkerColor, Task};
+use crate::stats::DaySections;
+use crate::storage::outbox::EventKind;
+use crate::text;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use time::macros::format_description;
+use time::{Duration, Time};
 
 const CONFIG_FILE_NAME: &str = "config.json";
-const CONFIG_ENV_VAR: &str = "TODOAPP_CONFIG_PATH";
+pub const CONFIG_ENV_VAR: &str = "TODOAPP_CONFIG_PATH";
+const PROFILES_DIR: &str = "profiles";
+const MAX_PROFILE_NAME_LEN: usize = 64;
+
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
 #[derive(Debug, Clone)]
 pub struct Palette {
     pub accent: &'static str,
     pub muted: &'static str,
     pub reset: &'static str,
+    /// Spell markers out as words and skip tables and symbols, one fact per line, so output
+    /// reads well with a screen reader.
+    pub accessible: bool,
 }
 
 impl Palette {
+    /// No colors and [`Palette::accessible`] set, for `accessible` in the config or
+    /// `--accessible`.
+    pub fn for_screen_readers() -> Self {
+        Palette {
+            accent: "",
+            muted: "",
+            reset: "",
+            accessible: true,
+        }
+    }
+
     pub fn accentize(&self, text: &str) -> String {
         if self.accent.is_empty() {
             text.to_string()
@@ -29,30 +54,67 @@ impl Palette {
             format!("{}{}{}", self.muted, text, self.reset)
         }
     }
+
+    /// A task's marker as shown before its title: the symbol, then a dot in the marker color,
+    /// or the color's name in brackets when the theme has no colors.
+    pub fn marker(&self, marker: &Marker) -> String {
+        let mut shown = marker.symbol.clone().unwrap_or_default();
+        if let Some(color) = marker.color {
+            if self.reset.is_empty() {
+                shown.push_str(&format!("[{}]", color.as_str()));
+            } else {
+                let code = match color {
+                    MarkerColor::Red => 196,
+                    MarkerColor::Orange => 208,
+                    MarkerColor::Yellow => 220,
+                    MarkerColor::Green => 34,
+                    MarkerColor::Blue => 33,
+                    MarkerColor::Purple => 129,
+                };
+                shown.push_str(&format!("\x1b[38;5;{code}m●{}", self.reset));
+            }
+        }
+        shown
+    }
+
+    /// Muted and struck through, for replaced values. Plain when the theme has no colors.
+    pub fn strike(&self, text: &str) -> String {
+        if self.muted.is_empty() {
+            text.to_string()
+        } else {
+            format!("{}\x1b[9m{}{}", self.muted, text, self.reset)
+        }
+    }
 }
 
+/// Canonical names [`palette_for_theme`] knows; any other theme falls back to no colors.
+pub const THEMES: [&str; 3] = ["default", "noir", "solarized"];
+
 pub fn palette_for_theme(theme: Option<&str>) -> Palette {
     match canonical_theme_name_option(theme) {
         Some(ref name) if name == "noir" => Palette {
             accent: "\x1b[38;5;208m",
             muted: "\x1b[38;5;250m",
             reset: "\x1b[0m",
+            accessible: false,
         },
         Some(ref name) if name == "solarized" => Palette {
             accent: "\x1b[38;5;108m",
             muted: "\x1b[38;5;250m",
             reset: "\x1b[0m",
+            accessible: false,
         },
         _ => Palette {
             accent: "",
             muted: "",
             reset: "",
+            accessible: false,
         },
     }
 }
 
 fn canonical_theme_name_option(theme: Option<&str>) -> Option<String> {
-    theme.and_then(|value| canonical_theme_name(value))
+    theme.and_then(canonical_theme_name)
 }
 
 pub fn canonical_theme_name(raw: &str) -> Option<String> {
@@ -87,12 +149,598 @@ pub struct Config {
     pub theme: Option<String>,
     #[serde(default)]
     pub aliases: HashMap<String, String>,
+    /// Where the store lives when neither `--profile` nor `TODOAPP_STORE_PATH` names one, as
+    /// chosen by `init`. Defaults to `tasks.json` beside the default config.
+    #[serde(default)]
+    pub store_path: Option<PathBuf>,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub insights: InsightsConfig,
+    #[serde(default)]
+    pub ordering: OrderingConfig,
+    #[serde(default)]
+    pub day_sections: DaySectionsConfig,
+    #[serde(default)]
+    pub tickets: TicketConfig,
+    #[serde(default)]
+    pub wip_limit: WipLimitConfig,
+    #[serde(default)]
+    pub goals: GoalsConfig,
+    #[serde(default)]
+    pub titles: TitleConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Checklists started with `routine start NAME`, such as
+    /// `"morning": ["stretch", "email triage"]`; each item becomes a task for the day.
+    #[serde(default)]
+    pub routines: BTreeMap<String, Vec<String>>,
+    /// Other copies of the store to `sync` with, by name.
+    #[serde(default)]
+    pub remotes: BTreeMap<String, RemoteConfig>,
+    #[serde(default)]
+    pub interactive: InteractiveConfig,
+    /// Plain, word-based output for screen readers, as with `--accessible`.
+    #[serde(default)]
+    pub accessible: bool,
+    /// Show `list`, `search`, and `show` output taller than the terminal in the built-in pager.
+    #[serde(default)]
+    pub pager: bool,
+    /// Skip the warnings about store and config files other users own or can write, for a store
+    /// deliberately shared on this machine. `doctor` still reports them.
+    #[serde(default)]
+    pub allow_shared_store: bool,
+    /// The only fields `--config-override` may change, such as `["theme"]`, for shared or kiosk
+    /// installs. Unset allows every field; `[]` allows none.
+    #[serde(default)]
+    pub allow_overrides: Option<Vec<String>>,
+    /// How long past its deadline a task may run before it counts as overdue, such as `15m`,
+    /// in lists, filters, and notifications. Defaults to none.
+    #[serde(default, with = "crate::duration::text")]
+    pub overdue_grace: Duration,
+    /// Count down to deadlines closer than this, such as `2h`: lists show "due in 1h23m" and
+    /// JSON adds `due_in_seconds`. Defaults to none.
+    #[serde(default, with = "crate::duration::text")]
+    pub due_soon_within: Duration,
+    /// URLs POSTed each store event as it happens, the same JSON `events pull` prints.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Only these event types, such as `task_completed`; empty sends every one.
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+}
+
+impl WebhookConfig {
+    pub fn wants(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    /// A store file, such as one in a folder shared between machines. The first sync creates
+    /// it.
+    pub path: PathBuf,
+    /// Only tasks matching this filter expression, such as `tag:shared`, are synced. Unset
+    /// syncs every task.
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Fields never sent to the remote. Each side keeps its own value of them.
+    #[serde(default)]
+    pub exclude_fields: Vec<SyncField>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncField {
+    Notes,
+    Comments,
+    Metadata,
+    Ticket,
+    Project,
+    CompletionHistory,
+}
+
+/// Settings for interactive mode (`todo_opt` without arguments).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InteractiveConfig {
+    /// Rewrite the store at most this often; changes in between go to the journal. 0 rewrites
+    /// it after every change, as one-shot commands do.
+    #[serde(default = "default_autosave_secs")]
+    pub autosave_secs: u64,
+}
+
+fn default_autosave_secs() -> u64 {
+    5
+}
+
+impl Default for InteractiveConfig {
+    fn default() -> Self {
+        Self {
+            autosave_secs: default_autosave_secs(),
+        }
+    }
+}
+
+/// Rules applied by `maintain`, see [`crate::storage::archive`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Move completed tasks to the archive this many days after completion. Unset keeps them
+    /// in the store.
+    #[serde(default)]
+    pub archive_completed_after_days: Option<u64>,
+    /// Drop archived tasks this many days after they were archived. Unset keeps them forever.
+    #[serde(default)]
+    pub purge_archived_after_days: Option<u64>,
+    /// Also apply the rules before other commands, at most once per `startup_interval_hours`.
+    #[serde(default)]
+    pub on_startup: bool,
+    #[serde(default = "default_startup_interval_hours")]
+    pub startup_interval_hours: u64,
+}
+
+fn default_startup_interval_hours() -> u64 {
+    24
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            archive_completed_after_days: None,
+            purge_archived_after_days: None,
+            on_startup: false,
+            startup_interval_hours: default_startup_interval_hours(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WipLimitConfig {
+    /// Most pending tasks planned for today: those scheduled today or earlier, plus the focused
+    /// task. Unset means no limit.
+    #[serde(default)]
+    pub today: Option<usize>,
+    #[serde(default)]
+    pub enforce: WipEnforcement,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoalsConfig {
+    /// Completions to aim for each ISO week, Monday to Sunday. `insights` and `status` show
+    /// progress toward it, and `done` cheers the completion that reaches it. Unset means no
+    /// goal.
+    #[serde(default)]
+    pub completions_per_week: Option<usize>,
+}
+
+/// Cleanups applied to titles given to `add` and `edit`, for lists pasted or imported from messy
+/// sources. Each rule is off until turned on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TitleConfig {
+    /// Capitalize the first letter and lowercase words such as `Milk`, or a title in all capitals.
+    #[serde(default)]
+    pub sentence_case: bool,
+    /// Drop the periods, commas, colons, semicolons, and exclamation marks a title ends with.
+    #[serde(default)]
+    pub trim_trailing_punctuation: bool,
+    /// Remove emoji, flags, and keycaps.
+    #[serde(default)]
+    pub strip_emoji: bool,
+}
+
+impl TitleConfig {
+    /// `title` with the rules that are on applied; unchanged when they would leave nothing.
+    pub fn apply(&self, title: &str) -> String {
+        let mut cleaned = title.to_string();
+        if self.strip_emoji {
+            cleaned = text::strip_emoji(&cleaned);
+        }
+        if self.trim_trailing_punctuation {
+            cleaned = text::trim_trailing_punctuation(&cleaned);
+        }
+        if self.sentence_case {
+            cleaned = text::sentence_case(&cleaned);
+        }
+        if cleaned.trim().is_empty() {
+            title.to_string()
+        } else {
+            cleaned
+        }
+    }
+}
+
+/// What `schedule`, `reschedule`, and `focus` do when they would exceed `wip_limit.today`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WipEnforcement {
+    /// Make the change and print a warning.
+    #[default]
+    Warn,
+    /// Refuse the change unless `--force` is given.
+    Block,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketConfig {
+    /// Ticket systems `refresh-tickets` can read, by name.
+    #[serde(default)]
+    pub providers: BTreeMap<String, TicketProvider>,
+}
+
+/// Generic REST mapping: fetch `url` and read the title and status from the JSON response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketProvider {
+    /// Tickets whose key starts with this text (e.g. `JIRA-`) use this provider unless they name
+    /// one explicitly. Compared without regard to case.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Request URL; `{key}` is replaced with the ticket key.
+    pub url: String,
+    /// Extra request headers. `${NAME}` in a value is replaced with the environment variable
+    /// `NAME`, so tokens need not be written into the config file.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// JSON Pointer (RFC 6901) to the ticket title, e.g. `/fields/summary`.
+    pub title_pointer: String,
+    /// JSON Pointer to the ticket status, e.g. `/fields/status/name`.
+    #[serde(default)]
+    pub status_pointer: Option<String>,
+    /// Statuses that mean the ticket is closed; linked pending tasks are then completed.
+    #[serde(default)]
+    pub closed_statuses: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderingConfig {
+    /// Sort keys for `list today`, applied in order; later keys break ties of earlier ones.
+    /// An empty list keeps store order.
+    #[serde(default = "default_today_order")]
+    pub today: Vec<SortKey>,
+}
+
+fn default_today_order() -> Vec<SortKey> {
+    vec![
+        SortKey::Focus,
+        SortKey::Urgent,
+        SortKey::Overdue,
+        SortKey::Scheduled,
+    ]
+}
+
+impl Default for OrderingConfig {
+    fn default() -> Self {
+        Self {
+            today: default_today_order(),
+        }
+    }
+}
+
+/// How `list today` splits the day into morning, afternoon, and evening sections by planned
+/// time, with unscheduled tasks last.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaySectionsConfig {
+    /// Set to false for one table, as before sections.
+    #[serde(default = "default_day_sections_enabled")]
+    pub enabled: bool,
+    /// Local time the afternoon starts, as `HH:MM`.
+    #[serde(default = "default_afternoon")]
+    pub afternoon: String,
+    /// Local time the evening starts, as `HH:MM`; after `afternoon`.
+    #[serde(default = "default_evening")]
+    pub evening: String,
+}
+
+fn default_day_sections_enabled() -> bool {
+    true
+}
+
+fn default_afternoon() -> String {
+    "12:00".to_string()
+}
+
+fn default_evening() -> String {
+    "17:00".to_string()
+}
+
+impl Default for DaySectionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_day_sections_enabled(),
+            afternoon: default_afternoon(),
+            evening: default_evening(),
+        }
+    }
+}
+
+impl DaySectionsConfig {
+    /// The boundaries, checked; `list today --json` reports sections even when they are not
+    /// shown.
+    pub fn sections(&self) -> Result<DaySections, AppError> {
+        let parse = |key: &str, value: &str| {
+            Time::parse(value.trim(), format_description!("[hour]:[minute]")).map_err(|_| {
+                AppError::invalid_data(format!(
+                    "day_sections.{key} must be a time such as 17:00, not '{value}'"
+                ))
+            })
+        };
+        let afternoon = parse("afternoon", &self.afternoon)?;
+        let evening = parse("evening", &self.evening)?;
+        if evening <= afternoon {
+            return Err(AppError::invalid_data(
+                "day_sections.evening must be later than day_sections.afternoon",
+            ));
+        }
+        Ok(DaySections { afternoon, evening })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// The focused task first.
+    Focus,
+    /// Urgent tasks before the rest.
+    Urgent,
+    /// Overdue pending tasks before the rest, most overdue first.
+    Overdue,
+    /// Earliest planned time first (see [`crate::model::Task::planned_at`]); unscheduled
+    /// tasks last.
+    Scheduled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InsightsConfig {
+    /// Append each successful command (name and task id only) to the local usage log read by
+    /// `insights`. Nothing is ever sent anywhere.
+    #[serde(default = "default_record_usage")]
+    pub record_usage: bool,
+}
+
+fn default_record_usage() -> bool {
+    true
+}
+
+impl Default for InsightsConfig {
+    fn default() -> Self {
+        Self {
+            record_usage: default_record_usage(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Send a single digest instead of one notification per task once this many tasks qualify.
+    #[serde(default)]
+    pub digest_after: Option<usize>,
+    /// Most notifications one destination gets per run: past it, the first tasks are notified
+    /// singly and the rest share a digest. `null` lifts the cap.
+    #[serde(default = "default_max_per_run")]
+    pub max_per_run: Option<usize>,
+    #[serde(default)]
+    pub backend: NotificationBackend,
+    /// Where the `webhook` backend posts, such as a Slack incoming webhook.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Backends for some tasks instead of `backend`: each task goes to the first route whose
+    /// project or tag it has, and the rest to `backend`.
+    #[serde(default)]
+    pub routes: Vec<NotificationRoute>,
+    /// Hint on stderr when urgent tasks are overdue and nothing was notified for this many
+    /// hours. `0` disables the hint.
+    #[serde(default = "default_stale_hint_hours")]
+    pub stale_hint_hours: u64,
+    /// Confirm each `schedule` and `reschedule` with a notification naming the new time, so
+    /// tasks scheduled by scripts and other tools do not go unnoticed.
+    #[serde(default)]
+    pub on_schedule: bool,
+    #[serde(default)]
+    pub windows: WindowsNotificationConfig,
+    #[serde(default)]
+    pub linux: LinuxNotificationConfig,
+}
+
+fn default_max_per_run() -> Option<usize> {
+    Some(5)
+}
+
+fn default_stale_hint_hours() -> u64 {
+    24
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            digest_after: None,
+            max_per_run: default_max_per_run(),
+            backend: NotificationBackend::default(),
+            webhook_url: None,
+            routes: Vec::new(),
+            stale_hint_hours: default_stale_hint_hours(),
+            on_schedule: false,
+            windows: WindowsNotificationConfig::default(),
+            linux: LinuxNotificationConfig::default(),
+        }
+    }
+}
+
+/// Hints for desktop notifications on Linux. Each task's notification replaces the one shown
+/// for it last time rather than stacking.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxNotificationConfig {
+    /// An icon name from the theme, such as `appointment-soon`, or a path to an image.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// The freedesktop category, such as `im.received`, or a server's own `x-` category
+    /// such as `x-canonical-private-synchronous`.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// How long notifications stay up; `0m` keeps them until dismissed. Left out, the
+    /// server decides.
+    #[serde(default, with = "crate::duration::optional_text")]
+    pub timeout: Option<Duration>,
+    /// Keep notifications in the server's list after their action is clicked.
+    #[serde(default)]
+    pub resident: bool,
+}
+
+/// How Windows toasts present the app. Toasts borrow PowerShell's identity until `app_id` is
+/// set and registered with `todo notify register-windows-app`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowsNotificationConfig {
+    /// The AppUserModelID toasts are sent under.
+    #[serde(default)]
+    pub app_id: Option<String>,
+    /// The name Windows shows on toasts once the app is registered.
+    #[serde(default = "default_windows_display_name")]
+    pub display_name: String,
+    /// An `.ico` or `.png` shown next to the display name once registered.
+    #[serde(default)]
+    pub icon: Option<PathBuf>,
+}
+
+fn default_windows_display_name() -> String {
+    "Todo".to_string()
+}
+
+impl Default for WindowsNotificationConfig {
+    fn default() -> Self {
+        Self {
+            app_id: None,
+            display_name: default_windows_display_name(),
+            icon: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationBackend {
+    /// Desktop notifications when a GUI session is detected, console output otherwise.
+    #[default]
+    Auto,
+    Desktop,
+    Console,
+    /// A JSON `{"text": ...}` POST to `webhook_url`, the shape Slack and Mattermost accept.
+    Webhook,
+    None,
+}
+
+impl NotificationBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationBackend::Auto => "auto",
+            NotificationBackend::Desktop => "desktop",
+            NotificationBackend::Console => "console",
+            NotificationBackend::Webhook => "webhook",
+            NotificationBackend::None => "none",
+        }
+    }
+}
+
+/// One entry of `notifications.routes`. A route names a project, a tag, or both, and needs
+/// every one it names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationRoute {
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    pub backend: NotificationBackend,
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+impl NotificationRoute {
+    pub fn matches(&self, task: &Task) -> bool {
+        if self.project.is_none() && self.tag.is_none() {
+            return false;
+        }
+        let project = self
+            .project
+            .as_deref()
+            .is_none_or(|project| task.project.as_deref() == Some(project));
+        let tag = self
+            .tag
+            .as_deref()
+            .is_none_or(|tag| task.tags.iter().any(|own| own == tag));
+        project && tag
+    }
+
+    /// `config` with this route's backend and webhook in place of its own.
+    pub fn apply(&self, config: &NotificationConfig) -> NotificationConfig {
+        NotificationConfig {
+            backend: self.backend,
+            webhook_url: self.webhook_url.clone(),
+            routes: Vec::new(),
+            ..config.clone()
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ConfigLoad {
     pub config: Config,
     pub error: Option<AppError>,
+    /// Settings the file has that nothing reads, such as a misspelled key or an unknown theme.
+    pub warnings: Vec<String>,
+}
+
+/// The choices made in `init`, written as a new config file by [`write_setup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    /// One of [`THEMES`].
+    pub theme: String,
+    /// `false` sets `notifications.backend` to `none`.
+    pub notifications: bool,
+    /// `None` leaves the store in its default place.
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self {
+            theme: "default".to_string(),
+            notifications: true,
+            store_path: None,
+        }
+    }
+}
+
+impl Setup {
+    /// Only the keys the choices set, so everything else keeps following the defaults.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut config = serde_json::json!({ "theme": self.theme });
+        if !self.notifications {
+            config["notifications"] = serde_json::json!({ "backend": "none" });
+        }
+        if let Some(path) = &self.store_path {
+            config["store_path"] = serde_json::json!(path);
+        }
+        config
+    }
+}
+
+/// Write `setup` as a new config file at `path`, creating its folder. An existing config is
+/// never replaced.
+pub fn write_setup(path: &Path, setup: &Setup) -> Result<(), AppError> {
+    if path.exists() {
+        return Err(AppError::invalid_input(format!(
+            "{} already exists; edit it instead, or move it aside to run init again",
+            path.display()
+        )));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| AppError::io(err.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(&setup.to_json())
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    std::fs::write(path, content + "\n").map_err(|err| AppError::io(err.to_string()))
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -101,25 +749,69 @@ pub struct ConfigOverrides {
     pub aliases: HashMap<String, String>,
 }
 
+/// Point every later [`config_path`] call in this process at `path`, ahead of
+/// `TODOAPP_CONFIG_PATH`. Used by `--config` and `--profile`; it can only be set once.
+pub fn override_config_path(path: PathBuf) -> Result<(), AppError> {
+    CONFIG_PATH_OVERRIDE
+        .set(path)
+        .map_err(|_| AppError::invalid_input("config path is already overridden"))
+}
+
 pub fn config_path() -> Result<PathBuf, AppError> {
-    if let Ok(path) = std::env::var(CONFIG_ENV_VAR)
-        && !path.trim().is_empty()
-    {
-        return Ok(PathBuf::from(path));
+    if let Some(path) = CONFIG_PATH_OVERRIDE.get() {
+        return Ok(path.clone());
+    }
+
+    if let Some(path) = env_config_path() {
+        return Ok(path);
+    }
+
+    default_config_path()
+}
+
+/// The config [`CONFIG_ENV_VAR`] names, when it is set.
+pub fn env_config_path() -> Option<PathBuf> {
+    std::env::var(CONFIG_ENV_VAR)
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// The config loaded when nothing names another one.
+pub fn default_config_path() -> Result<PathBuf, AppError> {
+    Ok(app_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// The config and store paths of the profile `name`, both in `profiles/<name>/` beside the
+/// default config, so each profile keeps its own settings and tasks. Names use letters, digits,
+/// `-`, and `_`.
+pub fn profile_paths(name: &str) -> Result<(PathBuf, PathBuf), AppError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME_LEN
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_'));
+    if !valid {
+        return Err(AppError::invalid_input(format!(
+            "profile names use letters, digits, '-', and '_' (at most {MAX_PROFILE_NAME_LEN}): '{name}'"
+        )));
     }
 
+    let dir = app_dir()?.join(PROFILES_DIR).join(name);
+    Ok((
+        dir.join(CONFIG_FILE_NAME),
+        dir.join(crate::storage::json_store::STORE_FILE_NAME),
+    ))
+}
+
+fn app_dir() -> Result<PathBuf, AppError> {
     if cfg!(windows) {
         let appdata =
             std::env::var("APPDATA").map_err(|_| AppError::invalid_data("APPDATA is not set"))?;
-        Ok(PathBuf::from(appdata)
-            .join("todoapp")
-            .join(CONFIG_FILE_NAME))
+        Ok(PathBuf::from(appdata).join("todoapp"))
     } else {
         let home = std::env::var("HOME").map_err(|_| AppError::invalid_data("HOME is not set"))?;
-        Ok(PathBuf::from(home)
-            .join(".config")
-            .join("todoapp")
-            .join(CONFIG_FILE_NAME))
+        Ok(PathBuf::from(home).join(".config").join("todoapp"))
     }
 }
 
@@ -134,37 +826,80 @@ pub fn load_config_with_fallback() -> ConfigLoad {
         Err(err) => ConfigLoad {
             config: Config::default(),
             error: Some(err),
+            warnings: Vec::new(),
         },
     }
 }
 
-fn load_config_with_fallback_from_path(path: &Path) -> ConfigLoad {
+/// [`load_config_with_fallback`] for the config at `path`.
+pub fn load_config_with_fallback_from_path(path: &Path) -> ConfigLoad {
     if !path.exists() {
         return ConfigLoad {
             config: Config::default(),
             error: None,
+            warnings: Vec::new(),
         };
     }
 
-    match load_config_from_path(path) {
-        Ok(config) => ConfigLoad {
+    match load_config_and_warnings_from_path(path) {
+        Ok((config, warnings)) => ConfigLoad {
             config,
             error: None,
+            warnings,
         },
         Err(err) => ConfigLoad {
             config: Config::default(),
             error: Some(err),
+            warnings: Vec::new(),
         },
     }
 }
 
 fn load_config_from_path(path: &Path) -> Result<Config, AppError> {
+    Ok(load_config_and_warnings_from_path(path)?.0)
+}
+
+fn load_config_and_warnings_from_path(path: &Path) -> Result<(Config, Vec<String>), AppError> {
     let content = std::fs::read_to_string(path)
         .map_err(|err| AppError::io(format!("{}: {}", path.display(), err)))?;
     let config = serde_json::from_str(&content).map_err(|err| {
         AppError::invalid_data(format!("invalid JSON in {}: {}", path.display(), err))
     })?;
-    Ok(normalize_config_theme(config))
+    let config = normalize_config_theme(config);
+    let warnings = config_warnings(&content, &config)
+        .into_iter()
+        .map(|warning| format!("{}: {warning}", path.display()))
+        .collect();
+    Ok((config, warnings))
+}
+
+/// Keys serde skipped because no field has that name, checked against the default config's
+/// own keys one section deep, plus a theme that has no palette. Map-valued settings such as
+/// `aliases` take any key.
+fn config_warnings(content: &str, config: &Config) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let known = serde_json::to_value(Config::default()).unwrap_or_default();
+    if let Ok(serde_json::Value::Object(raw)) = serde_json::from_str(content) {
+        for (key, value) in &raw {
+            match (known.get(key), value) {
+                (None, _) => warnings.push(format!("unknown key `{key}` is ignored")),
+                (Some(serde_json::Value::Object(section)), serde_json::Value::Object(fields))
+                    if !section.is_empty() =>
+                {
+                    for field in fields.keys().filter(|field| !section.contains_key(*field)) {
+                        warnings.push(format!("unknown key `{key}.{field}` is ignored"));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(theme) = config.theme.as_deref()
+        && !THEMES.contains(&theme)
+    {
+        warnings.push(format!("unknown theme `{theme}`, using default"));
+    }
+    warnings
 }
 
 fn normalize_config_theme(mut config: Config) -> Config {
@@ -178,10 +913,10 @@ fn normalize_theme_value(value: Option<String>) -> Option<String> {
 
 pub fn merge_overrides(base: &Config, overrides: &ConfigOverrides) -> Config {
     let mut merged = base.clone();
-    if let Some(theme) = overrides.theme.as_ref() {
-        if let Some(normalized) = canonical_theme_name(theme) {
-            merged.theme = Some(normalized);
-        }
+    if let Some(theme) = overrides.theme.as_ref()
+        && let Some(normalized) = canonical_theme_name(theme)
+    {
+        merged.theme = Some(normalized);
     }
 
     for (alias, value) in overrides.aliases.iter() {
@@ -194,12 +929,17 @@ pub fn merge_overrides(base: &Config, overrides: &ConfigOverrides) -> Config {
 #[cfg(test)]
 mod tests {
     use super::{
-        Config, ConfigOverrides, canonical_theme_name, load_config_from_path,
-        load_config_with_fallback_from_path, merge_overrides, palette_for_theme,
+        Config, ConfigOverrides, NotificationBackend, NotificationRoute, Palette, Setup, SortKey,
+        TitleConfig, canonical_theme_name, load_config_from_path,
+        load_config_with_fallback_from_path, merge_overrides, palette_for_theme, profile_paths,
+        write_setup,
     };
+    use crate::model::{Marker, MarkerColor};
+    use crate::testing;
     use std::fs;
     use std::path::PathBuf;
     use std::time::{SystemTime, UNIX_EPOCH};
+    use time::Duration;
 
     fn temp_path(file_name: &str) -> PathBuf {
         let nanos = SystemTime::now()
@@ -230,6 +970,38 @@ mod tests {
         assert!(result.error.is_some());
     }
 
+    #[test]
+    fn load_config_warns_about_ignored_keys_and_unknown_themes() {
+        let path = temp_path("typo-config.json");
+        let content = serde_json::json!({
+            "theme": "Oceanic",
+            "acessible": true,
+            "aliases": { "ls": "list today" },
+            "wip_limit": { "today": 3, "enforce_mode": "block" },
+            "tickets": { "providers": {} }
+        });
+        fs::write(&path, serde_json::to_string(&content).unwrap()).unwrap();
+
+        let result = load_config_with_fallback_from_path(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(result.error.is_none());
+        assert_eq!(result.config.wip_limit.today, Some(3));
+        let warnings: Vec<&str> = result
+            .warnings
+            .iter()
+            .map(|warning| warning.rsplit(": ").next().unwrap())
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "unknown key `acessible` is ignored",
+                "unknown key `wip_limit.enforce_mode` is ignored",
+                "unknown theme `oceanic`, using default",
+            ]
+        );
+    }
+
     #[test]
     fn load_config_reads_valid_file() {
         let path = temp_path("valid-config.json");
@@ -249,6 +1021,46 @@ mod tests {
             loaded.aliases.get("ls").map(String::as_str),
             Some("list today")
         );
+        assert_eq!(loaded.notifications.digest_after, None);
+        assert!(loaded.insights.record_usage);
+        assert_eq!(loaded.ordering.today.len(), 4);
+    }
+
+    #[test]
+    fn load_config_reads_notification_block() {
+        let path = temp_path("notifications-config.json");
+        let content = serde_json::json!({
+            "notifications": {
+                "digest_after": 3
+            }
+        });
+        fs::write(&path, serde_json::to_string(&content).unwrap()).unwrap();
+
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.notifications.digest_after, Some(3));
+        assert_eq!(loaded.notifications.backend, NotificationBackend::Auto);
+        assert_eq!(loaded.notifications.stale_hint_hours, 24);
+    }
+
+    #[test]
+    fn load_config_reads_overdue_grace_as_a_duration() {
+        let path = temp_path("grace-config.json");
+        fs::write(&path, r#"{ "overdue_grace": "15m" }"#).unwrap();
+        let loaded = load_config_from_path(&path);
+        fs::write(&path, r#"{ "overdue_grace": "soon" }"#).unwrap();
+        let invalid = load_config_from_path(&path);
+        fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.unwrap().overdue_grace, Duration::minutes(15));
+        assert_eq!(Config::default().overdue_grace, Duration::ZERO);
+        assert!(
+            invalid
+                .unwrap_err()
+                .message()
+                .contains("invalid duration 'soon'")
+        );
     }
 
     #[test]
@@ -256,6 +1068,7 @@ mod tests {
         let base = Config {
             theme: Some("light".into()),
             aliases: [("ls".into(), "list today".into())].into_iter().collect(),
+            ..Config::default()
         };
 
         let overrides = ConfigOverrides {
@@ -285,6 +1098,7 @@ mod tests {
         let base = Config {
             theme: Some("light".into()),
             aliases: [("ls".into(), "list today".into())].into_iter().collect(),
+            ..Config::default()
         };
 
         let overrides = ConfigOverrides {
@@ -297,7 +1111,7 @@ mod tests {
         let merged = merge_overrides(&base, &overrides);
 
         assert_eq!(base.theme.as_deref(), Some("light"));
-        assert!(base.aliases.get("focus").is_none());
+        assert!(!base.aliases.contains_key("focus"));
 
         assert_eq!(merged.theme.as_deref(), Some("noir"));
         assert_eq!(
@@ -310,11 +1124,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_setup_writes_only_the_chosen_keys_once() {
+        let path = temp_path("setup").join("config.json");
+        let setup = Setup {
+            theme: "noir".to_string(),
+            notifications: false,
+            store_path: Some(PathBuf::from("/srv/tasks.json")),
+        };
+
+        write_setup(&path, &setup).unwrap();
+        let loaded = load_config_from_path(&path).unwrap();
+        let again = write_setup(&path, &Setup::default());
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+
+        assert_eq!(loaded.theme.as_deref(), Some("noir"));
+        assert_eq!(loaded.notifications.backend, NotificationBackend::None);
+        assert_eq!(loaded.store_path, Some(PathBuf::from("/srv/tasks.json")));
+        assert_eq!(loaded.aliases, Config::default().aliases);
+        assert!(again.unwrap_err().message().contains("already exists"));
+    }
+
     #[test]
     fn merge_overrides_with_empty_overrides_returns_clone() {
         let base = Config {
             theme: Some("light".into()),
             aliases: [("ls".into(), "list today".into())].into_iter().collect(),
+            ..Config::default()
         };
 
         let merged = merge_overrides(&base, &ConfigOverrides::default());
@@ -343,5 +1179,214 @@ mod tests {
 
         let unknown_palette = palette_for_theme(Some("oceanic"));
         assert!(unknown_palette.accent.is_empty());
+        assert!(!noir_palette.accessible);
+    }
+
+    #[test]
+    fn marker_is_a_colored_dot_or_the_color_name_without_colors() {
+        let marker = Marker {
+            color: Some(MarkerColor::Red),
+            symbol: Some("🔥".to_string()),
+        };
+        let color_only = Marker {
+            color: Some(MarkerColor::Blue),
+            symbol: None,
+        };
+
+        assert_eq!(
+            palette_for_theme(Some("noir")).marker(&marker),
+            "🔥\x1b[38;5;196m●\x1b[0m"
+        );
+        assert_eq!(palette_for_theme(None).marker(&marker), "🔥[red]");
+        assert_eq!(palette_for_theme(None).marker(&color_only), "[blue]");
+    }
+
+    #[test]
+    fn load_config_reads_accessible_and_defaults_it_off() {
+        let path = temp_path("accessible-config.json");
+        fs::write(&path, r#"{"theme": "noir", "accessible": true}"#).unwrap();
+
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert!(loaded.accessible);
+        assert!(!Config::default().accessible);
+        let palette = Palette::for_screen_readers();
+        assert!(palette.accessible);
+        assert_eq!(palette.accentize("Buy milk"), "Buy milk");
+    }
+
+    #[test]
+    fn load_config_reads_notification_backend() {
+        let path = temp_path("notifications-backend.json");
+        let content = serde_json::json!({
+            "notifications": {
+                "backend": "console"
+            }
+        });
+        fs::write(&path, serde_json::to_string(&content).unwrap()).unwrap();
+
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.notifications.backend, NotificationBackend::Console);
+    }
+
+    #[test]
+    fn load_config_reads_linux_notification_hints() {
+        let path = temp_path("notifications-linux.json");
+        fs::write(
+            &path,
+            r#"{ "notifications": { "linux": { "icon": "appointment-soon", "timeout": "30s" } } }"#,
+        )
+        .unwrap();
+        let invalid = load_config_from_path(&path);
+        fs::write(
+            &path,
+            r#"{ "notifications": { "linux": { "category": "im.received", "timeout": "1m" } } }"#,
+        )
+        .unwrap();
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert!(
+            invalid
+                .unwrap_err()
+                .message()
+                .contains("invalid duration '30s'")
+        );
+        let linux = loaded.notifications.linux;
+        assert_eq!(linux.category.as_deref(), Some("im.received"));
+        assert_eq!(linux.timeout, Some(Duration::minutes(1)));
+        assert_eq!(linux.icon, None);
+        assert!(!linux.resident);
+        assert_eq!(Config::default().notifications.linux.timeout, None);
+    }
+
+    #[test]
+    fn load_config_reads_notification_routes() {
+        let path = temp_path("notifications-routes.json");
+        let content = serde_json::json!({
+            "notifications": {
+                "routes": [
+                    { "project": "work", "backend": "webhook", "webhook_url": "https://hooks.example.com/t" },
+                    { "tag": "personal", "backend": "desktop" }
+                ]
+            }
+        });
+        fs::write(&path, serde_json::to_string(&content).unwrap()).unwrap();
+
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let routes = &loaded.notifications.routes;
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].backend, NotificationBackend::Webhook);
+        let routed = routes[0].apply(&loaded.notifications);
+        assert_eq!(routed.backend, NotificationBackend::Webhook);
+        assert_eq!(
+            routed.webhook_url.as_deref(),
+            Some("https://hooks.example.com/t")
+        );
+        assert!(routed.routes.is_empty());
+
+        let work = testing::task("task-1", "Deploy")
+            .project("work")
+            .build()
+            .unwrap();
+        let personal = testing::task("task-2", "Dentist")
+            .tag("personal")
+            .build()
+            .unwrap();
+        assert!(routes[0].matches(&work));
+        assert!(!routes[0].matches(&personal));
+        assert!(routes[1].matches(&personal));
+        let unnamed = NotificationRoute {
+            project: None,
+            tag: None,
+            backend: NotificationBackend::Console,
+            webhook_url: None,
+        };
+        assert!(!unnamed.matches(&work));
+    }
+
+    #[test]
+    fn load_config_reads_ticket_providers() {
+        let path = temp_path("tickets-config.json");
+        let content = serde_json::json!({
+            "tickets": {
+                "providers": {
+                    "jira": {
+                        "prefix": "JIRA-",
+                        "url": "https://jira.example.com/rest/api/2/issue/{key}",
+                        "headers": { "Authorization": "Bearer ${JIRA_TOKEN}" },
+                        "title_pointer": "/fields/summary",
+                        "status_pointer": "/fields/status/name",
+                        "closed_statuses": ["Done", "Closed"]
+                    }
+                }
+            }
+        });
+        fs::write(&path, serde_json::to_string(&content).unwrap()).unwrap();
+
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let jira = &loaded.tickets.providers["jira"];
+        assert_eq!(jira.prefix.as_deref(), Some("JIRA-"));
+        assert_eq!(jira.title_pointer, "/fields/summary");
+        assert_eq!(jira.headers["Authorization"], "Bearer ${JIRA_TOKEN}");
+        assert_eq!(jira.closed_statuses, vec!["Done", "Closed"]);
+    }
+
+    #[test]
+    fn load_config_reads_today_order() {
+        let path = temp_path("ordering-config.json");
+        let content = serde_json::json!({
+            "ordering": {
+                "today": ["urgent", "scheduled"]
+            }
+        });
+        fs::write(&path, serde_json::to_string(&content).unwrap()).unwrap();
+
+        let loaded = load_config_from_path(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(
+            loaded.ordering.today,
+            vec![SortKey::Urgent, SortKey::Scheduled]
+        );
+    }
+
+    #[test]
+    fn title_config_applies_only_the_rules_that_are_on() {
+        let title = "🛒 BUY MILK!!";
+        assert_eq!(TitleConfig::default().apply(title), title);
+
+        let rules = TitleConfig {
+            sentence_case: true,
+            trim_trailing_punctuation: true,
+            strip_emoji: true,
+        };
+        assert_eq!(rules.apply(title), "Buy milk");
+        assert_eq!(rules.apply("🎉🎉"), "🎉🎉");
+
+        let emoji_only = TitleConfig {
+            strip_emoji: true,
+            ..TitleConfig::default()
+        };
+        assert_eq!(emoji_only.apply(title), "BUY MILK!!");
+    }
+
+    #[test]
+    fn profile_paths_share_a_folder_per_profile() {
+        let (config, store) = profile_paths("work").unwrap();
+
+        assert!(config.ends_with("todoapp/profiles/work/config.json"));
+        assert!(store.ends_with("todoapp/profiles/work/tasks.json"));
+        assert!(profile_paths("client-a_2").is_ok());
+        for name in ["", "../work", "a/b", "work space"] {
+            assert_eq!(profile_paths(name).unwrap_err().code(), "invalid_input");
+        }
     }
 }
@@ -0,0 +1,158 @@
+//! Task-level differences between two stores, e.g. a backup and the live store.
+
+use crate::error::AppError;
+use crate::model::Task;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StoreDiff {
+    /// Tasks only in the new store, in its order.
+    pub added: Vec<Task>,
+    /// Tasks only in the old store, in its order.
+    pub removed: Vec<Task>,
+    /// Tasks present in both with at least one differing field, in new-store order.
+    pub changed: Vec<TaskChange>,
+}
+
+impl StoreDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskChange {
+    pub before: Task,
+    pub after: Task,
+    pub fields: Vec<FieldChange>,
+}
+
+/// One field as it is serialized in the store, so every task field is compared without
+/// listing them here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Matches tasks by id. When a store holds duplicate ids, the first task with each id is used.
+pub fn diff_tasks(old: &[Task], new: &[Task]) -> Result<StoreDiff, AppError> {
+    let mut old_by_id: HashMap<&str, &Task> = HashMap::new();
+    for task in old {
+        old_by_id.entry(task.id.as_str()).or_insert(task);
+    }
+    let mut new_by_id: HashMap<&str, &Task> = HashMap::new();
+    for task in new {
+        new_by_id.entry(task.id.as_str()).or_insert(task);
+    }
+
+    let mut diff = StoreDiff::default();
+    for task in old {
+        if !new_by_id.contains_key(task.id.as_str()) {
+            diff.removed.push(task.clone());
+        }
+    }
+    for task in new {
+        if !std::ptr::eq(new_by_id[task.id.as_str()], task) {
+            continue;
+        }
+        match old_by_id.get(task.id.as_str()) {
+            None => diff.added.push(task.clone()),
+            Some(before) if *before != task => diff.changed.push(TaskChange {
+                before: (*before).clone(),
+                after: task.clone(),
+                fields: field_changes(before, task)?,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    Ok(diff)
+}
+
+/// The task's fields as they are serialized in the store, by name.
+pub(crate) fn task_fields(task: &Task) -> Result<Map<String, Value>, AppError> {
+    match serde_json::to_value(task) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(AppError::invalid_data("task must serialize to an object")),
+        Err(err) => Err(AppError::invalid_data(err.to_string())),
+    }
+}
+
+pub(crate) fn field_changes(before: &Task, after: &Task) -> Result<Vec<FieldChange>, AppError> {
+    let before = task_fields(before)?;
+    let mut after = task_fields(after)?;
+
+    let mut changes = Vec::new();
+    for (field, old_value) in before {
+        let new_value = after.remove(&field).unwrap_or(Value::Null);
+        if old_value != new_value {
+            changes.push(FieldChange {
+                field,
+                before: old_value,
+                after: new_value,
+            });
+        }
+    }
+    for (field, new_value) in after {
+        changes.push(FieldChange {
+            field,
+            before: Value::Null,
+            after: new_value,
+        });
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_tasks;
+    use crate::model::{Task, TaskStatus};
+    use crate::testing;
+    use serde_json::json;
+
+    fn task(id: &str, title: &str) -> Task {
+        testing::task(id, title).build().unwrap()
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_fields() {
+        let old = vec![task("1", "keep"), task("2", "gone"), task("3", "draft")];
+        let mut edited = task("3", "final");
+        edited.status = TaskStatus::Completed;
+        let new = vec![task("4", "fresh"), edited, task("1", "keep")];
+
+        let diff = diff_tasks(&old, &new).unwrap();
+
+        assert_eq!(diff.added, vec![task("4", "fresh")]);
+        assert_eq!(diff.removed, vec![task("2", "gone")]);
+        assert_eq!(diff.changed.len(), 1);
+        let fields: Vec<(&str, _, _)> = diff.changed[0]
+            .fields
+            .iter()
+            .map(|change| {
+                (
+                    change.field.as_str(),
+                    change.before.clone(),
+                    change.after.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("status", json!("pending"), json!("completed")),
+                ("title", json!("draft"), json!("final")),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_stores_have_an_empty_diff() {
+        let tasks = vec![task("1", "a"), task("2", "b")];
+
+        assert!(diff_tasks(&tasks, &tasks).unwrap().is_empty());
+    }
+}
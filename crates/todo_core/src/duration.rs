@@ -0,0 +1,183 @@
+//! Lengths of time written the way people type them, such as `90m`, `1h30m`, `2d`, or `1w`.
+//!
+//! Every option and config value that takes a length of time parses it here, so they all
+//! accept the same forms and print them back the same way.
+
+use crate::error::AppError;
+use std::fmt::Write;
+use time::Duration;
+
+const UNITS: [(&str, i64); 4] = [("w", 7 * 24 * 60), ("d", 24 * 60), ("h", 60), ("m", 1)];
+
+/// One or more parts of a whole number and a unit, optionally separated by spaces: `m` (also
+/// `min`, `minute`), `h` (`hr`, `hour`), `d` (`day`), or `w` (`wk`, `week`), each with or
+/// without a plural `s`. The parts add up, so `1h30m` and `90m` are the same. No sign.
+///
+/// ```
+/// use time::Duration;
+/// use todo_core::duration;
+///
+/// assert_eq!(duration::parse("1h30m")?, Duration::minutes(90));
+/// assert_eq!(duration::format(duration::parse("90 min")?), "1h30m");
+/// # Ok::<(), todo_core::error::AppError>(())
+/// ```
+pub fn parse(text: &str) -> Result<Duration, AppError> {
+    let invalid = || {
+        AppError::invalid_input(format!(
+            "invalid duration '{}' (expected e.g. 90m, 1h30m, 2d, or 1w)",
+            text.trim()
+        ))
+    };
+    let lowered = text.trim().to_lowercase();
+    let mut rest = lowered.as_str();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut minutes: i64 = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = rest[digits..].trim_start();
+        let letters = rest
+            .find(|ch: char| !ch.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let size = unit_minutes(&rest[..letters]).ok_or_else(invalid)?;
+        rest = rest[letters..].trim_start();
+        minutes = amount
+            .checked_mul(size)
+            .and_then(|part| minutes.checked_add(part))
+            .ok_or_else(invalid)?;
+    }
+    minutes
+        .checked_mul(60)
+        .map(Duration::seconds)
+        .ok_or_else(invalid)
+}
+
+fn unit_minutes(unit: &str) -> Option<i64> {
+    let unit = match unit {
+        "m" | "min" | "mins" | "minute" | "minutes" => "m",
+        "h" | "hr" | "hrs" | "hour" | "hours" => "h",
+        "d" | "day" | "days" => "d",
+        "w" | "wk" | "wks" | "week" | "weeks" => "w",
+        _ => return None,
+    };
+    UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, size)| *size)
+}
+
+/// The shortest form [`parse`] reads back as `duration`, largest unit first, such as
+/// `1w2d` or `1h30m`. Whole minutes only: seconds are dropped. A negative duration gets a
+/// leading `-`.
+pub fn format(duration: Duration) -> String {
+    let minutes = duration.whole_minutes();
+    if minutes == 0 {
+        return "0m".to_string();
+    }
+    let mut text = if minutes < 0 {
+        "-".to_string()
+    } else {
+        String::new()
+    };
+    let mut rest = minutes.unsigned_abs();
+    for (unit, size) in UNITS {
+        let size = size.unsigned_abs();
+        if rest >= size {
+            let _ = write!(text, "{}{unit}", rest / size);
+            rest %= size;
+        }
+    }
+    text
+}
+
+/// `#[serde(with = "crate::duration::text")]` for a setting written the way [`parse`] reads,
+/// and saved back with [`format`].
+pub(crate) mod text {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format(*duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse(&text).map_err(|err| serde::de::Error::custom(err.message()))
+    }
+}
+
+/// [`text`] for a setting that may be left out or `null`.
+pub(crate) mod optional_text {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::text::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| super::parse(&text).map_err(|err| serde::de::Error::custom(err.message())))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, parse};
+    use time::Duration;
+
+    #[test]
+    fn parse_adds_up_parts_in_any_spelling() {
+        assert_eq!(parse("90m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse(" 1 hour 30 mins ").unwrap(), Duration::minutes(90));
+        assert_eq!(parse("2d").unwrap(), Duration::days(2));
+        assert_eq!(parse("1W").unwrap(), Duration::weeks(1));
+        assert_eq!(parse("0m").unwrap(), Duration::ZERO);
+
+        for bad in [
+            "",
+            "15",
+            "m",
+            "1x",
+            "-1h",
+            "1.5h",
+            "1h30",
+            "99999999999999999w",
+            "153722867280912931m",
+        ] {
+            let err = parse(bad).unwrap_err();
+            assert_eq!(err.code(), "invalid_input", "{bad}");
+        }
+        assert_eq!(
+            parse("soon").unwrap_err().message(),
+            "invalid duration 'soon' (expected e.g. 90m, 1h30m, 2d, or 1w)"
+        );
+    }
+
+    #[test]
+    fn format_round_trips_through_parse() {
+        assert_eq!(format(Duration::minutes(90)), "1h30m");
+        assert_eq!(format(Duration::days(9)), "1w2d");
+        assert_eq!(format(Duration::ZERO), "0m");
+        assert_eq!(format(Duration::seconds(150)), "2m");
+        assert_eq!(format(-Duration::hours(3)), "-3h");
+        for text in ["45m", "1h", "1d1m", "3w6d23h59m"] {
+            assert_eq!(format(parse(text).unwrap()), text);
+        }
+    }
+}
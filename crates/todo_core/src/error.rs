@@ -1,39 +1,70 @@
 use std::fmt;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    InvalidInput,
+    InvalidData,
+    Io,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum AppError {
-    InvalidInput(String),
-    InvalidData(String),
-    Io(String),
+pub struct AppError {
+    kind: ErrorKind,
+    message: String,
+    /// What to do about it, such as the command to run first; shown beneath the message.
+    hint: Option<String>,
 }
 
 impl AppError {
+    fn new(kind: ErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            hint: None,
+        }
+    }
+
     pub fn invalid_input<M: Into<String>>(message: M) -> Self {
-        Self::InvalidInput(message.into())
+        Self::new(ErrorKind::InvalidInput, message.into())
     }
 
     pub fn invalid_data<M: Into<String>>(message: M) -> Self {
-        Self::InvalidData(message.into())
+        Self::new(ErrorKind::InvalidData, message.into())
     }
 
     pub fn io<M: Into<String>>(message: M) -> Self {
-        Self::Io(message.into())
+        Self::new(ErrorKind::Io, message.into())
+    }
+
+    pub fn with_hint<H: Into<String>>(mut self, hint: H) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// The same error with `message` instead, keeping its kind and hint.
+    pub fn with_message<M: Into<String>>(mut self, message: M) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
     }
 
     pub fn code(&self) -> &'static str {
-        match self {
-            Self::InvalidInput(_) => "invalid_input",
-            Self::InvalidData(_) => "invalid_data",
-            Self::Io(_) => "io_error",
+        match self.kind {
+            ErrorKind::InvalidInput => "invalid_input",
+            ErrorKind::InvalidData => "invalid_data",
+            ErrorKind::Io => "io_error",
         }
     }
 
     pub fn message(&self) -> &str {
-        match self {
-            Self::InvalidInput(message) => message,
-            Self::InvalidData(message) => message,
-            Self::Io(message) => message,
-        }
+        &self.message
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
     }
 }
 
@@ -0,0 +1,67 @@
+//! Tasks as a CSV file for spreadsheets, in the columns `import` reads without a map.
+//!
+//! Only the fields with a column travel: ids, statuses, timestamps other than the start and
+//! deadline, and history are left out, so a file imported back gives new, pending tasks.
+
+use crate::model::Task;
+
+/// The header row, one column per task field in [`FIELDS`].
+pub const COLUMNS: [&str; 7] = [
+    "title", "due", "start", "tags", "project", "notes", "urgent",
+];
+
+/// The task fields the columns carry, as the store names them.
+pub const FIELDS: [&str; 7] = [
+    "title", "due_at", "start_at", "tags", "project", "notes", "urgent",
+];
+
+pub fn render(tasks: &[Task]) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for task in tasks {
+        let cells = [
+            task.title.as_str(),
+            task.due_at.as_deref().unwrap_or(""),
+            task.start_at.as_deref().unwrap_or(""),
+            &task.tags.join(", "),
+            task.project.as_deref().unwrap_or(""),
+            task.notes.as_deref().unwrap_or(""),
+            if task.urgent { "yes" } else { "" },
+        ];
+        let cells: Vec<String> = cells.iter().map(|value| cell(value)).collect();
+        csv.push_str(&cells.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quoted when it holds a comma, quote, or line break, or starts or ends with a space.
+fn cell(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) || value.trim() != value {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::testing;
+
+    #[test]
+    fn render_quotes_cells_that_need_it() {
+        let mut task = testing::task("task-1", "Pay \"rent\", today")
+            .build()
+            .unwrap();
+        task.tags = vec!["home".to_string(), "bills".to_string()];
+        task.notes = Some("line one\nline two".to_string());
+        task.urgent = true;
+
+        assert_eq!(
+            render(&[task]),
+            "title,due,start,tags,project,notes,urgent\n\
+             \"Pay \"\"rent\"\", today\",,,\"home, bills\",,\"line one\nline two\",yes\n"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Periodic digest: what was completed, what slipped, and what is coming up.
+//!
+//! Rendered as Markdown, or as a plain-text email message (headers included) that can be piped
+//! straight into `sendmail`.
+
+use crate::error::AppError;
+use crate::model::{Task, TaskStatus};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use time::macros::format_description;
+use time::{Duration, OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestPeriod {
+    Day,
+    Week,
+}
+
+impl DigestPeriod {
+    pub fn duration(self) -> Duration {
+        match self {
+            DigestPeriod::Day => Duration::days(1),
+            DigestPeriod::Week => Duration::weeks(1),
+        }
+    }
+
+    fn heading(self) -> &'static str {
+        match self {
+            DigestPeriod::Day => "Daily digest",
+            DigestPeriod::Week => "Weekly digest",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub period: DigestPeriod,
+    /// Local time the digest was generated; its offset is used to display every timestamp.
+    pub generated_at: OffsetDateTime,
+    /// Tasks completed during the last period, most recent first.
+    pub completed: Vec<Task>,
+    /// Pending tasks whose scheduled time has passed, longest overdue first.
+    pub slipped: Vec<Task>,
+    /// Pending tasks scheduled within the next period, soonest first.
+    pub upcoming: Vec<Task>,
+    /// Notes written with `journal` for the days of the last period, keyed by day
+    /// (`YYYY-MM-DD`). [`Digest::from_tasks`] leaves them empty.
+    pub day_notes: BTreeMap<String, String>,
+}
+
+impl Digest {
+    pub fn from_tasks(
+        tasks: &[Task],
+        period: DigestPeriod,
+        now: OffsetDateTime,
+    ) -> Result<Self, AppError> {
+        let since = now - period.duration();
+        let until = now + period.duration();
+        let mut completed = Vec::new();
+        let mut slipped = Vec::new();
+        let mut upcoming = Vec::new();
+
+        for task in tasks {
+            match task.status {
+                TaskStatus::Completed => {
+                    if let Some(at) = parse_time(task.completed_at.as_deref())?
+                        && since <= at
+                        && at <= now
+                    {
+                        completed.push((at, task.clone()));
+                    }
+                }
+                TaskStatus::Cancelled => {}
+                _ => match parse_time(task.due_at.as_deref())? {
+                    Some(at) if at < now => slipped.push((at, task.clone())),
+                    Some(at) if at <= until => upcoming.push((at, task.clone())),
+                    _ => {}
+                },
+            }
+        }
+
+        completed.sort_by(|(left, _), (right, _)| right.cmp(left));
+        slipped.sort_by_key(|(at, _)| *at);
+        upcoming.sort_by_key(|(at, _)| *at);
+        let tasks_only = |entries: Vec<(OffsetDateTime, Task)>| {
+            entries.into_iter().map(|(_, task)| task).collect()
+        };
+
+        Ok(Digest {
+            period,
+            generated_at: now,
+            completed: tasks_only(completed),
+            slipped: tasks_only(slipped),
+            upcoming: tasks_only(upcoming),
+            day_notes: BTreeMap::new(),
+        })
+    }
+
+    /// One-line summary, used as the email subject.
+    pub fn subject(&self) -> String {
+        format!(
+            "{}: {} completed, {} slipped, {} upcoming",
+            self.period.heading(),
+            self.completed.len(),
+            self.slipped.len(),
+            self.upcoming.len()
+        )
+    }
+}
+
+pub fn render_markdown(digest: &Digest) -> Result<String, AppError> {
+    let now = digest.generated_at;
+    let mut page = String::new();
+    let _ = writeln!(page, "# {}\n", digest.period.heading());
+    let _ = writeln!(
+        page,
+        "{} to {}\n",
+        display_time(now - digest.period.duration())?,
+        display_time(now)?
+    );
+
+    let _ = writeln!(page, "## Completed ({})\n", digest.completed.len());
+    for task in &digest.completed {
+        let at = local_time(task.completed_at.as_deref(), now)?;
+        let _ = writeln!(page, "- {}{}", task_line(task), detail(at)?);
+        let message = task
+            .completion_history
+            .last()
+            .filter(|entry| task.completed_at.as_deref() == Some(entry.completed_at.as_str()));
+        if let Some(entry) = message {
+            let _ = writeln!(page, "  > {}", escape(&entry.message));
+        }
+    }
+    empty_note(&mut page, &digest.completed);
+
+    let _ = writeln!(page, "## Slipped ({})\n", digest.slipped.len());
+    for task in &digest.slipped {
+        let at = local_time(task.due_at.as_deref(), now)?;
+        let late = at.map(|at| days_late(at, now)).unwrap_or_default();
+        let _ = writeln!(page, "- {}{}, {late} late", task_line(task), detail(at)?);
+    }
+    empty_note(&mut page, &digest.slipped);
+
+    let _ = writeln!(page, "## Upcoming ({})\n", digest.upcoming.len());
+    for task in &digest.upcoming {
+        let at = local_time(task.due_at.as_deref(), now)?;
+        let _ = writeln!(page, "- {}{}", task_line(task), detail(at)?);
+    }
+    empty_note(&mut page, &digest.upcoming);
+
+    if !digest.day_notes.is_empty() {
+        page.push_str("## Journal\n\n");
+        for (day, note) in &digest.day_notes {
+            let note = note.lines().map(escape).collect::<Vec<_>>().join("\n  ");
+            let _ = writeln!(page, "- **{day}** {note}");
+        }
+        page.push('\n');
+    }
+
+    // No blank line after the last section.
+    page.truncate(page.trim_end().len());
+    page.push('\n');
+    Ok(page)
+}
+
+/// The Markdown digest as an RFC 5322 message without recipients, e.g. for
+/// `todo digest --format email | sendmail me@example.com`.
+pub fn render_email(digest: &Digest) -> Result<String, AppError> {
+    let date = digest
+        .generated_at
+        .format(&Rfc2822)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let mut message = String::new();
+    let _ = writeln!(message, "Subject: {}", digest.subject());
+    let _ = writeln!(message, "Date: {date}");
+    message.push_str("MIME-Version: 1.0\n");
+    message.push_str("Content-Type: text/plain; charset=utf-8\n");
+    message.push_str("Content-Transfer-Encoding: 8bit\n\n");
+    message.push_str(&render_markdown(digest)?);
+    Ok(message)
+}
+
+fn task_line(task: &Task) -> String {
+    let mut line = format!("**{}** (#{})", escape(&task.title), task.id);
+    if task.urgent && task.status.is_open() {
+        line.push_str(" urgent");
+    }
+    if let Some(project) = task.project.as_deref() {
+        let _ = write!(line, ", project {}", escape(project));
+    }
+    line
+}
+
+fn detail(at: Option<OffsetDateTime>) -> Result<String, AppError> {
+    Ok(match at {
+        Some(at) => format!(" — {}", display_time(at)?),
+        None => String::new(),
+    })
+}
+
+fn empty_note(page: &mut String, tasks: &[Task]) {
+    if tasks.is_empty() {
+        page.push_str("_Nothing._\n");
+    }
+    page.push('\n');
+}
+
+fn days_late(scheduled: OffsetDateTime, now: OffsetDateTime) -> String {
+    match (now - scheduled).whole_days() {
+        0 => "less than a day".to_string(),
+        1 => "1 day".to_string(),
+        days => format!("{days} days"),
+    }
+}
+
+fn parse_time(value: Option<&str>) -> Result<Option<OffsetDateTime>, AppError> {
+    value
+        .map(|value| {
+            OffsetDateTime::parse(value, &Rfc3339)
+                .map_err(|_| AppError::invalid_data("timestamps must be RFC3339"))
+        })
+        .transpose()
+}
+
+fn local_time(
+    value: Option<&str>,
+    now: OffsetDateTime,
+) -> Result<Option<OffsetDateTime>, AppError> {
+    Ok(parse_time(value)?.map(|at| at.to_offset(now.offset())))
+}
+
+fn display_time(value: OffsetDateTime) -> Result<String, AppError> {
+    value
+        .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+        .map_err(|err| AppError::invalid_data(err.to_string()))
+}
+
+/// Backslash-escapes characters Markdown would treat as formatting.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Digest, DigestPeriod, render_email, render_markdown};
+    use crate::model::{CompletionEntry, Task, TaskStatus};
+    use time::macros::datetime;
+
+    fn task(id: &str, title: &str, scheduled_at: Option<&str>) -> Task {
+        Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            due_at: scheduled_at.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn completed(id: &str, title: &str, completed_at: &str) -> Task {
+        let mut task = task(id, title, None);
+        task.status = TaskStatus::Completed;
+        task.completed_at = Some(completed_at.to_string());
+        task
+    }
+
+    #[test]
+    fn digest_sorts_tasks_into_sections_within_the_period() {
+        let mut shipped = completed("1", "Ship *release*", "2025-12-19T17:00:00Z");
+        shipped.completion_history.push(CompletionEntry {
+            message: "Tagged v2".to_string(),
+            completed_at: "2025-12-19T17:00:00Z".to_string(),
+        });
+        let tasks = vec![
+            completed("2", "Old errand", "2025-12-01T10:00:00Z"),
+            completed("3", "Send report", "2025-12-15T09:00:00Z"),
+            shipped,
+            task("4", "Call plumber", Some("2025-12-17T09:00:00Z")),
+            task("5", "Plan trip", Some("2025-12-26T09:00:00Z")),
+            task("6", "Far away", Some("2026-01-20T09:00:00Z")),
+            task("7", "Someday", None),
+        ];
+        let now = datetime!(2025-12-20 12:00 +1);
+
+        let digest = Digest::from_tasks(&tasks, DigestPeriod::Week, now).unwrap();
+        let ids = |tasks: &[Task]| tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
+
+        assert_eq!(ids(&digest.completed), vec!["1", "3"]);
+        assert_eq!(ids(&digest.slipped), vec!["4"]);
+        assert_eq!(ids(&digest.upcoming), vec!["5"]);
+        assert_eq!(
+            digest.subject(),
+            "Weekly digest: 2 completed, 1 slipped, 1 upcoming"
+        );
+
+        let markdown = render_markdown(&digest).unwrap();
+        assert!(markdown.starts_with("# Weekly digest\n\n2025-12-13 12:00 to 2025-12-20 12:00\n"));
+        assert!(
+            markdown.contains("- **Ship \\*release\\*** (#1) — 2025-12-19 18:00\n  > Tagged v2\n")
+        );
+        assert!(markdown.contains("- **Call plumber** (#4) — 2025-12-17 10:00, 3 days late\n"));
+        assert!(markdown.ends_with("- **Plan trip** (#5) — 2025-12-26 10:00\n"));
+    }
+
+    #[test]
+    fn email_adds_headers_and_marks_empty_sections() {
+        let now = datetime!(2025-12-20 12:00 UTC);
+        let digest = Digest::from_tasks(&[], DigestPeriod::Day, now).unwrap();
+
+        let email = render_email(&digest).unwrap();
+
+        assert!(email.starts_with(
+            "Subject: Daily digest: 0 completed, 0 slipped, 0 upcoming\n\
+             Date: Sat, 20 Dec 2025 12:00:00 +0000\n"
+        ));
+        assert!(email.contains("Content-Type: text/plain; charset=utf-8\n"));
+        assert!(email.contains("\n\n# Daily digest\n"));
+        assert_eq!(email.matches("_Nothing._").count(), 3);
+    }
+
+    #[test]
+    fn journal_section_lists_day_notes_only_when_there_are_some() {
+        let now = datetime!(2025-12-20 12:00 UTC);
+        let mut digest = Digest::from_tasks(&[], DigestPeriod::Week, now).unwrap();
+        let without = render_markdown(&digest).unwrap();
+        digest.day_notes = [
+            (
+                "2025-12-18".to_string(),
+                "Offsite\nBring *slides*".to_string(),
+            ),
+            ("2025-12-19".to_string(), "Quiet day".to_string()),
+        ]
+        .into();
+
+        let markdown = render_markdown(&digest).unwrap();
+
+        assert!(!without.contains("## Journal"));
+        assert!(markdown.ends_with(
+            "## Journal\n\n- **2025-12-18** Offsite\n  Bring \\*slides\\*\n- **2025-12-19** Quiet day\n"
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+//! Graphviz DOT rendering of tasks and the links between them.
+//!
+//! Tasks are grouped into one cluster per project. Tasks have no dependency or subtask
+//! relations, so the only edges are ticket links: each linked ticket becomes its own node,
+//! and tasks that share a ticket end up connected through it.
+
+use crate::model::Task;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+pub fn render(tasks: &[Task]) -> String {
+    let mut graph = String::new();
+    graph.push_str("digraph tasks {\n");
+    graph.push_str("  rankdir=LR;\n");
+    graph.push_str("  node [shape=box, style=\"rounded\", fontname=\"sans-serif\"];\n");
+
+    let mut projects: BTreeMap<Option<&str>, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        projects
+            .entry(task.project.as_deref())
+            .or_default()
+            .push(task);
+    }
+    for (index, (project, tasks)) in projects.iter().enumerate() {
+        let indent = match project {
+            Some(project) => {
+                let _ = writeln!(graph, "  subgraph cluster_{index} {{");
+                let _ = writeln!(graph, "    label=\"{}\";", escape(project));
+                "    "
+            }
+            None => "  ",
+        };
+        for task in tasks {
+            let _ = writeln!(graph, "{indent}{};", task_node(task));
+        }
+        if project.is_some() {
+            graph.push_str("  }\n");
+        }
+    }
+
+    let mut tickets: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+    for task in tasks {
+        if let Some(ticket) = task.ticket.as_ref() {
+            let status = tickets.entry(ticket.key.as_str()).or_default();
+            if status.is_none() {
+                *status = ticket.status.as_deref();
+            }
+        }
+    }
+    for (key, status) in &tickets {
+        let label = match status {
+            Some(status) => format!("{}\\n{}", escape(key), escape(status)),
+            None => escape(key),
+        };
+        let _ = writeln!(
+            graph,
+            "  \"ticket:{}\" [label=\"{label}\", shape=note];",
+            escape(key)
+        );
+    }
+    for task in tasks {
+        if let Some(ticket) = task.ticket.as_ref() {
+            let _ = writeln!(
+                graph,
+                "  \"task:{}\" -> \"ticket:{}\" [style=dashed];",
+                escape(&task.id),
+                escape(&ticket.key)
+            );
+        }
+    }
+
+    graph.push_str("}\n");
+    graph
+}
+
+fn task_node(task: &Task) -> String {
+    let mut attributes = vec![format!(
+        "label=\"{}\\n#{}\"",
+        escape(&task.title),
+        escape(&task.id)
+    )];
+    if !task.status.is_open() {
+        attributes.push("style=\"rounded,dashed\"".to_string());
+        attributes.push("fontcolor=gray50".to_string());
+        attributes.push("color=gray50".to_string());
+    } else if task.urgent {
+        attributes.push("color=red".to_string());
+        attributes.push("penwidth=2".to_string());
+    }
+    format!("\"task:{}\" [{}]", escape(&task.id), attributes.join(", "))
+}
+
+/// Escapes text for a double-quoted DOT string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::model::{Task, TaskStatus, TicketLink};
+
+    fn task(id: &str, title: &str, project: Option<&str>) -> Task {
+        Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            project: project.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn render_clusters_projects_and_links_shared_tickets() {
+        let ticket = TicketLink {
+            key: "ENG-7".to_string(),
+            url: None,
+            provider: None,
+            status: Some("In \"review\"".to_string()),
+            synced_at: None,
+        };
+        let mut fix = task("1", "Fix login", Some("work"));
+        fix.ticket = Some(ticket.clone());
+        fix.urgent = true;
+        let mut verify = task("2", "Verify fix", Some("work"));
+        verify.ticket = Some(ticket);
+        verify.status = TaskStatus::Completed;
+        let errand = task("3", "Buy milk", None);
+
+        let dot = render(&[fix, verify, errand]);
+
+        assert!(dot.starts_with("digraph tasks {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("  \"task:3\" [label=\"Buy milk\\n#3\"];\n"));
+        let cluster = dot.find("    label=\"work\";").unwrap();
+        assert!(
+            cluster
+                < dot
+                    .find("    \"task:1\" [label=\"Fix login\\n#1\", color=red")
+                    .unwrap()
+        );
+        assert!(dot.contains("\"task:2\" [label=\"Verify fix\\n#2\", style=\"rounded,dashed\""));
+        assert_eq!(dot.matches("\"ticket:ENG-7\" [label").count(), 1);
+        assert!(dot.contains("label=\"ENG-7\\nIn \\\"review\\\"\", shape=note"));
+        assert!(dot.contains("  \"task:1\" -> \"ticket:ENG-7\" [style=dashed];\n"));
+        assert!(dot.contains("  \"task:2\" -> \"ticket:ENG-7\" [style=dashed];\n"));
+    }
+}
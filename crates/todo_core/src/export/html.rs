@@ -0,0 +1,249 @@
+//! Standalone HTML page: inline CSS, no scripts or external assets, so the file can be dropped
+//! on a wiki or any static host as is.
+
+use super::ExportSnapshot;
+use crate::error::AppError;
+use crate::model::Task;
+use crate::task_api;
+use std::fmt::Write;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+
+const STYLE: &str = "\
+body{font-family:system-ui,-apple-system,\"Segoe UI\",sans-serif;max-width:52rem;margin:2rem auto;padding:0 1rem;color:#1f2328;background:#fff}\
+h1{font-size:1.6rem;margin-bottom:.2rem}\
+.generated{color:#6e7781;margin-top:0}\
+h2{font-size:1.15rem;border-bottom:1px solid #d0d7de;padding-bottom:.3rem;margin-top:2rem}\
+h2 .count{color:#6e7781;font-weight:normal}\
+ul{list-style:none;padding:0}\
+li{padding:.55rem 0;border-bottom:1px solid #eaeef2}\
+.title{font-weight:600}\
+.done .title{text-decoration:line-through;color:#6e7781}\
+.meta{color:#6e7781;font-size:.85rem;margin-top:.15rem}\
+.badge{display:inline-block;font-size:.75rem;padding:0 .45rem;border-radius:1rem;margin-left:.4rem;vertical-align:middle}\
+.urgent{background:#ffebe9;color:#cf222e}\
+.overdue{background:#fff8c5;color:#9a6700}\
+.tag{background:#ddf4ff;color:#0969da}\
+.notes{white-space:pre-wrap;font-size:.9rem;margin:.3rem 0 0}\
+.comments{margin:.4rem 0 0;padding-left:.8rem;border-left:2px solid #d0d7de}\
+.comments li{border:0;padding:.15rem 0;font-size:.9rem;white-space:pre-wrap}\
+.comments .meta{display:inline;margin-right:.4rem}\
+.empty{color:#6e7781;font-style:italic}\
+@media (prefers-color-scheme:dark){body{color:#e6edf3;background:#0d1117}h2{border-color:#30363d}li{border-color:#21262d}}";
+
+pub fn render(snapshot: &ExportSnapshot) -> Result<String, AppError> {
+    let mut page = String::new();
+    let generated = display_time(snapshot.generated_at)?;
+
+    page.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    page.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    page.push_str("<title>Tasks</title>\n");
+    let _ = writeln!(page, "<style>{STYLE}</style>");
+    page.push_str("</head>\n<body>\n<h1>Tasks</h1>\n");
+    let _ = writeln!(page, "<p class=\"generated\">Generated {generated}</p>");
+
+    let sections = [
+        ("today", "Today", &snapshot.today),
+        ("backlog", "Backlog", &snapshot.backlog),
+        ("done", "Done", &snapshot.done),
+    ];
+    for (class, heading, tasks) in sections {
+        let _ = writeln!(
+            page,
+            "<section class=\"{class}\">\n<h2>{heading} <span class=\"count\">({})</span></h2>",
+            tasks.len()
+        );
+        if tasks.is_empty() {
+            page.push_str("<p class=\"empty\">Nothing here.</p>\n");
+        } else {
+            page.push_str("<ul>\n");
+            for task in tasks.iter() {
+                render_task(&mut page, task, snapshot)?;
+            }
+            page.push_str("</ul>\n");
+        }
+        page.push_str("</section>\n");
+    }
+
+    page.push_str("</body>\n</html>\n");
+    Ok(page)
+}
+
+fn render_task(page: &mut String, task: &Task, snapshot: &ExportSnapshot) -> Result<(), AppError> {
+    let now = snapshot.generated_at;
+    let _ = write!(
+        page,
+        "<li id=\"task-{}\"><span class=\"title\">{}</span>",
+        escape(&task.id),
+        escape(&task.title)
+    );
+    let pending = task.status.is_open();
+    if task.urgent && pending {
+        page.push_str("<span class=\"badge urgent\">urgent</span>");
+    }
+
+    let scheduled = parse_time(task.due_at.as_deref())?;
+    if pending && scheduled.is_some_and(|at| task_api::overdue_at(at, now, snapshot.overdue_grace))
+    {
+        page.push_str("<span class=\"badge overdue\">overdue</span>");
+    }
+    for tag in &task.tags {
+        let _ = write!(page, "<span class=\"badge tag\">{}</span>", escape(tag));
+    }
+
+    let mut meta = vec![format!("#{}", escape(&task.id))];
+    if let Some(project) = task.project.as_deref() {
+        meta.push(format!("project {}", escape(project)));
+    }
+    if let Some(at) = scheduled {
+        meta.push(format!(
+            "scheduled {}",
+            display_time(at.to_offset(now.offset()))?
+        ));
+    }
+    if let Some(at) = parse_time(task.completed_at.as_deref())? {
+        meta.push(format!(
+            "completed {}",
+            display_time(at.to_offset(now.offset()))?
+        ));
+    }
+    if let Some(cancellation) = task.cancellation.as_ref()
+        && let Some(at) = parse_time(Some(&cancellation.cancelled_at))?
+    {
+        let mut cancelled = format!("cancelled {}", display_time(at.to_offset(now.offset()))?);
+        if let Some(reason) = cancellation.reason.as_deref() {
+            let _ = write!(cancelled, ": {}", escape(reason));
+        }
+        meta.push(cancelled);
+    }
+    let _ = write!(
+        page,
+        "<div class=\"meta\">{}</div>",
+        meta.join(" &middot; ")
+    );
+
+    if let Some(notes) = task.notes.as_deref() {
+        let _ = write!(page, "<p class=\"notes\">{}</p>", escape(notes));
+    }
+    if !task.comments.is_empty() {
+        page.push_str("<ul class=\"comments\">");
+        for comment in &task.comments {
+            let at = parse_time(Some(&comment.created_at))?
+                .map(|at| display_time(at.to_offset(now.offset())))
+                .transpose()?
+                .unwrap_or_default();
+            let _ = write!(
+                page,
+                "<li><span class=\"meta\">{} &middot; {at}</span>{}</li>",
+                escape(&comment.author),
+                escape(&comment.text)
+            );
+        }
+        page.push_str("</ul>");
+    }
+    page.push_str("</li>\n");
+    Ok(())
+}
+
+fn parse_time(value: Option<&str>) -> Result<Option<OffsetDateTime>, AppError> {
+    value
+        .map(|value| {
+            OffsetDateTime::parse(value, &Rfc3339)
+                .map_err(|_| AppError::invalid_data("timestamps must be RFC3339"))
+        })
+        .transpose()
+}
+
+fn display_time(value: OffsetDateTime) -> Result<String, AppError> {
+    value
+        .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+        .map_err(|err| AppError::invalid_data(err.to_string()))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::export::ExportSnapshot;
+    use crate::model::{Comment, Task, TaskStatus};
+    use time::Duration;
+    use time::macros::datetime;
+
+    fn task(id: &str, title: &str, scheduled_at: Option<&str>) -> Task {
+        Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            due_at: scheduled_at.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn render_groups_sections_and_escapes_text() {
+        let mut overdue = task(
+            "1",
+            "Fix <script> & \"quotes\"",
+            Some("2025-12-19T09:00:00Z"),
+        );
+        overdue.urgent = true;
+        overdue.tags = vec!["work".to_string()];
+        overdue.comments.push(Comment {
+            author: "ana".to_string(),
+            text: "Needs <review>".to_string(),
+            created_at: "2025-12-19T08:00:00Z".to_string(),
+        });
+        let later = task("2", "Plan trip", Some("2025-12-24T09:00:00Z"));
+        let mut done = task("3", "Send report", None);
+        done.status = TaskStatus::Completed;
+        done.completed_at = Some("2025-12-19T17:00:00Z".to_string());
+        let snapshot = ExportSnapshot::from_tasks(
+            &[later, done, overdue],
+            datetime!(2025-12-20 12:00 +1),
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        let html = render(&snapshot).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Generated 2025-12-20 12:00"));
+        assert!(html.contains("Fix &lt;script&gt; &amp; &quot;quotes&quot;"));
+        assert!(!html.contains("<script>"));
+        let today = html
+            .find("<h2>Today <span class=\"count\">(1)</span>")
+            .unwrap();
+        let backlog = html
+            .find("<h2>Backlog <span class=\"count\">(1)</span>")
+            .unwrap();
+        let done = html
+            .find("<h2>Done <span class=\"count\">(1)</span>")
+            .unwrap();
+        let overdue_item = html.find("id=\"task-1\"").unwrap();
+        let later_item = html.find("id=\"task-2\"").unwrap();
+        let done_item = html.find("id=\"task-3\"").unwrap();
+        assert!(today < overdue_item && overdue_item < backlog);
+        assert!(backlog < later_item && later_item < done);
+        assert!(done < done_item);
+        assert!(html.contains("<span class=\"badge overdue\">overdue</span>"));
+        assert!(html.contains("completed 2025-12-19 18:00"));
+        assert!(html.contains(
+            "<li><span class=\"meta\">ana &middot; 2025-12-19 09:00</span>Needs &lt;review&gt;</li>"
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+//! Read-only renderings of the store for sharing outside the CLI.
+
+pub mod csv;
+pub mod digest;
+pub mod dot;
+pub mod html;
+pub mod verify;
+
+use crate::config::OrderingConfig;
+use crate::error::AppError;
+use crate::model::Task;
+use crate::ordering;
+use std::cmp::Reverse;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+/// Tasks split into the sections every export presents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSnapshot {
+    /// Local time the snapshot was taken; its offset is used to display every timestamp.
+    pub generated_at: OffsetDateTime,
+    /// How long past its deadline a task may run before it is shown as overdue.
+    pub overdue_grace: Duration,
+    /// Pending tasks scheduled today or earlier, in the default `list today` order.
+    pub today: Vec<Task>,
+    /// Pending tasks scheduled later or not at all, in store order.
+    pub backlog: Vec<Task>,
+    /// Completed and cancelled tasks, most recently closed first.
+    pub done: Vec<Task>,
+}
+
+impl ExportSnapshot {
+    pub fn from_tasks(
+        tasks: &[Task],
+        now: OffsetDateTime,
+        overdue_grace: Duration,
+    ) -> Result<Self, AppError> {
+        let mut today = Vec::new();
+        let mut backlog = Vec::new();
+        let mut done = Vec::new();
+
+        for task in tasks {
+            if !task.status.is_open() {
+                done.push(task.clone());
+                continue;
+            }
+            let scheduled_date = task
+                .planned_at()
+                .map(|value| {
+                    OffsetDateTime::parse(value, &Rfc3339)
+                        .map(|scheduled| scheduled.to_offset(now.offset()).date())
+                        .map_err(|_| AppError::invalid_data("start_at and due_at must be RFC3339"))
+                })
+                .transpose()?;
+            match scheduled_date {
+                Some(date) if date <= now.date() => today.push(task.clone()),
+                _ => backlog.push(task.clone()),
+            }
+        }
+
+        let today = ordering::sort_tasks(
+            today,
+            &OrderingConfig::default().today,
+            None,
+            now,
+            overdue_grace,
+        )?;
+        done.sort_by_cached_key(|task| {
+            Reverse(
+                task.closed_at()
+                    .and_then(|value| OffsetDateTime::parse(value, &Rfc3339).ok()),
+            )
+        });
+
+        Ok(Self {
+            generated_at: now,
+            overdue_grace,
+            today,
+            backlog,
+            done,
+        })
+    }
+}
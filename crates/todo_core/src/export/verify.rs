@@ -0,0 +1,122 @@
+//! `export --verify`: tasks written in each format that can be read back are read into an empty
+//! store and compared with the originals, field by field, so a format that drops or mangles a
+//! field it is meant to carry is caught before anyone relies on it.
+
+use crate::diff;
+use crate::error::AppError;
+use crate::model::Task;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How the tasks fared through one format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrip {
+    pub format: &'static str,
+    pub tasks: usize,
+    /// Fields the format carries that came back different, with the tasks they changed in.
+    pub lost: Vec<LostField>,
+    /// Fields some task has a value for that the format has no place for.
+    pub not_carried: Vec<String>,
+}
+
+impl RoundTrip {
+    pub fn is_lossless(&self) -> bool {
+        self.lost.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LostField {
+    pub field: String,
+    pub task_ids: Vec<String>,
+}
+
+/// Compares `original` with `read_back`, the same tasks in the same order after a round trip
+/// through `format`. `carried` lists the fields the format is meant to keep; `None` means all of
+/// them. A task missing from `read_back` loses every field it has a value for.
+pub fn compare(
+    format: &'static str,
+    original: &[Task],
+    read_back: &[Task],
+    carried: Option<&[&str]>,
+) -> Result<RoundTrip, AppError> {
+    let mut lost: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut not_carried = BTreeSet::new();
+    for (index, task) in original.iter().enumerate() {
+        let before = diff::task_fields(task)?;
+        let after = match read_back.get(index) {
+            Some(back) => diff::task_fields(back)?,
+            None => Default::default(),
+        };
+        for (field, value) in &before {
+            let is_carried = carried.is_none_or(|fields| fields.contains(&field.as_str()));
+            if !is_carried {
+                if has_value(value) {
+                    not_carried.insert(field.clone());
+                }
+                continue;
+            }
+            if after.get(field).unwrap_or(&Value::Null) != value {
+                lost.entry(field.clone()).or_default().push(task.id.clone());
+            }
+        }
+        // A field the original leaves out that came back set was made up on the way.
+        for (field, value) in &after {
+            let is_carried = carried.is_none_or(|fields| fields.contains(&field.as_str()));
+            if is_carried && !before.contains_key(field) && has_value(value) {
+                lost.entry(field.clone()).or_default().push(task.id.clone());
+            }
+        }
+    }
+
+    Ok(RoundTrip {
+        format,
+        tasks: original.len(),
+        lost: lost
+            .into_iter()
+            .map(|(field, task_ids)| LostField { field, task_ids })
+            .collect(),
+        not_carried: not_carried.into_iter().collect(),
+    })
+}
+
+fn has_value(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => false,
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+        Value::Number(_) | Value::Bool(true) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compare;
+    use crate::testing;
+
+    #[test]
+    fn compare_separates_lost_fields_from_ones_the_format_never_carried() {
+        let mut original = testing::task("task-1", "Pay rent").build().unwrap();
+        original.tags = vec!["side project".to_string()];
+        original.project = Some("home".to_string());
+        let mut back = testing::task("task-9", "Pay rent").build().unwrap();
+        back.tags = vec!["side".to_string(), "project".to_string()];
+        back.project = Some("home".to_string());
+
+        let trip = compare(
+            "csv",
+            &[original],
+            &[back],
+            Some(&["title", "tags", "project"]),
+        )
+        .unwrap();
+
+        assert!(!trip.is_lossless());
+        assert_eq!(trip.lost.len(), 1);
+        assert_eq!(trip.lost[0].field, "tags");
+        assert_eq!(trip.lost[0].task_ids, ["task-1"]);
+        assert!(trip.not_carried.contains(&"id".to_string()));
+        assert!(!trip.not_carried.contains(&"urgent".to_string()));
+    }
+}
@@ -0,0 +1,805 @@
+//! Task filter expressions such as `project:work or (tag:home and not status:completed)`.
+//!
+//! Terms are `project:NAME`, `tag:NAME`, `status:STATUS`, `urgent:yes|no`, a bare word that must appear in the title, or a comparison
+//! `FIELD OP VALUE` such as `due_at < now + 2d` or `tag ~ 'work'`. Values compare without
+//! regard to case and may be quoted to include spaces. Adjacent terms are joined with an
+//! implicit `and`; `not` binds tighter than `and`, which binds tighter than `or`.
+//!
+//! Comparisons use `=`, `!=`, `~` (contains), and, on timestamps, `<`, `<=`, `>`, `>=`.
+//! Fields are `id`, `title`, `notes`, `project`, `tag`, `status`, `urgent`, `created_at`,
+//! `start_at`, `due_at` (or its old name `scheduled_at`), `completed_at`, and `meta.KEY` for a
+//! metadata value. Timestamps are compared
+//! with `now` or `today`, optionally shifted by `+`/`-` a number of `m`, `h`, `d`, or `w`, or
+//! with a date such as `2025-12-24` or `'2025-12-24 09:00'` in local time. `none` matches a
+//! missing project, notes, timestamp, or metadata key.
+//! `a != b` is always the same as `not a = b`.
+//!
+//! A status is `pending`, `in_progress`, `waiting`, `completed`, or `cancelled`, or one of
+//! `open` (neither completed nor cancelled) and `overdue` (open and past its deadline, allowing for
+//! the grace passed to [`Filter::matches`]).
+
+use crate::duration;
+use crate::error::AppError;
+use crate::model::{Task, TaskStatus};
+use crate::task_api;
+use crate::text::fold_case;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Project(String),
+    Tag(String),
+    Status(StatusFilter),
+    Urgent(bool),
+    /// Case-insensitive substring of the title.
+    Text(String),
+    Compare(Comparison),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFilter {
+    Pending,
+    InProgress,
+    Waiting,
+    Completed,
+    Cancelled,
+    /// Pending, in progress, or waiting; see [`TaskStatus::is_open`].
+    Open,
+    /// Open and scheduled before now.
+    Overdue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub field: Field,
+    pub op: CompareOp,
+    pub value: Operand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Title,
+    Notes,
+    Project,
+    Tag,
+    Status,
+    Urgent,
+    CreatedAt,
+    StartAt,
+    /// `due_at`, also read as `scheduled_at`, the name it had before `start_at` was split off.
+    DueAt,
+    CompletedAt,
+    /// `meta.KEY`: a metadata value, with the key matched without regard to case.
+    Meta(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring; for `tag`, any tag containing the value.
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// Text folded with [`fold_case`].
+    Text(String),
+    Status(StatusFilter),
+    Bool(bool),
+    Time(TimeValue),
+    /// The field is not set.
+    None,
+}
+
+/// A point in time, resolved against the evaluation time so relative values stay current.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeValue {
+    Now(Duration),
+    /// Local midnight at the start of today, shifted by the duration.
+    Today(Duration),
+    /// Date and time in the evaluation time's offset.
+    Local(PrimitiveDateTime),
+    Exact(OffsetDateTime),
+}
+
+impl TimeValue {
+    fn resolve(&self, now: OffsetDateTime) -> OffsetDateTime {
+        match self {
+            TimeValue::Now(shift) => now + *shift,
+            TimeValue::Today(shift) => now.replace_time(Time::MIDNIGHT) + *shift,
+            TimeValue::Local(at) => at.assume_offset(now.offset()),
+            TimeValue::Exact(at) => *at,
+        }
+    }
+}
+
+impl StatusFilter {
+    /// One status name as written after `status:`, ignoring case.
+    pub fn parse(value: &str) -> Result<StatusFilter, AppError> {
+        status_value(&value.trim().to_lowercase())
+    }
+}
+
+impl Filter {
+    pub fn parse(expression: &str) -> Result<Filter, AppError> {
+        let tokens = tokenize(expression)?;
+        if tokens.is_empty() {
+            return Err(AppError::invalid_input("filter must not be empty"));
+        }
+
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let filter = parser.or_expression()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(AppError::invalid_input(format!(
+                "unexpected '{}' in filter",
+                token.text()
+            ))),
+        }
+    }
+
+    /// Whether `task` matches at `now`; an open task only counts as `overdue` once it is
+    /// `grace` past its deadline.
+    pub fn matches(&self, task: &Task, now: OffsetDateTime, grace: Duration) -> bool {
+        match self {
+            Filter::Project(project) => task
+                .project
+                .as_deref()
+                .is_some_and(|value| fold_case(value) == *project),
+            Filter::Tag(tag) => task.tags.iter().any(|value| fold_case(value) == *tag),
+            Filter::Status(StatusFilter::Pending) => task.status == TaskStatus::Pending,
+            Filter::Status(StatusFilter::InProgress) => task.status == TaskStatus::InProgress,
+            Filter::Status(StatusFilter::Waiting) => task.status == TaskStatus::Waiting,
+            Filter::Status(StatusFilter::Completed) => task.status == TaskStatus::Completed,
+            Filter::Status(StatusFilter::Cancelled) => task.status == TaskStatus::Cancelled,
+            Filter::Status(StatusFilter::Open) => task.status.is_open(),
+            Filter::Status(StatusFilter::Overdue) => {
+                task.status.is_open()
+                    && task
+                        .due_at
+                        .as_deref()
+                        .and_then(|value| OffsetDateTime::parse(value, &Rfc3339).ok())
+                        .is_some_and(|due| task_api::overdue_at(due, now, grace))
+            }
+            Filter::Urgent(urgent) => task.urgent == *urgent,
+            Filter::Text(text) => fold_case(&task.title).contains(text.as_str()),
+            Filter::Compare(comparison) => comparison.matches(task, now, grace),
+            Filter::Not(inner) => !inner.matches(task, now, grace),
+            Filter::And(left, right) => {
+                left.matches(task, now, grace) && right.matches(task, now, grace)
+            }
+            Filter::Or(left, right) => {
+                left.matches(task, now, grace) || right.matches(task, now, grace)
+            }
+        }
+    }
+}
+
+impl Comparison {
+    fn matches(&self, task: &Task, now: OffsetDateTime, grace: Duration) -> bool {
+        if self.op == CompareOp::Ne {
+            let equal = Comparison {
+                op: CompareOp::Eq,
+                ..self.clone()
+            };
+            return !equal.matches(task, now, grace);
+        }
+
+        match (&self.field, &self.value) {
+            (Field::Status, Operand::Status(status)) => {
+                Filter::Status(*status).matches(task, now, grace)
+            }
+            (Field::Urgent, Operand::Bool(urgent)) => task.urgent == *urgent,
+            (Field::Tag, Operand::Text(value)) => task
+                .tags
+                .iter()
+                .any(|tag| compare_text(&fold_case(tag), self.op, value)),
+            (Field::CreatedAt | Field::StartAt | Field::DueAt | Field::CompletedAt, value) => {
+                let at = self
+                    .field_text(task)
+                    .and_then(|value| OffsetDateTime::parse(value, &Rfc3339).ok());
+                match (at, value) {
+                    (at, Operand::None) => at.is_none(),
+                    (Some(at), Operand::Time(value)) => {
+                        let value = value.resolve(now);
+                        match self.op {
+                            CompareOp::Lt => at < value,
+                            CompareOp::Le => at <= value,
+                            CompareOp::Gt => at > value,
+                            CompareOp::Ge => at >= value,
+                            _ => at == value,
+                        }
+                    }
+                    _ => false,
+                }
+            }
+            (_, Operand::None) => self.field_text(task).is_none(),
+            (_, Operand::Text(value)) => self
+                .field_text(task)
+                .is_some_and(|text| compare_text(&fold_case(text), self.op, value)),
+            _ => false,
+        }
+    }
+
+    fn field_text<'a>(&self, task: &'a Task) -> Option<&'a str> {
+        match &self.field {
+            Field::Id => Some(&task.id),
+            Field::Title => Some(&task.title),
+            Field::Notes => task.notes.as_deref(),
+            Field::Project => task.project.as_deref(),
+            Field::CreatedAt => Some(&task.created_at),
+            Field::StartAt => task.start_at.as_deref(),
+            Field::DueAt => task.due_at.as_deref(),
+            Field::CompletedAt => task.completed_at.as_deref(),
+            Field::Meta(key) => task
+                .metadata
+                .iter()
+                .find(|(name, _)| name.to_lowercase() == *key)
+                .map(|(_, value)| value.as_str()),
+            Field::Tag | Field::Status | Field::Urgent => None,
+        }
+    }
+}
+
+fn compare_text(text: &str, op: CompareOp, value: &str) -> bool {
+    match op {
+        CompareOp::Contains => text.contains(value),
+        _ => text == value,
+    }
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Field> {
+        let field = match name.to_lowercase().as_str() {
+            "id" => Field::Id,
+            "title" => Field::Title,
+            "notes" => Field::Notes,
+            "project" => Field::Project,
+            "tag" | "tags" => Field::Tag,
+            "status" => Field::Status,
+            "urgent" => Field::Urgent,
+            "created_at" => Field::CreatedAt,
+            "start_at" => Field::StartAt,
+            "due_at" | "scheduled_at" => Field::DueAt,
+            "completed_at" => Field::CompletedAt,
+            name => match name.strip_prefix("meta.") {
+                Some(key) if !key.is_empty() => Field::Meta(key.to_string()),
+                _ => return None,
+            },
+        };
+        Some(field)
+    }
+
+    fn is_time(&self) -> bool {
+        matches!(
+            self,
+            Field::CreatedAt | Field::StartAt | Field::DueAt | Field::CompletedAt
+        )
+    }
+}
+
+impl CompareOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Contains => "~",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Op(CompareOp),
+    Word(String),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::And => "and",
+            Token::Or => "or",
+            Token::Not => "not",
+            Token::Op(op) => op.symbol(),
+            Token::Word(word) => word,
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, AppError> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if ch == '(' || ch == ')' {
+            chars.next();
+            tokens.push(if ch == '(' { Token::Open } else { Token::Close });
+            continue;
+        }
+        if is_operator_char(ch) {
+            chars.next();
+            let followed_by_equals = chars.next_if_eq(&'=').is_some();
+            let op = match (ch, followed_by_equals) {
+                ('=', _) => CompareOp::Eq,
+                ('!', true) => CompareOp::Ne,
+                ('<', false) => CompareOp::Lt,
+                ('<', true) => CompareOp::Le,
+                ('>', false) => CompareOp::Gt,
+                ('>', true) => CompareOp::Ge,
+                ('~', false) => CompareOp::Contains,
+                _ => {
+                    return Err(AppError::invalid_input(format!(
+                        "unknown operator '{ch}' in filter"
+                    )));
+                }
+            };
+            tokens.push(Token::Op(op));
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut quoted = false;
+        while let Some(&ch) = chars.peek() {
+            // Single quotes only open a value, so apostrophes inside title words still work.
+            let opens_quote = ch == '"' || (ch == '\'' && (word.is_empty() || word.ends_with(':')));
+            if opens_quote {
+                chars.next();
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some(inner) if inner == ch => break,
+                        Some(inner) => word.push(inner),
+                        None => {
+                            return Err(AppError::invalid_input("unterminated quote in filter"));
+                        }
+                    }
+                }
+            } else if ch.is_whitespace() || ch == '(' || ch == ')' || is_operator_char(ch) {
+                break;
+            } else {
+                word.push(ch);
+                chars.next();
+            }
+        }
+
+        let keyword = if quoted {
+            None
+        } else {
+            match word.to_lowercase().as_str() {
+                "and" => Some(Token::And),
+                "or" => Some(Token::Or),
+                "not" => Some(Token::Not),
+                _ => None,
+            }
+        };
+        tokens.push(keyword.unwrap_or(Token::Word(word)));
+    }
+
+    Ok(tokens)
+}
+
+fn is_operator_char(ch: char) -> bool {
+    matches!(ch, '=' | '!' | '<' | '>' | '~')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or_expression(&mut self) -> Result<Filter, AppError> {
+        let mut filter = self.and_expression()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            filter = Filter::Or(Box::new(filter), Box::new(self.and_expression()?));
+        }
+        Ok(filter)
+    }
+
+    fn and_expression(&mut self) -> Result<Filter, AppError> {
+        let mut filter = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                Some(Token::Open | Token::Not | Token::Word(_)) => {}
+                _ => return Ok(filter),
+            }
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, AppError> {
+        match self.next() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let filter = self.or_expression()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err(AppError::invalid_input("missing ')' in filter")),
+                }
+            }
+            Some(Token::Word(word)) => match self.peek() {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    self.next();
+                    self.comparison(&word, op)
+                }
+                _ => term(&word),
+            },
+            Some(token) => Err(AppError::invalid_input(format!(
+                "unexpected '{}' in filter",
+                token.text()
+            ))),
+            None => Err(AppError::invalid_input("filter ends unexpectedly")),
+        }
+    }
+}
+
+impl Parser {
+    fn comparison(&mut self, name: &str, op: CompareOp) -> Result<Filter, AppError> {
+        let field = Field::parse(name).ok_or_else(|| {
+            AppError::invalid_input(format!(
+                "unknown field '{name}' (expected id, title, notes, project, tag, status, urgent, \
+                 created_at, start_at, due_at, completed_at, or meta.KEY)"
+            ))
+        })?;
+        let Some(Token::Word(mut value)) = self.next() else {
+            return Err(AppError::invalid_input(format!(
+                "missing value after '{name} {}'",
+                op.symbol()
+            )));
+        };
+        // `now + 2d` arrives as three words; `now+2d` as one.
+        if field.is_time()
+            && matches!(value.to_lowercase().as_str(), "now" | "today")
+            && let Some(Token::Word(shift)) = self.peek()
+            && (shift.starts_with('+') || shift.starts_with('-'))
+        {
+            let shift = shift.clone();
+            self.next();
+            value.push_str(&shift);
+            if shift.len() == 1 {
+                match self.next() {
+                    Some(Token::Word(amount)) => value.push_str(&amount),
+                    _ => {
+                        return Err(AppError::invalid_input(format!(
+                            "missing duration after '{value}'"
+                        )));
+                    }
+                }
+            }
+        }
+
+        let value = operand(&field, op, value.trim())?;
+        Ok(Filter::Compare(Comparison { field, op, value }))
+    }
+}
+
+fn operand(field: &Field, op: CompareOp, value: &str) -> Result<Operand, AppError> {
+    let lowered = fold_case(value);
+    let ordered = matches!(
+        op,
+        CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge
+    );
+    if ordered && !field.is_time() {
+        return Err(AppError::invalid_input(format!(
+            "'{}' only applies to created_at, start_at, due_at, and completed_at",
+            op.symbol()
+        )));
+    }
+    if op == CompareOp::Contains
+        && (field.is_time() || matches!(field, Field::Status | Field::Urgent))
+    {
+        return Err(AppError::invalid_input(
+            "'~' only applies to id, title, notes, project, tag, and meta.KEY",
+        ));
+    }
+
+    match field {
+        Field::Status => status_value(&lowered).map(Operand::Status),
+        Field::Urgent => urgent_value(&lowered).map(Operand::Bool),
+        Field::Notes
+        | Field::Project
+        | Field::StartAt
+        | Field::DueAt
+        | Field::CompletedAt
+        | Field::Meta(_)
+            if lowered == "none" && !ordered =>
+        {
+            Ok(Operand::None)
+        }
+        Field::CreatedAt | Field::StartAt | Field::DueAt | Field::CompletedAt => {
+            time_value(&lowered).map(Operand::Time)
+        }
+        Field::Id | Field::Title | Field::Notes | Field::Project | Field::Tag | Field::Meta(_) => {
+            Ok(Operand::Text(lowered))
+        }
+    }
+}
+
+fn time_value(value: &str) -> Result<TimeValue, AppError> {
+    for (anchor, make) in [
+        ("now", TimeValue::Now as fn(Duration) -> TimeValue),
+        ("today", TimeValue::Today),
+    ] {
+        if let Some(rest) = value.strip_prefix(anchor) {
+            let rest = rest.trim();
+            let shift = match rest.chars().next() {
+                None => Duration::ZERO,
+                Some('+') => duration::parse(&rest[1..])?,
+                Some('-') => -duration::parse(&rest[1..])?,
+                _ => break,
+            };
+            return Ok(make(shift));
+        }
+    }
+
+    if let Ok(at) = PrimitiveDateTime::parse(
+        value,
+        &format_description!("[year]-[month]-[day] [hour]:[minute]"),
+    ) {
+        Ok(TimeValue::Local(at))
+    } else if let Ok(date) = Date::parse(value, &format_description!("[year]-[month]-[day]")) {
+        Ok(TimeValue::Local(date.midnight()))
+    } else {
+        OffsetDateTime::parse(&value.to_uppercase(), &Rfc3339)
+            .map(TimeValue::Exact)
+            .map_err(|_| {
+                AppError::invalid_input(format!(
+                    "invalid time '{value}' (expected now, today, YYYY-MM-DD, or \
+                     'YYYY-MM-DD HH:MM', optionally now/today +/- a duration such as 2d or 1h30m)"
+                ))
+            })
+    }
+}
+
+fn term(word: &str) -> Result<Filter, AppError> {
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(Filter::Text(fold_case(word)));
+    };
+    let value = fold_case(value.trim());
+    if value.is_empty() {
+        return Err(AppError::invalid_input(format!(
+            "missing value for '{key}:'"
+        )));
+    }
+
+    match key.to_lowercase().as_str() {
+        "project" => Ok(Filter::Project(value)),
+        "tag" => Ok(Filter::Tag(value)),
+        "status" => status_value(&value).map(Filter::Status),
+        "urgent" => urgent_value(&value).map(Filter::Urgent),
+        _ => Err(AppError::invalid_input(format!(
+            "unknown filter key '{key}' (expected project, tag, status, or urgent)"
+        ))),
+    }
+}
+
+fn status_value(value: &str) -> Result<StatusFilter, AppError> {
+    match value {
+        "pending" => Ok(StatusFilter::Pending),
+        "in_progress" | "in-progress" => Ok(StatusFilter::InProgress),
+        "waiting" => Ok(StatusFilter::Waiting),
+        "completed" => Ok(StatusFilter::Completed),
+        "cancelled" | "canceled" => Ok(StatusFilter::Cancelled),
+        "open" => Ok(StatusFilter::Open),
+        "overdue" => Ok(StatusFilter::Overdue),
+        _ => Err(AppError::invalid_input(format!(
+            "unknown status '{value}' (expected pending, in_progress, waiting, completed, \
+             cancelled, open, or overdue)"
+        ))),
+    }
+}
+
+fn urgent_value(value: &str) -> Result<bool, AppError> {
+    match value {
+        "yes" | "true" => Ok(true),
+        "no" | "false" => Ok(false),
+        _ => Err(AppError::invalid_input(format!(
+            "unknown urgent value '{value}' (expected yes or no)"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, StatusFilter};
+    use crate::model::{Task, TaskStatus};
+    use time::Duration;
+    use time::macros::datetime;
+
+    fn task(title: &str, project: Option<&str>, tags: &[&str]) -> Task {
+        Task {
+            id: "task-1".to_string(),
+            title: title.to_string(),
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            due_at: Some("2025-12-19T09:00:00Z".to_string()),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            project: project.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_respects_precedence_and_implicit_and() {
+        let filter = Filter::parse("project:Work or tag:home not urgent:yes").unwrap();
+
+        assert_eq!(
+            filter,
+            Filter::Or(
+                Box::new(Filter::Project("work".to_string())),
+                Box::new(Filter::And(
+                    Box::new(Filter::Tag("home".to_string())),
+                    Box::new(Filter::Not(Box::new(Filter::Urgent(true)))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn matches_groups_quotes_and_status() {
+        let now = datetime!(2025-12-20 12:00 UTC);
+        let filter =
+            Filter::parse("(project:\"Side Project\" or tag:work) and status:overdue").unwrap();
+
+        assert!(filter.matches(&task("a", Some("side project"), &[]), now, Duration::ZERO));
+        assert!(filter.matches(&task("b", None, &["Work"]), now, Duration::ZERO));
+        assert!(!filter.matches(&task("c", None, &["home"]), now, Duration::ZERO));
+        let mut completed = task("d", None, &["work"]);
+        completed.status = TaskStatus::Completed;
+        assert!(!filter.matches(&completed, now, Duration::ZERO));
+        assert_eq!(
+            Filter::parse("STATUS:Pending").unwrap(),
+            Filter::Status(StatusFilter::Pending)
+        );
+        assert!(Filter::parse("milk").unwrap().matches(
+            &task("Buy Milk", None, &[]),
+            now,
+            Duration::ZERO
+        ));
+    }
+
+    #[test]
+    fn status_terms_tell_open_states_apart() {
+        let now = datetime!(2025-12-20 12:00 UTC);
+        let mut started = task("Write draft", None, &[]);
+        started.status = TaskStatus::InProgress;
+        let mut blocked = task("Get sign-off", None, &[]);
+        blocked.status = TaskStatus::Waiting;
+        blocked.due_at = None;
+        let matches = |expression: &str, task: &Task| {
+            Filter::parse(expression)
+                .unwrap()
+                .matches(task, now, Duration::ZERO)
+        };
+
+        assert!(matches("status:in_progress", &started));
+        assert!(matches("status = in-progress", &started));
+        assert!(matches("status:overdue", &started));
+        assert!(!Filter::parse("status:overdue").unwrap().matches(
+            &started,
+            now,
+            Duration::days(2)
+        ));
+        assert!(!matches("status:pending", &started));
+        assert!(matches("status:waiting and status:open", &blocked));
+        assert!(!matches("status:overdue", &blocked));
+        blocked.status = TaskStatus::Cancelled;
+        blocked.due_at = Some("2025-12-19T09:00:00Z".to_string());
+        assert!(matches("status:canceled", &blocked));
+        assert!(!matches("status:open or status:overdue", &blocked));
+        assert!(Filter::parse("status:blocked").is_err());
+    }
+
+    #[test]
+    fn comparisons_match_fields_and_relative_times() {
+        let now = datetime!(2025-12-20 12:00 +1);
+        let mut soon = task("Ship release", Some("work"), &["Work-Q4"]);
+        soon.due_at = Some("2025-12-21T09:00:00Z".to_string());
+        let mut later = task("Plan trip", None, &["home"]);
+        later.due_at = Some("2026-01-10T09:00:00Z".to_string());
+        let query = "status = pending and scheduled_at < now + 2d and tag ~ 'work'";
+        let filter = Filter::parse(query).unwrap();
+
+        assert!(filter.matches(&soon, now, Duration::ZERO));
+        assert!(!filter.matches(&later, now, Duration::ZERO));
+        let matches = |expression: &str, task: &Task| {
+            Filter::parse(expression)
+                .unwrap()
+                .matches(task, now, Duration::ZERO)
+        };
+        assert!(matches("scheduled_at>=today-1d", &soon));
+        assert!(matches("scheduled_at > '2025-12-31 23:00'", &later));
+        assert!(matches("scheduled_at <= 2025-12-22", &soon));
+        assert!(matches("project = none", &later));
+        assert!(matches("project != work", &later));
+        assert!(!matches("project != work", &soon));
+        assert!(matches("title ~ trip and urgent = no", &later));
+        assert!(matches("completed_at = none and notes = none", &soon));
+        assert!(matches("title = \"ship release\"", &soon));
+        soon.metadata.insert("Sprint".to_string(), "42".to_string());
+        assert!(matches("meta.sprint = 42", &soon));
+        assert!(matches("meta.sprint ~ 4 and meta.owner = none", &soon));
+        assert!(matches("meta.sprint != 42", &later));
+        assert!(Filter::parse("meta.sprint > 40").is_err());
+        assert!(Filter::parse("meta. = 1").is_err());
+    }
+
+    #[test]
+    fn comparisons_reject_mismatched_operators_and_values() {
+        for expression in [
+            "priority = high",
+            "title < b",
+            "status ~ pend",
+            "scheduled_at ~ now",
+            "scheduled_at < soon",
+            "scheduled_at < now + 2y",
+            "scheduled_at < now +",
+            "status =",
+            "title =< x",
+            "urgent = maybe",
+        ] {
+            let err = Filter::parse(expression).unwrap_err();
+            assert_eq!(err.code(), "invalid_input", "{expression}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_malformed_expressions() {
+        for expression in [
+            "",
+            "project:",
+            "(tag:work",
+            "tag:work)",
+            "priority:high",
+            "status:done",
+            "tag:work or",
+            "\"open",
+        ] {
+            let err = Filter::parse(expression).unwrap_err();
+            assert_eq!(err.code(), "invalid_input", "{expression}");
+        }
+    }
+}
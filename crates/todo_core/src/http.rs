@@ -0,0 +1,104 @@
+//! HTTP requests for ticket lookups and webhooks, made with the system `curl` so no HTTP or TLS
+//! stack is linked into the binary.
+//!
+//! URLs and headers often carry tokens, so the whole request reaches curl as a config file on
+//! stdin rather than in its arguments, which any local user can read.
+
+use crate::error::AppError;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Seconds before a single request is abandoned.
+const REQUEST_TIMEOUT_SECS: u32 = 20;
+
+/// GET `url` and return the response body.
+pub fn get(url: &str, headers: &[(String, String)]) -> Result<Vec<u8>, AppError> {
+    run(&request_config(url, headers, None), "request failed")
+}
+
+/// POST `body` to a webhook at `url` as JSON; the response body is ignored.
+pub fn post_json(url: &str, headers: &[(String, String)], body: &str) -> Result<(), AppError> {
+    let mut headers = headers.to_vec();
+    headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    run(&request_config(url, &headers, Some(body)), "webhook failed").map(|_| ())
+}
+
+/// Run curl on `config`; a request curl reports as failed is an error starting with `failure`.
+fn run(config: &str, failure: &str) -> Result<Vec<u8>, AppError> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", &REQUEST_TIMEOUT_SECS.to_string()])
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| AppError::io(format!("failed to run curl: {err}")))?;
+    let written = child
+        .stdin
+        .take()
+        .map_or(Ok(()), |mut stdin| stdin.write_all(config.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|err| AppError::io(format!("failed to run curl: {err}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::io(format!("{failure}: {}", stderr.trim())));
+    }
+    written.map_err(|err| AppError::io(format!("failed to pass the request to curl: {err}")))?;
+    Ok(output.stdout)
+}
+
+/// The request as a curl config file: quoted `url`, `header`, and `data-raw` lines. `data-raw`
+/// sends a body starting with `@` as is instead of reading it from a file.
+fn request_config(url: &str, headers: &[(String, String)], body: Option<&str>) -> String {
+    let mut config = String::new();
+    push_option(&mut config, "url", url);
+    for (name, value) in headers {
+        push_option(&mut config, "header", &format!("{name}: {value}"));
+    }
+    if let Some(body) = body {
+        push_option(&mut config, "data-raw", body);
+    }
+    config
+}
+
+fn push_option(config: &mut String, name: &str, value: &str) {
+    config.push_str(name);
+    config.push_str(" = \"");
+    for ch in value.chars() {
+        match ch {
+            '\\' => config.push_str("\\\\"),
+            '"' => config.push_str("\\\""),
+            '\n' => config.push_str("\\n"),
+            '\r' => config.push_str("\\r"),
+            _ => config.push(ch),
+        }
+    }
+    config.push_str("\"\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::request_config;
+
+    #[test]
+    fn request_config_quotes_each_option_for_curl() {
+        let headers = [
+            ("Authorization".to_string(), "Bearer s3cret".to_string()),
+            ("X-Note".to_string(), "say \"hi\" \\ bye\n".to_string()),
+        ];
+
+        assert_eq!(
+            request_config("https://example.com/t?token=abc", &headers, None),
+            "url = \"https://example.com/t?token=abc\"\n\
+             header = \"Authorization: Bearer s3cret\"\n\
+             header = \"X-Note: say \\\"hi\\\" \\\\ bye\\n\"\n"
+        );
+        assert_eq!(
+            request_config("https://example.com", &[], Some(r#"{"text":"a \"b\""}"#)),
+            "url = \"https://example.com\"\n\
+             data-raw = \"{\\\"text\\\":\\\"a \\\\\\\"b\\\\\\\"\\\"}\"\n"
+        );
+    }
+}
@@ -0,0 +1,570 @@
+//! CSV files as spreadsheets save them, mapped to task fields column by column.
+//!
+//! A map such as `title=2,due=5,tags=7` picks columns by number, counting from 1, and
+//! `title=Task,due=Due date` by the name in the header row; without a map, columns named after
+//! the fields are used. Fields are `title`, `due`, `start`, `tags`, `project`, `notes`, and
+//! `urgent`. Cells may be quoted, with `""` for a quote inside, and quoted cells may span lines.
+//!
+//! Dates are read as `YYYY-MM-DD` unless a [`DateOrder`] hint such as `DD/MM/YYYY` says
+//! otherwise, optionally followed by a time such as `9:30`, `17:30`, or `5:30pm`; RFC3339
+//! timestamps are always accepted.
+
+use crate::error::AppError;
+use crate::import::ImportedTask;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Month, OffsetDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Title,
+    Due,
+    Start,
+    Tags,
+    Project,
+    Notes,
+    Urgent,
+}
+
+const FIELDS: [Field; 7] = [
+    Field::Title,
+    Field::Due,
+    Field::Start,
+    Field::Tags,
+    Field::Project,
+    Field::Notes,
+    Field::Urgent,
+];
+
+impl Field {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Field::Title => "title",
+            Field::Due => "due",
+            Field::Start => "start",
+            Field::Tags => "tags",
+            Field::Project => "project",
+            Field::Notes => "notes",
+            Field::Urgent => "urgent",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Field> {
+        match name.trim().to_lowercase().as_str() {
+            "title" => Some(Field::Title),
+            "due" | "due_at" => Some(Field::Due),
+            "start" | "start_at" => Some(Field::Start),
+            "tags" | "tag" => Some(Field::Tags),
+            "project" => Some(Field::Project),
+            "notes" | "note" => Some(Field::Notes),
+            "urgent" => Some(Field::Urgent),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    /// Counting from 0.
+    Index(usize),
+    /// Matched against the header row, ignoring case.
+    Header(String),
+}
+
+/// Which column each field is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMap {
+    columns: Vec<(Field, Column)>,
+}
+
+impl ColumnMap {
+    /// `FIELD=COLUMN` pairs separated by commas, where COLUMN is a number counting from 1 or a
+    /// header name. `title` is required.
+    ///
+    /// ```
+    /// use todo_core::import::csv::{Column, ColumnMap, Field};
+    ///
+    /// let map = ColumnMap::parse("title=2, due=Due date")?;
+    /// assert_eq!(map.column(Field::Title), Some(&Column::Index(1)));
+    /// assert_eq!(map.column(Field::Due), Some(&Column::Header("Due date".into())));
+    /// # Ok::<(), todo_core::error::AppError>(())
+    /// ```
+    pub fn parse(spec: &str) -> Result<Self, AppError> {
+        let mut columns: Vec<(Field, Column)> = Vec::new();
+        for pair in spec.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let Some((name, column)) = pair.split_once('=') else {
+                return Err(AppError::invalid_input(format!(
+                    "invalid mapping '{}' (expected FIELD=COLUMN, e.g. title=2)",
+                    pair.trim()
+                )));
+            };
+            let field = Field::parse(name).ok_or_else(|| {
+                AppError::invalid_input(format!("unknown field '{}' in --map", name.trim()))
+                    .with_hint(field_names())
+            })?;
+            if columns.iter().any(|(existing, _)| *existing == field) {
+                return Err(AppError::invalid_input(format!(
+                    "'{}' is mapped more than once",
+                    field.as_str()
+                )));
+            }
+            let column = column.trim();
+            let column = match column.parse::<usize>() {
+                Ok(0) => {
+                    return Err(AppError::invalid_input(
+                        "column numbers start at 1 in --map",
+                    ));
+                }
+                Ok(number) => Column::Index(number - 1),
+                Err(_) if column.is_empty() => {
+                    return Err(AppError::invalid_input(format!(
+                        "no column given for '{}' in --map",
+                        field.as_str()
+                    )));
+                }
+                Err(_) => Column::Header(column.to_string()),
+            };
+            columns.push((field, column));
+        }
+        if !columns.iter().any(|(field, _)| *field == Field::Title) {
+            return Err(AppError::invalid_input("--map must include title")
+                .with_hint("e.g. --map title=1,due=3"));
+        }
+        Ok(Self { columns })
+    }
+
+    /// Every field read from the column of the same name.
+    pub fn by_header() -> Self {
+        Self {
+            columns: FIELDS
+                .iter()
+                .map(|field| (*field, Column::Header(field.as_str().to_string())))
+                .collect(),
+        }
+    }
+
+    pub fn column(&self, field: Field) -> Option<&Column> {
+        self.columns
+            .iter()
+            .find(|(mapped, _)| *mapped == field)
+            .map(|(_, column)| column)
+    }
+
+    /// Column indexes for the mapped fields. Fields the default map can't find are left out,
+    /// except `title`.
+    fn resolve(
+        &self,
+        header: Option<&[String]>,
+        explicit: bool,
+    ) -> Result<Vec<(Field, usize)>, AppError> {
+        let mut resolved = Vec::new();
+        for (field, column) in &self.columns {
+            let index = match column {
+                Column::Index(index) => *index,
+                Column::Header(name) => {
+                    let Some(header) = header else {
+                        return Err(AppError::invalid_input(format!(
+                            "column '{name}' needs a header row"
+                        ))
+                        .with_hint("map columns by number with --no-header, e.g. title=1"));
+                    };
+                    match header
+                        .iter()
+                        .position(|cell| cell.trim().eq_ignore_ascii_case(name.trim()))
+                    {
+                        Some(index) => index,
+                        None if !explicit && *field != Field::Title => continue,
+                        None => {
+                            return Err(AppError::invalid_input(format!(
+                                "no column named '{name}'"
+                            ))
+                            .with_hint(format!("the header has: {}", header.join(", "))));
+                        }
+                    }
+                }
+            };
+            resolved.push((*field, index));
+        }
+        Ok(resolved)
+    }
+}
+
+fn field_names() -> String {
+    let names: Vec<&str> = FIELDS.iter().map(|field| field.as_str()).collect();
+    format!("fields are {}", names.join(", "))
+}
+
+/// The order of year, month, and day in a date, from a hint such as `DD/MM/YYYY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateOrder {
+    parts: [DatePart; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DatePart {
+    Year,
+    Month,
+    Day,
+}
+
+impl Default for DateOrder {
+    fn default() -> Self {
+        Self {
+            parts: [DatePart::Year, DatePart::Month, DatePart::Day],
+        }
+    }
+}
+
+impl DateOrder {
+    /// `YYYY` (or `YY`), `MM`, and `DD` in any order, separated by anything but letters.
+    /// A time, if the hint includes one, is ignored: times are always read after the date.
+    pub fn parse(hint: &str) -> Result<Self, AppError> {
+        let invalid = || {
+            AppError::invalid_input(format!(
+                "invalid date format '{}' (expected e.g. DD/MM/YYYY or MM/DD/YYYY)",
+                hint.trim()
+            ))
+        };
+        let mut parts = Vec::new();
+        for token in hint
+            .split(|ch: char| !ch.is_ascii_alphabetic())
+            .filter(|token| !token.is_empty())
+        {
+            let part = match token {
+                "YYYY" | "yyyy" | "YY" | "yy" => DatePart::Year,
+                "MM" | "M" => DatePart::Month,
+                "DD" | "dd" | "D" | "d" => DatePart::Day,
+                "HH" | "hh" | "H" | "h" | "mm" | "m" | "SS" | "ss" => continue,
+                _ => return Err(invalid()),
+            };
+            if parts.contains(&part) {
+                return Err(invalid());
+            }
+            parts.push(part);
+        }
+        match parts[..] {
+            [first, second, third] => Ok(Self {
+                parts: [first, second, third],
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn hint(&self) -> String {
+        let tokens: Vec<&str> = self
+            .parts
+            .iter()
+            .map(|part| match part {
+                DatePart::Year => "YYYY",
+                DatePart::Month => "MM",
+                DatePart::Day => "DD",
+            })
+            .collect();
+        tokens.join("-")
+    }
+
+    /// `value` as `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`, or `None` when it isn't a date in this
+    /// order.
+    fn read(&self, value: &str) -> Option<String> {
+        let numbers: Vec<&str> = value
+            .split(|ch: char| !ch.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .collect();
+        if !(3..=6).contains(&numbers.len())
+            || value
+                .trim_start()
+                .starts_with(|ch: char| !ch.is_ascii_digit())
+        {
+            return None;
+        }
+        let (mut year, mut month, mut day) = (0, 0, 0);
+        for (part, number) in self.parts.iter().zip(&numbers) {
+            match part {
+                DatePart::Year if number.len() == 2 => year = 2000 + number.parse::<i32>().ok()?,
+                DatePart::Year if number.len() == 4 => year = number.parse().ok()?,
+                DatePart::Year => return None,
+                DatePart::Month => month = number.parse().ok()?,
+                DatePart::Day => day = number.parse().ok()?,
+            }
+        }
+        let date = Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()?;
+        let date = format!(
+            "{:04}-{:02}-{:02}",
+            date.year(),
+            u8::from(date.month()),
+            date.day()
+        );
+        if numbers.len() == 3 {
+            return Some(date);
+        }
+
+        let mut hour: u8 = numbers[3].parse().ok()?;
+        let minute: u8 = numbers
+            .get(4)
+            .map_or(Some(0), |number| number.parse().ok())?;
+        let lowered = value.to_lowercase();
+        let meridiem = lowered.trim_end();
+        if meridiem.ends_with("pm") || meridiem.ends_with("am") {
+            if !(1..=12).contains(&hour) {
+                return None;
+            }
+            hour %= 12;
+            if meridiem.ends_with("pm") {
+                hour += 12;
+            }
+        }
+        (hour < 24 && minute < 60).then(|| format!("{date} {hour:02}:{minute:02}"))
+    }
+}
+
+/// How to read a file for [`read`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// `None` reads every field from the column named after it.
+    pub map: Option<ColumnMap>,
+    pub date_order: DateOrder,
+    /// Whether the first row names the columns rather than holding a task.
+    pub header: bool,
+    pub delimiter: char,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            map: None,
+            date_order: DateOrder::default(),
+            header: true,
+            delimiter: ',',
+        }
+    }
+}
+
+/// The tasks in a CSV file, one per row, in file order. Blank rows are skipped; anything that
+/// can't be read fails the whole file with the row it is on.
+///
+/// ```
+/// use todo_core::import::csv::{self, ColumnMap, CsvOptions, DateOrder};
+///
+/// let content = "Task,Owner,Due\n\"Renew passport, urgently\",me,24/12/2025\n";
+/// let options = CsvOptions {
+///     map: Some(ColumnMap::parse("title=1,due=Due")?),
+///     date_order: DateOrder::parse("DD/MM/YYYY")?,
+///     ..CsvOptions::default()
+/// };
+/// let tasks = csv::read(content, &options)?;
+/// assert_eq!(tasks[0].title, "Renew passport, urgently");
+/// assert_eq!(tasks[0].due_at.as_deref(), Some("2025-12-24"));
+/// # Ok::<(), todo_core::error::AppError>(())
+/// ```
+pub fn read(content: &str, options: &CsvOptions) -> Result<Vec<ImportedTask>, AppError> {
+    let mut records = records(content, options.delimiter)?.into_iter();
+    let header = if options.header {
+        records.next().map(|(_, cells)| cells)
+    } else {
+        None
+    };
+    let (map, explicit) = match &options.map {
+        Some(map) => (map.clone(), true),
+        None if options.header => (ColumnMap::by_header(), false),
+        None => {
+            return Err(AppError::invalid_input("--no-header needs --map")
+                .with_hint("map columns by number, e.g. --map title=1,due=3"));
+        }
+    };
+    let columns = map.resolve(header.as_deref(), explicit)?;
+
+    let mut tasks = Vec::new();
+    for (row, cells) in records {
+        if cells.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let at_row = |err: AppError| {
+            let message = format!("row {row}: {}", err.message());
+            err.with_message(message)
+        };
+        let mut task = ImportedTask {
+            row,
+            ..ImportedTask::default()
+        };
+        for (field, index) in &columns {
+            let cell = cells.get(*index).map_or("", |cell| cell.trim());
+            read_cell(&mut task, *field, cell, options.date_order).map_err(at_row)?;
+        }
+        if task.title.is_empty() {
+            return Err(at_row(AppError::invalid_input("title is empty")));
+        }
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+fn read_cell(
+    task: &mut ImportedTask,
+    field: Field,
+    cell: &str,
+    dates: DateOrder,
+) -> Result<(), AppError> {
+    let text = || (!cell.is_empty()).then(|| cell.to_string());
+    match field {
+        Field::Title => task.title = cell.to_string(),
+        Field::Due => task.due_at = read_date(field, cell, dates)?,
+        Field::Start => task.start_at = read_date(field, cell, dates)?,
+        Field::Tags => {
+            task.tags = cell
+                .split(|ch: char| ch == ',' || ch == ';' || ch.is_whitespace())
+                .map(|tag| tag.trim_start_matches('#'))
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        Field::Project => task.project = text(),
+        Field::Notes => task.notes = text(),
+        Field::Urgent => {
+            task.urgent = match cell.to_lowercase().as_str() {
+                "" | "no" | "n" | "false" | "0" => false,
+                "yes" | "y" | "true" | "1" | "x" | "urgent" => true,
+                other => {
+                    return Err(AppError::invalid_input(format!(
+                        "urgent '{other}' is not yes or no"
+                    )));
+                }
+            };
+        }
+    }
+    Ok(())
+}
+
+fn read_date(field: Field, cell: &str, dates: DateOrder) -> Result<Option<String>, AppError> {
+    if cell.is_empty() {
+        return Ok(None);
+    }
+    if OffsetDateTime::parse(cell, &Rfc3339).is_ok() {
+        return Ok(Some(cell.to_string()));
+    }
+    dates.read(cell).map(Some).ok_or_else(|| {
+        AppError::invalid_input(format!("{} '{cell}' is not a date", field.as_str())).with_hint(
+            format!(
+                "dates are read as {}; use --date-format for others, e.g. DD/MM/YYYY",
+                dates.hint()
+            ),
+        )
+    })
+}
+
+/// The records in `content` with the row each starts on, counting from 1.
+fn records(content: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, AppError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut records = Vec::new();
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut row = 1;
+    let mut line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if quoted {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    cell.push(ch);
+                }
+                _ => cell.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                cells.push(std::mem::take(&mut cell));
+                records.push((row, std::mem::take(&mut cells)));
+                line += 1;
+                row = line;
+            }
+            ch if ch == delimiter => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(ch),
+        }
+    }
+    if quoted {
+        return Err(AppError::invalid_input(format!(
+            "row {row}: a quoted cell is never closed"
+        )));
+    }
+    if !cell.is_empty() || !cells.is_empty() {
+        cells.push(cell);
+        records.push((row, cells));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnMap, CsvOptions, DateOrder, read, records};
+
+    #[test]
+    fn records_handle_quotes_line_breaks_and_a_byte_order_mark() {
+        let content = "\u{feff}a,\"b, \"\"c\"\"\"\r\n\"multi\nline\",d\n\ne,f";
+        let parsed = records(content, ',').unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                (1, vec!["a".to_string(), "b, \"c\"".to_string()]),
+                (2, vec!["multi\nline".to_string(), "d".to_string()]),
+                (4, vec![String::new()]),
+                (5, vec!["e".to_string(), "f".to_string()]),
+            ]
+        );
+        assert!(records("\"open", ',').is_err());
+    }
+
+    #[test]
+    fn date_orders_read_dates_and_times() {
+        let european = DateOrder::parse("DD/MM/YYYY").unwrap();
+        assert_eq!(european.read("3/4/2025").as_deref(), Some("2025-04-03"));
+        assert_eq!(
+            european.read("24.12.25 5:30pm").as_deref(),
+            Some("2025-12-24 17:30")
+        );
+        assert_eq!(european.read("31/02/2025"), None);
+        let default = DateOrder::default();
+        assert_eq!(
+            default.read("2025-12-24 09:00:00").as_deref(),
+            Some("2025-12-24 09:00")
+        );
+        assert_eq!(default.read("12/24/2025"), None);
+        assert!(DateOrder::parse("DD/MM").is_err());
+    }
+
+    #[test]
+    fn read_maps_columns_by_number_or_header() {
+        let content = "Task,Due,Labels,Urgent\nPay rent,2025-12-01,home; bills,x\n,,,\n";
+        let tasks = read(
+            content,
+            &CsvOptions {
+                map: Some(ColumnMap::parse("title=1,due=2,tags=Labels,urgent=4").unwrap()),
+                ..CsvOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].row, 2);
+        assert_eq!(tasks[0].due_at.as_deref(), Some("2025-12-01"));
+        assert_eq!(tasks[0].tags, ["home", "bills"]);
+        assert!(tasks[0].urgent);
+
+        let by_header = read("title,project\nWater plants,home\n", &CsvOptions::default()).unwrap();
+        assert_eq!(by_header[0].project.as_deref(), Some("home"));
+
+        let err = read("title,due\nShip,soon\n", &CsvOptions::default()).unwrap_err();
+        assert_eq!(err.message(), "row 2: due 'soon' is not a date");
+        let err = ColumnMap::parse("due=2").unwrap_err();
+        assert_eq!(err.message(), "--map must include title");
+    }
+}
@@ -0,0 +1,19 @@
+//! Tasks read from other tools' files for `import`, checked before anything is added.
+
+pub mod csv;
+
+/// One task to add, with its fields already parsed. Timestamps are local `YYYY-MM-DD` or
+/// `YYYY-MM-DD HH:MM`, as `schedule` accepts them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedTask {
+    /// Where the task came from in the file, for errors and previews: the spreadsheet row,
+    /// counting a header row as row 1.
+    pub row: usize,
+    pub title: String,
+    pub due_at: Option<String>,
+    pub start_at: Option<String>,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub notes: Option<String>,
+    pub urgent: bool,
+}
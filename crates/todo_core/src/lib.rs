@@ -1,9 +1,31 @@
+pub mod clock;
 pub mod config;
+pub mod diff;
+pub mod duration;
 pub mod error;
+pub mod export;
+pub mod filter;
+pub mod http;
+pub mod import;
 pub mod model;
 pub mod notify;
+pub mod ordering;
+pub mod origin;
+pub mod period;
+pub mod permissions;
+pub mod secrets;
+pub mod seed;
+pub mod stats;
 pub mod storage;
+#[cfg(feature = "sync")]
+pub mod sync;
 pub mod task_api;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod text;
+pub mod tickets;
+pub mod usage;
+pub mod webhooks;
 
 #[cfg(test)]
 mod tests {
@@ -15,19 +37,15 @@ mod tests {
         let task = Task {
             id: "task-1".to_string(),
             title: "demo".to_string(),
-            status: TaskStatus::Pending,
             created_at: "2025-12-20T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+            ..Default::default()
         };
 
         assert_eq!(task.id, "task-1");
         assert_eq!(task.title, "demo");
         assert_eq!(task.status, TaskStatus::Pending);
         assert_eq!(task.created_at, "2025-12-20T00:00:00Z");
-        assert_eq!(task.scheduled_at, None);
+        assert_eq!(task.due_at, None);
         assert_eq!(task.completed_at, None);
         assert!(task.completion_history.is_empty());
         assert!(!task.urgent);
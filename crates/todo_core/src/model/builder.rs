@@ -0,0 +1,246 @@
+use crate::clock;
+use crate::error::AppError;
+use crate::model::{Task, TaskStatus};
+use crate::text;
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// A new pending task, checked the way the task API checks its input. Start one with
+/// [`Task::builder`]; struct literals ending in `..Default::default()` still work for tasks
+/// that skip the checks, such as ones read back from elsewhere.
+///
+/// ```
+/// use todo_core::model::Task;
+///
+/// let task = Task::builder().title("Buy milk").urgent(true).tag("home").build()?;
+///
+/// assert!(task.urgent);
+/// assert_eq!(task.tags, vec!["home"]);
+/// # Ok::<(), todo_core::error::AppError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TaskBuilder {
+    id: Option<String>,
+    title: String,
+    created_at: Option<String>,
+    start_at: Option<String>,
+    due_at: Option<String>,
+    urgent: bool,
+    tags: Vec<String>,
+    notes: Option<String>,
+    project: Option<String>,
+    metadata: Vec<(String, String)>,
+}
+
+impl Task {
+    pub fn builder() -> TaskBuilder {
+        TaskBuilder::default()
+    }
+}
+
+impl TaskBuilder {
+    /// Defaults to the last four digits of the current time in nanoseconds, like `add`, which
+    /// also steps past ids already in the store. Set one when the task joins a store that may
+    /// hold the default.
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Required. Trimmed and normalized like every other title, see [`crate::text::normalize`].
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// RFC3339; defaults to now.
+    pub fn created_at(mut self, at: &str) -> Self {
+        self.created_at = Some(at.to_string());
+        self
+    }
+
+    /// RFC3339; when the task is planned to be worked on.
+    pub fn start_at(mut self, at: &str) -> Self {
+        self.start_at = Some(at.to_string());
+        self
+    }
+
+    /// RFC3339; the deadline.
+    pub fn due_at(mut self, at: &str) -> Self {
+        self.due_at = Some(at.to_string());
+        self
+    }
+
+    pub fn urgent(mut self, urgent: bool) -> Self {
+        self.urgent = urgent;
+        self
+    }
+
+    /// Trimmed; a tag given twice is kept once.
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Blank notes are left unset.
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.notes = Some(notes.to_string());
+        self
+    }
+
+    /// A blank project is left unset.
+    pub fn project(mut self, project: &str) -> Self {
+        self.project = Some(project.to_string());
+        self
+    }
+
+    /// Keys follow the rules of `meta set`; a later value for the same key wins.
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.metadata.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// The task, or `invalid_input` naming the first field that is missing or malformed.
+    pub fn build(self) -> Result<Task, AppError> {
+        let title = text::normalize(&self.title);
+        if title.is_empty() {
+            return Err(AppError::invalid_input("title is required"));
+        }
+
+        let now = clock::now_utc();
+        let id = match self.id {
+            Some(id) if id.trim().is_empty() => {
+                return Err(AppError::invalid_input("id is required"));
+            }
+            Some(id) => id.trim().to_string(),
+            None => (now.unix_timestamp_nanos() % 10000).to_string(),
+        };
+        let created_at = match self.created_at {
+            Some(at) => rfc3339("created_at", &at)?,
+            None => now
+                .format(&Rfc3339)
+                .map_err(|err| AppError::invalid_data(err.to_string()))?,
+        };
+        let start_at = self
+            .start_at
+            .map(|at| rfc3339("start_at", &at))
+            .transpose()?;
+        let due_at = self.due_at.map(|at| rfc3339("due_at", &at)).transpose()?;
+
+        let mut tags: Vec<String> = Vec::with_capacity(self.tags.len());
+        for tag in &self.tags {
+            let trimmed = tag.trim();
+            if trimmed.is_empty() {
+                return Err(AppError::invalid_input("tag must not be empty"));
+            }
+            if !tags.iter().any(|existing| existing == trimmed) {
+                tags.push(trimmed.to_string());
+            }
+        }
+
+        let mut metadata = BTreeMap::new();
+        for (key, value) in &self.metadata {
+            let key = crate::task_api::metadata_key(key)?;
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(AppError::invalid_input("metadata value is required"));
+            }
+            metadata.insert(key.to_string(), value.to_string());
+        }
+
+        Ok(Task {
+            id,
+            title,
+            status: TaskStatus::Pending,
+            created_at,
+            start_at,
+            due_at,
+            urgent: self.urgent,
+            tags,
+            notes: non_blank(self.notes),
+            project: non_blank(self.project),
+            metadata,
+            ..Task::default()
+        })
+    }
+}
+
+fn rfc3339(field: &str, value: &str) -> Result<String, AppError> {
+    let trimmed = value.trim();
+    OffsetDateTime::parse(trimmed, &Rfc3339)
+        .map(|_| trimmed.to_string())
+        .map_err(|_| AppError::invalid_input(format!("{field} must be RFC3339: '{trimmed}'")))
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Task, TaskStatus};
+
+    #[test]
+    fn build_fills_defaults_and_normalizes_fields() {
+        let task = Task::builder()
+            .title("  Cafe\u{301} run ")
+            .tag(" errands ")
+            .tag("errands")
+            .notes("   ")
+            .project(" home ")
+            .meta("sprint", " 42 ")
+            .build()
+            .unwrap();
+
+        assert_eq!(task.title, "Caf\u{e9} run");
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert_eq!(task.id.len(), task.id.trim().len());
+        assert!(task.id.parse::<u16>().unwrap() < 10000);
+        assert!(
+            time::OffsetDateTime::parse(
+                &task.created_at,
+                &time::format_description::well_known::Rfc3339
+            )
+            .is_ok()
+        );
+        assert_eq!(task.tags, vec!["errands"]);
+        assert_eq!(task.notes, None);
+        assert_eq!(task.project.as_deref(), Some("home"));
+        assert_eq!(task.metadata["sprint"], "42");
+        assert!(!task.urgent);
+    }
+
+    #[test]
+    fn build_keeps_explicit_values_and_rejects_bad_ones() {
+        let task = Task::builder()
+            .id("task-1")
+            .title("Ship")
+            .created_at("2025-12-20T00:00:00Z")
+            .due_at("2025-12-24T09:00:00+01:00")
+            .urgent(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(task.id, "task-1");
+        assert_eq!(task.created_at, "2025-12-20T00:00:00Z");
+        assert_eq!(task.due_at.as_deref(), Some("2025-12-24T09:00:00+01:00"));
+        assert!(task.urgent);
+
+        let errors = [
+            Task::builder().build(),
+            Task::builder().title("x").id(" ").build(),
+            Task::builder().title("x").due_at("tomorrow").build(),
+            Task::builder().title("x").tag("").build(),
+            Task::builder().title("x").meta("a b", "c").build(),
+        ]
+        .map(|result| result.unwrap_err().message().to_string());
+        assert_eq!(errors[0], "title is required");
+        assert_eq!(errors[1], "id is required");
+        assert_eq!(errors[2], "due_at must be RFC3339: 'tomorrow'");
+        assert_eq!(errors[3], "tag must not be empty");
+        assert!(errors[4].starts_with("metadata key may only contain"));
+    }
+}
@@ -1,3 +1,8 @@
+mod builder;
 mod task;
 
-pub use task::{CompletionEntry, Task, TaskStatus};
+pub use builder::TaskBuilder;
+pub use task::{
+    Cancellation, Comment, CompletionEntry, Marker, MarkerColor, Task, TaskStatus, TicketLink,
+    WorkSession,
+};
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CompletionEntry {
@@ -6,25 +7,199 @@ pub struct CompletionEntry {
     pub completed_at: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The default task is pending and has every optional field unset, but an empty id, title, and
+/// `created_at`; it is a base for struct update syntax, not a task to store as is. Use
+/// [`Task::builder`] for a checked new task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Task {
     pub id: String,
     pub title: String,
     pub status: TaskStatus,
     pub created_at: String,
+    /// When the task is planned to be worked on (RFC3339); `list today` keys off this, or off
+    /// [`Task::due_at`] when it is unset.
     #[serde(default)]
-    pub scheduled_at: Option<String>,
+    pub start_at: Option<String>,
+    /// Hard deadline (RFC3339); the task is overdue once it passes. Stores before schema 18
+    /// called this `scheduled_at`, and are read as if they had written `due_at`.
+    #[serde(default, alias = "scheduled_at")]
+    pub due_at: Option<String>,
     #[serde(default)]
     pub completed_at: Option<String>,
     #[serde(default)]
     pub completion_history: Vec<CompletionEntry>,
     #[serde(default)]
     pub urgent: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Free-form key/value pairs set with `meta`, for integrations.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub ticket: Option<TicketLink>,
+    /// Discussion added with `comment`, oldest first; separate from completion messages.
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    /// Who last changed the task, as the store's
+    /// [`author`](crate::storage::store::TaskStore::author) gave it: `user@host` for the store
+    /// file. Unset on tasks not changed since this was introduced, or changed without an author.
+    #[serde(default)]
+    pub modified_by: Option<String>,
+    /// When the task last changed (RFC3339, UTC).
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    /// When to check back on a [`TaskStatus::Waiting`] task (RFC3339); set with `wait --until`.
+    #[serde(default)]
+    pub waiting_until: Option<String>,
+    /// When and why a [`TaskStatus::Cancelled`] task was dropped.
+    #[serde(default)]
+    pub cancellation: Option<Cancellation>,
+    /// Routine this task was started from with `routine start`. Such tasks are ephemeral:
+    /// `maintain` and startup archive them once the day they were scheduled for is over.
+    #[serde(default)]
+    pub routine: Option<String>,
+    /// Set with `mark` to pick a few tasks out in lists.
+    #[serde(default)]
+    pub marker: Option<Marker>,
+    /// Time spent in progress, oldest first: `start` opens a session, and completing, waiting
+    /// on, or cancelling the task closes it.
+    #[serde(default)]
+    pub sessions: Vec<WorkSession>,
+}
+
+/// One stretch of work on a task, from `start` until it left [`TaskStatus::InProgress`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkSession {
+    pub started_at: String,
+    /// Unset while the task is still in progress.
+    #[serde(default)]
+    pub ended_at: Option<String>,
+}
+
+/// A color, a short symbol such as an emoji, or both, shown before the title in lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    #[serde(default)]
+    pub color: Option<MarkerColor>,
+    #[serde(default)]
+    pub symbol: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
+pub enum MarkerColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl MarkerColor {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MarkerColor::Red => "red",
+            MarkerColor::Orange => "orange",
+            MarkerColor::Yellow => "yellow",
+            MarkerColor::Green => "green",
+            MarkerColor::Blue => "blue",
+            MarkerColor::Purple => "purple",
+        }
+    }
+}
+
+impl Task {
+    /// When the task is planned for: [`Task::start_at`], or the deadline for a task with no
+    /// start. Today's list, the backlog, and `list week` go by this.
+    pub fn planned_at(&self) -> Option<&str> {
+        self.start_at.as_deref().or(self.due_at.as_deref())
+    }
+
+    /// When a completed or cancelled task was closed, or `None` while it is open.
+    pub fn closed_at(&self) -> Option<&str> {
+        match self.status {
+            TaskStatus::Completed => self.completed_at.as_deref(),
+            TaskStatus::Cancelled => self
+                .cancellation
+                .as_ref()
+                .map(|cancellation| cancellation.cancelled_at.as_str()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cancellation {
+    pub cancelled_at: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub author: String,
+    pub text: String,
+    pub created_at: String,
+}
+
+/// External ticket a task tracks, kept in sync by `refresh-tickets`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketLink {
+    pub key: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Configured provider to fetch from; when unset, the provider is chosen by key prefix.
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Status reported by the provider at the last refresh.
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub synced_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
+    #[default]
     Pending,
+    /// Being worked on; set with `start`.
+    InProgress,
+    /// Blocked on someone or something else; set with `wait`.
+    Waiting,
     Completed,
+    /// Dropped without being done; set with `cancel`, see [`Task::cancellation`].
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub const ALL: [TaskStatus; 5] = [
+        TaskStatus::Pending,
+        TaskStatus::InProgress,
+        TaskStatus::Waiting,
+        TaskStatus::Completed,
+        TaskStatus::Cancelled,
+    ];
+
+    /// Neither completed nor cancelled: pending, in progress, or waiting. Open tasks are the
+    /// ones that can be overdue, notified about, completed, and cancelled.
+    pub fn is_open(self) -> bool {
+        !matches!(self, TaskStatus::Completed | TaskStatus::Cancelled)
+    }
+
+    /// The name used in the store, JSON output, and filters.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Waiting => "waiting",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
 }
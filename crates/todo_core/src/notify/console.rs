@@ -0,0 +1,54 @@
+use crate::error::AppError;
+use crate::model::Task;
+use crate::notify::Notifier;
+
+/// Prints notifications to stderr for sessions without a desktop (SSH, cron mail, containers).
+pub struct ConsoleNotifier;
+
+impl Notifier for ConsoleNotifier {
+    fn notify(&self, task: &Task) -> Result<(), AppError> {
+        eprintln!("{}", format_task_line(task));
+        Ok(())
+    }
+
+    fn notify_message(&self, summary: &str, body: &str) -> Result<(), AppError> {
+        eprintln!("{}", format_message_line(summary, body));
+        Ok(())
+    }
+}
+
+pub(crate) fn format_task_line(task: &Task) -> String {
+    let marker = if task.urgent { " [urgent]" } else { "" };
+    format!("[todoapp] {} ({}){marker}", task.title, task.id)
+}
+
+pub(crate) fn format_message_line(summary: &str, body: &str) -> String {
+    format!("[{summary}] {body}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_message_line, format_task_line};
+    use crate::model::Task;
+
+    #[test]
+    fn format_task_line_marks_urgent_tasks() {
+        let task = Task {
+            id: "task-1".to_string(),
+            title: "demo".to_string(),
+            created_at: "2025-12-20T00:00:00Z".to_string(),
+            urgent: true,
+            ..Default::default()
+        };
+
+        assert_eq!(format_task_line(&task), "[todoapp] demo (task-1) [urgent]");
+    }
+
+    #[test]
+    fn format_message_line_prefixes_summary() {
+        assert_eq!(
+            format_message_line("todoapp", "2 tasks need attention"),
+            "[todoapp] 2 tasks need attention"
+        );
+    }
+}
@@ -1,9 +1,65 @@
+use crate::config::LinuxNotificationConfig;
 use crate::error::AppError;
 use crate::model::Task;
 use crate::notify::{Notifier, launch_show};
-use notify_rust::Notification;
+use crate::storage::notify_ids::{self, NotificationIds};
+use notify_rust::{Hint, Notification, Timeout};
+use std::cell::RefCell;
+use std::path::PathBuf;
 
-pub struct LinuxNotifier;
+pub struct LinuxNotifier {
+    config: LinuxNotificationConfig,
+    /// The store the notification IDs are kept beside; without one, notifications stack.
+    store_path: Option<PathBuf>,
+    ids: RefCell<NotificationIds>,
+}
+
+impl LinuxNotifier {
+    pub fn new(config: &LinuxNotificationConfig, store_path: Option<PathBuf>) -> Self {
+        let ids = store_path
+            .as_deref()
+            .map(notify_ids::read)
+            .unwrap_or_default();
+        Self {
+            config: config.clone(),
+            store_path,
+            ids: RefCell::new(ids),
+        }
+    }
+
+    fn notification(&self, summary: &str, body: &str) -> Notification {
+        let mut notification = Notification::new();
+        notification.appname("todoapp").summary(summary).body(body);
+        if let Some(icon) = self.config.icon.as_deref() {
+            notification.icon(icon);
+        }
+        if let Some(category) = self.config.category.as_deref() {
+            notification.hint(Hint::Category(category.to_string()));
+        }
+        if let Some(timeout) = self.config.timeout {
+            notification.timeout(if timeout.is_zero() {
+                Timeout::Never
+            } else {
+                let millis = u32::try_from(timeout.whole_milliseconds()).unwrap_or(u32::MAX);
+                Timeout::Milliseconds(millis)
+            });
+        }
+        if self.config.resident {
+            notification.hint(Hint::Resident(true));
+        }
+        notification
+    }
+
+    /// Keep `id` for the next notification about `task_id`. Failing to save only means that
+    /// one stacks instead of replacing this, so it is not reported.
+    fn remember(&self, task_id: &str, id: u32) {
+        let mut ids = self.ids.borrow_mut();
+        ids.insert(task_id.to_string(), id);
+        if let Some(store_path) = self.store_path.as_deref() {
+            let _ = notify_ids::write(store_path, &ids);
+        }
+    }
+}
 
 impl Notifier for LinuxNotifier {
     fn notify(&self, task: &Task) -> Result<(), AppError> {
@@ -11,9 +67,11 @@ impl Notifier for LinuxNotifier {
     }
 
     fn notify_with_action(&self, task: &Task, action: &str) -> Result<(), AppError> {
-        let mut notification = Notification::new();
-        notification.summary("todoapp");
-        notification.body(&format!("{} ({})", task.title, task.id));
+        let mut notification =
+            self.notification("todoapp", &format!("{} ({})", task.title, task.id));
+        if let Some(id) = self.ids.borrow().get(&task.id) {
+            notification.id(*id);
+        }
         if !action.trim().is_empty() {
             notification.action(action, "Open");
         }
@@ -21,12 +79,13 @@ impl Notifier for LinuxNotifier {
         let handle = notification
             .show()
             .map_err(|err| AppError::io(err.to_string()))?;
+        self.remember(&task.id, handle.id());
 
         if !action.trim().is_empty() {
             let action_key = action.to_string();
             let task_id = task.id.clone();
             std::thread::spawn(move || {
-                let _ = handle.wait_for_action(|selected| {
+                handle.wait_for_action(|selected| {
                     if selected == action_key || selected == "default" {
                         let _ = launch_show(&task_id);
                     }
@@ -36,4 +95,47 @@ impl Notifier for LinuxNotifier {
 
         Ok(())
     }
+
+    fn notify_message(&self, summary: &str, body: &str) -> Result<(), AppError> {
+        self.notification(summary, body)
+            .show()
+            .map_err(|err| AppError::io(err.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinuxNotifier;
+    use crate::config::LinuxNotificationConfig;
+    use notify_rust::{Hint, Timeout};
+    use time::Duration;
+
+    #[test]
+    fn notification_carries_the_configured_hints() {
+        let config = LinuxNotificationConfig {
+            icon: Some("appointment-soon".to_string()),
+            category: Some("x-canonical-private-synchronous".to_string()),
+            timeout: Some(Duration::seconds(8)),
+            resident: true,
+        };
+
+        let notification = LinuxNotifier::new(&config, None).notification("todoapp", "Ship");
+
+        assert_eq!(notification.appname, "todoapp");
+        assert_eq!(notification.icon, "appointment-soon");
+        assert_eq!(notification.timeout, Timeout::Milliseconds(8000));
+        assert!(notification.hints.contains(&Hint::Category(
+            "x-canonical-private-synchronous".to_string()
+        )));
+        assert!(notification.hints.contains(&Hint::Resident(true)));
+
+        let sticky = LinuxNotificationConfig {
+            timeout: Some(Duration::ZERO),
+            ..LinuxNotificationConfig::default()
+        };
+        let notification = LinuxNotifier::new(&sticky, None).notification("todoapp", "Ship");
+        assert_eq!(notification.timeout, Timeout::Never);
+        assert!(notification.hints.is_empty());
+    }
 }
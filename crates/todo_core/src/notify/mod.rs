@@ -1,14 +1,26 @@
-use crate::error::AppError;
+use crate::config::{NotificationBackend, NotificationConfig};
+use crate::error::{AppError, ErrorKind};
 use crate::model::Task;
 
-#[cfg(target_os = "linux")]
+mod console;
+pub use console::ConsoleNotifier;
+
+pub mod run_log;
+pub mod schedule;
+
+mod webhook;
+pub use webhook::WebhookNotifier;
+
+pub mod windows_app;
+
+#[cfg(all(target_os = "linux", feature = "desktop-notifications"))]
 mod linux;
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "desktop-notifications"))]
 pub use linux::LinuxNotifier;
 
-#[cfg(windows)]
+#[cfg(all(windows, feature = "desktop-notifications"))]
 mod windows;
-#[cfg(windows)]
+#[cfg(all(windows, feature = "desktop-notifications"))]
 pub use windows::WindowsNotifier;
 
 pub trait Notifier {
@@ -18,6 +30,9 @@ pub trait Notifier {
         let _ = action;
         self.notify(task)
     }
+
+    /// Send a free-form notification that is not tied to a single task.
+    fn notify_message(&self, summary: &str, body: &str) -> Result<(), AppError>;
 }
 
 pub struct NoopNotifier;
@@ -26,22 +41,124 @@ impl Notifier for NoopNotifier {
     fn notify(&self, _task: &Task) -> Result<(), AppError> {
         Ok(())
     }
+
+    fn notify_message(&self, _summary: &str, _body: &str) -> Result<(), AppError> {
+        Ok(())
+    }
 }
 
 pub fn notifier_from_env() -> Result<Box<dyn Notifier>, AppError> {
+    notifier_for_config(&NotificationConfig::default())
+}
+
+pub fn notifier_for_config(config: &NotificationConfig) -> Result<Box<dyn Notifier>, AppError> {
     if std::env::var("TODOAPP_DISABLE_NOTIFICATIONS").is_ok() {
         return Ok(Box::new(NoopNotifier));
     }
 
-    match platform_notifier() {
+    let use_desktop = match config.backend {
+        NotificationBackend::None => return Ok(Box::new(NoopNotifier)),
+        NotificationBackend::Console => return Ok(Box::new(ConsoleNotifier)),
+        NotificationBackend::Webhook => {
+            return match config.webhook_url.as_deref().map(str::trim) {
+                Some(url) if !url.is_empty() => Ok(Box::new(WebhookNotifier::new(url))),
+                _ => Err(AppError::invalid_input(
+                    "the webhook notification backend needs a webhook_url",
+                )),
+            };
+        }
+        NotificationBackend::Desktop => true,
+        NotificationBackend::Auto => has_gui_session(),
+    };
+    if !use_desktop {
+        return Ok(Box::new(ConsoleNotifier));
+    }
+
+    match platform_notifier(config) {
         Ok(notifier) => Ok(notifier),
-        Err(err) => match err {
-            AppError::InvalidData(_) => Ok(Box::new(NoopNotifier)),
-            other => Err(other),
+        Err(err) => match err.kind() {
+            ErrorKind::InvalidData if config.backend == NotificationBackend::Auto => {
+                Ok(Box::new(ConsoleNotifier))
+            }
+            ErrorKind::InvalidData => Ok(Box::new(NoopNotifier)),
+            _ => Err(err),
         },
     }
 }
 
+/// A notifier for each of `config.routes`, in the same order.
+pub fn route_notifiers(config: &NotificationConfig) -> Result<Vec<Box<dyn Notifier>>, AppError> {
+    config
+        .routes
+        .iter()
+        .map(|route| notifier_for_config(&route.apply(config)))
+        .collect()
+}
+
+/// How one destination took the sample notification sent by `todo notify test`.
+#[derive(Debug)]
+pub struct NotificationCheck {
+    /// `default`, or the route, such as `project:work` or `tag:personal`.
+    pub destination: String,
+    pub backend: NotificationBackend,
+    pub result: Result<(), AppError>,
+}
+
+/// Send a sample notification through `backend` and through each of `routes`.
+pub fn send_test_notifications(config: &NotificationConfig) -> Vec<NotificationCheck> {
+    destinations(config)
+        .into_iter()
+        .map(|(destination, config)| {
+            let result = notifier_for_config(&config).and_then(|notifier| {
+                notifier.notify_message("todoapp", "Test notification: todo can reach you here")
+            });
+            NotificationCheck {
+                destination,
+                backend: config.backend,
+                result,
+            }
+        })
+        .collect()
+}
+
+fn destinations(config: &NotificationConfig) -> Vec<(String, NotificationConfig)> {
+    let mut destinations = vec![("default".to_string(), config.clone())];
+    for route in &config.routes {
+        let name = [
+            route
+                .project
+                .as_deref()
+                .map(|project| format!("project:{project}")),
+            route.tag.as_deref().map(|tag| format!("tag:{tag}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+        destinations.push((name, route.apply(config)));
+    }
+    destinations
+}
+
+/// Whether desktop notifications can be displayed in the current session.
+pub fn has_gui_session() -> bool {
+    if cfg!(target_os = "linux") {
+        gui_session_from_vars(
+            std::env::var("DISPLAY").ok().as_deref(),
+            std::env::var("WAYLAND_DISPLAY").ok().as_deref(),
+        )
+    } else {
+        true
+    }
+}
+
+fn gui_session_from_vars(display: Option<&str>, wayland_display: Option<&str>) -> bool {
+    [display, wayland_display]
+        .into_iter()
+        .flatten()
+        .any(|value| !value.trim().is_empty())
+}
+
 const ACTION_PREFIX: &str = "show:";
 
 pub fn activation_argument(task_id: &str) -> String {
@@ -64,26 +181,38 @@ pub fn launch_show(task_id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-#[cfg(target_os = "linux")]
-pub fn platform_notifier() -> Result<Box<dyn Notifier>, AppError> {
-    Ok(Box::new(LinuxNotifier))
+#[cfg(all(target_os = "linux", feature = "desktop-notifications"))]
+pub fn platform_notifier(config: &NotificationConfig) -> Result<Box<dyn Notifier>, AppError> {
+    Ok(Box::new(LinuxNotifier::new(
+        &config.linux,
+        crate::storage::json_store::store_path().ok(),
+    )))
 }
 
-#[cfg(windows)]
-pub fn platform_notifier() -> Result<Box<dyn Notifier>, AppError> {
-    Ok(Box::new(WindowsNotifier))
+#[cfg(all(windows, feature = "desktop-notifications"))]
+pub fn platform_notifier(config: &NotificationConfig) -> Result<Box<dyn Notifier>, AppError> {
+    Ok(Box::new(WindowsNotifier::new(
+        config.windows.app_id.as_deref(),
+    )))
 }
 
-#[cfg(not(any(target_os = "linux", windows)))]
-pub fn platform_notifier() -> Result<Box<dyn Notifier>, AppError> {
+#[cfg(not(all(feature = "desktop-notifications", any(target_os = "linux", windows))))]
+pub fn platform_notifier(_config: &NotificationConfig) -> Result<Box<dyn Notifier>, AppError> {
     Err(AppError::invalid_data(
-        "notifications are not supported on this platform",
+        if cfg!(feature = "desktop-notifications") {
+            "notifications are not supported on this platform"
+        } else {
+            "this build leaves out desktop notifications (the `desktop-notifications` feature)"
+        },
     ))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{activation_argument, parse_activation_argument};
+    use super::{
+        activation_argument, destinations, gui_session_from_vars, parse_activation_argument,
+    };
+    use crate::config::{NotificationBackend, NotificationConfig, NotificationRoute};
 
     #[test]
     fn activation_argument_round_trip() {
@@ -96,4 +225,34 @@ mod tests {
     fn parse_activation_argument_rejects_other_values() {
         assert!(parse_activation_argument("other:task-1").is_none());
     }
+
+    #[test]
+    fn destinations_are_the_default_then_each_route() {
+        let config = NotificationConfig {
+            backend: NotificationBackend::Console,
+            routes: vec![NotificationRoute {
+                project: Some("work".to_string()),
+                tag: Some("ops".to_string()),
+                backend: NotificationBackend::Webhook,
+                webhook_url: Some("https://hooks.example.com/t".to_string()),
+            }],
+            ..NotificationConfig::default()
+        };
+
+        let destinations = destinations(&config);
+
+        assert_eq!(destinations.len(), 2);
+        assert_eq!(destinations[0].0, "default");
+        assert_eq!(destinations[0].1.backend, NotificationBackend::Console);
+        assert_eq!(destinations[1].0, "project:work tag:ops");
+        assert_eq!(destinations[1].1.backend, NotificationBackend::Webhook);
+    }
+
+    #[test]
+    fn gui_session_requires_a_display_variable() {
+        assert!(!gui_session_from_vars(None, None));
+        assert!(!gui_session_from_vars(Some(" "), None));
+        assert!(gui_session_from_vars(Some(":0"), None));
+        assert!(gui_session_from_vars(None, Some("wayland-0")));
+    }
 }
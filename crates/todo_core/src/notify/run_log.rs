@@ -0,0 +1,169 @@
+//! `todo notify --log FILE`: one JSON line per run, appended, so unattended runs from cron or
+//! a timer can be audited later.
+
+use crate::error::AppError;
+use crate::task_api::NotificationOutcome;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunRecord {
+    /// When the run started (RFC3339, UTC).
+    pub at: String,
+    pub duration_ms: u64,
+    /// Every task the run tried to notify, whether or not it got through.
+    pub selected: Vec<String>,
+    pub notified: Vec<String>,
+    pub failed: Vec<FailedRecord>,
+    pub digests: Vec<String>,
+    /// Set when the run stopped before notifying anything, such as on an unreadable store.
+    pub error: Option<ErrorRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedRecord {
+    pub task_id: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorRecord {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl RunRecord {
+    pub fn new(
+        result: &Result<NotificationOutcome, AppError>,
+        started: OffsetDateTime,
+        elapsed: Duration,
+    ) -> Result<Self, AppError> {
+        let mut record = Self {
+            at: started
+                .format(&Rfc3339)
+                .map_err(|err| AppError::invalid_data(err.to_string()))?,
+            duration_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            selected: Vec::new(),
+            notified: Vec::new(),
+            failed: Vec::new(),
+            digests: Vec::new(),
+            error: None,
+        };
+        match result {
+            Ok(outcome) => {
+                record.notified = outcome.tasks.iter().map(|task| task.id.clone()).collect();
+                record.failed = outcome
+                    .failures
+                    .iter()
+                    .map(|failure| FailedRecord {
+                        task_id: failure.task_id.clone(),
+                        code: failure.error.code(),
+                        message: failure.error.message().to_string(),
+                    })
+                    .collect();
+                record.selected = record
+                    .notified
+                    .iter()
+                    .cloned()
+                    .chain(record.failed.iter().map(|failure| failure.task_id.clone()))
+                    .collect();
+                record.digests = outcome.digests.clone();
+            }
+            Err(err) => {
+                record.error = Some(ErrorRecord {
+                    code: err.code(),
+                    message: err.message().to_string(),
+                })
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// Add `record` as the last line of `path`, creating it owner-only if it does not exist yet.
+pub fn append(path: &Path, record: &RunRecord) -> Result<(), AppError> {
+    let mut line =
+        serde_json::to_string(record).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    line.push('\n');
+
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|err| AppError::io(format!("{}: {err}", path.display())))?;
+    // One write per line, so runs that overlap do not interleave within a record.
+    file.write_all(line.as_bytes())
+        .map_err(|err| AppError::io(format!("{}: {err}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RunRecord, append};
+    use crate::error::AppError;
+    use crate::task_api::{NotificationFailure, NotificationOutcome};
+    use crate::testing;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use time::macros::datetime;
+
+    fn temp_path(file_name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+    }
+
+    #[test]
+    fn runs_append_one_json_line_each() {
+        let path = temp_path("notify-log.ndjson");
+        let outcome = NotificationOutcome {
+            tasks: vec![testing::task("task-1", "Ship").build().unwrap()],
+            failures: vec![NotificationFailure {
+                task_id: "task-2".to_string(),
+                error: AppError::io("no bus"),
+            }],
+            digests: Vec::new(),
+        };
+        let started = datetime!(2025-12-20 09:00 UTC);
+
+        let sent = RunRecord::new(&Ok(outcome), started, Duration::from_millis(42)).unwrap();
+        let broken = RunRecord::new(
+            &Err(AppError::invalid_data("store is corrupt")),
+            started,
+            Duration::ZERO,
+        )
+        .unwrap();
+        append(&path, &sent).unwrap();
+        append(&path, &broken).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["at"], "2025-12-20T09:00:00Z");
+        assert_eq!(lines[0]["duration_ms"], 42);
+        assert_eq!(
+            lines[0]["selected"],
+            serde_json::json!(["task-1", "task-2"])
+        );
+        assert_eq!(lines[0]["notified"], serde_json::json!(["task-1"]));
+        assert_eq!(lines[0]["failed"][0]["message"], "no bus");
+        assert!(lines[0]["error"].is_null());
+        assert_eq!(lines[1]["error"]["code"], "invalid_data");
+        assert_eq!(lines[1]["selected"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::duration;
+use crate::error::AppError;
+use std::path::{Path, PathBuf};
+#[cfg(any(target_os = "linux", windows))]
+use std::process::Command;
+
+#[cfg(target_os = "linux")]
+const UNIT_NAME: &str = "todoapp-notify";
+#[cfg(windows)]
+const TASK_NAME: &str = "todoapp-notify";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleInstallation {
+    /// `systemd` or `schtasks`.
+    pub mechanism: &'static str,
+    pub interval_minutes: Option<u32>,
+    pub files: Vec<PathBuf>,
+}
+
+/// Parse an interval such as `15m`, `90min`, or `1h30m` into whole minutes; see
+/// [`crate::duration::parse`].
+pub fn parse_interval_minutes(raw: &str) -> Result<u32, AppError> {
+    let minutes = duration::parse(raw)
+        .map_err(|_| AppError::invalid_input("interval must look like '15m' or '1h'"))?
+        .whole_minutes();
+    if minutes == 0 {
+        return Err(AppError::invalid_input(
+            "interval must be at least 1 minute",
+        ));
+    }
+    u32::try_from(minutes).map_err(|_| AppError::invalid_input("interval is too long"))
+}
+
+pub fn install_schedule(every: &str) -> Result<ScheduleInstallation, AppError> {
+    let minutes = parse_interval_minutes(every)?;
+    let exe = std::env::current_exe().map_err(|err| AppError::io(err.to_string()))?;
+    install_for_platform(&exe, minutes)
+}
+
+pub fn uninstall_schedule() -> Result<ScheduleInstallation, AppError> {
+    uninstall_for_platform()
+}
+
+#[cfg(target_os = "linux")]
+fn systemd_user_dir() -> Result<PathBuf, AppError> {
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME")
+        && !dir.trim().is_empty()
+    {
+        return Ok(PathBuf::from(dir).join("systemd").join("user"));
+    }
+    let home = std::env::var("HOME").map_err(|_| AppError::invalid_data("HOME is not set"))?;
+    Ok(PathBuf::from(home)
+        .join(".config")
+        .join("systemd")
+        .join("user"))
+}
+
+/// The store and config are the ones resolved for the installing command, including
+/// `--profile` and `--config`, so the timer notifies about the same tasks with the same
+/// settings.
+#[cfg(any(target_os = "linux", test))]
+fn render_service_unit(exe: &Path, store_path: &Path, config_path: &Path) -> String {
+    format!(
+        "[Unit]\nDescription=todoapp notifications\n\n[Service]\nType=oneshot\nEnvironment=\"TODOAPP_STORE_PATH={}\"\nEnvironment=\"TODOAPP_CONFIG_PATH={}\"\nExecStart=\"{}\" notify\n",
+        store_path.display(),
+        config_path.display(),
+        exe.display()
+    )
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn render_timer_unit(minutes: u32) -> String {
+    format!(
+        "[Unit]\nDescription=Run todoapp notifications every {minutes} minutes\n\n[Timer]\nOnBootSec={minutes}min\nOnUnitActiveSec={minutes}min\n\n[Install]\nWantedBy=timers.target\n"
+    )
+}
+
+#[cfg(any(target_os = "linux", windows))]
+pub(super) fn run(program: &str, args: &[&str]) -> Result<(), AppError> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|err| AppError::io(format!("failed to run {program}: {err}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(AppError::io(format!(
+            "{program} {} exited with {status}",
+            args.join(" ")
+        )))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn install_for_platform(exe: &Path, minutes: u32) -> Result<ScheduleInstallation, AppError> {
+    let dir = systemd_user_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|err| AppError::io(err.to_string()))?;
+
+    let store_path = crate::storage::json_store::store_path()?;
+    let config_path = crate::config::config_path()?;
+    let service_path = dir.join(format!("{UNIT_NAME}.service"));
+    let timer_path = dir.join(format!("{UNIT_NAME}.timer"));
+    std::fs::write(
+        &service_path,
+        render_service_unit(exe, &store_path, &config_path),
+    )
+    .map_err(|err| AppError::io(err.to_string()))?;
+    std::fs::write(&timer_path, render_timer_unit(minutes))
+        .map_err(|err| AppError::io(err.to_string()))?;
+
+    run("systemctl", &["--user", "daemon-reload"])?;
+    run(
+        "systemctl",
+        &["--user", "enable", "--now", &format!("{UNIT_NAME}.timer")],
+    )?;
+
+    Ok(ScheduleInstallation {
+        mechanism: "systemd",
+        interval_minutes: Some(minutes),
+        files: vec![service_path, timer_path],
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn uninstall_for_platform() -> Result<ScheduleInstallation, AppError> {
+    let dir = systemd_user_dir()?;
+    let service_path = dir.join(format!("{UNIT_NAME}.service"));
+    let timer_path = dir.join(format!("{UNIT_NAME}.timer"));
+
+    if timer_path.exists() {
+        run(
+            "systemctl",
+            &["--user", "disable", "--now", &format!("{UNIT_NAME}.timer")],
+        )?;
+    }
+
+    let mut removed = Vec::new();
+    for path in [service_path, timer_path] {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|err| AppError::io(err.to_string()))?;
+            removed.push(path);
+        }
+    }
+    if removed.is_empty() {
+        return Err(AppError::invalid_input(
+            "no notification schedule is installed",
+        ));
+    }
+    run("systemctl", &["--user", "daemon-reload"])?;
+
+    Ok(ScheduleInstallation {
+        mechanism: "systemd",
+        interval_minutes: None,
+        files: removed,
+    })
+}
+
+#[cfg(windows)]
+fn install_for_platform(exe: &Path, minutes: u32) -> Result<ScheduleInstallation, AppError> {
+    if minutes > 1439 {
+        return Err(AppError::invalid_input(
+            "interval must be shorter than 24h on Windows",
+        ));
+    }
+    let command = format!("\"{}\" notify", exe.display());
+    let interval = minutes.to_string();
+    run(
+        "schtasks",
+        &[
+            "/Create", "/SC", "MINUTE", "/MO", &interval, "/TN", TASK_NAME, "/TR", &command, "/F",
+        ],
+    )?;
+
+    Ok(ScheduleInstallation {
+        mechanism: "schtasks",
+        interval_minutes: Some(minutes),
+        files: Vec::new(),
+    })
+}
+
+#[cfg(windows)]
+fn uninstall_for_platform() -> Result<ScheduleInstallation, AppError> {
+    run("schtasks", &["/Delete", "/TN", TASK_NAME, "/F"])?;
+    Ok(ScheduleInstallation {
+        mechanism: "schtasks",
+        interval_minutes: None,
+        files: Vec::new(),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn install_for_platform(_exe: &Path, _minutes: u32) -> Result<ScheduleInstallation, AppError> {
+    Err(AppError::invalid_input(
+        "installing a notification schedule is not supported on this platform",
+    ))
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn uninstall_for_platform() -> Result<ScheduleInstallation, AppError> {
+    Err(AppError::invalid_input(
+        "installing a notification schedule is not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_interval_minutes, render_service_unit, render_timer_unit};
+    use std::path::Path;
+
+    #[test]
+    fn parse_interval_minutes_accepts_minutes_and_hours() {
+        assert_eq!(parse_interval_minutes("15m").unwrap(), 15);
+        assert_eq!(parse_interval_minutes(" 90min ").unwrap(), 90);
+        assert_eq!(parse_interval_minutes("2h").unwrap(), 120);
+        assert_eq!(parse_interval_minutes("1h30m").unwrap(), 90);
+        assert_eq!(parse_interval_minutes("1d").unwrap(), 1440);
+    }
+
+    #[test]
+    fn parse_interval_minutes_rejects_bad_values() {
+        assert_eq!(
+            parse_interval_minutes("").unwrap_err().code(),
+            "invalid_input"
+        );
+        assert_eq!(
+            parse_interval_minutes("15").unwrap_err().code(),
+            "invalid_input"
+        );
+        assert_eq!(
+            parse_interval_minutes("0m").unwrap_err().code(),
+            "invalid_input"
+        );
+        assert_eq!(
+            parse_interval_minutes("1x").unwrap_err().code(),
+            "invalid_input"
+        );
+    }
+
+    #[test]
+    fn service_unit_runs_notify_with_store_and_config_paths() {
+        let unit = render_service_unit(
+            Path::new("/usr/bin/todo_opt"),
+            Path::new("/tmp/work/tasks.json"),
+            Path::new("/tmp/work.json"),
+        );
+        assert!(unit.contains("ExecStart=\"/usr/bin/todo_opt\" notify"));
+        assert!(unit.contains("Environment=\"TODOAPP_STORE_PATH=/tmp/work/tasks.json\""));
+        assert!(unit.contains("Environment=\"TODOAPP_CONFIG_PATH=/tmp/work.json\""));
+    }
+
+    #[test]
+    fn timer_unit_uses_interval() {
+        let unit = render_timer_unit(15);
+        assert!(unit.contains("OnUnitActiveSec=15min"));
+        assert!(unit.contains("WantedBy=timers.target"));
+    }
+}
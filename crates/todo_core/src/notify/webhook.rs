@@ -0,0 +1,51 @@
+use crate::error::AppError;
+use crate::http;
+use crate::model::Task;
+use crate::notify::Notifier;
+use crate::notify::console::{format_message_line, format_task_line};
+use crate::secrets::{self, Keyring};
+
+/// Posts each notification as `{"text": ...}` JSON to a chat webhook through [`http`]. The URL
+/// may refer to secrets as `${secret:NAME}`; they are looked up on each post, so commands that
+/// notify nobody never touch the keyring.
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    fn post(&self, text: &str) -> Result<(), AppError> {
+        let url = secrets::expand(&self.url, &Keyring)?;
+        http::post_json(&url, &[], &payload(text))
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, task: &Task) -> Result<(), AppError> {
+        self.post(&format_task_line(task))
+    }
+
+    fn notify_message(&self, summary: &str, body: &str) -> Result<(), AppError> {
+        self.post(&format_message_line(summary, body))
+    }
+}
+
+fn payload(text: &str) -> String {
+    serde_json::json!({ "text": text }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::payload;
+
+    #[test]
+    fn payload_is_a_text_message() {
+        assert_eq!(
+            payload("[todoapp] \"ship\" (task-1)"),
+            r#"{"text":"[todoapp] \"ship\" (task-1)"}"#
+        );
+    }
+}
@@ -3,7 +3,18 @@ use crate::model::Task;
 use crate::notify::{Notifier, launch_show, parse_activation_argument};
 use tauri_winrt_notification::Toast;
 
-pub struct WindowsNotifier;
+pub struct WindowsNotifier {
+    app_id: String,
+}
+
+impl WindowsNotifier {
+    /// Toasts go out under `app_id`, or PowerShell's AppUserModelID when it is not set.
+    pub fn new(app_id: Option<&str>) -> Self {
+        Self {
+            app_id: app_id.unwrap_or(Toast::POWERSHELL_APP_ID).to_string(),
+        }
+    }
+}
 
 impl Notifier for WindowsNotifier {
     fn notify(&self, task: &Task) -> Result<(), AppError> {
@@ -13,7 +24,7 @@ impl Notifier for WindowsNotifier {
     fn notify_with_action(&self, task: &Task, action: &str) -> Result<(), AppError> {
         let task_id = task.id.clone();
         let action_value = action.to_string();
-        let mut toast = Toast::new(Toast::POWERSHELL_APP_ID)
+        let mut toast = Toast::new(&self.app_id)
             .title("todoapp")
             .text1(&task.title)
             .text2(&task.id);
@@ -45,4 +56,13 @@ impl Notifier for WindowsNotifier {
             .map_err(|err| AppError::io(err.to_string()))?;
         Ok(())
     }
+
+    fn notify_message(&self, summary: &str, body: &str) -> Result<(), AppError> {
+        Toast::new(&self.app_id)
+            .title(summary)
+            .text1(body)
+            .show()
+            .map_err(|err| AppError::io(err.to_string()))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,155 @@
+//! `todo notify register-windows-app`: registers an AppUserModelID for the current user so
+//! toasts sent under it show the configured name and icon instead of PowerShell's.
+
+use crate::config::WindowsNotificationConfig;
+use crate::error::AppError;
+
+/// The AppUserModelID registered when `notifications.windows.app_id` is not set.
+pub const DEFAULT_APP_ID: &str = "Todo.Cli";
+
+/// Windows caps an AppUserModelID at 128 characters.
+const MAX_APP_ID_LEN: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppRegistration {
+    pub app_id: String,
+    pub display_name: String,
+    /// The registry key written, under `HKEY_CURRENT_USER`.
+    pub key: String,
+    /// Whether `app_id` came from the config; when not, toasts keep PowerShell's identity
+    /// until it is set there.
+    pub configured: bool,
+}
+
+pub fn register_windows_app(
+    config: &WindowsNotificationConfig,
+) -> Result<AppRegistration, AppError> {
+    let (registration, commands) = registration_plan(config)?;
+    apply(&commands)?;
+    Ok(registration)
+}
+
+/// What to register and the `reg add` arguments that do it, one value per command.
+fn registration_plan(
+    config: &WindowsNotificationConfig,
+) -> Result<(AppRegistration, Vec<Vec<String>>), AppError> {
+    let app_id = config
+        .app_id
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(DEFAULT_APP_ID);
+    if app_id.is_empty() || app_id.len() > MAX_APP_ID_LEN || app_id.contains(['\\', ' ']) {
+        return Err(AppError::invalid_input(format!(
+            "notifications.windows.app_id '{app_id}' must be 1 to {MAX_APP_ID_LEN} characters with no spaces or backslashes, such as {DEFAULT_APP_ID}"
+        )));
+    }
+    let display_name = config.display_name.trim();
+    if display_name.is_empty() {
+        return Err(AppError::invalid_input(
+            "notifications.windows.display_name must not be empty",
+        ));
+    }
+
+    let key = format!(r"HKCU\Software\Classes\AppUserModelId\{app_id}");
+    let value = |name: &str, kind: &str, data: &str| -> Vec<String> {
+        ["add", &key, "/v", name, "/t", kind, "/d", data, "/f"]
+            .map(String::from)
+            .to_vec()
+    };
+    let mut commands = vec![value("DisplayName", "REG_SZ", display_name)];
+    if let Some(icon) = &config.icon {
+        if !icon.is_file() {
+            return Err(AppError::invalid_input(format!(
+                "notifications.windows.icon '{}' does not exist",
+                icon.display()
+            )));
+        }
+        commands.push(value(
+            "IconUri",
+            "REG_EXPAND_SZ",
+            &icon.display().to_string(),
+        ));
+    }
+
+    let registration = AppRegistration {
+        app_id: app_id.to_string(),
+        display_name: display_name.to_string(),
+        key,
+        configured: config.app_id.is_some(),
+    };
+    Ok((registration, commands))
+}
+
+#[cfg(windows)]
+fn apply(commands: &[Vec<String>]) -> Result<(), AppError> {
+    for args in commands {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        super::schedule::run("reg", &args)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn apply(_commands: &[Vec<String>]) -> Result<(), AppError> {
+    Err(AppError::invalid_input(
+        "registering a notification app is only supported on Windows",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_APP_ID, registration_plan};
+    use crate::config::WindowsNotificationConfig;
+
+    #[test]
+    fn registration_plan_writes_the_display_name_under_the_app_id() {
+        let config = WindowsNotificationConfig {
+            app_id: Some("Acme.Todo".to_string()),
+            ..WindowsNotificationConfig::default()
+        };
+
+        let (registration, commands) = registration_plan(&config).unwrap();
+
+        assert_eq!(registration.app_id, "Acme.Todo");
+        assert_eq!(registration.display_name, "Todo");
+        assert!(registration.configured);
+        assert_eq!(
+            commands,
+            [[
+                "add",
+                r"HKCU\Software\Classes\AppUserModelId\Acme.Todo",
+                "/v",
+                "DisplayName",
+                "/t",
+                "REG_SZ",
+                "/d",
+                "Todo",
+                "/f",
+            ]]
+        );
+    }
+
+    #[test]
+    fn registration_plan_falls_back_to_the_default_id_and_checks_the_icon() {
+        let (registration, _) = registration_plan(&WindowsNotificationConfig::default()).unwrap();
+        assert_eq!(registration.app_id, DEFAULT_APP_ID);
+        assert!(!registration.configured);
+
+        let missing_icon = WindowsNotificationConfig {
+            icon: Some("/no/such/todo.ico".into()),
+            ..WindowsNotificationConfig::default()
+        };
+        assert_eq!(
+            registration_plan(&missing_icon).unwrap_err().message(),
+            "notifications.windows.icon '/no/such/todo.ico' does not exist"
+        );
+        let spaced = WindowsNotificationConfig {
+            app_id: Some("My Todo".to_string()),
+            ..WindowsNotificationConfig::default()
+        };
+        assert_eq!(
+            registration_plan(&spaced).unwrap_err().code(),
+            "invalid_input"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Sorting of task lists by a configurable sequence of [`SortKey`]s.
+
+use crate::config::SortKey;
+use crate::error::AppError;
+use crate::model::Task;
+use crate::task_api;
+use std::cmp::Ordering;
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+/// Stable sort of `tasks` by `keys`, each key breaking ties left by the ones before it. Tasks
+/// equal under every key keep their store order. [`SortKey::Overdue`] counts a task as overdue
+/// once it is `grace` past its deadline.
+pub fn sort_tasks(
+    tasks: Vec<Task>,
+    keys: &[SortKey],
+    focused_task_id: Option<&str>,
+    now: OffsetDateTime,
+    grace: Duration,
+) -> Result<Vec<Task>, AppError> {
+    let mut keyed = tasks
+        .into_iter()
+        .map(|task| {
+            let planned = timestamp(task.planned_at())?;
+            let due = timestamp(task.due_at.as_deref())?;
+            Ok(SortEntry { task, planned, due })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    keyed.sort_by(|left, right| {
+        keys.iter().fold(Ordering::Equal, |ordering, key| {
+            ordering.then_with(|| compare(*key, left, right, focused_task_id, now, grace))
+        })
+    });
+
+    Ok(keyed.into_iter().map(|entry| entry.task).collect())
+}
+
+fn timestamp(value: Option<&str>) -> Result<Option<OffsetDateTime>, AppError> {
+    value
+        .map(|value| {
+            OffsetDateTime::parse(value, &Rfc3339)
+                .map_err(|_| AppError::invalid_data("start_at and due_at must be RFC3339"))
+        })
+        .transpose()
+}
+
+struct SortEntry {
+    task: Task,
+    /// [`Task::planned_at`], what [`SortKey::Scheduled`] orders by.
+    planned: Option<OffsetDateTime>,
+    due: Option<OffsetDateTime>,
+}
+
+impl SortEntry {
+    fn overdue_since(&self, now: OffsetDateTime, grace: Duration) -> Option<OffsetDateTime> {
+        self.due
+            .filter(|due| self.task.status.is_open() && task_api::overdue_at(*due, now, grace))
+    }
+}
+
+fn compare(
+    key: SortKey,
+    left: &SortEntry,
+    right: &SortEntry,
+    focused_task_id: Option<&str>,
+    now: OffsetDateTime,
+    grace: Duration,
+) -> Ordering {
+    match key {
+        SortKey::Focus => {
+            let focused = |entry: &SortEntry| focused_task_id == Some(entry.task.id.as_str());
+            focused(right).cmp(&focused(left))
+        }
+        SortKey::Urgent => right.task.urgent.cmp(&left.task.urgent),
+        SortKey::Overdue => match (
+            left.overdue_since(now, grace),
+            right.overdue_since(now, grace),
+        ) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+        SortKey::Scheduled => match (left.planned, right.planned) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sort_tasks;
+    use crate::config::SortKey;
+    use crate::model::{Task, TaskStatus};
+    use time::macros::datetime;
+    use time::{Duration, OffsetDateTime};
+
+    fn task(id: &str, scheduled_at: Option<&str>, urgent: bool) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            due_at: scheduled_at.map(str::to_string),
+            urgent,
+            ..Default::default()
+        }
+    }
+
+    fn ids(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.id.as_str()).collect()
+    }
+
+    const NOW: OffsetDateTime = datetime!(2025-12-20 12:00 UTC);
+
+    #[test]
+    fn default_order_is_focus_urgent_overdue_scheduled() {
+        let tasks = vec![
+            task("later", Some("2025-12-20T18:00:00Z"), false),
+            task("overdue-recent", Some("2025-12-20T11:00:00Z"), false),
+            task("urgent", Some("2025-12-20T17:00:00Z"), true),
+            task("overdue-old", Some("2025-12-18T09:00:00Z"), false),
+            task("focused", Some("2025-12-20T20:00:00Z"), false),
+            task("soon", Some("2025-12-20T13:00:00Z"), false),
+        ];
+
+        let sorted = sort_tasks(
+            tasks,
+            &crate::config::OrderingConfig::default().today,
+            Some("focused"),
+            NOW,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ids(&sorted),
+            vec![
+                "focused",
+                "urgent",
+                "overdue-old",
+                "overdue-recent",
+                "soon",
+                "later"
+            ]
+        );
+    }
+
+    #[test]
+    fn completed_tasks_are_not_overdue() {
+        let mut done = task("done", Some("2025-12-18T09:00:00Z"), false);
+        done.status = TaskStatus::Completed;
+        let tasks = vec![done, task("pending", Some("2025-12-19T09:00:00Z"), false)];
+
+        let sorted = sort_tasks(tasks, &[SortKey::Overdue], None, NOW, Duration::ZERO).unwrap();
+
+        assert_eq!(ids(&sorted), vec!["pending", "done"]);
+    }
+
+    #[test]
+    fn empty_strategy_keeps_store_order() {
+        let tasks = vec![
+            task("b", Some("2025-12-20T18:00:00Z"), false),
+            task("a", Some("2025-12-20T08:00:00Z"), true),
+        ];
+
+        let sorted = sort_tasks(tasks, &[], Some("a"), NOW, Duration::ZERO).unwrap();
+
+        assert_eq!(ids(&sorted), vec!["b", "a"]);
+    }
+}
@@ -0,0 +1,28 @@
+//! Who is making a change, as `user@host`, for stamping shared stores.
+
+/// The login name and host name of this process, e.g. `ana@laptop`. Parts that cannot be
+/// determined read `unknown`.
+pub fn current() -> String {
+    format!("{}@{}", user_name(), host_name())
+}
+
+fn user_name() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|var| non_empty(std::env::var(var).ok()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn host_name() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|var| non_empty(std::env::var(var).ok()))
+        .or_else(|| non_empty(std::fs::read_to_string("/etc/hostname").ok()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
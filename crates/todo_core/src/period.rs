@@ -0,0 +1,183 @@
+//! Calendar spans for `list week` and `list quarter`: ISO 8601 weeks, Monday to Sunday, and
+//! quarters of the calendar year.
+
+use crate::error::AppError;
+use std::fmt;
+use time::{Date, Duration, Month, Weekday};
+
+/// Made only by the constructors below, so the week or quarter always exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period(Span);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Span {
+    /// ISO week `week` of ISO week-numbering year `year`, which may start in December or end
+    /// in January of the calendar year.
+    Week { year: i32, week: u8 },
+    /// Quarter 1 to 4 of a calendar year.
+    Quarter { year: i32, quarter: u8 },
+}
+
+impl Period {
+    pub fn week_of(date: Date) -> Self {
+        let (year, week, _) = date.to_iso_week_date();
+        Self(Span::Week { year, week })
+    }
+
+    pub fn quarter_of(date: Date) -> Self {
+        Self(Span::Quarter {
+            year: date.year(),
+            quarter: (u8::from(date.month()) - 1) / 3 + 1,
+        })
+    }
+
+    /// An ISO week such as `2026-W07`; the `W` may be lowercase and the zero left out.
+    pub fn parse_week(text: &str) -> Result<Self, AppError> {
+        let invalid = || {
+            AppError::invalid_input(format!(
+                "invalid week '{}' (expected YYYY-Www, such as 2026-W07)",
+                text.trim()
+            ))
+        };
+        let (year, week) = split(text, 'w').ok_or_else(invalid)?;
+        if week == 0 || week > time::util::weeks_in_year(year) {
+            return Err(AppError::invalid_input(format!(
+                "{year} has no week {week}; its ISO weeks run 1 to {}",
+                time::util::weeks_in_year(year)
+            )));
+        }
+        // The last week of 9999 ends in a year `Date` cannot hold.
+        let monday =
+            Date::from_iso_week_date(year, week, Weekday::Monday).map_err(|_| invalid())?;
+        if monday.checked_add(Duration::days(6)).is_none() {
+            return Err(AppError::invalid_input(format!(
+                "{year}-W{week:02} ends after {}, the last date supported",
+                Date::MAX
+            )));
+        }
+        Ok(Self(Span::Week { year, week }))
+    }
+
+    /// A quarter such as `2026-Q1`; the `Q` may be lowercase.
+    pub fn parse_quarter(text: &str) -> Result<Self, AppError> {
+        let invalid = || {
+            AppError::invalid_input(format!(
+                "invalid quarter '{}' (expected YYYY-Qn, such as 2026-Q1)",
+                text.trim()
+            ))
+        };
+        match split(text, 'q').ok_or_else(invalid)? {
+            (year, quarter @ 1..=4) => Ok(Self(Span::Quarter { year, quarter })),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn first_day(self) -> Date {
+        match self.0 {
+            Span::Week { year, week } => Date::from_iso_week_date(year, week, Weekday::Monday)
+                .expect("week checked when the period was made"),
+            Span::Quarter { year, quarter } => {
+                Date::from_calendar_date(year, first_month(quarter), 1)
+                    .expect("every quarter starts on the first of a month")
+            }
+        }
+    }
+
+    pub fn last_day(self) -> Date {
+        match self.0 {
+            Span::Week { year, week } => Date::from_iso_week_date(year, week, Weekday::Sunday)
+                .expect("week checked when the period was made"),
+            Span::Quarter { year, quarter } => {
+                let month = first_month(quarter).nth_next(2);
+                Date::from_calendar_date(year, month, month.length(year))
+                    .expect("the last day of a month exists")
+            }
+        }
+    }
+
+    pub fn contains(self, date: Date) -> bool {
+        (self.first_day()..=self.last_day()).contains(&date)
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Span::Week { year, week } => write!(f, "{year}-W{week:02}"),
+            Span::Quarter { year, quarter } => write!(f, "{year}-Q{quarter}"),
+        }
+    }
+}
+
+/// `YYYY-<marker>N` as the year and N.
+fn split(text: &str, marker: char) -> Option<(i32, u8)> {
+    let lowered = text.trim().to_lowercase();
+    let (year, number) = lowered.split_once('-')?;
+    let number = number.strip_prefix(marker)?;
+    if year.len() != 4 || !year.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    if number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some((year.parse().ok()?, number.parse().ok()?))
+}
+
+fn first_month(quarter: u8) -> Month {
+    Month::January.nth_next((quarter - 1) * 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Period;
+    use time::macros::date;
+
+    #[test]
+    fn weeks_follow_iso_numbering_across_year_ends() {
+        let week = Period::parse_week("2026-W07").unwrap();
+        let first = Period::parse_week("2026-w1").unwrap();
+
+        assert_eq!(week.first_day(), date!(2026 - 02 - 09));
+        assert_eq!(week.last_day(), date!(2026 - 02 - 15));
+        assert_eq!(first.first_day(), date!(2025 - 12 - 29));
+        assert_eq!(first.to_string(), "2026-W01");
+        assert_eq!(
+            Period::week_of(date!(2027 - 01 - 01)).to_string(),
+            "2026-W53"
+        );
+        assert!(week.contains(date!(2026 - 02 - 15)));
+        assert!(!week.contains(date!(2026 - 02 - 16)));
+        assert_eq!(
+            Period::parse_week("2025-W53").unwrap_err().message(),
+            "2025 has no week 53; its ISO weeks run 1 to 52"
+        );
+        assert_eq!(
+            Period::parse_week("9999-W52").unwrap_err().message(),
+            "9999-W52 ends after 9999-12-31, the last date supported"
+        );
+        assert_eq!(
+            Period::parse_week("9999-W51").unwrap().last_day(),
+            date!(9999 - 12 - 26)
+        );
+        assert!(Period::parse_week("2026-07").is_err());
+        assert!(Period::parse_week("26-W07").is_err());
+    }
+
+    #[test]
+    fn quarters_cover_three_calendar_months() {
+        let fourth = Period::parse_quarter("2026-q4").unwrap();
+
+        assert_eq!(fourth.first_day(), date!(2026 - 10 - 01));
+        assert_eq!(fourth.last_day(), date!(2026 - 12 - 31));
+        assert_eq!(
+            Period::quarter_of(date!(2024 - 02 - 29)).to_string(),
+            "2024-Q1"
+        );
+        assert_eq!(
+            Period::parse_quarter("2024-Q1").unwrap().last_day(),
+            date!(2024 - 03 - 31)
+        );
+        assert!(Period::parse_quarter("2026-Q5").is_err());
+        assert!(Period::parse_quarter("2026-Q0").is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Ownership and permission checks for the store, its sidecar files, and the config file.
+//!
+//! Tasks can hold private notes, and anyone who can write the store or the config can change
+//! what the CLI does, so these files should belong to the current user and be writable by nobody
+//! else. Writers already create most of them `0o600`; [`fix`] brings the rest in line. Modes
+//! and owners only exist on Unix, so elsewhere nothing is reported.
+
+use crate::error::AppError;
+use crate::storage::{
+    activity, archive, backup, index, journal, legacy, notify_ids, outbox, search, seeds, status,
+    sync_state, undo, webhook_queue,
+};
+use crate::usage;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionProblem {
+    /// Group or other users may write it; holds the permission bits.
+    Writable(u32),
+    /// Owned by this user id rather than the current user.
+    Owner(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionIssue {
+    pub path: PathBuf,
+    pub problem: PermissionProblem,
+}
+
+impl fmt::Display for PermissionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.problem {
+            PermissionProblem::Writable(mode) => write!(
+                f,
+                "{} is writable by other users (mode {mode:o})",
+                self.path.display()
+            ),
+            PermissionProblem::Owner(uid) => write!(
+                f,
+                "{} is owned by another user (uid {uid})",
+                self.path.display()
+            ),
+        }
+    }
+}
+
+/// How a guarded path should be locked down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Guard {
+    /// A file the CLI writes: `0o600`.
+    File,
+    /// A directory the CLI creates for its own files: `0o700`.
+    Dir,
+    /// The directory a store or config lives in. It may be shared, such as `/tmp`, so it only
+    /// loses write access for group and others, a sticky directory is fine, and root may own it.
+    Parent,
+}
+
+/// Problems with the store, the sidecars and backups that exist, the config file, and the
+/// directories holding them.
+pub fn check(store_path: &Path, config_path: &Path) -> Result<Vec<PermissionIssue>, AppError> {
+    let mut issues = Vec::new();
+    for (path, guard) in guarded(store_path, config_path)? {
+        issues.extend(
+            problems(&path, guard)?
+                .into_iter()
+                .map(|problem| PermissionIssue {
+                    path: path.clone(),
+                    problem,
+                }),
+        );
+    }
+    Ok(issues)
+}
+
+/// Chmod the files to `0o600`, the backup directory to `0o700`, and take write access for
+/// group and others off a parent directory that has it. Paths owned by someone else are left
+/// alone, since only their owner can change them. Returns the paths that changed.
+pub fn fix(store_path: &Path, config_path: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let mut fixed = Vec::new();
+    for (path, guard) in guarded(store_path, config_path)? {
+        if fix_path(&path, guard)? {
+            fixed.push(path);
+        }
+    }
+    Ok(fixed)
+}
+
+fn guarded(store_path: &Path, config_path: &Path) -> Result<Vec<(PathBuf, Guard)>, AppError> {
+    let mut paths = Vec::new();
+    for parent in [store_path.parent(), config_path.parent()]
+        .into_iter()
+        .flatten()
+    {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if !paths.iter().any(|(path, _)| path == parent) {
+            paths.push((parent.to_path_buf(), Guard::Parent));
+        }
+    }
+
+    paths.push((config_path.to_path_buf(), Guard::File));
+    for path in [
+        store_path.to_path_buf(),
+        index::index_path(store_path),
+        journal::journal_path(store_path),
+        legacy::original_path(store_path),
+        status::status_path(store_path),
+        undo::undo_path(store_path),
+        notify_ids::notify_ids_path(store_path),
+        archive::archive_path(store_path),
+        activity::activity_path(store_path),
+        activity::seen_path(store_path),
+        outbox::outbox_path(store_path),
+        webhook_queue::webhook_queue_path(store_path),
+        search::search_index_path(store_path),
+        seeds::seeds_path(store_path),
+        sync_state::sync_state_path(store_path),
+        usage::usage_log_path(store_path),
+    ] {
+        paths.push((path, Guard::File));
+    }
+
+    let backups = backup::backup_dir(store_path);
+    match std::fs::read_dir(&backups) {
+        Ok(entries) => {
+            for entry in entries {
+                let entry = entry.map_err(|err| AppError::io(err.to_string()))?;
+                paths.push((entry.path(), Guard::File));
+            }
+            paths.push((backups, Guard::Dir));
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(AppError::io(err.to_string())),
+    }
+
+    paths.retain(|(path, _)| path.exists());
+    Ok(paths)
+}
+
+#[cfg(unix)]
+fn problems(path: &Path, guard: Guard) -> Result<Vec<PermissionProblem>, AppError> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path).map_err(|err| AppError::io(err.to_string()))?;
+    let mode = metadata.mode() & 0o7777;
+    let mut problems = Vec::new();
+    let sticky = mode & 0o1000 != 0;
+    if mode & 0o022 != 0 && !(guard == Guard::Parent && sticky) {
+        problems.push(PermissionProblem::Writable(mode));
+    }
+    let uid = metadata.uid();
+    if uid != current_uid() && !(guard == Guard::Parent && uid == 0) {
+        problems.push(PermissionProblem::Owner(uid));
+    }
+    Ok(problems)
+}
+
+#[cfg(not(unix))]
+fn problems(_path: &Path, _guard: Guard) -> Result<Vec<PermissionProblem>, AppError> {
+    Ok(Vec::new())
+}
+
+#[cfg(unix)]
+fn fix_path(path: &Path, guard: Guard) -> Result<bool, AppError> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let metadata = std::fs::metadata(path).map_err(|err| AppError::io(err.to_string()))?;
+    if metadata.uid() != current_uid() {
+        return Ok(false);
+    }
+    let mode = metadata.mode() & 0o7777;
+    let wanted = match guard {
+        Guard::File => 0o600,
+        Guard::Dir => 0o700,
+        Guard::Parent if mode & 0o1000 != 0 => mode,
+        Guard::Parent => mode & !0o022,
+    };
+    if mode == wanted {
+        return Ok(false);
+    }
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(wanted))
+        .map_err(|err| AppError::io(err.to_string()))?;
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+fn fix_path(_path: &Path, _guard: Guard) -> Result<bool, AppError> {
+    Ok(false)
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    rustix::process::geteuid().as_raw()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{PermissionIssue, PermissionProblem, check, fix};
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("todoapp-{nanos}-{name}"));
+        std::fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    fn mode(path: &PathBuf) -> u32 {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o7777
+    }
+
+    #[test]
+    fn fix_locks_down_writable_files_and_directories() {
+        let dir = temp_dir("permissions");
+        let store = dir.join("tasks.json");
+        let journal = dir.join("tasks.json.journal");
+        let backups = dir.join("tasks.json.backups");
+        let backup = backups.join("before-cleanup.json");
+        let config = dir.join("config.json");
+        std::fs::create_dir(&backups).unwrap();
+        for (path, mode) in [
+            (&store, 0o666),
+            (&journal, 0o600),
+            (&backup, 0o644),
+            (&config, 0o620),
+        ] {
+            std::fs::write(path, "{}").unwrap();
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+        }
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+        let before = check(&store, &config).unwrap();
+        let fixed = fix(&store, &config).unwrap();
+        let after = check(&store, &config).unwrap();
+        let modes = [&dir, &store, &journal, &backups, &backup, &config].map(mode);
+        std::fs::remove_dir_all(&dir).ok();
+
+        let writable = |path: &PathBuf, mode| PermissionIssue {
+            path: path.clone(),
+            problem: PermissionProblem::Writable(mode),
+        };
+        assert_eq!(
+            before,
+            vec![
+                writable(&dir, 0o777),
+                writable(&config, 0o620),
+                writable(&store, 0o666),
+            ]
+        );
+        assert_eq!(fixed, vec![dir.clone(), config, store, backup, backups]);
+        assert!(after.is_empty());
+        assert_eq!(modes, [0o755, 0o600, 0o600, 0o700, 0o600, 0o600]);
+    }
+
+    #[test]
+    fn missing_files_and_sticky_shared_directories_are_fine() {
+        let dir = temp_dir("permissions-sticky");
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o1777)).unwrap();
+
+        let issues = check(&dir.join("tasks.json"), &dir.join("config.json")).unwrap();
+        let fixed = fix(&dir.join("tasks.json"), &dir.join("config.json")).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(issues.is_empty());
+        assert!(fixed.is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+//! Tokens for backends such as ticket providers, kept in the OS keyring instead of the config.
+//!
+//! Config values refer to a secret as `${secret:NAME}`. Each one is stored under the service
+//! [`SERVICE`] with its name as the account: the macOS Keychain, the Windows Credential
+//! Manager, or the Secret Service (GNOME Keyring, KWallet) elsewhere. Where no keyring is
+//! running, such as in CI, `TODOAPP_SECRET_<NAME>` in the environment takes its place; builds
+//! without the `keyring` feature only read those.
+
+use crate::error::AppError;
+
+pub const SERVICE: &str = "todoapp";
+const ENV_PREFIX: &str = "TODOAPP_SECRET_";
+const MAX_NAME_LEN: usize = 64;
+
+pub trait SecretStore {
+    fn get(&self, name: &str) -> Result<Option<String>, AppError>;
+    fn set(&self, name: &str, value: &str) -> Result<(), AppError>;
+    /// Returns `false` when there was nothing to delete.
+    fn delete(&self, name: &str) -> Result<bool, AppError>;
+}
+
+pub struct Keyring;
+
+#[cfg(feature = "keyring")]
+impl SecretStore for Keyring {
+    fn get(&self, name: &str) -> Result<Option<String>, AppError> {
+        match entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(keyring_error(err)),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), AppError> {
+        entry(name)?.set_password(value).map_err(keyring_error)
+    }
+
+    fn delete(&self, name: &str) -> Result<bool, AppError> {
+        match entry(name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(err) => Err(keyring_error(err)),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn entry(name: &str) -> Result<keyring::Entry, AppError> {
+    keyring::Entry::new(SERVICE, name).map_err(keyring_error)
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_error(err: keyring::Error) -> AppError {
+    AppError::io(format!("OS keyring: {err}"))
+}
+
+#[cfg(not(feature = "keyring"))]
+impl SecretStore for Keyring {
+    fn get(&self, _name: &str) -> Result<Option<String>, AppError> {
+        Err(no_keyring())
+    }
+
+    fn set(&self, _name: &str, _value: &str) -> Result<(), AppError> {
+        Err(no_keyring())
+    }
+
+    fn delete(&self, _name: &str) -> Result<bool, AppError> {
+        Err(no_keyring())
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn no_keyring() -> AppError {
+    AppError::io("this build leaves out the OS keyring (the `keyring` feature)")
+}
+
+/// Secret names are lowercase letters, digits, and `_`, such as `github_token`, so each one
+/// also works as part of an environment variable name.
+pub fn validate_name(name: &str) -> Result<(), AppError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::invalid_input(format!(
+            "secret names use lowercase letters, digits, and '_' (at most {MAX_NAME_LEN}): '{name}'"
+        )))
+    }
+}
+
+/// The environment variable that overrides the keyring for `name`.
+pub fn env_var(name: &str) -> String {
+    format!("{ENV_PREFIX}{}", name.to_ascii_uppercase())
+}
+
+/// The secret's value from the environment, or else from `store`.
+pub fn resolve(store: &dyn SecretStore, name: &str) -> Result<String, AppError> {
+    validate_name(name)?;
+    if let Ok(value) = std::env::var(env_var(name))
+        && !value.is_empty()
+    {
+        return Ok(value);
+    }
+    let stored = store
+        .get(name)
+        .map_err(|err| AppError::io(format!("{} (or set {})", err.message(), env_var(name))))?;
+    stored.ok_or_else(|| {
+        AppError::invalid_input(format!(
+            "secret {name} is not set; run `todo secret set {name}` or set {}",
+            env_var(name)
+        ))
+    })
+}
+
+/// Replaces `${NAME}` in a config value with the environment variable and `${secret:NAME}`
+/// with the secret.
+pub fn expand(value: &str, store: &dyn SecretStore) -> Result<String, AppError> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| AppError::invalid_input(format!("unterminated '${{' in '{value}'")))?;
+        let name = &after[..end];
+        let variable = match name.strip_prefix("secret:") {
+            Some(secret) => resolve(store, secret)?,
+            None => std::env::var(name).map_err(|_| {
+                AppError::invalid_input(format!("environment variable {name} is not set"))
+            })?,
+        };
+        expanded.push_str(&variable);
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SecretStore, env_var, expand, resolve, validate_name};
+    use crate::error::AppError;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStore(RefCell<HashMap<String, String>>);
+
+    impl SecretStore for MemoryStore {
+        fn get(&self, name: &str) -> Result<Option<String>, AppError> {
+            Ok(self.0.borrow().get(name).cloned())
+        }
+
+        fn set(&self, name: &str, value: &str) -> Result<(), AppError> {
+            self.0
+                .borrow_mut()
+                .insert(name.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, name: &str) -> Result<bool, AppError> {
+            Ok(self.0.borrow_mut().remove(name).is_some())
+        }
+    }
+
+    #[test]
+    fn names_are_lowercase_identifiers() {
+        assert!(validate_name("github_token").is_ok());
+        assert!(validate_name("jira2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("GitHub").is_err());
+        assert!(validate_name("a-b").is_err());
+        assert!(validate_name(&"x".repeat(65)).is_err());
+        assert_eq!(env_var("github_token"), "TODOAPP_SECRET_GITHUB_TOKEN");
+    }
+
+    #[test]
+    fn resolve_reads_the_store_and_names_the_fixes_when_missing() {
+        let store = MemoryStore::default();
+        store.set("todoapp_test_stored", "from-keyring").unwrap();
+
+        let stored = resolve(&store, "todoapp_test_stored").unwrap();
+        let missing = resolve(&store, "todoapp_test_missing").unwrap_err();
+        let deleted = store.delete("todoapp_test_stored").unwrap();
+        let deleted_again = store.delete("todoapp_test_stored").unwrap();
+
+        assert_eq!(stored, "from-keyring");
+        assert_eq!(missing.code(), "invalid_input");
+        assert!(
+            missing
+                .message()
+                .contains("todo secret set todoapp_test_missing")
+        );
+        assert!(
+            missing
+                .message()
+                .contains("TODOAPP_SECRET_TODOAPP_TEST_MISSING")
+        );
+        assert!(deleted);
+        assert!(!deleted_again);
+    }
+
+    #[test]
+    fn expand_substitutes_variables_and_secrets() {
+        let store = MemoryStore::default();
+        store.set("ops_token", "s3cret").unwrap();
+
+        assert_eq!(
+            expand("Bearer ${PATH}", &store).unwrap(),
+            format!("Bearer {}", std::env::var("PATH").unwrap())
+        );
+        assert_eq!(expand("plain", &store).unwrap(), "plain");
+        assert!(expand("${TODOAPP_SURELY_UNSET_VARIABLE}", &store).is_err());
+        assert!(expand("${OPEN", &store).is_err());
+        assert_eq!(
+            expand("https://hooks.example.com/${secret:ops_token}", &store).unwrap(),
+            "https://hooks.example.com/s3cret"
+        );
+        let missing = expand("${secret:jira_token}", &store).unwrap_err();
+        assert!(missing.message().contains("todo secret set jira_token"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Rules for `seed`: a task added on a fixed cadence, such as
+//! `every monday 9am: Weekly report`.
+//!
+//! A lighter alternative to recurring tasks. Each time a rule falls due, `seed run` adds one
+//! ordinary task planned for that time; nothing ties the tasks to each other afterwards.
+
+use crate::error::AppError;
+use std::fmt;
+use time::{Date, Duration, OffsetDateTime, Time, Weekday};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedRule {
+    days: Days,
+    at: Time,
+    title: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Days {
+    Every,
+    /// Monday to Friday.
+    Weekdays,
+    On(Weekday),
+}
+
+impl SeedRule {
+    /// `every DAYS [at] [TIME]: TITLE`, ignoring case. DAYS is `day`, `weekday` (Monday to
+    /// Friday), or a day of the week such as `monday` or `mon`. TIME is `9am`, `9:30pm`, or
+    /// `17:30`, and midnight when left out.
+    ///
+    /// ```
+    /// use todo_core::seed::SeedRule;
+    ///
+    /// let rule = SeedRule::parse("Every Monday 9am: Weekly report")?;
+    /// assert_eq!(rule.title(), "Weekly report");
+    /// assert_eq!(rule.to_string(), "every monday 09:00: Weekly report");
+    /// # Ok::<(), todo_core::error::AppError>(())
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, AppError> {
+        let invalid = || {
+            AppError::invalid_input(format!(
+                "invalid rule '{}' (expected e.g. \"every monday 9am: Weekly report\")",
+                text.trim()
+            ))
+        };
+        // The first colon not followed by a digit, so one inside a time such as 9:30 is kept.
+        let split = text
+            .char_indices()
+            .find(|&(index, ch)| {
+                ch == ':' && !text[index + 1..].starts_with(|next: char| next.is_ascii_digit())
+            })
+            .map(|(index, _)| index)
+            .ok_or_else(invalid)?;
+        let (when, title) = (&text[..split], &text[split + 1..]);
+        let title = crate::text::normalize(title);
+        if title.is_empty() {
+            return Err(AppError::invalid_input("rule needs a title after the ':'"));
+        }
+
+        let lowered = when.to_lowercase();
+        let mut words = lowered.split_whitespace();
+        if words.next() != Some("every") {
+            return Err(invalid());
+        }
+        let days = match words.next().ok_or_else(invalid)? {
+            "day" => Days::Every,
+            "weekday" => Days::Weekdays,
+            name => Days::On(weekday(name).ok_or_else(invalid)?),
+        };
+        let mut time_words: Vec<&str> = words.collect();
+        if time_words.first() == Some(&"at") {
+            time_words.remove(0);
+        }
+        let at = match time_words.as_slice() {
+            [] => Time::MIDNIGHT,
+            [time] => time_of_day(time).ok_or_else(invalid)?,
+            [time, meridiem @ ("am" | "pm")] => {
+                time_of_day(&format!("{time}{meridiem}")).ok_or_else(invalid)?
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(Self { days, at, title })
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The latest time at or before `now` the rule falls due, in `now`'s offset.
+    pub fn latest_due(&self, now: OffsetDateTime) -> OffsetDateTime {
+        let mut date = now.date();
+        loop {
+            let due = date.with_time(self.at).assume_offset(now.offset());
+            if due <= now && self.falls_on(date) {
+                return due;
+            }
+            date -= Duration::days(1);
+        }
+    }
+
+    fn falls_on(&self, date: Date) -> bool {
+        match self.days {
+            Days::Every => true,
+            Days::Weekdays => !matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday),
+            Days::On(day) => date.weekday() == day,
+        }
+    }
+}
+
+impl fmt::Display for SeedRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = match self.days {
+            Days::Every => "day".to_string(),
+            Days::Weekdays => "weekday".to_string(),
+            Days::On(day) => day.to_string().to_lowercase(),
+        };
+        write!(
+            f,
+            "every {days} {:02}:{:02}: {}",
+            self.at.hour(),
+            self.at.minute(),
+            self.title
+        )
+    }
+}
+
+/// A day of the week by its English name or first three letters.
+fn weekday(name: &str) -> Option<Weekday> {
+    WEEKDAYS.into_iter().find(|day| {
+        let full = day.to_string().to_lowercase();
+        name == full || name == &full[..3]
+    })
+}
+
+/// `9am`, `12:15pm`, or `17:30`.
+fn time_of_day(text: &str) -> Option<Time> {
+    let (clock, meridiem) = match text.strip_suffix("am") {
+        Some(clock) => (clock, Some(0)),
+        None => match text.strip_suffix("pm") {
+            Some(clock) => (clock, Some(12)),
+            None => (text, None),
+        },
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse().ok()?),
+        Some(_) => return None,
+        None if meridiem.is_some() => (clock, 0),
+        None => return None,
+    };
+    let mut hour: u8 = hour.parse().ok()?;
+    if let Some(offset) = meridiem {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+    Time::from_hms(hour, minute, 0).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeedRule;
+    use time::macros::datetime;
+
+    #[test]
+    fn parse_reads_days_times_and_titles() {
+        let cases = [
+            (
+                "every monday 9am: Weekly report",
+                "every monday 09:00: Weekly report",
+            ),
+            (
+                "EVERY Fri at 5:30pm: Timesheet",
+                "every friday 17:30: Timesheet",
+            ),
+            (
+                "every weekday 08:15: Stand-up: notes",
+                "every weekday 08:15: Stand-up: notes",
+            ),
+            ("every day: Water plants", "every day 00:00: Water plants"),
+            ("every day 12 am: Backup", "every day 00:00: Backup"),
+        ];
+        for (text, canonical) in cases {
+            assert_eq!(
+                SeedRule::parse(text).unwrap().to_string(),
+                canonical,
+                "{text}"
+            );
+        }
+
+        for bad in [
+            "monday 9am: Report",
+            "every someday: Report",
+            "every monday 25:00: Report",
+            "every monday 13pm: Report",
+            "every monday 9",
+            "every monday 9am:  ",
+        ] {
+            assert_eq!(
+                SeedRule::parse(bad).unwrap_err().code(),
+                "invalid_input",
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn latest_due_looks_back_to_the_last_matching_day() {
+        let weekly = SeedRule::parse("every monday 9am: Weekly report").unwrap();
+        let weekdays = SeedRule::parse("every weekday 9am: Stand-up").unwrap();
+
+        // 2025-12-22 is a Monday.
+        assert_eq!(
+            weekly.latest_due(datetime!(2025-12-22 09:00 UTC)),
+            datetime!(2025-12-22 09:00 UTC)
+        );
+        assert_eq!(
+            weekly.latest_due(datetime!(2025-12-22 08:59 UTC)),
+            datetime!(2025-12-15 09:00 UTC)
+        );
+        assert_eq!(
+            weekdays.latest_due(datetime!(2025-12-21 12:00 +1)),
+            datetime!(2025-12-19 09:00 +1)
+        );
+    }
+}
@@ -0,0 +1,590 @@
+//! Aggregation helpers over tasks and the usage log.
+
+use crate::duration;
+use crate::error::AppError;
+use crate::model::{Task, TaskStatus};
+use crate::period::Period;
+use crate::task_api;
+use crate::usage::UsageEntry;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime, Time, UtcOffset};
+
+/// Buckets items under every key `keys` yields for them, in key order. Items keep their input
+/// order within a bucket; an item with several keys lands in several buckets.
+pub fn group_by<T, K, I, F>(items: impl IntoIterator<Item = T>, keys: F) -> Vec<(K, Vec<T>)>
+where
+    T: Clone,
+    K: Ord,
+    I: IntoIterator<Item = K>,
+    F: Fn(&T) -> I,
+{
+    let mut groups: BTreeMap<K, Vec<T>> = BTreeMap::new();
+    for item in items {
+        for key in keys(&item) {
+            groups.entry(key).or_default().push(item.clone());
+        }
+    }
+    groups.into_iter().collect()
+}
+
+/// Counts items by key, most frequent first; ties keep key order.
+pub fn count_by<T, K, F>(items: impl IntoIterator<Item = T>, key: F) -> Vec<(K, usize)>
+where
+    T: Clone,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let mut counted: Vec<(K, usize)> = group_by(items, |item| [key(item)])
+        .into_iter()
+        .map(|(key, items)| (key, items.len()))
+        .collect();
+    counted.sort_by_key(|(_, count)| Reverse(*count));
+    counted
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One group per tag; a task with several tags appears in each.
+    Tag,
+    Project,
+    /// `overdue`, `in_progress`, `pending`, `waiting`, `completed`, then `cancelled`.
+    Status,
+    /// Local scheduled date, earliest first.
+    Day,
+    /// `morning`, `afternoon`, then `evening` by local planned time of day, whatever the date.
+    Section(DaySections),
+}
+
+/// Where the afternoon and the evening start, in local time; the morning is everything before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaySections {
+    pub afternoon: Time,
+    pub evening: Time,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskGroup {
+    /// `None` holds the tasks without a tag, project, or schedule; it always comes last.
+    pub key: Option<String>,
+    pub tasks: Vec<Task>,
+}
+
+/// Splits `tasks` into groups, keeping their order within each group. By status, an open task
+/// is overdue once it is `grace` past its deadline.
+pub fn group_tasks(
+    tasks: &[Task],
+    by: GroupBy,
+    now: OffsetDateTime,
+    grace: Duration,
+) -> Result<Vec<TaskGroup>, AppError> {
+    let keyed = tasks
+        .iter()
+        .map(|task| Ok((group_keys(task, by, now, grace)?, task)))
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok(group_by(keyed, |(keys, _)| keys.clone())
+        .into_iter()
+        .map(|((_, key), members)| TaskGroup {
+            key,
+            tasks: members.into_iter().map(|(_, task)| task.clone()).collect(),
+        })
+        .collect())
+}
+
+/// Keys are `(rank, name)` so that named groups sort before the `None` group, and status
+/// groups follow a fixed order rather than an alphabetical one.
+fn group_keys(
+    task: &Task,
+    by: GroupBy,
+    now: OffsetDateTime,
+    grace: Duration,
+) -> Result<Vec<(u8, Option<String>)>, AppError> {
+    let timestamp = |value: Option<&str>| {
+        value
+            .map(|value| {
+                OffsetDateTime::parse(value, &Rfc3339)
+                    .map_err(|_| AppError::invalid_data("start_at and due_at must be RFC3339"))
+            })
+            .transpose()
+    };
+    let due = timestamp(task.due_at.as_deref())?;
+    let planned = timestamp(task.planned_at())?;
+
+    let keys = match by {
+        GroupBy::Tag if task.tags.is_empty() => vec![(1, None)],
+        GroupBy::Tag => task.tags.iter().map(|tag| (0, Some(tag.clone()))).collect(),
+        GroupBy::Project => match task.project.as_ref() {
+            Some(project) => vec![(0, Some(project.clone()))],
+            None => vec![(1, None)],
+        },
+        GroupBy::Status => vec![match task.status {
+            status
+                if status.is_open()
+                    && due.is_some_and(|at| task_api::overdue_at(at, now, grace)) =>
+            {
+                (0, Some("overdue".to_string()))
+            }
+            TaskStatus::InProgress => (1, Some("in_progress".to_string())),
+            TaskStatus::Pending => (2, Some("pending".to_string())),
+            TaskStatus::Waiting => (3, Some("waiting".to_string())),
+            TaskStatus::Completed => (4, Some("completed".to_string())),
+            TaskStatus::Cancelled => (5, Some("cancelled".to_string())),
+        }],
+        GroupBy::Day => match planned {
+            Some(at) => vec![(0, Some(at.to_offset(now.offset()).date().to_string()))],
+            None => vec![(1, None)],
+        },
+        GroupBy::Section(sections) => match planned {
+            Some(at) => {
+                let time = at.to_offset(now.offset()).time();
+                vec![if time < sections.afternoon {
+                    (0, Some("morning".to_string()))
+                } else if time < sections.evening {
+                    (1, Some("afternoon".to_string()))
+                } else {
+                    (2, Some("evening".to_string()))
+                }]
+            }
+            None => vec![(3, None)],
+        },
+    };
+    Ok(keys)
+}
+
+/// Number of completions per local hour of day, counting every completion of a task (a task
+/// may have been completed, reopened, and completed again).
+pub fn completions_by_hour(tasks: &[Task], local_offset: UtcOffset) -> [usize; 24] {
+    let mut hours = [0; 24];
+    for at in tasks.iter().flat_map(completion_times) {
+        hours[usize::from(at.to_offset(local_offset).hour())] += 1;
+    }
+    hours
+}
+
+/// Number of completions on a local day of `period`, counting each completion of a task as
+/// [`completions_by_hour`] does.
+pub fn completions_in(tasks: &[Task], period: Period, local_offset: UtcOffset) -> usize {
+    tasks
+        .iter()
+        .flat_map(completion_times)
+        .filter(|at| period.contains(at.to_offset(local_offset).date()))
+        .count()
+}
+
+/// Every time `task` was completed, once each; unreadable stamps are skipped.
+fn completion_times(task: &Task) -> Vec<OffsetDateTime> {
+    let mut seen = HashSet::new();
+    task.completion_history
+        .iter()
+        .map(|entry| entry.completed_at.as_str())
+        .chain(task.completed_at.as_deref())
+        .filter(|stamp| seen.insert(*stamp))
+        .filter_map(|stamp| OffsetDateTime::parse(stamp, &Rfc3339).ok())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insights {
+    pub total_runs: usize,
+    /// Command paths with their run counts, most used first.
+    pub top_commands: Vec<(String, usize)>,
+    pub completions_by_hour: [usize; 24],
+    /// Total `reschedule` runs (snoozes).
+    pub reschedules: usize,
+    /// Distinct tasks rescheduled at least once.
+    pub rescheduled_tasks: usize,
+    /// Completions so far in the current ISO week, local time.
+    pub completions_this_week: usize,
+}
+
+impl Insights {
+    /// Average number of reschedules per task that was rescheduled at all.
+    pub fn average_reschedules(&self) -> Option<f64> {
+        (self.rescheduled_tasks > 0)
+            .then(|| self.reschedules as f64 / self.rescheduled_tasks as f64)
+    }
+
+    /// Up to `limit` hours with at least one completion, busiest first.
+    pub fn busiest_hours(&self, limit: usize) -> Vec<(u8, usize)> {
+        let mut hours: Vec<(u8, usize)> = (0u8..24)
+            .map(|hour| (hour, self.completions_by_hour[usize::from(hour)]))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        hours.sort_by_key(|(_, count)| Reverse(*count));
+        hours.truncate(limit);
+        hours
+    }
+}
+
+/// `now` is in local time; its offset decides which hour and week a completion falls in.
+pub fn insights(tasks: &[Task], usage: &[UsageEntry], now: OffsetDateTime) -> Insights {
+    let reschedule_ids: Vec<&str> = usage
+        .iter()
+        .filter(|entry| entry.command == "reschedule")
+        .filter_map(|entry| entry.task_id.as_deref())
+        .collect();
+
+    Insights {
+        total_runs: usage.len(),
+        top_commands: count_by(usage, |entry| entry.command.clone()),
+        completions_by_hour: completions_by_hour(tasks, now.offset()),
+        reschedules: reschedule_ids.len(),
+        rescheduled_tasks: reschedule_ids.iter().collect::<HashSet<_>>().len(),
+        completions_this_week: completions_in(tasks, Period::week_of(now.date()), now.offset()),
+    }
+}
+
+/// Metadata key holding a task's estimate, written the way [`duration::parse`] reads it.
+pub const ESTIMATE_KEY: &str = "estimate";
+
+/// Estimated against tracked time for the completed tasks under one tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagAccuracy {
+    /// `None` for tasks without tags.
+    pub tag: Option<String>,
+    pub tasks: usize,
+    pub estimated: Duration,
+    /// Sum of the tasks' finished work sessions.
+    pub tracked: Duration,
+}
+
+impl TagAccuracy {
+    /// Tracked over estimated time: above 1 means the work ran over its estimate.
+    pub fn ratio(&self) -> Option<f64> {
+        self.estimated
+            .is_positive()
+            .then(|| self.tracked.as_seconds_f64() / self.estimated.as_seconds_f64())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccuracyReport {
+    /// One entry per tag in name order, then the untagged tasks. A task with several tags
+    /// counts under each.
+    pub tags: Vec<TagAccuracy>,
+    /// Completed tasks left out for lacking a readable estimate or a finished session.
+    pub skipped: usize,
+}
+
+/// Compares each completed task's [`ESTIMATE_KEY`] with the time its work sessions took.
+pub fn estimate_accuracy(tasks: &[Task]) -> AccuracyReport {
+    let mut by_tag: BTreeMap<Option<&str>, TagAccuracy> = BTreeMap::new();
+    let mut skipped = 0;
+    for task in tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Completed)
+    {
+        let estimated = task
+            .metadata
+            .get(ESTIMATE_KEY)
+            .and_then(|value| duration::parse(value).ok());
+        let tracked = tracked_time(task);
+        let (Some(estimated), Some(tracked)) = (estimated, tracked) else {
+            skipped += 1;
+            continue;
+        };
+        let tags: Vec<Option<&str>> = if task.tags.is_empty() {
+            vec![None]
+        } else {
+            task.tags.iter().map(|tag| Some(tag.as_str())).collect()
+        };
+        for tag in tags {
+            let entry = by_tag.entry(tag).or_insert_with(|| TagAccuracy {
+                tag: tag.map(str::to_string),
+                tasks: 0,
+                estimated: Duration::ZERO,
+                tracked: Duration::ZERO,
+            });
+            entry.tasks += 1;
+            entry.estimated += estimated;
+            entry.tracked += tracked;
+        }
+    }
+
+    // `None` sorts first in the map; untagged tasks read better after the tags.
+    let untagged = by_tag.remove(&None);
+    AccuracyReport {
+        tags: by_tag.into_values().chain(untagged).collect(),
+        skipped,
+    }
+}
+
+/// Total length of the task's finished sessions, or `None` when it has none; a session still
+/// open or with an unreadable stamp is left out.
+fn tracked_time(task: &Task) -> Option<Duration> {
+    let lengths: Vec<Duration> = task
+        .sessions
+        .iter()
+        .filter_map(|session| {
+            let started = OffsetDateTime::parse(&session.started_at, &Rfc3339).ok()?;
+            let ended = OffsetDateTime::parse(session.ended_at.as_deref()?, &Rfc3339).ok()?;
+            Some(ended - started)
+        })
+        .collect();
+    (!lengths.is_empty()).then(|| lengths.into_iter().sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DaySections, GroupBy, completions_by_hour, completions_in, count_by, estimate_accuracy,
+        group_tasks, insights,
+    };
+    use crate::model::{CompletionEntry, Task, TaskStatus, WorkSession};
+    use crate::period::Period;
+    use crate::usage::UsageEntry;
+    use time::format_description::well_known::Rfc3339;
+    use time::macros::{date, datetime, time};
+    use time::{Duration, UtcOffset};
+
+    fn usage(command: &str, task_id: Option<&str>) -> UsageEntry {
+        UsageEntry {
+            at: "2025-12-20T09:00:00Z".to_string(),
+            command: command.to_string(),
+            task_id: task_id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn count_by_orders_by_frequency_then_key() {
+        let counted = count_by(["b", "a", "b", "c", "a", "b"], |item| item.to_string());
+
+        assert_eq!(
+            counted,
+            vec![
+                ("b".to_string(), 3),
+                ("a".to_string(), 2),
+                ("c".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn completion_counts_use_local_offset_and_history() {
+        let task = Task {
+            id: "task-1".to_string(),
+            title: "demo".to_string(),
+            status: TaskStatus::Completed,
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            completed_at: Some("2025-12-03T09:30:00Z".to_string()),
+            completion_history: vec![
+                CompletionEntry {
+                    message: "first".to_string(),
+                    completed_at: "2025-12-02T22:15:00Z".to_string(),
+                },
+                CompletionEntry {
+                    message: "again".to_string(),
+                    completed_at: "2025-12-03T09:30:00Z".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+
+        let hours = completions_by_hour(std::slice::from_ref(&task), offset);
+        let week =
+            |date| completions_in(std::slice::from_ref(&task), Period::week_of(date), offset);
+
+        assert_eq!(hours[0], 1);
+        assert_eq!(hours[11], 1);
+        assert_eq!(hours.iter().sum::<usize>(), 2);
+        assert_eq!(week(date!(2025 - 12 - 07)), 2);
+        assert_eq!(week(date!(2025 - 12 - 08)), 0);
+    }
+
+    #[test]
+    fn insights_counts_commands_and_snoozes() {
+        let log = vec![
+            usage("list today", None),
+            usage("reschedule", Some("1")),
+            usage("reschedule", Some("1")),
+            usage("reschedule", Some("2")),
+            usage("list today", None),
+            usage("done", Some("1")),
+        ];
+
+        let result = insights(&[], &log, datetime!(2025-12-20 12:00 UTC));
+
+        assert_eq!(result.total_runs, 6);
+        assert_eq!(result.top_commands[0], ("reschedule".to_string(), 3));
+        assert_eq!(result.top_commands[1], ("list today".to_string(), 2));
+        assert_eq!(result.reschedules, 3);
+        assert_eq!(result.rescheduled_tasks, 2);
+        assert_eq!(result.average_reschedules(), Some(1.5));
+        assert!(result.busiest_hours(3).is_empty());
+    }
+
+    fn grouped(id: &str, tags: &[&str], project: Option<&str>, scheduled_at: Option<&str>) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            due_at: scheduled_at.map(str::to_string),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            project: project.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn summary(groups: &[super::TaskGroup]) -> Vec<(Option<&str>, Vec<&str>)> {
+        groups
+            .iter()
+            .map(|group| {
+                (
+                    group.key.as_deref(),
+                    group.tasks.iter().map(|task| task.id.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn group_tasks_by_tag_repeats_multi_tag_tasks_and_puts_untagged_last() {
+        let tasks = vec![
+            grouped("1", &["work", "errands"], None, None),
+            grouped("2", &[], None, None),
+            grouped("3", &["work"], None, None),
+        ];
+
+        let groups = group_tasks(
+            &tasks,
+            GroupBy::Tag,
+            datetime!(2025-12-20 12:00 UTC),
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary(&groups),
+            vec![
+                (Some("errands"), vec!["1"]),
+                (Some("work"), vec!["1", "3"]),
+                (None, vec!["2"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn group_tasks_by_status_and_day_use_fixed_and_local_order() {
+        let mut done = grouped("done", &[], Some("home"), Some("2025-12-19T23:30:00Z"));
+        done.status = TaskStatus::Completed;
+        let tasks = vec![
+            done,
+            grouped("later", &[], None, Some("2025-12-21T09:00:00Z")),
+            grouped("late", &[], Some("home"), Some("2025-12-20T09:00:00Z")),
+        ];
+        let now = datetime!(2025-12-20 12:00 +1);
+
+        let by_status = group_tasks(&tasks, GroupBy::Status, now, Duration::ZERO).unwrap();
+        let by_day = group_tasks(&tasks, GroupBy::Day, now, Duration::ZERO).unwrap();
+        let by_project = group_tasks(&tasks, GroupBy::Project, now, Duration::ZERO).unwrap();
+
+        assert_eq!(
+            summary(&by_status),
+            vec![
+                (Some("overdue"), vec!["late"]),
+                (Some("pending"), vec!["later"]),
+                (Some("completed"), vec!["done"]),
+            ]
+        );
+        assert_eq!(
+            summary(&by_day),
+            vec![
+                (Some("2025-12-20"), vec!["done", "late"]),
+                (Some("2025-12-21"), vec!["later"]),
+            ]
+        );
+        assert_eq!(
+            summary(&by_project),
+            vec![(Some("home"), vec!["done", "late"]), (None, vec!["later"])]
+        );
+    }
+
+    #[test]
+    fn group_tasks_by_section_uses_the_local_time_of_day() {
+        let tasks = vec![
+            grouped("dinner", &[], None, Some("2025-12-20T17:00:00Z")),
+            grouped("someday", &[], None, None),
+            grouped("stand-up", &[], None, Some("2025-12-20T08:30:00Z")),
+            grouped("lunch", &[], None, Some("2025-12-19T11:00:00Z")),
+        ];
+        let sections = DaySections {
+            afternoon: time!(12:00),
+            evening: time!(18:00),
+        };
+
+        let groups = group_tasks(
+            &tasks,
+            GroupBy::Section(sections),
+            datetime!(2025-12-20 12:00 +1),
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary(&groups),
+            vec![
+                (Some("morning"), vec!["stand-up"]),
+                (Some("afternoon"), vec!["lunch"]),
+                (Some("evening"), vec!["dinner"]),
+                (None, vec!["someday"]),
+            ]
+        );
+    }
+
+    fn worked(id: &str, tags: &[&str], estimate: Option<&str>, minutes: &[i64]) -> Task {
+        let mut task = grouped(id, tags, None, None);
+        task.status = TaskStatus::Completed;
+        if let Some(estimate) = estimate {
+            task.metadata
+                .insert(super::ESTIMATE_KEY.to_string(), estimate.to_string());
+        }
+        let start = datetime!(2025-12-20 09:00 UTC);
+        task.sessions = minutes
+            .iter()
+            .map(|minutes| WorkSession {
+                started_at: start.format(&Rfc3339).unwrap(),
+                ended_at: Some(
+                    (start + Duration::minutes(*minutes))
+                        .format(&Rfc3339)
+                        .unwrap(),
+                ),
+            })
+            .collect();
+        task
+    }
+
+    #[test]
+    fn estimate_accuracy_sums_sessions_per_tag_and_skips_unmeasured_tasks() {
+        let mut open = worked("open", &["work"], Some("1h"), &[30]);
+        open.status = TaskStatus::InProgress;
+        let tasks = vec![
+            worked("1", &["work", "home"], Some("1h"), &[45, 45]),
+            worked("2", &["work"], Some("1h30m"), &[60]),
+            worked("3", &[], Some("30m"), &[30]),
+            worked("no-estimate", &["work"], None, &[30]),
+            worked("no-session", &["work"], Some("1h"), &[]),
+            open,
+        ];
+
+        let report = estimate_accuracy(&tasks);
+
+        let rows: Vec<(Option<&str>, usize, Option<f64>)> = report
+            .tags
+            .iter()
+            .map(|row| (row.tag.as_deref(), row.tasks, row.ratio()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (Some("home"), 1, Some(1.5)),
+                (Some("work"), 2, Some(1.0)),
+                (None, 1, Some(1.0)),
+            ]
+        );
+        assert_eq!(report.tags[1].tracked, Duration::minutes(150));
+        assert_eq!(report.skipped, 2);
+    }
+}
@@ -0,0 +1,296 @@
+//! Who changed which task, kept in `<store>.activity.jsonl`.
+//!
+//! Every commit appends one stamped line per added, changed, or removed task, whether the store
+//! was rewritten or journaled, so people sharing a store can see what others did since they last
+//! looked. Focus changes are personal and are not recorded. Only the newest [`MAX_ENTRIES`] lines
+//! are kept. When each origin last looked is kept in `<store>.seen.json`.
+
+use crate::error::AppError;
+use crate::model::{Task, TaskStatus};
+use crate::storage::json_store::TaskState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+pub const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// When the change was committed (RFC3339, UTC).
+    pub at: String,
+    /// [`crate::origin::current`] of the process that made the change.
+    pub by: String,
+    pub task_id: String,
+    /// Title after the change, or before it for removals.
+    pub title: String,
+    pub change: ActivityChange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityChange {
+    Added,
+    Updated,
+    Completed,
+    Reopened,
+    Cancelled,
+    Removed,
+}
+
+pub fn activity_path(store_path: &Path) -> PathBuf {
+    sidecar_path(store_path, ".activity.jsonl")
+}
+
+pub fn seen_path(store_path: &Path) -> PathBuf {
+    sidecar_path(store_path, ".seen.json")
+}
+
+fn sidecar_path(store_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(suffix);
+    store_path.with_file_name(name)
+}
+
+/// Oldest first. Lines that cannot be parsed, such as a torn final line, are skipped.
+pub fn read(store_path: &Path) -> Result<Vec<ActivityEntry>, AppError> {
+    let content = match std::fs::read_to_string(activity_path(store_path)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The entries describing the transition from `before` to `after`, stamped with `by` and `at`.
+pub(crate) fn entries(
+    before: &TaskState,
+    after: &TaskState,
+    by: &str,
+    at: &str,
+) -> Vec<ActivityEntry> {
+    let before_by_id: HashMap<&str, &Task> = before
+        .tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task))
+        .collect();
+    let entry = |task: &Task, change| ActivityEntry {
+        at: at.to_string(),
+        by: by.to_string(),
+        task_id: task.id.clone(),
+        title: task.title.clone(),
+        change,
+    };
+
+    let mut entries = Vec::new();
+    for task in &after.tasks {
+        let change = match before_by_id.get(task.id.as_str()) {
+            None => ActivityChange::Added,
+            Some(previous) if *previous == task => continue,
+            Some(previous) => match (previous.status, task.status) {
+                (before, TaskStatus::Completed) if before.is_open() => ActivityChange::Completed,
+                (before, TaskStatus::Cancelled) if before.is_open() => ActivityChange::Cancelled,
+                (TaskStatus::Completed, after) if after.is_open() => ActivityChange::Reopened,
+                _ => ActivityChange::Updated,
+            },
+        };
+        entries.push(entry(task, change));
+    }
+    let after_ids: HashSet<&str> = after.tasks.iter().map(|task| task.id.as_str()).collect();
+    for task in &before.tasks {
+        if !after_ids.contains(task.id.as_str()) {
+            entries.push(entry(task, ActivityChange::Removed));
+        }
+    }
+    entries
+}
+
+pub(crate) fn record(
+    store_path: &Path,
+    before: &TaskState,
+    after: &TaskState,
+    by: &str,
+    at: &str,
+) -> Result<(), AppError> {
+    let new_entries = entries(before, after, by, at);
+    if new_entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut all = read(store_path)?;
+    all.extend(new_entries);
+    let start = all.len().saturating_sub(MAX_ENTRIES);
+    let mut content = String::new();
+    for entry in &all[start..] {
+        let line =
+            serde_json::to_string(entry).map_err(|err| AppError::invalid_data(err.to_string()))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    write_private(&activity_path(store_path), &content)
+}
+
+/// Entries made by anyone other than `origin` since `origin` last looked, then marks `now` as
+/// the time it last looked. The first look only sets the mark, so nobody is greeted with the
+/// whole history.
+pub fn unseen_changes(
+    store_path: &Path,
+    origin: &str,
+    now: OffsetDateTime,
+) -> Result<Vec<ActivityEntry>, AppError> {
+    let path = seen_path(store_path);
+    let mut seen: BTreeMap<String, String> = match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display())))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+
+    let mut unseen = Vec::new();
+    if let Some(last) = seen.get(origin) {
+        let last = parse_time(last)?;
+        for entry in read(store_path)? {
+            if entry.by != origin && parse_time(&entry.at)? > last {
+                unseen.push(entry);
+            }
+        }
+    }
+
+    let now = now
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    seen.insert(origin.to_string(), now);
+    let content = serde_json::to_string_pretty(&seen)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    write_private(&path, &content)?;
+    Ok(unseen)
+}
+
+fn parse_time(value: &str) -> Result<OffsetDateTime, AppError> {
+    OffsetDateTime::parse(value, &Rfc3339)
+        .map_err(|_| AppError::invalid_data("activity timestamps must be RFC3339"))
+}
+
+/// Atomic replace with owner-only permissions.
+pub(crate) fn write_private(path: &Path, content: &str) -> Result<(), AppError> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    std::fs::write(&temp_path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&temp_path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    std::fs::rename(&temp_path, path).map_err(|err| AppError::io(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActivityChange, ActivityEntry, activity_path, entries, seen_path, unseen_changes};
+    use crate::model::{Task, TaskStatus};
+    use crate::storage::json_store::TaskState;
+    use crate::testing::{self, TempStore};
+    use time::macros::datetime;
+
+    fn task(id: &str, title: &str, status: TaskStatus) -> Task {
+        Task {
+            status,
+            ..testing::task(id, title).build().unwrap()
+        }
+    }
+
+    fn state(tasks: Vec<Task>) -> TaskState {
+        TaskState {
+            tasks,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn entries_classify_each_changed_task() {
+        let before = state(vec![
+            task("1", "Keep", TaskStatus::Pending),
+            task("2", "Finish", TaskStatus::Pending),
+            task("3", "Reopen", TaskStatus::Completed),
+            task("4", "Drop", TaskStatus::Pending),
+            task("5", "Rename", TaskStatus::Pending),
+            task("7", "Abandon", TaskStatus::Waiting),
+        ]);
+        let mut after = state(vec![
+            task("1", "Keep", TaskStatus::Pending),
+            task("2", "Finish", TaskStatus::Completed),
+            task("3", "Reopen", TaskStatus::Pending),
+            task("5", "Renamed", TaskStatus::Pending),
+            task("6", "New", TaskStatus::Pending),
+            task("7", "Abandon", TaskStatus::Cancelled),
+        ]);
+        after.focused_task_id = Some("1".to_string());
+
+        let changes: Vec<(String, ActivityChange)> =
+            entries(&before, &after, "ana@laptop", "2025-12-20T12:00:00Z")
+                .into_iter()
+                .map(|entry| (entry.task_id, entry.change))
+                .collect();
+
+        assert_eq!(
+            changes,
+            vec![
+                ("2".to_string(), ActivityChange::Completed),
+                ("3".to_string(), ActivityChange::Reopened),
+                ("5".to_string(), ActivityChange::Updated),
+                ("6".to_string(), ActivityChange::Added),
+                ("7".to_string(), ActivityChange::Cancelled),
+                ("4".to_string(), ActivityChange::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn unseen_changes_skip_own_and_already_seen_entries() {
+        let store = TempStore::new();
+        let path = store.path();
+        let entry = |by: &str, at: &str, id: &str| ActivityEntry {
+            at: at.to_string(),
+            by: by.to_string(),
+            task_id: id.to_string(),
+            title: format!("task {id}"),
+            change: ActivityChange::Added,
+        };
+        let lines: Vec<String> = [
+            entry("ben@desk", "2025-12-20T09:00:00Z", "1"),
+            entry("ana@laptop", "2025-12-20T11:00:00Z", "2"),
+            entry("ben@desk", "2025-12-20T11:30:00Z", "3"),
+        ]
+        .iter()
+        .map(|entry| serde_json::to_string(entry).unwrap())
+        .collect();
+        std::fs::write(activity_path(path), lines.join("\n") + "\n").unwrap();
+
+        let first = unseen_changes(path, "ana@laptop", datetime!(2025-12-20 10:00 UTC)).unwrap();
+        std::fs::write(seen_path(path), r#"{"ana@laptop": "2025-12-20T10:00:00Z"}"#).unwrap();
+        let unseen = unseen_changes(path, "ana@laptop", datetime!(2025-12-20 12:00 UTC)).unwrap();
+        let again = unseen_changes(path, "ana@laptop", datetime!(2025-12-20 12:05 UTC)).unwrap();
+
+        assert!(first.is_empty());
+        assert_eq!(
+            unseen
+                .iter()
+                .map(|entry| entry.task_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["3"]
+        );
+        assert!(again.is_empty());
+    }
+}
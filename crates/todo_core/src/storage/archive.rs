@@ -0,0 +1,68 @@
+//! Completed tasks moved out of the live store, kept in `<store>.archive.json`.
+//!
+//! Archiving keeps the live store (and every `list`) small without losing history. The file
+//! also records when retention rules last ran, so startup maintenance can be rate limited.
+
+use crate::error::AppError;
+use crate::model::Task;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Archive {
+    /// When retention rules last ran against the store (RFC3339, UTC).
+    #[serde(default)]
+    pub last_maintained_at: Option<String>,
+    /// Oldest first.
+    #[serde(default)]
+    pub tasks: Vec<ArchivedTask>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedTask {
+    /// When the task left the live store (RFC3339, UTC).
+    pub archived_at: String,
+    pub task: Task,
+}
+
+pub fn archive_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".archive.json");
+    store_path.with_file_name(name)
+}
+
+/// The archive for the store, empty before anything was archived.
+pub fn read(store_path: &Path) -> Result<Archive, AppError> {
+    let path = archive_path(store_path);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Archive::default()),
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+    serde_json::from_str(&content)
+        .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display())))
+}
+
+pub(crate) fn write(store_path: &Path, archive: &Archive) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(archive)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+
+    let path = archive_path(store_path);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    std::fs::write(&temp_path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&temp_path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    std::fs::rename(&temp_path, &path).map_err(|err| AppError::io(err.to_string()))
+}
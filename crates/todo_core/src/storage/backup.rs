@@ -0,0 +1,198 @@
+//! Named full copies of the store, kept in `<store>.backups/`.
+//!
+//! Each backup is a plain snapshot in the store's own format, so it can be loaded, compared
+//! with `diff`, or copied back by hand. Names are reduced to lowercase letters, digits, and
+//! dashes for the file name, so `"Before cleanup"` is saved as `before-cleanup.json`.
+
+use crate::error::AppError;
+use crate::storage::json_store::{self, TaskState};
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const BACKUP_EXTENSION: &str = "json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub name: String,
+    pub path: PathBuf,
+    /// When the backup file was last written (RFC3339, UTC).
+    pub created_at: String,
+    pub task_count: usize,
+}
+
+pub fn backup_dir(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".backups");
+    store_path.with_file_name(name)
+}
+
+/// File-name form of a backup name: lowercase letters and digits, other runs become `-`.
+pub fn backup_name(label: &str) -> Result<String, AppError> {
+    let mut name = String::new();
+    for ch in label.trim().chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() {
+            name.push(ch);
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.trim_end_matches('-').to_string();
+    if name.is_empty() {
+        return Err(AppError::invalid_input(
+            "backup name must contain a letter or digit",
+        ));
+    }
+    Ok(name)
+}
+
+/// Save `state` under `label`, refusing to replace an existing backup of the same name.
+pub fn create(store_path: &Path, label: &str, state: &TaskState) -> Result<Backup, AppError> {
+    let name = backup_name(label)?;
+    if backup_path(store_path, &name).exists() {
+        return Err(AppError::invalid_input(format!(
+            "backup '{name}' already exists"
+        )));
+    }
+    write(store_path, &name, state)
+}
+
+/// Save `state` under `label`, replacing any backup of that name.
+pub fn replace(store_path: &Path, label: &str, state: &TaskState) -> Result<Backup, AppError> {
+    let name = backup_name(label)?;
+    write(store_path, &name, state)
+}
+
+pub fn find(store_path: &Path, label: &str) -> Result<Backup, AppError> {
+    let name = backup_name(label)?;
+    let path = backup_path(store_path, &name);
+    if !path.exists() {
+        return Err(AppError::invalid_input(format!(
+            "backup '{name}' not found"
+        )));
+    }
+    describe(name, path)
+}
+
+pub fn load(backup: &Backup) -> Result<TaskState, AppError> {
+    json_store::load_state(&backup.path)
+}
+
+/// Every backup of the store, oldest first.
+pub fn list(store_path: &Path) -> Result<Vec<Backup>, AppError> {
+    let dir = backup_dir(store_path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    let entries = std::fs::read_dir(&dir).map_err(|err| AppError::io(err.to_string()))?;
+    for entry in entries {
+        let path = entry.map_err(|err| AppError::io(err.to_string()))?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(BACKUP_EXTENSION) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        backups.push(describe(name.to_string(), path.clone())?);
+    }
+    backups.sort_by(|left, right| {
+        (&left.created_at, &left.name).cmp(&(&right.created_at, &right.name))
+    });
+    Ok(backups)
+}
+
+fn backup_path(store_path: &Path, name: &str) -> PathBuf {
+    backup_dir(store_path).join(format!("{name}.{BACKUP_EXTENSION}"))
+}
+
+fn write(store_path: &Path, name: &str, state: &TaskState) -> Result<Backup, AppError> {
+    let path = backup_path(store_path, name);
+    json_store::save_detached_state(&path, state)?;
+    describe(name.to_string(), path)
+}
+
+fn describe(name: String, path: PathBuf) -> Result<Backup, AppError> {
+    let modified = std::fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|err| AppError::io(format!("{}: {err}", path.display())))?;
+    let created_at = OffsetDateTime::from(modified)
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let task_count = json_store::load_state(&path)
+        .map_err(|err| AppError::invalid_data(format!("{}: {}", path.display(), err.message())))?
+        .tasks
+        .len();
+
+    Ok(Backup {
+        name,
+        path,
+        created_at,
+        task_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup_name, create, find, list, load, replace};
+    use crate::model::Task;
+    use crate::storage::json_store::TaskState;
+    use crate::testing::TempStore;
+
+    fn state(titles: &[&str]) -> TaskState {
+        TaskState {
+            tasks: titles
+                .iter()
+                .enumerate()
+                .map(|(index, title)| Task {
+                    id: index.to_string(),
+                    title: title.to_string(),
+                    created_at: "2025-12-20T00:00:00Z".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backup_name_normalizes_labels() {
+        assert_eq!(backup_name("Before cleanup").unwrap(), "before-cleanup");
+        assert_eq!(backup_name("  v2 / final!! ").unwrap(), "v2-final");
+        assert_eq!(backup_name("--").unwrap_err().code(), "invalid_input");
+    }
+
+    #[test]
+    fn create_find_and_list_round_trip() {
+        let temp = TempStore::new();
+        let store = temp.path();
+        let saved = state(&["a", "b"]);
+
+        let backup = create(store, "Before cleanup", &saved).unwrap();
+        let duplicate = create(store, "before  cleanup", &saved).unwrap_err();
+        replace(store, "before-cleanup", &state(&["a"])).unwrap();
+        create(store, "second", &saved).unwrap();
+        let found = find(store, "BEFORE CLEANUP").unwrap();
+        let loaded = load(&found).unwrap();
+        let names: Vec<String> = list(store)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.name)
+            .collect();
+        let missing = find(store, "nope").unwrap_err();
+
+        assert_eq!(backup.name, "before-cleanup");
+        assert_eq!(backup.task_count, 2);
+        assert_eq!(duplicate.code(), "invalid_input");
+        assert_eq!(found.task_count, 1);
+        assert_eq!(loaded.tasks, state(&["a"]).tasks);
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"second".to_string()));
+        assert_eq!(missing.code(), "invalid_input");
+        assert!(!store.exists());
+    }
+}
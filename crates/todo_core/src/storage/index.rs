@@ -0,0 +1,318 @@
+//! Sorted id → byte-range index for the JSON snapshot.
+//!
+//! Point lookups (`show <id>`) binary-search `<store>.idx` and deserialize only the matching
+//! task object instead of the whole store. The header records the snapshot's length and
+//! modification time, followed by the focused task id; an index that does not match the
+//! snapshot on disk is ignored and the caller falls back to a full load.
+
+use crate::error::AppError;
+use crate::model::Task;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Snapshots with fewer tasks than this are not indexed; loading them in full is cheap.
+pub const INDEX_MIN_TASKS: usize = 500;
+const HEADER_TAG: &str = "todoapp-index 2";
+
+/// Location of one task object inside the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct IndexEntry {
+    pub id: String,
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum IndexLookup {
+    Found(Box<Task>),
+    Missing,
+    /// No index, or it is stale or corrupt; the caller must scan the snapshot.
+    Unavailable,
+}
+
+pub(crate) fn index_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".idx");
+    store_path.with_file_name(name)
+}
+
+/// Writes the index for the snapshot currently at `store_path`.
+pub(crate) fn write(
+    store_path: &Path,
+    mut entries: Vec<IndexEntry>,
+    focused_task_id: Option<&str>,
+) -> Result<(), AppError> {
+    entries.sort_by(|left, right| left.id.cmp(&right.id));
+    if entries.windows(2).any(|pair| pair[0].id == pair[1].id) {
+        // Lookups would be ambiguous; full loads keep the first match.
+        return remove(store_path);
+    }
+
+    let focus = serde_json::to_string(&focused_task_id)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let mut content = format!("{HEADER_TAG} {}\n{focus}\n", fingerprint(store_path)?);
+    for entry in &entries {
+        let id = serde_json::to_string(&entry.id)
+            .map_err(|err| AppError::invalid_data(err.to_string()))?;
+        content.push_str(&format!("{id}\t{}\t{}\n", entry.offset, entry.len));
+    }
+
+    let path = index_path(store_path);
+    std::fs::write(&path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn remove(store_path: &Path) -> Result<(), AppError> {
+    match std::fs::remove_file(index_path(store_path)) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(AppError::io(err.to_string())),
+    }
+}
+
+/// Focused task id recorded in the index, or `None` when the index is unavailable.
+pub(crate) fn focused_task_id(store_path: &Path) -> Result<Option<Option<String>>, AppError> {
+    Ok(open_current(store_path)?.map(|opened| opened.focused_task_id))
+}
+
+pub(crate) fn lookup(store_path: &Path, id: &str) -> Result<IndexLookup, AppError> {
+    let Some(mut opened) = open_current(store_path)? else {
+        return Ok(IndexLookup::Unavailable);
+    };
+
+    // A corrupt index is only a missed shortcut: the scan that follows reads the snapshot, and
+    // reports any trouble with it.
+    let line = match find_line(&mut opened.reader, opened.body_start, opened.end, id) {
+        Ok(Some(line)) => line,
+        Ok(None) => return Ok(IndexLookup::Missing),
+        Err(_) => return Ok(IndexLookup::Unavailable),
+    };
+    let Ok(entry) = parse_line(&line) else {
+        return Ok(IndexLookup::Unavailable);
+    };
+
+    let mut snapshot = File::open(store_path).map_err(|err| AppError::io(err.to_string()))?;
+    snapshot
+        .seek(SeekFrom::Start(entry.offset as u64))
+        .map_err(|err| AppError::io(err.to_string()))?;
+    let mut bytes = vec![0; entry.len];
+    if snapshot.read_exact(&mut bytes).is_err() {
+        return Ok(IndexLookup::Unavailable);
+    }
+    match serde_json::from_slice::<Task>(&bytes) {
+        Ok(task) if task.id == id => Ok(IndexLookup::Found(Box::new(task))),
+        _ => Ok(IndexLookup::Unavailable),
+    }
+}
+
+struct OpenedIndex {
+    reader: BufReader<File>,
+    focused_task_id: Option<String>,
+    body_start: u64,
+    end: u64,
+}
+
+/// Opens the index and reads its header, returning `None` if it is missing, stale, or its header
+/// is unreadable.
+fn open_current(store_path: &Path) -> Result<Option<OpenedIndex>, AppError> {
+    let Ok(file) = File::open(index_path(store_path)) else {
+        return Ok(None);
+    };
+    let end = file
+        .metadata()
+        .map_err(|err| AppError::io(err.to_string()))?
+        .len();
+    let mut reader = BufReader::new(file);
+
+    let mut header = String::new();
+    if reader.read_line(&mut header).is_err() {
+        return Ok(None);
+    }
+    let expected = format!("{HEADER_TAG} {}", fingerprint(store_path)?);
+    if header.trim_end() != expected {
+        return Ok(None);
+    }
+
+    let mut focus = String::new();
+    if reader.read_line(&mut focus).is_err() {
+        return Ok(None);
+    }
+    let Ok(focused_task_id) = serde_json::from_str(&focus) else {
+        return Ok(None);
+    };
+
+    Ok(Some(OpenedIndex {
+        reader,
+        focused_task_id,
+        body_start: (header.len() + focus.len()) as u64,
+        end,
+    }))
+}
+
+/// Binary search over the variable-length lines starting in `[lo, hi)`, which must be sorted
+/// by `key` and each begin with it as a JSON string followed by a tab. Returns the matching
+/// line, newline included.
+pub(crate) fn find_line(
+    reader: &mut BufReader<File>,
+    mut lo: u64,
+    mut hi: u64,
+    key: &str,
+) -> Result<Option<String>, AppError> {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let line_start = if mid == lo {
+            lo
+        } else {
+            // Skip the tail of the line that contains `mid - 1`.
+            seek(reader, mid - 1)?;
+            let mut skipped = Vec::new();
+            let read = reader
+                .read_until(b'\n', &mut skipped)
+                .map_err(|err| AppError::io(err.to_string()))?;
+            mid - 1 + read as u64
+        };
+        if line_start >= hi {
+            hi = mid;
+            continue;
+        }
+
+        seek(reader, line_start)?;
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|err| AppError::io(err.to_string()))?;
+        match line_key(&line)?.as_str().cmp(key) {
+            std::cmp::Ordering::Equal => return Ok(Some(line)),
+            std::cmp::Ordering::Less => lo = line_start + read as u64,
+            std::cmp::Ordering::Greater => hi = line_start,
+        }
+    }
+
+    Ok(None)
+}
+
+fn seek(reader: &mut BufReader<File>, position: u64) -> Result<(), AppError> {
+    reader
+        .seek(SeekFrom::Start(position))
+        .map(|_| ())
+        .map_err(|err| AppError::io(err.to_string()))
+}
+
+/// The JSON string a sorted line starts with, decoded.
+pub(crate) fn line_key(line: &str) -> Result<String, AppError> {
+    let invalid = || AppError::invalid_data("malformed index line");
+    let (key, _) = line.split_once('\t').ok_or_else(invalid)?;
+    serde_json::from_str(key).map_err(|_| invalid())
+}
+
+fn parse_line(line: &str) -> Result<IndexEntry, AppError> {
+    let invalid = || AppError::invalid_data("malformed index line");
+    let mut fields = line.trim_end_matches('\n').split('\t');
+    let id: String =
+        serde_json::from_str(fields.next().ok_or_else(invalid)?).map_err(|_| invalid())?;
+    let offset = fields
+        .next()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(invalid)?;
+    let len = fields
+        .next()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(invalid)?;
+
+    Ok(IndexEntry { id, offset, len })
+}
+
+/// Length and modification time of the file at `path`, `0 0` when it does not exist. A file
+/// rewritten in any way almost certainly changes one of them.
+pub(crate) fn fingerprint(path: &Path) -> Result<String, AppError> {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok("0 0".to_string()),
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    Ok(format!("{} {modified}", metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexLookup, index_path, lookup};
+    use crate::model::Task;
+    use crate::storage::json_store::{TaskState, load_task, save_state};
+    use crate::testing::TempStore;
+
+    fn indexed_state() -> TaskState {
+        TaskState {
+            tasks: (0..super::INDEX_MIN_TASKS)
+                .map(|index| Task {
+                    id: format!("id-{}", index * 7 % 1000),
+                    title: format!("task \"{index}\"\n"),
+                    created_at: "2025-12-20T00:00:00Z".to_string(),
+                    urgent: index % 2 == 0,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lookup_finds_every_indexed_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        let state = indexed_state();
+        save_state(path, &state).unwrap();
+
+        for task in &state.tasks {
+            assert_eq!(
+                lookup(path, &task.id).unwrap(),
+                IndexLookup::Found(Box::new(task.clone()))
+            );
+        }
+        let missing = lookup(path, "id-1001").unwrap();
+        let before_first = lookup(path, "a").unwrap();
+        let after_last = lookup(path, "zz").unwrap();
+
+        assert_eq!(missing, IndexLookup::Missing);
+        assert_eq!(before_first, IndexLookup::Missing);
+        assert_eq!(after_last, IndexLookup::Missing);
+    }
+
+    #[test]
+    fn a_corrupt_index_falls_back_to_the_snapshot() {
+        let store = TempStore::new();
+        let path = store.path();
+        let state = indexed_state();
+        save_state(path, &state).unwrap();
+        let index = std::fs::read_to_string(index_path(path)).unwrap();
+        let header: Vec<&str> = index.lines().take(2).collect();
+        let wanted = &state.tasks[10];
+        let garbled = format!("{}\n{:?}\tnot a number\n", header.join("\n"), wanted.id);
+        std::fs::write(index_path(path), garbled).unwrap();
+
+        let looked_up = lookup(path, &wanted.id).unwrap();
+        let loaded = load_task(path, &wanted.id).unwrap();
+
+        assert_eq!(looked_up, IndexLookup::Unavailable);
+        assert_eq!(loaded.as_ref(), Some(wanted));
+    }
+}
@@ -0,0 +1,377 @@
+//! Append-only change log that sits next to the JSON store.
+//!
+//! Large stores would otherwise be rewritten in full on every mutation. Instead, commits against
+//! a large store append one line per changed task to `<store>.journal`; loading replays those
+//! lines on top of the snapshot, and the journal is folded back into the snapshot (compacted)
+//! once it grows past [`COMPACT_AFTER_OPS`] entries. Each line is stamped with the `user@host`
+//! and time of the commit that wrote it, which is what to look at when two machines sharing a
+//! store disagree.
+
+use crate::error::AppError;
+use crate::model::Task;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::json_store::TaskState;
+
+/// Stores with fewer tasks than this are always rewritten in full; a single small file is
+/// easier to inspect and back up, and rewriting it is cheap.
+pub const JOURNAL_MIN_TASKS: usize = 500;
+/// Number of journal entries after which the next commit compacts into the snapshot.
+pub const COMPACT_AFTER_OPS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum JournalOp {
+    Upsert { task: Box<Task> },
+    Remove { id: String },
+    Focus { id: Option<String> },
+    LastNotified { at: Option<String> },
+}
+
+/// One journal line. Lines written before stamping was introduced have no `by` or `at`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct JournalEntry {
+    #[serde(default)]
+    pub by: Option<String>,
+    #[serde(default)]
+    pub at: Option<String>,
+    #[serde(flatten)]
+    pub op: JournalOp,
+}
+
+pub(crate) fn journal_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".journal");
+    store_path.with_file_name(name)
+}
+
+/// Reads the journal, ignoring a torn final line left by an interrupted append.
+pub(crate) fn read_ops(store_path: &Path) -> Result<Vec<JournalOp>, AppError> {
+    Ok(read_entries(store_path)?
+        .into_iter()
+        .map(|entry| entry.op)
+        .collect())
+}
+
+pub(crate) fn read_entries(store_path: &Path) -> Result<Vec<JournalEntry>, AppError> {
+    let path = journal_path(store_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path).map_err(|err| AppError::io(err.to_string()))?;
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let mut ops = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(op) => ops.push(op),
+            Err(_) if index + 1 == lines.len() && !content.ends_with('\n') => break,
+            Err(err) => {
+                return Err(AppError::invalid_data(format!(
+                    "journal line {}: {err}",
+                    index + 1
+                )));
+            }
+        }
+    }
+
+    Ok(ops)
+}
+
+/// Applies journal entries in order. Replay is idempotent so a journal that survived a
+/// compaction (crash between snapshot write and journal removal) is harmless.
+pub(crate) fn replay(state: &mut TaskState, ops: Vec<JournalOp>) {
+    for op in ops {
+        match op {
+            JournalOp::Upsert { task } => {
+                match state
+                    .tasks
+                    .iter_mut()
+                    .find(|existing| existing.id == task.id)
+                {
+                    Some(existing) => *existing = *task,
+                    None => state.tasks.push(*task),
+                }
+            }
+            JournalOp::Remove { id } => {
+                if let Some(index) = state.tasks.iter().position(|task| task.id == id) {
+                    state.tasks.remove(index);
+                }
+            }
+            JournalOp::Focus { id } => state.focused_task_id = id,
+            JournalOp::LastNotified { at } => state.last_notified_at = at,
+        }
+    }
+}
+
+/// Describes `after` as a list of entries applied to `before`. Returns `None` when the change
+/// cannot be expressed that way (tasks reordered, or ids that are not unique), in which case
+/// the caller must rewrite the snapshot.
+pub(crate) fn diff(before: &TaskState, after: &TaskState) -> Option<Vec<JournalOp>> {
+    let before_by_id: HashMap<&str, &Task> = before
+        .tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task))
+        .collect();
+    let after_ids: HashSet<&str> = after.tasks.iter().map(|task| task.id.as_str()).collect();
+    if before_by_id.len() != before.tasks.len() || after_ids.len() != after.tasks.len() {
+        return None;
+    }
+    // Context and day note changes are rare; they go through a full save.
+    if before.contexts != after.contexts
+        || before.active_context != after.active_context
+        || before.day_notes != after.day_notes
+    {
+        return None;
+    }
+
+    // Surviving tasks must keep their relative order and precede every new task, which is
+    // exactly what replaying removals and appending upserts reproduces.
+    let survivors = before
+        .tasks
+        .iter()
+        .filter(|task| after_ids.contains(task.id.as_str()));
+    let mut after_iter = after.tasks.iter();
+    for survivor in survivors {
+        if after_iter.next()?.id != survivor.id {
+            return None;
+        }
+    }
+
+    let mut ops: Vec<JournalOp> = before
+        .tasks
+        .iter()
+        .filter(|task| !after_ids.contains(task.id.as_str()))
+        .map(|task| JournalOp::Remove {
+            id: task.id.clone(),
+        })
+        .collect();
+    for task in &after.tasks {
+        if before_by_id.get(task.id.as_str()) != Some(&task) {
+            ops.push(JournalOp::Upsert {
+                task: Box::new(task.clone()),
+            });
+        }
+    }
+    if before.focused_task_id != after.focused_task_id {
+        ops.push(JournalOp::Focus {
+            id: after.focused_task_id.clone(),
+        });
+    }
+    if before.last_notified_at != after.last_notified_at {
+        ops.push(JournalOp::LastNotified {
+            at: after.last_notified_at.clone(),
+        });
+    }
+
+    Some(ops)
+}
+
+/// Appends `ops`, each stamped with `by` and `at`. A torn final line left by an interrupted
+/// append is cut off first, since reading skips it anyway and the new entries must not be glued
+/// onto it.
+pub(crate) fn append_ops(
+    store_path: &Path,
+    ops: &[JournalOp],
+    by: &str,
+    at: &str,
+) -> Result<(), AppError> {
+    let mut buffer = String::new();
+    for op in ops {
+        let entry = JournalEntry {
+            by: Some(by.to_string()),
+            at: Some(at.to_string()),
+            op: op.clone(),
+        };
+        let line =
+            serde_json::to_string(&entry).map_err(|err| AppError::invalid_data(err.to_string()))?;
+        buffer.push_str(&line);
+        buffer.push('\n');
+    }
+
+    let path = journal_path(store_path);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|err| AppError::io(err.to_string()))?;
+    drop_torn_line(&mut file)
+        .and_then(|()| file.seek(SeekFrom::End(0)))
+        .map_err(|err| AppError::io(err.to_string()))?;
+    file.write_all(buffer.as_bytes())
+        .map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Truncates the file back to just after its last newline when it does not end in one.
+fn drop_torn_line(file: &mut std::fs::File) -> std::io::Result<()> {
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(());
+    }
+    let mut last = [0u8];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    if last[0] == b'\n' {
+        return Ok(());
+    }
+
+    let mut content = Vec::with_capacity(len as usize);
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut content)?;
+    let keep = content
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+    file.set_len(keep as u64)
+}
+
+pub(crate) fn remove(store_path: &Path) -> Result<(), AppError> {
+    match std::fs::remove_file(journal_path(store_path)) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(AppError::io(err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JournalOp, append_ops, diff, journal_path, read_entries, replay};
+    use crate::model::Task;
+    use crate::storage::json_store::TaskState;
+    use crate::testing::{self, TempStore};
+
+    fn task(id: &str, title: &str) -> Task {
+        testing::task(id, title)
+            .created_at("2025-12-20T00:00:00Z")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn diff_replays_to_the_same_state() {
+        let before = TaskState {
+            tasks: vec![task("1", "a"), task("2", "b"), task("3", "c")],
+            focused_task_id: Some("2".to_string()),
+            ..Default::default()
+        };
+        let after = TaskState {
+            tasks: vec![task("1", "a"), task("3", "changed"), task("4", "new")],
+            focused_task_id: None,
+            last_notified_at: Some("2025-12-21T08:00:00Z".to_string()),
+            ..Default::default()
+        };
+
+        let ops = diff(&before, &after).unwrap();
+        assert_eq!(
+            ops[0],
+            JournalOp::Remove {
+                id: "2".to_string()
+            }
+        );
+        assert_eq!(ops.len(), 5);
+
+        let mut replayed = before.clone();
+        replay(&mut replayed, ops.clone());
+        assert_eq!(replayed, after);
+
+        // Replaying twice must not change the result.
+        replay(&mut replayed, ops);
+        assert_eq!(replayed, after);
+    }
+
+    #[test]
+    fn diff_refuses_reordered_or_duplicate_tasks() {
+        let before = TaskState {
+            tasks: vec![task("1", "a"), task("2", "b")],
+            ..Default::default()
+        };
+        let reordered = TaskState {
+            tasks: vec![task("2", "b"), task("1", "a")],
+            ..Default::default()
+        };
+        let duplicated = TaskState {
+            tasks: vec![task("1", "a"), task("1", "b")],
+            ..Default::default()
+        };
+
+        assert!(diff(&before, &reordered).is_none());
+        assert!(diff(&duplicated, &before).is_none());
+    }
+
+    #[test]
+    fn entries_carry_the_stamp_and_older_lines_still_read() {
+        let store = TempStore::new();
+        let path = store.path();
+        std::fs::write(journal_path(path), "{\"op\":\"remove\",\"id\":\"1\"}\n").unwrap();
+
+        let focus = JournalOp::Focus {
+            id: Some("2".to_string()),
+        };
+        append_ops(
+            path,
+            std::slice::from_ref(&focus),
+            "ana@laptop",
+            "2025-12-20T12:00:00Z",
+        )
+        .unwrap();
+        let entries = read_entries(path).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].by, None);
+        assert_eq!(entries[1].by.as_deref(), Some("ana@laptop"));
+        assert_eq!(entries[1].at.as_deref(), Some("2025-12-20T12:00:00Z"));
+        assert_eq!(entries[1].op, focus);
+    }
+
+    #[test]
+    fn append_after_a_torn_line_starts_a_line_of_its_own() {
+        let store = TempStore::new();
+        std::fs::write(
+            journal_path(store.path()),
+            "{\"op\":\"remove\",\"id\":\"1\"}\n{\"op\":\"remo",
+        )
+        .unwrap();
+
+        let focus = JournalOp::Focus {
+            id: Some("2".to_string()),
+        };
+        append_ops(
+            store.path(),
+            std::slice::from_ref(&focus),
+            "ana@laptop",
+            "2025-12-20T12:00:00Z",
+        )
+        .unwrap();
+        let entries = read_entries(store.path()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].op,
+            JournalOp::Remove {
+                id: "1".to_string()
+            }
+        );
+        assert_eq!(entries[1].op, focus);
+    }
+}
@@ -1,10 +1,30 @@
+use crate::clock;
 use crate::error::AppError;
 use crate::model::Task;
+use crate::origin;
+use crate::storage::activity;
+use crate::storage::index::{self, INDEX_MIN_TASKS, IndexEntry, IndexLookup};
+use crate::storage::journal::{self, COMPACT_AFTER_OPS, JOURNAL_MIN_TASKS, JournalOp};
+use crate::storage::legacy::{self, Coercion};
+use crate::storage::outbox;
+use crate::storage::search;
+use crate::storage::status;
+use crate::storage::webhook_queue;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use time::format_description::well_known::Rfc3339;
 
-pub const SCHEMA_VERSION: u32 = 5;
-const STORE_FILE_NAME: &str = "tasks.json";
+pub const SCHEMA_VERSION: u32 = 20;
+pub(crate) const STORE_FILE_NAME: &str = "tasks.json";
+const LENIENT_HINT: &str = "if an older version or a hand edit wrote this, `todo migrate --lenient` coerces the values \
+     it recognizes";
+
+static STORE_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static CONFIGURED_STORE_PATH: OnceLock<PathBuf> = OnceLock::new();
+static DEFER_SNAPSHOTS: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug, Serialize, Deserialize)]
 struct StoredTasks {
@@ -12,21 +32,66 @@ struct StoredTasks {
     tasks: Vec<Task>,
     #[serde(default)]
     focused_task_id: Option<String>,
+    #[serde(default)]
+    last_notified_at: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    contexts: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_context: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    day_notes: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct TaskState {
     pub tasks: Vec<Task>,
     pub focused_task_id: Option<String>,
+    /// When `notify` last delivered at least one notification (RFC3339).
+    pub last_notified_at: Option<String>,
+    /// Named filter expressions, see [`crate::filter`].
+    pub contexts: BTreeMap<String, String>,
+    /// Context whose filter every listing applies until it is cleared.
+    pub active_context: Option<String>,
+    /// Free-text notes written with `journal`, keyed by the local day (`YYYY-MM-DD`).
+    pub day_notes: BTreeMap<String, String>,
+}
+
+/// Point every later [`store_path`] call in this process at `path`, ahead of
+/// `TODOAPP_STORE_PATH`. Used by sandboxed sessions; it can only be set once.
+pub fn override_store_path(path: PathBuf) -> Result<(), AppError> {
+    STORE_PATH_OVERRIDE
+        .set(path)
+        .map_err(|_| AppError::invalid_input("store path is already overridden"))
+}
+
+/// Use `path` for the store when neither an override nor `TODOAPP_STORE_PATH` names one: the
+/// config's `store_path`. It can only be set once.
+pub fn set_configured_store_path(path: PathBuf) -> Result<(), AppError> {
+    CONFIGURED_STORE_PATH
+        .set(path)
+        .map_err(|_| AppError::invalid_input("configured store path is already set"))
 }
 
 pub fn store_path() -> Result<PathBuf, AppError> {
+    if let Some(path) = STORE_PATH_OVERRIDE.get() {
+        return Ok(path.clone());
+    }
+
     if let Ok(path) = std::env::var("TODOAPP_STORE_PATH")
         && !path.trim().is_empty()
     {
         return Ok(PathBuf::from(path));
     }
 
+    if let Some(path) = CONFIGURED_STORE_PATH.get() {
+        return Ok(path.clone());
+    }
+
+    default_store_path()
+}
+
+/// Where the store lives when nothing else names a place for it.
+pub fn default_store_path() -> Result<PathBuf, AppError> {
     if cfg!(windows) {
         let appdata =
             std::env::var("APPDATA").map_err(|_| AppError::invalid_data("APPDATA is not set"))?;
@@ -45,60 +110,317 @@ pub fn load_tasks(path: &Path) -> Result<Vec<Task>, AppError> {
 }
 
 pub fn load_state(path: &Path) -> Result<TaskState, AppError> {
-    if !path.exists() {
-        return Ok(TaskState {
-            tasks: Vec::new(),
-            focused_task_id: None,
+    let mut state = load_snapshot(path)?;
+    journal::replay(&mut state, journal::read_ops(path)?);
+
+    if let Some(focused_task_id) = state.focused_task_id.as_deref() {
+        let exists = state.tasks.iter().any(|task| task.id == focused_task_id);
+        if !exists {
+            return Err(AppError::invalid_data("focused_task_id not found"));
+        }
+    }
+
+    Ok(state)
+}
+
+/// Look up a single task without deserializing the whole store when an index is available.
+pub fn load_task(path: &Path, id: &str) -> Result<Option<Task>, AppError> {
+    // The journal is bounded by compaction, so scanning it is cheap and it overrides the
+    // snapshot for any task it mentions.
+    for op in journal::read_ops(path)?.into_iter().rev() {
+        match op {
+            JournalOp::Upsert { task } if task.id == id => return Ok(Some(*task)),
+            JournalOp::Remove { id: removed } if removed == id => return Ok(None),
+            _ => {}
+        }
+    }
+
+    match index::lookup(path, id)? {
+        IndexLookup::Found(task) => Ok(Some(*task)),
+        IndexLookup::Missing => Ok(None),
+        IndexLookup::Unavailable => Ok(load_snapshot(path)?
+            .tasks
+            .into_iter()
+            .find(|task| task.id == id)),
+    }
+}
+
+/// The tasks with these ids, in no particular order; ids no task has are skipped. Like
+/// [`load_task`], but reads the journal once for all of them.
+pub fn load_tasks_by_id(path: &Path, ids: &BTreeSet<String>) -> Result<Vec<Task>, AppError> {
+    let mut journaled: HashMap<String, Option<Task>> = HashMap::new();
+    for op in journal::read_ops(path)? {
+        match op {
+            JournalOp::Upsert { task } => {
+                journaled.insert(task.id.clone(), Some(*task));
+            }
+            JournalOp::Remove { id } => {
+                journaled.insert(id, None);
+            }
+            _ => {}
+        }
+    }
+
+    let mut tasks = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(task) = journaled.remove(id) {
+            tasks.extend(task);
+            continue;
+        }
+        match index::lookup(path, id)? {
+            IndexLookup::Found(task) => tasks.push(*task),
+            IndexLookup::Missing => {}
+            IndexLookup::Unavailable => {
+                let mut tasks = load_state(path)?.tasks;
+                tasks.retain(|task| ids.contains(&task.id));
+                return Ok(tasks);
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+/// Focused task id, read from the journal and index when possible instead of a full load.
+pub fn load_focused_task_id(path: &Path) -> Result<Option<String>, AppError> {
+    let latest_focus = journal::read_ops(path)?
+        .into_iter()
+        .rev()
+        .find_map(|op| match op {
+            JournalOp::Focus { id } => Some(id),
+            _ => None,
         });
+    if let Some(focused_task_id) = latest_focus {
+        return Ok(focused_task_id);
+    }
+
+    match index::focused_task_id(path)? {
+        Some(focused_task_id) => Ok(focused_task_id),
+        None => Ok(load_state(path)?.focused_task_id),
     }
+}
 
+/// Problems reads work around or only some of them notice: a snapshot in an older schema, read
+/// with defaults for the fields added since, and a focus on a task that no longer exists, which
+/// the index lookups behind `show` and `list` pass over. Used by `--strict`.
+pub fn check(path: &Path) -> Result<Vec<String>, AppError> {
+    #[derive(Deserialize)]
+    struct Header {
+        schema_version: u32,
+    }
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut warnings = Vec::new();
     let content = std::fs::read_to_string(path).map_err(|err| AppError::io(err.to_string()))?;
-    let stored: StoredTasks =
+    let header: Header =
         serde_json::from_str(&content).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    if header.schema_version < SCHEMA_VERSION {
+        warnings.push(format!(
+            "{} uses schema_version {}; it is read as version {SCHEMA_VERSION} and upgraded on \
+             the next save",
+            path.display(),
+            header.schema_version
+        ));
+    }
 
+    let mut state = load_snapshot(path)?;
+    journal::replay(&mut state, journal::read_ops(path)?);
+    if let Some(focused_task_id) = state.focused_task_id.as_deref()
+        && !state.tasks.iter().any(|task| task.id == focused_task_id)
+    {
+        warnings.push(format!("focused task {focused_task_id} does not exist"));
+    }
+    Ok(warnings)
+}
+
+/// The store read for `migrate`: its state with the journal replayed, the schema version the
+/// file was written in, and, when `lenient`, the [`legacy`] values coerced to read it.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub state: TaskState,
+    pub schema_version: u32,
+    pub coercions: Vec<Coercion>,
+}
+
+pub fn load_for_migration(path: &Path, lenient: bool) -> Result<Migration, AppError> {
+    let content = std::fs::read_to_string(path).map_err(|err| AppError::io(err.to_string()))?;
+    let mut value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let coercions = if lenient {
+        legacy::coerce(&mut value)
+    } else {
+        Vec::new()
+    };
+    let stored: StoredTasks = serde_json::from_value(value).map_err(|err| {
+        let error = AppError::invalid_data(err.to_string());
+        if lenient {
+            error.with_hint("this value has no obvious current form; fix it by hand and rerun")
+        } else {
+            error.with_hint(LENIENT_HINT)
+        }
+    })?;
     if !(1..=SCHEMA_VERSION).contains(&stored.schema_version) {
         return Err(AppError::invalid_data("schema_version mismatch"));
     }
 
-    if let Some(focused_task_id) = stored.focused_task_id.as_deref() {
-        let exists = stored.tasks.iter().any(|task| task.id == focused_task_id);
-        if !exists {
-            return Err(AppError::invalid_data("focused_task_id not found"));
-        }
+    let schema_version = stored.schema_version;
+    let mut state = TaskState {
+        tasks: stored.tasks,
+        focused_task_id: stored.focused_task_id,
+        last_notified_at: stored.last_notified_at,
+        contexts: stored.contexts,
+        active_context: stored.active_context,
+        day_notes: stored.day_notes,
+    };
+    journal::replay(&mut state, journal::read_ops(path)?);
+    Ok(Migration {
+        state,
+        schema_version,
+        coercions,
+    })
+}
+
+fn load_snapshot(path: &Path) -> Result<TaskState, AppError> {
+    if !path.exists() {
+        return Ok(TaskState::default());
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|err| AppError::io(err.to_string()))?;
+    parse_state(&content)
+}
+
+/// The tasks in a store file's contents, without its journal.
+pub fn parse_state(content: &str) -> Result<TaskState, AppError> {
+    let stored: StoredTasks = serde_json::from_str(content)
+        .map_err(|err| AppError::invalid_data(err.to_string()).with_hint(LENIENT_HINT))?;
+
+    if !(1..=SCHEMA_VERSION).contains(&stored.schema_version) {
+        return Err(AppError::invalid_data("schema_version mismatch"));
     }
 
     Ok(TaskState {
         tasks: stored.tasks,
         focused_task_id: stored.focused_task_id,
+        last_notified_at: stored.last_notified_at,
+        contexts: stored.contexts,
+        active_context: stored.active_context,
+        day_notes: stored.day_notes,
     })
 }
 
+/// Persist the transition from `before` (as loaded) to `after`. Large stores append the
+/// difference to the journal instead of rewriting the snapshot; everything else, and every
+/// [`COMPACT_AFTER_OPS`] journal entries, falls back to [`save_state`]. Either way the status
+/// summary is refreshed, a current search index is updated, and the changed tasks are recorded
+/// in the activity log, the event outbox, and the webhook queue.
+///
+/// While snapshots are deferred (see [`defer_snapshots`]) stores of every size are journaled.
+pub fn commit_state(path: &Path, before: &TaskState, after: &TaskState) -> Result<(), AppError> {
+    if before == after && path.exists() {
+        return Ok(());
+    }
+    let by = origin::current();
+    let at = clock::now_utc()
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let search_current = search::is_current(path)?;
+    persist_changes(path, before, after, &by, &at)?;
+    if search_current {
+        search::update(path, before, after)?;
+    }
+    activity::record(path, before, after, &by, &at)?;
+    let entries = activity::entries(before, after, &by, &at);
+    outbox::record(path, &entries)?;
+    webhook_queue::record(path, &entries)
+}
+
+fn persist_changes(
+    path: &Path,
+    before: &TaskState,
+    after: &TaskState,
+    by: &str,
+    at: &str,
+) -> Result<(), AppError> {
+    let small = after.tasks.len() < JOURNAL_MIN_TASKS;
+    if (small && !DEFER_SNAPSHOTS.load(Ordering::Relaxed)) || !path.exists() {
+        return save_state(path, after);
+    }
+
+    let Some(ops) = journal::diff(before, after) else {
+        return save_state(path, after);
+    };
+    if journal::read_ops(path)?.len() + ops.len() > COMPACT_AFTER_OPS {
+        return save_state(path, after);
+    }
+
+    journal::append_ops(path, &ops, by, at)?;
+    status::write(path, after)
+}
+
+/// Journal every commit instead of rewriting the snapshot, however small the store, until
+/// turned off again. Interactive mode uses this to batch rapid changes: each one is still on
+/// disk in the journal, and [`flush`] writes the snapshot when convenient.
+pub fn defer_snapshots(enabled: bool) {
+    DEFER_SNAPSHOTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Fold the journal into the snapshot. Returns `false` when there was nothing to fold.
+pub fn flush(path: &Path) -> Result<bool, AppError> {
+    if journal::read_ops(path)?.is_empty() {
+        return Ok(false);
+    }
+    let search_current = search::is_current(path)?;
+    let state = load_state(path)?;
+    save_state(path, &state)?;
+    if search_current {
+        // Same tasks, rewritten files.
+        search::update(path, &state, &state)?;
+    }
+    Ok(true)
+}
+
 pub fn save_tasks(path: &Path, tasks: &[Task]) -> Result<(), AppError> {
-    let focused_task_id = if path.exists() {
-        load_state(path)?.focused_task_id
+    let mut state = if path.exists() {
+        load_state(path)?
     } else {
-        None
-    };
-    let state = TaskState {
-        tasks: tasks.to_vec(),
-        focused_task_id,
+        TaskState::default()
     };
+    state.tasks = tasks.to_vec();
     save_state(path, &state)
 }
 
 pub fn save_state(path: &Path, state: &TaskState) -> Result<(), AppError> {
+    let (content, stored) = write_snapshot(path, state)?;
+
+    match index_entries(&content, &stored.tasks)? {
+        Some(entries) if stored.tasks.len() >= INDEX_MIN_TASKS => {
+            index::write(path, entries, stored.focused_task_id.as_deref())?
+        }
+        _ => index::remove(path)?,
+    }
+
+    // The snapshot now includes everything the journal recorded.
+    journal::remove(path)?;
+    status::write(path, state)
+}
+
+/// Write `state` as a standalone snapshot, without the index or journal that accompany the
+/// live store, and without refreshing the status summary. Used for copies such as backups.
+pub(crate) fn save_detached_state(path: &Path, state: &TaskState) -> Result<(), AppError> {
+    write_snapshot(path, state).map(|_| ())
+}
+
+fn write_snapshot(path: &Path, state: &TaskState) -> Result<(String, StoredTasks), AppError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|err| AppError::io(err.to_string()))?;
     }
 
-    let stored = StoredTasks {
-        schema_version: SCHEMA_VERSION,
-        tasks: state.tasks.to_vec(),
-        focused_task_id: state.focused_task_id.clone(),
-    };
+    let stored = stored_tasks(state);
     let content = serde_json::to_string_pretty(&stored)
         .map_err(|err| AppError::invalid_data(err.to_string()))?;
-    std::fs::write(path, content).map_err(|err| AppError::io(err.to_string()))?;
+    std::fs::write(path, &content).map_err(|err| AppError::io(err.to_string()))?;
 
     #[cfg(unix)]
     {
@@ -107,85 +429,141 @@ pub fn save_state(path: &Path, state: &TaskState) -> Result<(), AppError> {
         std::fs::set_permissions(path, permissions).map_err(|err| AppError::io(err.to_string()))?;
     }
 
-    Ok(())
+    Ok((content, stored))
+}
+
+/// `state` as the contents of a store file in the current schema, as [`save_state`] writes it.
+pub fn render_state(state: &TaskState) -> Result<String, AppError> {
+    serde_json::to_string_pretty(&stored_tasks(state))
+        .map_err(|err| AppError::invalid_data(err.to_string()))
+}
+
+fn stored_tasks(state: &TaskState) -> StoredTasks {
+    StoredTasks {
+        schema_version: SCHEMA_VERSION,
+        tasks: state.tasks.to_vec(),
+        focused_task_id: state.focused_task_id.clone(),
+        last_notified_at: state.last_notified_at.clone(),
+        contexts: state.contexts.clone(),
+        active_context: state.active_context.clone(),
+        day_notes: state.day_notes.clone(),
+    }
+}
+
+/// Locates each task object inside the pretty-printed snapshot. Tasks appear in order and
+/// nested two levels deep, so each one is its own pretty rendering indented by four spaces.
+fn index_entries(content: &str, tasks: &[Task]) -> Result<Option<Vec<IndexEntry>>, AppError> {
+    let mut entries = Vec::with_capacity(tasks.len());
+    let mut cursor = 0;
+    for task in tasks {
+        let rendered = serde_json::to_string_pretty(task)
+            .map_err(|err| AppError::invalid_data(err.to_string()))?
+            .replace('\n', "\n    ");
+        let Some(found) = content[cursor..].find(&rendered) else {
+            return Ok(None);
+        };
+        let offset = cursor + found;
+        entries.push(IndexEntry {
+            id: task.id.clone(),
+            offset,
+            len: rendered.len(),
+        });
+        cursor = offset + rendered.len();
+    }
+
+    Ok(Some(entries))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{SCHEMA_VERSION, TaskState, load_state, load_tasks, save_state, save_tasks};
+    use super::{
+        SCHEMA_VERSION, TaskState, check, commit_state, load_focused_task_id, load_state,
+        load_task, load_tasks, save_state, save_tasks,
+    };
     use crate::model::{Task, TaskStatus};
+    use crate::storage::index::index_path;
+    use crate::storage::journal::{COMPACT_AFTER_OPS, JOURNAL_MIN_TASKS, journal_path};
+    use crate::storage::status;
+    use crate::testing::TempStore;
     use std::fs;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
 
-    fn temp_path(file_name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+    fn large_state(count: usize) -> TaskState {
+        TaskState {
+            tasks: (0..count)
+                .map(|index| Task {
+                    id: index.to_string(),
+                    title: format!("task {index}"),
+                    created_at: "2025-12-20T00:00:00Z".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
     }
 
     #[test]
     fn save_and_load_round_trip() {
-        let path = temp_path("tasks.json");
+        let store = TempStore::new();
+        let path = store.path();
         let task = Task {
             id: "task-1".to_string(),
             title: "demo".to_string(),
-            status: TaskStatus::Pending,
             created_at: "2025-12-20T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+            ..Default::default()
         };
 
-        save_tasks(&path, std::slice::from_ref(&task)).unwrap();
-        let loaded = load_tasks(&path).unwrap();
-        fs::remove_file(&path).ok();
+        save_tasks(path, std::slice::from_ref(&task)).unwrap();
+        let loaded = load_tasks(path).unwrap();
 
         assert_eq!(loaded.len(), 1);
         assert_eq!(loaded[0], task);
     }
 
     #[test]
-    fn save_and_load_state_preserves_focus() {
-        let path = temp_path("state.json");
+    fn save_and_load_state_preserves_focus_contexts_and_day_notes() {
+        let store = TempStore::new();
+        let path = store.path();
         let task = Task {
             id: "task-1".to_string(),
             title: "demo".to_string(),
-            status: TaskStatus::Pending,
             created_at: "2025-12-20T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+            ..Default::default()
         };
         let state = TaskState {
             tasks: vec![task.clone()],
             focused_task_id: Some(task.id.clone()),
+            last_notified_at: Some("2025-12-21T08:00:00Z".to_string()),
+            contexts: [("work".to_string(), "tag:work".to_string())].into(),
+            active_context: Some("work".to_string()),
+            day_notes: [("2025-12-21".to_string(), "Offsite".to_string())].into(),
         };
 
-        save_state(&path, &state).unwrap();
-        let loaded = load_state(&path).unwrap();
-        fs::remove_file(&path).ok();
+        save_state(path, &state).unwrap();
+        let loaded = load_state(path).unwrap();
 
         assert_eq!(loaded.tasks.len(), 1);
         assert_eq!(loaded.tasks[0], task);
         assert_eq!(loaded.focused_task_id, Some("task-1".to_string()));
+        assert_eq!(
+            loaded.last_notified_at,
+            Some("2025-12-21T08:00:00Z".to_string())
+        );
+        assert_eq!(loaded.contexts, state.contexts);
+        assert_eq!(loaded.active_context.as_deref(), Some("work"));
+        assert_eq!(loaded.day_notes, state.day_notes);
     }
 
     #[test]
     fn accepts_v1_schema_without_scheduled_at() {
-        let path = temp_path("v1-schema.json");
+        let store = TempStore::new();
+        let path = store.path();
         let content = "{\n  \"schema_version\": 1,\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\"\n    }\n  ]\n}";
-        fs::write(&path, content).unwrap();
+        fs::write(path, content).unwrap();
 
-        let loaded = load_tasks(&path).unwrap();
-        fs::remove_file(&path).ok();
+        let loaded = load_tasks(path).unwrap();
 
         assert_eq!(loaded.len(), 1);
-        assert_eq!(loaded[0].scheduled_at, None);
+        assert_eq!(loaded[0].due_at, None);
         assert_eq!(loaded[0].completed_at, None);
         assert!(loaded[0].completion_history.is_empty());
         assert!(!loaded[0].urgent);
@@ -193,59 +571,287 @@ mod tests {
 
     #[test]
     fn accepts_v2_schema_without_completion_fields() {
-        let path = temp_path("v2-schema.json");
+        let store = TempStore::new();
+        let path = store.path();
         let content = "{\n  \"schema_version\": 2,\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\",\n      \"scheduled_at\": \"2025-12-21T09:00:00Z\"\n    }\n  ]\n}";
-        fs::write(&path, content).unwrap();
+        fs::write(path, content).unwrap();
 
-        let loaded = load_tasks(&path).unwrap();
-        fs::remove_file(&path).ok();
+        let loaded = load_tasks(path).unwrap();
 
         assert_eq!(loaded.len(), 1);
-        assert_eq!(
-            loaded[0].scheduled_at,
-            Some("2025-12-21T09:00:00Z".to_string())
-        );
+        assert_eq!(loaded[0].due_at, Some("2025-12-21T09:00:00Z".to_string()));
         assert_eq!(loaded[0].completed_at, None);
         assert!(loaded[0].completion_history.is_empty());
         assert!(!loaded[0].urgent);
     }
 
+    #[test]
+    fn reads_v17_scheduled_at_as_due_at_and_saves_it_under_the_new_name() {
+        let store = TempStore::new();
+        let path = store.path();
+        let content = "{\n  \"schema_version\": 17,\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\",\n      \"scheduled_at\": \"2025-12-21T09:00:00Z\"\n    }\n  ]\n}";
+        fs::write(path, content).unwrap();
+
+        let state = load_state(path).unwrap();
+        save_state(path, &state).unwrap();
+        let saved = fs::read_to_string(path).unwrap();
+
+        assert_eq!(state.tasks[0].start_at, None);
+        assert_eq!(
+            state.tasks[0].due_at.as_deref(),
+            Some("2025-12-21T09:00:00Z")
+        );
+        assert!(saved.contains("\"due_at\": \"2025-12-21T09:00:00Z\""));
+        assert!(!saved.contains("scheduled_at"));
+    }
+
+    #[test]
+    fn accepts_v9_schema_without_metadata() {
+        let store = TempStore::new();
+        let path = store.path();
+        let content = "{\n  \"schema_version\": 9,\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\",\n      \"project\": \"work\"\n    }\n  ]\n}";
+        fs::write(path, content).unwrap();
+
+        let loaded = load_tasks(path).unwrap();
+
+        assert_eq!(loaded[0].project.as_deref(), Some("work"));
+        assert!(loaded[0].metadata.is_empty());
+    }
+
+    #[test]
+    fn accepts_v6_schema_without_tags_notes_or_project() {
+        let store = TempStore::new();
+        let path = store.path();
+        let content = "{\n  \"schema_version\": 6,\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\",\n      \"urgent\": true\n    }\n  ]\n}";
+        fs::write(path, content).unwrap();
+
+        let loaded = load_tasks(path).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded[0].tags.is_empty());
+        assert_eq!(loaded[0].notes, None);
+        assert_eq!(loaded[0].project, None);
+    }
+
     #[test]
     fn rejects_non_boolean_urgent_field() {
-        let path = temp_path("bad-urgent.json");
+        let store = TempStore::new();
+        let path = store.path();
         let content = "{\n  \"schema_version\": 5,\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\",\n      \"urgent\": \"yes\"\n    }\n  ]\n}";
-        fs::write(&path, content).unwrap();
+        fs::write(path, content).unwrap();
 
-        let err = load_tasks(&path).unwrap_err();
-        fs::remove_file(&path).ok();
+        let err = load_tasks(path).unwrap_err();
 
         assert_eq!(err.code(), "invalid_data");
     }
 
     #[test]
     fn rejects_unknown_focused_task_id() {
-        let path = temp_path("bad-focus.json");
+        let store = TempStore::new();
+        let path = store.path();
         let content = "{\n  \"schema_version\": 4,\n  \"focused_task_id\": \"task-missing\",\n  \"tasks\": [\n    {\n      \"id\": \"task-1\",\n      \"title\": \"demo\",\n      \"status\": \"pending\",\n      \"created_at\": \"2025-12-20T00:00:00Z\"\n    }\n  ]\n}";
-        fs::write(&path, content).unwrap();
+        fs::write(path, content).unwrap();
 
-        let err = load_state(&path).unwrap_err();
-        fs::remove_file(&path).ok();
+        let err = load_state(path).unwrap_err();
+        let warnings = check(path).unwrap();
 
         assert_eq!(err.code(), "invalid_data");
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].ends_with(&format!(
+            "uses schema_version 4; it is read as version {SCHEMA_VERSION} and upgraded on the \
+             next save"
+        )));
+        assert_eq!(warnings[1], "focused task task-missing does not exist");
+    }
+
+    #[test]
+    fn check_passes_a_current_store() {
+        let store = TempStore::new();
+        let path = store.path();
+        let missing = check(path).unwrap();
+        let mut state = large_state(2);
+        state.focused_task_id = Some("1".to_string());
+        save_state(path, &state).unwrap();
+
+        let warnings = check(path).unwrap();
+
+        assert!(missing.is_empty());
+        assert!(warnings.is_empty());
     }
 
     #[test]
     fn schema_version_must_match() {
-        let path = temp_path("bad-schema.json");
+        let store = TempStore::new();
+        let path = store.path();
         let bad = format!(
             "{{\n  \"schema_version\": {},\n  \"tasks\": []\n}}",
             SCHEMA_VERSION + 1
         );
-        fs::write(&path, bad).unwrap();
+        fs::write(path, bad).unwrap();
 
-        let err = load_tasks(&path).unwrap_err();
-        fs::remove_file(&path).ok();
+        let err = load_tasks(path).unwrap_err();
 
         assert_eq!(err.code(), "invalid_data");
     }
+
+    #[test]
+    fn commit_rewrites_small_stores_without_a_journal() {
+        let store = TempStore::new();
+        let path = store.path();
+        let before = large_state(3);
+        save_state(path, &before).unwrap();
+        let mut after = before.clone();
+        after.tasks[1].status = TaskStatus::Completed;
+
+        commit_state(path, &before, &after).unwrap();
+        let journal_exists = journal_path(path).exists();
+        let loaded = load_state(path).unwrap();
+
+        assert!(!journal_exists);
+        assert_eq!(loaded, after);
+    }
+
+    #[test]
+    fn commit_appends_to_journal_for_large_stores() {
+        let store = TempStore::new();
+        let path = store.path();
+        let before = large_state(JOURNAL_MIN_TASKS + 1);
+        save_state(path, &before).unwrap();
+        let snapshot = fs::read_to_string(path).unwrap();
+
+        let mut after = before.clone();
+        after.tasks[10].status = TaskStatus::Completed;
+        after.tasks.remove(20);
+        after.focused_task_id = Some("30".to_string());
+        commit_state(path, &before, &after).unwrap();
+
+        let snapshot_unchanged = fs::read_to_string(path).unwrap() == snapshot;
+        let journal = fs::read_to_string(journal_path(path)).unwrap();
+        let loaded = load_state(path).unwrap();
+
+        assert!(snapshot_unchanged);
+        assert_eq!(journal.lines().count(), 3);
+        assert_eq!(loaded, after);
+    }
+
+    #[test]
+    fn every_commit_refreshes_the_status_summary() {
+        let store = TempStore::new();
+        let path = store.path();
+        let before = large_state(JOURNAL_MIN_TASKS + 1);
+        save_state(path, &before).unwrap();
+        let saved = status::read(path).unwrap().unwrap();
+
+        let mut after = before.clone();
+        after.tasks[0].status = TaskStatus::Completed;
+        after.focused_task_id = Some("1".to_string());
+        commit_state(path, &before, &after).unwrap();
+        let journaled = status::read(path).unwrap().unwrap();
+
+        assert_eq!((saved.pending, saved.completed), (JOURNAL_MIN_TASKS + 1, 0));
+        assert_eq!(
+            (journaled.pending, journaled.completed),
+            (JOURNAL_MIN_TASKS, 1)
+        );
+        assert_eq!(journaled.focused_title.as_deref(), Some("task 1"));
+    }
+
+    #[test]
+    fn commit_compacts_journal_past_threshold() {
+        let store = TempStore::new();
+        let path = store.path();
+        let mut state = large_state(JOURNAL_MIN_TASKS);
+        save_state(path, &state).unwrap();
+
+        for index in 0..=COMPACT_AFTER_OPS {
+            let before = state.clone();
+            state.tasks[index].title = format!("renamed {index}");
+            commit_state(path, &before, &state).unwrap();
+        }
+
+        let journal_lines = fs::read_to_string(journal_path(path))
+            .map(|content| content.lines().count())
+            .unwrap_or(0);
+        let loaded = load_state(path).unwrap();
+
+        assert_eq!(journal_lines, 0);
+        assert_eq!(loaded, state);
+    }
+
+    #[test]
+    fn load_ignores_torn_final_journal_line() {
+        let store = TempStore::new();
+        let path = store.path();
+        let state = large_state(2);
+        save_state(path, &state).unwrap();
+        fs::write(
+            journal_path(path),
+            "{\"op\":\"remove\",\"id\":\"0\"}\n{\"op\":\"remove\",\"i",
+        )
+        .unwrap();
+
+        let loaded = load_state(path).unwrap();
+
+        assert_eq!(loaded.tasks.len(), 1);
+        assert_eq!(loaded.tasks[0].id, "1");
+    }
+
+    #[test]
+    fn load_task_uses_index_and_journal() {
+        let store = TempStore::new();
+        let path = store.path();
+        let before = large_state(JOURNAL_MIN_TASKS + 1);
+        save_state(path, &before).unwrap();
+        let indexed = index_path(path).exists();
+
+        let mut after = before.clone();
+        after.tasks[5].title = "renamed".to_string();
+        after.tasks.remove(6);
+        commit_state(path, &before, &after).unwrap();
+        let unfocused = load_focused_task_id(path).unwrap();
+        let mut focused_state = after.clone();
+        focused_state.focused_task_id = Some("30".to_string());
+        commit_state(path, &after, &focused_state).unwrap();
+
+        let focused = load_focused_task_id(path).unwrap();
+        let renamed = load_task(path, "5").unwrap();
+        let removed = load_task(path, "6").unwrap();
+        let untouched = load_task(path, "7").unwrap();
+        let unknown = load_task(path, "missing").unwrap();
+
+        assert!(indexed);
+        assert!(unfocused.is_none());
+        assert_eq!(focused.as_deref(), Some("30"));
+        assert_eq!(renamed.unwrap().title, "renamed");
+        assert!(removed.is_none());
+        assert_eq!(untouched, Some(before.tasks[7].clone()));
+        assert!(unknown.is_none());
+    }
+
+    #[test]
+    fn load_task_ignores_stale_index() {
+        let store = TempStore::new();
+        let path = store.path();
+        let state = large_state(JOURNAL_MIN_TASKS);
+        save_state(path, &state).unwrap();
+
+        // Rewrite the snapshot behind the index's back, shifting every offset.
+        let content = fs::read_to_string(path).unwrap();
+        fs::write(path, format!("  {content}")).unwrap();
+        let found = load_task(path, "42").unwrap();
+
+        assert_eq!(found, Some(state.tasks[42].clone()));
+    }
+
+    #[test]
+    fn small_stores_have_no_index() {
+        let store = TempStore::new();
+        let path = store.path();
+        save_state(path, &large_state(2)).unwrap();
+        let indexed = index_path(path).exists();
+        let found = load_task(path, "1").unwrap();
+
+        assert!(!indexed);
+        assert_eq!(found.unwrap().id, "1");
+    }
 }
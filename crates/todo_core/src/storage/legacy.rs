@@ -0,0 +1,270 @@
+//! Values older versions and hand edits left in the store that the current schema rejects,
+//! such as `"urgent": "yes"` or a Unix timestamp for `created_at`. `migrate --lenient` rewrites
+//! the ones with an obvious meaning before the store is read, and reports each change; anything
+//! else still fails to load.
+
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+const TIME_FIELDS: [&str; 7] = [
+    "created_at",
+    "start_at",
+    "due_at",
+    "scheduled_at",
+    "completed_at",
+    "modified_at",
+    "waiting_until",
+];
+
+/// One value changed by [`coerce`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coercion {
+    /// Id of the task the value belongs to (its position when it has none), or `None` for a
+    /// top-level field.
+    pub task: Option<String>,
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+impl fmt::Display for Coercion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(task) = &self.task {
+            write!(f, "task {task}: ")?;
+        }
+        write!(f, "{} {} -> {}", self.field, self.from, self.to)
+    }
+}
+
+/// Where `migrate` keeps the store file as it was before rewriting it.
+pub fn original_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".pre-migrate.json");
+    store_path.with_file_name(name)
+}
+
+/// Rewrite the legacy values in a store file's JSON in place, in file order.
+pub fn coerce(store: &mut Value) -> Vec<Coercion> {
+    let mut coercions = Vec::new();
+    let Some(store) = store.as_object_mut() else {
+        return coercions;
+    };
+
+    let mut top = Changes {
+        task: None,
+        coercions: &mut coercions,
+    };
+    match store.get("schema_version") {
+        // A store without a header is as old as they come.
+        None => top.record(store, "schema_version", Value::from(1)),
+        Some(Value::String(text)) => {
+            if let Ok(version) = text.trim().parse::<u32>() {
+                top.record(store, "schema_version", Value::from(version));
+            }
+        }
+        Some(_) => {}
+    }
+    if let Some(Value::Number(id)) = store.get("focused_task_id") {
+        let id = Value::String(id.to_string());
+        top.record(store, "focused_task_id", id);
+    }
+    if store.get("tasks") == Some(&Value::Null) {
+        top.record(store, "tasks", Value::Array(Vec::new()));
+    }
+
+    if let Some(Value::Array(tasks)) = store.get_mut("tasks") {
+        for (position, task) in tasks.iter_mut().enumerate() {
+            if let Some(task) = task.as_object_mut() {
+                coerce_task(task, position, &mut coercions);
+            }
+        }
+    }
+    coercions
+}
+
+fn coerce_task(task: &mut Map<String, Value>, position: usize, coercions: &mut Vec<Coercion>) {
+    let label = match task.get("id") {
+        Some(Value::String(id)) => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => format!("#{}", position + 1),
+    };
+    let mut changes = Changes {
+        task: Some(label.clone()),
+        coercions,
+    };
+
+    if let Some(Value::Number(_)) = task.get("id") {
+        changes.record(task, "id", Value::String(label));
+    }
+
+    if let Some(Value::Number(title)) = task.get("title") {
+        let title = Value::String(title.to_string());
+        changes.record(task, "title", title);
+    }
+    if let Some(Value::String(status)) = task.get("status")
+        && let Some(canonical) = status_name(status)
+        && canonical != status
+    {
+        changes.record(task, "status", Value::from(canonical));
+    }
+    match task.get("urgent") {
+        Some(Value::String(text)) => {
+            if let Some(urgent) = flag(text) {
+                changes.record(task, "urgent", Value::Bool(urgent));
+            }
+        }
+        Some(Value::Number(number)) => match number.as_u64() {
+            Some(0) => changes.record(task, "urgent", Value::Bool(false)),
+            Some(1) => changes.record(task, "urgent", Value::Bool(true)),
+            _ => {}
+        },
+        Some(Value::Null) => changes.record(task, "urgent", Value::Bool(false)),
+        _ => {}
+    }
+    match task.get("tags") {
+        Some(Value::String(text)) => {
+            let tags: Vec<Value> = text
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(Value::from)
+                .collect();
+            changes.record(task, "tags", Value::Array(tags));
+        }
+        Some(Value::Null) => changes.record(task, "tags", Value::Array(Vec::new())),
+        _ => {}
+    }
+    for field in TIME_FIELDS {
+        match task.get(field) {
+            Some(Value::Number(seconds)) => {
+                if let Some(time) = seconds
+                    .as_i64()
+                    .and_then(|seconds| OffsetDateTime::from_unix_timestamp(seconds).ok())
+                    .and_then(|time| time.format(&Rfc3339).ok())
+                {
+                    changes.record(task, field, Value::String(time));
+                }
+            }
+            Some(Value::String(text)) if text.trim().is_empty() && field != "created_at" => {
+                changes.record(task, field, Value::Null);
+            }
+            _ => {}
+        }
+    }
+    for field in ["notes", "project"] {
+        if let Some(Value::Number(number)) = task.get(field) {
+            let text = Value::String(number.to_string());
+            changes.record(task, field, text);
+        }
+    }
+    for field in ["completion_history", "comments"] {
+        if task.get(field) == Some(&Value::Null) {
+            changes.record(task, field, Value::Array(Vec::new()));
+        }
+    }
+    match task.get("metadata") {
+        Some(Value::Null) => changes.record(task, "metadata", Value::Object(Map::new())),
+        Some(Value::Object(metadata)) if metadata.values().any(|value| !value.is_string()) => {
+            let metadata = metadata
+                .iter()
+                .map(|(key, value)| {
+                    let text = match value {
+                        Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    };
+                    (key.clone(), Value::String(text))
+                })
+                .collect();
+            changes.record(task, "metadata", Value::Object(metadata));
+        }
+        _ => {}
+    }
+}
+
+struct Changes<'a> {
+    task: Option<String>,
+    coercions: &'a mut Vec<Coercion>,
+}
+
+impl Changes<'_> {
+    fn record(&mut self, object: &mut Map<String, Value>, field: &str, to: Value) {
+        let from = object.insert(field.to_string(), to.clone());
+        self.coercions.push(Coercion {
+            task: self.task.clone(),
+            field: field.to_string(),
+            from: from.unwrap_or(Value::Null),
+            to,
+        });
+    }
+}
+
+/// The status a legacy spelling such as `Done`, `in-progress`, or `canceled` stands for.
+fn status_name(text: &str) -> Option<&'static str> {
+    let name = text.trim().to_lowercase().replace(['-', ' '], "_");
+    Some(match name.as_str() {
+        "pending" | "todo" | "open" | "new" => "pending",
+        "in_progress" | "inprogress" | "doing" | "started" | "active" => "in_progress",
+        "waiting" | "blocked" | "on_hold" => "waiting",
+        "completed" | "complete" | "done" | "finished" => "completed",
+        "cancelled" | "canceled" | "dropped" => "cancelled",
+        _ => return None,
+    })
+}
+
+fn flag(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "yes" | "y" | "true" | "1" | "on" => Some(true),
+        "no" | "n" | "false" | "0" | "off" | "" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::coerce;
+    use serde_json::json;
+
+    #[test]
+    fn coerce_rewrites_obvious_legacy_values_and_reports_each() {
+        let mut store = json!({
+            "tasks": [
+                {
+                    "id": 7,
+                    "title": "Legacy",
+                    "status": "Done",
+                    "created_at": 1766397600,
+                    "due_at": "",
+                    "urgent": "yes",
+                    "tags": "work, home",
+                    "metadata": { "points": 3 },
+                },
+                { "id": "8", "title": "Fine", "status": "pending", "urgent": "maybe" },
+            ],
+        });
+
+        let coercions = coerce(&mut store);
+        let report: Vec<String> = coercions.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            report,
+            [
+                "schema_version null -> 1",
+                "task 7: id 7 -> \"7\"",
+                "task 7: status \"Done\" -> \"completed\"",
+                "task 7: urgent \"yes\" -> true",
+                "task 7: tags \"work, home\" -> [\"work\",\"home\"]",
+                "task 7: created_at 1766397600 -> \"2025-12-22T10:00:00Z\"",
+                "task 7: due_at \"\" -> null",
+                "task 7: metadata {\"points\":3} -> {\"points\":\"3\"}",
+            ]
+        );
+        assert_eq!(store["tasks"][0]["id"], "7");
+        assert_eq!(store["tasks"][1]["urgent"], "maybe");
+    }
+}
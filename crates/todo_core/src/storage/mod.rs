@@ -1 +1,21 @@
+pub mod activity;
+pub mod archive;
+pub mod backup;
+pub mod index;
+pub mod journal;
 pub mod json_store;
+pub mod legacy;
+// Read and written only by the Linux desktop notifier; its path is checked on every platform.
+#[cfg_attr(
+    not(all(target_os = "linux", feature = "desktop-notifications")),
+    allow(dead_code)
+)]
+pub mod notify_ids;
+pub mod outbox;
+pub mod search;
+pub mod seeds;
+pub mod status;
+pub mod store;
+pub mod sync_state;
+pub mod undo;
+pub mod webhook_queue;
@@ -0,0 +1,68 @@
+//! The desktop notification each task was last shown in, kept in `<store>.notify-ids.json` so
+//! the next `notify` run replaces it instead of stacking another one.
+
+use crate::error::AppError;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Notification server IDs by task ID.
+pub(crate) type NotificationIds = BTreeMap<String, u32>;
+
+pub(crate) fn notify_ids_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".notify-ids.json");
+    store_path.with_file_name(name)
+}
+
+/// The saved IDs; a missing or unreadable file means none, since the worst outcome is one
+/// notification stacking instead of replacing.
+pub(crate) fn read(store_path: &Path) -> NotificationIds {
+    std::fs::read_to_string(notify_ids_path(store_path))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write(store_path: &Path, ids: &NotificationIds) -> Result<(), AppError> {
+    let content =
+        serde_json::to_string(ids).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let path = notify_ids_path(store_path);
+    std::fs::write(&path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NotificationIds, notify_ids_path, read, write};
+    use crate::testing::TempStore;
+    use std::path::Path;
+
+    #[test]
+    fn ids_round_trip_beside_the_store() {
+        let temp = TempStore::new();
+        let store = temp.path();
+        assert!(read(store).is_empty());
+
+        let ids = NotificationIds::from([("task-1".to_string(), 42)]);
+        write(store, &ids).unwrap();
+        let loaded = read(store);
+
+        assert_eq!(loaded, ids);
+        assert_eq!(
+            notify_ids_path(Path::new("/tmp/tasks.json")),
+            Path::new("/tmp/tasks.json.notify-ids.json")
+        );
+    }
+}
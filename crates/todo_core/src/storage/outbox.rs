@@ -0,0 +1,219 @@
+//! Store events waiting for external automations, kept in `<store>.outbox.json`.
+//!
+//! Once a consumer has pulled for the first time, every commit adds one event per added,
+//! changed, or removed task, numbered in order. The consumer pulls them with `todo events pull`
+//! and acknowledges what it handled, which drops those events; nothing is pushed anywhere, so
+//! no daemon is needed. Stores nobody consumes keep no outbox. Only the newest [`MAX_EVENTS`]
+//! unacknowledged events are kept.
+
+use crate::error::AppError;
+use crate::storage::activity::{self, ActivityChange, ActivityEntry};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const MAX_EVENTS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    /// Increases by one per event and is never reused, even after events are acknowledged.
+    pub seq: u64,
+    #[serde(rename = "type")]
+    pub kind: EventKind,
+    /// When the change was committed (RFC3339, UTC).
+    pub at: String,
+    /// [`crate::origin::current`] of the process that made the change.
+    pub by: String,
+    pub task_id: String,
+    /// Title after the change, or before it for removals.
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    TaskAdded,
+    TaskUpdated,
+    TaskCompleted,
+    TaskReopened,
+    TaskCancelled,
+    TaskRemoved,
+}
+
+impl EventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::TaskAdded => "task_added",
+            EventKind::TaskUpdated => "task_updated",
+            EventKind::TaskCompleted => "task_completed",
+            EventKind::TaskReopened => "task_reopened",
+            EventKind::TaskCancelled => "task_cancelled",
+            EventKind::TaskRemoved => "task_removed",
+        }
+    }
+}
+
+impl From<ActivityChange> for EventKind {
+    fn from(change: ActivityChange) -> Self {
+        match change {
+            ActivityChange::Added => EventKind::TaskAdded,
+            ActivityChange::Updated => EventKind::TaskUpdated,
+            ActivityChange::Completed => EventKind::TaskCompleted,
+            ActivityChange::Reopened => EventKind::TaskReopened,
+            ActivityChange::Cancelled => EventKind::TaskCancelled,
+            ActivityChange::Removed => EventKind::TaskRemoved,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Outbox {
+    next_seq: u64,
+    events: Vec<OutboxEvent>,
+}
+
+pub fn outbox_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".outbox.json");
+    store_path.with_file_name(name)
+}
+
+/// `None` until a consumer first pulls.
+fn read(store_path: &Path) -> Result<Option<Outbox>, AppError> {
+    let path = outbox_path(store_path);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display()))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(AppError::io(err.to_string())),
+    }
+}
+
+fn write(store_path: &Path, outbox: &Outbox) -> Result<(), AppError> {
+    let content =
+        serde_json::to_string(outbox).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    activity::write_private(&outbox_path(store_path), &content)
+}
+
+/// Add an event for each of `entries`, the changes of one commit.
+pub(crate) fn record(store_path: &Path, entries: &[ActivityEntry]) -> Result<(), AppError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let Some(mut outbox) = read(store_path)? else {
+        return Ok(());
+    };
+    for entry in entries {
+        outbox.events.push(OutboxEvent {
+            seq: outbox.next_seq,
+            kind: entry.change.into(),
+            at: entry.at.clone(),
+            by: entry.by.clone(),
+            task_id: entry.task_id.clone(),
+            title: entry.title.clone(),
+        });
+        outbox.next_seq += 1;
+    }
+    let start = outbox.events.len().saturating_sub(MAX_EVENTS);
+    outbox.events.drain(..start);
+    write(store_path, &outbox)
+}
+
+/// Unacknowledged events, oldest first, at most `limit` of them. The first call starts the
+/// outbox, so it returns nothing.
+pub fn pending(store_path: &Path, limit: Option<usize>) -> Result<Vec<OutboxEvent>, AppError> {
+    let Some(outbox) = read(store_path)? else {
+        let outbox = Outbox {
+            next_seq: 1,
+            events: Vec::new(),
+        };
+        write(store_path, &outbox)?;
+        return Ok(Vec::new());
+    };
+    let mut events = outbox.events;
+    events.truncate(limit.unwrap_or(usize::MAX));
+    Ok(events)
+}
+
+/// Drop every event up to and including `through`; returns how many were dropped.
+pub fn acknowledge(store_path: &Path, through: u64) -> Result<usize, AppError> {
+    let Some(mut outbox) = read(store_path)? else {
+        return Ok(0);
+    };
+    let before = outbox.events.len();
+    outbox.events.retain(|event| event.seq > through);
+    let dropped = before - outbox.events.len();
+    if dropped > 0 {
+        write(store_path, &outbox)?;
+    }
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventKind, MAX_EVENTS, acknowledge, pending, record};
+    use crate::storage::activity::{ActivityChange, ActivityEntry};
+    use crate::testing::TempStore;
+
+    fn entry(task_id: &str, change: ActivityChange) -> ActivityEntry {
+        ActivityEntry {
+            at: "2025-12-20T12:00:00Z".to_string(),
+            by: "ana@laptop".to_string(),
+            task_id: task_id.to_string(),
+            title: format!("Task {task_id}"),
+            change,
+        }
+    }
+
+    #[test]
+    fn acknowledged_events_are_dropped_but_numbers_keep_counting() {
+        let temp = TempStore::new();
+        let store = temp.path();
+        record(store, &[entry("0", ActivityChange::Added)]).unwrap();
+        assert!(pending(store, None).unwrap().is_empty());
+        record(
+            store,
+            &[
+                entry("1", ActivityChange::Added),
+                entry("2", ActivityChange::Completed),
+            ],
+        )
+        .unwrap();
+        let first = pending(store, Some(1)).unwrap();
+        let dropped = acknowledge(store, first[0].seq).unwrap();
+        record(store, &[entry("3", ActivityChange::Removed)]).unwrap();
+        let rest = pending(store, None).unwrap();
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].kind, EventKind::TaskAdded);
+        assert_eq!(dropped, 1);
+        let seen: Vec<(u64, EventKind)> =
+            rest.iter().map(|event| (event.seq, event.kind)).collect();
+        assert_eq!(
+            seen,
+            [(2, EventKind::TaskCompleted), (3, EventKind::TaskRemoved)]
+        );
+        assert_eq!(
+            serde_json::to_value(&rest[1]).unwrap()["type"],
+            "task_removed"
+        );
+    }
+
+    #[test]
+    fn only_the_newest_events_are_kept() {
+        let temp = TempStore::new();
+        let store = temp.path();
+        pending(store, None).unwrap();
+        let entries: Vec<ActivityEntry> = (0..=MAX_EVENTS)
+            .map(|n| entry(&n.to_string(), ActivityChange::Updated))
+            .collect();
+        record(store, &entries).unwrap();
+        let events = pending(store, None).unwrap();
+
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert_eq!(events[0].seq, 2);
+    }
+}
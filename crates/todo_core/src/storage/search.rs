@@ -0,0 +1,382 @@
+//! Trigram index over task text for `search`, kept in `<store>.search.idx`.
+//!
+//! Each line maps one case-folded three-character sequence to the ids of the tasks whose title,
+//! notes, or tags contain it, sorted so lookups binary-search the file like
+//! [`crate::storage::index`] instead of reading the whole store. Every commit changes only the
+//! lines of the trigrams it added or removed. The header records the snapshot and journal the
+//! index describes; an index that does not match them is ignored until it is rebuilt.
+
+use crate::error::AppError;
+use crate::model::Task;
+use crate::storage::index;
+use crate::storage::journal;
+use crate::storage::json_store::TaskState;
+use crate::text::fold_case;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const HEADER_TAG: &str = "todoapp-search 1";
+/// Few enough candidates that checking each one beats parsing another list of ids.
+const NARROW_ENOUGH: usize = 20;
+
+#[derive(Default)]
+struct PostingChange<'a> {
+    removed: HashSet<&'a str>,
+    added: BTreeSet<&'a str>,
+}
+
+pub fn search_index_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".search.idx");
+    store_path.with_file_name(name)
+}
+
+/// The case-folded words of a search query; a task matches when its text contains all of them.
+pub fn query_terms(query: &str) -> Result<Vec<String>, AppError> {
+    let terms: Vec<String> = query.split_whitespace().map(fold_case).collect();
+    if terms.is_empty() {
+        return Err(AppError::invalid_input("search query is required"));
+    }
+    Ok(terms)
+}
+
+pub fn matches(task: &Task, terms: &[String]) -> bool {
+    let text = searchable_text(task);
+    terms.iter().all(|term| text.contains(term.as_str()))
+}
+
+/// Every trigram the terms contain. Terms shorter than three characters add none, so a query
+/// of only short words cannot use the index.
+pub(crate) fn query_trigrams(terms: &[String]) -> Vec<String> {
+    let grams: BTreeSet<String> = terms.iter().flat_map(|term| trigrams(term)).collect();
+    grams.into_iter().collect()
+}
+
+/// Title, notes, and tags, case-folded, one per line.
+fn searchable_text(task: &Task) -> String {
+    let mut text = fold_case(&task.title);
+    for part in task.notes.iter().chain(&task.tags) {
+        text.push('\n');
+        text.push_str(&fold_case(part));
+    }
+    text
+}
+
+/// Query terms never contain whitespace, so trigrams spanning it are left out.
+fn trigrams(text: &str) -> BTreeSet<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .windows(3)
+        .filter(|window| !window.iter().any(|ch| ch.is_whitespace()))
+        .map(|window| window.iter().collect())
+        .collect()
+}
+
+/// Writes the index for `state`, which must be what the store at `store_path` holds.
+pub(crate) fn build(store_path: &Path, state: &TaskState) -> Result<(), AppError> {
+    let mut postings: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+    for task in &state.tasks {
+        for gram in trigrams(&searchable_text(task)) {
+            postings.entry(gram).or_default().insert(task.id.as_str());
+        }
+    }
+
+    write_with(store_path, |writer| {
+        for (gram, ids) in &postings {
+            write_line(writer, gram, ids)?;
+        }
+        Ok(())
+    })
+}
+
+/// Brings a current index from `before` to `after`, which the store now holds.
+pub(crate) fn update(
+    store_path: &Path,
+    before: &TaskState,
+    after: &TaskState,
+) -> Result<(), AppError> {
+    let before_by_id: HashMap<&str, &Task> = before
+        .tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task))
+        .collect();
+    let mut changes: BTreeMap<String, PostingChange> = BTreeMap::new();
+    for task in &after.tasks {
+        let text = searchable_text(task);
+        let old = match before_by_id.get(task.id.as_str()) {
+            Some(previous) if searchable_text(previous) == text => continue,
+            Some(previous) => trigrams(&searchable_text(previous)),
+            None => BTreeSet::new(),
+        };
+        let new = trigrams(&text);
+        for gram in old.difference(&new) {
+            let change = changes.entry(gram.clone()).or_default();
+            change.removed.insert(task.id.as_str());
+        }
+        for gram in new.difference(&old) {
+            let change = changes.entry(gram.clone()).or_default();
+            change.added.insert(task.id.as_str());
+        }
+    }
+    let after_ids: HashSet<&str> = after.tasks.iter().map(|task| task.id.as_str()).collect();
+    for task in &before.tasks {
+        if !after_ids.contains(task.id.as_str()) {
+            for gram in trigrams(&searchable_text(task)) {
+                changes
+                    .entry(gram)
+                    .or_default()
+                    .removed
+                    .insert(task.id.as_str());
+            }
+        }
+    }
+
+    let file =
+        File::open(search_index_path(store_path)).map_err(|err| AppError::io(err.to_string()))?;
+    let mut lines = BufReader::new(file).lines();
+    // The old header.
+    lines
+        .next()
+        .transpose()
+        .map_err(|err| AppError::io(err.to_string()))?;
+
+    write_with(store_path, |writer| {
+        let mut pending = changes.into_iter().peekable();
+        for line in lines {
+            let line = line.map_err(|err| AppError::io(err.to_string()))?;
+            let gram = index::line_key(&line)?;
+            while let Some((new_gram, change)) = pending.next_if(|(next, _)| *next < gram) {
+                write_line(writer, &new_gram, &change.added)?;
+            }
+            match pending.next_if(|(next, _)| *next == gram) {
+                Some((_, change)) => {
+                    let mut ids = parse_ids(&line)?;
+                    ids.retain(|id| !change.removed.contains(id.as_str()));
+                    ids.extend(change.added.iter().map(|id| id.to_string()));
+                    write_line(writer, &gram, &ids)?;
+                }
+                None => writeln!(writer, "{line}").map_err(|err| AppError::io(err.to_string()))?,
+            }
+        }
+        for (gram, change) in pending {
+            write_line(writer, &gram, &change.added)?;
+        }
+        Ok(())
+    })
+}
+
+/// Whether the index describes the store as it is on disk now.
+pub(crate) fn is_current(store_path: &Path) -> Result<bool, AppError> {
+    let Ok(file) = File::open(search_index_path(store_path)) else {
+        return Ok(false);
+    };
+    let mut header = String::new();
+    BufReader::new(file)
+        .read_line(&mut header)
+        .map_err(|err| AppError::io(err.to_string()))?;
+    Ok(header.trim_end() == expected_header(store_path)?)
+}
+
+/// Ids of the tasks whose text may contain every one of `grams`, or `None` when the index is
+/// missing or stale. Narrowing stops once at most [`NARROW_ENOUGH`] ids are left, so callers
+/// must still check each task.
+pub(crate) fn candidates(
+    store_path: &Path,
+    grams: &[String],
+) -> Result<Option<BTreeSet<String>>, AppError> {
+    let Ok(file) = File::open(search_index_path(store_path)) else {
+        return Ok(None);
+    };
+    let end = file
+        .metadata()
+        .map_err(|err| AppError::io(err.to_string()))?
+        .len();
+    let mut reader = BufReader::new(file);
+    let mut header = String::new();
+    reader
+        .read_line(&mut header)
+        .map_err(|err| AppError::io(err.to_string()))?;
+    if header.trim_end() != expected_header(store_path)? {
+        return Ok(None);
+    }
+
+    let mut lines = Vec::with_capacity(grams.len());
+    for gram in grams {
+        match index::find_line(&mut reader, header.len() as u64, end, gram)? {
+            Some(line) => lines.push(line),
+            None => return Ok(Some(BTreeSet::new())),
+        }
+    }
+    // Rarest first: common trigrams have long lists that are costly to parse.
+    lines.sort_by_key(String::len);
+    let mut found: Option<BTreeSet<String>> = None;
+    for line in &lines {
+        if found
+            .as_ref()
+            .is_some_and(|found| found.len() <= NARROW_ENOUGH)
+        {
+            break;
+        }
+        let ids = parse_ids(line)?;
+        found = Some(match found {
+            Some(found) => found.intersection(&ids).cloned().collect(),
+            None => ids,
+        });
+    }
+    Ok(Some(found.unwrap_or_default()))
+}
+
+fn expected_header(store_path: &Path) -> Result<String, AppError> {
+    Ok(format!(
+        "{HEADER_TAG} {} {}",
+        index::fingerprint(store_path)?,
+        index::fingerprint(&journal::journal_path(store_path))?
+    ))
+}
+
+/// Atomically replaces the index with the current header followed by the lines `body` writes.
+fn write_with<F>(store_path: &Path, body: F) -> Result<(), AppError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), AppError>,
+{
+    let path = search_index_path(store_path);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let file = File::create(&temp_path).map_err(|err| AppError::io(err.to_string()))?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "{}", expected_header(store_path)?)
+        .map_err(|err| AppError::io(err.to_string()))?;
+    body(&mut writer)?;
+    writer
+        .into_inner()
+        .map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&temp_path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    std::fs::rename(&temp_path, &path).map_err(|err| AppError::io(err.to_string()))
+}
+
+/// Lines whose last id was removed are dropped.
+fn write_line<I, S>(writer: &mut BufWriter<File>, gram: &str, ids: I) -> Result<(), AppError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let ids: Vec<String> = ids.into_iter().map(|id| id.as_ref().to_string()).collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let gram =
+        serde_json::to_string(gram).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let ids = serde_json::to_string(&ids).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    writeln!(writer, "{gram}\t{ids}").map_err(|err| AppError::io(err.to_string()))
+}
+
+fn parse_ids(line: &str) -> Result<BTreeSet<String>, AppError> {
+    let invalid = || AppError::invalid_data("malformed search index line");
+    let (_, ids) = line.trim_end().split_once('\t').ok_or_else(invalid)?;
+    serde_json::from_str(ids).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build, candidates, is_current, matches, query_terms, query_trigrams, search_index_path,
+        update,
+    };
+    use crate::model::Task;
+    use crate::storage::json_store::{TaskState, save_state};
+    use crate::testing::{self, TempStore};
+    use std::collections::BTreeSet;
+
+    fn task(id: &str, title: &str) -> Task {
+        testing::task(id, title).build().unwrap()
+    }
+
+    fn state(tasks: Vec<Task>) -> TaskState {
+        TaskState {
+            tasks,
+            ..Default::default()
+        }
+    }
+
+    fn ids(values: &[&str]) -> Option<BTreeSet<String>> {
+        Some(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    #[test]
+    fn terms_match_title_notes_and_tags_ignoring_case() {
+        let mut task = task("1", "Renew Passport");
+        task.notes = Some("Bring two photos".to_string());
+        task.tags = vec!["Errands".to_string()];
+
+        assert!(matches(
+            &task,
+            &query_terms("passport PHOTOS errand").unwrap()
+        ));
+        assert!(!matches(&task, &query_terms("passport visa").unwrap()));
+        // Words never match across field boundaries.
+        assert!(!matches(&task, &query_terms("passportbring").unwrap()));
+        assert_eq!(query_terms("  ").unwrap_err().code(), "invalid_input");
+    }
+
+    #[test]
+    fn terms_match_non_ascii_text_in_any_case_or_composition() {
+        let mut task = task("1", "E\u{301}cole STRASSE: 東京駅で会う 🚆");
+        task.tags = vec!["ΤΑΞΊΔΙ".to_string()];
+
+        for query in ["\u{e9}cole", "ÉCOLE", "straße", "東京駅", "🚆", "ταξίδι"] {
+            assert!(
+                matches(&task, &query_terms(query).unwrap()),
+                "{query} should match"
+            );
+        }
+        assert!(!matches(&task, &query_terms("大阪").unwrap()));
+    }
+
+    #[test]
+    fn updates_keep_the_index_equal_to_a_rebuild() {
+        let store = TempStore::new();
+        let path = store.path();
+        let before = state(vec![
+            task("1", "Renew passport"),
+            task("2", "Buy milk"),
+            task("3", "Book passport photo"),
+        ]);
+        save_state(path, &before).unwrap();
+        build(path, &before).unwrap();
+        let grams = |query: &str| query_trigrams(&query_terms(query).unwrap());
+        let found_before = candidates(path, &grams("passport")).unwrap();
+
+        let mut after = before.clone();
+        after.tasks.remove(0);
+        after.tasks[0].title = "Buy oat milk".to_string();
+        after.tasks.push(task("4", "Passport renewal form"));
+        save_state(path, &after).unwrap();
+        assert!(!is_current(path).unwrap());
+        update(path, &before, &after).unwrap();
+        let updated = std::fs::read_to_string(search_index_path(path)).unwrap();
+        let found_after = candidates(path, &grams("passport")).unwrap();
+        let oat = candidates(path, &grams("oat milk")).unwrap();
+        build(path, &after).unwrap();
+        let rebuilt = std::fs::read_to_string(search_index_path(path)).unwrap();
+
+        assert_eq!(found_before, ids(&["1", "3"]));
+        assert_eq!(found_after, ids(&["3", "4"]));
+        assert_eq!(oat, ids(&["2"]));
+        assert_eq!(updated, rebuilt);
+    }
+}
@@ -0,0 +1,49 @@
+//! Rules added with `seed --rule`, kept in `<store>.seeds.json`.
+//!
+//! Each rule remembers when it was added and the last time it fell due and added its task, so
+//! `seed run` adds a task only for a due time after both, however often it runs.
+
+use crate::error::AppError;
+use crate::storage::activity;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredSeed {
+    /// The rule as [`crate::seed::SeedRule`] prints it.
+    pub rule: String,
+    /// RFC3339.
+    pub added_at: String,
+    /// The due time of the last task this rule added (RFC3339).
+    #[serde(default)]
+    pub last_seeded_at: Option<String>,
+}
+
+pub fn seeds_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".seeds.json");
+    store_path.with_file_name(name)
+}
+
+/// The rules in the order they were added; none when the file does not exist.
+pub fn read(store_path: &Path) -> Result<Vec<StoredSeed>, AppError> {
+    let path = seeds_path(store_path);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display()))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(AppError::io(err.to_string())),
+    }
+}
+
+pub fn write(store_path: &Path, seeds: &[StoredSeed]) -> Result<(), AppError> {
+    if let Some(parent) = store_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| AppError::io(err.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(seeds)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    activity::write_private(&seeds_path(store_path), &content)
+}
@@ -0,0 +1,254 @@
+//! Small derived summary of the store in `<store>.status.json`.
+//!
+//! Status bars poll this file instead of parsing the full store or running the binary. It is
+//! rewritten after every commit to the live store and replaced atomically, so a reader never
+//! sees a partial file. Overdue is not stored because it changes with the clock; a pending
+//! task is overdue once `next_due` has passed. The weekly completion count is stored with its
+//! week, so a reader can tell when a new week has started since.
+
+use crate::clock;
+use crate::error::AppError;
+use crate::model::Task;
+use crate::period::Period;
+use crate::stats;
+use crate::storage::json_store::TaskState;
+use crate::task_api;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Duration, OffsetDateTime, UtcOffset};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusSummary {
+    /// When the summary was written (RFC3339, UTC).
+    pub updated_at: String,
+    /// Tasks not completed yet, including ones in progress or waiting.
+    pub pending: usize,
+    /// Pending tasks marked urgent.
+    pub urgent: usize,
+    pub completed: usize,
+    /// Earliest `due_at` among pending tasks.
+    pub next_due: Option<String>,
+    pub next_due_title: Option<String>,
+    pub focused_title: Option<String>,
+    /// Completions during `week`, for `goals.completions_per_week`.
+    #[serde(default)]
+    pub completed_this_week: usize,
+    /// The local ISO week `completed_this_week` counts, such as `2026-W07`.
+    #[serde(default)]
+    pub week: Option<String>,
+    /// `due_at` of each pending urgent task that has one, so the stale-notification hint can
+    /// count the overdue ones without loading the store.
+    #[serde(default)]
+    pub urgent_due: Vec<String>,
+    /// When `notify` last sent anything, as in the store.
+    #[serde(default)]
+    pub last_notified_at: Option<String>,
+}
+
+impl StatusSummary {
+    /// `now` is in local time, which decides the week `completed_this_week` counts.
+    pub fn from_state(state: &TaskState, now: OffsetDateTime) -> Result<Self, AppError> {
+        let pending: Vec<&Task> = state
+            .tasks
+            .iter()
+            .filter(|task| task.status.is_open())
+            .collect();
+
+        let mut next_due: Option<(OffsetDateTime, &Task)> = None;
+        for task in &pending {
+            let Some(scheduled_at) = task.due_at.as_deref() else {
+                continue;
+            };
+            let scheduled = OffsetDateTime::parse(scheduled_at, &Rfc3339)
+                .map_err(|_| AppError::invalid_data("due_at must be RFC3339"))?;
+            if next_due.is_none_or(|(earliest, _)| scheduled < earliest) {
+                next_due = Some((scheduled, task));
+            }
+        }
+        let focused_title = state.focused_task_id.as_deref().and_then(|id| {
+            state
+                .tasks
+                .iter()
+                .find(|task| task.id == id)
+                .map(|task| task.title.clone())
+        });
+
+        let week = Period::week_of(now.date());
+        Ok(StatusSummary {
+            updated_at: now
+                .to_offset(UtcOffset::UTC)
+                .format(&Rfc3339)
+                .map_err(|err| AppError::invalid_data(err.to_string()))?,
+            pending: pending.len(),
+            urgent: pending.iter().filter(|task| task.urgent).count(),
+            completed: state.tasks.len() - pending.len(),
+            next_due: next_due.and_then(|(_, task)| task.due_at.clone()),
+            next_due_title: next_due.map(|(_, task)| task.title.clone()),
+            focused_title,
+            completed_this_week: stats::completions_in(&state.tasks, week, now.offset()),
+            week: Some(week.to_string()),
+            urgent_due: pending
+                .iter()
+                .filter(|task| task.urgent)
+                .filter_map(|task| task.due_at.clone())
+                .collect(),
+            last_notified_at: state.last_notified_at.clone(),
+        })
+    }
+
+    /// `completed_this_week` while `today` is still in that week; a summary last written in an
+    /// earlier week has nothing for this one yet.
+    pub fn completed_in_week_of(&self, today: Date) -> usize {
+        let current = Period::week_of(today).to_string();
+        if self.week.as_deref() == Some(current.as_str()) {
+            self.completed_this_week
+        } else {
+            0
+        }
+    }
+
+    /// Whether the earliest scheduled pending task is more than `grace` past due at `now`.
+    pub fn overdue(&self, now: OffsetDateTime, grace: Duration) -> bool {
+        self.next_due
+            .as_deref()
+            .and_then(|next_due| OffsetDateTime::parse(next_due, &Rfc3339).ok())
+            .is_some_and(|next_due| task_api::overdue_at(next_due, now, grace))
+    }
+}
+
+pub fn status_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".status.json");
+    store_path.with_file_name(name)
+}
+
+/// The summary last written for the store, or `None` before its first commit.
+pub fn read(store_path: &Path) -> Result<Option<StatusSummary>, AppError> {
+    let path = status_path(store_path);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display())))
+}
+
+pub(crate) fn write(store_path: &Path, state: &TaskState) -> Result<(), AppError> {
+    let now = clock::now_utc().to_offset(task_api::local_offset()?);
+    let summary = StatusSummary::from_state(state, now)?;
+    let content = serde_json::to_string_pretty(&summary)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+
+    let path = status_path(store_path);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    std::fs::write(&temp_path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&temp_path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    std::fs::rename(&temp_path, &path).map_err(|err| AppError::io(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatusSummary;
+    use crate::model::{Task, TaskStatus};
+    use crate::storage::json_store::TaskState;
+    use time::Duration;
+    use time::macros::datetime;
+
+    fn task(id: &str, scheduled_at: Option<&str>, urgent: bool, status: TaskStatus) -> Task {
+        Task {
+            id: id.to_string(),
+            title: format!("Task {id}"),
+            status,
+            created_at: "2025-12-01T00:00:00Z".to_string(),
+            due_at: scheduled_at.map(str::to_string),
+            urgent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn summary_counts_tasks_and_finds_next_due() {
+        let state = TaskState {
+            tasks: vec![
+                task("a", Some("2025-12-22T09:00:00Z"), true, TaskStatus::Pending),
+                task(
+                    "b",
+                    Some("2025-12-21T10:00:00+02:00"),
+                    false,
+                    TaskStatus::Pending,
+                ),
+                task(
+                    "c",
+                    Some("2025-12-01T00:00:00Z"),
+                    true,
+                    TaskStatus::Completed,
+                ),
+                task("d", None, false, TaskStatus::Pending),
+            ],
+            focused_task_id: Some("d".to_string()),
+            ..Default::default()
+        };
+
+        let summary = StatusSummary::from_state(&state, datetime!(2025-12-20 12:00 UTC)).unwrap();
+
+        assert_eq!(summary.updated_at, "2025-12-20T12:00:00Z");
+        assert_eq!(
+            (summary.pending, summary.urgent, summary.completed),
+            (3, 1, 1)
+        );
+        assert_eq!(
+            summary.next_due.as_deref(),
+            Some("2025-12-21T10:00:00+02:00")
+        );
+        assert_eq!(summary.next_due_title.as_deref(), Some("Task b"));
+        assert_eq!(summary.focused_title.as_deref(), Some("Task d"));
+    }
+
+    #[test]
+    fn empty_store_has_no_next_due_or_focus() {
+        let summary =
+            StatusSummary::from_state(&TaskState::default(), datetime!(2025-12-20 12:00 UTC))
+                .unwrap();
+
+        assert_eq!(
+            (summary.pending, summary.urgent, summary.completed),
+            (0, 0, 0)
+        );
+        assert!(summary.next_due.is_none() && summary.focused_title.is_none());
+        assert!(!summary.overdue(datetime!(2025-12-20 12:00 UTC), Duration::ZERO));
+    }
+
+    #[test]
+    fn overdue_once_next_due_has_passed() {
+        let state = TaskState {
+            tasks: vec![task(
+                "a",
+                Some("2025-12-20T09:00:00Z"),
+                false,
+                TaskStatus::Pending,
+            )],
+            ..Default::default()
+        };
+        let summary = StatusSummary::from_state(&state, datetime!(2025-12-20 08:00 UTC)).unwrap();
+
+        assert!(!summary.overdue(datetime!(2025-12-20 08:59 UTC), Duration::ZERO));
+        assert!(summary.overdue(datetime!(2025-12-20 09:01 UTC), Duration::ZERO));
+        assert!(!summary.overdue(datetime!(2025-12-20 09:01 UTC), Duration::minutes(5)));
+    }
+}
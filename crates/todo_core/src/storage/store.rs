@@ -0,0 +1,103 @@
+//! Where [`crate::task_api::transaction_in`] loads the tasks from and commits them to.
+//!
+//! [`FileStore`] is the store file with its journal and sidecar files, as every command uses
+//! it. [`MemoryStore`] holds the tasks in memory and touches neither the file system nor the
+//! environment, for hosts that have neither, such as a web UI built for WebAssembly; the host
+//! keeps [`MemoryStore::to_json`] wherever it likes and hands it back to
+//! [`MemoryStore::from_json`].
+
+use crate::error::AppError;
+use crate::origin;
+use crate::storage::json_store::{self, TaskState};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+pub trait TaskStore {
+    fn load(&self) -> Result<TaskState, AppError>;
+    /// Persist the change from `before`, as [`TaskStore::load`] returned it, to `after`.
+    fn commit(&self, before: &TaskState, after: &TaskState) -> Result<(), AppError>;
+    /// Who is making the change, stamped as `modified_by` on each task it touches; `None`
+    /// leaves that unset.
+    fn author(&self) -> Option<String>;
+}
+
+/// The store file at a path; see [`json_store::commit_state`] for what a commit writes.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TaskStore for FileStore {
+    fn load(&self) -> Result<TaskState, AppError> {
+        json_store::load_state(&self.path)
+    }
+
+    fn commit(&self, before: &TaskState, after: &TaskState) -> Result<(), AppError> {
+        json_store::commit_state(&self.path, before, after)
+    }
+
+    fn author(&self) -> Option<String> {
+        Some(origin::current())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: RefCell<TaskState>,
+    author: Option<String>,
+}
+
+impl MemoryStore {
+    pub fn new(state: TaskState) -> Self {
+        Self {
+            state: RefCell::new(state),
+            author: None,
+        }
+    }
+
+    /// Stamp changes as made by `author`, such as the signed-in user of a web UI. Without one,
+    /// changed tasks carry no `modified_by`.
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Read a store file's contents, in any schema the file store reads.
+    pub fn from_json(content: &str) -> Result<Self, AppError> {
+        json_store::parse_state(content).map(Self::new)
+    }
+
+    /// The tasks as a store file in the current schema, which the CLI can also open.
+    pub fn to_json(&self) -> Result<String, AppError> {
+        json_store::render_state(&self.state.borrow())
+    }
+
+    pub fn state(&self) -> TaskState {
+        self.state.borrow().clone()
+    }
+}
+
+impl TaskStore for MemoryStore {
+    fn load(&self) -> Result<TaskState, AppError> {
+        Ok(self.state())
+    }
+
+    fn commit(&self, _before: &TaskState, after: &TaskState) -> Result<(), AppError> {
+        *self.state.borrow_mut() = after.clone();
+        Ok(())
+    }
+
+    fn author(&self) -> Option<String> {
+        self.author.clone()
+    }
+}
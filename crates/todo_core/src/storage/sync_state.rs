@@ -0,0 +1,97 @@
+//! What each remote held when it was last synced, kept in `<store>.sync.json`.
+//!
+//! Sync compares both sides with this copy to tell a change made here from one made on the
+//! remote; see [`crate::sync`].
+
+use crate::error::AppError;
+use crate::model::Task;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// By remote name, as configured under `remotes`.
+    #[serde(default)]
+    pub remotes: BTreeMap<String, SyncedRemote>,
+    /// Conflicts found by a sync that did not finish, by remote name. Cleared by the next
+    /// sync with that remote that does.
+    #[serde(default)]
+    pub conflicts: BTreeMap<String, Vec<UnsettledConflict>>,
+    /// Remotes the last sync could not reach, by name. Changes made here since the last sync
+    /// with one stay queued until `sync flush` or a sync reaches it again.
+    #[serde(default)]
+    pub unreachable: BTreeMap<String, Unreachable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedRemote {
+    /// When the last sync finished (RFC3339, UTC).
+    pub synced_at: String,
+    /// The tasks both sides held after it.
+    pub tasks: Vec<Task>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsettledConflict {
+    pub task_id: String,
+    /// The title here.
+    pub title: String,
+    /// Fields changed differently on both sides.
+    pub fields: Vec<String>,
+    /// When the sync that found it stopped (RFC3339, UTC).
+    pub found_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Unreachable {
+    /// When a sync first failed to reach it (RFC3339, UTC).
+    pub since: String,
+    /// Why the latest attempt failed.
+    pub error: String,
+}
+
+pub fn sync_state_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".sync.json");
+    store_path.with_file_name(name)
+}
+
+/// Empty before the first sync.
+pub fn read(store_path: &Path) -> Result<SyncState, AppError> {
+    let path = sync_state_path(store_path);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(SyncState::default());
+        }
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+    serde_json::from_str(&content)
+        .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display())))
+}
+
+#[cfg(feature = "sync")]
+pub(crate) fn write(store_path: &Path, state: &SyncState) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+
+    let path = sync_state_path(store_path);
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    std::fs::write(&temp_path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&temp_path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    std::fs::rename(&temp_path, &path).map_err(|err| AppError::io(err.to_string()))
+}
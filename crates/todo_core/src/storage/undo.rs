@@ -0,0 +1,62 @@
+//! The most recent completion, kept in `<store>.undo.json` so `undo` can revert it without a
+//! full change history. Each completion replaces the previous record.
+
+use crate::error::AppError;
+use crate::model::Task;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CompletionUndo {
+    /// The task as it was before `done`.
+    pub before: Box<Task>,
+    /// The task as `done` left it; the undo only applies while it is unchanged.
+    pub after: Box<Task>,
+    /// The task was focused, so reverting restores focus.
+    pub was_focused: bool,
+}
+
+pub(crate) fn undo_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".undo.json");
+    store_path.with_file_name(name)
+}
+
+pub(crate) fn write(store_path: &Path, undo: &CompletionUndo) -> Result<(), AppError> {
+    let content =
+        serde_json::to_string(undo).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let path = undo_path(store_path);
+    std::fs::write(&path, content).map_err(|err| AppError::io(err.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(&path, permissions)
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read(store_path: &Path) -> Result<Option<CompletionUndo>, AppError> {
+    let content = match std::fs::read_to_string(undo_path(store_path)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(AppError::io(err.to_string())),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|err| AppError::invalid_data(format!("undo record: {err}")))
+}
+
+pub(crate) fn remove(store_path: &Path) -> Result<(), AppError> {
+    match std::fs::remove_file(undo_path(store_path)) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(AppError::io(err.to_string())),
+    }
+}
@@ -0,0 +1,231 @@
+//! Store events waiting to be POSTed to `webhooks`, kept in `<store>.webhooks.json`.
+//!
+//! The file also holds the webhooks themselves, as [`subscribe`] last set them, so every
+//! commit to the store queues one delivery per event and interested webhook. Deliveries leave the
+//! queue once a webhook accepts them; the rest stay, with their attempts and last error, for
+//! the next `webhooks deliver`. Only the newest [`MAX_DELIVERIES`] are kept.
+
+use crate::config::WebhookConfig;
+use crate::error::AppError;
+use crate::storage::activity::{self, ActivityEntry};
+use crate::storage::outbox::OutboxEvent;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const MAX_DELIVERIES: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delivery {
+    /// Unique within the queue, so a delivery sent while others were queued can be removed.
+    pub id: u64,
+    pub url: String,
+    pub event: OutboxEvent,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Queue {
+    next_id: u64,
+    /// Numbers the events themselves; every webhook gets the same `seq` for one event.
+    next_seq: u64,
+    /// Who later commits queue deliveries for; see [`subscribe`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    webhooks: Vec<WebhookConfig>,
+    deliveries: Vec<Delivery>,
+}
+
+pub fn webhook_queue_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".webhooks.json");
+    store_path.with_file_name(name)
+}
+
+fn read(store_path: &Path) -> Result<Queue, AppError> {
+    let path = webhook_queue_path(store_path);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|err| AppError::invalid_data(format!("{}: {err}", path.display()))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Queue {
+            next_id: 1,
+            next_seq: 1,
+            webhooks: Vec::new(),
+            deliveries: Vec::new(),
+        }),
+        Err(err) => Err(AppError::io(err.to_string())),
+    }
+}
+
+fn write(store_path: &Path, queue: &Queue) -> Result<(), AppError> {
+    let content =
+        serde_json::to_string(queue).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    activity::write_private(&webhook_queue_path(store_path), &content)
+}
+
+/// Queue deliveries to `webhooks` for every later commit to the store, from any process,
+/// until another call changes them. The CLI calls it with the `webhooks` config on startup;
+/// the file is only rewritten when they change.
+pub fn subscribe(store_path: &Path, webhooks: &[WebhookConfig]) -> Result<(), AppError> {
+    let mut queue = read(store_path)?;
+    if queue.webhooks == webhooks {
+        return Ok(());
+    }
+    queue.webhooks = webhooks.to_vec();
+    if let Some(parent) = store_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| AppError::io(err.to_string()))?;
+    }
+    write(store_path, &queue)
+}
+
+/// Queue the changes of one commit for the webhooks set with [`subscribe`].
+pub(crate) fn record(store_path: &Path, entries: &[ActivityEntry]) -> Result<(), AppError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut queue = read(store_path)?;
+    if queue.webhooks.is_empty() {
+        return Ok(());
+    }
+    let webhooks = queue.webhooks.clone();
+    let before = queue.deliveries.len();
+    for entry in entries {
+        let event = OutboxEvent {
+            seq: queue.next_seq,
+            kind: entry.change.into(),
+            at: entry.at.clone(),
+            by: entry.by.clone(),
+            task_id: entry.task_id.clone(),
+            title: entry.title.clone(),
+        };
+        queue.next_seq += 1;
+        for webhook in webhooks.iter().filter(|webhook| webhook.wants(event.kind)) {
+            queue.deliveries.push(Delivery {
+                id: queue.next_id,
+                url: webhook.url.clone(),
+                event: event.clone(),
+                attempts: 0,
+                last_error: None,
+            });
+            queue.next_id += 1;
+        }
+    }
+    if queue.deliveries.len() == before {
+        return Ok(());
+    }
+    let start = queue.deliveries.len().saturating_sub(MAX_DELIVERIES);
+    queue.deliveries.drain(..start);
+    write(store_path, &queue)
+}
+
+/// Every queued delivery, oldest first.
+pub fn pending(store_path: &Path) -> Result<Vec<Delivery>, AppError> {
+    Ok(read(store_path)?.deliveries)
+}
+
+/// Drop the deliveries in `delivered` and note one more attempt, with its error, on each of
+/// `failed`. Deliveries queued meanwhile are kept as they are.
+pub(crate) fn settle(
+    store_path: &Path,
+    delivered: &[u64],
+    failed: &[(u64, String)],
+) -> Result<(), AppError> {
+    if delivered.is_empty() && failed.is_empty() {
+        return Ok(());
+    }
+    let mut queue = read(store_path)?;
+    queue
+        .deliveries
+        .retain(|delivery| !delivered.contains(&delivery.id));
+    for delivery in &mut queue.deliveries {
+        if let Some((_, error)) = failed.iter().find(|(id, _)| *id == delivery.id) {
+            delivery.attempts += 1;
+            delivery.last_error = Some(error.clone());
+        }
+    }
+    write(store_path, &queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pending, record, settle, subscribe};
+    use crate::config::WebhookConfig;
+    use crate::storage::activity::{ActivityChange, ActivityEntry};
+    use crate::storage::outbox::EventKind;
+    use crate::testing::TempStore;
+
+    fn entry(task_id: &str, change: ActivityChange) -> ActivityEntry {
+        ActivityEntry {
+            at: "2025-12-20T12:00:00Z".to_string(),
+            by: "ana@laptop".to_string(),
+            task_id: task_id.to_string(),
+            title: format!("Task {task_id}"),
+            change,
+        }
+    }
+
+    #[test]
+    fn each_event_is_queued_for_the_webhooks_that_want_it() {
+        let temp = TempStore::new();
+        let store = temp.path();
+        let webhooks = [
+            WebhookConfig {
+                url: "https://hooks.example.com/all".to_string(),
+                events: Vec::new(),
+            },
+            WebhookConfig {
+                url: "https://hooks.example.com/done".to_string(),
+                events: vec![EventKind::TaskCompleted],
+            },
+        ];
+
+        record(store, &[entry("1", ActivityChange::Added)]).unwrap();
+        assert!(pending(store).unwrap().is_empty());
+        subscribe(store, &webhooks).unwrap();
+        record(
+            store,
+            &[
+                entry("1", ActivityChange::Added),
+                entry("2", ActivityChange::Completed),
+            ],
+        )
+        .unwrap();
+        let queued = pending(store).unwrap();
+        settle(
+            store,
+            &[queued[0].id],
+            &[(queued[1].id, "HTTP 500".to_string())],
+        )
+        .unwrap();
+        let left = pending(store).unwrap();
+
+        let routed: Vec<(&str, u64, EventKind)> = queued
+            .iter()
+            .map(|delivery| {
+                (
+                    delivery.url.as_str(),
+                    delivery.event.seq,
+                    delivery.event.kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            routed,
+            [
+                ("https://hooks.example.com/all", 1, EventKind::TaskAdded),
+                ("https://hooks.example.com/all", 2, EventKind::TaskCompleted),
+                (
+                    "https://hooks.example.com/done",
+                    2,
+                    EventKind::TaskCompleted
+                ),
+            ]
+        );
+        assert_eq!(left.len(), 2);
+        assert_eq!(left[0].attempts, 1);
+        assert_eq!(left[0].last_error.as_deref(), Some("HTTP 500"));
+        assert_eq!(left[1].attempts, 0);
+    }
+}
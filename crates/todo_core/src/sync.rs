@@ -0,0 +1,574 @@
+//! Two-way sync between the store and a remote copy of its tasks.
+//!
+//! A connector only loads and saves the remote's tasks through [`Remote`]; what goes where is
+//! worked out here, the same way for every connector. Both sides are compared with the tasks
+//! they held after the last sync ([`crate::storage::sync_state`]). A task changed on one side
+//! only is copied to the other. A task changed differently on both sides is a [`Conflict`],
+//! settled by the caller with a [`Resolution`], either by asking or by a [`Prefer`] rule.
+
+use crate::config::{RemoteConfig, SyncField};
+use crate::diff::{self, FieldChange};
+use crate::error::AppError;
+use crate::filter::Filter;
+use crate::model::Task;
+use crate::storage::json_store;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+/// Stamped on every save, so they differ whenever both sides changed a task; never a
+/// conflict on their own.
+const STAMP_FIELDS: [&str; 2] = ["modified_by", "modified_at"];
+
+pub trait Remote {
+    fn load(&self) -> Result<Vec<Task>, AppError>;
+    /// Replace the remote's tasks with `tasks`.
+    fn save(&self, tasks: &[Task]) -> Result<(), AppError>;
+}
+
+/// A store file elsewhere, such as in a folder shared between machines. It is written like
+/// the live store, so it also works as one with `TODOAPP_STORE_PATH`. The file is created by
+/// the first sync, but its folder must exist: while it is missing, such as when a share is
+/// not mounted, the remote is unreachable rather than empty.
+pub struct FileRemote {
+    path: PathBuf,
+}
+
+impl FileRemote {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl Remote for FileRemote {
+    fn load(&self) -> Result<Vec<Task>, AppError> {
+        if let Some(folder) = self
+            .path
+            .parent()
+            .filter(|folder| !folder.as_os_str().is_empty())
+            && !folder.is_dir()
+        {
+            return Err(AppError::io(format!(
+                "{} is not reachable",
+                folder.display()
+            )));
+        }
+        json_store::load_tasks(&self.path)
+    }
+
+    fn save(&self, tasks: &[Task]) -> Result<(), AppError> {
+        json_store::save_tasks(&self.path, tasks)
+    }
+}
+
+/// The connector for a configured remote.
+pub fn remote_for(config: &RemoteConfig) -> Box<dyn Remote> {
+    Box::new(FileRemote::new(&config.path))
+}
+
+/// What a sync changes on each side.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SyncPlan {
+    /// Tasks added or changed here since the last sync, to copy to the remote.
+    pub push: Vec<Task>,
+    /// Tasks added or changed on the remote, to copy here.
+    pub pull: Vec<Task>,
+    /// Tasks deleted here, to delete on the remote.
+    pub delete_remote: Vec<Task>,
+    /// Tasks deleted on the remote, to delete here.
+    pub delete_local: Vec<Task>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.push.is_empty()
+            && self.pull.is_empty()
+            && self.delete_remote.is_empty()
+            && self.delete_local.is_empty()
+            && self.conflicts.is_empty()
+    }
+}
+
+/// A task changed differently here and on the remote since the last sync.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub local: Task,
+    pub remote: Task,
+    /// The fields that differ, with the local value as `before` and the remote one as `after`.
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Local,
+    Remote,
+    /// The local task with these fields taken from the remote one.
+    Merge(Vec<String>),
+}
+
+/// A rule that settles every conflict the same way, for runs nobody answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prefer {
+    Local,
+    Remote,
+    /// The side changed last, by `modified_at`. The local task wins a tie.
+    Newest,
+}
+
+impl Prefer {
+    pub fn resolve(self, conflict: &Conflict) -> Resolution {
+        match self {
+            Prefer::Local => Resolution::Local,
+            Prefer::Remote => Resolution::Remote,
+            Prefer::Newest if modified(&conflict.remote) > modified(&conflict.local) => {
+                Resolution::Remote
+            }
+            Prefer::Newest => Resolution::Local,
+        }
+    }
+}
+
+fn modified(task: &Task) -> Option<OffsetDateTime> {
+    let at = task.modified_at.as_deref().unwrap_or(&task.created_at);
+    OffsetDateTime::parse(at, &Rfc3339).ok()
+}
+
+impl Conflict {
+    /// The task both sides keep. A merge is a new version of the task, so it is stamped with
+    /// `by` and `at` like any other change.
+    pub fn resolved(&self, resolution: &Resolution, by: &str, at: &str) -> Result<Task, AppError> {
+        let fields = match resolution {
+            Resolution::Local => return Ok(self.local.clone()),
+            Resolution::Remote => return Ok(self.remote.clone()),
+            Resolution::Merge(fields) => fields,
+        };
+        let mut merged = diff::task_fields(&self.local)?;
+        let remote = diff::task_fields(&self.remote)?;
+        for field in fields {
+            if !self.fields.iter().any(|change| change.field == *field) {
+                return Err(AppError::invalid_input(format!(
+                    "field '{field}' is the same on both sides of task {}",
+                    self.local.id
+                )));
+            }
+            match remote.get(field) {
+                Some(value) => merged.insert(field.clone(), value.clone()),
+                None => merged.remove(field),
+            };
+        }
+        let mut task: Task = serde_json::from_value(Value::Object(merged))
+            .map_err(|err| AppError::invalid_data(err.to_string()))?;
+        task.modified_by = Some(by.to_string());
+        task.modified_at = Some(at.to_string());
+        Ok(task)
+    }
+}
+
+/// Compare both sides with `base`, what they held after the last sync. Before the first sync
+/// `base` is empty, so a task on one side only is copied and one that differs is a conflict.
+pub fn plan(base: &[Task], local: &[Task], remote: &[Task]) -> Result<SyncPlan, AppError> {
+    let base_by_id = by_id(base);
+    let local_by_id = by_id(local);
+    let remote_by_id = by_id(remote);
+
+    let mut seen = HashSet::new();
+    let mut plan = SyncPlan::default();
+    for id in local
+        .iter()
+        .chain(remote)
+        .chain(base)
+        .map(|task| task.id.as_str())
+    {
+        if !seen.insert(id) {
+            continue;
+        }
+        let before = base_by_id.get(id).copied();
+        let here = local_by_id.get(id).copied();
+        let there = remote_by_id.get(id).copied();
+        if here == there {
+            continue;
+        }
+        let changed_here = here != before;
+        let changed_there = there != before;
+        match (here, there) {
+            (Some(here), Some(there)) if changed_here && changed_there => {
+                let conflict = Conflict {
+                    local: here.clone(),
+                    remote: there.clone(),
+                    fields: diff::field_changes(here, there)?
+                        .into_iter()
+                        .filter(|change| !STAMP_FIELDS.contains(&change.field.as_str()))
+                        .collect(),
+                };
+                // The same edit made on both sides: keep the later stamp without asking.
+                if !conflict.fields.is_empty() {
+                    plan.conflicts.push(conflict);
+                } else if Prefer::Newest.resolve(&conflict) == Resolution::Remote {
+                    plan.pull.push(conflict.remote);
+                } else {
+                    plan.push.push(conflict.local);
+                }
+            }
+            (Some(here), Some(_)) if changed_here => plan.push.push(here.clone()),
+            (Some(_), Some(there)) => plan.pull.push(there.clone()),
+            // Deleted on one side: follow the deletion, unless the other side has changed the
+            // task since; then the changed task is kept.
+            (Some(here), None) if changed_here => plan.push.push(here.clone()),
+            (Some(here), None) => plan.delete_local.push(here.clone()),
+            (None, Some(there)) if changed_there => plan.pull.push(there.clone()),
+            (None, Some(there)) => plan.delete_remote.push(there.clone()),
+            (None, None) => {}
+        }
+    }
+    Ok(plan)
+}
+
+/// How many tasks one side added, changed, or deleted since `base`.
+pub fn changes_since(base: &[Task], tasks: &[Task]) -> Result<usize, AppError> {
+    Ok(queued_changes(base, tasks)?.len())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Changed,
+    Deleted,
+}
+
+/// A change made here since the last sync with a remote, waiting for the next one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedChange {
+    pub kind: ChangeKind,
+    /// The task as it is now, or as it was last synced when deleted.
+    pub task: Task,
+}
+
+/// The tasks one side added, changed, or deleted since `base`, in the order of `tasks` with
+/// deletions last.
+pub fn queued_changes(base: &[Task], tasks: &[Task]) -> Result<Vec<QueuedChange>, AppError> {
+    let plan = plan(base, tasks, base)?;
+    let base_by_id = by_id(base);
+    let changed = plan.push.into_iter().map(|task| QueuedChange {
+        kind: if base_by_id.contains_key(task.id.as_str()) {
+            ChangeKind::Changed
+        } else {
+            ChangeKind::Added
+        },
+        task,
+    });
+    let deleted = plan.delete_remote.into_iter().map(|task| QueuedChange {
+        kind: ChangeKind::Deleted,
+        task,
+    });
+    Ok(changed.chain(deleted).collect())
+}
+
+fn by_id(tasks: &[Task]) -> HashMap<&str, &Task> {
+    let mut by_id = HashMap::new();
+    for task in tasks {
+        by_id.entry(task.id.as_str()).or_insert(task);
+    }
+    by_id
+}
+
+/// Both sides after a sync. They hold the same tasks, each side in its own order with tasks
+/// new to it at the end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Synced {
+    pub local: Vec<Task>,
+    pub remote: Vec<Task>,
+}
+
+/// Carry out `plan` on all of each side's tasks, with `resolved` holding the task kept for
+/// each of its conflicts. A task replaced on either side keeps that side's values of the
+/// fields `scope` leaves out.
+pub fn apply(
+    plan: &SyncPlan,
+    resolved: &[Task],
+    local: &[Task],
+    remote: &[Task],
+    scope: &SyncScope,
+) -> Synced {
+    Synced {
+        local: update(
+            local,
+            plan.pull.iter().chain(resolved),
+            &plan.delete_local,
+            scope,
+        ),
+        remote: update(
+            remote,
+            plan.push.iter().chain(resolved),
+            &plan.delete_remote,
+            scope,
+        ),
+    }
+}
+
+fn update<'a>(
+    tasks: &[Task],
+    changed: impl Iterator<Item = &'a Task>,
+    deleted: &[Task],
+    scope: &SyncScope,
+) -> Vec<Task> {
+    let mut updated: Vec<Task> = tasks
+        .iter()
+        .filter(|task| !deleted.iter().any(|gone| gone.id == task.id))
+        .cloned()
+        .collect();
+    for task in changed {
+        match updated.iter_mut().find(|existing| existing.id == task.id) {
+            Some(existing) => *existing = scope.keep_excluded(task, existing),
+            None => updated.push(task.clone()),
+        }
+    }
+    updated
+}
+
+/// Which tasks a remote shares and which of their fields it never gets, from its `filter` and
+/// `exclude_fields` config. Sync plans, conflicts, and the stored last-sync copy only ever see
+/// tasks through [`SyncScope::local_view`] and [`SyncScope::remote_view`], so nothing outside
+/// the scope reaches the remote.
+#[derive(Debug, Clone, Default)]
+pub struct SyncScope {
+    filter: Option<Filter>,
+    excluded: Vec<SyncField>,
+    /// Allowed past a deadline before `status:overdue` in the filter matches.
+    overdue_grace: Duration,
+}
+
+impl SyncScope {
+    pub fn from_config(
+        name: &str,
+        config: &RemoteConfig,
+        overdue_grace: Duration,
+    ) -> Result<Self, AppError> {
+        let filter = config
+            .filter
+            .as_deref()
+            .map(Filter::parse)
+            .transpose()
+            .map_err(|err| {
+                AppError::invalid_input(format!("remotes.{name}.filter: {}", err.message()))
+            })?;
+        Ok(Self {
+            filter,
+            excluded: config.exclude_fields.clone(),
+            overdue_grace,
+        })
+    }
+
+    /// The local tasks in scope, without the excluded fields.
+    pub fn local_view(&self, local: &[Task], now: OffsetDateTime) -> Vec<Task> {
+        local
+            .iter()
+            .filter(|task| self.includes(task, now))
+            .map(|task| self.strip(task))
+            .collect()
+    }
+
+    /// The remote tasks in scope, without the excluded fields. Whether a task is in scope is
+    /// decided by its local copy when there is one, so a task that leaves the scope here
+    /// stops syncing instead of looking deleted.
+    pub fn remote_view(&self, remote: &[Task], local: &[Task], now: OffsetDateTime) -> Vec<Task> {
+        let local_by_id = by_id(local);
+        remote
+            .iter()
+            .filter(|task| {
+                let judged = local_by_id.get(task.id.as_str()).copied().unwrap_or(task);
+                self.includes(judged, now)
+            })
+            .map(|task| self.strip(task))
+            .collect()
+    }
+
+    fn includes(&self, task: &Task, now: OffsetDateTime) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(task, now, self.overdue_grace))
+    }
+
+    fn strip(&self, task: &Task) -> Task {
+        let mut task = task.clone();
+        for field in &self.excluded {
+            match field {
+                SyncField::Notes => task.notes = None,
+                SyncField::Comments => task.comments.clear(),
+                SyncField::Metadata => task.metadata.clear(),
+                SyncField::Ticket => task.ticket = None,
+                SyncField::Project => task.project = None,
+                SyncField::CompletionHistory => task.completion_history.clear(),
+            }
+        }
+        task
+    }
+
+    /// `synced` with the excluded fields of `existing`, the task it replaces on one side.
+    fn keep_excluded(&self, synced: &Task, existing: &Task) -> Task {
+        let mut task = synced.clone();
+        for field in &self.excluded {
+            match field {
+                SyncField::Notes => task.notes.clone_from(&existing.notes),
+                SyncField::Comments => task.comments.clone_from(&existing.comments),
+                SyncField::Metadata => task.metadata.clone_from(&existing.metadata),
+                SyncField::Ticket => task.ticket.clone_from(&existing.ticket),
+                SyncField::Project => task.project.clone_from(&existing.project),
+                SyncField::CompletionHistory => task
+                    .completion_history
+                    .clone_from(&existing.completion_history),
+            }
+        }
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Prefer, Resolution, SyncScope, apply, changes_since, plan};
+    use crate::config::{RemoteConfig, SyncField};
+    use crate::model::Task;
+    use crate::testing;
+    use std::path::PathBuf;
+    use time::Duration;
+    use time::macros::datetime;
+
+    fn task(id: &str, title: &str) -> Task {
+        testing::task(id, title).build().unwrap()
+    }
+
+    fn edited(task: &Task, title: &str, at: &str) -> Task {
+        let mut task = task.clone();
+        task.title = title.to_string();
+        task.modified_at = Some(at.to_string());
+        task
+    }
+
+    #[test]
+    fn changes_on_one_side_are_copied_to_the_other() {
+        let base = vec![
+            task("1", "Pay rent"),
+            task("2", "Call mum"),
+            task("3", "Old"),
+        ];
+        let renamed = edited(&base[0], "Pay rent early", "2025-12-20T09:00:00Z");
+        let local = vec![renamed.clone(), base[1].clone(), task("4", "New here")];
+        let remote = vec![base[0].clone(), task("5", "New there")];
+
+        let plan = plan(&base, &local, &remote).unwrap();
+        let synced = apply(&plan, &[], &local, &remote, &SyncScope::default());
+
+        assert_eq!(changes_since(&base, &local).unwrap(), 3);
+        assert_eq!(changes_since(&base, &base).unwrap(), 0);
+        assert_eq!(plan.push, vec![renamed, task("4", "New here")]);
+        assert_eq!(plan.pull, vec![task("5", "New there")]);
+        assert_eq!(plan.delete_local, vec![task("2", "Call mum")]);
+        assert!(plan.delete_remote.is_empty());
+        assert!(plan.conflicts.is_empty());
+        let ids = |tasks: &[Task]| tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&synced.local), ["1", "4", "5"]);
+        assert_eq!(ids(&synced.remote), ["1", "5", "4"]);
+        assert_eq!(synced.local[0], synced.remote[0]);
+    }
+
+    #[test]
+    fn tasks_changed_on_both_sides_conflict_and_resolve_by_rule_or_merge() {
+        let mut original = task("1", "Pay rent");
+        original.urgent = true;
+        let base = vec![original.clone()];
+        let local = edited(&original, "Pay rent early", "2025-12-20T09:00:00Z");
+        let mut remote = edited(&original, "Pay the rent", "2025-12-20T10:00:00Z");
+        remote.urgent = false;
+
+        let plan = plan(&base, &[local], &[remote]).unwrap();
+        let conflict = &plan.conflicts[0];
+        let merged = conflict
+            .resolved(
+                &Resolution::Merge(vec!["urgent".to_string()]),
+                "ana@laptop",
+                "2025-12-20T11:00:00Z",
+            )
+            .unwrap();
+
+        let fields: Vec<&str> = conflict
+            .fields
+            .iter()
+            .map(|change| change.field.as_str())
+            .collect();
+        assert_eq!(fields, ["title", "urgent"]);
+        assert_eq!(Prefer::Newest.resolve(conflict), Resolution::Remote);
+        assert_eq!(Prefer::Local.resolve(conflict), Resolution::Local);
+        assert_eq!(merged.title, "Pay rent early");
+        assert!(!merged.urgent);
+        assert_eq!(merged.modified_by.as_deref(), Some("ana@laptop"));
+        assert!(
+            conflict
+                .resolved(
+                    &Resolution::Merge(vec!["id".to_string()]),
+                    "ana@laptop",
+                    "2025-12-20T11:00:00Z"
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn a_task_changed_on_one_side_survives_its_deletion_on_the_other() {
+        let base = vec![task("1", "Pay rent")];
+        let local = vec![edited(&base[0], "Pay rent early", "2025-12-20T09:00:00Z")];
+
+        let plan = plan(&base, &local, &[]).unwrap();
+
+        assert_eq!(plan.push, local);
+        assert!(plan.delete_local.is_empty());
+    }
+
+    #[test]
+    fn a_scoped_remote_only_sees_matching_tasks_without_excluded_fields() {
+        let config = RemoteConfig {
+            path: PathBuf::from("shared.json"),
+            filter: Some("tag:shared".to_string()),
+            exclude_fields: vec![SyncField::Notes],
+        };
+        let scope = SyncScope::from_config("shared", &config, Duration::ZERO).unwrap();
+        let now = datetime!(2025-12-20 12:00 UTC);
+        let shared = testing::task("1", "Plan trip")
+            .tag("shared")
+            .notes("passport in drawer")
+            .build()
+            .unwrap();
+        let private = testing::task("2", "Dentist").build().unwrap();
+        let local = vec![shared.clone(), private];
+        let base = scope.local_view(&[shared], now);
+        let mut renamed = base[0].clone();
+        renamed.title = "Plan the trip".to_string();
+        let remote = vec![renamed.clone()];
+
+        let local_view = scope.local_view(&local, now);
+        let plan = plan(&base, &local_view, &scope.remote_view(&remote, &local, now)).unwrap();
+        let synced = apply(&plan, &[], &local, &remote, &scope);
+        let mut untagged = local.clone();
+        untagged[0].tags.clear();
+
+        assert_eq!(local_view.len(), 1);
+        assert_eq!(local_view[0].notes, None);
+        assert_eq!(plan.pull, vec![renamed]);
+        assert_eq!(synced.local[0].title, "Plan the trip");
+        assert_eq!(synced.local[0].notes.as_deref(), Some("passport in drawer"));
+        assert_eq!(synced.local.len(), 2);
+        assert_eq!(synced.remote.len(), 1);
+        assert!(scope.remote_view(&remote, &untagged, now).is_empty());
+        let bad = RemoteConfig {
+            filter: Some("colour:red".to_string()),
+            ..config
+        };
+        let err = SyncScope::from_config("shared", &bad, Duration::ZERO).unwrap_err();
+        assert!(err.message().starts_with("remotes.shared.filter: "));
+    }
+}
@@ -1,21 +1,91 @@
+use crate::clock;
+use crate::config::{
+    NotificationConfig, NotificationRoute, OrderingConfig, RetentionConfig, SortKey, TicketConfig,
+    TitleConfig, WebhookConfig, WipEnforcement, WipLimitConfig,
+};
+use crate::diff::{self, StoreDiff};
+use crate::duration;
 use crate::error::AppError;
-use crate::model::{CompletionEntry, Task, TaskStatus};
-use crate::notify::{Notifier, activation_argument, notifier_from_env};
-use crate::storage::json_store;
-use std::path::Path;
+use crate::export::digest::{Digest, DigestPeriod};
+use crate::export::verify::{self, RoundTrip};
+use crate::export::{self, ExportSnapshot};
+use crate::filter::Filter;
+use crate::import::csv::CsvOptions;
+use crate::import::{self, ImportedTask};
+use crate::model::{
+    Cancellation, Comment, CompletionEntry, Marker, MarkerColor, Task, TaskStatus, TicketLink,
+    WorkSession,
+};
+use crate::notify::{Notifier, activation_argument, notifier_for_config, route_notifiers};
+use crate::ordering;
+use crate::origin;
+use crate::period::Period;
+use crate::permissions::{self, PermissionIssue};
+use crate::seed::SeedRule;
+use crate::stats::{self, AccuracyReport, GroupBy, Insights, TaskGroup};
+use crate::storage::activity::{self, ActivityEntry};
+use crate::storage::archive::{self, ArchivedTask};
+use crate::storage::backup::{self, Backup};
+use crate::storage::index::INDEX_MIN_TASKS;
+use crate::storage::journal;
+use crate::storage::json_store::{self, TaskState};
+use crate::storage::legacy::{self, Coercion};
+use crate::storage::outbox::{self, OutboxEvent};
+use crate::storage::search;
+use crate::storage::seeds::{self, StoredSeed};
+use crate::storage::status::{self, StatusSummary};
+use crate::storage::store::{FileStore, MemoryStore, TaskStore};
+use crate::storage::undo::{self, CompletionUndo};
+use crate::storage::webhook_queue::{self, Delivery};
+use crate::text;
+use crate::tickets::{self, CurlFetcher, TicketFetcher, TicketState};
+use crate::usage;
+use crate::webhooks::{self, CurlPoster, DeliveryReport};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use time::format_description::well_known::Rfc3339;
 use time::{Date, OffsetDateTime, PrimitiveDateTime, UtcOffset, macros::format_description};
 
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(feature = "sync")]
+pub use sync::{
+    RemoteStatus, SyncFailure, SyncFlush, SyncQueue, SyncReport, flush_sync_queue, plan_sync,
+    sync_queue, sync_remote, sync_status,
+};
+
 #[derive(Debug, Clone)]
 pub struct ListResult {
     pub tasks: Vec<Task>,
     pub focused_task_id: Option<String>,
+    /// Active context whose filter was applied to `tasks`.
+    pub context: Option<String>,
+    /// Today's note from `journal`; only set for `list today`.
+    pub day_note: Option<String>,
+}
+
+/// Defined contexts (name to filter expression) and the active one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextList {
+    pub contexts: BTreeMap<String, String>,
+    pub active: Option<String>,
+}
+
+/// Everything `show` needs to render a single task.
+#[derive(Debug, Clone)]
+pub struct TaskDetail {
+    pub task: Task,
+    pub focused: bool,
+    pub overdue: bool,
 }
 
 #[derive(Debug)]
 pub struct NotificationOutcome {
     pub tasks: Vec<Task>,
     pub failures: Vec<NotificationFailure>,
+    /// The digest sent to each destination, or empty when every task was announced on its
+    /// own.
+    pub digests: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -24,6 +94,106 @@ pub struct NotificationFailure {
     pub error: AppError,
 }
 
+#[derive(Debug)]
+pub struct TicketRefresh {
+    /// Linked pending tasks whose ticket was fetched, after the update.
+    pub synced: Vec<TicketSync>,
+    pub failures: Vec<TicketFailure>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TicketSync {
+    pub task: Task,
+    /// The title was replaced with the ticket's title.
+    pub retitled: bool,
+    /// The ticket is closed, so the task was completed.
+    pub completed: bool,
+}
+
+#[derive(Debug)]
+pub struct TicketFailure {
+    pub task_id: String,
+    pub key: String,
+    pub error: AppError,
+}
+
+/// What one run of the retention rules did.
+#[derive(Debug, Clone, Default)]
+pub struct Maintenance {
+    /// Closed tasks and routine tasks from earlier days moved from the store to the archive.
+    pub archived: Vec<Task>,
+    /// Archived tasks dropped for good.
+    pub purged: Vec<Task>,
+}
+
+/// What `doctor --fix-permissions` changed and what it could not.
+#[derive(Debug, Clone, Default)]
+pub struct PermissionRepair {
+    /// Paths whose mode was tightened.
+    pub fixed: Vec<PathBuf>,
+    /// Problems left afterwards, on paths owned by someone else.
+    pub remaining: Vec<PermissionIssue>,
+}
+
+/// Size and shape of the store, for `info`.
+#[derive(Debug, Clone)]
+pub struct StoreInfo {
+    pub path: PathBuf,
+    /// Size of the store file; 0 before the first save.
+    pub store_bytes: u64,
+    /// Tasks in each status, in [`TaskStatus::ALL`] order.
+    pub by_status: Vec<(TaskStatus, usize)>,
+    /// The open task created first.
+    pub oldest_open: Option<Task>,
+    /// Changes held in the journal that the store file does not have yet.
+    pub journal_entries: usize,
+    pub journal_bytes: u64,
+    /// The newest snapshot.
+    pub last_backup: Option<Backup>,
+}
+
+impl StoreInfo {
+    pub fn total(&self) -> usize {
+        self.by_status.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// What `migrate` did to the store.
+#[derive(Debug, Clone)]
+pub struct StoreMigration {
+    /// Schema version the store file was written in.
+    pub from_version: u32,
+    /// Legacy values rewritten by `--lenient`, in file order.
+    pub coercions: Vec<Coercion>,
+    /// Copy of the store file as it was before it was rewritten; `None` when it was already
+    /// current and left alone.
+    pub original: Option<PathBuf>,
+}
+
+/// A schedule or focus change checked against `wip_limit.today`.
+#[derive(Debug, Clone)]
+pub struct PlannedChange {
+    pub task: Task,
+    /// Set when the change took today's plan over the limit and was made anyway.
+    pub over_limit: Option<WipExceeded>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WipExceeded {
+    /// Pending tasks planned for today after the change.
+    pub planned: usize,
+    pub limit: usize,
+}
+
+/// Where `triage` moves an overdue deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriageDay {
+    Today,
+    Tomorrow,
+    /// The coming Monday.
+    NextWeek,
+}
+
 pub fn add_task(title: &str) -> Result<Task, AppError> {
     add_task_with_urgency(title, false)
 }
@@ -34,56 +204,80 @@ pub fn add_task_with_urgency(title: &str, urgent: bool) -> Result<Task, AppError
 }
 
 fn add_task_with_path(path: &Path, title: &str, urgent: bool) -> Result<Task, AppError> {
-    let trimmed = title.trim();
-    if trimmed.is_empty() {
-        return Err(AppError::invalid_input("title is required"));
-    }
+    transaction_with_path(path, |txn| txn.add(title, urgent))
+}
 
-    let created_at = OffsetDateTime::now_utc()
-        .format(&Rfc3339)
-        .map_err(|err| AppError::invalid_data(err.to_string()))?;
-    
-    // Generate a shorter, 4-character ID based on the last 4 digits of nanoseconds
-    // This is simple but might have collisions in high-concurrency, but fine for a local todo app.
-    // For better uniqueness while keeping it short, we could use a hash, but this is requested by user.
-    let nanos = OffsetDateTime::now_utc().unix_timestamp_nanos();
-    let id = (nanos % 10000).to_string();
-
-    let task = Task {
-        id,
-        title: trimmed.to_string(),
-        status: TaskStatus::Pending,
-        created_at,
-        scheduled_at: None,
-        completed_at: None,
-        completion_history: Vec::new(),
-        urgent,
-    };
+// The list functions take the `overdue_grace` for the active context's filter, where
+// `status:overdue` allows for it.
 
-    let mut state = json_store::load_state(path)?;
-    state.tasks.push(task.clone());
-    json_store::save_state(path, &state)?;
+pub fn list_today(grace: time::Duration) -> Result<Vec<Task>, AppError> {
+    Ok(list_today_with_focus(grace)?.tasks)
+}
 
-    Ok(task)
+pub fn list_backlog(grace: time::Duration) -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    list_without_focus(&path, ListMode::Backlog, grace)
 }
 
-pub fn list_today() -> Result<Vec<Task>, AppError> {
-    Ok(list_today_with_focus()?.tasks)
+/// `list today` in the default [`OrderingConfig`] order.
+pub fn list_today_with_focus(grace: time::Duration) -> Result<ListResult, AppError> {
+    let path = json_store::store_path()?;
+    list_today_with_focus_with_path(&path, grace)
+}
+
+/// `list today` sorted by `order`; see [`SortKey`] for what each key does.
+pub fn list_today_with_order(
+    order: &[SortKey],
+    grace: time::Duration,
+) -> Result<ListResult, AppError> {
+    let path = json_store::store_path()?;
+    list_today_with_order_with_path(&path, order, grace)
 }
 
-pub fn list_backlog() -> Result<Vec<Task>, AppError> {
+/// Tasks planned for a day of `period` (see [`Task::planned_at`]), local time, whatever their
+/// status.
+pub fn list_period(period: Period, grace: time::Duration) -> Result<Vec<Task>, AppError> {
     let path = json_store::store_path()?;
-    list_without_focus(&path, ListMode::Backlog)
+    list_without_focus(&path, ListMode::Period(period), grace)
+}
+
+/// Open tasks planned for today or one of the `days - 1` days after it (see
+/// [`Task::planned_at`]), local time, earliest first.
+pub fn list_upcoming(days: u32, grace: time::Duration) -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    let mut tasks = list_without_focus(&path, ListMode::Upcoming(days), grace)?;
+    // Filtering already parsed every planned time.
+    tasks.sort_by_cached_key(|task| {
+        task.planned_at()
+            .and_then(|at| OffsetDateTime::parse(at, &Rfc3339).ok())
+    });
+    Ok(tasks)
+}
+
+/// Open tasks more than `grace` past their deadline, the longest overdue first. The active
+/// context is not applied.
+pub fn overdue_tasks(grace: time::Duration) -> Result<Vec<Task>, AppError> {
+    overdue_tasks_with_path(&json_store::store_path()?, grace)
 }
 
-pub fn list_today_with_focus() -> Result<ListResult, AppError> {
+/// Move each task's deadline to the day picked for it in `triage`, in one write; see
+/// [`Transaction::move_overdue`].
+pub fn triage_overdue(
+    moves: &[(String, TriageDay)],
+    grace: time::Duration,
+) -> Result<Vec<Task>, AppError> {
     let path = json_store::store_path()?;
-    list_today_with_focus_with_path(&path)
+    triage_overdue_with_path(&path, moves, grace)
+}
+
+/// Today's date in local time, the day `list today` is about.
+pub fn local_today() -> Result<Date, AppError> {
+    Ok(clock::now_utc().to_offset(local_offset()?).date())
 }
 
-pub fn list_backlog_with_focus() -> Result<ListResult, AppError> {
+pub fn list_backlog_with_focus(grace: time::Duration) -> Result<ListResult, AppError> {
     let path = json_store::store_path()?;
-    list_backlog_with_focus_with_path(&path)
+    list_backlog_with_focus_with_path(&path, grace)
 }
 
 pub fn edit_task(id: &str, new_title: &str) -> Result<Task, AppError> {
@@ -106,14 +300,82 @@ pub fn complete_focused_task(message: Option<&str>) -> Result<Task, AppError> {
     complete_focused_task_with_path(&path, message)
 }
 
+/// How long after `done` the completion can be reverted with [`undo_completion`].
+pub const UNDO_WINDOW_SECS: i64 = 60;
+
+/// Reopen the task completed by the most recent `done`, restoring it exactly as it was
+/// (including focus), if that was under [`UNDO_WINDOW_SECS`] ago and the task is unchanged.
+pub fn undo_completion() -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    undo_completion_with_path(&path, clock::now_utc())
+}
+
 pub fn schedule_task(id: &str, datetime: &str) -> Result<Task, AppError> {
     let path = json_store::store_path()?;
     schedule_task_with_path(&path, id, datetime)
 }
 
-pub fn reschedule_task(id: &str, datetime: &str) -> Result<Task, AppError> {
+/// Moves the deadline of a task that is more than `grace` past it; see
+/// [`Transaction::reschedule`].
+pub fn reschedule_task(id: &str, datetime: &str, grace: time::Duration) -> Result<Task, AppError> {
     let path = json_store::store_path()?;
-    reschedule_task_with_path(&path, id, datetime)
+    reschedule_task_with_path(&path, id, datetime, grace)
+}
+
+/// [`schedule_task`] checked against [`WipLimitConfig`]; `force` overrides a blocking limit.
+/// A deadline that has already passed by more than `grace` is refused unless `allow_past`.
+pub fn schedule_task_within_limit(
+    id: &str,
+    datetime: &str,
+    limit: &WipLimitConfig,
+    force: bool,
+    allow_past: bool,
+    grace: time::Duration,
+) -> Result<PlannedChange, AppError> {
+    let path = json_store::store_path()?;
+    within_wip_limit_with_path(&path, limit, force, |txn| {
+        let task = txn.schedule(id, datetime)?;
+        if !allow_past {
+            ensure_deadline_ahead(datetime, txn.local_offset, clock::now_utc(), grace)?;
+        }
+        Ok(task)
+    })
+}
+
+/// Plans when to work on the task, setting its `start_at` and leaving the deadline alone,
+/// checked against [`WipLimitConfig`] like [`schedule_task_within_limit`].
+pub fn schedule_start_within_limit(
+    id: &str,
+    datetime: &str,
+    limit: &WipLimitConfig,
+    force: bool,
+) -> Result<PlannedChange, AppError> {
+    let path = json_store::store_path()?;
+    within_wip_limit_with_path(&path, limit, force, |txn| txn.schedule_start(id, datetime))
+}
+
+/// [`reschedule_task`] checked against [`WipLimitConfig`]; `force` overrides a blocking limit.
+pub fn reschedule_task_within_limit(
+    id: &str,
+    datetime: &str,
+    limit: &WipLimitConfig,
+    force: bool,
+    grace: time::Duration,
+) -> Result<PlannedChange, AppError> {
+    let path = json_store::store_path()?;
+    within_wip_limit_with_path(&path, limit, force, |txn| {
+        txn.reschedule(id, datetime, grace)
+    })
+}
+
+/// [`set_focus`] checked against [`WipLimitConfig`]; `force` overrides a blocking limit.
+pub fn set_focus_within_limit(
+    id: &str,
+    limit: &WipLimitConfig,
+    force: bool,
+) -> Result<PlannedChange, AppError> {
+    let path = json_store::store_path()?;
+    within_wip_limit_with_path(&path, limit, force, |txn| txn.set_focus(id))
 }
 
 pub fn set_focus(id: &str) -> Result<Task, AppError> {
@@ -126,1796 +388,5110 @@ pub fn set_task_urgent(id: &str, urgent: bool) -> Result<Task, AppError> {
     set_task_urgent_with_path(&path, id, urgent)
 }
 
-pub fn get_task_by_id(id: &str) -> Result<Task, AppError> {
+pub fn start_task(id: &str) -> Result<Task, AppError> {
     let path = json_store::store_path()?;
-    get_task_by_id_with_path(&path, id)
+    transaction_with_path(&path, |txn| txn.start(id))
 }
 
-pub fn notify_overdue_or_urgent() -> Result<NotificationOutcome, AppError> {
+pub fn wait_task(id: &str, until: Option<&str>) -> Result<Task, AppError> {
     let path = json_store::store_path()?;
-    let notifier = notifier_from_env()?;
-    notify_overdue_or_urgent_with_path(&path, notifier.as_ref())
+    transaction_with_path(&path, |txn| txn.wait(id, until))
 }
 
-fn list_today_with_focus_with_path(path: &Path) -> Result<ListResult, AppError> {
-    list_with_focus(path, ListMode::Today)
+/// `None` for both clears the marker.
+pub fn mark_task(
+    id: &str,
+    color: Option<MarkerColor>,
+    symbol: Option<&str>,
+) -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| match (color, symbol) {
+        (None, None) => txn.clear_marker(id),
+        _ => txn.set_marker(id, color, symbol),
+    })
 }
 
-fn get_task_by_id_with_path(path: &Path, id: &str) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
-    }
-
-    let state = json_store::load_state(path)?;
-    state
-        .tasks
-        .into_iter()
-        .find(|task| task.id == trimmed_id)
-        .ok_or_else(|| AppError::invalid_input("task not found"))
+pub fn cancel_task(id: &str, reason: Option<&str>) -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.cancel(id, reason))
 }
 
-fn set_task_urgent_with_path(path: &Path, id: &str, urgent: bool) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
-    }
+/// Set (`Some`) or remove (`None`) one metadata key on a task.
+pub fn set_task_metadata(id: &str, key: &str, value: Option<&str>) -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.set_metadata(id, key, value))
+}
 
-    let mut state = json_store::load_state(path)?;
-    let mut updated_task = None;
+/// Append a comment by `author` to the task's thread.
+pub fn add_comment(id: &str, author: &str, text: &str) -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.add_comment(id, author, text))
+}
 
-    for task in &mut state.tasks {
-        if task.id == trimmed_id {
-            task.urgent = urgent;
-            updated_task = Some(task.clone());
-            break;
-        }
-    }
+/// Link a task to an external ticket, replacing any previous link; `None` removes the link.
+pub fn link_ticket(id: &str, link: Option<TicketLink>) -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.set_ticket(id, link))
+}
 
-    let updated = updated_task.ok_or_else(|| AppError::invalid_input("task not found"))?;
-    json_store::save_state(path, &state)?;
+/// Fetch every pending task's linked ticket and apply its title and status.
+pub fn refresh_tickets(config: &TicketConfig) -> Result<TicketRefresh, AppError> {
+    let path = json_store::store_path()?;
+    refresh_tickets_with_path(&path, config, &CurlFetcher)
+}
 
-    Ok(updated)
+pub fn get_task_by_id(id: &str) -> Result<Task, AppError> {
+    let path = json_store::store_path()?;
+    get_task_by_id_with_path(&path, id)
 }
 
-fn notify_overdue_or_urgent_with_path(
-    path: &Path,
-    notifier: &dyn Notifier,
-) -> Result<NotificationOutcome, AppError> {
-    let state = json_store::load_state(path)?;
-    let mut notified = Vec::new();
-    let mut failures = Vec::new();
+/// The task with its focus, and whether it is more than `grace` past its deadline.
+pub fn get_task_detail(id: &str, grace: time::Duration) -> Result<TaskDetail, AppError> {
+    let path = json_store::store_path()?;
+    get_task_detail_with_path(&path, id, grace)
+}
 
-    for task in &state.tasks {
-        if task.status != TaskStatus::Pending {
-            continue;
-        }
+/// Usage patterns from the local usage log and completion timestamps in the store.
+pub fn usage_insights() -> Result<Insights, AppError> {
+    let path = json_store::store_path()?;
+    usage_insights_with_path(&path)
+}
 
-        let overdue = task_overdue(task)?;
-        if !overdue && !task.urgent {
-            continue;
-        }
+/// Estimates against tracked time for completed tasks, per tag.
+pub fn estimate_accuracy() -> Result<AccuracyReport, AppError> {
+    let path = json_store::store_path()?;
+    let state = json_store::load_state(&path)?;
+    Ok(stats::estimate_accuracy(&state.tasks))
+}
 
-        let action = activation_argument(&task.id);
-        match notifier.notify_with_action(task, &action) {
-            Ok(_) => notified.push(task.clone()),
-            Err(err) => failures.push(NotificationFailure {
-                task_id: task.id.clone(),
-                error: err,
-            }),
-        }
-    }
+/// File sizes, task counts, and the newest snapshot, for judging whether the store needs a
+/// `flush` or `maintain`.
+pub fn store_info() -> Result<StoreInfo, AppError> {
+    let path = json_store::store_path()?;
+    store_info_with_path(&path)
+}
 
-    Ok(NotificationOutcome {
-        tasks: notified,
-        failures,
-    })
+/// Rewrite the store in the current schema, keeping the file as it was at
+/// [`legacy::original_path`]. With `lenient`, values older versions wrote that the current
+/// schema rejects are coerced first; see [`legacy::coerce`].
+pub fn migrate_store(lenient: bool) -> Result<StoreMigration, AppError> {
+    let path = json_store::store_path()?;
+    migrate_store_with_path(&path, lenient)
 }
 
-fn list_backlog_with_focus_with_path(path: &Path) -> Result<ListResult, AppError> {
-    list_with_focus(path, ListMode::Backlog)
+/// The status summary kept next to the store, computed from the store if it has never been
+/// written.
+pub fn status_summary() -> Result<StatusSummary, AppError> {
+    let path = json_store::store_path()?;
+    status_summary_with_path(&path)
 }
 
-fn list_without_focus(path: &Path, mode: ListMode) -> Result<Vec<Task>, AppError> {
-    let tasks = json_store::load_state(path)?.tasks;
-    let local_offset = local_offset()?;
-    let today = OffsetDateTime::now_utc().to_offset(local_offset).date();
-    filter_tasks(&tasks, today, local_offset, mode)
+/// Unacknowledged store events, oldest first; with `ack`, they are acknowledged as they are
+/// returned. See [`outbox`].
+pub fn pull_events(limit: Option<usize>, ack: bool) -> Result<Vec<OutboxEvent>, AppError> {
+    let path = json_store::store_path()?;
+    let events = outbox::pending(&path, limit)?;
+    if ack && let Some(last) = events.last() {
+        outbox::acknowledge(&path, last.seq)?;
+    }
+    Ok(events)
 }
 
-fn list_with_focus(path: &Path, mode: ListMode) -> Result<ListResult, AppError> {
-    let state = json_store::load_state(path)?;
-    let local_offset = local_offset()?;
-    let today = OffsetDateTime::now_utc().to_offset(local_offset).date();
-    let mut tasks = filter_tasks(&state.tasks, today, local_offset, mode)?;
-    let focused_task_id = state.focused_task_id.clone();
+/// Acknowledge every store event up to and including `through`; returns how many there were.
+pub fn ack_events(through: u64) -> Result<usize, AppError> {
+    outbox::acknowledge(&json_store::store_path()?, through)
+}
 
-    if let Some(focused_id) = focused_task_id.as_deref()
-        && let Some(index) = tasks.iter().position(|task| task.id == focused_id)
-    {
-        let focused_task = tasks.remove(index);
-        tasks.insert(0, focused_task);
-    }
+/// Post the deliveries queued for `webhooks`, waiting between retries.
+pub fn deliver_webhooks() -> Result<DeliveryReport, AppError> {
+    webhooks::deliver(
+        &json_store::store_path()?,
+        &CurlPoster,
+        &mut std::thread::sleep,
+    )
+}
 
-    Ok(ListResult {
-        tasks,
-        focused_task_id,
-    })
+/// Queue deliveries to `webhooks` for every later change to the store; see
+/// [`webhook_queue::subscribe`].
+pub fn subscribe_webhooks(webhooks: &[WebhookConfig]) -> Result<(), AppError> {
+    webhook_queue::subscribe(&json_store::store_path()?, webhooks)
 }
 
-fn local_offset() -> Result<UtcOffset, AppError> {
-    Ok(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+/// Deliveries still waiting for their webhook, oldest first.
+pub fn queued_webhooks() -> Result<Vec<Delivery>, AppError> {
+    webhook_queue::pending(&json_store::store_path()?)
 }
 
-enum ListMode {
-    Today,
-    Backlog,
+/// Compare the tasks of two store files; `new` defaults to the live store.
+pub fn diff_stores(old: &Path, new: Option<&Path>) -> Result<StoreDiff, AppError> {
+    let live;
+    let new = match new {
+        Some(path) => path,
+        None => {
+            live = json_store::store_path()?;
+            &live
+        }
+    };
+    diff_stores_with_paths(old, new)
 }
 
-fn filter_tasks(
-    tasks: &[Task],
-    today: Date,
-    local_offset: UtcOffset,
-    mode: ListMode,
-) -> Result<Vec<Task>, AppError> {
-    let mut filtered = Vec::new();
-    for task in tasks {
-        let scheduled_at = match task.scheduled_at.as_deref() {
-            Some(value) => value,
-            None => {
-                if matches!(mode, ListMode::Backlog) {
-                    filtered.push(task.clone());
-                }
-                continue;
-            }
-        };
+/// Backup written automatically before a snapshot is restored, so a restore can be undone.
+pub const PRE_RESTORE_SNAPSHOT: &str = "pre-restore";
 
-        let scheduled = OffsetDateTime::parse(scheduled_at, &Rfc3339)
-            .map_err(|_| AppError::invalid_data("scheduled_at must be RFC3339"))?;
-        let scheduled_local = scheduled.to_offset(local_offset);
-        let scheduled_date = scheduled_local.date();
+#[derive(Debug, Clone)]
+pub struct SnapshotRestore {
+    pub restored: Backup,
+    /// The store as it was just before the restore.
+    pub previous: Backup,
+}
 
-        let matches = match mode {
-            ListMode::Today => scheduled_date <= today,
-            ListMode::Backlog => scheduled_date > today,
-        };
+/// Save the whole store as a named checkpoint.
+pub fn save_snapshot(name: &str) -> Result<Backup, AppError> {
+    let path = json_store::store_path()?;
+    save_snapshot_with_path(&path, name)
+}
 
-        if matches {
-            filtered.push(task.clone());
-        }
-    }
+/// Replace the store with a saved checkpoint, first saving the current store as
+/// [`PRE_RESTORE_SNAPSHOT`].
+pub fn restore_snapshot(name: &str) -> Result<SnapshotRestore, AppError> {
+    let path = json_store::store_path()?;
+    restore_snapshot_with_path(&path, name)
+}
 
-    Ok(filtered)
+pub fn list_snapshots() -> Result<Vec<Backup>, AppError> {
+    backup::list(&json_store::store_path()?)
 }
 
-fn edit_task_with_path(path: &Path, id: &str, new_title: &str) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
-    }
+/// Every task matching a [`Filter`] expression, in store order. The active context is not
+/// applied.
+pub fn query_tasks(expression: &str, grace: time::Duration) -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    query_tasks_with_path(&path, expression, grace)
+}
 
-    let trimmed_title = new_title.trim();
-    if trimmed_title.is_empty() {
-        return Err(AppError::invalid_input("title is required"));
-    }
+/// Tasks whose title, notes, or tags contain every word of `query`, ignoring case, oldest
+/// first. Stores of [`INDEX_MIN_TASKS`] or more are answered from the search index, which the
+/// first search builds. The active context is not applied.
+pub fn search_tasks(query: &str) -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    search_tasks_with_path(&path, query)
+}
 
-    let mut state = json_store::load_state(path)?;
-    let mut updated_task = None;
+/// Build the search index from scratch, whatever the store's size. Returns how many tasks it
+/// covers.
+pub fn rebuild_search_index() -> Result<usize, AppError> {
+    let path = json_store::store_path()?;
+    let state = json_store::load_state(&path)?;
+    search::build(&path, &state)?;
+    Ok(state.tasks.len())
+}
 
-    for task in &mut state.tasks {
-        if task.id == trimmed_id {
-            task.title = trimmed_title.to_string();
-            updated_task = Some(task.clone());
-            break;
-        }
-    }
+/// Every task, split into today/backlog/done sections for [`crate::export`] renderers.
+pub fn export_snapshot(grace: time::Duration) -> Result<ExportSnapshot, AppError> {
+    let path = json_store::store_path()?;
+    export_snapshot_with_path(&path, grace)
+}
 
-    let updated = updated_task.ok_or_else(|| AppError::invalid_input("task not found"))?;
-    if state.focused_task_id.as_deref() == Some(trimmed_id) {
-        state.focused_task_id = None;
-    }
-    json_store::save_state(path, &state)?;
+/// Every task in store order, ignoring the active context.
+pub fn all_tasks() -> Result<Vec<Task>, AppError> {
+    json_store::load_tasks(&json_store::store_path()?)
+}
 
-    Ok(updated)
+/// Completed, slipped, and upcoming tasks around now for [`crate::export::digest`].
+pub fn digest(period: DigestPeriod) -> Result<Digest, AppError> {
+    let path = json_store::store_path()?;
+    digest_with_path(&path, period)
 }
 
-fn delete_task_with_path(path: &Path, id: &str) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
-    }
+/// Apply the retention rules now: archive old completed tasks, then purge old archived ones.
+pub fn maintain(config: &RetentionConfig) -> Result<Maintenance, AppError> {
+    let path = json_store::store_path()?;
+    maintain_with_path(&path, config, clock::now_utc())
+}
 
-    let mut state = json_store::load_state(path)?;
-    let index = state
-        .tasks
-        .iter()
-        .position(|task| task.id == trimmed_id)
-        .ok_or_else(|| AppError::invalid_input("task not found"))?;
+/// [`maintain`] for startup: `None` unless `retention.on_startup` is set and the rules last
+/// ran more than `startup_interval_hours` ago.
+pub fn maintain_if_due(config: &RetentionConfig) -> Result<Option<Maintenance>, AppError> {
+    let path = json_store::store_path()?;
+    maintain_if_due_with_path(&path, config, clock::now_utc())
+}
 
-    let removed = state.tasks.remove(index);
-    if state.focused_task_id.as_deref() == Some(trimmed_id) {
-        state.focused_task_id = None;
-    }
-    json_store::save_state(path, &state)?;
+/// Add today's tasks for the routine `name` from the `routines` config.
+pub fn start_routine(
+    routines: &BTreeMap<String, Vec<String>>,
+    name: &str,
+) -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    start_routine_with_path(&path, routines, name)
+}
 
-    Ok(removed)
+/// Add `tasks` read by [`crate::import`] in one transaction, in order. With `dry_run` they are
+/// added to a copy of the store instead and returned as they would be saved, titles cleaned
+/// and ids assigned, with nothing written.
+pub fn import_tasks(tasks: &[ImportedTask], dry_run: bool) -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    import_tasks_with_path(&path, tasks, dry_run)
 }
 
-fn complete_task_with_path(path: &Path, id: &str, message: Option<&str>) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
+fn import_tasks_with_path(
+    path: &Path,
+    tasks: &[ImportedTask],
+    dry_run: bool,
+) -> Result<Vec<Task>, AppError> {
+    let apply = |txn: &mut Transaction| tasks.iter().map(|task| txn.import(task)).collect();
+    if dry_run {
+        let copy = MemoryStore::new(json_store::load_state(path)?);
+        transaction_in(&copy, apply)
+    } else {
+        transaction_with_path(path, apply)
     }
+}
 
-    let mut state = json_store::load_state(path)?;
-    let mut updated_task = None;
+/// Write every task in each format that can be read back, read it into an empty store, and
+/// compare the result with the store: the store file itself, then each `export` format that
+/// `import` reads. Nothing is written.
+pub fn verify_exports() -> Result<Vec<RoundTrip>, AppError> {
+    let path = json_store::store_path()?;
+    verify_exports_with_path(&path)
+}
 
-    for task in &mut state.tasks {
-        if task.id == trimmed_id {
-            if task.status == TaskStatus::Completed {
-                return Err(AppError::invalid_input("task already completed"));
-            }
-
-            let trimmed_message = match message {
-                Some(value) => {
-                    let trimmed = value.trim();
-                    if trimmed.is_empty() {
-                        return Err(AppError::invalid_input("message is required"));
-                    }
-                    Some(trimmed.to_string())
-                }
-                None => None,
-            };
-
-            let completed_at = OffsetDateTime::now_utc()
-                .format(&Rfc3339)
-                .map_err(|err| AppError::invalid_data(err.to_string()))?;
+fn verify_exports_with_path(path: &Path) -> Result<Vec<RoundTrip>, AppError> {
+    let state = json_store::load_state(path)?;
+    let reloaded = json_store::parse_state(&json_store::render_state(&state)?)?;
+    let store = verify::compare("store", &state.tasks, &reloaded.tasks, None)?;
+
+    let rows = import::csv::read(&export::csv::render(&state.tasks), &CsvOptions::default())?;
+    let read_back: Vec<Task> = transaction_in(&MemoryStore::default(), |txn| {
+        rows.iter().map(|row| txn.import(row)).collect()
+    })?;
+    let csv = verify::compare("csv", &state.tasks, &read_back, Some(&export::csv::FIELDS))?;
+    Ok(vec![store, csv])
+}
 
-            task.status = TaskStatus::Completed;
-            task.completed_at = Some(completed_at.clone());
+/// Move routine tasks from earlier days to the archive, whatever their status. Also part of
+/// [`maintain`]; run on startup when any routine is configured.
+pub fn archive_expired_routines() -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    archive_expired_routines_with_path(&path, clock::now_utc())
+}
 
-            if let Some(message) = trimmed_message {
-                task.completion_history.push(CompletionEntry {
-                    message,
-                    completed_at: completed_at.clone(),
-                });
-            }
+/// Add a `seed` rule, checked by [`SeedRule::parse`] and kept in the form it prints. The rule
+/// first falls due after now.
+pub fn add_seed_rule(text: &str) -> Result<StoredSeed, AppError> {
+    let path = json_store::store_path()?;
+    add_seed_rule_with_path(&path, text, clock::now_utc())
+}
 
-            updated_task = Some(task.clone());
-            break;
-        }
-    }
+/// The `seed` rules in the order they were added.
+pub fn seed_rules() -> Result<Vec<StoredSeed>, AppError> {
+    let path = json_store::store_path()?;
+    seeds::read(&path)
+}
 
-    let updated = updated_task.ok_or_else(|| AppError::invalid_input("task not found"))?;
-    if state.focused_task_id.as_deref() == Some(trimmed_id) {
-        state.focused_task_id = None;
+/// Remove the `number`th rule, counting from 1 as `seed list` does.
+pub fn remove_seed_rule(number: usize) -> Result<StoredSeed, AppError> {
+    let path = json_store::store_path()?;
+    let mut stored = seeds::read(&path)?;
+    if number == 0 || number > stored.len() {
+        return Err(AppError::invalid_input(format!("no seed rule {number}"))
+            .with_hint("`todo seed list` numbers the rules"));
     }
-    json_store::save_state(path, &state)?;
-
-    Ok(updated)
+    let removed = stored.remove(number - 1);
+    seeds::write(&path, &stored)?;
+    Ok(removed)
 }
 
-fn complete_focused_task_with_path(path: &Path, message: Option<&str>) -> Result<Task, AppError> {
-    let mut state = json_store::load_state(path)?;
-    let focused_id = state
-        .focused_task_id
-        .clone()
-        .ok_or_else(|| AppError::invalid_input("no focused task"))?;
-    let trimmed_message = match message {
-        Some(value) => {
-            let trimmed = value.trim();
-            if trimmed.is_empty() {
-                return Err(AppError::invalid_input("message is required"));
-            }
-            Some(trimmed.to_string())
-        }
-        None => None,
-    };
-    let mut updated_task = None;
+/// Add a task for each rule that has fallen due since it was added and since it last added
+/// one, planned to start at the time it fell due. A rule adds at most one task per run, for
+/// its latest due time, so running this late or twice adds nothing extra.
+pub fn run_seed_rules() -> Result<Vec<Task>, AppError> {
+    let path = json_store::store_path()?;
+    run_seed_rules_with_path(&path, clock::now_utc())
+}
 
-    for task in &mut state.tasks {
-        if task.id == focused_id {
-            if task.status == TaskStatus::Completed {
-                return Err(AppError::invalid_input("task already completed"));
-            }
+/// Changes other people made to a shared store since this user@host last asked, oldest
+/// first. Empty for a store that does not exist yet and on the first call.
+pub fn changes_by_others() -> Result<Vec<ActivityEntry>, AppError> {
+    let path = json_store::store_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    activity::unseen_changes(&path, &origin::current(), clock::now_utc())
+}
 
-            let completed_at = OffsetDateTime::now_utc()
-                .format(&Rfc3339)
-                .map_err(|err| AppError::invalid_data(err.to_string()))?;
+/// Journal changes instead of rewriting the store until turned off; see
+/// [`json_store::defer_snapshots`].
+pub fn defer_store_writes(enabled: bool) {
+    json_store::defer_snapshots(enabled);
+}
 
-            task.status = TaskStatus::Completed;
-            task.completed_at = Some(completed_at.clone());
+/// Rewrite the store with every change still held in its journal. Returns `false` when there
+/// was nothing to write.
+pub fn flush_store() -> Result<bool, AppError> {
+    let path = json_store::store_path()?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    json_store::flush(&path)
+}
 
-            if let Some(message) = trimmed_message {
-                task.completion_history.push(CompletionEntry {
-                    message,
-                    completed_at: completed_at.clone(),
-                });
-            }
+/// What the store's readers would otherwise work around, see [`json_store::check`].
+pub fn store_warnings() -> Result<Vec<String>, AppError> {
+    let path = json_store::store_path()?;
+    json_store::check(&path)
+}
 
-            updated_task = Some(task.clone());
-            break;
-        }
-    }
+/// Store and config files that other users own or can write; see [`permissions`].
+pub fn permission_issues() -> Result<Vec<PermissionIssue>, AppError> {
+    let path = json_store::store_path()?;
+    permissions::check(&path, &crate::config::config_path()?)
+}
 
-    let updated = updated_task.ok_or_else(|| AppError::invalid_input("task not found"))?;
-    state.focused_task_id = None;
-    json_store::save_state(path, &state)?;
+/// Tighten modes with [`permissions::fix`], then check again for what is left.
+pub fn fix_permissions() -> Result<PermissionRepair, AppError> {
+    let path = json_store::store_path()?;
+    let config_path = crate::config::config_path()?;
+    let fixed = permissions::fix(&path, &config_path)?;
+    Ok(PermissionRepair {
+        fixed,
+        remaining: permissions::check(&path, &config_path)?,
+    })
+}
 
-    Ok(updated)
+/// The activity log entries for the task with exactly this id, oldest first.
+pub fn task_activity(id: &str) -> Result<Vec<ActivityEntry>, AppError> {
+    let path = json_store::store_path()?;
+    Ok(activity::read(&path)?
+        .into_iter()
+        .filter(|entry| entry.task_id == id)
+        .collect())
 }
 
-fn schedule_task_with_path(path: &Path, id: &str, datetime: &str) -> Result<Task, AppError> {
-    update_schedule_with_path(path, id, datetime, false, false)
+pub fn list_contexts() -> Result<ContextList, AppError> {
+    let state = json_store::load_state(&json_store::store_path()?)?;
+    Ok(ContextList {
+        contexts: state.contexts,
+        active: state.active_context,
+    })
 }
 
-fn reschedule_task_with_path(path: &Path, id: &str, datetime: &str) -> Result<Task, AppError> {
-    update_schedule_with_path(path, id, datetime, true, true)
+pub fn define_context(name: &str, filter: &str) -> Result<ContextList, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.define_context(name, filter))
 }
 
-fn set_focus_with_path(path: &Path, id: &str) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
-    }
+pub fn delete_context(name: &str) -> Result<ContextList, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.delete_context(name))
+}
 
-    let mut state = json_store::load_state(path)?;
-    let mut focused = None;
+pub fn set_context(name: Option<&str>) -> Result<ContextList, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.set_context(name))
+}
 
-    for task in &state.tasks {
-        if task.id == trimmed_id {
-            focused = Some(task.clone());
-            break;
-        }
+/// A day for `journal`: `YYYY-MM-DD`, `today`, `yesterday`, or `tomorrow`, in local time.
+pub fn parse_day(value: &str) -> Result<Date, AppError> {
+    let today = local_today()?;
+    let value = value.trim();
+    match value.to_lowercase().as_str() {
+        "today" => Ok(today),
+        "yesterday" => today
+            .previous_day()
+            .ok_or_else(|| AppError::invalid_input("date out of range")),
+        "tomorrow" => today
+            .next_day()
+            .ok_or_else(|| AppError::invalid_input("date out of range")),
+        _ => Date::parse(value, &format_description!("[year]-[month]-[day]")).map_err(|_| {
+            AppError::invalid_input(format!("invalid date '{value}'"))
+                .with_hint("use YYYY-MM-DD, today, yesterday, or tomorrow")
+        }),
     }
+}
 
-    let task = focused.ok_or_else(|| AppError::invalid_input("task not found"))?;
-    state.focused_task_id = Some(task.id.clone());
-    json_store::save_state(path, &state)?;
-
-    Ok(task)
+/// The note written for `date` with `journal`, if any.
+pub fn day_note(date: Date) -> Result<Option<String>, AppError> {
+    let state = json_store::load_state(&json_store::store_path()?)?;
+    Ok(state.day_notes.get(&date.to_string()).cloned())
 }
 
-fn update_schedule_with_path(
-    path: &Path,
-    id: &str,
-    datetime: &str,
-    require_existing: bool,
-    require_overdue: bool,
-) -> Result<Task, AppError> {
-    let trimmed_id = id.trim();
-    if trimmed_id.is_empty() {
-        return Err(AppError::invalid_input("id is required"));
-    }
+/// Notes for the days from `first` to `last`, both included, keyed by day (`YYYY-MM-DD`).
+pub fn day_notes_between(first: Date, last: Date) -> Result<BTreeMap<String, String>, AppError> {
+    let state = json_store::load_state(&json_store::store_path()?)?;
+    Ok(notes_between(&state, first, last))
+}
 
-    let trimmed_datetime = datetime.trim();
-    if trimmed_datetime.is_empty() {
-        return Err(AppError::invalid_input("datetime is required"));
-    }
+/// Add `text` to the note for `date`, see [`Transaction::add_day_note`].
+pub fn add_day_note(date: Date, text: &str) -> Result<String, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| txn.add_day_note(date, text))
+}
 
-    let local_offset = local_offset()?;
-    let parsed = if let Ok(dt) = PrimitiveDateTime::parse(
-        trimmed_datetime,
-        &format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"),
-    ) {
-        dt.assume_offset(local_offset)
-    } else if let Ok(dt) = PrimitiveDateTime::parse(
-        trimmed_datetime,
-        &format_description!("[year]-[month]-[day] [hour]:[minute]"),
-    ) {
-        dt.assume_offset(local_offset)
-    } else if let Ok(time) = time::Time::parse(
-        trimmed_datetime,
-        &format_description!("[hour]:[minute]"),
-    ) {
-        let today = OffsetDateTime::now_utc().to_offset(local_offset).date();
-        today.with_time(time).assume_offset(local_offset)
-    } else if let Ok(date) = Date::parse(
-        trimmed_datetime,
-        &format_description!("[year]-[month]-[day]"),
-    ) {
-        date.with_hms(0, 0, 0).unwrap().assume_offset(local_offset)
-    } else {
-        OffsetDateTime::parse(trimmed_datetime, &Rfc3339).map_err(|_| {
-            AppError::invalid_input(
-                "datetime must be in format 'YYYY-MM-DD HH:MM:SS', 'YYYY-MM-DD', or RFC3339",
-            )
-        })?
-    };
+/// Remove the note for `date`, returning what it said.
+pub fn clear_day_note(date: Date) -> Result<Option<String>, AppError> {
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, |txn| Ok(txn.clear_day_note(date)))
+}
 
-    let scheduled_at = parsed
-        .format(&Rfc3339)
-        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+pub fn notify_overdue_or_urgent() -> Result<NotificationOutcome, AppError> {
+    notify_overdue_or_urgent_with_config(&NotificationConfig::default(), time::Duration::ZERO)
+}
 
-    let mut state = json_store::load_state(path)?;
-    let mut updated_task = None;
-    let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
+/// Notifies about urgent tasks and tasks more than `grace` past their deadline.
+pub fn notify_overdue_or_urgent_with_config(
+    config: &NotificationConfig,
+    grace: time::Duration,
+) -> Result<NotificationOutcome, AppError> {
+    let path = json_store::store_path()?;
+    let notifier = notifier_for_config(config)?;
+    let routed = route_notifiers(config)?;
+    let routes: Vec<(&NotificationRoute, &dyn Notifier)> = config
+        .routes
+        .iter()
+        .zip(routed.iter().map(|notifier| notifier.as_ref()))
+        .collect();
+    notify_overdue_or_urgent_with_path(
+        &path,
+        notifier.as_ref(),
+        &routes,
+        config,
+        clock::now_utc(),
+        grace,
+    )
+}
 
-    for task in &mut state.tasks {
-        if task.id == trimmed_id {
-            if require_existing && task.scheduled_at.is_none() {
-                return Err(AppError::invalid_input("task is not scheduled"));
-            }
-            if require_overdue {
-                let scheduled_at = task
-                    .scheduled_at
-                    .as_deref()
-                    .ok_or_else(|| AppError::invalid_input("task is not scheduled"))?;
-                if !is_overdue(scheduled_at, local_offset, now_local)? {
-                    return Err(AppError::invalid_input("task is not overdue"));
-                }
-            }
-            task.scheduled_at = Some(scheduled_at.clone());
-            updated_task = Some(task.clone());
-            break;
-        }
+/// With `notifications.on_schedule` set, confirm with a notification that `task` was just
+/// scheduled; otherwise do nothing.
+pub fn confirm_schedule(task: &Task, config: &NotificationConfig) -> Result<(), AppError> {
+    if !config.on_schedule {
+        return Ok(());
     }
+    let notifier = notifier_for_config(config)?;
+    notifier.notify_message("todoapp", &schedule_confirmation(task, local_offset()?)?)
+}
 
-    let updated = updated_task.ok_or_else(|| AppError::invalid_input("task not found"))?;
-    json_store::save_state(path, &state)?;
+/// Apply several mutations against a single load of the store and persist them with one
+/// save. Nothing is written when the closure or the final validation fails.
+///
+/// ```no_run
+/// use todo_core::task_api;
+///
+/// task_api::transaction(|txn| {
+///     let task = txn.add("Write report", false)?;
+///     txn.schedule(&task.id, "2025-12-24 09:00")?;
+///     txn.set_focus(&task.id)?;
+///     Ok(())
+/// })?;
+/// # Ok::<(), todo_core::error::AppError>(())
+/// ```
+pub fn transaction<T, F>(apply: F) -> Result<T, AppError>
+where
+    F: FnOnce(&mut Transaction) -> Result<T, AppError>,
+{
+    let path = json_store::store_path()?;
+    transaction_with_path(&path, apply)
+}
 
-    Ok(updated)
+/// [`transaction`] against `store` instead of the store file, such as a [`MemoryStore`] in a
+/// host without a file system.
+///
+/// ```
+/// use todo_core::storage::store::MemoryStore;
+/// use todo_core::task_api;
+///
+/// let store = MemoryStore::default();
+/// let task = task_api::transaction_in(&store, |txn| txn.add("Write report", false))?;
+/// assert_eq!(store.state().tasks[0].title, task.title);
+/// # Ok::<(), todo_core::error::AppError>(())
+/// ```
+///
+/// [`MemoryStore`]: crate::storage::store::MemoryStore
+pub fn transaction_in<T, F>(store: &dyn TaskStore, apply: F) -> Result<T, AppError>
+where
+    F: FnOnce(&mut Transaction) -> Result<T, AppError>,
+{
+    let original = store.load()?;
+    let mut txn = Transaction {
+        preexisting_duplicates: duplicate_ids(&original.tasks),
+        state: original.clone(),
+        local_offset: local_offset()?,
+        keep_stamps: false,
+        title_rules: TitleConfig::default(),
+    };
+    let result = apply(&mut txn)?;
+    txn.validate()?;
+    txn.stamp_modified(&original, store.author())?;
+    store.commit(&original, &txn.state)?;
+    Ok(result)
 }
 
-fn is_overdue(
-    scheduled_at: &str,
-    local_offset: UtcOffset,
-    now_local: OffsetDateTime,
-) -> Result<bool, AppError> {
-    let scheduled = OffsetDateTime::parse(scheduled_at, &Rfc3339)
-        .map_err(|_| AppError::invalid_data("scheduled_at must be RFC3339"))?;
-    Ok(scheduled.to_offset(local_offset) < now_local)
+fn transaction_with_path<T, F>(path: &Path, apply: F) -> Result<T, AppError>
+where
+    F: FnOnce(&mut Transaction) -> Result<T, AppError>,
+{
+    transaction_in(&FileStore::new(path), apply)
 }
 
-pub fn task_overdue(task: &Task) -> Result<bool, AppError> {
-    let scheduled_at = match task.scheduled_at.as_deref() {
-        Some(value) => value,
-        None => return Ok(false),
-    };
-    let local_offset = local_offset()?;
-    let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
-    is_overdue(scheduled_at, local_offset, now_local)
+/// In-memory view of the store handed to [`transaction`] closures.
+///
+/// Focus rules: only [`set_focus`](Self::set_focus) sets the focused task, and focus is cleared
+/// only when the focused task itself is deleted or completed. Every other mutation, including
+/// editing the focused task, leaves focus untouched. Because each transaction reloads the store
+/// before applying its changes, a command never restores or clears focus based on a stale view.
+pub struct Transaction {
+    state: TaskState,
+    local_offset: UtcOffset,
+    // Older stores may already contain colliding short ids; only new collisions are rejected.
+    preexisting_duplicates: HashSet<String>,
+    // Set when tasks arrive from a sync with the stamps of whoever changed them last.
+    keep_stamps: bool,
+    // The `titles` rules for this transaction's `add` and `edit`; none unless set.
+    title_rules: TitleConfig,
 }
-#[cfg(test)]
-mod tests {
-    use super::{
-        ListMode, add_task_with_path, complete_focused_task_with_path, complete_task_with_path,
-        delete_task_with_path, edit_task_with_path, filter_tasks, get_task_by_id_with_path,
-        list_today_with_focus_with_path, list_without_focus, notify_overdue_or_urgent_with_path,
-        reschedule_task_with_path, schedule_task_with_path, set_focus_with_path,
-        set_task_urgent_with_path,
-    };
-    use crate::error::AppError;
-    use crate::model::{CompletionEntry, Task, TaskStatus};
-    use crate::notify::Notifier;
-    use crate::storage::json_store;
-    use std::cell::RefCell;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
-    use time::format_description::well_known::Rfc3339;
-    use time::{Date, Duration, Month, OffsetDateTime, UtcOffset};
 
-    fn temp_path(file_name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+impl Transaction {
+    pub fn tasks(&self) -> &[Task] {
+        &self.state.tasks
     }
 
-    #[test]
-    fn add_task_rejects_blank_title() {
-        let path = temp_path("blank-title.json");
-        let err = add_task_with_path(&path, "  ", false).unwrap_err();
-        std::fs::remove_file(&path).ok();
+    pub fn focused_task_id(&self) -> Option<&str> {
+        self.state.focused_task_id.as_deref()
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    /// Clean the titles [`add`](Self::add) and [`edit`](Self::edit) set from here on with
+    /// `rules`, such as the `titles` config.
+    pub fn set_title_rules(&mut self, rules: TitleConfig) {
+        self.title_rules = rules;
     }
 
-    #[test]
-    fn add_task_writes_to_store() {
-        let path = temp_path("add-task.json");
-        let task = add_task_with_path(&path, "demo", false).unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+    pub fn add(&mut self, title: &str, urgent: bool) -> Result<Task, AppError> {
+        let title = self.stored_title(title);
+        let task = Task::builder()
+            .id(&self.next_task_id())
+            .title(&title)
+            .urgent(urgent)
+            .build()?;
 
-        assert_eq!(loaded.len(), 1);
-        assert_eq!(loaded[0].id, task.id);
-        assert_eq!(loaded[0].title, task.title);
-        assert_eq!(loaded[0].scheduled_at, None);
-        assert_eq!(loaded[0].completed_at, None);
-        assert!(loaded[0].completion_history.is_empty());
+        self.state.tasks.push(task.clone());
+        Ok(task)
     }
 
-    #[test]
-    fn set_focus_sets_focused_task_id() {
-        let path = temp_path("focus.json");
-        let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "first".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "second".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-        ];
+    /// An open task titled `title`, ignoring case, that was added today or is planned for
+    /// today in local time. `add --once-per-day` returns it instead of adding another.
+    pub fn open_task_titled_today(&self, title: &str) -> Option<Task> {
+        let wanted = text::fold_case(&self.stored_title(title));
+        let today = clock::now_utc().to_offset(self.local_offset).date();
+        let is_today = |stamp: Option<&str>| {
+            stamp
+                .and_then(|value| OffsetDateTime::parse(value, &Rfc3339).ok())
+                .is_some_and(|at| at.to_offset(self.local_offset).date() == today)
+        };
+        self.state
+            .tasks
+            .iter()
+            .find(|task| {
+                task.status.is_open()
+                    && text::fold_case(&task.title) == wanted
+                    && (is_today(Some(&task.created_at)) || is_today(task.planned_at()))
+            })
+            .cloned()
+    }
 
-        json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks: tasks.clone(),
-                focused_task_id: None,
-            },
-        )
-        .unwrap();
+    pub fn edit(&mut self, id: &str, new_title: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let normalized_title = self.stored_title(new_title);
+        if normalized_title.is_empty() {
+            return Err(AppError::invalid_input("title is required"));
+        }
 
-        let focused = set_focus_with_path(&path, "task-2").unwrap();
-        let loaded = json_store::load_state(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+        let task = self.task_mut(trimmed_id)?;
+        task.title = normalized_title;
 
-        assert_eq!(focused.id, "task-2");
-        assert_eq!(loaded.focused_task_id, Some("task-2".to_string()));
-        assert_eq!(loaded.tasks.len(), 2);
+        Ok(task.clone())
     }
 
-    #[test]
-    fn set_focus_rejects_missing_task() {
-        let path = temp_path("focus-missing.json");
-        let tasks = vec![Task {
-            id: "task-1".to_string(),
-            title: "first".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        }];
-
-        json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks,
-                focused_task_id: None,
-            },
-        )
-        .unwrap();
+    /// `title` as [`add`](Self::add) and [`edit`](Self::edit) store it: normalized, then
+    /// cleaned by the [title rules](Self::set_title_rules).
+    fn stored_title(&self, title: &str) -> String {
+        self.title_rules.apply(&text::normalize(title))
+    }
 
-        let err = set_focus_with_path(&path, "task-2").unwrap_err();
-        std::fs::remove_file(&path).ok();
+    pub fn delete(&mut self, id: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let index = self
+            .state
+            .tasks
+            .iter()
+            .position(|task| task.id == trimmed_id)
+            .ok_or_else(task_not_found)?;
+
+        let removed = self.state.tasks.remove(index);
+        if self.state.focused_task_id.as_deref() == Some(trimmed_id) {
+            self.state.focused_task_id = None;
+        }
 
-        assert_eq!(err.code(), "invalid_input");
+        Ok(removed)
     }
 
-    #[test]
-    fn set_task_urgent_updates_flag() {
-        let path = temp_path("urgent-toggle.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "urgent".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
-
-        json_store::save_tasks(&path, &[task.clone()]).unwrap();
+    pub fn complete(&mut self, id: &str, message: Option<&str>) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?;
+        ensure_open(task)?;
+        let trimmed_message = optional_message(message)?;
+        mark_completed(task, trimmed_message)?;
+        let updated = task.clone();
 
-        let updated = set_task_urgent_with_path(&path, "task-1", true).unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+        if self.state.focused_task_id.as_deref() == Some(trimmed_id) {
+            self.state.focused_task_id = None;
+        }
 
-        assert!(updated.urgent);
-        assert!(!loaded.is_empty());
-        assert!(loaded[0].urgent);
+        Ok(updated)
     }
 
-    #[test]
-    fn set_task_urgent_rejects_missing_task() {
-        let path = temp_path("urgent-missing.json");
-        json_store::save_tasks(&path, &[]).unwrap();
+    pub fn complete_focused(&mut self, message: Option<&str>) -> Result<Task, AppError> {
+        let focused_id = self.state.focused_task_id.clone().ok_or_else(|| {
+            AppError::invalid_input("no focused task").with_hint("pick one with `todo focus <id>`")
+        })?;
+        let trimmed_message = optional_message(message)?;
 
-        let err = set_task_urgent_with_path(&path, "task-1", true).unwrap_err();
-        std::fs::remove_file(&path).ok();
+        let task = self.task_mut(&focused_id)?;
+        ensure_open(task)?;
+        mark_completed(task, trimmed_message)?;
+        let updated = task.clone();
+        self.state.focused_task_id = None;
 
-        assert_eq!(err.code(), "invalid_input");
+        Ok(updated)
     }
 
-    #[test]
-    fn get_task_by_id_returns_task() {
-        let path = temp_path("get-task.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    fn revert_completion(&mut self, record: &CompletionUndo) -> Result<Task, AppError> {
+        let task = self.task_mut(&record.after.id)?;
+        // `record.after` was copied before the commit stamped the task, so ignore the stamp.
+        let unchanged = Task {
+            modified_by: record.after.modified_by.clone(),
+            modified_at: record.after.modified_at.clone(),
+            ..task.clone()
         };
+        if unchanged != *record.after {
+            return Err(AppError::invalid_input(
+                "cannot undo: the task has changed since it was completed",
+            ));
+        }
+        *task = (*record.before).clone();
+        let reopened = task.clone();
+        if record.was_focused && self.state.focused_task_id.is_none() {
+            self.state.focused_task_id = Some(reopened.id.clone());
+        }
 
-        json_store::save_tasks(&path, std::slice::from_ref(&task)).unwrap();
+        Ok(reopened)
+    }
 
-        let fetched = get_task_by_id_with_path(&path, "task-1").unwrap();
-        std::fs::remove_file(&path).ok();
+    pub fn schedule(&mut self, id: &str, datetime: &str) -> Result<Task, AppError> {
+        self.update_schedule(id, datetime, false, None)
+    }
 
-        assert_eq!(fetched, task);
+    /// Moves the deadline of a task that is more than `grace` past it.
+    pub fn reschedule(
+        &mut self,
+        id: &str,
+        datetime: &str,
+        grace: time::Duration,
+    ) -> Result<Task, AppError> {
+        self.update_schedule(id, datetime, true, Some(grace))
     }
 
-    #[test]
-    fn get_task_by_id_rejects_missing_task() {
-        let path = temp_path("get-task-missing.json");
-        json_store::save_tasks(&path, &[]).unwrap();
+    /// Sets `start_at`, when the task is planned to be worked on; the deadline stays as is.
+    pub fn schedule_start(&mut self, id: &str, datetime: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let start_at = schedule_timestamp(datetime, self.local_offset)?;
+        let task = self.task_mut(trimmed_id)?;
+        task.start_at = Some(start_at);
+        Ok(task.clone())
+    }
 
-        let err = get_task_by_id_with_path(&path, "task-1").unwrap_err();
-        std::fs::remove_file(&path).ok();
+    pub fn set_focus(&mut self, id: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?.clone();
+        self.state.focused_task_id = Some(task.id.clone());
+        Ok(task)
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    /// Move a pending or waiting task to in progress, dropping any `waiting_until`, and open a
+    /// work session.
+    pub fn start(&mut self, id: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let started_at = now_rfc3339()?;
+        let task = self.task_mut(trimmed_id)?;
+        ensure_open(task)?;
+        if task.status == TaskStatus::InProgress {
+            return Err(AppError::invalid_input("task already in progress"));
+        }
+        task.status = TaskStatus::InProgress;
+        task.waiting_until = None;
+        task.sessions.push(WorkSession {
+            started_at,
+            ended_at: None,
+        });
+        Ok(task.clone())
     }
 
-    #[test]
-    fn complete_focused_task_marks_completed_and_clears_focus() {
-        let path = temp_path("complete-focused.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    /// Park an open task as waiting, optionally until a date given like `schedule` takes it.
+    /// Waiting again replaces the date, or clears it when none is given.
+    pub fn wait(&mut self, id: &str, until: Option<&str>) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let waiting_until = match until.map(str::trim) {
+            Some("") => return Err(AppError::invalid_input("datetime is required")),
+            Some(value) => Some(
+                parse_schedule_datetime(value, self.local_offset)?
+                    .format(&Rfc3339)
+                    .map_err(|err| AppError::invalid_data(err.to_string()))?,
+            ),
+            None => None,
         };
 
-        json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks: vec![task],
-                focused_task_id: Some("task-1".to_string()),
-            },
-        )
-        .unwrap();
+        let waited_at = now_rfc3339()?;
+        let task = self.task_mut(trimmed_id)?;
+        ensure_open(task)?;
+        task.status = TaskStatus::Waiting;
+        task.waiting_until = waiting_until;
+        end_session(task, &waited_at);
+        Ok(task.clone())
+    }
 
-        let completed = complete_focused_task_with_path(&path, Some("ship it")).unwrap();
-        let loaded = json_store::load_state(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+    /// Drop an open task without completing it, keeping it and its history for reports.
+    /// Clears the focus when it was on this task.
+    pub fn cancel(&mut self, id: &str, reason: Option<&str>) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let reason = optional_message(reason)?;
+        let cancelled_at = now_rfc3339()?;
+        let task = self.task_mut(trimmed_id)?;
+        ensure_open(task)?;
+        task.status = TaskStatus::Cancelled;
+        task.waiting_until = None;
+        end_session(task, &cancelled_at);
+        task.cancellation = Some(Cancellation {
+            cancelled_at,
+            reason,
+        });
+        let updated = task.clone();
+
+        if self.state.focused_task_id.as_deref() == Some(trimmed_id) {
+            self.state.focused_task_id = None;
+        }
 
-        assert_eq!(completed.status, TaskStatus::Completed);
-        assert!(completed.completed_at.is_some());
-        assert_eq!(completed.completion_history.len(), 1);
-        assert_eq!(loaded.focused_task_id, None);
+        Ok(updated)
     }
 
-    #[test]
-    fn complete_focused_task_rejects_missing_focus() {
-        let path = temp_path("complete-focused-missing.json");
-        json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks: Vec::new(),
-                focused_task_id: None,
-            },
-        )
-        .unwrap();
+    /// Set the marker's color and/or symbol, keeping the part not given.
+    pub fn set_marker(
+        &mut self,
+        id: &str,
+        color: Option<MarkerColor>,
+        symbol: Option<&str>,
+    ) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let symbol = symbol.map(marker_symbol).transpose()?;
+        if color.is_none() && symbol.is_none() {
+            return Err(AppError::invalid_input("marker needs a color or a symbol"));
+        }
+        let task = self.task_mut(trimmed_id)?;
+        let marker = task.marker.get_or_insert_with(Marker::default);
+        if color.is_some() {
+            marker.color = color;
+        }
+        if symbol.is_some() {
+            marker.symbol = symbol;
+        }
+        Ok(task.clone())
+    }
 
-        let err = complete_focused_task_with_path(&path, None).unwrap_err();
-        std::fs::remove_file(&path).ok();
+    pub fn clear_marker(&mut self, id: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?;
+        task.marker = None;
+        Ok(task.clone())
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    pub fn set_urgent(&mut self, id: &str, urgent: bool) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?;
+        task.urgent = urgent;
+        Ok(task.clone())
     }
 
-    #[test]
-    fn complete_task_clears_focus_when_matching_id() {
-        let path = temp_path("complete-clears-focus.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    /// Replace the task's tags. Tags are trimmed and de-duplicated, keeping first-seen order.
+    pub fn set_tags(&mut self, id: &str, tags: &[String]) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let trimmed = tag.trim();
+            if trimmed.is_empty() {
+                return Err(AppError::invalid_input("tag must not be empty"));
+            }
+            if !normalized.iter().any(|existing| existing == trimmed) {
+                normalized.push(trimmed.to_string());
+            }
+        }
 
-        json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks: vec![task],
-                focused_task_id: Some("task-1".to_string()),
-            },
-        )
-        .unwrap();
+        let task = self.task_mut(trimmed_id)?;
+        task.tags = normalized;
+        Ok(task.clone())
+    }
 
-        let completed = complete_task_with_path(&path, "task-1", None).unwrap();
-        let loaded = json_store::load_state(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+    /// Replace the task's notes; `None` or blank text clears them.
+    pub fn set_notes(&mut self, id: &str, notes: Option<&str>) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?;
+        task.notes = notes
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+        Ok(task.clone())
+    }
 
-        assert_eq!(completed.status, TaskStatus::Completed);
-        assert_eq!(loaded.focused_task_id, None);
-    }
-
-    #[test]
-    fn filter_tasks_returns_today_and_backlog() {
-        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
-        let offset = UtcOffset::UTC;
-        let today_dt = today.with_hms(12, 0, 0).unwrap().assume_offset(offset);
-        let tomorrow_dt = (today + Duration::days(1))
-            .with_hms(9, 0, 0)
-            .unwrap()
-            .assume_offset(offset);
-
-        let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "today".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(today_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "future".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(tomorrow_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-3".to_string(),
-                title: "unscheduled".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-        ];
-
-        let today_tasks = filter_tasks(&tasks, today, offset, ListMode::Today).unwrap();
-        assert_eq!(today_tasks.len(), 1);
-        assert_eq!(today_tasks[0].id, "task-1");
-
-        let backlog_tasks = filter_tasks(&tasks, today, offset, ListMode::Backlog).unwrap();
-        assert_eq!(backlog_tasks.len(), 2);
-        assert!(backlog_tasks.iter().any(|task| task.id == "task-2"));
-        assert!(backlog_tasks.iter().any(|task| task.id == "task-3"));
+    /// Assign the task to a project; `None` or blank text removes it from its project.
+    pub fn set_project(&mut self, id: &str, project: Option<&str>) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?;
+        task.project = project
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+        Ok(task.clone())
     }
 
-    #[test]
-    fn filter_tasks_backlog_includes_unscheduled_tasks() {
-        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
-        let offset = UtcOffset::UTC;
-        let future_dt = (today + Duration::days(2))
-            .with_hms(10, 0, 0)
-            .unwrap()
-            .assume_offset(offset);
-
-        let tasks = vec![
-            Task {
-                id: "future".to_string(),
-                title: "scheduled".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(future_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "unscheduled".to_string(),
-                title: "later".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-        ];
-
-        let backlog_tasks = filter_tasks(&tasks, today, offset, ListMode::Backlog).unwrap();
-        assert_eq!(backlog_tasks.len(), 2);
-        assert!(backlog_tasks.iter().any(|task| task.id == "future"));
-        assert!(backlog_tasks.iter().any(|task| task.id == "unscheduled"));
+    /// Set a metadata value, or remove the key when `value` is `None`.
+    pub fn set_metadata(
+        &mut self,
+        id: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let key = metadata_key(key)?;
+        let task = self.task_mut(trimmed_id)?;
+        match value.map(str::trim) {
+            Some("") => return Err(AppError::invalid_input("metadata value is required")),
+            Some(value) => {
+                if key == stats::ESTIMATE_KEY {
+                    duration::parse(value)?;
+                }
+                task.metadata.insert(key.to_string(), value.to_string());
+            }
+            None => {
+                if task.metadata.remove(key).is_none() {
+                    return Err(AppError::invalid_input(format!(
+                        "task has no metadata key '{key}'"
+                    )));
+                }
+            }
+        }
+        Ok(task.clone())
     }
 
-    #[test]
-    fn filter_tasks_reports_invalid_scheduled_at() {
-        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
-        let offset = UtcOffset::UTC;
-        let tasks = vec![Task {
-            id: "task-1".to_string(),
-            title: "bad".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some("not-a-date".to_string()),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        }];
-
-        let err = filter_tasks(&tasks, today, offset, ListMode::Today).unwrap_err();
-        assert_eq!(err.code(), "invalid_data");
+    /// Append a timestamped comment; the text is trimmed but otherwise kept as written.
+    pub fn add_comment(&mut self, id: &str, author: &str, text: &str) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let author = author.trim();
+        if author.is_empty() {
+            return Err(AppError::invalid_input("comment author is required"));
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(AppError::invalid_input("comment text is required"));
+        }
+        let created_at = now_rfc3339()?;
+
+        let task = self.task_mut(trimmed_id)?;
+        task.comments.push(Comment {
+            author: author.to_string(),
+            text: text.to_string(),
+            created_at,
+        });
+        Ok(task.clone())
     }
 
-    #[test]
-    fn edit_task_updates_title() {
-        let path = temp_path("edit-task.json");
-        let original = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some("2025-12-22T09:00:00Z".to_string()),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    pub fn set_ticket(&mut self, id: &str, link: Option<TicketLink>) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let link = match link {
+            Some(mut link) => {
+                link.key = link.key.trim().to_string();
+                if link.key.is_empty() || link.key.chars().any(char::is_whitespace) {
+                    return Err(AppError::invalid_input(
+                        "ticket key is required and must not contain spaces",
+                    ));
+                }
+                link.url = link
+                    .url
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty());
+                Some(link)
+            }
+            None => None,
         };
-
-        json_store::save_tasks(&path, std::slice::from_ref(&original)).unwrap();
-
-        let updated = edit_task_with_path(&path, "task-1", "new").unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
-
-        assert_eq!(updated.title, "new");
-        assert_eq!(updated.scheduled_at, original.scheduled_at);
-        assert_eq!(loaded[0].title, "new");
-        assert_eq!(loaded[0].scheduled_at, original.scheduled_at);
+        let task = self.task_mut(trimmed_id)?;
+        task.ticket = link;
+        Ok(task.clone())
     }
 
-    #[test]
-    fn edit_task_rejects_blank_title() {
-        let path = temp_path("edit-blank.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    /// Apply a fetched ticket: adopt its title, record its status, and complete the task when
+    /// the ticket is closed.
+    pub fn sync_ticket(
+        &mut self,
+        id: &str,
+        ticket: &TicketState,
+        synced_at: &str,
+    ) -> Result<TicketSync, AppError> {
+        let trimmed_id = required_id(id)?;
+        let task = self.task_mut(trimmed_id)?;
+        let Some(link) = task.ticket.as_mut() else {
+            return Err(
+                AppError::invalid_input("task is not linked to a ticket").with_hint(format!(
+                    "link one with `todo link-ticket {trimmed_id} <KEY>`"
+                )),
+            );
         };
+        link.status = ticket.status.clone();
+        link.synced_at = Some(synced_at.to_string());
+        let key = link.key.clone();
+        let title = text::normalize(&ticket.title);
+        let retitled = task.title != title;
+        if retitled {
+            task.title = title;
+        }
 
-        json_store::save_tasks(&path, &[task]).unwrap();
-
-        let err = edit_task_with_path(&path, "task-1", "  ").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        let completed = ticket.closed && task.status.is_open();
+        let task = if completed {
+            self.complete(trimmed_id, Some(&format!("Ticket {key} closed")))?
+        } else {
+            task.clone()
+        };
+        Ok(TicketSync {
+            task,
+            retitled,
+            completed,
+        })
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    /// Add `text` to the note for `date` as a line of its own, returning the whole note.
+    pub fn add_day_note(&mut self, date: Date, text: &str) -> Result<String, AppError> {
+        let text = text::normalize(text);
+        if text.is_empty() {
+            return Err(AppError::invalid_input("journal text is required"));
+        }
+        let note = self.state.day_notes.entry(date.to_string()).or_default();
+        if !note.is_empty() {
+            note.push('\n');
+        }
+        note.push_str(&text);
+        Ok(note.clone())
     }
 
-    #[test]
-    fn edit_task_rejects_missing_id() {
-        let path = temp_path("edit-missing.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    pub fn clear_day_note(&mut self, date: Date) -> Option<String> {
+        self.state.day_notes.remove(&date.to_string())
+    }
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+    pub fn contexts(&self) -> ContextList {
+        ContextList {
+            contexts: self.state.contexts.clone(),
+            active: self.state.active_context.clone(),
+        }
+    }
 
-        let err = edit_task_with_path(&path, "task-2", "new").unwrap_err();
-        std::fs::remove_file(&path).ok();
+    /// Define or replace a named filter; see [`crate::filter`] for the syntax.
+    pub fn define_context(&mut self, name: &str, filter: &str) -> Result<ContextList, AppError> {
+        let name = context_name(name)?;
+        let filter = filter.trim();
+        Filter::parse(filter)?;
+        self.state
+            .contexts
+            .insert(name.to_string(), filter.to_string());
+        Ok(self.contexts())
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    /// Remove a context, deactivating it first if it is active.
+    pub fn delete_context(&mut self, name: &str) -> Result<ContextList, AppError> {
+        let name = context_name(name)?;
+        if self.state.contexts.remove(name).is_none() {
+            return Err(AppError::invalid_input(format!(
+                "context '{name}' not found"
+            )));
+        }
+        if self.state.active_context.as_deref() == Some(name) {
+            self.state.active_context = None;
+        }
+        Ok(self.contexts())
     }
 
-    #[test]
-    fn edit_task_rejects_blank_id() {
-        let path = temp_path("edit-blank-id.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    /// Apply a defined context to every listing until cleared with `None`.
+    pub fn set_context(&mut self, name: Option<&str>) -> Result<ContextList, AppError> {
+        self.state.active_context = match name {
+            Some(name) => {
+                let name = context_name(name)?;
+                if !self.state.contexts.contains_key(name) {
+                    return Err(AppError::invalid_input(format!(
+                        "context '{name}' not found"
+                    )));
+                }
+                Some(name.to_string())
+            }
+            None => None,
         };
-
-        json_store::save_tasks(&path, &[task]).unwrap();
-
-        let err = edit_task_with_path(&path, "  ", "new").unwrap_err();
-        std::fs::remove_file(&path).ok();
-
-        assert_eq!(err.code(), "invalid_input");
+        Ok(self.contexts())
     }
 
-    #[test]
-    fn complete_task_sets_completed_at_and_history() {
-        let path = temp_path("complete-task.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some("2025-12-22T09:00:00Z".to_string()),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    /// With `overdue_grace`, only a task more than that past its deadline is rescheduled.
+    fn update_schedule(
+        &mut self,
+        id: &str,
+        datetime: &str,
+        require_existing: bool,
+        overdue_grace: Option<time::Duration>,
+    ) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+
+        let local_offset = self.local_offset;
+        let due_at = schedule_timestamp(datetime, local_offset)?;
+        let now_local = clock::now_utc().to_offset(local_offset);
+
+        let task = self.task_mut(trimmed_id)?;
+        let not_scheduled = || {
+            AppError::invalid_input("task is not scheduled")
+                .with_hint(format!("use `todo schedule {trimmed_id} <datetime>` first"))
         };
+        if require_existing && task.due_at.is_none() {
+            return Err(not_scheduled());
+        }
+        if let Some(grace) = overdue_grace {
+            let current = task.due_at.as_deref().ok_or_else(not_scheduled)?;
+            if !is_overdue(current, now_local, grace)? {
+                return Err(AppError::invalid_input("task is not overdue").with_hint(format!(
+                    "use `todo schedule {trimmed_id} <datetime>` to move a deadline that has not passed"
+                )));
+            }
+        }
+        task.due_at = Some(due_at);
 
-        json_store::save_tasks(&path, std::slice::from_ref(&task)).unwrap();
-
-        let updated = complete_task_with_path(&path, "task-1", Some("ship it")).unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
-
-        assert_eq!(updated.status, TaskStatus::Completed);
-        let completed_at = updated.completed_at.clone().expect("completed_at set");
-        OffsetDateTime::parse(&completed_at, &Rfc3339).unwrap();
-        assert_eq!(updated.scheduled_at, task.scheduled_at);
-        assert_eq!(updated.completion_history.len(), 1);
-        assert_eq!(updated.completion_history[0].message, "ship it");
-        assert_eq!(updated.completion_history[0].completed_at, completed_at);
-
-        assert_eq!(loaded[0].status, TaskStatus::Completed);
-        assert_eq!(loaded[0].completed_at, Some(completed_at));
-        assert_eq!(loaded[0].completion_history.len(), 1);
-        assert_eq!(loaded[0].completion_history[0].message, "ship it");
+        Ok(task.clone())
     }
 
-    #[test]
-    fn complete_task_without_message_keeps_history_empty() {
-        let path = temp_path("complete-no-message.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    /// Removes completed and cancelled tasks closed before `cutoff`, returning them.
+    fn take_closed_before(&mut self, cutoff: OffsetDateTime) -> Result<Vec<Task>, AppError> {
+        self.take_tasks(|task| {
+            let closed_at = task
+                .closed_at()
+                .map(|value| {
+                    OffsetDateTime::parse(value, &Rfc3339).map_err(|_| {
+                        AppError::invalid_data("completed_at and cancelled_at must be RFC3339")
+                    })
+                })
+                .transpose()?;
+            Ok(closed_at.is_some_and(|at| at < cutoff))
+        })
+    }
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+    /// Removes routine tasks scheduled for a day before `today`, returning them.
+    fn take_expired_routines(&mut self, today: Date) -> Result<Vec<Task>, AppError> {
+        let local_offset = self.local_offset;
+        self.take_tasks(|task| {
+            let Some(scheduled_at) = task.due_at.as_deref().filter(|_| task.routine.is_some())
+            else {
+                return Ok(false);
+            };
+            let scheduled = OffsetDateTime::parse(scheduled_at, &Rfc3339)
+                .map_err(|_| AppError::invalid_data("due_at must be RFC3339"))?;
+            Ok(scheduled.to_offset(local_offset).date() < today)
+        })
+    }
 
-        let updated = complete_task_with_path(&path, "task-1", None).unwrap();
-        std::fs::remove_file(&path).ok();
+    /// Removes the tasks `take` picks, clearing the focus if it was on one of them.
+    fn take_tasks<F>(&mut self, mut take: F) -> Result<Vec<Task>, AppError>
+    where
+        F: FnMut(&Task) -> Result<bool, AppError>,
+    {
+        let mut taken = Vec::new();
+        let mut kept = Vec::with_capacity(self.state.tasks.len());
+        for task in std::mem::take(&mut self.state.tasks) {
+            if take(&task)? {
+                taken.push(task);
+            } else {
+                kept.push(task);
+            }
+        }
+        self.state.tasks = kept;
+        if let Some(focused) = self.state.focused_task_id.as_deref()
+            && taken.iter().any(|task| task.id == focused)
+        {
+            self.state.focused_task_id = None;
+        }
 
-        assert_eq!(updated.status, TaskStatus::Completed);
-        assert!(updated.completed_at.is_some());
-        assert!(updated.completion_history.is_empty());
+        Ok(taken)
     }
 
-    #[test]
-    fn complete_task_rejects_already_completed() {
-        let path = temp_path("complete-already.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Completed,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: Some("2025-12-22T10:00:00Z".to_string()),
-            completion_history: vec![CompletionEntry {
-                message: "already".to_string(),
-                completed_at: "2025-12-22T10:00:00Z".to_string(),
-            }],
-            urgent: false,
-        };
+    /// Add a task for each item of the routine, due by the end of today and marked with the
+    /// routine's name so it is archived once the day is over. A routine starts once a day.
+    pub fn start_routine(&mut self, name: &str, items: &[String]) -> Result<Vec<Task>, AppError> {
+        let now = clock::now_utc().to_offset(self.local_offset);
+        let end_of_day = now
+            .replace_time(time::macros::time!(23:59:59))
+            .format(&Rfc3339)
+            .map_err(|err| AppError::invalid_data(err.to_string()))?;
+        if items.iter().all(|item| item.trim().is_empty()) {
+            return Err(AppError::invalid_input(format!(
+                "routine '{name}' has no items"
+            )));
+        }
+        for task in &self.state.tasks {
+            if task.routine.as_deref() == Some(name)
+                && task.due_at.as_deref().is_some_and(|at| {
+                    OffsetDateTime::parse(at, &Rfc3339)
+                        .is_ok_and(|at| at.to_offset(self.local_offset).date() == now.date())
+                })
+            {
+                return Err(AppError::invalid_input(format!(
+                    "routine '{name}' was already started today"
+                )));
+            }
+        }
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        let mut started = Vec::new();
+        for item in items.iter().filter(|item| !item.trim().is_empty()) {
+            let id = self.add(item, false)?.id;
+            let task = self.task_mut(&id)?;
+            task.due_at = Some(end_of_day.clone());
+            task.routine = Some(name.to_string());
+            started.push(task.clone());
+        }
+        Ok(started)
+    }
 
-        let err = complete_task_with_path(&path, "task-1", Some("ship it")).unwrap_err();
-        std::fs::remove_file(&path).ok();
+    /// Add a task read by `import` with every field it carries. Errors name its row.
+    pub fn import(&mut self, imported: &ImportedTask) -> Result<Task, AppError> {
+        self.add_imported(imported).map_err(|err| {
+            let message = format!("row {}: {}", imported.row, err.message());
+            err.with_message(message)
+        })
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    fn add_imported(&mut self, imported: &ImportedTask) -> Result<Task, AppError> {
+        let mut task = self.add(&imported.title, imported.urgent)?;
+        if !imported.tags.is_empty() {
+            task = self.set_tags(&task.id, &imported.tags)?;
+        }
+        if imported.notes.is_some() {
+            task = self.set_notes(&task.id, imported.notes.as_deref())?;
+        }
+        if imported.project.is_some() {
+            task = self.set_project(&task.id, imported.project.as_deref())?;
+        }
+        if let Some(due_at) = &imported.due_at {
+            task = self.schedule(&task.id, due_at)?;
+        }
+        if let Some(start_at) = &imported.start_at {
+            task = self.schedule_start(&task.id, start_at)?;
+        }
+        Ok(task)
     }
 
-    #[test]
-    fn complete_task_rejects_blank_id() {
-        let path = temp_path("complete-blank-id.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    /// Move the deadline of a task more than `grace` past it to `day`, keeping its time of day.
+    /// On today, a time that has already passed becomes the end of the day instead.
+    pub fn move_overdue(
+        &mut self,
+        id: &str,
+        day: TriageDay,
+        grace: time::Duration,
+    ) -> Result<Task, AppError> {
+        let trimmed_id = required_id(id)?;
+        let current = self.task_mut(trimmed_id)?.due_at.clone();
+        let Some(current) = current else {
+            return Err(AppError::invalid_input("task is not scheduled"));
         };
+        let due = OffsetDateTime::parse(&current, &Rfc3339)
+            .map_err(|_| AppError::invalid_data("due_at must be RFC3339"))?;
+        let now = clock::now_utc().to_offset(self.local_offset);
+        let moved = triage_deadline(due, now, day)
+            .format(&Rfc3339)
+            .map_err(|err| AppError::invalid_data(err.to_string()))?;
+        self.update_schedule(trimmed_id, &moved, true, Some(grace))
+    }
 
-        json_store::save_tasks(&path, &[task]).unwrap();
-
-        let err = complete_task_with_path(&path, "  ", None).unwrap_err();
-        std::fs::remove_file(&path).ok();
-
-        assert_eq!(err.code(), "invalid_input");
+    /// Pending tasks on today's plan: those planned for today or earlier (see
+    /// [`Task::planned_at`]), plus the focused task.
+    pub fn planned_today(&self) -> Result<usize, AppError> {
+        let today = clock::now_utc().to_offset(self.local_offset).date();
+        let mut planned = 0;
+        for task in &self.state.tasks {
+            if !task.status.is_open() {
+                continue;
+            }
+            let focused = self.state.focused_task_id.as_deref() == Some(task.id.as_str());
+            let scheduled_today = match task.planned_at() {
+                Some(value) => {
+                    OffsetDateTime::parse(value, &Rfc3339)
+                        .map_err(|_| AppError::invalid_data("start_at and due_at must be RFC3339"))?
+                        .to_offset(self.local_offset)
+                        .date()
+                        <= today
+                }
+                None => false,
+            };
+            if focused || scheduled_today {
+                planned += 1;
+            }
+        }
+        Ok(planned)
     }
 
-    #[test]
-    fn complete_task_rejects_blank_message() {
-        let path = temp_path("complete-blank-message.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    fn task_mut(&mut self, id: &str) -> Result<&mut Task, AppError> {
+        self.state
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == id)
+            .ok_or_else(task_not_found)
+    }
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+    // IDs are the last 4 digits of the current nanoseconds to keep them short to type.
+    // Collisions are resolved by probing forward, which matters once several tasks are added
+    // inside the same transaction.
+    fn next_task_id(&self) -> String {
+        let nanos = clock::now_utc().unix_timestamp_nanos();
+        let taken: HashSet<&str> = self
+            .state
+            .tasks
+            .iter()
+            .map(|task| task.id.as_str())
+            .collect();
+        let start = nanos % 10000;
+        (0..10000)
+            .map(|offset| ((start + offset) % 10000).to_string())
+            .find(|candidate| !taken.contains(candidate.as_str()))
+            .unwrap_or_else(|| nanos.to_string())
+    }
 
-        let err = complete_task_with_path(&path, "task-1", Some("   ")).unwrap_err();
-        std::fs::remove_file(&path).ok();
+    /// Sets `modified_by` to `by` and `modified_at` on every task that differs from `original`.
+    /// Runs just before the commit, so the copies mutations return do not carry the new stamp.
+    fn stamp_modified(&mut self, original: &TaskState, by: Option<String>) -> Result<(), AppError> {
+        if self.keep_stamps {
+            return Ok(());
+        }
+        let before: HashMap<&str, &Task> = original
+            .tasks
+            .iter()
+            .map(|task| (task.id.as_str(), task))
+            .collect();
+        let changed: Vec<usize> = self
+            .state
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| before.get(task.id.as_str()) != Some(task))
+            .map(|(index, _)| index)
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
 
-        assert_eq!(err.code(), "invalid_input");
+        let at = now_rfc3339()?;
+        for index in changed {
+            let task = &mut self.state.tasks[index];
+            task.modified_by.clone_from(&by);
+            task.modified_at = Some(at.clone());
+        }
+        Ok(())
     }
 
-    #[test]
-    fn complete_task_rejects_missing_id() {
-        let path = temp_path("complete-missing.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
-
-        json_store::save_tasks(&path, &[task]).unwrap();
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(id) = duplicate_ids(&self.state.tasks)
+            .into_iter()
+            .find(|id| !self.preexisting_duplicates.contains(id))
+        {
+            return Err(AppError::invalid_data(format!("duplicate task id '{id}'")));
+        }
 
-        let err = complete_task_with_path(&path, "task-2", None).unwrap_err();
-        std::fs::remove_file(&path).ok();
+        if let Some(focused_id) = self.state.focused_task_id.as_deref()
+            && !self.state.tasks.iter().any(|task| task.id == focused_id)
+        {
+            return Err(AppError::invalid_data("focused_task_id not found"));
+        }
 
-        assert_eq!(err.code(), "invalid_input");
+        Ok(())
     }
+}
 
-    #[test]
-    fn delete_task_removes_task() {
-        let path = temp_path("delete-task.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+fn context_name(name: &str) -> Result<&str, AppError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid_input("context name is required"));
+    }
+    if trimmed.chars().any(char::is_whitespace) {
+        return Err(AppError::invalid_input(
+            "context name must not contain spaces",
+        ));
+    }
+    if trimmed.eq_ignore_ascii_case("none") {
+        return Err(AppError::invalid_input(
+            "'none' is reserved for clearing the context",
+        ));
+    }
+    Ok(trimmed)
+}
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+/// Keys appear in filters as `meta.KEY`, so they are limited to characters that need no quoting.
+pub(crate) fn metadata_key(key: &str) -> Result<&str, AppError> {
+    let trimmed = key.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid_input("metadata key is required"));
+    }
+    if !trimmed
+        .chars()
+        .all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.'))
+    {
+        return Err(AppError::invalid_input(
+            "metadata key may only contain letters, digits, '_', '-', and '.'",
+        ));
+    }
+    Ok(trimmed)
+}
 
-        let removed = delete_task_with_path(&path, "task-1").unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+fn duplicate_ids(tasks: &[Task]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    tasks
+        .iter()
+        .filter(|task| !seen.insert(task.id.as_str()))
+        .map(|task| task.id.clone())
+        .collect()
+}
 
-        assert_eq!(removed.id, "task-1");
-        assert!(loaded.is_empty());
+fn required_id(id: &str) -> Result<&str, AppError> {
+    let trimmed_id = id.trim();
+    if trimmed_id.is_empty() {
+        return Err(AppError::invalid_input("id is required"));
     }
+    Ok(trimmed_id)
+}
 
-    #[test]
-    fn delete_task_rejects_missing_id() {
-        let path = temp_path("delete-missing.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+fn optional_message(message: Option<&str>) -> Result<Option<String>, AppError> {
+    match message {
+        Some(value) => {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                return Err(AppError::invalid_input("message is required"));
+            }
+            Ok(Some(trimmed.to_string()))
+        }
+        None => Ok(None),
+    }
+}
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+/// One emoji or a character or two, so the marker fits before the title.
+fn marker_symbol(symbol: &str) -> Result<String, AppError> {
+    let trimmed = symbol.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid_input("marker symbol must not be empty"));
+    }
+    if trimmed.chars().any(char::is_whitespace) || text::display_width(trimmed) > 2 {
+        return Err(AppError::invalid_input(format!(
+            "marker symbol must be one emoji or at most two characters: '{trimmed}'"
+        )));
+    }
+    Ok(trimmed.to_string())
+}
 
-        let err = delete_task_with_path(&path, "task-2").unwrap_err();
-        std::fs::remove_file(&path).ok();
+fn ensure_open(task: &Task) -> Result<(), AppError> {
+    match task.status {
+        TaskStatus::Completed => Err(AppError::invalid_input("task already completed")),
+        TaskStatus::Cancelled => Err(AppError::invalid_input("task already cancelled")),
+        _ => Ok(()),
+    }
+}
 
-        assert_eq!(err.code(), "invalid_input");
+fn mark_completed(task: &mut Task, message: Option<String>) -> Result<(), AppError> {
+    let completed_at = now_rfc3339()?;
+    task.status = TaskStatus::Completed;
+    task.completed_at = Some(completed_at.clone());
+    task.waiting_until = None;
+    end_session(task, &completed_at);
+
+    if let Some(message) = message {
+        task.completion_history.push(CompletionEntry {
+            message,
+            completed_at,
+        });
     }
 
-    #[test]
-    fn delete_task_rejects_blank_id() {
-        let path = temp_path("delete-blank-id.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "old".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    Ok(())
+}
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+/// Close the session `start` opened, if the task is still in one.
+fn end_session(task: &mut Task, at: &str) {
+    if let Some(session) = task
+        .sessions
+        .last_mut()
+        .filter(|session| session.ended_at.is_none())
+    {
+        session.ended_at = Some(at.to_string());
+    }
+}
 
-        let err = delete_task_with_path(&path, "").unwrap_err();
-        std::fs::remove_file(&path).ok();
+/// A `schedule` datetime as stored: RFC3339 in `local_offset` unless it carried its own.
+fn schedule_timestamp(datetime: &str, local_offset: UtcOffset) -> Result<String, AppError> {
+    let trimmed = datetime.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid_input("datetime is required"));
+    }
+    parse_schedule_datetime(trimmed, local_offset)?
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))
+}
 
-        assert_eq!(err.code(), "invalid_input");
+/// Refuses a deadline that would make the task overdue as soon as it is set.
+fn ensure_deadline_ahead(
+    datetime: &str,
+    local_offset: UtcOffset,
+    now: OffsetDateTime,
+    grace: time::Duration,
+) -> Result<(), AppError> {
+    let due = parse_schedule_datetime(datetime.trim(), local_offset)?;
+    if overdue_at(due, now, grace) {
+        return Err(AppError::invalid_input(format!(
+            "{} is in the past, so the task would be overdue right away",
+            datetime.trim()
+        ))
+        .with_hint("pass --allow-past to schedule it anyway"));
     }
+    Ok(())
+}
 
-    #[test]
-    fn schedule_task_updates_scheduled_at_and_persists() {
-        let path = temp_path("schedule-task.json");
+fn parse_schedule_datetime(
+    value: &str,
+    local_offset: UtcOffset,
+) -> Result<OffsetDateTime, AppError> {
+    if let Ok(dt) = PrimitiveDateTime::parse(
+        value,
+        &format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"),
+    ) {
+        Ok(dt.assume_offset(local_offset))
+    } else if let Ok(dt) = PrimitiveDateTime::parse(
+        value,
+        &format_description!("[year]-[month]-[day] [hour]:[minute]"),
+    ) {
+        Ok(dt.assume_offset(local_offset))
+    } else if let Ok(time) = time::Time::parse(value, &format_description!("[hour]:[minute]")) {
+        let today = clock::now_utc().to_offset(local_offset).date();
+        Ok(today.with_time(time).assume_offset(local_offset))
+    } else if let Ok(date) = Date::parse(value, &format_description!("[year]-[month]-[day]")) {
+        Ok(date.with_hms(0, 0, 0).unwrap().assume_offset(local_offset))
+    } else {
+        OffsetDateTime::parse(value, &Rfc3339).map_err(|_| {
+            AppError::invalid_input(
+                "datetime must be in format 'YYYY-MM-DD HH:MM:SS', 'YYYY-MM-DD', or RFC3339",
+            )
+        })
+    }
+}
+
+fn list_today_with_focus_with_path(
+    path: &Path,
+    grace: time::Duration,
+) -> Result<ListResult, AppError> {
+    list_today_with_order_with_path(path, &OrderingConfig::default().today, grace)
+}
+
+fn list_today_with_order_with_path(
+    path: &Path,
+    order: &[SortKey],
+    grace: time::Duration,
+) -> Result<ListResult, AppError> {
+    let state = json_store::load_state(path)?;
+    let local_offset = local_offset()?;
+    let now = clock::now_utc().to_offset(local_offset);
+    let tasks = filter_tasks(&state.tasks, now.date(), local_offset, ListMode::Today)?;
+    let tasks = apply_context(&state, tasks, now, grace)?;
+    let focused_task_id = state.focused_task_id.as_deref();
+    let tasks = ordering::sort_tasks(tasks, order, focused_task_id, now, grace)?;
+
+    Ok(ListResult {
+        tasks,
+        focused_task_id: state.focused_task_id,
+        context: state.active_context,
+        day_note: state.day_notes.get(&now.date().to_string()).cloned(),
+    })
+}
+
+fn get_task_by_id_with_path(path: &Path, id: &str) -> Result<Task, AppError> {
+    let trimmed_id = id.trim();
+    if trimmed_id.is_empty() {
+        return Err(AppError::invalid_input("id is required"));
+    }
+
+    json_store::load_task(path, trimmed_id)?.ok_or_else(task_not_found)
+}
+
+fn get_task_detail_with_path(
+    path: &Path,
+    id: &str,
+    grace: time::Duration,
+) -> Result<TaskDetail, AppError> {
+    let task = get_task_by_id_with_path(path, id)?;
+    let focused = json_store::load_focused_task_id(path)?.as_deref() == Some(task.id.as_str());
+    let overdue = task.status.is_open() && task_overdue(&task, grace)?;
+
+    Ok(TaskDetail {
+        task,
+        focused,
+        overdue,
+    })
+}
+
+fn diff_stores_with_paths(old: &Path, new: &Path) -> Result<StoreDiff, AppError> {
+    let load = |path: &Path| {
+        if !path.exists() {
+            return Err(AppError::invalid_input(format!(
+                "{}: store file not found",
+                path.display()
+            )));
+        }
+        json_store::load_state(path).map_err(|err| {
+            let message = format!("{}: {}", path.display(), err.message());
+            err.with_message(message)
+        })
+    };
+    let old_state = load(old)?;
+    let new_state = load(new)?;
+    diff::diff_tasks(&old_state.tasks, &new_state.tasks)
+}
+
+fn refresh_tickets_with_path(
+    path: &Path,
+    config: &TicketConfig,
+    fetcher: &dyn TicketFetcher,
+) -> Result<TicketRefresh, AppError> {
+    let state = json_store::load_state(path)?;
+    let mut fetched = Vec::new();
+    let mut failures = Vec::new();
+    // Fetched before the transaction so slow requests never hold a half-applied store.
+    for task in &state.tasks {
+        let Some(link) = task.ticket.as_ref() else {
+            continue;
+        };
+        if !task.status.is_open() {
+            continue;
+        }
+        let result = tickets::provider_for(config, link)
+            .and_then(|provider| tickets::fetch_ticket(provider, &link.key, fetcher));
+        match result {
+            Ok(ticket) => fetched.push((task.id.clone(), link.key.clone(), ticket)),
+            Err(error) => failures.push(TicketFailure {
+                task_id: task.id.clone(),
+                key: link.key.clone(),
+                error,
+            }),
+        }
+    }
+    if fetched.is_empty() {
+        return Ok(TicketRefresh {
+            synced: Vec::new(),
+            failures,
+        });
+    }
+
+    let synced_at = clock::now_utc()
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let synced = transaction_with_path(path, |txn| {
+        let mut synced = Vec::with_capacity(fetched.len());
+        for (id, key, ticket) in &fetched {
+            // Skip tasks deleted, relinked, or completed while the tickets were fetched.
+            let unchanged = txn.tasks().iter().any(|task| {
+                task.id == *id
+                    && task.status.is_open()
+                    && task.ticket.as_ref().is_some_and(|link| link.key == *key)
+            });
+            if unchanged {
+                synced.push(txn.sync_ticket(id, ticket, &synced_at)?);
+            }
+        }
+        Ok(synced)
+    })?;
+    Ok(TicketRefresh { synced, failures })
+}
+
+fn query_tasks_with_path(
+    path: &Path,
+    expression: &str,
+    grace: time::Duration,
+) -> Result<Vec<Task>, AppError> {
+    let filter = Filter::parse(expression)?;
+    let state = json_store::load_state(path)?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    Ok(state
+        .tasks
+        .into_iter()
+        .filter(|task| filter.matches(task, now, grace))
+        .collect())
+}
+
+/// Beyond this many index candidates, one full load is cheaper than a lookup for each.
+const SEARCH_LOOKUP_LIMIT: usize = 200;
+
+fn search_tasks_with_path(path: &Path, query: &str) -> Result<Vec<Task>, AppError> {
+    let terms = search::query_terms(query)?;
+    let grams = search::query_trigrams(&terms);
+    let candidates = if grams.is_empty() {
+        None
+    } else {
+        search::candidates(path, &grams)?
+    };
+
+    let mut tasks = match candidates {
+        Some(ids) if ids.len() <= SEARCH_LOOKUP_LIMIT => json_store::load_tasks_by_id(path, &ids)?,
+        found => {
+            let state = json_store::load_state(path)?;
+            if found.is_none() && state.tasks.len() >= INDEX_MIN_TASKS && !search::is_current(path)?
+            {
+                // For the next search; this one already has every task in hand.
+                search::build(path, &state)?;
+            }
+            state.tasks
+        }
+    };
+    tasks.retain(|task| search::matches(task, &terms));
+    // Fractional seconds vary in length, so the strings do not sort chronologically.
+    tasks.sort_by_cached_key(|task| {
+        (
+            OffsetDateTime::parse(&task.created_at, &Rfc3339).ok(),
+            task.id.clone(),
+        )
+    });
+    Ok(tasks)
+}
+
+fn save_snapshot_with_path(path: &Path, name: &str) -> Result<Backup, AppError> {
+    if backup::backup_name(name)? == PRE_RESTORE_SNAPSHOT {
+        return Err(AppError::invalid_input(format!(
+            "'{PRE_RESTORE_SNAPSHOT}' is reserved for the automatic backup taken by restore"
+        )));
+    }
+    let state = json_store::load_state(path)?;
+    backup::create(path, name, &state)
+}
+
+fn restore_snapshot_with_path(path: &Path, name: &str) -> Result<SnapshotRestore, AppError> {
+    let restored = backup::find(path, name)?;
+    // Loaded before the current store is backed up, since restoring `pre-restore` replaces it.
+    let state = backup::load(&restored)?;
+    let previous = backup::replace(path, PRE_RESTORE_SNAPSHOT, &json_store::load_state(path)?)?;
+    json_store::save_state(path, &state)?;
+    Ok(SnapshotRestore { restored, previous })
+}
+
+fn export_snapshot_with_path(
+    path: &Path,
+    grace: time::Duration,
+) -> Result<ExportSnapshot, AppError> {
+    let state = json_store::load_state(path)?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    ExportSnapshot::from_tasks(&state.tasks, now, grace)
+}
+
+fn digest_with_path(path: &Path, period: DigestPeriod) -> Result<Digest, AppError> {
+    let state = json_store::load_state(path)?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    let mut digest = Digest::from_tasks(&state.tasks, period, now)?;
+    digest.day_notes = notes_between(&state, (now - period.duration()).date(), now.date());
+    Ok(digest)
+}
+
+fn notes_between(state: &TaskState, first: Date, last: Date) -> BTreeMap<String, String> {
+    // Keys are `YYYY-MM-DD`, so they sort and compare as the days do.
+    state
+        .day_notes
+        .range(first.to_string()..=last.to_string())
+        .map(|(day, note)| (day.clone(), note.clone()))
+        .collect()
+}
+
+fn usage_insights_with_path(path: &Path) -> Result<Insights, AppError> {
+    let state = json_store::load_state(path)?;
+    let entries = usage::load_entries(&usage::usage_log_path(path))?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    Ok(stats::insights(&state.tasks, &entries, now))
+}
+
+fn migrate_store_with_path(path: &Path, lenient: bool) -> Result<StoreMigration, AppError> {
+    if !path.exists() {
+        return Err(AppError::invalid_input(format!(
+            "no store at {} to migrate",
+            path.display()
+        )));
+    }
+    let migration = json_store::load_for_migration(path, lenient)?;
+    let mut result = StoreMigration {
+        from_version: migration.schema_version,
+        coercions: migration.coercions,
+        original: None,
+    };
+    if result.from_version == json_store::SCHEMA_VERSION && result.coercions.is_empty() {
+        return Ok(result);
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|err| AppError::io(err.to_string()))?;
+    let original = legacy::original_path(path);
+    activity::write_private(&original, &content)?;
+    json_store::save_state(path, &migration.state)?;
+    result.original = Some(original);
+    Ok(result)
+}
+
+fn store_info_with_path(path: &Path) -> Result<StoreInfo, AppError> {
+    let file_size = |path: &Path| match std::fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(err) => Err(AppError::io(format!("{}: {err}", path.display()))),
+    };
+    let state = json_store::load_state(path)?;
+    let by_status = TaskStatus::ALL
+        .into_iter()
+        .map(|status| {
+            let count = state
+                .tasks
+                .iter()
+                .filter(|task| task.status == status)
+                .count();
+            (status, count)
+        })
+        .collect();
+    // RFC3339 stamps in different offsets do not sort as text.
+    let oldest_open = state
+        .tasks
+        .iter()
+        .filter(|task| task.status.is_open())
+        .filter_map(|task| {
+            Some((
+                OffsetDateTime::parse(&task.created_at, &Rfc3339).ok()?,
+                task,
+            ))
+        })
+        .min_by_key(|(created_at, _)| *created_at)
+        .map(|(_, task)| task.clone());
+
+    Ok(StoreInfo {
+        path: path.to_path_buf(),
+        store_bytes: file_size(path)?,
+        by_status,
+        oldest_open,
+        journal_entries: journal::read_ops(path)?.len(),
+        journal_bytes: file_size(&journal::journal_path(path))?,
+        last_backup: backup::list(path)?.pop(),
+    })
+}
+
+fn status_summary_with_path(path: &Path) -> Result<StatusSummary, AppError> {
+    match status::read(path)? {
+        Some(summary) => Ok(summary),
+        None => StatusSummary::from_state(
+            &json_store::load_state(path)?,
+            clock::now_utc().to_offset(local_offset()?),
+        ),
+    }
+}
+
+fn set_task_urgent_with_path(path: &Path, id: &str, urgent: bool) -> Result<Task, AppError> {
+    transaction_with_path(path, |txn| txn.set_urgent(id, urgent))
+}
+
+fn notify_overdue_or_urgent_with_path(
+    path: &Path,
+    notifier: &dyn Notifier,
+    routes: &[(&NotificationRoute, &dyn Notifier)],
+    config: &NotificationConfig,
+    now: OffsetDateTime,
+    grace: time::Duration,
+) -> Result<NotificationOutcome, AppError> {
+    let original = json_store::load_state(path)?;
+    let outcome = dispatch_notifications(&original.tasks, notifier, routes, config, now, grace)?;
+
+    if !outcome.tasks.is_empty() {
+        let mut state = original.clone();
+        state.last_notified_at = Some(now_rfc3339()?);
+        json_store::commit_state(path, &original, &state)?;
+    }
+
+    Ok(outcome)
+}
+
+/// Notify each overdue or urgent open task through the first of `routes` that matches it, or
+/// else `notifier`. Past `digest_after` tasks in all, every destination gets one digest of its
+/// own tasks instead. Otherwise a destination with more than `max_per_run` tasks gets the first
+/// ones singly and a digest of the rest, so a bulk change never fires a toast per task.
+fn dispatch_notifications(
+    tasks: &[Task],
+    notifier: &dyn Notifier,
+    routes: &[(&NotificationRoute, &dyn Notifier)],
+    config: &NotificationConfig,
+    now: OffsetDateTime,
+    grace: time::Duration,
+) -> Result<NotificationOutcome, AppError> {
+    // Keyed by index into `routes`, with `routes.len()` for `notifier`.
+    let mut groups: BTreeMap<usize, Vec<(&Task, bool)>> = BTreeMap::new();
+    let mut selected = 0;
+
+    for task in tasks {
+        if !task.status.is_open() {
+            continue;
+        }
+
+        let overdue = task_overdue_at(task, now, grace)?;
+        if !overdue && !task.urgent {
+            continue;
+        }
+
+        let route = routes
+            .iter()
+            .position(|(route, _)| route.matches(task))
+            .unwrap_or(routes.len());
+        groups.entry(route).or_default().push((task, overdue));
+        selected += 1;
+    }
+
+    let digest = config
+        .digest_after
+        .is_some_and(|threshold| selected > 0 && selected >= threshold);
+    let mut outcome = NotificationOutcome {
+        tasks: Vec::new(),
+        failures: Vec::new(),
+        digests: Vec::new(),
+    };
+    for (route, mut group) in groups {
+        let notifier = routes.get(route).map_or(notifier, |(_, routed)| *routed);
+        if digest {
+            send_digest(notifier, group, &mut outcome);
+            continue;
+        }
+
+        // The last slot goes to the digest of everything past the cap.
+        let rest = match config.max_per_run.map(|cap| cap.max(1)) {
+            Some(cap) if group.len() > cap => group.split_off(cap - 1),
+            _ => Vec::new(),
+        };
+        for (task, _) in group {
+            let action = activation_argument(&task.id);
+            match notifier.notify_with_action(task, &action) {
+                Ok(_) => outcome.tasks.push(task.clone()),
+                Err(err) => outcome.failures.push(NotificationFailure {
+                    task_id: task.id.clone(),
+                    error: err,
+                }),
+            }
+        }
+        if !rest.is_empty() {
+            send_digest(notifier, rest, &mut outcome);
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// One notification for all of `group`, each paired with whether it is overdue.
+fn send_digest(
+    notifier: &dyn Notifier,
+    group: Vec<(&Task, bool)>,
+    outcome: &mut NotificationOutcome,
+) {
+    let overdue_count = group.iter().filter(|(_, overdue)| *overdue).count();
+    let tasks: Vec<&Task> = group.into_iter().map(|(task, _)| task).collect();
+    let urgent_count = tasks.iter().filter(|task| task.urgent).count();
+    let body = digest_body(&tasks, overdue_count, urgent_count);
+    match notifier.notify_message("todoapp", &body) {
+        Ok(_) => {
+            outcome.tasks.extend(tasks.into_iter().cloned());
+            outcome.digests.push(body);
+        }
+        Err(err) => outcome
+            .failures
+            .extend(tasks.into_iter().map(|task| NotificationFailure {
+                task_id: task.id.clone(),
+                error: err.clone(),
+            })),
+    }
+}
+
+fn task_not_found() -> AppError {
+    AppError::invalid_input("task not found")
+        .with_hint("`todo search <words>` or `todo list backlog` shows task ids")
+}
+
+fn now_rfc3339() -> Result<String, AppError> {
+    clock::now_utc()
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleNotificationWarning {
+    pub overdue_urgent_count: usize,
+    pub last_notified_at: Option<String>,
+}
+
+/// Detect urgent tasks more than `grace` overdue that nobody has been notified about recently,
+/// which usually means `notify` is not running on a schedule.
+pub fn stale_notification_warning(
+    config: &NotificationConfig,
+    grace: time::Duration,
+) -> Result<Option<StaleNotificationWarning>, AppError> {
+    let path = json_store::store_path()?;
+    stale_notification_warning_with_path(&path, config, clock::now_utc(), grace)
+}
+
+fn maintain_with_path(
+    path: &Path,
+    config: &RetentionConfig,
+    now: OffsetDateTime,
+) -> Result<Maintenance, AppError> {
+    let now_text = now
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    transaction_with_path(path, |txn| {
+        let mut archived = match config.archive_completed_after_days {
+            Some(days) => txn.take_closed_before(now - time::Duration::days(days as i64))?,
+            None => Vec::new(),
+        };
+        archived.extend(txn.take_expired_routines(now.to_offset(txn.local_offset).date())?);
+
+        let mut archive = archive::read(path)?;
+        let mut purged = Vec::new();
+        if let Some(days) = config.purge_archived_after_days {
+            let cutoff = now - time::Duration::days(days as i64);
+            let mut kept = Vec::with_capacity(archive.tasks.len());
+            for entry in archive.tasks {
+                let archived_at = OffsetDateTime::parse(&entry.archived_at, &Rfc3339)
+                    .map_err(|_| AppError::invalid_data("archived_at must be RFC3339"))?;
+                if archived_at < cutoff {
+                    purged.push(entry.task);
+                } else {
+                    kept.push(entry);
+                }
+            }
+            archive.tasks = kept;
+        }
+        archive
+            .tasks
+            .extend(archived.iter().map(|task| ArchivedTask {
+                archived_at: now_text.clone(),
+                task: task.clone(),
+            }));
+        archive.last_maintained_at = Some(now_text.clone());
+        // Written before the store is committed: if the commit fails, the tasks end up in both
+        // files rather than in neither.
+        archive::write(path, &archive)?;
+
+        Ok(Maintenance { archived, purged })
+    })
+}
+
+fn add_seed_rule_with_path(
+    path: &Path,
+    text: &str,
+    now: OffsetDateTime,
+) -> Result<StoredSeed, AppError> {
+    let rule = SeedRule::parse(text)?.to_string();
+    let mut stored = seeds::read(path)?;
+    if stored.iter().any(|seed| seed.rule == rule) {
+        return Err(AppError::invalid_input(format!(
+            "seed rule '{rule}' already exists"
+        )));
+    }
+    let seed = StoredSeed {
+        rule,
+        added_at: now
+            .format(&Rfc3339)
+            .map_err(|err| AppError::invalid_data(err.to_string()))?,
+        last_seeded_at: None,
+    };
+    stored.push(seed.clone());
+    seeds::write(path, &stored)?;
+    Ok(seed)
+}
+
+fn run_seed_rules_with_path(path: &Path, now: OffsetDateTime) -> Result<Vec<Task>, AppError> {
+    let mut stored = seeds::read(path)?;
+    let now = now.to_offset(local_offset()?);
+    let mut due = Vec::new();
+    for (index, seed) in stored.iter().enumerate() {
+        let rule = SeedRule::parse(&seed.rule)?;
+        let since = seed.last_seeded_at.as_deref().unwrap_or(&seed.added_at);
+        let since = OffsetDateTime::parse(since, &Rfc3339)
+            .map_err(|_| AppError::invalid_data("seed rule times must be RFC3339"))?;
+        let at = rule.latest_due(now);
+        if at > since {
+            let at = at
+                .format(&Rfc3339)
+                .map_err(|err| AppError::invalid_data(err.to_string()))?;
+            due.push((index, rule, at));
+        }
+    }
+    if due.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let added = transaction_with_path(path, |txn| {
+        let mut added = Vec::new();
+        for (_, rule, at) in &due {
+            let id = txn.add(rule.title(), false)?.id;
+            let task = txn.task_mut(&id)?;
+            task.start_at = Some(at.clone());
+            added.push(task.clone());
+        }
+        Ok(added)
+    })?;
+    for (index, _, at) in due {
+        stored[index].last_seeded_at = Some(at);
+    }
+    seeds::write(path, &stored)?;
+    Ok(added)
+}
+
+fn start_routine_with_path(
+    path: &Path,
+    routines: &BTreeMap<String, Vec<String>>,
+    name: &str,
+) -> Result<Vec<Task>, AppError> {
+    let name = name.trim();
+    let items = routines.get(name).ok_or_else(|| {
+        AppError::invalid_input(format!(
+            "no routine named '{name}'; define it under `routines` in the config"
+        ))
+    })?;
+    transaction_with_path(path, |txn| txn.start_routine(name, items))
+}
+
+fn archive_expired_routines_with_path(
+    path: &Path,
+    now: OffsetDateTime,
+) -> Result<Vec<Task>, AppError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let now_text = now
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    transaction_with_path(path, |txn| {
+        let expired = txn.take_expired_routines(now.to_offset(txn.local_offset).date())?;
+        if !expired.is_empty() {
+            let mut archive = archive::read(path)?;
+            archive
+                .tasks
+                .extend(expired.iter().map(|task| ArchivedTask {
+                    archived_at: now_text.clone(),
+                    task: task.clone(),
+                }));
+            // Before the commit, as in `maintain`.
+            archive::write(path, &archive)?;
+        }
+        Ok(expired)
+    })
+}
+
+fn maintain_if_due_with_path(
+    path: &Path,
+    config: &RetentionConfig,
+    now: OffsetDateTime,
+) -> Result<Option<Maintenance>, AppError> {
+    if !config.on_startup || !path.exists() {
+        return Ok(None);
+    }
+    if let Some(last) = archive::read(path)?.last_maintained_at.as_deref() {
+        let last = OffsetDateTime::parse(last, &Rfc3339)
+            .map_err(|_| AppError::invalid_data("last_maintained_at must be RFC3339"))?;
+        let interval = time::Duration::hours(config.startup_interval_hours as i64);
+        if now - last < interval {
+            return Ok(None);
+        }
+    }
+    maintain_with_path(path, config, now).map(Some)
+}
+
+fn stale_notification_warning_with_path(
+    path: &Path,
+    config: &NotificationConfig,
+    now: OffsetDateTime,
+    grace: time::Duration,
+) -> Result<Option<StaleNotificationWarning>, AppError> {
+    if config.stale_hint_hours == 0 || !path.exists() {
+        return Ok(None);
+    }
+
+    // Runs before nearly every command, so it reads the status summary rather than the store.
+    let summary = status_summary_with_path(path)?;
+    let mut overdue_urgent_count = 0;
+    for due_at in &summary.urgent_due {
+        if is_overdue(due_at, now, grace)? {
+            overdue_urgent_count += 1;
+        }
+    }
+    if overdue_urgent_count == 0 {
+        return Ok(None);
+    }
+
+    if let Some(last) = summary.last_notified_at.as_deref() {
+        let last = OffsetDateTime::parse(last, &Rfc3339)
+            .map_err(|_| AppError::invalid_data("last_notified_at must be RFC3339"))?;
+        let threshold = time::Duration::hours(config.stale_hint_hours as i64);
+        if now - last <= threshold {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(StaleNotificationWarning {
+        overdue_urgent_count,
+        last_notified_at: summary.last_notified_at,
+    }))
+}
+
+const DIGEST_TOP_COUNT: usize = 3;
+
+/// "Scheduled Title (id): starts 2025-12-22 09:00, due 2025-12-24 17:00", in local time,
+/// naming whichever of the two times the task has.
+fn schedule_confirmation(task: &Task, local_offset: UtcOffset) -> Result<String, AppError> {
+    let local = |label: &str, value: &str| -> Result<String, AppError> {
+        let at = OffsetDateTime::parse(value, &Rfc3339)
+            .map_err(|_| AppError::invalid_data(format!("{label} must be RFC3339")))?
+            .to_offset(local_offset)
+            .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+            .map_err(|err| AppError::invalid_data(err.to_string()))?;
+        Ok(at)
+    };
+    let mut times = Vec::new();
+    if let Some(start_at) = task.start_at.as_deref() {
+        times.push(format!("starts {}", local("start_at", start_at)?));
+    }
+    if let Some(due_at) = task.due_at.as_deref() {
+        times.push(format!("due {}", local("due_at", due_at)?));
+    }
+    Ok(format!(
+        "Scheduled {} ({}): {}",
+        task.title,
+        task.id,
+        times.join(", ")
+    ))
+}
+
+fn digest_body(tasks: &[&Task], overdue_count: usize, urgent_count: usize) -> String {
+    let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+    let top = tasks
+        .iter()
+        .take(DIGEST_TOP_COUNT)
+        .map(|task| task.title.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{} {noun} need attention ({overdue_count} overdue, {urgent_count} urgent) \u{2014} top: {top}",
+        tasks.len()
+    )
+}
+
+fn list_backlog_with_focus_with_path(
+    path: &Path,
+    grace: time::Duration,
+) -> Result<ListResult, AppError> {
+    list_with_focus(path, ListMode::Backlog, grace)
+}
+
+fn list_without_focus(
+    path: &Path,
+    mode: ListMode,
+    grace: time::Duration,
+) -> Result<Vec<Task>, AppError> {
+    let state = json_store::load_state(path)?;
+    let local_offset = local_offset()?;
+    let now = clock::now_utc().to_offset(local_offset);
+    let tasks = filter_tasks(&state.tasks, now.date(), local_offset, mode)?;
+    apply_context(&state, tasks, now, grace)
+}
+
+fn list_with_focus(
+    path: &Path,
+    mode: ListMode,
+    grace: time::Duration,
+) -> Result<ListResult, AppError> {
+    let state = json_store::load_state(path)?;
+    let local_offset = local_offset()?;
+    let now = clock::now_utc().to_offset(local_offset);
+    let tasks = filter_tasks(&state.tasks, now.date(), local_offset, mode)?;
+    let mut tasks = apply_context(&state, tasks, now, grace)?;
+    let focused_task_id = state.focused_task_id.clone();
+
+    if let Some(focused_id) = focused_task_id.as_deref()
+        && let Some(index) = tasks.iter().position(|task| task.id == focused_id)
+    {
+        let focused_task = tasks.remove(index);
+        tasks.insert(0, focused_task);
+    }
+
+    Ok(ListResult {
+        tasks,
+        focused_task_id,
+        context: state.active_context,
+        day_note: None,
+    })
+}
+
+/// Keeps only the tasks matching the active context's filter, if a context is active.
+fn apply_context(
+    state: &TaskState,
+    tasks: Vec<Task>,
+    now: OffsetDateTime,
+    grace: time::Duration,
+) -> Result<Vec<Task>, AppError> {
+    let Some(name) = state.active_context.as_deref() else {
+        return Ok(tasks);
+    };
+    let expression = state
+        .contexts
+        .get(name)
+        .ok_or_else(|| AppError::invalid_data(format!("active context '{name}' is not defined")))?;
+    let filter = Filter::parse(expression)
+        .map_err(|err| AppError::invalid_data(format!("context '{name}': {}", err.message())))?;
+    Ok(tasks
+        .into_iter()
+        .filter(|task| filter.matches(task, now, grace))
+        .collect())
+}
+
+pub(crate) fn local_offset() -> Result<UtcOffset, AppError> {
+    Ok(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+}
+
+enum ListMode {
+    Today,
+    Backlog,
+    Period(Period),
+    /// Open tasks over this many days from today.
+    Upcoming(u32),
+}
+
+fn filter_tasks(
+    tasks: &[Task],
+    today: Date,
+    local_offset: UtcOffset,
+    mode: ListMode,
+) -> Result<Vec<Task>, AppError> {
+    let mut filtered = Vec::new();
+    for task in tasks {
+        let scheduled_at = match task.planned_at() {
+            Some(value) => value,
+            None => {
+                if matches!(mode, ListMode::Backlog) {
+                    filtered.push(task.clone());
+                }
+                continue;
+            }
+        };
+
+        let scheduled = OffsetDateTime::parse(scheduled_at, &Rfc3339)
+            .map_err(|_| AppError::invalid_data("start_at and due_at must be RFC3339"))?;
+        let scheduled_local = scheduled.to_offset(local_offset);
+        let scheduled_date = scheduled_local.date();
+
+        let matches = match mode {
+            ListMode::Today => scheduled_date <= today,
+            ListMode::Backlog => scheduled_date > today,
+            ListMode::Period(period) => period.contains(scheduled_date),
+            ListMode::Upcoming(days) => {
+                task.status.is_open()
+                    && scheduled_date >= today
+                    && scheduled_date < today + time::Duration::days(days.into())
+            }
+        };
+
+        if matches {
+            filtered.push(task.clone());
+        }
+    }
+
+    Ok(filtered)
+}
+
+fn edit_task_with_path(path: &Path, id: &str, new_title: &str) -> Result<Task, AppError> {
+    transaction_with_path(path, |txn| txn.edit(id, new_title))
+}
+
+fn delete_task_with_path(path: &Path, id: &str) -> Result<Task, AppError> {
+    transaction_with_path(path, |txn| txn.delete(id))
+}
+
+fn complete_task_with_path(path: &Path, id: &str, message: Option<&str>) -> Result<Task, AppError> {
+    let (before, was_focused, task) = transaction_with_path(path, |txn| {
+        let trimmed_id = required_id(id)?;
+        let before = txn.task_mut(trimmed_id)?.clone();
+        let was_focused = txn.focused_task_id() == Some(trimmed_id);
+        Ok((before, was_focused, txn.complete(id, message)?))
+    })?;
+    record_completion_undo(path, before, was_focused, &task)?;
+    Ok(task)
+}
+
+fn complete_focused_task_with_path(path: &Path, message: Option<&str>) -> Result<Task, AppError> {
+    let (before, task) = transaction_with_path(path, |txn| {
+        let before = match txn.state.focused_task_id.clone() {
+            Some(id) => Some(txn.task_mut(&id)?.clone()),
+            None => None,
+        };
+        Ok((before, txn.complete_focused(message)?))
+    })?;
+    if let Some(before) = before {
+        record_completion_undo(path, before, true, &task)?;
+    }
+    Ok(task)
+}
+
+fn record_completion_undo(
+    path: &Path,
+    before: Task,
+    was_focused: bool,
+    after: &Task,
+) -> Result<(), AppError> {
+    undo::write(
+        path,
+        &CompletionUndo {
+            before: Box::new(before),
+            after: Box::new(after.clone()),
+            was_focused,
+        },
+    )
+}
+
+fn undo_completion_with_path(path: &Path, now: OffsetDateTime) -> Result<Task, AppError> {
+    let Some(record) = undo::read(path)? else {
+        return Err(AppError::invalid_input("nothing to undo"));
+    };
+    let completed_at = record
+        .after
+        .completed_at
+        .as_deref()
+        .and_then(|value| OffsetDateTime::parse(value, &Rfc3339).ok())
+        .ok_or_else(|| AppError::invalid_data("undo record has no completion time"))?;
+    let elapsed = (now - completed_at).whole_seconds();
+    if elapsed > UNDO_WINDOW_SECS {
+        undo::remove(path)?;
+        return Err(AppError::invalid_input(format!(
+            "too late to undo: the task was completed {elapsed}s ago (limit {UNDO_WINDOW_SECS}s)"
+        )));
+    }
+
+    let task = transaction_with_path(path, |txn| txn.revert_completion(&record))?;
+    undo::remove(path)?;
+    Ok(task)
+}
+
+fn schedule_task_with_path(path: &Path, id: &str, datetime: &str) -> Result<Task, AppError> {
+    transaction_with_path(path, |txn| txn.schedule(id, datetime))
+}
+
+fn reschedule_task_with_path(
+    path: &Path,
+    id: &str,
+    datetime: &str,
+    grace: time::Duration,
+) -> Result<Task, AppError> {
+    transaction_with_path(path, |txn| txn.reschedule(id, datetime, grace))
+}
+
+fn overdue_tasks_with_path(path: &Path, grace: time::Duration) -> Result<Vec<Task>, AppError> {
+    let now = clock::now_utc();
+    let mut overdue = Vec::new();
+    for task in json_store::load_tasks(path)? {
+        if task.status.is_open() && task_overdue_at(&task, now, grace)? {
+            overdue.push(task);
+        }
+    }
+    // Each deadline was parsed just above.
+    overdue.sort_by_cached_key(|task| {
+        task.due_at
+            .as_deref()
+            .and_then(|at| OffsetDateTime::parse(at, &Rfc3339).ok())
+    });
+    Ok(overdue)
+}
+
+fn triage_overdue_with_path(
+    path: &Path,
+    moves: &[(String, TriageDay)],
+    grace: time::Duration,
+) -> Result<Vec<Task>, AppError> {
+    transaction_with_path(path, |txn| {
+        moves
+            .iter()
+            .map(|(id, day)| txn.move_overdue(id, *day, grace))
+            .collect()
+    })
+}
+
+fn set_focus_with_path(path: &Path, id: &str) -> Result<Task, AppError> {
+    transaction_with_path(path, |txn| txn.set_focus(id))
+}
+
+/// Applies `change` and compares today's plan before and after. Only a change that grows the
+/// plan past the limit counts, so tidying an already crowded day is never refused.
+fn within_wip_limit_with_path<F>(
+    path: &Path,
+    limit: &WipLimitConfig,
+    force: bool,
+    change: F,
+) -> Result<PlannedChange, AppError>
+where
+    F: FnOnce(&mut Transaction) -> Result<Task, AppError>,
+{
+    transaction_with_path(path, |txn| {
+        let before = txn.planned_today()?;
+        let task = change(txn)?;
+        let Some(max) = limit.today else {
+            return Ok(PlannedChange {
+                task,
+                over_limit: None,
+            });
+        };
+        let planned = txn.planned_today()?;
+        if planned <= max || planned <= before {
+            return Ok(PlannedChange {
+                task,
+                over_limit: None,
+            });
+        }
+        if limit.enforce == WipEnforcement::Block && !force {
+            return Err(AppError::invalid_input(format!(
+                "today would have {planned} planned tasks, over wip_limit.today = {max}; \
+                 use --force to plan it anyway"
+            )));
+        }
+        Ok(PlannedChange {
+            task,
+            over_limit: Some(WipExceeded {
+                planned,
+                limit: max,
+            }),
+        })
+    })
+}
+
+/// Whether a deadline at `due` has passed by more than `grace` at `now`: with a 15 minute
+/// grace, a task due at 09:00 is still on time at 09:15 and overdue just after.
+pub fn overdue_at(due: OffsetDateTime, now: OffsetDateTime, grace: time::Duration) -> bool {
+    now - due > grace
+}
+
+/// `due` moved to `day` relative to `now`, both in local time; see [`Transaction::move_overdue`].
+fn triage_deadline(due: OffsetDateTime, now: OffsetDateTime, day: TriageDay) -> OffsetDateTime {
+    let today = now.date();
+    let date = match day {
+        TriageDay::Today => today,
+        TriageDay::Tomorrow => today + time::Duration::days(1),
+        TriageDay::NextWeek => {
+            today + time::Duration::days(7 - i64::from(today.weekday().number_days_from_monday()))
+        }
+    };
+    let moved = date
+        .with_time(due.to_offset(now.offset()).time())
+        .assume_offset(now.offset());
+    if moved > now {
+        moved
+    } else {
+        now.replace_time(time::macros::time!(23:59:59))
+    }
+}
+
+/// Time left before an open task's deadline when that is at most `horizon` after `now`;
+/// `None` for tasks that are closed, have no deadline, are further out, or are already past
+/// it.
+pub fn due_in_at(
+    task: &Task,
+    now: OffsetDateTime,
+    horizon: time::Duration,
+) -> Result<Option<time::Duration>, AppError> {
+    let Some(due_at) = task.due_at.as_deref().filter(|_| task.status.is_open()) else {
+        return Ok(None);
+    };
+    let due = OffsetDateTime::parse(due_at, &Rfc3339)
+        .map_err(|_| AppError::invalid_data("due_at must be RFC3339"))?;
+    let left = due - now;
+    Ok((left.is_positive() && left <= horizon).then_some(left))
+}
+
+/// [`due_in_at`] now.
+pub fn task_due_in(
+    task: &Task,
+    horizon: time::Duration,
+) -> Result<Option<time::Duration>, AppError> {
+    due_in_at(task, clock::now_utc(), horizon)
+}
+
+fn is_overdue(due_at: &str, now: OffsetDateTime, grace: time::Duration) -> Result<bool, AppError> {
+    let due = OffsetDateTime::parse(due_at, &Rfc3339)
+        .map_err(|_| AppError::invalid_data("due_at must be RFC3339"))?;
+    Ok(overdue_at(due, now, grace))
+}
+
+/// [`stats::group_tasks`] relative to the current local time.
+pub fn group_tasks(
+    tasks: &[Task],
+    by: GroupBy,
+    grace: time::Duration,
+) -> Result<Vec<TaskGroup>, AppError> {
+    let now = clock::now_utc().to_offset(local_offset()?);
+    stats::group_tasks(tasks, by, now, grace)
+}
+
+/// Whether `task` is more than `grace` past its deadline now.
+pub fn task_overdue(task: &Task, grace: time::Duration) -> Result<bool, AppError> {
+    task_overdue_at(task, clock::now_utc(), grace)
+}
+
+fn task_overdue_at(
+    task: &Task,
+    now: OffsetDateTime,
+    grace: time::Duration,
+) -> Result<bool, AppError> {
+    match task.due_at.as_deref() {
+        Some(due_at) => is_overdue(due_at, now, grace),
+        None => Ok(false),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::{
+        ListMode, Period, TriageDay, add_seed_rule_with_path, add_task_with_path,
+        archive_expired_routines_with_path, complete_focused_task_with_path,
+        complete_task_with_path, delete_task_with_path, digest_body, digest_with_path,
+        dispatch_notifications, due_in_at, edit_task_with_path, ensure_deadline_ahead,
+        filter_tasks, get_task_by_id_with_path, get_task_detail_with_path, import_tasks_with_path,
+        list_today_with_focus_with_path, list_without_focus, local_today,
+        maintain_if_due_with_path, maintain_with_path, notify_overdue_or_urgent_with_path,
+        overdue_at, refresh_tickets_with_path, reschedule_task_with_path,
+        restore_snapshot_with_path, run_seed_rules_with_path, save_snapshot_with_path,
+        schedule_confirmation, schedule_task_with_path, search_tasks_with_path,
+        set_focus_with_path, set_task_urgent_with_path, stale_notification_warning_with_path,
+        start_routine_with_path, transaction_in, transaction_with_path, triage_deadline,
+        undo_completion_with_path, verify_exports_with_path, within_wip_limit_with_path,
+    };
+    use crate::config::{
+        NotificationBackend, NotificationConfig, NotificationRoute, RetentionConfig, TicketConfig,
+        TicketProvider, TitleConfig, WipEnforcement, WipLimitConfig,
+    };
+    use crate::error::AppError;
+    use crate::export::digest::DigestPeriod;
+    use crate::import::ImportedTask;
+    use crate::model::{CompletionEntry, Marker, MarkerColor, Task, TaskStatus, TicketLink};
+    use crate::notify::Notifier;
+    use crate::storage::archive::{self, Archive, ArchivedTask};
+    use crate::storage::index::INDEX_MIN_TASKS;
+    use crate::storage::json_store::{self, TaskState};
+    use crate::storage::search;
+    use crate::storage::seeds;
+    use crate::storage::store::MemoryStore;
+    use crate::testing::{self, TempStore};
+    use serde_json::{Value, json};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use time::format_description::well_known::Rfc3339;
+    use time::macros::datetime;
+    use time::{Date, Duration, Month, OffsetDateTime, UtcOffset};
+
+    #[test]
+    fn add_task_rejects_blank_title() {
+        let store = TempStore::new();
+        let path = store.path();
+        let err = add_task_with_path(path, "  ", false).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn add_task_writes_to_store() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "demo", false).unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, task.id);
+        assert_eq!(loaded[0].title, task.title);
+        assert_eq!(loaded[0].due_at, None);
+        assert_eq!(loaded[0].completed_at, None);
+        assert!(loaded[0].completion_history.is_empty());
+    }
+
+    #[test]
+    fn set_focus_sets_focused_task_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let tasks = vec![
+            testing::task("task-1", "first").build().unwrap(),
+            testing::task("task-2", "second").build().unwrap(),
+        ];
+
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks: tasks.clone(),
+                focused_task_id: None,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let focused = set_focus_with_path(path, "task-2").unwrap();
+        let loaded = json_store::load_state(path).unwrap();
+
+        assert_eq!(focused.id, "task-2");
+        assert_eq!(loaded.focused_task_id, Some("task-2".to_string()));
+        assert_eq!(loaded.tasks.len(), 2);
+    }
+
+    #[test]
+    fn set_focus_rejects_missing_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        let tasks = vec![testing::task("task-1", "first").build().unwrap()];
+
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks,
+                focused_task_id: None,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let err = set_focus_with_path(path, "task-2").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn set_task_urgent_updates_flag() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "urgent").build().unwrap();
+
+        json_store::save_tasks(path, std::slice::from_ref(&task)).unwrap();
+
+        let updated = set_task_urgent_with_path(path, "task-1", true).unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert!(updated.urgent);
+        assert!(!loaded.is_empty());
+        assert!(loaded[0].urgent);
+    }
+
+    #[test]
+    fn set_task_urgent_rejects_missing_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        json_store::save_tasks(path, &[]).unwrap();
+
+        let err = set_task_urgent_with_path(path, "task-1", true).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn get_task_by_id_returns_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, std::slice::from_ref(&task)).unwrap();
+
+        let fetched = get_task_by_id_with_path(path, "task-1").unwrap();
+
+        assert_eq!(fetched, task);
+    }
+
+    #[test]
+    fn get_task_by_id_rejects_missing_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        json_store::save_tasks(path, &[]).unwrap();
+
+        let err = get_task_by_id_with_path(path, "task-1").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn complete_focused_task_marks_completed_and_clears_focus() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks: vec![task],
+                focused_task_id: Some("task-1".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let completed = complete_focused_task_with_path(path, Some("ship it")).unwrap();
+        let loaded = json_store::load_state(path).unwrap();
+
+        assert_eq!(completed.status, TaskStatus::Completed);
+        assert!(completed.completed_at.is_some());
+        assert_eq!(completed.completion_history.len(), 1);
+        assert_eq!(loaded.focused_task_id, None);
+    }
+
+    #[test]
+    fn undo_completion_reopens_within_window_only() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = |id: &str| testing::task(id, &format!("task {id}")).build().unwrap();
+        json_store::save_state(
+            path,
+            &TaskState {
+                tasks: vec![task("1"), task("2")],
+                focused_task_id: Some("1".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let now = OffsetDateTime::now_utc;
+
+        complete_focused_task_with_path(path, Some("shipped")).unwrap();
+        let reopened = undo_completion_with_path(path, now()).unwrap();
+        let after_undo = json_store::load_state(path).unwrap();
+        let nothing = undo_completion_with_path(path, now()).unwrap_err();
+
+        complete_task_with_path(path, "2", None).unwrap();
+        edit_task_with_path(path, "2", "renamed").unwrap();
+        let changed = undo_completion_with_path(path, now()).unwrap_err();
+
+        complete_task_with_path(path, "1", None).unwrap();
+        let late = undo_completion_with_path(path, now() + Duration::seconds(61)).unwrap_err();
+        let after_late = undo_completion_with_path(path, now()).unwrap_err();
+
+        assert_eq!(reopened, task("1"));
+        // Reopening is itself a change, so only the stamp differs from before completion.
+        let mut undone = after_undo.tasks[0].clone();
+        assert!(undone.modified_by.take().is_some());
+        assert!(undone.modified_at.take().is_some());
+        assert_eq!(undone, task("1"));
+        assert_eq!(after_undo.focused_task_id.as_deref(), Some("1"));
+        assert_eq!(nothing.message(), "nothing to undo");
+        assert!(changed.message().contains("has changed"));
+        assert!(late.message().starts_with("too late to undo"));
+        assert_eq!(after_late.message(), "nothing to undo");
+    }
+
+    #[test]
+    fn wip_limit_warns_or_blocks_only_when_the_plan_grows() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = |id: &str, scheduled_at: Option<&str>| Task {
+            due_at: scheduled_at.map(str::to_string),
+            ..testing::task(id, &format!("task {id}")).build().unwrap()
+        };
+        json_store::save_state(
+            path,
+            &TaskState {
+                tasks: vec![
+                    task("1", Some("2025-12-01T09:00:00Z")),
+                    task("2", Some("2025-12-02T09:00:00Z")),
+                    task("3", None),
+                    task("4", None),
+                ],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let warn = WipLimitConfig {
+            today: Some(2),
+            enforce: WipEnforcement::Warn,
+        };
+        let block = WipLimitConfig {
+            today: Some(2),
+            enforce: WipEnforcement::Block,
+        };
+
+        let blocked =
+            within_wip_limit_with_path(path, &block, false, |txn| txn.schedule("3", "2025-12-03"))
+                .unwrap_err();
+        let unchanged = json_store::load_state(path).unwrap();
+        let warned =
+            within_wip_limit_with_path(path, &warn, false, |txn| txn.set_focus("3")).unwrap();
+        let moved = within_wip_limit_with_path(path, &block, false, |txn| {
+            txn.reschedule("1", "2025-12-04", Duration::ZERO)
+        })
+        .unwrap();
+        let forced =
+            within_wip_limit_with_path(path, &block, true, |txn| txn.schedule("4", "2025-12-06"))
+                .unwrap();
+        let unlimited =
+            within_wip_limit_with_path(path, &WipLimitConfig::default(), false, |txn| {
+                txn.schedule("3", "2025-12-05")
+            })
+            .unwrap();
+
+        assert_eq!(blocked.code(), "invalid_input");
+        assert!(blocked.message().contains("over wip_limit.today = 2"));
+        assert!(unchanged.tasks[2].due_at.is_none());
+        assert_eq!(warned.task.id, "3");
+        assert_eq!(
+            warned
+                .over_limit
+                .map(|exceeded| (exceeded.planned, exceeded.limit)),
+            Some((3, 2))
+        );
+        assert!(moved.over_limit.is_none());
+        assert_eq!(forced.over_limit.map(|exceeded| exceeded.planned), Some(4));
+        assert!(unlimited.over_limit.is_none());
+    }
+
+    #[test]
+    fn routines_start_once_a_day_and_are_archived_after_it() {
+        let store = TempStore::new();
+        let path = store.path();
+        let routines = BTreeMap::from([(
+            "morning".to_string(),
+            vec![
+                "Stretch".to_string(),
+                " ".to_string(),
+                "Email triage".to_string(),
+            ],
+        )]);
+        add_task_with_path(path, "Unrelated", false).unwrap();
+
+        let started = start_routine_with_path(path, &routines, " morning ").unwrap();
+        let again = start_routine_with_path(path, &routines, "morning").unwrap_err();
+        let unknown = start_routine_with_path(path, &routines, "evening").unwrap_err();
+        let today = archive_expired_routines_with_path(path, OffsetDateTime::now_utc()).unwrap();
+        let later =
+            archive_expired_routines_with_path(path, OffsetDateTime::now_utc() + Duration::days(2))
+                .unwrap();
+        let state = json_store::load_state(path).unwrap();
+        let archive = archive::read(path).unwrap();
+
+        let titles: Vec<&str> = started.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, vec!["Stretch", "Email triage"]);
+        assert!(
+            started
+                .iter()
+                .all(|task| task.routine.as_deref() == Some("morning") && task.due_at.is_some())
+        );
+        assert_eq!(
+            again.message(),
+            "routine 'morning' was already started today"
+        );
+        assert!(unknown.message().starts_with("no routine named 'evening'"));
+        assert!(today.is_empty());
+        assert_eq!(later.len(), 2);
+        assert_eq!(state.tasks.len(), 1);
+        assert_eq!(state.tasks[0].title, "Unrelated");
+        assert_eq!(archive.tasks.len(), 2);
+    }
+
+    #[test]
+    fn seed_rules_add_one_task_per_due_time() {
+        let store = TempStore::new();
+        let path = store.path();
+        // 2025-12-22 is a Monday.
+        let added_at = datetime!(2025-12-20 12:00 UTC);
+        let seed = add_seed_rule_with_path(path, "every mon 9am: Weekly report", added_at).unwrap();
+        let again = add_seed_rule_with_path(path, "Every Monday 09:00: Weekly report", added_at)
+            .unwrap_err();
+
+        let early = run_seed_rules_with_path(path, datetime!(2025-12-21 12:00 UTC)).unwrap();
+        let due = run_seed_rules_with_path(path, datetime!(2025-12-22 09:30 UTC)).unwrap();
+        let rerun = run_seed_rules_with_path(path, datetime!(2025-12-22 18:00 UTC)).unwrap();
+        // Two missed Mondays add one task, for the later one.
+        let late = run_seed_rules_with_path(path, datetime!(2026-01-06 08:00 UTC)).unwrap();
+        let stored = seeds::read(path).unwrap();
+        let state = json_store::load_state(path).unwrap();
+
+        assert_eq!(seed.rule, "every monday 09:00: Weekly report");
+        assert_eq!(
+            again.message(),
+            "seed rule 'every monday 09:00: Weekly report' already exists"
+        );
+        assert!(early.is_empty());
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].title, "Weekly report");
+        assert_eq!(due[0].start_at.as_deref(), Some("2025-12-22T09:00:00Z"));
+        assert!(rerun.is_empty());
+        assert_eq!(late[0].start_at.as_deref(), Some("2026-01-05T09:00:00Z"));
+        assert_eq!(
+            stored[0].last_seeded_at.as_deref(),
+            Some("2026-01-05T09:00:00Z")
+        );
+        assert_eq!(state.tasks.len(), 2);
+    }
+
+    #[test]
+    fn maintain_archives_old_completions_and_purges_old_archive_entries() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = |id: &str, completed_at: Option<&str>| Task {
+            status: if completed_at.is_some() {
+                TaskStatus::Completed
+            } else {
+                TaskStatus::Pending
+            },
+            completed_at: completed_at.map(str::to_string),
+            ..testing::task(id, &format!("task {id}"))
+                .created_at("2025-01-01T00:00:00Z")
+                .build()
+                .unwrap()
+        };
+        json_store::save_state(
+            path,
+            &TaskState {
+                tasks: vec![
+                    task("1", Some("2025-11-01T09:00:00Z")),
+                    task("2", Some("2025-12-15T09:00:00Z")),
+                    task("3", None),
+                ],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        archive::write(
+            path,
+            &Archive {
+                last_maintained_at: None,
+                tasks: vec![
+                    ArchivedTask {
+                        archived_at: "2024-06-01T00:00:00Z".to_string(),
+                        task: task("old", Some("2024-05-01T00:00:00Z")),
+                    },
+                    ArchivedTask {
+                        archived_at: "2025-06-01T00:00:00Z".to_string(),
+                        task: task("recent", Some("2025-05-01T00:00:00Z")),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+        let config = RetentionConfig {
+            archive_completed_after_days: Some(30),
+            purge_archived_after_days: Some(365),
+            on_startup: true,
+            startup_interval_hours: 24,
+        };
+        let now = OffsetDateTime::parse("2025-12-20T12:00:00Z", &Rfc3339).unwrap();
+
+        let report = maintain_with_path(path, &config, now).unwrap();
+        let store = json_store::load_state(path).unwrap();
+        let archived = archive::read(path).unwrap();
+        let too_soon = maintain_if_due_with_path(path, &config, now + Duration::hours(23)).unwrap();
+        let due = maintain_if_due_with_path(path, &config, now + Duration::hours(25)).unwrap();
+
+        let ids = |tasks: &[Task]| tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&report.archived), vec!["1"]);
+        assert_eq!(ids(&report.purged), vec!["old"]);
+        assert_eq!(ids(&store.tasks), vec!["2", "3"]);
+        assert_eq!(
+            archived
+                .tasks
+                .iter()
+                .map(|entry| (entry.task.id.as_str(), entry.archived_at.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("recent", "2025-06-01T00:00:00Z"),
+                ("1", "2025-12-20T12:00:00Z")
+            ]
+        );
+        assert_eq!(
+            archived.last_maintained_at.as_deref(),
+            Some("2025-12-20T12:00:00Z")
+        );
+        assert!(too_soon.is_none());
+        assert!(due.is_some_and(|report| report.archived.is_empty()));
+    }
+
+    #[test]
+    fn complete_focused_task_rejects_missing_focus() {
+        let store = TempStore::new();
+        let path = store.path();
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks: Vec::new(),
+                focused_task_id: None,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let err = complete_focused_task_with_path(path, None).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn complete_task_clears_focus_when_matching_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks: vec![task],
+                focused_task_id: Some("task-1".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let completed = complete_task_with_path(path, "task-1", None).unwrap();
+        let loaded = json_store::load_state(path).unwrap();
+
+        assert_eq!(completed.status, TaskStatus::Completed);
+        assert_eq!(loaded.focused_task_id, None);
+    }
+
+    #[test]
+    fn filter_tasks_returns_today_and_backlog() {
+        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
+        let offset = UtcOffset::UTC;
+        let today_dt = today.with_hms(12, 0, 0).unwrap().assume_offset(offset);
+        let tomorrow_dt = (today + Duration::days(1))
+            .with_hms(9, 0, 0)
+            .unwrap()
+            .assume_offset(offset);
+
+        let tasks = vec![
+            Task {
+                due_at: Some(today_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-1", "today").build().unwrap()
+            },
+            Task {
+                due_at: Some(tomorrow_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-2", "future").build().unwrap()
+            },
+            testing::task("task-3", "unscheduled").build().unwrap(),
+        ];
+
+        let today_tasks = filter_tasks(&tasks, today, offset, ListMode::Today).unwrap();
+        assert_eq!(today_tasks.len(), 1);
+        assert_eq!(today_tasks[0].id, "task-1");
+
+        let backlog_tasks = filter_tasks(&tasks, today, offset, ListMode::Backlog).unwrap();
+        assert_eq!(backlog_tasks.len(), 2);
+        assert!(backlog_tasks.iter().any(|task| task.id == "task-2"));
+        assert!(backlog_tasks.iter().any(|task| task.id == "task-3"));
+    }
+
+    #[test]
+    fn filter_tasks_backlog_includes_unscheduled_tasks() {
+        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
+        let offset = UtcOffset::UTC;
+        let future_dt = (today + Duration::days(2))
+            .with_hms(10, 0, 0)
+            .unwrap()
+            .assume_offset(offset);
+
+        let tasks = vec![
+            Task {
+                due_at: Some(future_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("future", "scheduled").build().unwrap()
+            },
+            testing::task("unscheduled", "later").build().unwrap(),
+        ];
+
+        let backlog_tasks = filter_tasks(&tasks, today, offset, ListMode::Backlog).unwrap();
+        assert_eq!(backlog_tasks.len(), 2);
+        assert!(backlog_tasks.iter().any(|task| task.id == "future"));
+        assert!(backlog_tasks.iter().any(|task| task.id == "unscheduled"));
+    }
+
+    #[test]
+    fn filter_tasks_keeps_tasks_scheduled_within_a_period_in_local_time() {
+        let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+        let today = time::macros::date!(2026 - 02 - 11);
+        let scheduled = |id: &str, at: &str| {
+            let mut task = testing::task(id, id).build().unwrap();
+            task.due_at = Some(at.to_string());
+            task
+        };
+        let tasks = vec![
+            // Monday 00:30 local time, still Sunday in UTC.
+            scheduled("monday", "2026-02-08T22:30:00Z"),
+            scheduled("sunday", "2026-02-15T21:59:00Z"),
+            scheduled("next-monday", "2026-02-15T22:00:00Z"),
+            scheduled("march", "2026-03-31T12:00:00Z"),
+            testing::task("unscheduled", "unscheduled").build().unwrap(),
+        ];
+        let ids = |mode| {
+            filter_tasks(&tasks, today, offset, mode)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(ListMode::Period(Period::week_of(today))),
+            ["monday", "sunday"]
+        );
+        assert_eq!(
+            ids(ListMode::Period(Period::quarter_of(today))),
+            ["monday", "sunday", "next-monday", "march"]
+        );
+    }
+
+    #[test]
+    fn filter_tasks_upcoming_keeps_open_tasks_from_today_on() {
+        let today = time::macros::date!(2025 - 12 - 20);
+        let tasks = vec![
+            testing::task("yesterday", "yesterday")
+                .due_at("2025-12-19T09:00:00Z")
+                .build()
+                .unwrap(),
+            testing::task("today", "today")
+                .start_at("2025-12-20T18:00:00Z")
+                .build()
+                .unwrap(),
+            Task {
+                status: TaskStatus::Completed,
+                ..testing::task("done", "done")
+                    .due_at("2025-12-21T09:00:00Z")
+                    .build()
+                    .unwrap()
+            },
+            testing::task("last", "last")
+                .due_at("2025-12-22T23:59:00Z")
+                .build()
+                .unwrap(),
+            testing::task("after", "after")
+                .due_at("2025-12-23T00:00:00Z")
+                .build()
+                .unwrap(),
+            testing::task("unscheduled", "unscheduled").build().unwrap(),
+        ];
+
+        let upcoming = filter_tasks(&tasks, today, UtcOffset::UTC, ListMode::Upcoming(3)).unwrap();
+
+        let ids: Vec<&str> = upcoming.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, ["today", "last"]);
+    }
+
+    #[test]
+    fn filter_tasks_reports_invalid_scheduled_at() {
+        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
+        let offset = UtcOffset::UTC;
+        let tasks = vec![Task {
+            due_at: Some("not-a-date".to_string()),
+            ..testing::task("task-1", "bad").build().unwrap()
+        }];
+
+        let err = filter_tasks(&tasks, today, offset, ListMode::Today).unwrap_err();
+        assert_eq!(err.code(), "invalid_data");
+    }
+
+    #[test]
+    fn edit_task_updates_title() {
+        let store = TempStore::new();
+        let path = store.path();
+        let original = testing::task("task-1", "old")
+            .due_at("2025-12-22T09:00:00Z")
+            .build()
+            .unwrap();
+
+        json_store::save_tasks(path, std::slice::from_ref(&original)).unwrap();
+
+        let updated = edit_task_with_path(path, "task-1", "new").unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(updated.title, "new");
+        assert_eq!(updated.due_at, original.due_at);
+        assert_eq!(loaded[0].title, "new");
+        assert_eq!(loaded[0].due_at, original.due_at);
+    }
+
+    #[test]
+    fn add_and_edit_store_titles_in_nfc() {
+        let store = TempStore::new();
+        let path = store.path();
+        let added = add_task_with_path(path, "  Cafe\u{301} ☕ with 東京 team ", false).unwrap();
+        let edited =
+            edit_task_with_path(path, &added.id, "Re\u{301}sume\u{301} 👩\u{200d}💻").unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(added.title, "Caf\u{e9} ☕ with 東京 team");
+        assert_eq!(edited.title, "R\u{e9}sum\u{e9} 👩\u{200d}💻");
+        assert_eq!(loaded[0].title, edited.title);
+    }
+
+    #[test]
+    fn title_rules_apply_only_in_the_transaction_that_sets_them() {
+        let store = TempStore::new();
+        let path = store.path();
+        let rules = TitleConfig {
+            sentence_case: true,
+            trim_trailing_punctuation: true,
+            ..TitleConfig::default()
+        };
+        let added = transaction_with_path(path, |txn| {
+            txn.set_title_rules(rules);
+            txn.add("BUY MILK!!", false)
+        })
+        .unwrap();
+        let edited = transaction_with_path(path, |txn| {
+            txn.set_title_rules(rules);
+            txn.edit(&added.id, "call Mum.")
+        })
+        .unwrap();
+        let plain = add_task_with_path(path, "BUY MILK!!", false).unwrap();
+
+        assert_eq!(added.title, "Buy milk");
+        assert_eq!(edited.title, "Call mum");
+        assert_eq!(plain.title, "BUY MILK!!");
+    }
+
+    #[test]
+    fn edit_task_rejects_blank_title() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "old").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = edit_task_with_path(path, "task-1", "  ").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn edit_task_rejects_missing_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "old").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = edit_task_with_path(path, "task-2", "new").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn edit_task_rejects_blank_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "old").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = edit_task_with_path(path, "  ", "new").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn complete_task_sets_completed_at_and_history() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo")
+            .due_at("2025-12-22T09:00:00Z")
+            .build()
+            .unwrap();
+
+        json_store::save_tasks(path, std::slice::from_ref(&task)).unwrap();
+
+        let updated = complete_task_with_path(path, "task-1", Some("ship it")).unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(updated.status, TaskStatus::Completed);
+        let completed_at = updated.completed_at.clone().expect("completed_at set");
+        OffsetDateTime::parse(&completed_at, &Rfc3339).unwrap();
+        assert_eq!(updated.due_at, task.due_at);
+        assert_eq!(updated.completion_history.len(), 1);
+        assert_eq!(updated.completion_history[0].message, "ship it");
+        assert_eq!(updated.completion_history[0].completed_at, completed_at);
+
+        assert_eq!(loaded[0].status, TaskStatus::Completed);
+        assert_eq!(loaded[0].completed_at, Some(completed_at));
+        assert_eq!(loaded[0].completion_history.len(), 1);
+        assert_eq!(loaded[0].completion_history[0].message, "ship it");
+    }
+
+    #[test]
+    fn complete_task_without_message_keeps_history_empty() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let updated = complete_task_with_path(path, "task-1", None).unwrap();
+
+        assert_eq!(updated.status, TaskStatus::Completed);
+        assert!(updated.completed_at.is_some());
+        assert!(updated.completion_history.is_empty());
+    }
+
+    #[test]
+    fn complete_task_rejects_already_completed() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = Task {
+            status: TaskStatus::Completed,
+            completed_at: Some("2025-12-22T10:00:00Z".to_string()),
+            completion_history: vec![CompletionEntry {
+                message: "already".to_string(),
+                completed_at: "2025-12-22T10:00:00Z".to_string(),
+            }],
+            ..testing::task("task-1", "demo").build().unwrap()
+        };
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = complete_task_with_path(path, "task-1", Some("ship it")).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn complete_task_rejects_blank_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = complete_task_with_path(path, "  ", None).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn complete_task_rejects_blank_message() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = complete_task_with_path(path, "task-1", Some("   ")).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn complete_task_rejects_missing_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = complete_task_with_path(path, "task-2", None).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn delete_task_removes_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "old").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let removed = delete_task_with_path(path, "task-1").unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(removed.id, "task-1");
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn delete_task_rejects_missing_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "old").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = delete_task_with_path(path, "task-2").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn delete_task_rejects_blank_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "old").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = delete_task_with_path(path, "").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn schedule_task_updates_scheduled_at_and_persists() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let updated = schedule_task_with_path(path, "task-1", "2025-12-21T09:00:00Z").unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(updated.due_at, Some("2025-12-21T09:00:00Z".to_string()));
+        assert_eq!(loaded[0].due_at, updated.due_at);
+    }
+
+    #[test]
+    fn schedule_task_rejects_invalid_datetime() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = schedule_task_with_path(path, "task-1", "bad-date").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn schedule_task_rejects_blank_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = schedule_task_with_path(path, "  ", "2025-12-21T09:00:00Z").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn schedule_task_rejects_unknown_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = schedule_task_with_path(path, "task-2", "2025-12-21T09:00:00Z").unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn reschedule_task_rejects_unscheduled_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = testing::task("task-1", "demo").build().unwrap();
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = reschedule_task_with_path(path, "task-1", "2025-12-21T09:00:00Z", Duration::ZERO)
+            .unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn reschedule_task_rejects_non_overdue_task() {
+        let store = TempStore::new();
+        let path = store.path();
+        let future = (OffsetDateTime::now_utc() + Duration::days(1))
+            .format(&Rfc3339)
+            .unwrap();
+        let task = Task {
+            due_at: Some(future),
+            ..testing::task("task-1", "demo").build().unwrap()
+        };
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = reschedule_task_with_path(path, "task-1", "2025-12-21T09:00:00Z", Duration::ZERO)
+            .unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn reschedule_task_updates_scheduled_at_and_persists() {
+        let store = TempStore::new();
+        let path = store.path();
+        let now = OffsetDateTime::now_utc();
+        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
+        let future = (now + Duration::days(1)).format(&Rfc3339).unwrap();
+        let task = Task {
+            due_at: Some(past),
+            ..testing::task("task-1", "demo").build().unwrap()
+        };
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let updated = reschedule_task_with_path(path, "task-1", &future, Duration::ZERO).unwrap();
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(updated.due_at, Some(future));
+        assert_eq!(loaded[0].due_at, updated.due_at);
+    }
+
+    #[test]
+    fn reschedule_task_rejects_invalid_datetime() {
+        let store = TempStore::new();
+        let path = store.path();
+        let past = (OffsetDateTime::now_utc() - Duration::days(1))
+            .format(&Rfc3339)
+            .unwrap();
+        let task = Task {
+            due_at: Some(past),
+            ..testing::task("task-1", "demo").build().unwrap()
+        };
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err =
+            reschedule_task_with_path(path, "task-1", "bad-date", Duration::ZERO).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn reschedule_task_rejects_blank_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let past = (OffsetDateTime::now_utc() - Duration::days(1))
+            .format(&Rfc3339)
+            .unwrap();
+        let task = Task {
+            due_at: Some(past),
+            ..testing::task("task-1", "demo").build().unwrap()
+        };
+
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = reschedule_task_with_path(path, "  ", "2025-12-21T09:00:00Z", Duration::ZERO)
+            .unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn reschedule_task_rejects_unknown_id() {
+        let store = TempStore::new();
+        let path = store.path();
+        let past = (OffsetDateTime::now_utc() - Duration::days(1))
+            .format(&Rfc3339)
+            .unwrap();
         let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+            due_at: Some(past),
+            ..testing::task("task-1", "demo").build().unwrap()
         };
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        json_store::save_tasks(path, &[task]).unwrap();
+
+        let err = reschedule_task_with_path(path, "task-2", "2025-12-21T09:00:00Z", Duration::ZERO)
+            .unwrap_err();
+
+        assert_eq!(err.code(), "invalid_input");
+    }
+
+    #[test]
+    fn schedule_task_keeps_list_filters_working() {
+        let store = TempStore::new();
+        let path = store.path();
+        let tasks = vec![
+            testing::task("task-1", "today").build().unwrap(),
+            testing::task("task-2", "future").build().unwrap(),
+        ];
+
+        json_store::save_tasks(path, &tasks).unwrap();
+
+        schedule_task_with_path(path, "task-1", "2025-12-20T12:00:00Z").unwrap();
+        schedule_task_with_path(path, "task-2", "2025-12-21T09:00:00Z").unwrap();
+
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
+        let offset = UtcOffset::UTC;
+
+        let today_tasks = filter_tasks(&loaded, today, offset, ListMode::Today).unwrap();
+        assert_eq!(today_tasks.len(), 1);
+        assert_eq!(today_tasks[0].id, "task-1");
+
+        let backlog_tasks = filter_tasks(&loaded, today, offset, ListMode::Backlog).unwrap();
+        assert_eq!(backlog_tasks.len(), 1);
+        assert_eq!(backlog_tasks[0].id, "task-2");
+    }
+
+    #[test]
+    fn reschedule_task_keeps_list_filters_working() {
+        let store = TempStore::new();
+        let path = store.path();
+        let now = OffsetDateTime::now_utc();
+        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
+        let future = (now + Duration::days(1)).format(&Rfc3339).unwrap();
+        let tasks = vec![
+            Task {
+                due_at: Some(past.clone()),
+                ..testing::task("task-1", "today").build().unwrap()
+            },
+            Task {
+                due_at: Some(past),
+                ..testing::task("task-2", "future").build().unwrap()
+            },
+        ];
+
+        json_store::save_tasks(path, &tasks).unwrap();
+
+        reschedule_task_with_path(path, "task-2", &future, Duration::ZERO).unwrap();
+
+        let loaded = json_store::load_tasks(path).unwrap();
+
+        let offset = UtcOffset::UTC;
+        let today = OffsetDateTime::now_utc().to_offset(offset).date();
+
+        let today_tasks = filter_tasks(&loaded, today, offset, ListMode::Today).unwrap();
+        assert_eq!(today_tasks.len(), 1);
+        assert_eq!(today_tasks[0].id, "task-1");
+
+        let backlog_tasks = filter_tasks(&loaded, today, offset, ListMode::Backlog).unwrap();
+        assert_eq!(backlog_tasks.len(), 1);
+        assert_eq!(backlog_tasks[0].id, "task-2");
+    }
+
+    #[test]
+    fn list_today_backlog_with_scheduled_tasks_smoke() {
+        let store = TempStore::new();
+        let path = store.path();
+        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
+        let today = now_local.date();
+        let today_dt = today
+            .with_hms(12, 0, 0)
+            .unwrap()
+            .assume_offset(local_offset);
+        let future_dt = (today + Duration::days(2))
+            .with_hms(12, 0, 0)
+            .unwrap()
+            .assume_offset(local_offset);
+
+        let tasks = vec![
+            Task {
+                created_at: now_local.format(&Rfc3339).unwrap(),
+                due_at: Some(today_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-1", "today").build().unwrap()
+            },
+            Task {
+                created_at: now_local.format(&Rfc3339).unwrap(),
+                due_at: Some(future_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-2", "future").build().unwrap()
+            },
+        ];
+
+        json_store::save_tasks(path, &tasks).unwrap();
+
+        let today_tasks = list_today_with_focus_with_path(path, Duration::ZERO)
+            .unwrap()
+            .tasks;
+        let backlog_tasks = list_without_focus(path, ListMode::Backlog, Duration::ZERO).unwrap();
+
+        assert_eq!(today_tasks.len(), 1);
+        assert_eq!(today_tasks[0].id, "task-1");
+        assert_eq!(backlog_tasks.len(), 1);
+        assert_eq!(backlog_tasks[0].id, "task-2");
+    }
+
+    #[test]
+    fn list_today_with_focus_orders_focused_task_first() {
+        let store = TempStore::new();
+        let path = store.path();
+        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
+        let today = now_local.date();
+        let today_dt = today
+            .with_hms(12, 0, 0)
+            .unwrap()
+            .assume_offset(local_offset);
+
+        let tasks = vec![
+            Task {
+                created_at: now_local.format(&Rfc3339).unwrap(),
+                due_at: Some(today_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-1", "first").build().unwrap()
+            },
+            Task {
+                created_at: now_local.format(&Rfc3339).unwrap(),
+                due_at: Some(today_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-2", "second").build().unwrap()
+            },
+        ];
+
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks,
+                focused_task_id: Some("task-2".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let result = list_today_with_focus_with_path(path, Duration::ZERO).unwrap();
+
+        assert_eq!(result.focused_task_id, Some("task-2".to_string()));
+        assert_eq!(result.tasks.len(), 2);
+        assert_eq!(result.tasks[0].id, "task-2");
+    }
+
+    #[test]
+    fn list_today_with_focus_omits_focus_when_not_in_list() {
+        let store = TempStore::new();
+        let path = store.path();
+        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
+        let today = now_local.date();
+        let today_dt = today
+            .with_hms(12, 0, 0)
+            .unwrap()
+            .assume_offset(local_offset);
+        let future_dt = (today + Duration::days(1))
+            .with_hms(9, 0, 0)
+            .unwrap()
+            .assume_offset(local_offset);
+
+        let tasks = vec![
+            Task {
+                created_at: now_local.format(&Rfc3339).unwrap(),
+                due_at: Some(today_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-1", "today").build().unwrap()
+            },
+            Task {
+                created_at: now_local.format(&Rfc3339).unwrap(),
+                due_at: Some(future_dt.format(&Rfc3339).unwrap()),
+                ..testing::task("task-2", "future").build().unwrap()
+            },
+        ];
+
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks,
+                focused_task_id: Some("task-2".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let result = list_today_with_focus_with_path(path, Duration::ZERO).unwrap();
+
+        assert_eq!(result.focused_task_id, Some("task-2".to_string()));
+        assert_eq!(result.tasks.len(), 1);
+        assert_eq!(result.tasks[0].id, "task-1");
+    }
+
+    #[derive(Default)]
+    struct MockNotifier {
+        notified: RefCell<Vec<(String, String)>>,
+        messages: RefCell<Vec<String>>,
+    }
+
+    impl Notifier for MockNotifier {
+        fn notify(&self, task: &Task) -> Result<(), AppError> {
+            self.notify_with_action(task, "")
+        }
 
-        let updated = schedule_task_with_path(&path, "task-1", "2025-12-21T09:00:00Z").unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+        fn notify_with_action(&self, task: &Task, action: &str) -> Result<(), AppError> {
+            self.notified
+                .borrow_mut()
+                .push((task.id.clone(), action.to_string()));
+            Ok(())
+        }
 
-        assert_eq!(
-            updated.scheduled_at,
-            Some("2025-12-21T09:00:00Z".to_string())
-        );
-        assert_eq!(loaded[0].scheduled_at, updated.scheduled_at);
+        fn notify_message(&self, _summary: &str, body: &str) -> Result<(), AppError> {
+            self.messages.borrow_mut().push(body.to_string());
+            Ok(())
+        }
     }
 
     #[test]
-    fn schedule_task_rejects_invalid_datetime() {
-        let path = temp_path("schedule-invalid.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    fn notify_overdue_or_urgent_selects_pending_tasks() {
+        let store = TempStore::new();
+        let path = store.path();
+        let now = OffsetDateTime::now_utc();
+        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        let tasks = vec![
+            Task {
+                due_at: Some(past.clone()),
+                ..testing::task("task-1", "overdue").build().unwrap()
+            },
+            testing::task("task-2", "urgent")
+                .urgent(true)
+                .build()
+                .unwrap(),
+            testing::task("task-3", "normal").build().unwrap(),
+            Task {
+                status: TaskStatus::Completed,
+                due_at: Some(past),
+                completed_at: Some("2025-12-02T00:00:00Z".to_string()),
+                ..testing::task("task-4", "done")
+                    .urgent(true)
+                    .build()
+                    .unwrap()
+            },
+        ];
 
-        let err = schedule_task_with_path(&path, "task-1", "bad-date").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        json_store::save_tasks(path, &tasks).unwrap();
 
-        assert_eq!(err.code(), "invalid_input");
+        let notifier = MockNotifier::default();
+        let outcome = notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &NotificationConfig::default(),
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        let ids = notifier.notified.borrow().clone();
+        assert_eq!(
+            ids,
+            vec![
+                ("task-1".to_string(), "show:task-1".to_string()),
+                ("task-2".to_string(), "show:task-2".to_string())
+            ]
+        );
+        assert!(outcome.failures.is_empty());
+        assert_eq!(outcome.tasks.len(), 2);
+        assert_eq!(outcome.tasks[0].id, "task-1");
+        assert_eq!(outcome.tasks[1].id, "task-2");
     }
 
     #[test]
-    fn schedule_task_rejects_blank_id() {
-        let path = temp_path("schedule-blank-id.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
-
-        json_store::save_tasks(&path, &[task]).unwrap();
+    fn triage_deadline_keeps_the_time_of_day_on_the_picked_day() {
+        // 2026-03-02 is a Monday.
+        let now = datetime!(2026-03-02 10:00 +1);
+        let due = datetime!(2026-02-26 08:00 UTC);
 
-        let err = schedule_task_with_path(&path, "  ", "2025-12-21T09:00:00Z").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            triage_deadline(datetime!(2026-02-26 16:30 +1), now, TriageDay::Today),
+            datetime!(2026-03-02 16:30 +1)
+        );
+        assert_eq!(
+            triage_deadline(due, now, TriageDay::Today),
+            datetime!(2026-03-02 23:59:59 +1)
+        );
+        assert_eq!(
+            triage_deadline(due, now, TriageDay::Tomorrow),
+            datetime!(2026-03-03 09:00 +1)
+        );
+        assert_eq!(
+            triage_deadline(due, now, TriageDay::NextWeek),
+            datetime!(2026-03-09 09:00 +1)
+        );
+        assert_eq!(
+            triage_deadline(due, datetime!(2026-03-08 10:00 +1), TriageDay::NextWeek),
+            datetime!(2026-03-09 09:00 +1)
+        );
+    }
 
-        assert_eq!(err.code(), "invalid_input");
+    #[test]
+    fn overdue_at_waits_out_the_grace() {
+        let due = datetime!(2026-03-02 09:00 UTC);
+        let grace = Duration::minutes(15);
+
+        assert!(!overdue_at(due, datetime!(2026-03-02 09:01 UTC), grace));
+        assert!(!overdue_at(due, datetime!(2026-03-02 09:15 UTC), grace));
+        assert!(overdue_at(due, datetime!(2026-03-02 09:15:01 UTC), grace));
+        assert!(!overdue_at(due, due, Duration::ZERO));
+        assert!(overdue_at(
+            due,
+            datetime!(2026-03-02 09:00:01 UTC),
+            Duration::ZERO
+        ));
     }
 
     #[test]
-    fn schedule_task_rejects_unknown_id() {
-        let path = temp_path("schedule-missing.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
+    fn due_in_at_counts_down_only_within_the_horizon() {
+        let now = datetime!(2026-03-02 08:00 UTC);
+        let horizon = Duration::hours(2);
+        let due = |at: &str| Task {
+            due_at: Some(at.to_string()),
+            ..testing::task("task-1", "task").build().unwrap()
         };
 
-        json_store::save_tasks(&path, &[task]).unwrap();
-
-        let err = schedule_task_with_path(&path, "task-2", "2025-12-21T09:00:00Z").unwrap_err();
-        std::fs::remove_file(&path).ok();
-
-        assert_eq!(err.code(), "invalid_input");
+        assert_eq!(
+            due_in_at(&due("2026-03-02T09:23:00Z"), now, horizon).unwrap(),
+            Some(Duration::minutes(83))
+        );
+        assert_eq!(
+            due_in_at(&due("2026-03-02T10:00:00Z"), now, horizon).unwrap(),
+            Some(horizon)
+        );
+        assert_eq!(
+            due_in_at(&due("2026-03-02T10:00:01Z"), now, horizon).unwrap(),
+            None
+        );
+        assert_eq!(
+            due_in_at(&due("2026-03-02T08:00:00Z"), now, horizon).unwrap(),
+            None
+        );
+        assert_eq!(
+            due_in_at(&due("2026-03-02T09:00:00Z"), now, Duration::ZERO).unwrap(),
+            None
+        );
+        let done = Task {
+            status: TaskStatus::Completed,
+            completed_at: Some("2026-03-02T07:00:00Z".to_string()),
+            ..due("2026-03-02T09:00:00Z")
+        };
+        assert_eq!(due_in_at(&done, now, horizon).unwrap(), None);
+        assert!(due_in_at(&due("soon"), now, horizon).is_err());
     }
 
     #[test]
-    fn reschedule_task_rejects_unscheduled_task() {
-        let path = temp_path("reschedule-unscheduled.json");
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: None,
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
-
-        json_store::save_tasks(&path, &[task]).unwrap();
+    fn notify_overdue_or_urgent_skips_tasks_still_within_the_grace() {
+        let store = TempStore::new();
+        let path = store.path();
+        let now = datetime!(2026-03-02 09:16 UTC);
+        let tasks = vec![
+            testing::task("at-nine", "due at nine")
+                .due_at("2026-03-02T09:00:00Z")
+                .build()
+                .unwrap(),
+            testing::task("at-five-past", "due at five past")
+                .due_at("2026-03-02T09:05:00Z")
+                .build()
+                .unwrap(),
+        ];
+        json_store::save_tasks(path, &tasks).unwrap();
 
-        let err = reschedule_task_with_path(&path, "task-1", "2025-12-21T09:00:00Z").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        let notifier = MockNotifier::default();
+        let outcome = notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &NotificationConfig::default(),
+            now,
+            Duration::minutes(15),
+        )
+        .unwrap();
 
-        assert_eq!(err.code(), "invalid_input");
+        let ids: Vec<&str> = outcome.tasks.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, ["at-nine"]);
     }
 
     #[test]
-    fn reschedule_task_rejects_non_overdue_task() {
-        let path = temp_path("reschedule-not-overdue.json");
-        let future = (OffsetDateTime::now_utc() + Duration::days(1))
-            .format(&Rfc3339)
-            .unwrap();
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(future),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    fn notify_overdue_or_urgent_records_last_notified_at() {
+        let store = TempStore::new();
+        let path = store.path();
+        let tasks = vec![
+            testing::task("task-1", "urgent")
+                .urgent(true)
+                .build()
+                .unwrap(),
+        ];
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        json_store::save_tasks(path, &tasks).unwrap();
 
-        let err = reschedule_task_with_path(&path, "task-1", "2025-12-21T09:00:00Z").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        let notifier = MockNotifier::default();
+        notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &NotificationConfig::default(),
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
+        let loaded = json_store::load_state(path).unwrap();
 
-        assert_eq!(err.code(), "invalid_input");
+        let recorded = loaded.last_notified_at.expect("last_notified_at set");
+        OffsetDateTime::parse(&recorded, &Rfc3339).unwrap();
     }
 
     #[test]
-    fn reschedule_task_updates_scheduled_at_and_persists() {
-        let path = temp_path("reschedule-task.json");
+    fn stale_notification_warning_requires_old_or_missing_notification() {
+        let store = TempStore::new();
+        let path = store.path();
         let now = OffsetDateTime::now_utc();
-        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
-        let future = (now + Duration::days(1)).format(&Rfc3339).unwrap();
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(past),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+        let past = (now - Duration::days(2)).format(&Rfc3339).unwrap();
+        let tasks = vec![Task {
+            due_at: Some(past.clone()),
+            ..testing::task("task-1", "urgent")
+                .urgent(true)
+                .build()
+                .unwrap()
+        }];
+        let config = NotificationConfig::default();
+
+        json_store::save_tasks(path, &tasks).unwrap();
+        let never =
+            stale_notification_warning_with_path(path, &config, now, Duration::ZERO).unwrap();
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        let recent = (now - Duration::hours(1)).format(&Rfc3339).unwrap();
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks: tasks.clone(),
+                focused_task_id: None,
+                last_notified_at: Some(recent),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let fresh =
+            stale_notification_warning_with_path(path, &config, now, Duration::ZERO).unwrap();
 
-        let updated = reschedule_task_with_path(&path, "task-1", &future).unwrap();
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+        json_store::save_state(
+            path,
+            &json_store::TaskState {
+                tasks,
+                focused_task_id: None,
+                last_notified_at: Some(past.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let stale =
+            stale_notification_warning_with_path(path, &config, now, Duration::ZERO).unwrap();
+        let disabled = stale_notification_warning_with_path(
+            path,
+            &NotificationConfig {
+                stale_hint_hours: 0,
+                ..NotificationConfig::default()
+            },
+            now,
+            Duration::ZERO,
+        )
+        .unwrap();
 
-        assert_eq!(updated.scheduled_at, Some(future));
-        assert_eq!(loaded[0].scheduled_at, updated.scheduled_at);
+        assert_eq!(
+            never,
+            Some(super::StaleNotificationWarning {
+                overdue_urgent_count: 1,
+                last_notified_at: None,
+            })
+        );
+        assert!(fresh.is_none());
+        assert_eq!(stale.unwrap().last_notified_at, Some(past));
+        assert!(disabled.is_none());
     }
 
     #[test]
-    fn reschedule_task_rejects_invalid_datetime() {
-        let path = temp_path("reschedule-invalid.json");
-        let past = (OffsetDateTime::now_utc() - Duration::days(1))
+    fn notify_overdue_or_urgent_returns_empty_when_none() {
+        let store = TempStore::new();
+        let path = store.path();
+        let future = (OffsetDateTime::now_utc() + Duration::days(1))
             .format(&Rfc3339)
             .unwrap();
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(past),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+        let tasks = vec![Task {
+            due_at: Some(future),
+            ..testing::task("task-1", "future").build().unwrap()
+        }];
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        json_store::save_tasks(path, &tasks).unwrap();
 
-        let err = reschedule_task_with_path(&path, "task-1", "bad-date").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        let notifier = MockNotifier::default();
+        let outcome = notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &NotificationConfig::default(),
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
 
-        assert_eq!(err.code(), "invalid_input");
+        assert!(notifier.notified.borrow().is_empty());
+        assert!(outcome.failures.is_empty());
+        assert!(outcome.tasks.is_empty());
     }
 
-    #[test]
-    fn reschedule_task_rejects_blank_id() {
-        let path = temp_path("reschedule-blank-id.json");
-        let past = (OffsetDateTime::now_utc() - Duration::days(1))
-            .format(&Rfc3339)
-            .unwrap();
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(past),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    struct FailingNotifier;
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+    impl Notifier for FailingNotifier {
+        fn notify(&self, _task: &Task) -> Result<(), AppError> {
+            Err(AppError::io("no display"))
+        }
 
-        let err = reschedule_task_with_path(&path, "  ", "2025-12-21T09:00:00Z").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        fn notify_with_action(&self, _task: &Task, _action: &str) -> Result<(), AppError> {
+            Err(AppError::io("no display"))
+        }
 
-        assert_eq!(err.code(), "invalid_input");
+        fn notify_message(&self, _summary: &str, _body: &str) -> Result<(), AppError> {
+            Err(AppError::io("no display"))
+        }
     }
 
     #[test]
-    fn reschedule_task_rejects_unknown_id() {
-        let path = temp_path("reschedule-missing.json");
-        let past = (OffsetDateTime::now_utc() - Duration::days(1))
-            .format(&Rfc3339)
-            .unwrap();
-        let task = Task {
-            id: "task-1".to_string(),
-            title: "demo".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(past),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        };
+    fn notify_overdue_or_urgent_reports_failures() {
+        let store = TempStore::new();
+        let path = store.path();
+        let now = OffsetDateTime::now_utc();
+        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
 
-        json_store::save_tasks(&path, &[task]).unwrap();
+        let tasks = vec![Task {
+            due_at: Some(past),
+            ..testing::task("task-urgent", "urgent")
+                .urgent(true)
+                .build()
+                .unwrap()
+        }];
 
-        let err = reschedule_task_with_path(&path, "task-2", "2025-12-21T09:00:00Z").unwrap_err();
-        std::fs::remove_file(&path).ok();
+        json_store::save_tasks(path, &tasks).unwrap();
 
-        assert_eq!(err.code(), "invalid_input");
+        let notifier = FailingNotifier;
+        let outcome = notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &NotificationConfig::default(),
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(outcome.tasks.is_empty());
+        assert_eq!(outcome.failures.len(), 1);
+        assert_eq!(outcome.failures[0].task_id, "task-urgent");
+        assert!(outcome.failures[0].error.message().contains("no display"));
     }
 
     #[test]
-    fn schedule_task_keeps_list_filters_working() {
-        let path = temp_path("schedule-list.json");
+    fn notify_overdue_or_urgent_sends_digest_over_threshold() {
+        let store = TempStore::new();
+        let path = store.path();
+        let past = (OffsetDateTime::now_utc() - Duration::days(1))
+            .format(&Rfc3339)
+            .unwrap();
         let tasks = vec![
             Task {
-                id: "task-1".to_string(),
-                title: "today".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "future".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
+                due_at: Some(past),
+                ..testing::task("task-1", "overdue").build().unwrap()
             },
+            testing::task("task-2", "urgent")
+                .urgent(true)
+                .build()
+                .unwrap(),
         ];
 
-        json_store::save_tasks(&path, &tasks).unwrap();
+        json_store::save_tasks(path, &tasks).unwrap();
+
+        let notifier = MockNotifier::default();
+        let config = NotificationConfig {
+            digest_after: Some(2),
+            ..NotificationConfig::default()
+        };
+        let outcome = notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &config,
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
 
-        schedule_task_with_path(&path, "task-1", "2025-12-20T12:00:00Z").unwrap();
-        schedule_task_with_path(&path, "task-2", "2025-12-21T09:00:00Z").unwrap();
+        assert!(notifier.notified.borrow().is_empty());
+        let messages = notifier.messages.borrow().clone();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("2 tasks need attention (1 overdue, 1 urgent)"));
+        assert_eq!(outcome.digests, messages);
+        assert_eq!(outcome.tasks.len(), 2);
+    }
 
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+    #[test]
+    fn notify_overdue_or_urgent_below_threshold_notifies_individually() {
+        let store = TempStore::new();
+        let path = store.path();
+        let tasks = vec![
+            testing::task("task-1", "urgent")
+                .urgent(true)
+                .build()
+                .unwrap(),
+        ];
 
-        let today = Date::from_calendar_date(2025, Month::December, 20).unwrap();
-        let offset = UtcOffset::UTC;
+        json_store::save_tasks(path, &tasks).unwrap();
 
-        let today_tasks = filter_tasks(&loaded, today, offset, ListMode::Today).unwrap();
-        assert_eq!(today_tasks.len(), 1);
-        assert_eq!(today_tasks[0].id, "task-1");
+        let notifier = MockNotifier::default();
+        let config = NotificationConfig {
+            digest_after: Some(2),
+            ..NotificationConfig::default()
+        };
+        let outcome = notify_overdue_or_urgent_with_path(
+            path,
+            &notifier,
+            &[],
+            &config,
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
 
-        let backlog_tasks = filter_tasks(&loaded, today, offset, ListMode::Backlog).unwrap();
-        assert_eq!(backlog_tasks.len(), 1);
-        assert_eq!(backlog_tasks[0].id, "task-2");
+        assert!(notifier.messages.borrow().is_empty());
+        assert_eq!(notifier.notified.borrow().len(), 1);
+        assert!(outcome.digests.is_empty());
     }
 
     #[test]
-    fn reschedule_task_keeps_list_filters_working() {
-        let path = temp_path("reschedule-list.json");
-        let now = OffsetDateTime::now_utc();
-        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
-        let future = (now + Duration::days(1)).format(&Rfc3339).unwrap();
+    fn dispatch_notifications_sends_each_task_down_its_first_matching_route() {
         let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "today".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(past.clone()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "future".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(past),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
+            testing::task("task-1", "Deploy")
+                .project("work")
+                .urgent(true)
+                .build()
+                .unwrap(),
+            testing::task("task-2", "Dentist")
+                .tag("personal")
+                .urgent(true)
+                .build()
+                .unwrap(),
+            testing::task("task-3", "Taxes")
+                .urgent(true)
+                .build()
+                .unwrap(),
+            testing::task("task-4", "Standup")
+                .project("work")
+                .tag("personal")
+                .urgent(true)
+                .build()
+                .unwrap(),
         ];
+        let work = NotificationRoute {
+            project: Some("work".to_string()),
+            tag: None,
+            backend: NotificationBackend::Webhook,
+            webhook_url: Some("https://hooks.example.com/work".to_string()),
+        };
+        let personal = NotificationRoute {
+            tag: Some("personal".to_string()),
+            project: None,
+            backend: NotificationBackend::Desktop,
+            webhook_url: None,
+        };
+        let (fallback, work_notifier, personal_notifier) = (
+            MockNotifier::default(),
+            MockNotifier::default(),
+            MockNotifier::default(),
+        );
+        let routes: [(&NotificationRoute, &dyn Notifier); 2] =
+            [(&work, &work_notifier), (&personal, &personal_notifier)];
+        let ids = |notifier: &MockNotifier| -> Vec<String> {
+            let notified = notifier.notified.borrow();
+            notified.iter().map(|(id, _)| id.clone()).collect()
+        };
 
-        json_store::save_tasks(&path, &tasks).unwrap();
+        let now = OffsetDateTime::now_utc();
+        let config = NotificationConfig::default();
+        let outcome =
+            dispatch_notifications(&tasks, &fallback, &routes, &config, now, Duration::ZERO)
+                .unwrap();
+
+        assert_eq!(outcome.tasks.len(), 4);
+        assert_eq!(ids(&work_notifier), ["task-1", "task-4"]);
+        assert_eq!(ids(&personal_notifier), ["task-2"]);
+        assert_eq!(ids(&fallback), ["task-3"]);
+
+        let config = NotificationConfig {
+            digest_after: Some(3),
+            ..NotificationConfig::default()
+        };
+        let outcome =
+            dispatch_notifications(&tasks, &fallback, &routes, &config, now, Duration::ZERO)
+                .unwrap();
+
+        assert_eq!(outcome.digests.len(), 3);
+        assert_eq!(work_notifier.messages.borrow().len(), 1);
+        assert!(work_notifier.messages.borrow()[0].starts_with("2 tasks need attention"));
+        assert!(personal_notifier.messages.borrow()[0].starts_with("1 task need attention"));
+        assert!(fallback.messages.borrow()[0].ends_with("top: Taxes"));
+    }
 
-        reschedule_task_with_path(&path, "task-2", &future).unwrap();
+    #[test]
+    fn dispatch_notifications_folds_tasks_past_max_per_run_into_one_digest() {
+        let tasks: Vec<Task> = (1..=50)
+            .map(|n| {
+                testing::task(&format!("task-{n}"), &format!("Import {n}"))
+                    .urgent(true)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let notifier = MockNotifier::default();
+        let config = NotificationConfig {
+            max_per_run: Some(5),
+            ..NotificationConfig::default()
+        };
 
-        let loaded = json_store::load_tasks(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+        let outcome = dispatch_notifications(
+            &tasks,
+            &notifier,
+            &[],
+            &config,
+            OffsetDateTime::now_utc(),
+            Duration::ZERO,
+        )
+        .unwrap();
 
-        let offset = UtcOffset::UTC;
-        let today = OffsetDateTime::now_utc().to_offset(offset).date();
+        let notified = notifier.notified.borrow();
+        assert_eq!(notified.len(), 4);
+        assert_eq!(notified[3].0, "task-4");
+        assert_eq!(
+            notifier.messages.borrow().as_slice(),
+            [
+                "46 tasks need attention (0 overdue, 46 urgent) \u{2014} top: Import 5, Import 6, Import 7"
+            ]
+        );
+        assert_eq!(outcome.tasks.len(), 50);
+        assert_eq!(outcome.digests.len(), 1);
+    }
 
-        let today_tasks = filter_tasks(&loaded, today, offset, ListMode::Today).unwrap();
-        assert_eq!(today_tasks.len(), 1);
-        assert_eq!(today_tasks[0].id, "task-1");
+    #[test]
+    fn ensure_deadline_ahead_refuses_deadlines_already_past() {
+        let now = datetime!(2025-12-20 12:00 UTC);
+        let check = |datetime: &str, grace: Duration| {
+            ensure_deadline_ahead(datetime, UtcOffset::UTC, now, grace)
+        };
 
-        let backlog_tasks = filter_tasks(&loaded, today, offset, ListMode::Backlog).unwrap();
-        assert_eq!(backlog_tasks.len(), 1);
-        assert_eq!(backlog_tasks[0].id, "task-2");
+        assert!(check("2025-12-20 13:00", Duration::ZERO).is_ok());
+        assert!(check("2025-12-20 11:50", Duration::minutes(15)).is_ok());
+        let err = check("2025-12-19", Duration::ZERO).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "2025-12-19 is in the past, so the task would be overdue right away"
+        );
+        assert_eq!(err.hint(), Some("pass --allow-past to schedule it anyway"));
+        assert!(check("2025-12-20 11:50", Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn schedule_confirmation_names_start_and_deadline_in_local_time() {
+        let offset = UtcOffset::from_hms(1, 0, 0).unwrap();
+        let due_only = testing::task("task-1", "Ship")
+            .due_at("2025-12-24T16:00:00Z")
+            .build()
+            .unwrap();
+        let both = testing::task("task-2", "Draft")
+            .start_at("2025-12-22T08:00:00Z")
+            .due_at("2025-12-24T16:00:00Z")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            schedule_confirmation(&due_only, offset).unwrap(),
+            "Scheduled Ship (task-1): due 2025-12-24 17:00"
+        );
+        assert_eq!(
+            schedule_confirmation(&both, offset).unwrap(),
+            "Scheduled Draft (task-2): starts 2025-12-22 09:00, due 2025-12-24 17:00"
+        );
     }
 
     #[test]
-    fn list_today_backlog_with_scheduled_tasks_smoke() {
-        let path = temp_path("list-smoke.json");
-        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
-        let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
-        let today = now_local.date();
-        let today_dt = today
-            .with_hms(12, 0, 0)
-            .unwrap()
-            .assume_offset(local_offset);
-        let future_dt = (today + Duration::days(2))
-            .with_hms(12, 0, 0)
-            .unwrap()
-            .assume_offset(local_offset);
+    fn digest_body_lists_top_titles() {
+        let task = |title: &str| testing::task(title, title).urgent(true).build().unwrap();
+        let tasks = [task("a"), task("b"), task("c"), task("d")];
+        let refs = tasks.iter().collect::<Vec<_>>();
 
-        let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "today".to_string(),
-                status: TaskStatus::Pending,
-                created_at: now_local.format(&Rfc3339).unwrap(),
-                scheduled_at: Some(today_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "future".to_string(),
-                status: TaskStatus::Pending,
-                created_at: now_local.format(&Rfc3339).unwrap(),
-                scheduled_at: Some(future_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-        ];
+        let body = digest_body(&refs, 0, 4);
 
-        json_store::save_tasks(&path, &tasks).unwrap();
+        assert_eq!(
+            body,
+            "4 tasks need attention (0 overdue, 4 urgent) \u{2014} top: a, b, c"
+        );
+    }
 
-        let today_tasks = list_today_with_focus_with_path(&path).unwrap().tasks;
-        let backlog_tasks = list_without_focus(&path, ListMode::Backlog).unwrap();
+    #[test]
+    fn transaction_applies_multiple_mutations_in_one_save() {
+        let store = TempStore::new();
+        let path = store.path();
+        let (first, second) = transaction_with_path(path, |txn| {
+            let first = txn.add("First", false)?;
+            let second = txn.add("Second", true)?;
+            txn.complete(&first.id, Some("done"))?;
+            txn.schedule(&second.id, "2030-01-02 09:30")?;
+            txn.set_focus(&second.id)?;
+            Ok((first, second))
+        })
+        .unwrap();
 
-        std::fs::remove_file(&path).ok();
+        let state = json_store::load_state(path).unwrap();
 
-        assert_eq!(today_tasks.len(), 1);
-        assert_eq!(today_tasks[0].id, "task-1");
-        assert_eq!(backlog_tasks.len(), 1);
-        assert_eq!(backlog_tasks[0].id, "task-2");
+        assert_ne!(first.id, second.id);
+        assert_eq!(state.tasks.len(), 2);
+        assert_eq!(state.tasks[0].status, TaskStatus::Completed);
+        assert_eq!(state.tasks[0].completion_history[0].message, "done");
+        assert!(state.tasks[1].urgent);
+        assert!(state.tasks[1].due_at.is_some());
+        assert_eq!(state.focused_task_id.as_deref(), Some(second.id.as_str()));
     }
 
     #[test]
-    fn list_today_with_focus_orders_focused_task_first() {
-        let path = temp_path("list-focus.json");
-        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
-        let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
-        let today = now_local.date();
-        let today_dt = today
-            .with_hms(12, 0, 0)
-            .unwrap()
-            .assume_offset(local_offset);
+    fn transaction_persists_nothing_when_a_step_fails() {
+        let store = TempStore::new();
+        let path = store.path();
+        let existing = add_task_with_path(path, "Existing", false).unwrap();
 
-        let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "first".to_string(),
-                status: TaskStatus::Pending,
-                created_at: now_local.format(&Rfc3339).unwrap(),
-                scheduled_at: Some(today_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "second".to_string(),
-                status: TaskStatus::Pending,
-                created_at: now_local.format(&Rfc3339).unwrap(),
-                scheduled_at: Some(today_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-        ];
+        let err = transaction_with_path(path, |txn| {
+            txn.add("Discarded", false)?;
+            txn.edit(&existing.id, "Renamed")?;
+            txn.complete("missing", None)
+        })
+        .unwrap_err();
 
+        let state = json_store::load_state(path).unwrap();
+
+        assert_eq!(err.code(), "invalid_input");
+        assert_eq!(state.tasks.len(), 1);
+        assert_eq!(state.tasks[0].title, "Existing");
+    }
+
+    fn save_focused_pair(path: &Path) {
+        let task = |id: &str| {
+            testing::task(id, "demo")
+                .due_at("2025-12-01T09:00:00Z")
+                .build()
+                .unwrap()
+        };
         json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks,
-                focused_task_id: Some("task-2".to_string()),
+            path,
+            &TaskState {
+                tasks: vec![task("task-1"), task("task-2")],
+                focused_task_id: Some("task-1".to_string()),
+                ..Default::default()
             },
         )
         .unwrap();
+    }
 
-        let result = list_today_with_focus_with_path(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+    fn focus_after(mutate: impl Fn(&Path) -> Result<Task, AppError>) -> Option<String> {
+        let store = TempStore::new();
+        let path = store.path();
+        save_focused_pair(path);
+        mutate(path).unwrap();
+        let loaded = json_store::load_state(path).unwrap();
+        loaded.focused_task_id
+    }
 
-        assert_eq!(result.focused_task_id, Some("task-2".to_string()));
-        assert_eq!(result.tasks.len(), 2);
-        assert_eq!(result.tasks[0].id, "task-2");
+    #[test]
+    fn focus_survives_mutations_other_than_removing_the_focused_task() {
+        let kept = Some("task-1".to_string());
+        assert_eq!(
+            focus_after(|path| edit_task_with_path(path, "task-1", "renamed")),
+            kept
+        );
+        assert_eq!(
+            focus_after(|path| set_task_urgent_with_path(path, "task-1", true)),
+            kept
+        );
+        assert_eq!(
+            focus_after(|path| { schedule_task_with_path(path, "task-1", "2030-01-01 10:00") }),
+            kept
+        );
+        assert_eq!(
+            focus_after(|path| {
+                reschedule_task_with_path(path, "task-1", "2030-01-01 10:00", Duration::ZERO)
+            }),
+            kept
+        );
+        assert_eq!(
+            focus_after(|path| add_task_with_path(path, "new", false)),
+            kept
+        );
+        assert_eq!(
+            focus_after(|path| delete_task_with_path(path, "task-2")),
+            kept
+        );
+        assert_eq!(
+            focus_after(|path| { complete_task_with_path(path, "task-2", None) }),
+            kept
+        );
     }
 
     #[test]
-    fn list_today_with_focus_omits_focus_when_not_in_list() {
-        let path = temp_path("list-focus-missing.json");
-        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
-        let now_local = OffsetDateTime::now_utc().to_offset(local_offset);
-        let today = now_local.date();
-        let today_dt = today
-            .with_hms(12, 0, 0)
-            .unwrap()
-            .assume_offset(local_offset);
-        let future_dt = (today + Duration::days(1))
-            .with_hms(9, 0, 0)
-            .unwrap()
-            .assume_offset(local_offset);
+    fn focus_clears_when_focused_task_is_deleted_or_completed() {
+        assert_eq!(
+            focus_after(|path| delete_task_with_path(path, "task-1")),
+            None
+        );
+        assert_eq!(
+            focus_after(|path| complete_task_with_path(path, "task-1", None)),
+            None
+        );
+        assert_eq!(
+            focus_after(|path| { complete_focused_task_with_path(path, None) }),
+            None
+        );
+    }
 
-        let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "today".to_string(),
-                status: TaskStatus::Pending,
-                created_at: now_local.format(&Rfc3339).unwrap(),
-                scheduled_at: Some(today_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "future".to_string(),
-                status: TaskStatus::Pending,
-                created_at: now_local.format(&Rfc3339).unwrap(),
-                scheduled_at: Some(future_dt.format(&Rfc3339).unwrap()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-        ];
+    #[test]
+    fn transaction_sets_tags_and_notes() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = transaction_with_path(path, |txn| {
+            let task = txn.add("Tagged", false)?;
+            txn.set_tags(
+                &task.id,
+                &[" work ".to_string(), "home".to_string(), "work".to_string()],
+            )?;
+            txn.set_notes(&task.id, Some("  call Alex first  "))
+        })
+        .unwrap();
+        let blank_tag =
+            transaction_with_path(path, |txn| txn.set_tags(&task.id, &[" ".to_string()]))
+                .unwrap_err();
+        let cleared =
+            transaction_with_path(path, |txn| txn.set_notes(&task.id, Some(" "))).unwrap();
+
+        assert_eq!(task.tags, vec!["work".to_string(), "home".to_string()]);
+        assert_eq!(task.notes.as_deref(), Some("call Alex first"));
+        assert_eq!(blank_tag.code(), "invalid_input");
+        assert_eq!(cleared.notes, None);
+    }
 
-        json_store::save_state(
-            &path,
-            &json_store::TaskState {
-                tasks,
-                focused_task_id: Some("task-2".to_string()),
-            },
-        )
+    #[test]
+    fn transaction_sets_and_clears_project() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = transaction_with_path(path, |txn| {
+            let task = txn.add("Quarterly report", false)?;
+            txn.set_project(&task.id, Some(" work "))
+        })
         .unwrap();
+        let cleared =
+            transaction_with_path(path, |txn| txn.set_project(&task.id, Some(""))).unwrap();
 
-        let result = list_today_with_focus_with_path(&path).unwrap();
-        std::fs::remove_file(&path).ok();
+        assert_eq!(task.project.as_deref(), Some("work"));
+        assert_eq!(cleared.project, None);
+    }
 
-        assert_eq!(result.focused_task_id, Some("task-2".to_string()));
-        assert_eq!(result.tasks.len(), 1);
-        assert_eq!(result.tasks[0].id, "task-1");
+    #[test]
+    fn start_and_wait_move_open_tasks_between_states() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "Review PR", false).unwrap();
+
+        let waiting =
+            transaction_with_path(path, |txn| txn.wait(&task.id, Some("2025-12-24T09:00:00Z")))
+                .unwrap();
+        let started = transaction_with_path(path, |txn| txn.start(&task.id)).unwrap();
+        let again = transaction_with_path(path, |txn| txn.start(&task.id)).unwrap_err();
+        let blank = transaction_with_path(path, |txn| txn.wait(&task.id, Some(" "))).unwrap_err();
+        let done = transaction_with_path(path, |txn| {
+            txn.wait(&task.id, None)?;
+            txn.complete(&task.id, None)
+        })
+        .unwrap();
+        let after_done = transaction_with_path(path, |txn| txn.start(&task.id)).unwrap_err();
+
+        assert_eq!(waiting.status, TaskStatus::Waiting);
+        assert_eq!(
+            waiting.waiting_until.as_deref(),
+            Some("2025-12-24T09:00:00Z")
+        );
+        assert_eq!(started.status, TaskStatus::InProgress);
+        assert_eq!(started.waiting_until, None);
+        assert_eq!(started.sessions.len(), 1);
+        assert_eq!(started.sessions[0].ended_at, None);
+        assert_eq!(again.message(), "task already in progress");
+        assert_eq!(blank.message(), "datetime is required");
+        assert_eq!(done.status, TaskStatus::Completed);
+        assert_eq!(done.sessions.len(), 1);
+        assert!(done.sessions[0].ended_at.is_some());
+        assert_eq!(after_done.message(), "task already completed");
     }
 
-    #[derive(Default)]
-    struct MockNotifier {
-        notified: RefCell<Vec<(String, String)>>,
+    #[test]
+    fn cancel_keeps_the_task_with_its_reason_and_closes_it() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "Book venue", false).unwrap();
+        set_focus_with_path(path, &task.id).unwrap();
+
+        let cancelled = transaction_with_path(path, |txn| {
+            txn.cancel(&task.id, Some(" Event moved online "))
+        })
+        .unwrap();
+        let state = json_store::load_state(path).unwrap();
+        let again = transaction_with_path(path, |txn| txn.cancel(&task.id, None)).unwrap_err();
+        let complete = transaction_with_path(path, |txn| txn.complete(&task.id, None)).unwrap_err();
+
+        assert_eq!(cancelled.status, TaskStatus::Cancelled);
+        let cancellation = cancelled.cancellation.as_ref().unwrap();
+        assert_eq!(cancellation.reason.as_deref(), Some("Event moved online"));
+        assert_eq!(
+            cancelled.closed_at(),
+            Some(cancellation.cancelled_at.as_str())
+        );
+        assert_eq!(cancelled.completed_at, None);
+        assert_eq!(state.tasks.len(), 1);
+        assert_eq!(state.focused_task_id, None);
+        assert_eq!(again.message(), "task already cancelled");
+        assert_eq!(complete.message(), "task already cancelled");
     }
 
-    impl Notifier for MockNotifier {
-        fn notify(&self, task: &Task) -> Result<(), AppError> {
-            self.notify_with_action(task, "")
-        }
+    #[test]
+    fn set_marker_keeps_the_part_not_given_and_checks_the_symbol() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "Renew passport", false).unwrap();
+
+        transaction_with_path(path, |txn| {
+            txn.set_marker(&task.id, Some(MarkerColor::Red), None)
+        })
+        .unwrap();
+        let both = transaction_with_path(path, |txn| txn.set_marker(&task.id, None, Some(" 🔥 ")))
+            .unwrap();
+        let too_long =
+            transaction_with_path(path, |txn| txn.set_marker(&task.id, None, Some("abc")))
+                .unwrap_err();
+        let cleared = transaction_with_path(path, |txn| txn.clear_marker(&task.id)).unwrap();
 
-        fn notify_with_action(&self, task: &Task, action: &str) -> Result<(), AppError> {
-            self.notified
-                .borrow_mut()
-                .push((task.id.clone(), action.to_string()));
-            Ok(())
-        }
+        assert_eq!(
+            both.marker,
+            Some(Marker {
+                color: Some(MarkerColor::Red),
+                symbol: Some("🔥".to_string()),
+            })
+        );
+        assert!(
+            too_long
+                .message()
+                .starts_with("marker symbol must be one emoji")
+        );
+        assert_eq!(cleared.marker, None);
     }
 
     #[test]
-    fn notify_overdue_or_urgent_selects_pending_tasks() {
-        let path = temp_path("notify-selects.json");
-        let now = OffsetDateTime::now_utc();
-        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
+    fn set_metadata_adds_replaces_and_removes_keys() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "Ship release", false).unwrap();
+
+        let set = transaction_with_path(path, |txn| {
+            txn.set_metadata(&task.id, "sprint", Some("41"))?;
+            txn.set_metadata(&task.id, " sprint ", Some(" 42 "))
+        })
+        .unwrap();
+        let bad_key = transaction_with_path(path, |txn| {
+            txn.set_metadata(&task.id, "sprint id", Some("1"))
+        })
+        .unwrap_err();
+        let blank = transaction_with_path(path, |txn| txn.set_metadata(&task.id, "x", Some(" ")))
+            .unwrap_err();
+        let removed =
+            transaction_with_path(path, |txn| txn.set_metadata(&task.id, "sprint", None)).unwrap();
+        let missing = transaction_with_path(path, |txn| txn.set_metadata(&task.id, "sprint", None))
+            .unwrap_err();
+
+        assert_eq!(set.metadata.get("sprint").map(String::as_str), Some("42"));
+        assert_eq!(set.metadata.len(), 1);
+        assert_eq!(bad_key.code(), "invalid_input");
+        assert_eq!(blank.code(), "invalid_input");
+        assert!(removed.metadata.is_empty());
+        assert_eq!(missing.code(), "invalid_input");
+    }
 
-        let tasks = vec![
-            Task {
-                id: "task-1".to_string(),
-                title: "overdue".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(past.clone()),
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-2".to_string(),
-                title: "urgent".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: true,
-            },
-            Task {
-                id: "task-3".to_string(),
-                title: "normal".to_string(),
-                status: TaskStatus::Pending,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: None,
-                completed_at: None,
-                completion_history: Vec::new(),
-                urgent: false,
-            },
-            Task {
-                id: "task-4".to_string(),
-                title: "done".to_string(),
-                status: TaskStatus::Completed,
-                created_at: "2025-12-01T00:00:00Z".to_string(),
-                scheduled_at: Some(past),
-                completed_at: Some("2025-12-02T00:00:00Z".to_string()),
-                completion_history: Vec::new(),
-                urgent: true,
-            },
-        ];
+    #[test]
+    fn add_comment_appends_to_the_thread() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "Ship release", false).unwrap();
+
+        transaction_with_path(path, |txn| {
+            txn.add_comment(&task.id, "ana", "Blocked on QA")
+        })
+        .unwrap();
+        let commented =
+            transaction_with_path(path, |txn| txn.add_comment(&task.id, " ben ", " QA done\n"))
+                .unwrap();
+        let blank =
+            transaction_with_path(path, |txn| txn.add_comment(&task.id, "ana", "  ")).unwrap_err();
+        let anonymous =
+            transaction_with_path(path, |txn| txn.add_comment(&task.id, "", "hi")).unwrap_err();
+        let stored = json_store::load_state(path).unwrap();
+
+        let thread: Vec<(&str, &str)> = commented
+            .comments
+            .iter()
+            .map(|comment| (comment.author.as_str(), comment.text.as_str()))
+            .collect();
+        assert_eq!(thread, vec![("ana", "Blocked on QA"), ("ben", "QA done")]);
+        assert!(commented.completion_history.is_empty());
+        assert_eq!(stored.tasks[0].comments, commented.comments);
+        assert_eq!(blank.message(), "comment text is required");
+        assert_eq!(anonymous.message(), "comment author is required");
+    }
 
-        json_store::save_tasks(&path, &tasks).unwrap();
+    #[test]
+    fn search_indexes_large_stores_and_keeps_the_index_current() {
+        let store = TempStore::new();
+        let path = store.path();
+        transaction_with_path(path, |txn| {
+            for number in 0..INDEX_MIN_TASKS {
+                txn.add(&format!("Chore {number}"), false)?;
+            }
+            txn.add("Renew passport", false)
+        })
+        .unwrap();
 
-        let notifier = MockNotifier::default();
-        let outcome = notify_overdue_or_urgent_with_path(&path, &notifier).unwrap();
-        std::fs::remove_file(&path).ok();
+        let first = search_tasks_with_path(path, "PASSPORT").unwrap();
+        let indexed = search::is_current(path).unwrap();
+        transaction_with_path(path, |txn| txn.add("Passport photos", false)).unwrap();
+        let second = search_tasks_with_path(path, "passport").unwrap();
+        let still_indexed = search::is_current(path).unwrap();
+        let with_short_word = search_tasks_with_path(path, "or 123").unwrap();
+
+        let mut titles: Vec<&str> = second.iter().map(|task| task.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(first.len(), 1);
+        assert!(indexed);
+        assert_eq!(titles, vec!["Passport photos", "Renew passport"]);
+        assert!(still_indexed);
+        assert_eq!(with_short_word.len(), 1);
+        assert_eq!(with_short_word[0].title, "Chore 123");
+    }
 
-        let ids = notifier.notified.borrow().clone();
-        assert_eq!(
-            ids,
-            vec![
-                ("task-1".to_string(), "show:task-1".to_string()),
-                ("task-2".to_string(), "show:task-2".to_string())
-            ]
-        );
-        assert!(outcome.failures.is_empty());
-        assert_eq!(outcome.tasks.len(), 2);
-        assert_eq!(outcome.tasks[0].id, "task-1");
-        assert_eq!(outcome.tasks[1].id, "task-2");
+    #[test]
+    fn transactions_stamp_only_the_tasks_they_change() {
+        let store = TempStore::new();
+        let path = store.path();
+        let first = add_task_with_path(path, "First", false).unwrap();
+        let second = add_task_with_path(path, "Second", false).unwrap();
+        let mut state = json_store::load_state(path).unwrap();
+        state.tasks[1].modified_by = Some("ben@desk".to_string());
+        json_store::save_state(path, &state).unwrap();
+
+        transaction_with_path(path, |txn| txn.set_urgent(&first.id, true)).unwrap();
+        transaction_with_path(path, |txn| txn.set_urgent(&second.id, false)).unwrap();
+        let stored = json_store::load_state(path).unwrap();
+
+        let me = Some(crate::origin::current());
+        assert_eq!(stored.tasks[0].modified_by, me);
+        assert!(stored.tasks[0].modified_at.is_some());
+        assert_eq!(stored.tasks[1].modified_by.as_deref(), Some("ben@desk"));
     }
 
     #[test]
-    fn notify_overdue_or_urgent_returns_empty_when_none() {
-        let path = temp_path("notify-none.json");
-        let future = (OffsetDateTime::now_utc() + Duration::days(1))
-            .format(&Rfc3339)
-            .unwrap();
-        let tasks = vec![Task {
-            id: "task-1".to_string(),
-            title: "future".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(future),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: false,
-        }];
+    fn a_memory_store_stamps_only_the_author_it_is_given() {
+        let anonymous = MemoryStore::default();
+        let named = MemoryStore::default().with_author("ana@browser");
+
+        transaction_in(&anonymous, |txn| txn.add("First", false)).unwrap();
+        transaction_in(&named, |txn| txn.add("First", false)).unwrap();
+
+        let unnamed = &anonymous.state().tasks[0];
+        assert_eq!(unnamed.modified_by, None);
+        assert!(unnamed.modified_at.is_some());
+        let stamped = named.state().tasks[0].modified_by.clone();
+        assert_eq!(stamped.as_deref(), Some("ana@browser"));
+    }
 
-        json_store::save_tasks(&path, &tasks).unwrap();
+    struct FakeTicketFetcher;
 
-        let notifier = MockNotifier::default();
-        let outcome = notify_overdue_or_urgent_with_path(&path, &notifier).unwrap();
-        std::fs::remove_file(&path).ok();
+    impl crate::tickets::TicketFetcher for FakeTicketFetcher {
+        fn fetch(&self, url: &str, _headers: &[(String, String)]) -> Result<Value, AppError> {
+            match url.rsplit('/').next() {
+                Some("OPS-1") => Ok(json!({ "title": "Renew certificates", "state": "open" })),
+                Some("OPS-2") => Ok(json!({ "title": "Rotate keys", "state": "closed" })),
+                _ => Err(AppError::io("request failed: 404")),
+            }
+        }
+    }
 
-        assert!(notifier.notified.borrow().is_empty());
-        assert!(outcome.failures.is_empty());
-        assert!(outcome.tasks.is_empty());
+    #[test]
+    fn refresh_tickets_retitles_completes_and_reports_failures() {
+        let store = TempStore::new();
+        let path = store.path();
+        let link = |key: &str| TicketLink {
+            key: key.to_string(),
+            url: None,
+            provider: None,
+            status: None,
+            synced_at: None,
+        };
+        let (open, closed, missing) = transaction_with_path(path, |txn| {
+            let open = txn.add("certs", false)?;
+            txn.set_ticket(&open.id, Some(link("OPS-1")))?;
+            let closed = txn.add("Rotate keys", false)?;
+            txn.set_ticket(&closed.id, Some(link("OPS-2")))?;
+            let missing = txn.add("Gone", false)?;
+            txn.set_ticket(&missing.id, Some(link("OPS-3")))?;
+            txn.add("Unlinked", false)?;
+            Ok((open, closed, missing))
+        })
+        .unwrap();
+        let mut config = TicketConfig::default();
+        config.providers.insert(
+            "ops".to_string(),
+            TicketProvider {
+                prefix: Some("OPS-".to_string()),
+                url: "https://ops.example.com/{key}".to_string(),
+                headers: BTreeMap::new(),
+                title_pointer: "/title".to_string(),
+                status_pointer: Some("/state".to_string()),
+                closed_statuses: vec!["Closed".to_string()],
+            },
+        );
+
+        let refresh = refresh_tickets_with_path(path, &config, &FakeTicketFetcher).unwrap();
+        let tasks = json_store::load_tasks(path).unwrap();
+        let again = refresh_tickets_with_path(path, &config, &FakeTicketFetcher).unwrap();
+
+        assert_eq!(refresh.synced.len(), 2);
+        assert_eq!(refresh.synced[0].task.id, open.id);
+        assert!(refresh.synced[0].retitled && !refresh.synced[0].completed);
+        assert_eq!(refresh.synced[1].task.id, closed.id);
+        assert!(!refresh.synced[1].retitled && refresh.synced[1].completed);
+        assert_eq!(refresh.failures.len(), 1);
+        assert_eq!(refresh.failures[0].task_id, missing.id);
+        assert_eq!(tasks[0].title, "Renew certificates");
+        let open_link = tasks[0].ticket.as_ref().unwrap();
+        assert_eq!(open_link.status.as_deref(), Some("open"));
+        assert!(open_link.synced_at.is_some());
+        assert_eq!(tasks[1].status, TaskStatus::Completed);
+        assert_eq!(
+            tasks[1].completion_history[0].message,
+            "Ticket OPS-2 closed"
+        );
+        assert_eq!(again.synced.len(), 1);
     }
 
-    struct FailingNotifier;
+    #[test]
+    fn set_ticket_validates_key_and_clears_link() {
+        let store = TempStore::new();
+        let path = store.path();
+        let task = add_task_with_path(path, "Fix login", false).unwrap();
+        let link = TicketLink {
+            key: " JIRA-123 ".to_string(),
+            url: Some(" ".to_string()),
+            provider: None,
+            status: None,
+            synced_at: None,
+        };
 
-    impl Notifier for FailingNotifier {
-        fn notify(&self, _task: &Task) -> Result<(), AppError> {
-            Err(AppError::io("no display"))
-        }
+        let linked =
+            transaction_with_path(path, |txn| txn.set_ticket(&task.id, Some(link.clone())))
+                .unwrap();
+        let bad = transaction_with_path(path, |txn| {
+            txn.set_ticket(
+                &task.id,
+                Some(TicketLink {
+                    key: "JIRA 1".to_string(),
+                    ..link.clone()
+                }),
+            )
+        })
+        .unwrap_err();
+        let cleared = transaction_with_path(path, |txn| txn.set_ticket(&task.id, None)).unwrap();
+
+        let linked = linked.ticket.unwrap();
+        assert_eq!(linked.key, "JIRA-123");
+        assert_eq!(linked.url, None);
+        assert_eq!(bad.code(), "invalid_input");
+        assert_eq!(cleared.ticket, None);
+    }
 
-        fn notify_with_action(&self, _task: &Task, _action: &str) -> Result<(), AppError> {
-            Err(AppError::io("no display"))
-        }
+    #[test]
+    fn active_context_filters_listings_until_cleared() {
+        let store = TempStore::new();
+        let path = store.path();
+        let work = transaction_with_path(path, |txn| {
+            let work = txn.add("Write spec", false)?;
+            txn.set_project(&work.id, Some("work"))?;
+            txn.add("Water plants", false)?;
+            txn.define_context("work", "project:work or tag:work")?;
+            txn.set_context(Some("work"))?;
+            Ok(work)
+        })
+        .unwrap();
+
+        let filtered = list_without_focus(path, ListMode::Backlog, Duration::ZERO).unwrap();
+        let unknown = transaction_with_path(path, |txn| txn.set_context(Some("home"))).unwrap_err();
+        let bad_filter =
+            transaction_with_path(path, |txn| txn.define_context("home", "tag:")).unwrap_err();
+        let reserved =
+            transaction_with_path(path, |txn| txn.define_context("none", "tag:x")).unwrap_err();
+        let deleted = transaction_with_path(path, |txn| txn.delete_context("work")).unwrap();
+        let unfiltered = list_without_focus(path, ListMode::Backlog, Duration::ZERO).unwrap();
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, work.id);
+        assert_eq!(unknown.code(), "invalid_input");
+        assert_eq!(bad_filter.code(), "invalid_input");
+        assert_eq!(reserved.code(), "invalid_input");
+        assert!(deleted.contexts.is_empty());
+        assert_eq!(deleted.active, None);
+        assert_eq!(unfiltered.len(), 2);
     }
 
     #[test]
-    fn notify_overdue_or_urgent_reports_failures() {
-        let path = temp_path("notify-failures.json");
-        let now = OffsetDateTime::now_utc();
-        let past = (now - Duration::days(1)).format(&Rfc3339).unwrap();
+    fn day_notes_gather_lines_and_reach_list_today_and_the_digest() {
+        let store = TempStore::new();
+        let path = store.path();
+        let today = local_today().unwrap();
+        let yesterday = today.previous_day().unwrap();
+        let note = transaction_with_path(path, |txn| {
+            txn.add_day_note(today, "Offsite in Berlin")?;
+            txn.add_day_note(yesterday, "Quiet day")?;
+            txn.add_day_note(today, " Keep the afternoon free ")
+        })
+        .unwrap();
+        let blank = transaction_with_path(path, |txn| txn.add_day_note(today, "  ")).unwrap_err();
+        let listed = list_today_with_focus_with_path(path, Duration::ZERO).unwrap();
+        let digest = digest_with_path(path, DigestPeriod::Day).unwrap();
+        let cleared = transaction_with_path(path, |txn| Ok(txn.clear_day_note(yesterday))).unwrap();
+        let reloaded = json_store::load_state(path).unwrap();
+
+        assert_eq!(note, "Offsite in Berlin\nKeep the afternoon free");
+        assert_eq!(blank.code(), "invalid_input");
+        assert_eq!(listed.day_note.as_deref(), Some(note.as_str()));
+        assert_eq!(
+            digest.day_notes.keys().cloned().collect::<Vec<_>>(),
+            [yesterday.to_string(), today.to_string()]
+        );
+        assert_eq!(cleared.as_deref(), Some("Quiet day"));
+        assert_eq!(reloaded.day_notes.len(), 1);
+    }
 
-        let tasks = vec![Task {
-            id: "task-urgent".to_string(),
-            title: "urgent".to_string(),
-            status: TaskStatus::Pending,
-            created_at: "2025-12-01T00:00:00Z".to_string(),
-            scheduled_at: Some(past),
-            completed_at: None,
-            completion_history: Vec::new(),
-            urgent: true,
-        }];
+    #[test]
+    fn import_adds_every_field_in_one_save_and_dry_run_writes_nothing() {
+        let store = TempStore::new();
+        let path = store.path();
+        let rows = vec![
+            ImportedTask {
+                row: 2,
+                title: "Renew passport".to_string(),
+                due_at: Some("2025-12-24 09:00".to_string()),
+                tags: vec!["admin".to_string()],
+                project: Some("travel".to_string()),
+                urgent: true,
+                ..ImportedTask::default()
+            },
+            ImportedTask {
+                row: 3,
+                title: "Book flights".to_string(),
+                start_at: Some("2025-12-20".to_string()),
+                ..ImportedTask::default()
+            },
+        ];
+        let preview = import_tasks_with_path(path, &rows, true).unwrap();
+        let untouched = path.exists();
+        let imported = import_tasks_with_path(path, &rows, false).unwrap();
+        let bad = ImportedTask {
+            row: 7,
+            title: "Tagged".to_string(),
+            tags: vec![" ".to_string()],
+            ..ImportedTask::default()
+        };
+        let err = import_tasks_with_path(path, &[rows[0].clone(), bad], false).unwrap_err();
+        let stored = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(preview.len(), 2);
+        assert!(!untouched);
+        assert_eq!(imported[0].title, "Renew passport");
+        assert!(imported[0].urgent);
+        assert_eq!(imported[0].tags, ["admin"]);
+        assert_eq!(imported[0].project.as_deref(), Some("travel"));
+        assert!(
+            imported[0]
+                .due_at
+                .as_deref()
+                .unwrap()
+                .starts_with("2025-12-24T09:00:00")
+        );
+        assert!(
+            imported[1]
+                .start_at
+                .as_deref()
+                .unwrap()
+                .starts_with("2025-12-20T00:00:00")
+        );
+        assert_eq!(err.message(), "row 7: tag must not be empty");
+        assert_eq!(stored.len(), 2);
+    }
 
-        json_store::save_tasks(&path, &tasks).unwrap();
+    #[test]
+    fn verify_exports_reports_only_fields_a_format_mangles() {
+        let store = TempStore::new();
+        let path = store.path();
+        transaction_with_path(path, |txn| {
+            let task = txn.add("Pay \"rent\", today", true)?;
+            txn.set_tags(&task.id, &["home".to_string(), "bills".to_string()])?;
+            txn.set_notes(&task.id, Some("first line\nsecond line"))?;
+            txn.schedule(&task.id, "2025-12-24 09:00")?;
+            txn.complete(&task.id, Some("paid"))
+        })
+        .unwrap();
+        let clean = verify_exports_with_path(path).unwrap();
+        transaction_with_path(path, |txn| {
+            let task = txn.add("Plan launch", false)?;
+            txn.set_tags(&task.id, &["side project".to_string()])
+        })
+        .unwrap();
+        let lossy = verify_exports_with_path(path).unwrap();
+
+        assert_eq!(clean[0].format, "store");
+        assert!(clean.iter().all(|trip| trip.is_lossless()), "{clean:?}");
+        assert!(clean[1].not_carried.contains(&"status".to_string()));
+        assert!(
+            clean[1]
+                .not_carried
+                .contains(&"completion_history".to_string())
+        );
+        assert!(lossy[0].is_lossless());
+        assert_eq!(lossy[1].lost.len(), 1);
+        assert_eq!(lossy[1].lost[0].field, "tags");
+    }
 
-        let notifier = FailingNotifier;
-        let outcome = notify_overdue_or_urgent_with_path(&path, &notifier).unwrap();
-        std::fs::remove_file(&path).ok();
+    #[test]
+    fn restore_snapshot_rolls_back_and_keeps_pre_restore_copy() {
+        let store = TempStore::new();
+        let path = store.path();
+        add_task_with_path(path, "Keep", false).unwrap();
+        let saved = save_snapshot_with_path(path, "Before cleanup").unwrap();
+        let reserved = save_snapshot_with_path(path, "Pre restore").unwrap_err();
+        add_task_with_path(path, "Added later", false).unwrap();
+
+        let restore = restore_snapshot_with_path(path, "before cleanup").unwrap();
+        let restored = json_store::load_tasks(path).unwrap();
+        let undo = restore_snapshot_with_path(path, "pre-restore").unwrap();
+        let undone = json_store::load_tasks(path).unwrap();
+
+        assert_eq!(saved.name, "before-cleanup");
+        assert_eq!(reserved.code(), "invalid_input");
+        assert_eq!(restore.restored.name, "before-cleanup");
+        assert_eq!(restore.previous.task_count, 2);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].title, "Keep");
+        assert_eq!(undo.previous.task_count, 1);
+        assert_eq!(undone.len(), 2);
+    }
 
-        assert!(outcome.tasks.is_empty());
-        assert_eq!(outcome.failures.len(), 1);
-        assert_eq!(outcome.failures[0].task_id, "task-urgent");
-        assert!(outcome.failures[0].error.message().contains("no display"));
+    #[test]
+    fn get_task_detail_reports_focus_and_overdue() {
+        let store = TempStore::new();
+        let path = store.path();
+        save_focused_pair(path);
+
+        let focused = get_task_detail_with_path(path, "task-1", Duration::ZERO).unwrap();
+        let other = get_task_detail_with_path(path, "task-2", Duration::ZERO).unwrap();
+        let missing = get_task_detail_with_path(path, "task-3", Duration::ZERO).unwrap_err();
+
+        assert!(focused.focused);
+        assert!(focused.overdue);
+        assert!(!other.focused);
+        assert_eq!(missing.code(), "invalid_input");
     }
 }
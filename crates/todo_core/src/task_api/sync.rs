@@ -0,0 +1,637 @@
+//! Sync with the `remotes` from the config, built with the `sync` feature. The plan and the
+//! connectors live in [`crate::sync`]; this side loads the store, applies the plan in one
+//! transaction, and keeps the sync state next to the store.
+
+use super::{Transaction, local_offset, transaction_with_path};
+use crate::clock;
+use crate::config::RemoteConfig;
+use crate::error::{AppError, ErrorKind};
+use crate::model::Task;
+use crate::origin;
+use crate::storage::json_store;
+use crate::storage::sync_state::{self, SyncedRemote, Unreachable, UnsettledConflict};
+use crate::sync::{self, Conflict, QueuedChange, Remote, Resolution, SyncPlan, SyncScope};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+/// What a sync with one remote changed.
+#[derive(Debug, Clone)]
+pub struct SyncReport {
+    pub remote: String,
+    pub plan: SyncPlan,
+    /// How each of `plan.conflicts` was settled, in the same order.
+    pub resolutions: Vec<Resolution>,
+}
+
+/// One remote as `sync status` shows it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteStatus {
+    pub name: String,
+    pub path: PathBuf,
+    /// When the last sync finished; `None` before the first one.
+    pub synced_at: Option<String>,
+    /// Tasks added, changed, or deleted here since then. Before the first sync, every task.
+    pub local_changes: usize,
+    /// Left by a sync that stopped before settling them.
+    pub conflicts: Vec<UnsettledConflict>,
+}
+
+/// Changes made here that a remote has not received yet, as `sync queue` shows them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncQueue {
+    pub remote: String,
+    pub path: PathBuf,
+    /// Set when the last sync could not reach the remote.
+    pub unreachable: Option<Unreachable>,
+    pub changes: Vec<QueuedChange>,
+}
+
+/// What one `sync flush` did.
+#[derive(Debug, Default)]
+pub struct SyncFlush {
+    pub synced: Vec<SyncReport>,
+    /// Remotes that still could not be synced; their changes stay queued.
+    pub failures: Vec<SyncFailure>,
+}
+
+#[derive(Debug)]
+pub struct SyncFailure {
+    pub remote: String,
+    pub error: AppError,
+}
+
+/// Sync the store with the remote `name` from the `remotes` config. `resolve` settles each
+/// task changed differently on both sides; when it fails, neither side is changed.
+pub fn sync_remote(
+    remotes: &BTreeMap<String, RemoteConfig>,
+    name: &str,
+    grace: Duration,
+    resolve: &mut dyn FnMut(&Conflict) -> Result<Resolution, AppError>,
+) -> Result<SyncReport, AppError> {
+    let path = json_store::store_path()?;
+    let name = name.trim();
+    let (remote, scope) = open_remote(remotes, name, &path, grace)?;
+    sync_with_path(
+        &path,
+        name,
+        remote.as_ref(),
+        &scope,
+        resolve,
+        clock::now_utc(),
+    )
+}
+
+/// Each configured remote with its last sync, read from files next to the store only; the
+/// remotes themselves are not contacted.
+pub fn sync_status(
+    remotes: &BTreeMap<String, RemoteConfig>,
+    grace: Duration,
+) -> Result<Vec<RemoteStatus>, AppError> {
+    let path = json_store::store_path()?;
+    sync_status_with_path(&path, remotes, grace)
+}
+
+/// The remotes with changes from here waiting for them: those synced before, or found
+/// unreachable, that have missed a change since. Read from files next to the store only.
+pub fn sync_queue(
+    remotes: &BTreeMap<String, RemoteConfig>,
+    grace: Duration,
+) -> Result<Vec<SyncQueue>, AppError> {
+    let path = json_store::store_path()?;
+    sync_queue_with_path(&path, remotes, grace)
+}
+
+/// Sync every remote in [`sync_queue`], the ones still unreachable included. A remote that
+/// fails is reported and left queued; the others are synced anyway. `resolve` is given the
+/// remote's name with each conflict.
+pub fn flush_sync_queue(
+    remotes: &BTreeMap<String, RemoteConfig>,
+    grace: Duration,
+    resolve: &mut dyn FnMut(&str, &Conflict) -> Result<Resolution, AppError>,
+) -> Result<SyncFlush, AppError> {
+    let path = json_store::store_path()?;
+    flush_sync_queue_with_path(&path, remotes, grace, resolve, clock::now_utc())
+}
+
+/// What [`sync_remote`] would do, without changing either side. Conflicts are listed
+/// unsettled, so `resolutions` is empty.
+pub fn plan_sync(
+    remotes: &BTreeMap<String, RemoteConfig>,
+    name: &str,
+    grace: Duration,
+) -> Result<SyncReport, AppError> {
+    let path = json_store::store_path()?;
+    let name = name.trim();
+    let (remote, scope) = open_remote(remotes, name, &path, grace)?;
+    plan_sync_with_path(&path, name, remote.as_ref(), &scope)
+}
+
+fn sync_status_with_path(
+    path: &Path,
+    remotes: &BTreeMap<String, RemoteConfig>,
+    grace: Duration,
+) -> Result<Vec<RemoteStatus>, AppError> {
+    let local = json_store::load_tasks(path)?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    let mut sync_state = sync_state::read(path)?;
+    let mut statuses = Vec::with_capacity(remotes.len());
+    for (name, config) in remotes {
+        let scope = SyncScope::from_config(name, config, grace)?;
+        let synced = sync_state.remotes.remove(name);
+        let base = synced
+            .as_ref()
+            .map(|synced| synced.tasks.as_slice())
+            .unwrap_or_default();
+        statuses.push(RemoteStatus {
+            name: name.clone(),
+            path: config.path.clone(),
+            local_changes: sync::changes_since(base, &scope.local_view(&local, now))?,
+            synced_at: synced.map(|synced| synced.synced_at),
+            conflicts: sync_state.conflicts.remove(name).unwrap_or_default(),
+        });
+    }
+    Ok(statuses)
+}
+
+fn sync_queue_with_path(
+    path: &Path,
+    remotes: &BTreeMap<String, RemoteConfig>,
+    grace: Duration,
+) -> Result<Vec<SyncQueue>, AppError> {
+    let local = json_store::load_tasks(path)?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    let mut sync_state = sync_state::read(path)?;
+    let mut queues = Vec::new();
+    for (name, config) in remotes {
+        let synced = sync_state.remotes.remove(name);
+        let unreachable = sync_state.unreachable.remove(name);
+        // Never synced and never tried: nothing has been promised to it yet.
+        if synced.is_none() && unreachable.is_none() {
+            continue;
+        }
+        let scope = SyncScope::from_config(name, config, grace)?;
+        let base = synced.map(|synced| synced.tasks).unwrap_or_default();
+        let changes = sync::queued_changes(&base, &scope.local_view(&local, now))?;
+        if changes.is_empty() && unreachable.is_none() {
+            continue;
+        }
+        queues.push(SyncQueue {
+            remote: name.clone(),
+            path: config.path.clone(),
+            unreachable,
+            changes,
+        });
+    }
+    Ok(queues)
+}
+
+fn flush_sync_queue_with_path(
+    path: &Path,
+    remotes: &BTreeMap<String, RemoteConfig>,
+    grace: Duration,
+    resolve: &mut dyn FnMut(&str, &Conflict) -> Result<Resolution, AppError>,
+    now: OffsetDateTime,
+) -> Result<SyncFlush, AppError> {
+    let mut flush = SyncFlush::default();
+    for queue in sync_queue_with_path(path, remotes, grace)? {
+        let name = queue.remote;
+        let synced = open_remote(remotes, &name, path, grace).and_then(|(remote, scope)| {
+            sync_with_path(
+                path,
+                &name,
+                remote.as_ref(),
+                &scope,
+                &mut |conflict| resolve(&name, conflict),
+                now,
+            )
+        });
+        match synced {
+            Ok(report) => flush.synced.push(report),
+            Err(error) => flush.failures.push(SyncFailure {
+                remote: name,
+                error,
+            }),
+        }
+    }
+    Ok(flush)
+}
+
+fn open_remote(
+    remotes: &BTreeMap<String, RemoteConfig>,
+    name: &str,
+    store_path: &Path,
+    grace: Duration,
+) -> Result<(Box<dyn Remote>, SyncScope), AppError> {
+    let config = remotes.get(name).ok_or_else(|| {
+        AppError::invalid_input(format!(
+            "no remote named '{name}'; define it under `remotes` in the config"
+        ))
+    })?;
+    if config.path == store_path {
+        return Err(AppError::invalid_input(format!(
+            "remote '{name}' is the store itself"
+        )));
+    }
+    Ok((
+        sync::remote_for(config),
+        SyncScope::from_config(name, config, grace)?,
+    ))
+}
+
+/// `err` from contacting remote `name`. An I/O failure means the remote could not be
+/// reached: it is recorded for `sync queue`, and the changes made here wait for it.
+fn unreachable_remote(path: &Path, name: &str, err: AppError, at: &str) -> AppError {
+    if err.kind() != ErrorKind::Io {
+        return err;
+    }
+    let message = err.message();
+    let recorded = sync_state::read(path).and_then(|mut sync_state| {
+        sync_state
+            .unreachable
+            .entry(name.to_string())
+            .and_modify(|unreachable| unreachable.error = message.to_string())
+            .or_insert_with(|| Unreachable {
+                since: at.to_string(),
+                error: message.to_string(),
+            });
+        sync_state::write(path, &sync_state)
+    });
+    match recorded {
+        Ok(()) => AppError::io(format!(
+            "remote '{name}' is unreachable: {message}; changes here stay queued until `sync flush`"
+        )),
+        Err(err) => err,
+    }
+}
+
+fn synced_tasks(path: &Path, name: &str) -> Result<Vec<Task>, AppError> {
+    Ok(sync_state::read(path)?
+        .remotes
+        .remove(name)
+        .map(|synced| synced.tasks)
+        .unwrap_or_default())
+}
+
+fn plan_sync_with_path(
+    path: &Path,
+    name: &str,
+    remote: &dyn Remote,
+    scope: &SyncScope,
+) -> Result<SyncReport, AppError> {
+    let remote_tasks = remote.load()?;
+    let base = synced_tasks(path, name)?;
+    let local = json_store::load_tasks(path)?;
+    let now = clock::now_utc().to_offset(local_offset()?);
+    let local_view = scope.local_view(&local, now);
+    let remote_view = scope.remote_view(&remote_tasks, &local, now);
+    Ok(SyncReport {
+        remote: name.to_string(),
+        plan: sync::plan(&base, &local_view, &remote_view)?,
+        resolutions: Vec::new(),
+    })
+}
+
+fn sync_with_path(
+    path: &Path,
+    name: &str,
+    remote: &dyn Remote,
+    scope: &SyncScope,
+    resolve: &mut dyn FnMut(&Conflict) -> Result<Resolution, AppError>,
+    now: OffsetDateTime,
+) -> Result<SyncReport, AppError> {
+    let at = now
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let remote_tasks = remote
+        .load()
+        .map_err(|err| unreachable_remote(path, name, err, &at))?;
+    let base = synced_tasks(path, name)?;
+    let by = origin::current();
+    let local_now = now.to_offset(local_offset()?);
+
+    let mut found = Vec::new();
+    let result = transaction_with_path(path, |txn| {
+        let local_view = scope.local_view(txn.tasks(), local_now);
+        let remote_view = scope.remote_view(&remote_tasks, txn.tasks(), local_now);
+        let plan = sync::plan(&base, &local_view, &remote_view)?;
+        found.clone_from(&plan.conflicts);
+        let mut resolutions = Vec::with_capacity(plan.conflicts.len());
+        let mut resolved = Vec::with_capacity(plan.conflicts.len());
+        for conflict in &plan.conflicts {
+            let resolution = resolve(conflict)?;
+            resolved.push(conflict.resolved(&resolution, &by, &at)?);
+            resolutions.push(resolution);
+        }
+        let synced = sync::apply(&plan, &resolved, txn.tasks(), &remote_tasks, scope);
+        txn.replace_with_synced(synced.local.clone());
+        let report = SyncReport {
+            remote: name.to_string(),
+            plan,
+            resolutions,
+        };
+        Ok((report, synced))
+    });
+    let (report, synced) = match result {
+        Ok(done) => done,
+        Err(err) if !found.is_empty() => {
+            // Kept for `sync status`, so conflicts left for later are not forgotten.
+            let mut sync_state = sync_state::read(path)?;
+            let unsettled = found
+                .iter()
+                .map(|conflict| UnsettledConflict {
+                    task_id: conflict.local.id.clone(),
+                    title: conflict.local.title.clone(),
+                    fields: conflict
+                        .fields
+                        .iter()
+                        .map(|change| change.field.clone())
+                        .collect(),
+                    found_at: at.clone(),
+                })
+                .collect();
+            sync_state.conflicts.insert(name.to_string(), unsettled);
+            sync_state::write(path, &sync_state)?;
+            return Err(err);
+        }
+        Err(err) => return Err(err),
+    };
+
+    // After the local commit: if saving the remote fails, the next sync finds the same
+    // local changes and pushes them again.
+    if synced.remote != remote_tasks {
+        remote
+            .save(&synced.remote)
+            .map_err(|err| unreachable_remote(path, name, err, &at))?;
+    }
+    let mut sync_state = sync_state::read(path)?;
+    sync_state.conflicts.remove(name);
+    sync_state.unreachable.remove(name);
+    sync_state.remotes.insert(
+        name.to_string(),
+        SyncedRemote {
+            synced_at: at,
+            tasks: scope.local_view(&synced.local, local_now),
+        },
+    );
+    sync_state::write(path, &sync_state)?;
+    Ok(report)
+}
+
+impl Transaction {
+    /// Replace every task with the outcome of a sync. The tasks keep the stamps they arrived
+    /// with, since a pulled task was last changed on the remote, not here.
+    fn replace_with_synced(&mut self, tasks: Vec<Task>) {
+        self.state.tasks = tasks;
+        self.keep_stamps = true;
+        if let Some(focused) = self.state.focused_task_id.as_deref()
+            && !self.state.tasks.iter().any(|task| task.id == focused)
+        {
+            self.state.focused_task_id = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        flush_sync_queue_with_path, plan_sync_with_path, sync_queue_with_path,
+        sync_status_with_path, sync_with_path,
+    };
+    use crate::config::RemoteConfig;
+    use crate::error::AppError;
+    use crate::storage::json_store;
+    use crate::storage::sync_state;
+    use crate::sync::{ChangeKind, Conflict, FileRemote, Remote, Resolution, SyncScope};
+    use crate::task_api::{add_task_with_path, edit_task_with_path, transaction_with_path};
+    use crate::testing::TempStore;
+    use std::collections::BTreeMap;
+    use time::{Duration, OffsetDateTime};
+
+    #[test]
+    fn sync_copies_one_sided_changes_and_asks_about_conflicts() {
+        let store = TempStore::new();
+        let path = store.path();
+        let remote_path = path.with_file_name("remote.json");
+        let remote = FileRemote::new(&remote_path);
+        let rent = add_task_with_path(path, "Pay rent", false).unwrap();
+        let mum = add_task_with_path(path, "Call mum", false).unwrap();
+        let mut never_asked =
+            |_: &Conflict| -> Result<Resolution, AppError> { panic!("no conflict expected") };
+
+        let first = sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut never_asked,
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        let mut on_remote = remote.load().unwrap();
+        on_remote[0].title = "Pay rent early".to_string();
+        on_remote[0].modified_by = Some("ben@desk".to_string());
+        on_remote[1].title = "Call mum on Sunday".to_string();
+        json_store::save_tasks(&remote_path, &on_remote).unwrap();
+        transaction_with_path(path, |txn| txn.set_urgent(&mum.id, true)).unwrap();
+        let before_plan = json_store::load_tasks(path).unwrap();
+        let planned = plan_sync_with_path(path, "shared", &remote, &SyncScope::default()).unwrap();
+        let after_plan = json_store::load_tasks(path).unwrap();
+        let mut asked = Vec::new();
+        let second = sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut |conflict: &Conflict| {
+                asked.push(conflict.local.id.clone());
+                Ok(Resolution::Merge(vec!["title".to_string()]))
+            },
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        let third = sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut never_asked,
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        let local = json_store::load_tasks(path).unwrap();
+        let on_remote = remote.load().unwrap();
+        let synced = sync_state::read(path).unwrap();
+
+        assert_eq!(first.plan.push.len(), 2);
+        assert_eq!(planned.plan, second.plan);
+        assert!(planned.resolutions.is_empty());
+        assert_eq!(before_plan, after_plan);
+        assert_eq!(second.plan.pull.len(), 1);
+        assert_eq!(asked, vec![mum.id.clone()]);
+        assert!(third.plan.is_empty());
+        assert_eq!(local, on_remote);
+        assert_eq!(local[0].id, rent.id);
+        assert_eq!(local[0].title, "Pay rent early");
+        assert_eq!(local[0].modified_by.as_deref(), Some("ben@desk"));
+        assert_eq!(local[1].title, "Call mum on Sunday");
+        assert!(local[1].urgent);
+        assert_eq!(synced.remotes["shared"].tasks, local);
+    }
+
+    #[test]
+    fn sync_status_counts_local_changes_and_keeps_unsettled_conflicts() {
+        let store = TempStore::new();
+        let path = store.path();
+        let remote_path = path.with_file_name("remote.json");
+        let remote = FileRemote::new(&remote_path);
+        let remotes = BTreeMap::from([(
+            "shared".to_string(),
+            RemoteConfig {
+                path: remote_path.clone(),
+                filter: None,
+                exclude_fields: Vec::new(),
+            },
+        )]);
+        let task = add_task_with_path(path, "Pay rent", false).unwrap();
+        add_task_with_path(path, "Call mum", false).unwrap();
+
+        let never_synced = sync_status_with_path(path, &remotes, Duration::ZERO).unwrap();
+        sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut |_: &Conflict| Ok(Resolution::Local),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        let mut on_remote = remote.load().unwrap();
+        on_remote[0].title = "Pay the rent".to_string();
+        json_store::save_tasks(&remote_path, &on_remote).unwrap();
+        edit_task_with_path(path, &task.id, "Pay rent early").unwrap();
+        let stopped = sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut |_: &Conflict| Err(AppError::invalid_input("no answer")),
+            OffsetDateTime::now_utc(),
+        );
+        let after_stop = sync_status_with_path(path, &remotes, Duration::ZERO).unwrap();
+        sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut |_: &Conflict| Ok(Resolution::Remote),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        let settled = sync_status_with_path(path, &remotes, Duration::ZERO).unwrap();
+
+        assert_eq!(never_synced[0].synced_at, None);
+        assert_eq!(never_synced[0].local_changes, 2);
+        assert!(stopped.is_err());
+        assert!(after_stop[0].synced_at.is_some());
+        assert_eq!(after_stop[0].local_changes, 1);
+        assert_eq!(after_stop[0].conflicts.len(), 1);
+        assert_eq!(after_stop[0].conflicts[0].task_id, task.id);
+        assert_eq!(after_stop[0].conflicts[0].fields, vec!["title"]);
+        assert_eq!(settled[0].local_changes, 0);
+        assert!(settled[0].conflicts.is_empty());
+    }
+
+    #[test]
+    fn changes_wait_in_the_queue_while_a_remote_is_unreachable_and_flush_sends_them() {
+        let store = TempStore::new();
+        let path = store.path();
+        let share = path.with_file_name("share");
+        let unmounted = path.with_file_name("unmounted");
+        std::fs::create_dir_all(&share).unwrap();
+        let remote_path = share.join("tasks.json");
+        let remote = FileRemote::new(&remote_path);
+        let remotes = BTreeMap::from([(
+            "shared".to_string(),
+            RemoteConfig {
+                path: remote_path.clone(),
+                filter: None,
+                exclude_fields: Vec::new(),
+            },
+        )]);
+        let rent = add_task_with_path(path, "Pay rent", false).unwrap();
+        let never_tried = sync_queue_with_path(path, &remotes, Duration::ZERO).unwrap();
+        sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut |_: &Conflict| Ok(Resolution::Local),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        edit_task_with_path(path, &rent.id, "Pay rent early").unwrap();
+        let mum = add_task_with_path(path, "Call mum", false).unwrap();
+
+        std::fs::rename(&share, &unmounted).unwrap();
+        let offline = sync_with_path(
+            path,
+            "shared",
+            &remote,
+            &SyncScope::default(),
+            &mut |_: &Conflict| Ok(Resolution::Local),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap_err();
+        let queued = sync_queue_with_path(path, &remotes, Duration::ZERO).unwrap();
+        let still_offline = flush_sync_queue_with_path(
+            path,
+            &remotes,
+            Duration::ZERO,
+            &mut |_: &str, _: &Conflict| Ok(Resolution::Local),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        std::fs::rename(&unmounted, &share).unwrap();
+        let flushed = flush_sync_queue_with_path(
+            path,
+            &remotes,
+            Duration::ZERO,
+            &mut |_: &str, _: &Conflict| Ok(Resolution::Local),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
+        let after = sync_queue_with_path(path, &remotes, Duration::ZERO).unwrap();
+        let on_remote = remote.load().unwrap();
+
+        assert!(never_tried.is_empty());
+        assert_eq!(offline.code(), "io_error");
+        assert!(
+            offline
+                .message()
+                .starts_with("remote 'shared' is unreachable")
+        );
+        assert_eq!(queued.len(), 1);
+        assert!(queued[0].unreachable.is_some());
+        let changes: Vec<(ChangeKind, &str)> = queued[0]
+            .changes
+            .iter()
+            .map(|change| (change.kind, change.task.id.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (ChangeKind::Changed, rent.id.as_str()),
+                (ChangeKind::Added, mum.id.as_str())
+            ]
+        );
+        assert!(still_offline.synced.is_empty());
+        assert_eq!(still_offline.failures[0].remote, "shared");
+        assert_eq!(flushed.synced.len(), 1);
+        assert!(flushed.failures.is_empty());
+        assert!(after.is_empty());
+        assert_eq!(on_remote[0].title, "Pay rent early");
+        assert_eq!(on_remote[1].id, mum.id);
+    }
+}
@@ -0,0 +1,211 @@
+//! Fixtures for tests of this crate and of crates built on it, behind the `testing` feature.
+//!
+//! [`task`] starts a [`TaskBuilder`] that gives the same task every run, so a test only
+//! spells out what it checks. [`TempStore`] is a store file in a folder of its own that is
+//! removed on drop. [`FixedClock`] is a time that only moves when told to, passed in wherever
+//! `now` is a parameter or installed in place of the system clock.
+
+use crate::clock;
+use crate::error::AppError;
+use crate::storage::json_store::{self, TaskState};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use time::format_description::well_known::Rfc3339;
+use time::macros::datetime;
+use time::{Duration, OffsetDateTime};
+
+pub use crate::model::{Task, TaskBuilder};
+
+/// `created_at` of a task from [`task`] unless set otherwise.
+pub const DEFAULT_CREATED_AT: &str = "2025-12-01T00:00:00Z";
+
+/// [`Task::builder`] with `id`, `title`, and [`DEFAULT_CREATED_AT`] already set, so nothing is
+/// generated from the clock.
+///
+/// ```
+/// use todo_core::testing;
+///
+/// let task = testing::task("1", "Buy milk").urgent(true).tag("home").build()?;
+///
+/// assert_eq!(task.created_at, testing::DEFAULT_CREATED_AT);
+/// assert_eq!(task.tags, vec!["home"]);
+/// # Ok::<(), todo_core::error::AppError>(())
+/// ```
+pub fn task(id: &str, title: &str) -> TaskBuilder {
+    Task::builder()
+        .id(id)
+        .title(title)
+        .created_at(DEFAULT_CREATED_AT)
+}
+
+/// A store path in a fresh temporary folder. The folder, with the store and every sidecar file
+/// written next to it, is removed on drop. Panics when the folder cannot be created or written,
+/// since a test cannot carry on without it.
+#[derive(Debug)]
+pub struct TempStore {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl TempStore {
+    /// No store file yet, as on first run.
+    pub fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let dir = std::env::temp_dir().join(format!(
+            "todoapp-{nanos}-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).expect("failed to create a temporary store folder");
+        let path = dir.join("tasks.json");
+        Self { dir, path }
+    }
+
+    pub fn with_tasks(tasks: Vec<Task>) -> Self {
+        Self::with_state(&TaskState {
+            tasks,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_state(state: &TaskState) -> Self {
+        let store = Self::new();
+        json_store::save_state(&store.path, state).expect("failed to write the temporary store");
+        store
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The store as it is on disk now.
+    pub fn state(&self) -> TaskState {
+        json_store::load_state(&self.path).expect("failed to read the temporary store")
+    }
+}
+
+impl Default for TempStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+/// The time [`FixedClock::install`] last put in place of the system clock.
+static INSTALLED: Mutex<Option<OffsetDateTime>> = Mutex::new(None);
+
+/// A point in time that only moves when told to. Defaults to noon UTC on 2025-12-20, after
+/// [`DEFAULT_CREATED_AT`].
+///
+/// ```
+/// use time::Duration;
+/// use todo_core::testing::FixedClock;
+///
+/// let mut clock = FixedClock::default();
+/// clock.advance(Duration::hours(2));
+///
+/// assert_eq!(clock.rfc3339(Duration::ZERO), "2025-12-20T14:00:00Z");
+/// assert_eq!(clock.rfc3339(-Duration::days(1)), "2025-12-19T14:00:00Z");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock {
+    now: OffsetDateTime,
+}
+
+impl FixedClock {
+    pub fn at(now: OffsetDateTime) -> Self {
+        Self { now }
+    }
+
+    pub fn now(&self) -> OffsetDateTime {
+        self.now
+    }
+
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+    }
+
+    /// The time `offset` from now as RFC3339, for timestamps such as `due_at` or for
+    /// [`clock::NOW_ENV`] when running the CLI. A negative offset is in the past.
+    pub fn rfc3339(&self, offset: Duration) -> String {
+        (self.now + offset)
+            .format(&Rfc3339)
+            .expect("RFC3339 formats every UTC time")
+    }
+
+    /// Make [`clock::now_utc`] return this time for the rest of the process, through
+    /// [`clock::set_source`]. Install again after [`FixedClock::advance`] to move it. Fails when
+    /// something else already set the clock source. Every test in the process sees the
+    /// installed time, so it belongs in an integration test of its own.
+    pub fn install(&self) -> Result<(), AppError> {
+        let mut installed = INSTALLED.lock().unwrap_or_else(PoisonError::into_inner);
+        if installed.is_none() {
+            clock::set_source(installed_now)?;
+        }
+        *installed = Some(self.now);
+        Ok(())
+    }
+}
+
+impl Default for FixedClock {
+    fn default() -> Self {
+        Self::at(datetime!(2025-12-20 12:00 UTC))
+    }
+}
+
+fn installed_now() -> OffsetDateTime {
+    INSTALLED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .unwrap_or_else(OffsetDateTime::now_utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedClock, TempStore, task};
+    use crate::model::{Task, TaskStatus};
+    use time::Duration;
+
+    #[test]
+    fn temp_store_round_trips_and_cleans_up() {
+        let store = TempStore::with_tasks(vec![
+            task("1", "Plan trip").urgent(true).build().unwrap(),
+            Task {
+                status: TaskStatus::Completed,
+                ..task("2", "Pack").build().unwrap()
+            },
+        ]);
+        let path = store.path().to_path_buf();
+
+        let state = store.state();
+        drop(store);
+
+        assert_eq!(state.tasks.len(), 2);
+        assert!(state.tasks[0].urgent);
+        assert_eq!(state.tasks[1].status, TaskStatus::Completed);
+        assert!(!path.exists());
+        assert!(!path.parent().unwrap().exists());
+    }
+
+    #[test]
+    fn fixed_clock_moves_only_when_advanced() {
+        let mut clock = FixedClock::default();
+        let start = clock.now();
+
+        clock.advance(Duration::hours(2));
+
+        assert_eq!(clock.now() - start, Duration::hours(2));
+        assert_eq!(clock.rfc3339(Duration::days(-1)), "2025-12-19T14:00:00Z");
+    }
+}
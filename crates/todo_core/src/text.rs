@@ -0,0 +1,216 @@
+//! Unicode handling for task text: normalization, caseless matching, and display width.
+//!
+//! Titles are stored in Normalization Form C, so the same words typed on different systems
+//! (a precomposed `é` on one, `e` plus a combining accent on another) compare and search as
+//! equal. Widths are terminal columns: CJK ideographs and most emoji take two.
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// `text` trimmed and in NFC. Applied to titles whenever they are set.
+pub fn normalize(text: &str) -> String {
+    text.trim().nfc().collect()
+}
+
+/// A caseless form of `text` for matching: NFC, lowercased, with `ß` folded to `ss` and the
+/// final `ς` to `σ`, so `STRASSE` finds `Straße`. Both sides of a comparison must be folded.
+pub fn fold_case(text: &str) -> String {
+    text.nfc()
+        .collect::<String>()
+        .to_lowercase()
+        .replace('ß', "ss")
+        .replace('ς', "σ")
+}
+
+/// Terminal columns `text` occupies.
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// `text` cut to at most `max_width` columns, ending in `…` when anything was cut. Cuts fall
+/// between grapheme clusters, so accented letters, flags, and joined emoji are never split.
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+
+    // The ellipsis takes one column.
+    let budget = max_width.saturating_sub(1);
+    let mut cut = String::new();
+    let mut width = 0;
+    for grapheme in text.graphemes(true) {
+        width += grapheme.width();
+        if width > budget {
+            break;
+        }
+        cut.push_str(grapheme);
+    }
+    format!("{}…", cut.trim_end())
+}
+
+/// `text` broken into lines of at most `max_width` columns. Lines break at spaces where they
+/// can; a word wider than a whole line is split between grapheme clusters. Line breaks already in
+/// `text` are kept.
+pub fn wrap_to_width(text: &str, max_width: usize) -> Vec<String> {
+    let max_width = max_width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let needed = if line.is_empty() {
+                word.width()
+            } else {
+                line.width() + 1 + word.width()
+            };
+            if needed <= max_width {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for grapheme in word.graphemes(true) {
+                if !line.is_empty() && line.width() + grapheme.width() > max_width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push_str(grapheme);
+            }
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// `text` with its first letter capitalized. Later words written like `Milk`, or every word when
+/// the whole text is in capitals, are lowercased; acronyms and mixed case such as `API` or
+/// `iPhone` in otherwise lowercase text are kept.
+pub fn sentence_case(text: &str) -> String {
+    let shouting = !text.chars().any(char::is_lowercase);
+    let mut cased = String::with_capacity(text.len());
+    let mut first = true;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let mut letters = word.chars().filter(|ch| ch.is_alphabetic());
+        let capitalized =
+            letters.next().is_some_and(char::is_uppercase) && letters.all(|ch| !ch.is_uppercase());
+        let word = if shouting || capitalized {
+            word.to_lowercase()
+        } else {
+            word.to_string()
+        };
+        if first && let Some(at) = word.find(char::is_alphabetic) {
+            first = false;
+            let letter = word[at..].chars().next().unwrap_or_default();
+            cased.push_str(&word[..at]);
+            cased.extend(letter.to_uppercase());
+            cased.push_str(&word[at + letter.len_utf8()..]);
+        } else {
+            cased.push_str(&word);
+        }
+    }
+    cased
+}
+
+/// `text` without the periods, commas, colons, semicolons, exclamation marks, and ellipses it
+/// ends with. A closing `?` is kept, since it makes the title a question.
+pub fn trim_trailing_punctuation(text: &str) -> String {
+    text.trim_end_matches(|ch: char| {
+        ch.is_whitespace() || matches!(ch, '.' | ',' | ';' | ':' | '!' | '…')
+    })
+    .to_string()
+}
+
+/// `text` without emoji, flags, and keycaps, collapsing the spaces they leave.
+pub fn strip_emoji(text: &str) -> String {
+    let kept: String = text
+        .graphemes(true)
+        .filter(|grapheme| !is_emoji(grapheme))
+        .collect();
+    kept.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_emoji(grapheme: &str) -> bool {
+    grapheme.chars().any(|ch| {
+        matches!(
+            u32::from(ch),
+            0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x231A..=0x23FF | 0xFE0F
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        display_width, fold_case, normalize, sentence_case, strip_emoji, trim_trailing_punctuation,
+        truncate_to_width, wrap_to_width,
+    };
+
+    #[test]
+    fn normalize_composes_and_trims() {
+        let decomposed = " Cafe\u{301} au lait\n";
+
+        assert_eq!(normalize(decomposed), "Caf\u{e9} au lait");
+        assert_eq!(normalize("東京で会議"), "東京で会議");
+    }
+
+    #[test]
+    fn fold_case_matches_across_case_and_composition() {
+        assert_eq!(fold_case("E\u{301}COLE"), fold_case("\u{e9}cole"));
+        assert_eq!(fold_case("STRASSE"), fold_case("Straße"));
+        assert_eq!(fold_case("ΣΟΦΟΣ"), fold_case("σοφος"));
+        assert_eq!(fold_case("Привет"), "привет");
+    }
+
+    #[test]
+    fn truncation_counts_columns_and_keeps_graphemes_whole() {
+        assert_eq!(display_width("東京"), 4);
+        assert_eq!(truncate_to_width("Buy milk", 8), "Buy milk");
+        assert_eq!(truncate_to_width("東京で会議", 7), "東京で…");
+        assert_eq!(truncate_to_width("Ship 🚀🚀🚀", 8), "Ship 🚀…");
+        assert_eq!(
+            truncate_to_width("Family 👨\u{200d}👩\u{200d}👧 trip", 9),
+            "Family…"
+        );
+        assert_eq!(truncate_to_width("Cafe\u{301} crème", 5), "Cafe\u{301}…");
+    }
+
+    #[test]
+    fn wrapping_breaks_at_spaces_and_splits_only_overlong_words() {
+        assert_eq!(
+            wrap_to_width("Buy milk and eggs", 9),
+            ["Buy milk", "and eggs"]
+        );
+        assert_eq!(
+            wrap_to_width("東京で会議 tomorrow", 6),
+            ["東京で", "会議", "tomorr", "ow"]
+        );
+        assert_eq!(wrap_to_width("first\n\nthird", 20), ["first", "", "third"]);
+        assert_eq!(wrap_to_width("", 20), [""]);
+    }
+
+    #[test]
+    fn title_rules_case_trim_and_strip() {
+        assert_eq!(
+            sentence_case("buy Milk From The Store"),
+            "Buy milk from the store"
+        );
+        assert_eq!(
+            sentence_case("fix API bug on iPhone"),
+            "Fix API bug on iPhone"
+        );
+        assert_eq!(sentence_case("CALL THE BANK"), "Call the bank");
+        assert_eq!(sentence_case("\"école\" trip"), "\"École\" trip");
+        assert_eq!(trim_trailing_punctuation("Pay rent!!! "), "Pay rent");
+        assert_eq!(trim_trailing_punctuation("Ship it?"), "Ship it?");
+        assert_eq!(trim_trailing_punctuation("Wait for…"), "Wait for");
+        assert_eq!(strip_emoji("🚀 Ship the ✨ release 1️⃣"), "Ship the release");
+        assert_eq!(strip_emoji("Trip to 🇯🇵 東京"), "Trip to 東京");
+        assert_eq!(strip_emoji("Plain text"), "Plain text");
+    }
+}
@@ -0,0 +1,225 @@
+//! Ticket lookups for `refresh-tickets`, driven by the generic REST mapping in
+//! [`TicketConfig`]. Requests go through [`http`].
+
+use crate::config::{TicketConfig, TicketProvider};
+use crate::error::AppError;
+use crate::http;
+use crate::model::TicketLink;
+use crate::secrets::{self, Keyring};
+use serde_json::Value;
+
+pub trait TicketFetcher {
+    /// GET `url` and parse the response body as JSON.
+    fn fetch(&self, url: &str, headers: &[(String, String)]) -> Result<Value, AppError>;
+}
+
+pub struct CurlFetcher;
+
+impl TicketFetcher for CurlFetcher {
+    fn fetch(&self, url: &str, headers: &[(String, String)]) -> Result<Value, AppError> {
+        let mut headers = headers.to_vec();
+        headers.push(("Accept".to_string(), "application/json".to_string()));
+        let body = http::get(url, &headers)?;
+
+        serde_json::from_slice(&body)
+            .map_err(|err| AppError::invalid_data(format!("response is not JSON: {err}")))
+    }
+}
+
+/// What the provider currently reports for a ticket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketState {
+    pub title: String,
+    pub status: Option<String>,
+    /// The status is one of the provider's `closed_statuses`.
+    pub closed: bool,
+}
+
+/// The provider named by the link, or else the one with the longest matching key prefix.
+pub fn provider_for<'a>(
+    config: &'a TicketConfig,
+    link: &TicketLink,
+) -> Result<&'a TicketProvider, AppError> {
+    if let Some(name) = link.provider.as_deref() {
+        return config
+            .providers
+            .get(name)
+            .ok_or_else(|| AppError::invalid_input(format!("unknown ticket provider '{name}'")));
+    }
+
+    let key = link.key.to_lowercase();
+    config
+        .providers
+        .values()
+        .filter_map(|provider| {
+            let prefix = provider.prefix.as_deref()?.to_lowercase();
+            key.starts_with(&prefix).then_some((prefix.len(), provider))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, provider)| provider)
+        .ok_or_else(|| {
+            AppError::invalid_input(format!("no ticket provider matches '{}'", link.key))
+        })
+}
+
+pub fn fetch_ticket(
+    provider: &TicketProvider,
+    key: &str,
+    fetcher: &dyn TicketFetcher,
+) -> Result<TicketState, AppError> {
+    let url = provider.url.replace("{key}", &encode_key(key));
+    let mut headers = Vec::with_capacity(provider.headers.len());
+    for (name, value) in &provider.headers {
+        headers.push((name.clone(), secrets::expand(value, &Keyring)?));
+    }
+    let response = fetcher.fetch(&url, &headers)?;
+
+    let title = text_at(&response, &provider.title_pointer)
+        .filter(|title| !title.trim().is_empty())
+        .ok_or_else(|| {
+            AppError::invalid_data(format!(
+                "response has no title at '{}'",
+                provider.title_pointer
+            ))
+        })?;
+    let status = match provider.status_pointer.as_deref() {
+        Some(pointer) => Some(text_at(&response, pointer).ok_or_else(|| {
+            AppError::invalid_data(format!("response has no status at '{pointer}'"))
+        })?),
+        None => None,
+    };
+    let closed = status.as_deref().is_some_and(|status| {
+        provider
+            .closed_statuses
+            .iter()
+            .any(|closed| closed.eq_ignore_ascii_case(status))
+    });
+
+    Ok(TicketState {
+        title: title.trim().to_string(),
+        status,
+        closed,
+    })
+}
+
+fn text_at(value: &Value, pointer: &str) -> Option<String> {
+    match value.pointer(pointer)? {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+/// Keys go into URL paths, so anything other than unreserved characters is percent-encoded.
+fn encode_key(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TicketFetcher, encode_key, fetch_ticket, provider_for};
+    use crate::config::{TicketConfig, TicketProvider};
+    use crate::error::AppError;
+    use crate::model::TicketLink;
+    use serde_json::{Value, json};
+    use std::cell::RefCell;
+
+    type Request = (String, Vec<(String, String)>);
+
+    struct FakeFetcher {
+        response: Value,
+        requests: RefCell<Vec<Request>>,
+    }
+
+    impl TicketFetcher for FakeFetcher {
+        fn fetch(&self, url: &str, headers: &[(String, String)]) -> Result<Value, AppError> {
+            self.requests
+                .borrow_mut()
+                .push((url.to_string(), headers.to_vec()));
+            Ok(self.response.clone())
+        }
+    }
+
+    fn provider(prefix: &str) -> TicketProvider {
+        TicketProvider {
+            prefix: Some(prefix.to_string()),
+            url: "https://tickets.example.com/api/{key}".to_string(),
+            headers: Default::default(),
+            title_pointer: "/fields/summary".to_string(),
+            status_pointer: Some("/fields/status/name".to_string()),
+            closed_statuses: vec!["Done".to_string()],
+        }
+    }
+
+    fn link(key: &str, provider: Option<&str>) -> TicketLink {
+        TicketLink {
+            key: key.to_string(),
+            url: None,
+            provider: provider.map(str::to_string),
+            status: None,
+            synced_at: None,
+        }
+    }
+
+    #[test]
+    fn provider_for_prefers_named_provider_then_longest_prefix() {
+        let mut config = TicketConfig::default();
+        config.providers.insert("ops".to_string(), provider("OPS-"));
+        config
+            .providers
+            .insert("ops-sec".to_string(), provider("ops-sec-"));
+
+        let named = provider_for(&config, &link("OPS-SEC-1", Some("ops"))).unwrap();
+        let prefixed = provider_for(&config, &link("OPS-SEC-1", None)).unwrap();
+
+        assert_eq!(named.prefix.as_deref(), Some("OPS-"));
+        assert_eq!(prefixed.prefix.as_deref(), Some("ops-sec-"));
+        assert!(provider_for(&config, &link("GH-1", None)).is_err());
+        assert!(provider_for(&config, &link("OPS-1", Some("jira"))).is_err());
+    }
+
+    #[test]
+    fn fetch_ticket_reads_title_and_closed_status() {
+        let fetcher = FakeFetcher {
+            response: json!({ "fields": { "summary": " Fix login ", "status": { "name": "done" } } }),
+            requests: RefCell::new(Vec::new()),
+        };
+
+        let ticket = fetch_ticket(&provider("OPS-"), "OPS 7", &fetcher).unwrap();
+
+        assert_eq!(ticket.title, "Fix login");
+        assert_eq!(ticket.status.as_deref(), Some("done"));
+        assert!(ticket.closed);
+        assert_eq!(
+            fetcher.requests.borrow()[0].0,
+            "https://tickets.example.com/api/OPS%207"
+        );
+    }
+
+    #[test]
+    fn fetch_ticket_rejects_missing_title() {
+        let fetcher = FakeFetcher {
+            response: json!({ "fields": {} }),
+            requests: RefCell::new(Vec::new()),
+        };
+
+        let err = fetch_ticket(&provider("OPS-"), "OPS-7", &fetcher).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_data");
+    }
+
+    #[test]
+    fn encode_key_escapes_all_but_unreserved_characters() {
+        assert_eq!(encode_key("A-1_b.~"), "A-1_b.~");
+        assert_eq!(encode_key("OPS 7/é"), "OPS%207%2F%C3%A9");
+    }
+}
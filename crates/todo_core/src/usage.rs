@@ -0,0 +1,151 @@
+//! Local, append-only log of which commands were run, read by `insights`.
+//!
+//! Entries hold the command path (e.g. `list today`) and, for commands that target one task,
+//! its id. Titles, notes, and other arguments are never recorded.
+
+use crate::clock;
+use crate::error::AppError;
+use crate::storage::json_store;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+
+/// Entries kept when the log is trimmed.
+const MAX_ENTRIES: usize = 5000;
+/// Trim once the log grows past this size; roughly twice [`MAX_ENTRIES`] typical entries.
+const TRIM_AFTER_BYTES: u64 = 640 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub at: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+pub fn usage_log_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".usage.jsonl");
+    store_path.with_file_name(name)
+}
+
+pub fn record(command: &str, task_id: Option<&str>) -> Result<(), AppError> {
+    let path = usage_log_path(&json_store::store_path()?);
+    let at = clock::now_utc()
+        .format(&Rfc3339)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    record_with_path(
+        &path,
+        &UsageEntry {
+            at,
+            command: command.to_string(),
+            task_id: task_id.map(str::to_string),
+        },
+    )
+}
+
+fn record_with_path(path: &Path, entry: &UsageEntry) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    let mut line =
+        serde_json::to_string(entry).map_err(|err| AppError::invalid_data(err.to_string()))?;
+    line.push('\n');
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    // Owner-only, like the store; see `crate::permissions`.
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .map_err(|err| AppError::io(err.to_string()))?;
+    file.write_all(line.as_bytes())
+        .map_err(|err| AppError::io(err.to_string()))?;
+
+    let size = file
+        .metadata()
+        .map_err(|err| AppError::io(err.to_string()))?
+        .len();
+    if size > TRIM_AFTER_BYTES {
+        let content = std::fs::read_to_string(path).map_err(|err| AppError::io(err.to_string()))?;
+        let lines: Vec<&str> = content.lines().collect();
+        let kept = &lines[lines.len().saturating_sub(MAX_ENTRIES)..];
+        std::fs::write(path, format!("{}\n", kept.join("\n")))
+            .map_err(|err| AppError::io(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Reads the log, skipping lines that do not parse (e.g. a torn final write).
+pub fn load_entries(path: &Path) -> Result<Vec<UsageEntry>, AppError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|err| AppError::io(err.to_string()))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_ENTRIES, TRIM_AFTER_BYTES, UsageEntry, load_entries, record_with_path};
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_path(file_name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("todoapp-{nanos}-{file_name}"))
+    }
+
+    fn entry(command: &str) -> UsageEntry {
+        UsageEntry {
+            at: "2025-12-20T09:00:00Z".to_string(),
+            command: command.to_string(),
+            task_id: None,
+        }
+    }
+
+    #[test]
+    fn record_appends_and_load_skips_garbage() {
+        let path = temp_path("usage.jsonl");
+        record_with_path(&path, &entry("list today")).unwrap();
+        std::fs::write(
+            &path,
+            format!("{}not json\n", std::fs::read_to_string(&path).unwrap()),
+        )
+        .unwrap();
+        record_with_path(&path, &entry("done")).unwrap();
+
+        let entries = load_entries(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(entries, vec![entry("list today"), entry("done")]);
+    }
+
+    #[test]
+    fn record_trims_to_newest_entries() {
+        let path = temp_path("usage-trim.jsonl");
+        let line = format!("{}\n", serde_json::to_string(&entry("old")).unwrap());
+        let count = TRIM_AFTER_BYTES as usize / line.len() + 1;
+        std::fs::write(&path, line.repeat(count)).unwrap();
+
+        record_with_path(&path, &entry("new")).unwrap();
+        let entries = load_entries(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries.last().unwrap().command, "new");
+    }
+}
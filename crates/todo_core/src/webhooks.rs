@@ -0,0 +1,162 @@
+//! Posting the deliveries queued in [`crate::storage::webhook_queue`] to their webhooks.
+
+use crate::error::AppError;
+use crate::http;
+use crate::secrets::{self, Keyring};
+use crate::storage::webhook_queue::{self, Delivery};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Tries for one delivery in one run; the waits between them double from [`FIRST_BACKOFF`].
+pub const TRIES_PER_RUN: u32 = 3;
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+
+pub trait WebhookPoster {
+    /// POST `body` to `url`, with `event` as the `X-Todo-Event` header.
+    fn post(&self, url: &str, event: &str, body: &str) -> Result<(), AppError>;
+}
+
+/// Posts through [`http`], like the ticket lookups and chat notifications. `${secret:NAME}` in
+/// the URL is expanded only here, so the queue never holds the token.
+pub struct CurlPoster;
+
+impl WebhookPoster for CurlPoster {
+    fn post(&self, url: &str, event: &str, body: &str) -> Result<(), AppError> {
+        let url = secrets::expand(url, &Keyring)?;
+        let headers = [("X-Todo-Event".to_string(), event.to_string())];
+        http::post_json(&url, &headers, body)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeliveryReport {
+    pub delivered: usize,
+    /// Each webhook that still failed after [`TRIES_PER_RUN`] tries, with its last error.
+    pub failed: BTreeMap<String, String>,
+    /// Deliveries left in the queue for the next run.
+    pub queued: usize,
+}
+
+/// Post every queued delivery, oldest first. A webhook that fails [`TRIES_PER_RUN`] times is
+/// skipped for the rest of the run, so its events still arrive in order once it is back.
+pub fn deliver(
+    store_path: &Path,
+    poster: &dyn WebhookPoster,
+    sleep: &mut dyn FnMut(Duration),
+) -> Result<DeliveryReport, AppError> {
+    let pending = webhook_queue::pending(store_path)?;
+    let mut report = DeliveryReport::default();
+    let mut delivered = Vec::new();
+    let mut failed = Vec::new();
+    for delivery in &pending {
+        if report.failed.contains_key(&delivery.url) {
+            continue;
+        }
+        match post_with_retries(delivery, poster, sleep) {
+            Ok(()) => delivered.push(delivery.id),
+            Err(err) => {
+                failed.push((delivery.id, err.message().to_string()));
+                report
+                    .failed
+                    .insert(delivery.url.clone(), err.message().to_string());
+            }
+        }
+    }
+    webhook_queue::settle(store_path, &delivered, &failed)?;
+    report.delivered = delivered.len();
+    report.queued = pending.len() - delivered.len();
+    Ok(report)
+}
+
+fn post_with_retries(
+    delivery: &Delivery,
+    poster: &dyn WebhookPoster,
+    sleep: &mut dyn FnMut(Duration),
+) -> Result<(), AppError> {
+    let body = serde_json::to_string(&delivery.event)
+        .map_err(|err| AppError::invalid_data(err.to_string()))?;
+    let event = delivery.event.kind.as_str();
+    let mut backoff = FIRST_BACKOFF;
+    let mut tries = 1;
+    loop {
+        match poster.post(&delivery.url, event, &body) {
+            Ok(()) => return Ok(()),
+            Err(err) if tries == TRIES_PER_RUN => return Err(err),
+            Err(_) => {
+                sleep(backoff);
+                backoff *= 2;
+                tries += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WebhookPoster, deliver};
+    use crate::error::AppError;
+    use crate::storage::webhook_queue::{pending, webhook_queue_path};
+    use crate::testing::TempStore;
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    /// Accepts posts to every URL but `down`.
+    struct FakePoster {
+        down: &'static str,
+        posts: RefCell<Vec<(String, String)>>,
+    }
+
+    impl WebhookPoster for FakePoster {
+        fn post(&self, url: &str, event: &str, _body: &str) -> Result<(), AppError> {
+            self.posts
+                .borrow_mut()
+                .push((url.to_string(), event.to_string()));
+            if url == self.down {
+                return Err(AppError::io("webhook failed: HTTP 503"));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn a_failing_webhook_is_retried_then_left_queued_in_order() {
+        let temp = TempStore::new();
+        let store = temp.path();
+        let queue = webhook_queue_path(store);
+        std::fs::write(
+            &queue,
+            r#"{"next_id":4,"next_seq":3,"deliveries":[
+                {"id":1,"url":"https://down.example.com","event":{"seq":1,"type":"task_added","at":"2025-12-20T12:00:00Z","by":"ana@laptop","task_id":"1","title":"a"},"attempts":0,"last_error":null},
+                {"id":2,"url":"https://up.example.com","event":{"seq":1,"type":"task_added","at":"2025-12-20T12:00:00Z","by":"ana@laptop","task_id":"1","title":"a"},"attempts":0,"last_error":null},
+                {"id":3,"url":"https://down.example.com","event":{"seq":2,"type":"task_completed","at":"2025-12-20T12:01:00Z","by":"ana@laptop","task_id":"1","title":"a"},"attempts":0,"last_error":null}
+            ]}"#,
+        )
+        .unwrap();
+        let poster = FakePoster {
+            down: "https://down.example.com",
+            posts: RefCell::new(Vec::new()),
+        };
+        let mut waits = Vec::new();
+
+        let report = deliver(store, &poster, &mut |wait| waits.push(wait)).unwrap();
+        let left = pending(store).unwrap();
+
+        assert_eq!(report.delivered, 1);
+        assert_eq!(report.queued, 2);
+        assert_eq!(
+            report
+                .failed
+                .get("https://down.example.com")
+                .map(String::as_str),
+            Some("webhook failed: HTTP 503")
+        );
+        assert_eq!(waits, [Duration::from_secs(1), Duration::from_secs(2)]);
+        assert_eq!(poster.posts.borrow().len(), 4);
+        assert_eq!(poster.posts.borrow()[3].1, "task_added");
+        assert_eq!(
+            left.iter().map(|d| (d.id, d.attempts)).collect::<Vec<_>>(),
+            [(1, 1), (3, 0)]
+        );
+    }
+}
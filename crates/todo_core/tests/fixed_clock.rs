@@ -0,0 +1,24 @@
+//! [`FixedClock::install`] replaces the clock for the whole process, so it is tested in a binary
+//! of its own.
+
+use time::Duration;
+use todo_core::clock;
+use todo_core::storage::store::MemoryStore;
+use todo_core::task_api;
+use todo_core::testing::FixedClock;
+
+#[test]
+fn an_installed_clock_stamps_changes_until_it_is_moved() {
+    let mut fixed = FixedClock::default();
+    fixed.install().unwrap();
+    let store = MemoryStore::default();
+
+    let added = task_api::transaction_in(&store, |txn| txn.add("Water plants", false)).unwrap();
+    fixed.advance(Duration::hours(1));
+    fixed.install().unwrap();
+    let done = task_api::transaction_in(&store, |txn| txn.complete(&added.id, None)).unwrap();
+
+    assert_eq!(added.created_at, "2025-12-20T12:00:00Z");
+    assert_eq!(done.completed_at.as_deref(), Some("2025-12-20T13:00:00Z"));
+    assert_eq!(clock::now_utc(), fixed.now());
+}
@@ -0,0 +1,55 @@
+//! The task logic a web UI reuses, through only what a WebAssembly build has: tasks held in a
+//! `MemoryStore` and a clock and author passed in by the host. Nothing here touches the file system or the
+//! environment, so it passes the same natively and under a wasm runner:
+//!
+//! ```bash
+//! cargo test -p todo_core --test wasm_smoke --target wasm32-wasip1
+//! ```
+
+use time::OffsetDateTime;
+use time::macros::datetime;
+use todo_core::clock;
+use todo_core::model::TaskStatus;
+use todo_core::storage::store::MemoryStore;
+use todo_core::task_api;
+
+fn host_clock() -> OffsetDateTime {
+    datetime!(2025-12-22 09:00 UTC)
+}
+
+#[test]
+fn tasks_round_trip_through_a_memory_store() {
+    clock::set_source(host_clock).unwrap();
+    let store = MemoryStore::default().with_author("ana@browser");
+
+    let report = task_api::transaction_in(&store, |txn| txn.add("Write report", true)).unwrap();
+    let call = task_api::transaction_in(&store, |txn| {
+        let call = txn.add("Call Alice", false)?;
+        txn.schedule(&call.id, "2025-12-23T10:00:00Z")
+    })
+    .unwrap();
+    task_api::transaction_in(&store, |txn| txn.complete(&report.id, Some("Sent"))).unwrap();
+
+    assert_eq!(report.created_at, "2025-12-22T09:00:00Z");
+    assert_eq!(call.due_at.as_deref(), Some("2025-12-23T10:00:00Z"));
+
+    let reopened = MemoryStore::from_json(&store.to_json().unwrap()).unwrap();
+    let tasks = reopened.state().tasks;
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].status, TaskStatus::Completed);
+    assert_eq!(
+        tasks[0].completed_at.as_deref(),
+        Some("2025-12-22T09:00:00Z")
+    );
+    assert_eq!(tasks[1].status, TaskStatus::Pending);
+    assert_eq!(tasks[1].modified_by.as_deref(), Some("ana@browser"));
+    assert_eq!(
+        tasks[1].modified_at.as_deref(),
+        Some("2025-12-22T09:00:00Z")
+    );
+
+    let err =
+        task_api::transaction_in(&reopened, |txn| txn.complete(&report.id, None)).unwrap_err();
+    assert_eq!(err.code(), "invalid_input");
+    assert_eq!(reopened.state().tasks, tasks);
+}
@@ -0,0 +1,327 @@
+//! Python bindings for the task store, built with `maturin` as the `todo_py` module.
+//!
+//! ```python
+//! import pandas as pd
+//! import todo_py
+//!
+//! store = todo_py.Store()  # The store todo_opt uses; or Store("path/to/tasks.json")
+//! store.add("Write report", urgent=True)
+//! frame = pd.DataFrame(store.records())
+//! ```
+//!
+//! Every change goes through [`task_api::transaction_in`], so it is checked and recorded as
+//! it is by the matching CLI command.
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use todo_core::error::{AppError, ErrorKind};
+use todo_core::model::Task;
+use todo_core::storage::json_store;
+use todo_core::storage::store::{FileStore, MemoryStore, TaskStore};
+use todo_core::task_api::{self, Transaction};
+
+/// The columns of [`Store::records`], in order; metadata follows as `meta.<key>`.
+const RECORD_FIELDS: [&str; 15] = [
+    "id",
+    "title",
+    "status",
+    "urgent",
+    "project",
+    "tags",
+    "notes",
+    "created_at",
+    "start_at",
+    "due_at",
+    "completed_at",
+    "waiting_until",
+    "modified_at",
+    "modified_by",
+    "routine",
+];
+
+/// A store file, or tasks held in memory with `Store.from_json`.
+#[pyclass(unsendable, module = "todo_py")]
+struct Store {
+    backend: Backend,
+}
+
+enum Backend {
+    File(FileStore),
+    Memory(MemoryStore),
+}
+
+#[pymethods]
+impl Store {
+    /// The store at `path`, or the one `todo_opt` uses (`TODOAPP_STORE_PATH` or the default
+    /// location) when it is left out.
+    #[new]
+    #[pyo3(signature = (path = None))]
+    fn new(path: Option<PathBuf>) -> PyResult<Self> {
+        let path = match path {
+            Some(path) => path,
+            None => json_store::store_path().map_err(to_py_err)?,
+        };
+        Ok(Self {
+            backend: Backend::File(FileStore::new(path)),
+        })
+    }
+
+    /// Tasks read from a store file's contents and kept in memory; changes never touch a file.
+    #[staticmethod]
+    fn from_json(content: &str) -> PyResult<Self> {
+        Ok(Self {
+            backend: Backend::Memory(MemoryStore::from_json(content).map_err(to_py_err)?),
+        })
+    }
+
+    /// The store file, or `None` for one from `from_json`.
+    #[getter]
+    fn path(&self) -> Option<&Path> {
+        match &self.backend {
+            Backend::File(store) => Some(store.path()),
+            Backend::Memory(_) => None,
+        }
+    }
+
+    /// The tasks as a store file's contents.
+    fn to_json(&self) -> PyResult<String> {
+        let state = self.store().load().map_err(to_py_err)?;
+        json_store::render_state(&state).map_err(to_py_err)
+    }
+
+    /// Every task in store order, as a dict with the fields of the store file.
+    fn tasks<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let tasks = self.load_tasks()?;
+        let tasks = tasks
+            .iter()
+            .map(|task| to_py(py, &task_json(task)?))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, tasks)
+    }
+
+    /// Every task as a flat dict with the same keys, for `pandas.DataFrame`. Times stay RFC3339
+    /// strings (`pandas.to_datetime` reads them), tags are a list, and each metadata key is a
+    /// `meta.<key>` column.
+    fn records<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let tasks = self.load_tasks()?;
+        let records = tasks
+            .iter()
+            .map(|task| {
+                let dict = PyDict::new(py);
+                for (column, value) in record(task)? {
+                    dict.set_item(column, to_py(py, &value)?)?;
+                }
+                Ok(dict)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, records)
+    }
+
+    #[pyo3(signature = (title, urgent = false))]
+    fn add<'py>(&self, py: Python<'py>, title: &str, urgent: bool) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.add(title, urgent))
+    }
+
+    fn edit<'py>(&self, py: Python<'py>, id: &str, title: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.edit(id, title))
+    }
+
+    fn delete<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.delete(id))
+    }
+
+    fn start<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.start(id))
+    }
+
+    #[pyo3(signature = (id, message = None))]
+    fn complete<'py>(
+        &self,
+        py: Python<'py>,
+        id: &str,
+        message: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.complete(id, message))
+    }
+
+    #[pyo3(signature = (id, reason = None))]
+    fn cancel<'py>(
+        &self,
+        py: Python<'py>,
+        id: &str,
+        reason: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.cancel(id, reason))
+    }
+
+    /// Set the due time, in any form `schedule` accepts.
+    fn schedule<'py>(&self, py: Python<'py>, id: &str, when: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.schedule(id, when))
+    }
+
+    fn set_tags<'py>(
+        &self,
+        py: Python<'py>,
+        id: &str,
+        tags: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.set_tags(id, &tags))
+    }
+
+    #[pyo3(signature = (id, project = None))]
+    fn set_project<'py>(
+        &self,
+        py: Python<'py>,
+        id: &str,
+        project: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.change(py, |txn| txn.set_project(id, project))
+    }
+}
+
+impl Store {
+    fn store(&self) -> &dyn TaskStore {
+        match &self.backend {
+            Backend::File(store) => store,
+            Backend::Memory(store) => store,
+        }
+    }
+
+    fn load_tasks(&self) -> PyResult<Vec<Task>> {
+        self.store()
+            .load()
+            .map(|state| state.tasks)
+            .map_err(to_py_err)
+    }
+
+    /// Apply one change and return the task it touched as a dict.
+    fn change<'py, F>(&self, py: Python<'py>, apply: F) -> PyResult<Bound<'py, PyAny>>
+    where
+        F: FnOnce(&mut Transaction) -> Result<Task, AppError>,
+    {
+        let task = task_api::transaction_in(self.store(), apply).map_err(to_py_err)?;
+        to_py(py, &task_json(&task)?)
+    }
+}
+
+fn task_json(task: &Task) -> PyResult<Value> {
+    serde_json::to_value(task).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// [`RECORD_FIELDS`] of `task`, then its metadata.
+fn record(task: &Task) -> PyResult<Vec<(String, Value)>> {
+    let Value::Object(mut full) = task_json(task)? else {
+        return Err(PyValueError::new_err("task is not a JSON object"));
+    };
+    let mut record: Vec<(String, Value)> = RECORD_FIELDS
+        .iter()
+        .map(|field| {
+            (
+                field.to_string(),
+                full.remove(*field).unwrap_or(Value::Null),
+            )
+        })
+        .collect();
+    record.extend(
+        task.metadata
+            .iter()
+            .map(|(key, value)| (format!("meta.{key}"), Value::String(value.clone()))),
+    );
+    Ok(record)
+}
+
+fn to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(flag) => flag.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(number) => match number.as_i64() {
+            Some(whole) => whole.into_pyobject(py)?.into_any(),
+            None => number.as_f64().into_pyobject(py)?.into_any(),
+        },
+        Value::String(text) => text.into_pyobject(py)?.into_any(),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| to_py(py, item))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_any()
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, field) in fields {
+                dict.set_item(key, to_py(py, field)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// `ValueError` for bad input or a store it cannot read, `OSError` when the file system fails;
+/// the hint, if any, goes on a second line.
+fn to_py_err(err: AppError) -> PyErr {
+    let message = match err.hint() {
+        Some(hint) => format!("{}\n{hint}", err.message()),
+        None => err.message().to_string(),
+    };
+    match err.kind() {
+        ErrorKind::InvalidInput | ErrorKind::InvalidData => PyValueError::new_err(message),
+        ErrorKind::Io => PyOSError::new_err(message),
+    }
+}
+
+#[pymodule]
+fn todo_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Store>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Store;
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use todo_core::storage::store::MemoryStore;
+
+    #[test]
+    fn records_are_flat_rows_with_the_same_leading_columns() {
+        Python::initialize();
+        Python::attach(|py| {
+            let empty = MemoryStore::default().to_json().unwrap();
+            let store = Store::from_json(&empty).unwrap();
+            let task = store.add(py, "Write report", true).unwrap();
+            let id: String = task.get_item("id").unwrap().extract().unwrap();
+            store.set_tags(py, &id, vec!["work".to_string()]).unwrap();
+            store.add(py, "Call Alice", false).unwrap();
+
+            let records = store.records(py).unwrap();
+            assert_eq!(records.len(), 2);
+            let first = records.get_item(0).unwrap();
+            let first = first.cast::<PyDict>().unwrap();
+            let keys: Vec<String> = first.keys().extract().unwrap();
+            assert_eq!(keys[..4], ["id", "title", "status", "urgent"]);
+            assert_eq!(keys.len(), super::RECORD_FIELDS.len());
+            let status: String = first
+                .get_item("status")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            let tags: Vec<String> = first.get_item("tags").unwrap().unwrap().extract().unwrap();
+            assert_eq!(status, "pending");
+            assert_eq!(tags, ["work"]);
+            assert!(first.get_item("due_at").unwrap().unwrap().is_none());
+
+            let err = store.complete(py, "missing", None).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert!(
+                Store::from_json(&store.to_json().unwrap())
+                    .unwrap()
+                    .path()
+                    .is_none()
+            );
+        });
+    }
+}